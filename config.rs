@@ -1,14 +1,64 @@
-/// Configuration constants for ttt
+//! Configuration constants for ttt
 
 /// Maximum number of variables allowed in an expression
 pub const MAX_VARIABLES: usize = 20;  // 2^20 = ~1M rows max
 
+/// Deepest expression read or evaluated by default, in levels of parentheses,
+/// `not`s, operators and operands of chains such as `a and b and c` on one
+/// path. Every pass over an expression recurses once per level; this many fit
+/// the 2 MiB stack of a spawned thread in an unoptimized build with room to
+/// spare, and the parsers read deeper input on a thread of their own
+pub const MAX_NESTING_DEPTH: usize = 256;
+
+/// Stack the command line runs on, enough for a `--max-nesting` tens of
+/// times [`MAX_NESTING_DEPTH`]; it is reserved, not committed, until used
+pub const CLI_STACK_SIZE: usize = 1 << 30;
+
 /// Maximum length allowed for variable names
 pub const MAX_VARIABLE_NAME_LENGTH: usize = 50;
 
 /// Maximum number of differences to show in equivalence check output
 pub const MAX_DIFFERENCES_TO_SHOW: usize = 5;
 
+/// Most variables a report draws a Karnaugh map for; beyond four the map
+/// no longer shows adjacent cells side by side
+pub const MAX_KARNAUGH_VARIABLES: usize = 4;
+
+/// Maximum number of Quine-McCluskey combine attempts and cover search steps
+/// before reduction settles for the best cover found so far
+pub const MAX_REDUCTION_STEPS: usize = 5_000_000;
+
+/// Largest expression (in nodes) that `expand` will build, and largest normal
+/// form (in literals) that distributing operators may produce
+pub const MAX_EXPANSION_SIZE: usize = 10_000;
+
+/// Most relevant variables `threshold` will solve for; the linear program
+/// grows with the number of minimal true and maximal false assignments
+pub const MAX_THRESHOLD_VARIABLES: usize = 12;
+
+/// Maximum number of variables in a three-valued truth table (3^10 = 59049 rows)
+pub const MAX_THREE_VALUED_VARIABLES: usize = 10;
+
+/// Widest S-box input or output `sbox` analyzes; every nonzero combination
+/// of output bits gets its own Walsh–Hadamard transform
+pub const MAX_SBOX_BITS: usize = 10;
+
+/// Most variables `degree --immunity` will search annihilators for; each degree
+/// solves a linear system with a row per true (or false) assignment
+pub const MAX_IMMUNITY_VARIABLES: usize = 12;
+
+/// Most arguments a custom operator can take; its truth table is built up front
+pub const MAX_OPERATOR_ARITY: usize = 16;
+
+/// Most variables `synth --exact` searches over; truth tables are kept as 64-bit masks
+pub const MAX_SYNTHESIS_VARIABLES: usize = 5;
+
+/// Most operand pairs `synth --exact` combines before giving up
+pub const MAX_SYNTHESIS_STEPS: usize = 50_000_000;
+
+/// Most rows `table --sample` draws, the size of the largest full table
+pub const MAX_SAMPLE_ROWS: usize = 1 << MAX_VARIABLES;
+
 /// Default timeout for complex operations (in seconds)
 pub const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
 
@@ -20,3 +70,79 @@ pub const APP_NAME: &str = env!("CARGO_PKG_NAME");
 
 /// Application description
 pub const APP_DESCRIPTION: &str = env!("CARGO_PKG_DESCRIPTION");
+
+/// What this build of ttt supports, for wrapper tools to detect features
+/// without parsing `--help`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Capabilities {
+    pub name: String,
+    pub version: String,
+    /// Cargo features compiled in
+    pub features: Vec<String>,
+    pub commands: Vec<String>,
+    pub dialects: Vec<String>,
+    pub notations: Vec<String>,
+    pub input_formats: Vec<String>,
+    pub output_formats: Vec<String>,
+    pub limits: Limits,
+}
+
+/// The size limits defined above
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Limits {
+    pub max_variables: usize,
+    pub max_variable_name_length: usize,
+    pub max_nesting_depth: usize,
+    pub max_three_valued_variables: usize,
+    pub max_threshold_variables: usize,
+    pub max_immunity_variables: usize,
+    pub max_sbox_bits: usize,
+    pub max_operator_arity: usize,
+    pub max_expansion_size: usize,
+    pub max_reduction_steps: usize,
+    pub max_synthesis_variables: usize,
+    pub max_sample_rows: usize,
+}
+
+impl Capabilities {
+    /// The capabilities of this build, which offers `commands`
+    pub fn new(commands: Vec<String>) -> Self {
+        Self {
+            name: APP_NAME.to_string(),
+            version: VERSION.to_string(),
+            features: [("binary", cfg!(feature = "binary")), ("proptest", cfg!(feature = "proptest"))]
+                .iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(feature, _)| feature.to_string())
+                .collect(),
+            commands,
+            dialects: value_names::<crate::source::Dialect>(),
+            notations: value_names::<crate::source::Notation>(),
+            input_formats: value_names::<crate::io::input::InputFormat>(),
+            output_formats: value_names::<crate::io::output::OutputFormat>(),
+            limits: Limits {
+                max_variables: MAX_VARIABLES,
+                max_variable_name_length: MAX_VARIABLE_NAME_LENGTH,
+                max_nesting_depth: MAX_NESTING_DEPTH,
+                max_three_valued_variables: MAX_THREE_VALUED_VARIABLES,
+                max_threshold_variables: MAX_THRESHOLD_VARIABLES,
+                max_immunity_variables: MAX_IMMUNITY_VARIABLES,
+                max_sbox_bits: MAX_SBOX_BITS,
+                max_operator_arity: MAX_OPERATOR_ARITY,
+                max_expansion_size: MAX_EXPANSION_SIZE,
+                max_reduction_steps: MAX_REDUCTION_STEPS,
+                max_synthesis_variables: MAX_SYNTHESIS_VARIABLES,
+                max_sample_rows: MAX_SAMPLE_ROWS,
+            },
+        }
+    }
+}
+
+/// The command-line spelling of each value of `T`
+fn value_names<T: clap::ValueEnum>() -> Vec<String> {
+    T::value_variants()
+        .iter()
+        .filter_map(|value| value.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
@@ -0,0 +1,117 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::reduction::{QuineMcCluskey, PrimeImplicant};
+use crate::eval::truth_table::evaluate_expression;
+use crate::eval::options;
+use std::collections::{BTreeSet, HashMap};
+use serde::{Serialize, Deserialize};
+
+/// Minimal sets of assumptions that force an expression to a target value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Abduction {
+    pub expression: Expr,
+    pub target: bool,
+    pub fixed: HashMap<String, bool>,
+    /// Variables of the expression not fixed, which assumptions range over
+    pub free: Variables,
+    /// Each entry is a set of literals over the free variables that, together
+    /// with the fixed values, guarantees the target; no literal of a set can
+    /// be dropped. Fewest literals first; empty when the target is unreachable.
+    pub assumptions: Vec<PrimeImplicant>,
+}
+
+impl Abduction {
+    /// The fixed values alone already force the target
+    pub fn already_forced(&self) -> bool {
+        self.assumptions.iter().any(|a| a.term.is_none())
+    }
+}
+
+/// Find the minimal literal sets which, given the partial assignment `fixed`,
+/// guarantee that `expr` evaluates to `target`.
+///
+/// These are the prime implicants of the expression (or its negation)
+/// restricted by the fixed values.
+pub fn abduce(expr: &Expr, fixed: &HashMap<String, bool>, target: bool) -> Result<Abduction, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    if let Some(unknown) = fixed.keys().find(|var| !variables.contains(var)) {
+        return Err(EvaluationError::InvalidTruthAssignment {
+            variable: unknown.clone(),
+            context: "fixed value for a variable the expression does not use".to_string(),
+        });
+    }
+    let free_names: Vec<String> = variables.iter().filter(|var| !fixed.contains_key(*var)).map(str::to_string).collect();
+    let n = free_names.len();
+
+    let mut minterms = BTreeSet::new();
+    let mut assignment = fixed.clone();
+    for i in 0..1usize << n {
+        options::check_row(i)?;
+        for (j, var) in free_names.iter().enumerate() {
+            assignment.insert(var.clone(), (i >> (n - 1 - j)) & 1 == 1);
+        }
+        if evaluate_expression(expr, &assignment) == target {
+            minterms.insert(i);
+        }
+    }
+
+    let free = Variables::from_names(free_names)?;
+    let mut assumptions = if minterms.is_empty() {
+        Vec::new()
+    } else {
+        QuineMcCluskey::from_minterms(free.clone(), minterms).prime_implicants()
+    };
+    assumptions.sort_by_key(|a| a.cube.chars().filter(|&c| c != '-').count());
+
+    Ok(Abduction {
+        expression: expr.clone(),
+        target,
+        fixed: fixed.clone(),
+        free,
+        assumptions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn abduce_str(input: &str, fixed: &[(&str, bool)], target: bool) -> Abduction {
+        let expr = Parser::new(input).parse().unwrap();
+        let fixed = fixed.iter().map(|(var, value)| (var.to_string(), *value)).collect();
+        abduce(&expr, &fixed, target).unwrap()
+    }
+
+    fn cubes(abduction: &Abduction) -> Vec<&str> {
+        abduction.assumptions.iter().map(|a| a.cube.as_str()).collect()
+    }
+
+    #[test]
+    fn test_assumptions_for_guard() {
+        // With a set, the guard passes if b, or if c and not d
+        let abduction = abduce_str("a and (b or (c and not d))", &[("a", true)], true);
+        assert_eq!(abduction.free.to_vec(), ["b", "c", "d"]);
+        assert_eq!(cubes(&abduction), ["1--", "-10"]);
+        assert!(!abduction.already_forced());
+    }
+
+    #[test]
+    fn test_target_false() {
+        let abduction = abduce_str("a and (b or c)", &[("a", true)], false);
+        assert_eq!(cubes(&abduction), ["00"]);
+    }
+
+    #[test]
+    fn test_already_forced_and_unreachable() {
+        assert!(abduce_str("a or b", &[("a", true)], true).already_forced());
+        assert!(abduce_str("a and b", &[("a", false)], true).assumptions.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_fixed_variable() {
+        let expr = Parser::new("a and b").parse().unwrap();
+        let fixed = HashMap::from([("z".to_string(), true)]);
+        assert!(abduce(&expr, &fixed, true).is_err());
+    }
+}
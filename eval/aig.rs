@@ -0,0 +1,271 @@
+use crate::source::{Expr, Cardinality};
+use crate::eval::{options, Variables, EvaluationError};
+use std::collections::HashMap;
+
+/// A literal in an and-inverter graph: `2 * variable + negated`.
+///
+/// Variable 0 is the constant, so literal 0 is false and literal 1 is true.
+pub type Literal = u32;
+
+pub const FALSE: Literal = 0;
+pub const TRUE: Literal = 1;
+
+/// Negate a literal
+pub fn negate(lit: Literal) -> Literal {
+    lit ^ 1
+}
+
+/// An and-inverter graph with hash-consed AND gates.
+///
+/// Inputs occupy variables `1..=inputs.len()` and gates the variables after
+/// them, in creation order, so every gate refers only to earlier variables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aig {
+    inputs: Vec<String>,
+    ands: Vec<(Literal, Literal)>,
+    outputs: Vec<Literal>,
+    gates: HashMap<(Literal, Literal), Literal>,
+}
+
+impl Aig {
+    /// Create a graph with the given named inputs and no gates
+    pub fn new(inputs: Vec<String>) -> Self {
+        Self {
+            inputs,
+            ands: Vec::new(),
+            outputs: Vec::new(),
+            gates: HashMap::new(),
+        }
+    }
+
+    /// Build a single-output graph from an expression, with inputs in variable order
+    pub fn from_expr(expr: &Expr) -> Result<Self, EvaluationError> {
+        let variables = Variables::from_expr(expr)?;
+        let mut aig = Self::new(variables.to_vec());
+        let output = aig.build(expr);
+        aig.add_output(output);
+        Ok(aig)
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// AND gates as `(rhs0, rhs1)`; gate `i` defines variable `inputs.len() + 1 + i`
+    pub fn ands(&self) -> &[(Literal, Literal)] {
+        &self.ands
+    }
+
+    pub fn outputs(&self) -> &[Literal] {
+        &self.outputs
+    }
+
+    /// Highest variable index in use
+    pub fn max_variable(&self) -> u32 {
+        (self.inputs.len() + self.ands.len()) as u32
+    }
+
+    /// Positive literal of input `index`
+    pub fn input(&self, index: usize) -> Literal {
+        2 * (index as u32 + 1)
+    }
+
+    pub fn add_output(&mut self, lit: Literal) {
+        self.outputs.push(lit);
+    }
+
+    /// AND of two literals, reusing an existing gate when one matches
+    pub fn and(&mut self, a: Literal, b: Literal) -> Literal {
+        if a == FALSE || b == FALSE || a == negate(b) {
+            return FALSE;
+        }
+        if a == TRUE || a == b {
+            return b;
+        }
+        if b == TRUE {
+            return a;
+        }
+
+        let key = (a.max(b), a.min(b));
+        if let Some(&lit) = self.gates.get(&key) {
+            return lit;
+        }
+        self.ands.push(key);
+        let lit = 2 * self.max_variable();
+        self.gates.insert(key, lit);
+        lit
+    }
+
+    pub fn or(&mut self, a: Literal, b: Literal) -> Literal {
+        negate(self.and(negate(a), negate(b)))
+    }
+
+    pub fn xor(&mut self, a: Literal, b: Literal) -> Literal {
+        let left = self.and(a, negate(b));
+        let right = self.and(negate(a), b);
+        self.or(left, right)
+    }
+
+    /// Cardinality constraint over `inputs` as a counter: after each input,
+    /// `at_least[j]` is true when at least `j` of the inputs so far are.
+    /// Shared gates keep it to O(n·k) rather than one term per subset.
+    pub fn cardinality(&mut self, kind: Cardinality, k: usize, inputs: &[Literal]) -> Literal {
+        let mut at_least = vec![FALSE; k + 2];
+        at_least[0] = TRUE;
+        for &input in inputs {
+            for j in (1..at_least.len()).rev() {
+                let carried = self.and(input, at_least[j - 1]);
+                at_least[j] = self.or(at_least[j], carried);
+            }
+        }
+        match kind {
+            Cardinality::AtLeast => at_least[k],
+            Cardinality::AtMost => negate(at_least[k + 1]),
+            Cardinality::Exactly => self.and(at_least[k], negate(at_least[k + 1])),
+        }
+    }
+
+    fn build(&mut self, expr: &Expr) -> Literal {
+        match expr {
+            Expr::Identifier(name) => {
+                let index = self.inputs.iter().position(|input| input == name).expect("input collected from expression");
+                self.input(index)
+            }
+            Expr::True => TRUE,
+            Expr::False => FALSE,
+            Expr::Not(inner) => negate(self.build(inner)),
+            Expr::And(left, right) => {
+                let (l, r) = (self.build(left), self.build(right));
+                self.and(l, r)
+            }
+            Expr::Or(left, right) => {
+                let (l, r) = (self.build(left), self.build(right));
+                self.or(l, r)
+            }
+            Expr::Nand(left, right) => {
+                let (l, r) = (self.build(left), self.build(right));
+                negate(self.and(l, r))
+            }
+            Expr::Nor(left, right) => {
+                let (l, r) = (self.build(left), self.build(right));
+                negate(self.or(l, r))
+            }
+            Expr::Xor(left, right) => {
+                let (l, r) = (self.build(left), self.build(right));
+                self.xor(l, r)
+            }
+            Expr::Implication(left, right) => {
+                let (l, r) = (self.build(left), self.build(right));
+                self.or(negate(l), r)
+            }
+            Expr::Iff(left, right) => {
+                let (l, r) = (self.build(left), self.build(right));
+                negate(self.xor(l, r))
+            }
+            Expr::Ite(cond, then, otherwise) => {
+                let (c, t, e) = (self.build(cond), self.build(then), self.build(otherwise));
+                let left = self.and(c, t);
+                let right = self.and(negate(c), e);
+                self.or(left, right)
+            }
+            Expr::Cardinality(kind, k, operands) => {
+                let inputs: Vec<Literal> = operands.iter().map(|operand| self.build(operand)).collect();
+                self.cardinality(*kind, *k, &inputs)
+            }
+            Expr::Quantified(..) => self.build(&expr.eliminate_quantifiers()),
+            Expr::Custom(..) => self.build(&expr.expand_custom()),
+        }
+    }
+
+    /// Convert output `index` back to an expression.
+    ///
+    /// Negated gates whose inputs are both negated are read back as `or`.
+    pub fn to_expr(&self, index: usize) -> Result<Expr, EvaluationError> {
+        let output = *self.outputs.get(index).ok_or_else(|| EvaluationError::UnsupportedOperation {
+            operation: format!("output {} of a graph with {} output(s)", index, self.outputs.len()),
+        })?;
+        let mut cache = HashMap::new();
+        self.literal_expr(output, 0, &mut cache)
+    }
+
+    /// The expression `lit` stands for, `depth` levels below the output; a
+    /// graph deeper than the evaluation limit fails rather than overflow the stack
+    fn literal_expr(&self, lit: Literal, depth: usize, cache: &mut HashMap<Literal, Expr>) -> Result<Expr, EvaluationError> {
+        if let Some(expr) = cache.get(&lit) {
+            return Ok(expr.clone());
+        }
+        let max = options::current().max_depth;
+        if depth > max {
+            return Err(EvaluationError::ExpressionTooDeep { depth, max });
+        }
+
+        let variable = (lit / 2) as usize;
+        let negated = lit & 1 == 1;
+        let expr = if variable == 0 {
+            if negated { Expr::True } else { Expr::False }
+        } else if variable <= self.inputs.len() {
+            let input = Expr::Identifier(self.inputs[variable - 1].clone());
+            if negated { Expr::Not(Box::new(input)) } else { input }
+        } else {
+            let (a, b) = self.ands[variable - self.inputs.len() - 1];
+            if negated && a & 1 == 1 && b & 1 == 1 {
+                Expr::Or(
+                    Box::new(self.literal_expr(negate(b), depth + 1, cache)?),
+                    Box::new(self.literal_expr(negate(a), depth + 1, cache)?),
+                )
+            } else {
+                let and = Expr::And(
+                    Box::new(self.literal_expr(b, depth + 1, cache)?),
+                    Box::new(self.literal_expr(a, depth + 1, cache)?),
+                );
+                if negated { Expr::Not(Box::new(and)) } else { and }
+            }
+        };
+
+        cache.insert(lit, expr.clone());
+        Ok(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+    use crate::eval::equivalence::check_equivalence;
+
+    #[test]
+    fn test_gates_are_shared() {
+        let expr = Parser::new("(a and b) or not (a and b) and c").parse().unwrap();
+        let aig = Aig::from_expr(&expr).unwrap();
+        // a∧b once, ¬(a∧b)∧c, and the final or
+        assert_eq!(aig.ands().len(), 3);
+        assert_eq!(aig.inputs(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_round_trip_is_equivalent() {
+        for input in ["a xor b xor c", "a -> b or not c", "not (a or b) and c"] {
+            let expr = Parser::new(input).parse().unwrap();
+            let back = Aig::from_expr(&expr).unwrap().to_expr(0).unwrap();
+            assert!(check_equivalence(&expr, &back).unwrap().equivalent, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_cardinality_is_a_shared_counter() {
+        let expr = Parser::new("atleast(3; a, b, c, d, e, f, g, h)").parse().unwrap();
+        let aig = Aig::from_expr(&expr).unwrap();
+        // Linear in the inputs for a fixed bound, where the sum of products has 56 terms
+        assert!(aig.ands().len() < 60, "{} gates", aig.ands().len());
+        let back = aig.to_expr(0).unwrap();
+        assert!(check_equivalence(&expr, &back).unwrap().equivalent);
+    }
+
+    #[test]
+    fn test_constant_output_reads_back_as_constant() {
+        let expr = Parser::new("a and not a").parse().unwrap();
+        let aig = Aig::from_expr(&expr).unwrap();
+        assert_eq!(aig.outputs(), [FALSE]);
+        assert_eq!(aig.to_expr(0).unwrap(), Expr::False);
+    }
+}
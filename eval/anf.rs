@@ -0,0 +1,176 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::{assignment_from_index, evaluate_expression};
+use crate::eval::options;
+use crate::source::quote_identifier;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+/// Algebraic normal form: the xor of monomials, each a conjunction of variables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anf {
+    pub variables: Variables,
+    /// Monomials present, as bitmasks over the variables (bit 0 for the first);
+    /// `0` is the constant 1
+    pub monomials: Vec<usize>,
+}
+
+impl Anf {
+    /// The ANF of a truth table given in row order
+    pub fn from_truth_table(variables: Variables, results: &[bool]) -> Self {
+        let coefficients = moebius_transform(results);
+        let monomials = (0..coefficients.len()).filter(|&m| coefficients[m]).collect();
+        Self { variables, monomials }
+    }
+
+    pub fn from_expr(expr: &Expr) -> Result<Self, EvaluationError> {
+        let variables = Variables::from_expr(expr)?;
+        let results = (0..1usize << variables.len())
+            .map(|index| {
+                options::check_row(index)?;
+                Ok(evaluate_expression(expr, &assignment_from_index(&variables, index)))
+            })
+            .collect::<Result<Vec<bool>, EvaluationError>>()?;
+        Ok(Self::from_truth_table(variables, &results))
+    }
+
+    /// Most variables in any monomial; 0 for constants
+    pub fn degree(&self) -> usize {
+        self.monomials.iter().map(|m| m.count_ones() as usize).max().unwrap_or(0)
+    }
+
+    /// The variables of each monomial, ordered as [`Display`](fmt::Display)
+    /// writes them: by their variables in table order, with the constant 1 last
+    pub fn monomial_variables(&self) -> Vec<Vec<String>> {
+        let names = self.variables.to_vec();
+        let mut monomials: Vec<Vec<usize>> = self.monomials
+            .iter()
+            .map(|&monomial| (0..names.len()).filter(|bit| monomial >> bit & 1 == 1).collect())
+            .collect();
+        monomials.sort_by(|a, b| (a.is_empty(), a).cmp(&(b.is_empty(), b)));
+        monomials.into_iter().map(|monomial| monomial.into_iter().map(|bit| names[bit].clone()).collect()).collect()
+    }
+
+    /// The xor of the monomials, `false` when there are none
+    pub fn to_expr(&self) -> Expr {
+        let names = self.variables.to_vec();
+        self.monomials
+            .iter()
+            .map(|&monomial| {
+                names
+                    .iter()
+                    .enumerate()
+                    .filter(|(bit, _)| monomial >> bit & 1 == 1)
+                    .map(|(_, name)| Expr::Identifier(name.clone()))
+                    .reduce(|acc, next| Expr::And(Box::new(acc), Box::new(next)))
+                    .unwrap_or(Expr::True)
+            })
+            .reduce(|acc, next| Expr::Xor(Box::new(acc), Box::new(next)))
+            .unwrap_or(Expr::False)
+    }
+}
+
+/// The polynomial, as `a ⊕ ab ⊕ 1`: monomials multiply their variables by
+/// juxtaposition, or with `·` when a name is longer than one character
+impl fmt::Display for Anf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let compact = self.variables.iter().all(|name| name.chars().count() == 1);
+        let terms: Vec<String> = self
+            .monomial_variables()
+            .iter()
+            .map(|monomial| {
+                if monomial.is_empty() {
+                    return "1".to_string();
+                }
+                let names: Vec<String> = monomial.iter().map(|name| quote_identifier(name).into_owned()).collect();
+                names.join(if compact { "" } else { "·" })
+            })
+            .collect();
+        if terms.is_empty() { write!(f, "0") } else { write!(f, "{}", terms.join(" ⊕ ")) }
+    }
+}
+
+/// An expression's algebraic normal form, or Zhegalkin polynomial
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnfReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    /// The polynomial as written by the `Display` of [`Anf`]
+    pub polynomial: String,
+    /// Each monomial's variables, with none for the constant 1
+    pub monomials: Vec<Vec<String>>,
+    pub degree: usize,
+    /// The polynomial as an xor of conjunctions
+    pub anf: Expr,
+}
+
+/// Compute the algebraic normal form of `expr` with the Möbius transform of its truth table
+pub fn algebraic_normal_form(expr: &Expr) -> Result<AnfReport, EvaluationError> {
+    let anf = Anf::from_expr(expr)?;
+    Ok(AnfReport {
+        expression: expr.clone(),
+        variables: anf.variables.clone(),
+        polynomial: anf.to_string(),
+        monomials: anf.monomial_variables(),
+        degree: anf.degree(),
+        anf: anf.to_expr(),
+    })
+}
+
+/// ANF coefficients of a truth table in row order: coefficient `m` is the xor of
+/// the results of every row whose true variables are a subset of `m`
+pub(crate) fn moebius_transform(results: &[bool]) -> Vec<bool> {
+    let mut anf = results.to_vec();
+    let mut half = 1;
+    while half < anf.len() {
+        for block in (0..anf.len()).step_by(2 * half) {
+            for i in block..block + half {
+                anf[i + half] ^= anf[i];
+            }
+        }
+        half *= 2;
+    }
+    anf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+    use crate::eval::equivalence::check_equivalence;
+
+    fn anf_str(input: &str) -> Anf {
+        Anf::from_expr(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_or_has_a_quadratic_term() {
+        let anf = anf_str("a or b");
+        // a ∨ b = a ⊕ b ⊕ ab
+        assert_eq!(anf.monomials, [0b01, 0b10, 0b11]);
+        assert_eq!(anf.degree(), 2);
+        assert_eq!(anf.to_expr().to_string(), "((a ⊕ b) ⊕ (a ∧ b))");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let expr = Parser::new("(a -> b) and not (c iff a)").parse().unwrap();
+        let anf = Anf::from_expr(&expr).unwrap();
+        assert!(check_equivalence(&expr, &anf.to_expr()).unwrap().equivalent);
+        assert_eq!(anf_str("not a").monomials, [0b0, 0b1]);
+    }
+
+    #[test]
+    fn test_polynomial() {
+        assert_eq!(anf_str("a or b").to_string(), "a ⊕ ab ⊕ b");
+        assert_eq!(anf_str("not a or b").to_string(), "a ⊕ ab ⊕ 1");
+        assert_eq!(anf_str("a and not a").to_string(), "0");
+        assert_eq!(anf_str("true").to_string(), "1");
+        assert_eq!(anf_str("x and `long`").to_string(), "long·x");
+
+        let report = algebraic_normal_form(&Parser::new("a iff b").parse().unwrap()).unwrap();
+        assert_eq!(report.polynomial, "a ⊕ b ⊕ 1");
+        assert_eq!(report.monomials, [vec!["a".to_string()], vec!["b".to_string()], vec![]]);
+        assert_eq!(report.degree, 1);
+    }
+}
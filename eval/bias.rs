@@ -0,0 +1,109 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::evaluate_results;
+use serde::{Serialize, Deserialize};
+
+/// The bias of the expression with one variable held fixed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalBias {
+    pub variable: String,
+    /// True rows among the half of the table where the variable is true
+    pub true_when_set: usize,
+    /// True rows among the half of the table where the variable is false
+    pub true_when_clear: usize,
+    /// Fraction of true rows minus 1/2 when the variable is true
+    pub bias_when_set: f64,
+    /// Fraction of true rows minus 1/2 when the variable is false
+    pub bias_when_clear: f64,
+}
+
+/// How far an expression is from being true on half of its rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiasReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub true_count: usize,
+    pub total: usize,
+    /// Fraction of true rows minus 1/2, between -1/2 (never true) and 1/2 (always true)
+    pub bias: f64,
+    pub conditionals: Vec<ConditionalBias>,
+}
+
+impl BiasReport {
+    /// True on exactly half of the rows
+    pub fn balanced(&self) -> bool {
+        2 * self.true_count == self.total
+    }
+}
+
+/// Count the true rows of `expr` overall and with each variable fixed either way
+pub fn analyze_bias(expr: &Expr) -> Result<BiasReport, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let total = 1usize << variables.len();
+    let results = evaluate_results(expr, &variables)?;
+    let true_count = results.iter().filter(|&&r| r).count();
+
+    let bias = |count: usize, rows: usize| count as f64 / rows as f64 - 0.5;
+    let conditionals = variables
+        .iter()
+        .enumerate()
+        .map(|(bit, name)| {
+            let true_when_set = (0..total).filter(|i| (i >> bit) & 1 == 1 && results[*i]).count();
+            let true_when_clear = true_count - true_when_set;
+            ConditionalBias {
+                variable: name.to_string(),
+                true_when_set,
+                true_when_clear,
+                bias_when_set: bias(true_when_set, total / 2),
+                bias_when_clear: bias(true_when_clear, total / 2),
+            }
+        })
+        .collect();
+
+    Ok(BiasReport {
+        expression: expr.clone(),
+        variables,
+        true_count,
+        total,
+        bias: bias(true_count, total),
+        conditionals,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn bias_str(input: &str) -> BiasReport {
+        analyze_bias(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_overall_bias() {
+        let report = bias_str("a or b");
+        assert_eq!((report.true_count, report.total), (3, 4));
+        assert_eq!(report.bias, 0.25);
+        assert!(!report.balanced());
+        assert!(bias_str("a xor b").balanced());
+    }
+
+    #[test]
+    fn test_conditional_biases() {
+        let report = bias_str("a and (b or c)");
+        let a = &report.conditionals[0];
+        // Fixing a decides whether b ∨ c matters at all
+        assert_eq!((a.true_when_set, a.true_when_clear), (3, 0));
+        assert_eq!((a.bias_when_set, a.bias_when_clear), (0.25, -0.5));
+        let b = &report.conditionals[1];
+        assert_eq!((b.true_when_set, b.true_when_clear), (2, 1));
+    }
+
+    #[test]
+    fn test_constant_has_no_conditionals() {
+        let report = bias_str("true");
+        assert_eq!((report.true_count, report.total), (1, 1));
+        assert_eq!(report.bias, 0.5);
+        assert!(report.conditionals.is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::{assignment_from_index, evaluate_expression};
+use crate::eval::options;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// Whether an expression is true on every row, no row, or some rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Classification {
+    Tautology,
+    Contradiction,
+    Contingent,
+}
+
+impl std::fmt::Display for Classification {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Classification::Tautology => write!(f, "tautology"),
+            Classification::Contradiction => write!(f, "contradiction"),
+            Classification::Contingent => write!(f, "contingent"),
+        }
+    }
+}
+
+/// An expression's classification, with a row of each value it takes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassificationReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub classification: Classification,
+    /// The first row, in truth-table order, where the expression is true
+    pub true_example: Option<HashMap<String, bool>>,
+    /// The first row where the expression is false
+    pub false_example: Option<HashMap<String, bool>>,
+}
+
+/// Evaluate rows in truth-table order until the expression has been both true
+/// and false, or every row has been seen
+pub fn classify(expr: &Expr) -> Result<ClassificationReport, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let mut true_example = None;
+    let mut false_example = None;
+    for index in 0..1usize << variables.len() {
+        options::check_row(index)?;
+        let assignment = assignment_from_index(&variables, index);
+        let example = if evaluate_expression(expr, &assignment) { &mut true_example } else { &mut false_example };
+        example.get_or_insert(assignment);
+        if true_example.is_some() && false_example.is_some() {
+            break;
+        }
+    }
+
+    Ok(report(expr, variables, true_example, false_example))
+}
+
+/// Classify an expression from its results in truth-table order
+pub(crate) fn classify_results(expr: &Expr, variables: Variables, results: &[bool]) -> ClassificationReport {
+    let example = |value: bool| results.iter().position(|&result| result == value).map(|index| assignment_from_index(&variables, index));
+    let (true_example, false_example) = (example(true), example(false));
+    report(expr, variables, true_example, false_example)
+}
+
+fn report(
+    expr: &Expr,
+    variables: Variables,
+    true_example: Option<HashMap<String, bool>>,
+    false_example: Option<HashMap<String, bool>>,
+) -> ClassificationReport {
+    let classification = match (&true_example, &false_example) {
+        (Some(_), Some(_)) => Classification::Contingent,
+        (Some(_), None) => Classification::Tautology,
+        _ => Classification::Contradiction,
+    };
+    ClassificationReport { expression: expr.clone(), variables, classification, true_example, false_example }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn classify_str(input: &str) -> ClassificationReport {
+        classify(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_classifications() {
+        assert_eq!(classify_str("a or not a").classification, Classification::Tautology);
+        assert_eq!(classify_str("(a -> b) and a and not b").classification, Classification::Contradiction);
+        assert_eq!(classify_str("true").classification, Classification::Tautology);
+        assert_eq!(classify_str("false").classification, Classification::Contradiction);
+
+        let report = classify_str("a and b");
+        assert_eq!(report.classification, Classification::Contingent);
+        assert_eq!(report.false_example, Some(HashMap::from([("a".to_string(), false), ("b".to_string(), false)])));
+        assert_eq!(report.true_example, Some(HashMap::from([("a".to_string(), true), ("b".to_string(), true)])));
+    }
+}
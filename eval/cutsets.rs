@@ -0,0 +1,162 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::reduction::QuineMcCluskey;
+use crate::eval::truth_table::{evaluate_expression, assignment_from_index};
+use crate::eval::options;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// A minimal set of events that together make the expression true
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CutSet {
+    /// Events that occur
+    pub events: Vec<String>,
+    /// Events that must not occur; always empty for coherent (monotone) expressions
+    pub absent: Vec<String>,
+    /// Probability of the cut set with independent events, when every event has one
+    pub probability: Option<f64>,
+}
+
+impl CutSet {
+    /// Number of literals (the order of the cut set)
+    pub fn order(&self) -> usize {
+        self.events.len() + self.absent.len()
+    }
+}
+
+/// The minimal cut sets of an expression, smallest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CutSetReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub cut_sets: Vec<CutSet>,
+    /// Exact probability of the expression with independent events, when every variable has one
+    pub probability: Option<f64>,
+    /// Sum of the cut-set probabilities, the rare-event approximation used in fault-tree analysis
+    pub rare_event_approximation: Option<f64>,
+}
+
+/// List the minimal cut sets (prime implicants) of `expr`, ordered by size and
+/// then by decreasing probability.
+///
+/// `probabilities` gives the chance that each variable is true; cut sets whose
+/// events all have one are weighted. Probabilities outside `[0, 1]` and
+/// probabilities for variables the expression does not use are rejected.
+pub fn cut_sets(expr: &Expr, probabilities: &HashMap<String, f64>) -> Result<CutSetReport, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    for (var, &p) in probabilities {
+        if !variables.contains(var) {
+            return Err(EvaluationError::InvalidTruthAssignment {
+                variable: var.clone(),
+                context: "probability for a variable the expression does not use".to_string(),
+            });
+        }
+        if !(0.0..=1.0).contains(&p) {
+            return Err(EvaluationError::InvalidTruthAssignment {
+                variable: var.clone(),
+                context: format!("probability {} is outside [0, 1]", p),
+            });
+        }
+    }
+
+    let implicants = if variables.is_empty() {
+        // A constant has the empty cut set when it is true, and none otherwise
+        if evaluate_expression(expr, &HashMap::new()) { vec![String::new()] } else { Vec::new() }
+    } else {
+        QuineMcCluskey::from_expression(expr)?
+            .prime_implicants()
+            .into_iter()
+            .map(|implicant| implicant.cube)
+            .collect()
+    };
+
+    let weighted = !probabilities.is_empty();
+    let mut cut_sets: Vec<CutSet> = implicants
+        .iter()
+        .map(|cube| {
+            let mut cut_set = CutSet { events: Vec::new(), absent: Vec::new(), probability: weighted.then_some(1.0) };
+            for (var, c) in variables.iter().zip(cube.chars()) {
+                let (list, p) = match c {
+                    '1' => (&mut cut_set.events, probabilities.get(var).copied()),
+                    '0' => (&mut cut_set.absent, probabilities.get(var).map(|p| 1.0 - p)),
+                    _ => continue,
+                };
+                list.push(var.to_string());
+                cut_set.probability = cut_set.probability.zip(p).map(|(a, b)| a * b);
+            }
+            cut_set
+        })
+        .collect();
+    cut_sets.sort_by(|a, b| {
+        a.order()
+            .cmp(&b.order())
+            .then_with(|| b.probability.unwrap_or(0.0).total_cmp(&a.probability.unwrap_or(0.0)))
+            .then_with(|| (&a.events, &a.absent).cmp(&(&b.events, &b.absent)))
+    });
+
+    let probability = (weighted && variables.iter().all(|var| probabilities.contains_key(var)))
+        .then(|| exact_probability(expr, &variables, probabilities))
+        .transpose()?;
+    let rare_event_approximation = cut_sets.iter().map(|c| c.probability).sum();
+
+    Ok(CutSetReport {
+        expression: expr.clone(),
+        variables,
+        cut_sets,
+        probability,
+        rare_event_approximation,
+    })
+}
+
+/// Sum the probabilities of the satisfying assignments
+fn exact_probability(expr: &Expr, variables: &Variables, probabilities: &HashMap<String, f64>) -> Result<f64, EvaluationError> {
+    let mut probability = 0.0;
+    for index in 0..1usize << variables.len() {
+        options::check_row(index)?;
+        let assignment = assignment_from_index(variables, index);
+        if evaluate_expression(expr, &assignment) {
+            probability += assignment
+                .iter()
+                .map(|(var, &value)| if value { probabilities[var] } else { 1.0 - probabilities[var] })
+                .product::<f64>();
+        }
+    }
+    Ok(probability)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn cut_sets_str(input: &str, probabilities: &[(&str, f64)]) -> CutSetReport {
+        let expr = Parser::new(input).parse().unwrap();
+        let probabilities = probabilities.iter().map(|(var, p)| (var.to_string(), *p)).collect();
+        cut_sets(&expr, &probabilities).unwrap()
+    }
+
+    #[test]
+    fn test_cut_sets_are_minimal_and_sorted_by_order() {
+        let report = cut_sets_str("(a and b and c) or d or (a and b)", &[]);
+        let events: Vec<&[String]> = report.cut_sets.iter().map(|c| c.events.as_slice()).collect();
+        assert_eq!(events, [&["d".to_string()][..], &["a".to_string(), "b".to_string()][..]]);
+        assert_eq!(report.probability, None);
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let report = cut_sets_str("(a and b) or (a and c)", &[("a", 0.1), ("b", 0.2), ("c", 0.5)]);
+        // Same order, so the likelier cut set comes first
+        assert_eq!(report.cut_sets[0].events, ["a", "c"]);
+        assert!((report.cut_sets[0].probability.unwrap() - 0.05).abs() < 1e-12);
+        assert!((report.rare_event_approximation.unwrap() - 0.07).abs() < 1e-12);
+        // P(a) · P(b ∨ c) = 0.1 · 0.6
+        assert!((report.probability.unwrap() - 0.06).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_non_coherent_cut_sets_record_absent_events() {
+        let report = cut_sets_str("a and not b", &[("a", 0.5), ("b", 0.25)]);
+        assert_eq!(report.cut_sets, [CutSet { events: vec!["a".to_string()], absent: vec!["b".to_string()], probability: Some(0.375) }]);
+    }
+}
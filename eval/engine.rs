@@ -0,0 +1,252 @@
+use crate::source::{Expr, Parser, ParseError, Library};
+use crate::eval::{Evaluator, Variables, EvaluationError};
+use crate::eval::options::EvalOptions;
+use crate::eval::truth_table::TruthTable;
+use crate::eval::equivalence::EquivalenceCheck;
+use crate::eval::reduction::Reduction;
+use crate::eval::classify::ClassificationReport;
+use crate::eval::sat::Satisfiability;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Entries each cache holds before it is emptied and starts again
+pub const DEFAULT_CACHE_CAPACITY: usize = 4096;
+
+/// A map that empties itself rather than grow past its capacity
+#[derive(Debug)]
+struct Cache<K, V> {
+    entries: RwLock<HashMap<K, Arc<V>>>,
+}
+
+impl<K: Eq + Hash, V> Cache<K, V> {
+    fn new() -> Self {
+        Self { entries: RwLock::new(HashMap::new()) }
+    }
+
+    fn get(&self, key: &K) -> Option<Arc<V>> {
+        // A panic elsewhere can't leave a map of complete entries inconsistent
+        self.entries.read().unwrap_or_else(PoisonError::into_inner).get(key).cloned()
+    }
+
+    fn insert(&self, key: K, value: Arc<V>, capacity: usize) {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        if entries.len() >= capacity {
+            entries.clear();
+        }
+        if capacity > 0 {
+            entries.insert(key, value);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.read().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    fn clear(&self) {
+        self.entries.write().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+}
+
+/// An evaluator to share between the threads of a server, REPL or language
+/// server: it holds the limits every evaluation runs under, an operator
+/// library, interned variable names, and caches of parsed expressions and
+/// truth tables, all behind `&self`.
+///
+/// ```
+/// use std::sync::Arc;
+/// use ttt::eval::{Engine, EvalOptions};
+///
+/// let engine = Arc::new(Engine::new(EvalOptions { max_vars: 12, ..Default::default() }));
+/// let worker = Arc::clone(&engine);
+/// std::thread::spawn(move || {
+///     let expr = worker.parse("a and b").unwrap();
+///     assert_eq!(worker.truth_table(&expr).unwrap().rows.len(), 4);
+/// }).join().unwrap();
+/// // The same source is parsed once
+/// assert!(Arc::ptr_eq(&engine.parse("a and b").unwrap(), &engine.parse("a and b").unwrap()));
+/// ```
+#[derive(Debug)]
+pub struct Engine {
+    options: EvalOptions,
+    library: Option<Library>,
+    cache_capacity: usize,
+    names: RwLock<HashSet<Arc<str>>>,
+    expressions: Cache<String, Expr>,
+    tables: Cache<Expr, TruthTable>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new(EvalOptions::default())
+    }
+}
+
+impl Engine {
+    pub fn new(options: EvalOptions) -> Self {
+        Self {
+            options,
+            library: None,
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            names: RwLock::new(HashSet::new()),
+            expressions: Cache::new(),
+            tables: Cache::new(),
+        }
+    }
+
+    /// Expand the library's definitions in every expression this engine parses
+    pub fn with_library(mut self, library: Library) -> Self {
+        self.library = Some(library);
+        self
+    }
+
+    /// Hold at most `capacity` parsed expressions and as many truth tables; 0 disables caching
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    pub fn options(&self) -> &EvalOptions {
+        &self.options
+    }
+
+    /// Run `f` under this engine's limits, for the [`Evaluator`] operations
+    /// the engine has no method for
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        Evaluator::with_options(&self.options, f)
+    }
+
+    /// The shared copy of `name`, so that every variable set and truth table
+    /// the engine returns refers to one allocation per name
+    pub fn intern(&self, name: &str) -> Arc<str> {
+        if let Some(interned) = self.names.read().unwrap_or_else(PoisonError::into_inner).get(name) {
+            return Arc::clone(interned);
+        }
+        let mut names = self.names.write().unwrap_or_else(PoisonError::into_inner);
+        // Another thread may have interned it between the two locks
+        if let Some(interned) = names.get(name) {
+            return Arc::clone(interned);
+        }
+        let interned: Arc<str> = Arc::from(name);
+        names.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// Number of distinct variable names interned so far
+    pub fn interned_count(&self) -> usize {
+        self.names.read().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Parse `input`, returning the cached expression if the same text was parsed before
+    pub fn parse(&self, input: &str) -> Result<Arc<Expr>, ParseError> {
+        let key = input.trim();
+        if let Some(expr) = self.expressions.get(&key.to_string()) {
+            return Ok(expr);
+        }
+        let parser = Parser::new(key).with_max_nesting(self.options.max_depth);
+        let mut parser = match &self.library {
+            Some(library) => parser.with_library(library.clone()),
+            None => parser,
+        };
+        let expr = Arc::new(parser.parse()?);
+        self.expressions.insert(key.to_string(), Arc::clone(&expr), self.cache_capacity);
+        Ok(expr)
+    }
+
+    /// The expression's variables in truth-table order, interned
+    pub fn variables(&self, expr: &Expr) -> Result<Variables, EvaluationError> {
+        let variables = self.run(|| Variables::from_expr(expr))?;
+        Ok(variables.interned(|name| self.intern(name)))
+    }
+
+    /// The expression's truth table, computed once per distinct expression
+    pub fn truth_table(&self, expr: &Expr) -> Result<Arc<TruthTable>, EvaluationError> {
+        if let Some(table) = self.tables.get(expr) {
+            return Ok(table);
+        }
+        let mut table = self.run(|| Evaluator::generate_truth_table(expr))?;
+        table.variables = table.variables.interned(|name| self.intern(name));
+        let table = Arc::new(table);
+        self.tables.insert(expr.clone(), Arc::clone(&table), self.cache_capacity);
+        Ok(table)
+    }
+
+    pub fn check_equivalence(&self, left: &Expr, right: &Expr) -> Result<EquivalenceCheck, EvaluationError> {
+        self.run(|| Evaluator::check_equivalence(left, right))
+    }
+
+    pub fn reduce(&self, expr: &Expr) -> Result<Reduction, EvaluationError> {
+        self.run(|| Evaluator::reduce_expression(expr))
+    }
+
+    pub fn classify(&self, expr: &Expr) -> Result<ClassificationReport, EvaluationError> {
+        self.run(|| Evaluator::classify(expr))
+    }
+
+    pub fn check_satisfiability(&self, expr: &Expr) -> Result<Satisfiability, EvaluationError> {
+        self.run(|| Evaluator::check_satisfiability(expr))
+    }
+
+    /// Number of parsed expressions and truth tables cached
+    pub fn cached_count(&self) -> (usize, usize) {
+        (self.expressions.len(), self.tables.len())
+    }
+
+    /// Drop every cached expression and truth table; interned names are kept
+    pub fn clear_caches(&self) {
+        self.expressions.clear();
+        self.tables.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_engine_is_shared_across_threads() {
+        assert_send_sync::<Engine>();
+        let engine = Arc::new(Engine::default());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let engine = Arc::clone(&engine);
+                thread::spawn(move || {
+                    let expr = engine.parse("(a and b) or c").unwrap();
+                    engine.truth_table(&expr).unwrap().rows.len()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 8);
+        }
+        assert_eq!(engine.cached_count(), (1, 1));
+        assert_eq!(engine.interned_count(), 3);
+
+        let expr = engine.parse(" (a and b) or c ").unwrap();
+        let variables = engine.variables(&expr).unwrap();
+        let [a, b, c] = &variables.shared().collect::<Vec<_>>()[..] else { panic!("three variables") };
+        assert!(Arc::ptr_eq(a, &engine.intern("a")));
+        assert_eq!((b.as_ref(), c.as_ref()), ("b", "c"));
+        // The cached table and every later variable set share the names
+        let table = engine.truth_table(&expr).unwrap();
+        assert!(table.variables.shared().zip(variables.shared()).all(|(left, right)| Arc::ptr_eq(left, right)));
+    }
+
+    #[test]
+    fn test_options_and_caches() {
+        let engine = Engine::new(EvalOptions { max_vars: 2, ..Default::default() }).with_cache_capacity(1);
+        let wide = engine.parse("a and b and c").unwrap();
+        assert!(matches!(engine.truth_table(&wide), Err(EvaluationError::TooManyVariables { max: 2, .. })));
+        assert!(engine.classify(&wide).is_err());
+
+        engine.parse("a").unwrap();
+        engine.parse("b").unwrap();
+        assert_eq!(engine.cached_count().0, 1);
+        engine.clear_caches();
+        assert_eq!(engine.cached_count(), (0, 0));
+        assert!(engine.parse("a and").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::source::Expr;
 use crate::eval::{Variables, EvaluationError};
 use crate::eval::truth_table::evaluate_expression;
+use crate::eval::options;
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 
@@ -10,6 +11,9 @@ pub struct EquivalenceCheck {
     pub equivalent: bool,
     pub variables: Variables,
     pub differences: Vec<EquivalenceDifference>,
+    /// Number of assignments skipped because they satisfy the don't-care condition
+    #[serde(default)]
+    pub ignored: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,15 +25,40 @@ pub struct EquivalenceDifference {
 
 /// Check if two boolean expressions are equivalent
 pub fn check_equivalence(left: &Expr, right: &Expr) -> Result<EquivalenceCheck, EvaluationError> {
+    check_equivalence_inner(left, right, None)
+}
+
+/// Check if two boolean expressions are equivalent outside a don't-care set.
+///
+/// Assignments that satisfy `dont_care` are skipped, so the expressions only
+/// need to agree where the don't-care condition is false.
+pub fn check_equivalence_with_dont_care(left: &Expr, right: &Expr, dont_care: &Expr) -> Result<EquivalenceCheck, EvaluationError> {
+    check_equivalence_inner(left, right, Some(dont_care))
+}
+
+fn check_equivalence_inner(left: &Expr, right: &Expr, dont_care: Option<&Expr>) -> Result<EquivalenceCheck, EvaluationError> {
     let left_vars = Variables::from_expr(left)?;
     let right_vars = Variables::from_expr(right)?;
-    let all_vars = left_vars.union(&right_vars);
+    let mut all_vars = left_vars.union(&right_vars);
+    if let Some(dc) = dont_care {
+        all_vars = all_vars.union(&Variables::from_expr(dc)?);
+    }
     
     let mut differences = Vec::new();
+    let mut ignored = 0;
     let num_vars = all_vars.len();
     
     if num_vars == 0 {
         // Handle expressions with no variables
+        if dont_care.is_some_and(|dc| evaluate_expression(dc, &HashMap::new())) {
+            return Ok(EquivalenceCheck {
+                equivalent: true,
+                variables: all_vars,
+                differences: vec![],
+                ignored: 1,
+            });
+        }
+        
         let left_result = evaluate_expression(left, &HashMap::new());
         let right_result = evaluate_expression(right, &HashMap::new());
         
@@ -45,18 +74,25 @@ pub fn check_equivalence(left: &Expr, right: &Expr) -> Result<EquivalenceCheck,
             } else {
                 vec![]
             },
+            ignored: 0,
         });
     }
     
     let num_combinations = 1 << num_vars;
     
     for i in 0..num_combinations {
+        options::check_row(i)?;
         let mut assignments = HashMap::new();
         
         // Create assignment from bit pattern
         for (var_idx, var_name) in all_vars.iter().enumerate() {
             let bit_value = (i >> var_idx) & 1 == 1;
-            assignments.insert(var_name.clone(), bit_value);
+            assignments.insert(var_name.to_string(), bit_value);
+        }
+        
+        if dont_care.is_some_and(|dc| evaluate_expression(dc, &assignments)) {
+            ignored += 1;
+            continue;
         }
         
         let left_result = evaluate_expression(left, &assignments);
@@ -75,5 +111,6 @@ pub fn check_equivalence(left: &Expr, right: &Expr) -> Result<EquivalenceCheck,
         equivalent: differences.is_empty(),
         variables: all_vars,
         differences,
+        ignored,
     })
 }
\ No newline at end of file
@@ -0,0 +1,277 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::equivalence::check_equivalence;
+use crate::eval::rng::SplitMix64;
+use crate::eval::options;
+use crate::config::MAX_EXPANSION_SIZE;
+use serde::{Serialize, Deserialize};
+
+/// An expression grown by equivalence-preserving rewrites
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expansion {
+    pub original: Expr,
+    pub expanded: Expr,
+    /// Node count of the expanded expression
+    pub size: usize,
+    /// Number of rewrites applied
+    pub steps: usize,
+    pub seed: u64,
+}
+
+/// Grow `expr` to `target_size` nodes with randomly chosen rewrites that
+/// preserve its truth table.
+///
+/// Each rewrite is chosen among those that add no more nodes than are still
+/// missing, so the result has exactly `target_size` nodes, or one more when
+/// the expression starts 1 or 3 nodes short and no rewrite adds exactly that
+/// many. An expression that already has `target_size` nodes is returned
+/// unchanged. The same seed always produces the same expansion. The result is checked
+/// against the original with the equivalence checker before it is returned.
+pub fn expand_expression(expr: &Expr, target_size: usize, seed: u64) -> Result<Expansion, EvaluationError> {
+    if target_size > MAX_EXPANSION_SIZE {
+        return Err(EvaluationError::ExpressionTooComplex {
+            reason: format!("target size {} exceeds the maximum of {}", target_size, MAX_EXPANSION_SIZE),
+        });
+    }
+    let variables = Variables::from_expr(expr)?.to_vec();
+
+    let mut rng = SplitMix64(seed);
+    let mut expanded = expr.clone();
+    let mut size = expr.node_count();
+    let mut steps = 0;
+    while size < target_size {
+        options::check_deadline()?;
+        let target = rng.below(size);
+        let budget = target_size - size;
+        let mut added = 0;
+        expanded = rewrite_at(expanded, target, &mut |node| {
+            let (node, growth) = apply_random_rule(node, &variables, budget, &mut rng);
+            added = growth;
+            node
+        });
+        size += added;
+        steps += 1;
+    }
+    debug_assert_eq!(size, expanded.node_count());
+
+    if !check_equivalence(expr, &expanded)?.equivalent {
+        return Err(EvaluationError::UnsupportedOperation {
+            operation: "expansion produced a non-equivalent expression".to_string(),
+        });
+    }
+
+    Ok(Expansion {
+        original: expr.clone(),
+        size,
+        expanded,
+        steps,
+        seed,
+    })
+}
+
+/// Replace the node at pre-order position `index` with `rewrite(node)`
+fn rewrite_at(expr: Expr, index: usize, rewrite: &mut dyn FnMut(Expr) -> Expr) -> Expr {
+    if index == 0 {
+        return rewrite(expr);
+    }
+    let index = index - 1;
+    match expr {
+        Expr::Identifier(_) | Expr::True | Expr::False => expr,
+        Expr::Not(inner) => Expr::Not(Box::new(rewrite_at(*inner, index, rewrite))),
+        Expr::And(left, right) => rewrite_binary(Expr::And, *left, *right, index, rewrite),
+        Expr::Or(left, right) => rewrite_binary(Expr::Or, *left, *right, index, rewrite),
+        Expr::Xor(left, right) => rewrite_binary(Expr::Xor, *left, *right, index, rewrite),
+        Expr::Implication(left, right) => rewrite_binary(Expr::Implication, *left, *right, index, rewrite),
+        Expr::Iff(left, right) => rewrite_binary(Expr::Iff, *left, *right, index, rewrite),
+        Expr::Nand(left, right) => rewrite_binary(Expr::Nand, *left, *right, index, rewrite),
+        Expr::Nor(left, right) => rewrite_binary(Expr::Nor, *left, *right, index, rewrite),
+        Expr::Ite(cond, then, otherwise) => {
+            let (cond_size, then_size) = (cond.node_count(), then.node_count());
+            if index < cond_size {
+                Expr::Ite(Box::new(rewrite_at(*cond, index, rewrite)), then, otherwise)
+            } else if index < cond_size + then_size {
+                Expr::Ite(cond, Box::new(rewrite_at(*then, index - cond_size, rewrite)), otherwise)
+            } else {
+                Expr::Ite(cond, then, Box::new(rewrite_at(*otherwise, index - cond_size - then_size, rewrite)))
+            }
+        }
+        Expr::Cardinality(kind, k, operands) => Expr::Cardinality(kind, k, rewrite_operands(operands, index, rewrite)),
+        Expr::Custom(operator, operands) => Expr::Custom(operator, rewrite_operands(operands, index, rewrite)),
+        Expr::Quantified(quantifier, var, body) => Expr::Quantified(quantifier, var, Box::new(rewrite_at(*body, index, rewrite))),
+    }
+}
+
+/// Rewrite the node at pre-order position `index` within a list of operands
+fn rewrite_operands(operands: Vec<Expr>, index: usize, rewrite: &mut dyn FnMut(Expr) -> Expr) -> Vec<Expr> {
+    let mut remaining = Some(index);
+    operands
+        .into_iter()
+        .map(|operand| match remaining {
+            Some(index) if index < operand.node_count() => {
+                remaining = None;
+                rewrite_at(operand, index, rewrite)
+            }
+            Some(index) => {
+                remaining = Some(index - operand.node_count());
+                operand
+            }
+            None => operand,
+        })
+        .collect()
+}
+
+fn rewrite_binary(
+    make: fn(Box<Expr>, Box<Expr>) -> Expr,
+    left: Expr,
+    right: Expr,
+    index: usize,
+    rewrite: &mut dyn FnMut(Expr) -> Expr,
+) -> Expr {
+    let left_size = left.node_count();
+    if index < left_size {
+        make(Box::new(rewrite_at(left, index, rewrite)), Box::new(right))
+    } else {
+        make(Box::new(left), Box::new(rewrite_at(right, index - left_size, rewrite)))
+    }
+}
+
+fn not(expr: Expr) -> Expr {
+    Expr::Not(Box::new(expr))
+}
+
+fn and(left: Expr, right: Expr) -> Expr {
+    Expr::And(Box::new(left), Box::new(right))
+}
+
+fn or(left: Expr, right: Expr) -> Expr {
+    Expr::Or(Box::new(left), Box::new(right))
+}
+
+/// Nodes added by the operator-specific rewrite of a node of `size` nodes, if it has one
+fn specific_growth(node: &Expr, size: usize) -> Option<usize> {
+    match node {
+        Expr::And(..) | Expr::Or(..) => Some(3),
+        Expr::Implication(..) | Expr::Nand(..) | Expr::Nor(..) => Some(1),
+        // Both operands are repeated
+        Expr::Xor(..) | Expr::Iff(..) => Some(size + 3),
+        Expr::Ite(cond, _, _) => Some(cond.node_count() + 3),
+        _ => None,
+    }
+}
+
+/// Whether adding `growth` nodes leaves a number the general rules can still
+/// add exactly: double negation and the identities add 2 and 5 nodes to any
+/// node, which together make every count but 1 and 3
+fn fits(growth: usize, budget: usize) -> bool {
+    growth <= budget && !matches!(budget - growth, 1 | 3)
+}
+
+/// Apply one growing rewrite, chosen at random among those that fit the node
+/// and add at most `budget` nodes; returns the new node and the nodes added
+fn apply_random_rule(node: Expr, variables: &[String], budget: usize, rng: &mut SplitMix64) -> (Expr, usize) {
+    let var = Expr::Identifier(variables[rng.below(variables.len())].clone());
+    let size = node.node_count();
+
+    // Operator-specific rewrites are tried first half the time
+    if rng.below(2) == 0 {
+        match specific_growth(&node, size) {
+            Some(growth) if fits(growth, budget) => return (apply_specific_rule(node), growth),
+            _ => {}
+        }
+    }
+    apply_general_rule(node, size, var, budget, rng)
+}
+
+/// The rewrite that removes the operator at the top of `node`
+fn apply_specific_rule(node: Expr) -> Expr {
+    match node {
+        // De Morgan
+        Expr::And(left, right) => not(or(not(*left), not(*right))),
+        Expr::Or(left, right) => not(and(not(*left), not(*right))),
+        // a → b ≡ ¬a ∨ b
+        Expr::Implication(left, right) => or(not(*left), *right),
+        // a ⊕ b ≡ (a ∧ ¬b) ∨ (¬a ∧ b)
+        Expr::Xor(left, right) => or(and((*left).clone(), not((*right).clone())), and(not(*left), *right)),
+        // a ↑ b ≡ ¬(a ∧ b), a ↓ b ≡ ¬(a ∨ b)
+        Expr::Nand(left, right) => not(and(*left, *right)),
+        Expr::Nor(left, right) => not(or(*left, *right)),
+        // a ↔ b ≡ (a ∧ b) ∨ (¬a ∧ ¬b)
+        Expr::Iff(left, right) => or(and((*left).clone(), (*right).clone()), and(not(*left), not(*right))),
+        // c ? a : b ≡ (c ∧ a) ∨ (¬c ∧ b)
+        Expr::Ite(cond, then, otherwise) => or(and((*cond).clone(), *then), and(not(*cond), *otherwise)),
+        node => node,
+    }
+}
+
+/// Rewrites that apply to any node, with the nodes each adds to one of `size` nodes
+fn apply_general_rule(node: Expr, size: usize, var: Expr, budget: usize, rng: &mut SplitMix64) -> (Expr, usize) {
+    let growth = [2, size + 1, size + 6, 5, 5];
+    let mut fitting: Vec<usize> = (0..growth.len()).filter(|&rule| fits(growth[rule], budget)).collect();
+    if fitting.is_empty() {
+        fitting = (0..growth.len()).filter(|&rule| growth[rule] <= budget).collect();
+    }
+    // Double negation adds the fewest nodes, so it is used when nothing fits
+    let rule = if fitting.is_empty() { 0 } else { fitting[rng.below(fitting.len())] };
+    let expanded = match rule {
+        // Double negation
+        0 => not(not(node)),
+        // Idempotence
+        1 => if rng.below(2) == 0 { and(node.clone(), node) } else { or(node.clone(), node) },
+        // Shannon expansion on a variable: x ≡ (x ∧ v) ∨ (x ∧ ¬v)
+        2 => or(and(node.clone(), var.clone()), and(node, not(var))),
+        // Identity with a contradiction: x ≡ x ∨ (v ∧ ¬v)
+        3 => or(node, and(var.clone(), not(var))),
+        // Identity with a tautology: x ≡ x ∧ (v ∨ ¬v)
+        _ => and(node, or(var.clone(), not(var))),
+    };
+    (expanded, growth[rule])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    #[test]
+    fn test_expand_reaches_target_and_stays_equivalent() {
+        let expr = Parser::new("a and b or not c").parse().unwrap();
+        let expansion = expand_expression(&expr, 60, 7).unwrap();
+        assert_eq!(expansion.size, 60);
+        assert_eq!(expansion.size, expansion.expanded.node_count());
+        assert!(check_equivalence(&expr, &expansion.expanded).unwrap().equivalent);
+    }
+
+    #[test]
+    fn test_expand_stops_at_the_target() {
+        let expr = Parser::new("(a xor b) iff (c ? a : b)").parse().unwrap();
+        assert_eq!(expr.node_count(), 8);
+        for seed in 0..50 {
+            for target in [10, 12, 20, 37, 200] {
+                let expansion = expand_expression(&expr, target, seed).unwrap();
+                assert_eq!(expansion.size, target, "seed {}", seed);
+                assert_eq!(expansion.size, expansion.expanded.node_count());
+            }
+            // One node short, with no rewrite that adds exactly one
+            let expansion = expand_expression(&Parser::new("a and b").parse().unwrap(), 4, seed).unwrap();
+            assert!((4..=5).contains(&expansion.size));
+        }
+    }
+
+    #[test]
+    fn test_expand_is_reproducible() {
+        let expr = Parser::new("a xor b").parse().unwrap();
+        let first = expand_expression(&expr, 40, 42).unwrap();
+        let second = expand_expression(&expr, 40, 42).unwrap();
+        let other = expand_expression(&expr, 40, 43).unwrap();
+        assert_eq!(first.expanded, second.expanded);
+        assert_ne!(first.expanded, other.expanded);
+    }
+
+    #[test]
+    fn test_small_target_leaves_expression_unchanged() {
+        let expr = Parser::new("a and b").parse().unwrap();
+        let expansion = expand_expression(&expr, 2, 0).unwrap();
+        assert_eq!(expansion.expanded, expr);
+        assert_eq!(expansion.steps, 0);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::evaluate_results;
+use crate::eval::options;
+use crate::source::Expr;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// Why an expression has its value under one assignment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explanation {
+    pub assignment: HashMap<String, bool>,
+    pub result: bool,
+    /// Variables whose values in `assignment` force the result on their own, in
+    /// variable order. No variable can be dropped without the result depending
+    /// on the others; empty when the expression is constant.
+    pub reason: Vec<String>,
+}
+
+/// Explanations for a sequence of assignments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplanationReport {
+    pub variables: Variables,
+    pub explanations: Vec<Explanation>,
+}
+
+/// Explain the result of `expr` for each assignment with the literals responsible
+/// for it: a prime implicant of the expression (or of its negation, when the
+/// result is false) that agrees with the assignment.
+///
+/// Every variable of the expression must be present in every assignment; extra
+/// entries are ignored.
+pub fn explain_rows(expr: &Expr, assignments: Vec<HashMap<String, bool>>) -> Result<ExplanationReport, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let names = variables.to_vec();
+    let results = evaluate_results(expr, &variables)?;
+
+    let mut explanations = Vec::with_capacity(assignments.len());
+    for (row_idx, mut assignment) in assignments.into_iter().enumerate() {
+        // Each explanation reads up to every row
+        options::check_deadline()?;
+        if let Some(missing) = names.iter().find(|var| !assignment.contains_key(*var)) {
+            return Err(EvaluationError::InvalidTruthAssignment {
+                variable: missing.clone(),
+                context: format!("no value provided in row {}", row_idx + 1),
+            });
+        }
+        assignment.retain(|name, _| variables.contains(name));
+
+        let index = names
+            .iter()
+            .enumerate()
+            .filter(|(_, var)| assignment[*var])
+            .fold(0usize, |index, (bit, _)| index | 1 << bit);
+        let result = results[index];
+
+        // Start from the whole assignment and free each variable in turn if
+        // the result stays forced without it
+        let mut fixed = (1usize << names.len()) - 1;
+        for bit in 0..names.len() {
+            let candidate = fixed & !(1 << bit);
+            if forces(&results, index, candidate, result) {
+                fixed = candidate;
+            }
+        }
+
+        explanations.push(Explanation {
+            reason: names
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| fixed & (1 << bit) != 0)
+                .map(|(_, var)| var.clone())
+                .collect(),
+            assignment,
+            result,
+        });
+    }
+
+    Ok(ExplanationReport { variables, explanations })
+}
+
+/// Whether every row agreeing with row `index` on the bits in `fixed` has `result`
+fn forces(results: &[bool], index: usize, fixed: usize, result: bool) -> bool {
+    let free = (results.len() - 1) & !fixed;
+    let base = index & fixed;
+    // Enumerate the subsets of the free bits
+    let mut subset = 0usize;
+    loop {
+        if results[base | subset] != result {
+            return false;
+        }
+        if subset == free {
+            return true;
+        }
+        subset = (subset.wrapping_sub(free)) & free;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn explain(input: &str, assignment: &[(&str, bool)]) -> Explanation {
+        let expr = Parser::new(input).parse().unwrap();
+        let assignment = assignment.iter().map(|(var, value)| (var.to_string(), *value)).collect();
+        explain_rows(&expr, vec![assignment]).unwrap().explanations.remove(0)
+    }
+
+    #[test]
+    fn test_true_result_reason() {
+        // c is irrelevant once a and b are both true
+        let explanation = explain("(a and b) or (not a and c)", &[("a", true), ("b", true), ("c", false)]);
+        assert!(explanation.result);
+        assert_eq!(explanation.reason, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_false_result_reason() {
+        let explanation = explain("a and b and c", &[("a", true), ("b", false), ("c", true)]);
+        assert!(!explanation.result);
+        assert_eq!(explanation.reason, ["b"]);
+    }
+
+    #[test]
+    fn test_constant_has_empty_reason() {
+        let explanation = explain("a or not a", &[("a", false)]);
+        assert!(explanation.result);
+        assert!(explanation.reason.is_empty());
+    }
+
+    #[test]
+    fn test_missing_variable() {
+        let expr = Parser::new("a and b").parse().unwrap();
+        let assignment = HashMap::from([("a".to_string(), true)]);
+        assert!(explain_rows(&expr, vec![assignment]).is_err());
+    }
+}
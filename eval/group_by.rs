@@ -0,0 +1,114 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::evaluate_expression;
+use crate::eval::options;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// The rows of a truth table sharing one assignment of the grouped variables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Group {
+    pub assignment: HashMap<String, bool>,
+    /// Completions over the collapsed variables that make the expression true
+    pub true_count: usize,
+    pub total: usize,
+}
+
+impl Group {
+    /// Share of the completions that are true
+    pub fn fraction(&self) -> f64 {
+        self.true_count as f64 / self.total as f64
+    }
+}
+
+/// A truth table collapsed over every variable except the grouped ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupSummary {
+    pub expression: Expr,
+    /// Grouped variables, in the order requested
+    pub variables: Vec<String>,
+    /// Variables summed over within each group
+    pub collapsed: Vec<String>,
+    /// One group per assignment, the first variable varying fastest as in a truth table
+    pub groups: Vec<Group>,
+}
+
+/// Count the true completions of each assignment to `group_by`
+pub fn group_truth_table(expr: &Expr, group_by: &[String]) -> Result<GroupSummary, EvaluationError> {
+    let all = Variables::from_expr(expr)?;
+    let mut variables: Vec<String> = Vec::new();
+    for var in group_by {
+        if !all.contains(var) {
+            return Err(EvaluationError::InvalidTruthAssignment {
+                variable: var.clone(),
+                context: "group-by variable the expression does not use".to_string(),
+            });
+        }
+        if !variables.contains(var) {
+            variables.push(var.clone());
+        }
+    }
+    let collapsed: Vec<String> = all.iter().filter(|var| !variables.iter().any(|shown| shown == var)).map(str::to_string).collect();
+
+    let mut groups: Vec<Group> = (0..1usize << variables.len())
+        .map(|index| Group {
+            assignment: variables.iter().enumerate().map(|(i, var)| (var.clone(), (index >> i) & 1 == 1)).collect(),
+            true_count: 0,
+            total: 1 << collapsed.len(),
+        })
+        .collect();
+    for group in &mut groups {
+        let mut assignment = group.assignment.clone();
+        for completion in 0..group.total {
+            options::check_row(completion)?;
+            for (i, var) in collapsed.iter().enumerate() {
+                assignment.insert(var.clone(), (completion >> i) & 1 == 1);
+            }
+            if evaluate_expression(expr, &assignment) {
+                group.true_count += 1;
+            }
+        }
+    }
+
+    Ok(GroupSummary {
+        expression: expr.clone(),
+        variables,
+        collapsed,
+        groups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn group_str(input: &str, group_by: &[&str]) -> Result<GroupSummary, EvaluationError> {
+        let expr = Parser::new(input).parse().unwrap();
+        let group_by: Vec<String> = group_by.iter().map(|var| var.to_string()).collect();
+        group_truth_table(&expr, &group_by)
+    }
+
+    #[test]
+    fn test_counts_true_completions() {
+        let summary = group_str("(a and b) or (c and d)", &["a", "b"]).unwrap();
+        assert_eq!(summary.collapsed, ["c", "d"]);
+        let counts: Vec<usize> = summary.groups.iter().map(|g| g.true_count).collect();
+        // Only c ∧ d is left unless a ∧ b holds
+        assert_eq!(counts, [1, 1, 1, 4]);
+        assert_eq!(summary.groups[3].fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_group_order_follows_request() {
+        let summary = group_str("a and not b", &["b", "a"]).unwrap();
+        assert_eq!(summary.variables, ["b", "a"]);
+        // b varies fastest: (b=F, a=T) is the third group
+        assert_eq!(summary.groups[2].true_count, 1);
+    }
+
+    #[test]
+    fn test_unknown_group_variable_is_rejected() {
+        assert!(group_str("a and b", &["z"]).is_err());
+    }
+}
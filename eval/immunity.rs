@@ -0,0 +1,166 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::anf::Anf;
+use crate::eval::truth_table::evaluate_results;
+use crate::eval::options;
+use crate::config::MAX_IMMUNITY_VARIABLES;
+use serde::{Serialize, Deserialize};
+
+/// A lowest-degree nonzero function whose product with the expression (or its
+/// complement) is zero
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annihilator {
+    /// Whether it annihilates the complement of the expression rather than the expression
+    pub of_complement: bool,
+    pub degree: usize,
+    pub annihilator: Expr,
+}
+
+/// Algebraic degree of an expression and, when requested, its algebraic immunity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DegreeReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub anf: Anf,
+    pub degree: usize,
+    /// The algebraic immunity is the degree of this annihilator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub immunity: Option<Annihilator>,
+}
+
+/// Compute the ANF degree of `expr`, and with `immunity` its lowest-degree annihilator
+pub fn analyze_degree(expr: &Expr, immunity: bool) -> Result<DegreeReport, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let results = evaluate_results(expr, &variables)?;
+    let anf = Anf::from_truth_table(variables.clone(), &results);
+
+    let immunity = if immunity {
+        if variables.len() > MAX_IMMUNITY_VARIABLES {
+            return Err(EvaluationError::TooManyVariables { count: variables.len(), max: MAX_IMMUNITY_VARIABLES });
+        }
+        Some(lowest_annihilator(&variables, &results)?)
+    } else {
+        None
+    };
+
+    Ok(DegreeReport {
+        expression: expr.clone(),
+        degree: anf.degree(),
+        variables,
+        anf,
+        immunity,
+    })
+}
+
+/// Try each degree in turn for a nonzero annihilator of `f` or `¬f`. One always
+/// exists by degree ⌈n/2⌉, and `f ⊕ 1` annihilates `f` at the latest.
+fn lowest_annihilator(variables: &Variables, results: &[bool]) -> Result<Annihilator, EvaluationError> {
+    let n = variables.len();
+    for degree in 0..=n {
+        let monomials: Vec<usize> = (0..results.len()).filter(|m| m.count_ones() as usize <= degree).collect();
+        for of_complement in [false, true] {
+            options::check_deadline()?;
+            // g must vanish wherever the target is true
+            let support: Vec<usize> = (0..results.len()).filter(|&x| results[x] != of_complement).collect();
+            if let Some(coefficients) = null_vector(&support, &monomials) {
+                let anf = Anf {
+                    variables: variables.clone(),
+                    monomials: monomials.iter().zip(coefficients).filter(|(_, c)| *c).map(|(&m, _)| m).collect(),
+                };
+                return Ok(Annihilator { of_complement, degree: anf.degree(), annihilator: anf.to_expr() });
+            }
+        }
+    }
+    unreachable!("the complement of a function annihilates it")
+}
+
+/// A nonzero choice of monomial coefficients whose xor is 0 on every point of
+/// `support`, found by Gaussian elimination over GF(2)
+fn null_vector(support: &[usize], monomials: &[usize]) -> Option<Vec<bool>> {
+    let words = monomials.len().div_ceil(64);
+    let mut rows: Vec<Vec<u64>> = support
+        .iter()
+        .map(|&x| {
+            let mut row = vec![0u64; words];
+            for (col, &m) in monomials.iter().enumerate() {
+                // The monomial is true at x when all of its variables are
+                if m & x == m {
+                    row[col / 64] |= 1 << (col % 64);
+                }
+            }
+            row
+        })
+        .collect();
+    let bit = |row: &[u64], col: usize| row[col / 64] >> (col % 64) & 1 == 1;
+
+    let mut pivots = Vec::new();
+    for col in 0..monomials.len() {
+        let rank = pivots.len();
+        let Some(found) = (rank..rows.len()).find(|&r| bit(&rows[r], col)) else {
+            continue;
+        };
+        rows.swap(rank, found);
+        let pivot = rows[rank].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r != rank && bit(row, col) {
+                row.iter_mut().zip(&pivot).for_each(|(word, p)| *word ^= p);
+            }
+        }
+        pivots.push(col);
+    }
+
+    let free = (0..monomials.len()).find(|col| !pivots.contains(col))?;
+    let mut solution = vec![false; monomials.len()];
+    solution[free] = true;
+    for (rank, &col) in pivots.iter().enumerate() {
+        solution[col] = bit(&rows[rank], free);
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+    use crate::eval::truth_table::{assignment_from_index, evaluate_expression};
+
+    fn degree_str(input: &str) -> DegreeReport {
+        analyze_degree(&Parser::new(input).parse().unwrap(), true).unwrap()
+    }
+
+    #[test]
+    fn test_degree() {
+        assert_eq!(degree_str("a xor b xor c").degree, 1);
+        assert_eq!(degree_str("atleast(2; a, b, c)").degree, 2);
+        assert_eq!(degree_str("a and b and c and d").degree, 4);
+    }
+
+    #[test]
+    fn test_conjunction_has_a_linear_annihilator() {
+        // ¬a vanishes wherever a ∧ b ∧ c ∧ d holds
+        let immunity = degree_str("a and b and c and d").immunity.unwrap();
+        assert_eq!(immunity.degree, 1);
+        assert!(!immunity.of_complement);
+    }
+
+    #[test]
+    fn test_majority_reaches_the_bound() {
+        // Majority of five has the optimal immunity ⌈5/2⌉
+        let immunity = degree_str("atleast(3; a, b, c, d, e)").immunity.unwrap();
+        assert_eq!(immunity.degree, 3);
+        let annihilator = immunity.annihilator;
+        let expr = Parser::new("atleast(3; a, b, c, d, e)").parse().unwrap();
+        let variables = Variables::from_expr(&expr).unwrap();
+        for index in 0..32 {
+            let assignment = assignment_from_index(&variables, index);
+            let target = evaluate_expression(&expr, &assignment) != immunity.of_complement;
+            assert!(!(target && evaluate_expression(&annihilator, &assignment)));
+        }
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(degree_str("true").immunity.unwrap().degree, 0);
+        assert_eq!(degree_str("a or not a").degree, 0);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::evaluate_results;
+use crate::eval::options;
+use serde::{Serialize, Deserialize};
+
+/// How strongly one variable affects the result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableInfluence {
+    pub variable: String,
+    /// Fraction of assignments where flipping the variable changes the result
+    pub banzhaf: f64,
+    /// Shapley-Shubik value, treating the expression as a game whose coalitions
+    /// are the sets of true variables; negative when the variable works against the result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shapley: Option<f64>,
+}
+
+/// Per-variable influence of an expression, most influential first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfluenceReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub influences: Vec<VariableInfluence>,
+}
+
+/// Rank the variables of `expr` by Banzhaf index, optionally computing Shapley-Shubik values.
+///
+/// Ties keep variable order.
+pub fn analyze_influence(expr: &Expr, shapley: bool) -> Result<InfluenceReport, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let n = variables.len();
+    let results = evaluate_results(expr, &variables)?;
+
+    // weights[k] = k! (n - k - 1)! / n!, the chance that a coalition of size k
+    // precedes the variable in a random ordering
+    let weights: Vec<f64> = (0..n)
+        .map(|k| {
+            let mut weight = 1.0 / n as f64;
+            for j in 0..k {
+                weight *= (k - j) as f64 / (n - 1 - j) as f64;
+            }
+            weight
+        })
+        .collect();
+
+    let mut influences: Vec<VariableInfluence> = variables
+        .iter()
+        .enumerate()
+        .map(|(bit, name)| {
+            options::check_deadline()?;
+            let mask = 1usize << bit;
+            let mut pivotal = 0usize;
+            let mut value = 0.0;
+            for without in (0..results.len()).filter(|i| i & mask == 0) {
+                let (before, after) = (results[without], results[without | mask]);
+                if before != after {
+                    pivotal += 1;
+                    let weight = weights[without.count_ones() as usize];
+                    value += if after { weight } else { -weight };
+                }
+            }
+            Ok(VariableInfluence {
+                variable: name.to_string(),
+                // Each pivotal pair is two assignments out of 2^n
+                banzhaf: pivotal as f64 / (results.len() / 2) as f64,
+                shapley: shapley.then_some(value),
+            })
+        })
+        .collect::<Result<_, EvaluationError>>()?;
+    influences.sort_by(|a, b| b.banzhaf.total_cmp(&a.banzhaf));
+
+    Ok(InfluenceReport {
+        expression: expr.clone(),
+        variables,
+        influences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn influence(input: &str) -> InfluenceReport {
+        analyze_influence(&Parser::new(input).parse().unwrap(), true).unwrap()
+    }
+
+    #[test]
+    fn test_banzhaf_ranking() {
+        // a decides alone half the time; b and c only matter together with a
+        let report = influence("a and (b or c)");
+        let names: Vec<&str> = report.influences.iter().map(|i| i.variable.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        assert_eq!(report.influences[0].banzhaf, 0.75);
+        assert_eq!(report.influences[1].banzhaf, 0.25);
+    }
+
+    #[test]
+    fn test_shapley_values_sum_to_game_value() {
+        // Weighted vote: a has 2 votes, b and c one each, 3 needed
+        let report = influence("(a and b) or (a and c)");
+        let total: f64 = report.influences.iter().filter_map(|i| i.shapley).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        let a = report.influences.iter().find(|i| i.variable == "a").unwrap();
+        assert!((a.shapley.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_negated_variable_has_negative_shapley() {
+        let report = influence("not a");
+        assert_eq!(report.influences[0].banzhaf, 1.0);
+        assert_eq!(report.influences[0].shapley, Some(-1.0));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::{TruthTable, TruthTableRow};
+use std::collections::{BTreeMap, HashMap};
+use serde::{Serialize, Deserialize};
+
+/// Two partial tables that disagree on the result of one assignment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeConflict {
+    pub assignment: HashMap<String, bool>,
+    /// Index of the first table that defined the row, and its result
+    pub first_table: usize,
+    pub first_value: bool,
+    /// Index of the table that contradicted it, and its result
+    pub second_table: usize,
+    pub second_value: bool,
+}
+
+/// Result of combining partial truth tables over the same variables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableMerge {
+    /// Merged rows in truth table order; conflicting rows keep the first result
+    pub table: TruthTable,
+    /// Rows defined by more than one table with the same result
+    pub overlapping_rows: usize,
+    pub conflicts: Vec<MergeConflict>,
+    /// Assignments not defined by any table
+    pub missing_rows: usize,
+}
+
+impl TableMerge {
+    /// True when every assignment is defined exactly once or consistently
+    pub fn is_complete(&self) -> bool {
+        self.conflicts.is_empty() && self.missing_rows == 0
+    }
+}
+
+/// Combine partial truth tables, e.g. produced by workers over disjoint
+/// assignment ranges, into one table.
+///
+/// Every table must have the same variables; column order is irrelevant.
+pub fn merge_truth_tables(tables: &[TruthTable]) -> Result<TableMerge, EvaluationError> {
+    let Some(first) = tables.first() else {
+        return Err(EvaluationError::UnsupportedOperation { operation: "merging zero truth tables".to_string() });
+    };
+    let variables: &Variables = &first.variables;
+    for (index, table) in tables.iter().enumerate().skip(1) {
+        if table.variables != *variables {
+            return Err(EvaluationError::MismatchedVariables {
+                table: index,
+                expected: variables.to_vec(),
+                found: table.variables.to_vec(),
+            });
+        }
+    }
+    
+    // Row index in truth table order (the first variable is the lowest bit)
+    let row_index = |assignments: &HashMap<String, bool>| -> usize {
+        variables
+            .iter()
+            .enumerate()
+            .filter(|(_, var)| assignments.get(*var).copied().unwrap_or(false))
+            .map(|(bit, _)| 1 << bit)
+            .sum()
+    };
+    
+    let mut merged: BTreeMap<usize, (usize, TruthTableRow)> = BTreeMap::new();
+    let mut overlapping_rows = 0;
+    let mut conflicts = Vec::new();
+    for (table_index, table) in tables.iter().enumerate() {
+        for row in &table.rows {
+            match merged.get(&row_index(&row.assignments)) {
+                None => {
+                    merged.insert(row_index(&row.assignments), (table_index, row.clone()));
+                }
+                Some((_, existing)) if existing.result == row.result => overlapping_rows += 1,
+                Some((first_table, existing)) => conflicts.push(MergeConflict {
+                    assignment: row.assignments.clone(),
+                    first_table: *first_table,
+                    first_value: existing.result,
+                    second_table: table_index,
+                    second_value: row.result,
+                }),
+            }
+        }
+    }
+    
+    let missing_rows = (1usize << variables.len()) - merged.len();
+    Ok(TableMerge {
+        table: TruthTable {
+            variables: variables.clone(),
+            rows: merged.into_values().map(|(_, row)| row).collect(),
+            range: None,
+            sample: None,
+        },
+        overlapping_rows,
+        conflicts,
+        missing_rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::truth_table::generate_truth_table;
+    use crate::source::Parser;
+
+    fn full_table(input: &str) -> TruthTable {
+        generate_truth_table(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    fn slice(table: &TruthTable, range: std::ops::Range<usize>) -> TruthTable {
+        TruthTable { variables: table.variables.clone(), rows: table.rows[range].to_vec(), range: None, sample: None }
+    }
+
+    #[test]
+    fn test_merge_shards_with_overlap() {
+        let full = full_table("a and b or c");
+        let merge = merge_truth_tables(&[slice(&full, 4..8), slice(&full, 0..5)]).unwrap();
+        assert!(merge.is_complete());
+        assert_eq!(merge.overlapping_rows, 1);
+        assert_eq!(merge.table.rows, full.rows);
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts_and_gaps() {
+        let left = full_table("a and b");
+        let right = full_table("a or b");
+        let merge = merge_truth_tables(&[slice(&left, 0..2), slice(&right, 1..3)]).unwrap();
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.missing_rows, 1);
+        assert!(!merge.is_complete());
+    }
+
+    #[test]
+    fn test_merge_rejects_different_variables() {
+        let result = merge_truth_tables(&[full_table("a and b"), full_table("a and c")]);
+        assert!(matches!(result, Err(EvaluationError::MismatchedVariables { table: 1, .. })));
+    }
+}
@@ -1,13 +1,46 @@
 pub mod truth_table;
 pub mod equivalence;
 pub mod reduction;
+pub mod table_diff;
+pub mod subexpressions;
+pub mod proof;
+pub mod aig;
+pub mod merge;
+pub mod expand;
+pub mod warnings;
+pub mod influence;
+pub mod threshold;
+pub mod explain;
+pub mod abduction;
+pub mod propagate;
+pub mod three_valued;
+pub mod cutsets;
+pub mod group_by;
+pub mod spectrum;
+pub mod bias;
+pub mod sbox;
+pub mod anf;
+pub mod immunity;
+pub mod synthesis;
+pub mod projection;
+pub mod trace;
+pub mod sat;
+pub mod classify;
+pub mod regress;
+pub mod normal_forms;
+pub mod options;
+pub mod engine;
+pub mod report;
+pub(crate) mod rng;
 
-use crate::source::Expr;
+use crate::source::{Expr, Quantifier, ExprVisitor};
 use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::ops::ControlFlow;
 use std::fmt;
 use serde::{Serialize, Deserialize};
 
-use crate::config::{MAX_VARIABLES, MAX_VARIABLE_NAME_LENGTH};
+use crate::config::MAX_VARIABLE_NAME_LENGTH;
 
 /// Errors that can occur during evaluation
 #[derive(Debug, Clone)]
@@ -15,10 +48,15 @@ pub enum EvaluationError {
     TooManyVariables { count: usize, max: usize },
     InvalidVariableName(String),
     ExpressionTooComplex { reason: String },
+    ExpressionTooDeep { depth: usize, max: usize },
     ReductionTimeout { max_iterations: usize },
     UnsupportedOperation { operation: String },
     EmptyExpression,
     InvalidTruthAssignment { variable: String, context: String },
+    MismatchedVariables { table: usize, expected: Vec<String>, found: Vec<String> },
+    RowRangeOutOfBounds { start: usize, end: usize, rows: usize },
+    TimeBudgetExceeded { budget: std::time::Duration },
+    MemoryBudgetExceeded { estimated: usize, budget: usize },
 }
 
 impl fmt::Display for EvaluationError {
@@ -28,11 +66,14 @@ impl fmt::Display for EvaluationError {
                 write!(f, "Expression has too many variables ({} > {}). Consider simplifying the expression.", count, max)
             }
             EvaluationError::InvalidVariableName(name) => {
-                write!(f, "Invalid variable name '{}'. Variable names must be non-empty, alphanumeric (with underscores), and at most {} characters long.", name, MAX_VARIABLE_NAME_LENGTH)
+                write!(f, "Invalid variable name '{}'. Variable names must be non-empty, free of control characters, and at most {} characters long.", name, MAX_VARIABLE_NAME_LENGTH)
             }
             EvaluationError::ExpressionTooComplex { reason } => {
                 write!(f, "Expression is too complex to process: {}", reason)
             }
+            EvaluationError::ExpressionTooDeep { depth, max } => {
+                write!(f, "Expression nests {} levels deep, more than the {} that can be evaluated safely", depth, max)
+            }
             EvaluationError::ReductionTimeout { max_iterations } => {
                 write!(f, "Expression reduction timed out after {} iterations. The expression may be too complex to simplify.", max_iterations)
             }
@@ -45,18 +86,50 @@ impl fmt::Display for EvaluationError {
             EvaluationError::InvalidTruthAssignment { variable, context } => {
                 write!(f, "Invalid truth assignment for variable '{}' in context: {}", variable, context)
             }
+            EvaluationError::MismatchedVariables { table, expected, found } => {
+                write!(f, "Truth table {} has variables [{}], expected [{}]", table + 1, found.join(", "), expected.join(", "))
+            }
+            EvaluationError::RowRangeOutOfBounds { start, end, rows } => {
+                write!(f, "Row range {}..{} is outside the table's {} rows", start, end, rows)
+            }
+            EvaluationError::TimeBudgetExceeded { budget } => {
+                write!(f, "Evaluation ran past its time budget of {:?}", budget)
+            }
+            EvaluationError::MemoryBudgetExceeded { estimated, budget } => {
+                write!(f, "The truth table would take about {} bytes, more than the budget of {}", estimated, budget)
+            }
         }
     }
 }
 
 impl std::error::Error for EvaluationError {}
 
-/// A sorted set of variable names for consistent ordering
+/// A sorted set of variable names for consistent ordering. Each name is an
+/// `Arc<str>`, so that sets an [`Engine`] builds share one allocation per name.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "VariableNames", into = "VariableNames")]
 pub struct Variables {
+    names: BTreeSet<Arc<str>>,
+}
+
+/// How [`Variables`] are serialized
+#[derive(Clone, Serialize, Deserialize)]
+struct VariableNames {
     names: BTreeSet<String>,
 }
 
+impl From<VariableNames> for Variables {
+    fn from(variables: VariableNames) -> Self {
+        Self { names: variables.names.into_iter().map(Arc::from).collect() }
+    }
+}
+
+impl From<Variables> for VariableNames {
+    fn from(variables: Variables) -> Self {
+        Self { names: variables.names.iter().map(|name| name.to_string()).collect() }
+    }
+}
+
 impl Default for Variables {
     fn default() -> Self {
         Self::new()
@@ -69,41 +142,62 @@ impl Variables {
     }
     
     pub fn from_expr(expr: &Expr) -> Result<Self, EvaluationError> {
+        Self::collect(expr, true)
+    }
+    
+    /// The variables of `expr` without the variable limit, for
+    /// operations that never enumerate every row
+    pub fn from_expr_unbounded(expr: &Expr) -> Result<Self, EvaluationError> {
+        Self::collect(expr, false)
+    }
+    
+    fn collect(expr: &Expr, bounded: bool) -> Result<Self, EvaluationError> {
+        // Every pass over the expression recurses once per level
+        let (depth, max) = (expr.depth(), options::current().max_depth);
+        if depth > max {
+            return Err(EvaluationError::ExpressionTooDeep { depth, max });
+        }
+        let mut free = FreeVariables { variables: Self::new(), bound: Vec::new(), bounded };
+        match free.visit_expr(expr) {
+            ControlFlow::Continue(()) => Ok(free.variables),
+            ControlFlow::Break(error) => Err(error),
+        }
+    }
+    
+    /// Build a variable set from explicit names, validating each one
+    pub fn from_names<I, S>(names: I) -> Result<Self, EvaluationError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
         let mut vars = Self::new();
-        vars.collect_from_expr(expr)?;
+        for name in names {
+            vars.insert(name.into())?;
+        }
         Ok(vars)
     }
     
-    fn collect_from_expr(&mut self, expr: &Expr) -> Result<(), EvaluationError> {
-        match expr {
-            Expr::Identifier(name) => {
-                // Validate variable name
-                if name.is_empty() || name.len() > MAX_VARIABLE_NAME_LENGTH || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                    return Err(EvaluationError::InvalidVariableName(name.clone()));
-                }
-                
-                self.names.insert(name.clone());
-                
-                // Check variable count limit
-                if self.names.len() > MAX_VARIABLES {
-                    return Err(EvaluationError::TooManyVariables {
-                        count: self.names.len(),
-                        max: MAX_VARIABLES,
-                    });
-                }
-                
-                Ok(())
-            }
-            Expr::Not(e) => self.collect_from_expr(e),
-            Expr::And(left, right) 
-            | Expr::Or(left, right) 
-            | Expr::Xor(left, right) 
-            | Expr::Implication(left, right) => {
-                self.collect_from_expr(left)?;
-                self.collect_from_expr(right)?;
-                Ok(())
-            }
+    fn insert(&mut self, name: String) -> Result<(), EvaluationError> {
+        self.insert_unbounded(name)?;
+        
+        let max = options::max_vars();
+        if self.names.len() > max {
+            return Err(EvaluationError::TooManyVariables {
+                count: self.names.len(),
+                max,
+            });
+        }
+        
+        Ok(())
+    }
+    
+    fn insert_unbounded(&mut self, name: String) -> Result<(), EvaluationError> {
+        if name.is_empty() || name.len() > MAX_VARIABLE_NAME_LENGTH || name.chars().any(char::is_control) {
+            return Err(EvaluationError::InvalidVariableName(name));
         }
+        
+        self.names.insert(Arc::from(name));
+        Ok(())
     }
     
     pub fn len(&self) -> usize {
@@ -114,12 +208,22 @@ impl Variables {
         self.names.is_empty()
     }
     
-    pub fn iter(&self) -> impl Iterator<Item = &String> {
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(AsRef::as_ref)
+    }
+    
+    /// The names themselves, shared with every clone of the set
+    pub fn shared(&self) -> impl Iterator<Item = &Arc<str>> {
         self.names.iter()
     }
     
+    /// The same set with each name replaced by `intern`'s copy of it
+    pub(crate) fn interned(&self, intern: impl Fn(&str) -> Arc<str>) -> Self {
+        Self { names: self.names.iter().map(|name| intern(name)).collect() }
+    }
+    
     pub fn to_vec(&self) -> Vec<String> {
-        self.names.iter().cloned().collect()
+        self.names.iter().map(|name| name.to_string()).collect()
     }
     
     pub fn union(&self, other: &Variables) -> Variables {
@@ -128,30 +232,308 @@ impl Variables {
         }
     }
     
+    pub fn intersection(&self, other: &Variables) -> Variables {
+        Variables {
+            names: self.names.intersection(&other.names).cloned().collect()
+        }
+    }
+    
+    pub fn difference(&self, other: &Variables) -> Variables {
+        Variables {
+            names: self.names.difference(&other.names).cloned().collect()
+        }
+    }
+    
     pub fn contains(&self, name: &str) -> bool {
         self.names.contains(name)
     }
 }
 
+/// Collects the variables not bound by an enclosing quantifier
+struct FreeVariables {
+    variables: Variables,
+    bound: Vec<String>,
+    /// Whether to stop at more than the variable limit in force
+    bounded: bool,
+}
+
+impl ExprVisitor for FreeVariables {
+    type Break = EvaluationError;
+
+    fn visit_identifier(&mut self, name: &str) -> ControlFlow<EvaluationError> {
+        if self.bound.iter().any(|var| var == name) {
+            return ControlFlow::Continue(());
+        }
+        let inserted = if self.bounded {
+            self.variables.insert(name.to_string())
+        } else {
+            self.variables.insert_unbounded(name.to_string())
+        };
+        match inserted {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(error) => ControlFlow::Break(error),
+        }
+    }
+
+    fn visit_quantified(&mut self, _quantifier: Quantifier, var: &str, body: &Expr) -> ControlFlow<EvaluationError> {
+        self.bound.push(var.to_string());
+        let flow = self.visit_expr(body);
+        self.bound.pop();
+        flow
+    }
+}
+
 /// Main evaluator interface
 pub struct Evaluator;
 
 impl Evaluator {
+    /// Run `f` with `options` limiting every evaluation it makes on this thread,
+    /// such as the work for one request of a server; an inner call's options
+    /// replace the outer ones until it returns. The options don't follow work
+    /// onto other threads or async tasks, which must call this themselves
+    pub fn with_options<T>(options: &options::EvalOptions, f: impl FnOnce() -> T) -> T {
+        options::with_options(options, f)
+    }
+
     /// Generate a truth table from a boolean expression
     pub fn generate_truth_table(expr: &Expr) -> Result<truth_table::TruthTable, EvaluationError> {
         truth_table::generate_truth_table(expr)
     }
 
+    /// Generate rows `start..end` of a truth table (`end` defaults to the last row)
+    pub fn generate_truth_table_range(expr: &Expr, start: usize, end: Option<usize>) -> Result<truth_table::TruthTable, EvaluationError> {
+        truth_table::generate_truth_table_range(expr, start, end)
+    }
+
+    /// Show only `variables` of `table`, and its result if `result` is set
+    pub fn select_columns(table: &truth_table::TruthTable, variables: &[String], result: bool) -> Result<projection::TableView, EvaluationError> {
+        projection::select_columns(table, variables, result)
+    }
+
+    /// Show every column of `table` except `hidden`
+    pub fn hide_columns(table: &truth_table::TruthTable, hidden: &[String]) -> Result<projection::TableView, EvaluationError> {
+        projection::hide_columns(table, hidden)
+    }
+
+    /// Collapse the rows of `table` that agree on the columns `view` shows
+    pub fn project(table: &truth_table::TruthTable, view: &projection::TableView) -> projection::Projection {
+        projection::project(table, view)
+    }
+
+    /// Reorder the rows of `table`, a table of `expr`, by `sort`
+    pub fn sort_truth_table(expr: &Expr, table: &mut truth_table::TruthTable, sort: truth_table::RowSort) -> Result<(), EvaluationError> {
+        match sort {
+            truth_table::RowSort::Result => table.sort_by_result(),
+            truth_table::RowSort::Influence => {
+                let report = influence::analyze_influence(expr, false)?;
+                let ranking: Vec<String> = report.influences.into_iter().map(|influence| influence.variable).collect();
+                table.sort_by_variables(&ranking);
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate `rows` uniformly random assignments, for expressions with too
+    /// many variables to enumerate
+    pub fn sample_truth_table(expr: &Expr, rows: usize, seed: u64) -> Result<truth_table::TruthTable, EvaluationError> {
+        truth_table::sample_truth_table(expr, rows, seed)
+    }
+
     /// Check if two boolean expressions are equivalent
     pub fn check_equivalence(left: &Expr, right: &Expr) -> Result<equivalence::EquivalenceCheck, EvaluationError> {
         equivalence::check_equivalence(left, right)
     }
 
+    /// Check if two boolean expressions are equivalent, ignoring assignments that satisfy `dont_care`
+    pub fn check_equivalence_with_dont_care(left: &Expr, right: &Expr, dont_care: &Expr) -> Result<equivalence::EquivalenceCheck, EvaluationError> {
+        equivalence::check_equivalence_with_dont_care(left, right, dont_care)
+    }
+
     /// Reduce/simplify a boolean expression using Quine-McCluskey algorithm
     pub fn reduce_expression(expr: &Expr) -> Result<reduction::Reduction, EvaluationError> {
         reduction::reduce_expression(expr)
     }
     
+    /// Reduce an expression, tracing each reduced term back to the minterms and source terms it covers
+    pub fn reduce_expression_with_trace(expr: &Expr) -> Result<reduction::Reduction, EvaluationError> {
+        reduction::reduce_expression_with_trace(expr)
+    }
+    
+    /// Reduce an expression with explicit tracing, strictness and step budget
+    pub fn reduce_expression_with_options(expr: &Expr, options: reduction::ReductionOptions) -> Result<reduction::Reduction, EvaluationError> {
+        reduction::reduce_expression_with_options(expr, options)
+    }
+    
+    /// Check a chain of equivalences link by link
+    pub fn prove_chain(steps: &[Expr]) -> Result<proof::ChainProof, EvaluationError> {
+        proof::prove_chain(steps)
+    }
+    
+    /// Grow an expression to `target_size` nodes with seeded, equivalence-preserving rewrites
+    pub fn expand_expression(expr: &Expr, target_size: usize, seed: u64) -> Result<expand::Expansion, EvaluationError> {
+        expand::expand_expression(expr, target_size, seed)
+    }
+    
+    /// Compute the prime implicants of an expression (or only their count) without selecting a cover
+    pub fn prime_implicants(expr: &Expr, list: bool) -> Result<reduction::PrimeImplicantReport, EvaluationError> {
+        reduction::prime_implicants(expr, list)
+    }
+    
+    /// Evaluate an expression for each assignment in turn (e.g. rows loaded from a CSV file)
+    pub fn evaluate_rows(expr: &Expr, assignments: Vec<std::collections::HashMap<String, bool>>) -> Result<truth_table::TruthTable, EvaluationError> {
+        truth_table::evaluate_rows(expr, assignments)
+    }
+    
+    /// Generate a three-valued truth table, with every variable false, true or unknown
+    pub fn generate_three_valued_table(expr: &Expr) -> Result<three_valued::ThreeValuedTable, EvaluationError> {
+        three_valued::generate_three_valued_table(expr)
+    }
+    
+    /// Evaluate each assignment in Kleene logic; variables it leaves out are unknown
+    pub fn evaluate_rows_three_valued(expr: &Expr, assignments: Vec<std::collections::HashMap<String, three_valued::Ternary>>) -> Result<three_valued::ThreeValuedTable, EvaluationError> {
+        three_valued::evaluate_rows_three_valued(expr, assignments)
+    }
+    
+    /// Explain each assignment's result with the literals of the assignment that force it
+    pub fn explain_rows(expr: &Expr, assignments: Vec<std::collections::HashMap<String, bool>>) -> Result<explain::ExplanationReport, EvaluationError> {
+        explain::explain_rows(expr, assignments)
+    }
+    
+    /// The value of every subexpression under one assignment, and the operand chain that forces the result
+    pub fn trace(expr: &Expr, assignment: &std::collections::HashMap<String, bool>) -> Result<trace::Trace, EvaluationError> {
+        trace::trace(expr, assignment)
+    }
+    
+    /// Find minimal literal sets that, with the fixed values, force the expression to `target`
+    pub fn abduce(expr: &Expr, fixed: &std::collections::HashMap<String, bool>, target: bool) -> Result<abduction::Abduction, EvaluationError> {
+        abduction::abduce(expr, fixed, target)
+    }
+    
+    /// Simplify the expression under a partial assignment, propagating unit literals until nothing changes
+    pub fn propagate(expr: &Expr, fixed: &std::collections::HashMap<String, bool>) -> Result<propagate::Propagation, EvaluationError> {
+        propagate::propagate(expr, fixed)
+    }
+    
+    /// Collapse the truth table over all but the grouped variables, counting true completions
+    pub fn group_truth_table(expr: &Expr, group_by: &[String]) -> Result<group_by::GroupSummary, EvaluationError> {
+        group_by::group_truth_table(expr, group_by)
+    }
+    
+    /// Walsh–Hadamard spectrum with nonlinearity, correlation immunity and bentness
+    pub fn walsh_spectrum(expr: &Expr) -> Result<spectrum::SpectrumReport, EvaluationError> {
+        spectrum::walsh_spectrum(expr)
+    }
+    
+    /// Find the first row of the truth table where the expression is true, without evaluating the rest
+    pub fn check_satisfiability(expr: &Expr) -> Result<sat::Satisfiability, EvaluationError> {
+        sat::check_satisfiability(expr)
+    }
+    
+    /// Classify an expression as a tautology, a contradiction or contingent, with a row of each value it takes
+    pub fn classify(expr: &Expr) -> Result<classify::ClassificationReport, EvaluationError> {
+        classify::classify(expr)
+    }
+    
+    /// Pair the keyed expressions of a new corpus with a golden one and say how each pair differs
+    pub fn regress(golden: &[(String, Expr)], input: &[(String, Expr)]) -> regress::RegressionReport {
+        regress::regress(golden, input)
+    }
+    
+    /// Convert to conjunctive normal form, or with `tseitin` to its equisatisfiable Tseitin encoding
+    pub fn to_cnf(expr: &Expr, tseitin: bool) -> Result<normal_forms::Cnf, EvaluationError> {
+        normal_forms::to_cnf(expr, tseitin)
+    }
+    
+    /// Convert an expression to its canonical sum of minterms, or a minimal sum of products
+    pub fn to_dnf(expr: &Expr, canonical: bool) -> Result<normal_forms::Dnf, EvaluationError> {
+        normal_forms::to_dnf(expr, canonical)
+    }
+    
+    /// Push negations down to the variables, rewriting every operator but `and` and `or`
+    pub fn to_nnf(expr: &Expr) -> Result<normal_forms::Nnf, EvaluationError> {
+        normal_forms::to_nnf(expr)
+    }
+    
+    /// Truth table, classification, reduction, properties and Karnaugh map from one pass over the rows
+    pub fn report(expr: &Expr) -> Result<report::Report, EvaluationError> {
+        report::report(expr)
+    }
+    
+    /// Write an expression as an xor of conjunctions of variables, its Zhegalkin polynomial
+    pub fn algebraic_normal_form(expr: &Expr) -> Result<anf::AnfReport, EvaluationError> {
+        anf::algebraic_normal_form(expr)
+    }
+    
+    /// Count true rows and the bias from 1/2, overall and per fixed variable
+    pub fn analyze_bias(expr: &Expr) -> Result<bias::BiasReport, EvaluationError> {
+        bias::analyze_bias(expr)
+    }
+    
+    /// Nonlinearity, degree and differential properties of a lookup table and its component functions
+    pub fn analyze_sbox(table: &[usize], output_bits: usize) -> Result<sbox::SboxReport, EvaluationError> {
+        sbox::analyze_sbox(table, output_bits)
+    }
+    
+    /// ANF degree and, with `immunity`, a lowest-degree annihilator
+    pub fn analyze_degree(expr: &Expr, immunity: bool) -> Result<immunity::DegreeReport, EvaluationError> {
+        immunity::analyze_degree(expr, immunity)
+    }
+    
+    /// List the minimal cut sets of the expression, weighted by event probabilities where known
+    pub fn minimal_cut_sets(expr: &Expr, probabilities: &std::collections::HashMap<String, f64>) -> Result<cutsets::CutSetReport, EvaluationError> {
+        cutsets::cut_sets(expr, probabilities)
+    }
+    
+    /// Combine partial truth tables over the same variables, detecting overlaps, conflicts and gaps
+    pub fn merge_truth_tables(tables: &[truth_table::TruthTable]) -> Result<merge::TableMerge, EvaluationError> {
+        merge::merge_truth_tables(tables)
+    }
+    
+    /// Compare two previously generated truth tables row by row
+    pub fn diff_truth_tables(left: &truth_table::TruthTable, right: &truth_table::TruthTable) -> table_diff::TableDiff {
+        table_diff::diff_truth_tables(left, right)
+    }
+    
+    /// List every distinct subexpression with its occurrence count and truth density
+    pub fn analyze_subexpressions(expr: &Expr, order: subexpressions::SubexpressionOrder) -> Result<subexpressions::SubexpressionReport, EvaluationError> {
+        subexpressions::analyze_subexpressions(expr, order)
+    }
+    
+    /// Rank variables by how often flipping them changes the result (Banzhaf), optionally with Shapley-Shubik values
+    pub fn analyze_influence(expr: &Expr, shapley: bool) -> Result<influence::InfluenceReport, EvaluationError> {
+        influence::analyze_influence(expr, shapley)
+    }
+    
+    /// Search exhaustively for the smallest expression over `basis` equivalent to `expr`, up to `max_size` nodes
+    pub fn synthesize_exact(expr: &Expr, basis: &crate::source::Basis, max_size: usize) -> Result<synthesis::Synthesis, EvaluationError> {
+        synthesis::synthesize_exact(expr, basis, max_size)
+    }
+    
+    /// Use the reduced sum of products as the synthesized expression when it fits `basis` and `max_size`
+    pub fn synthesize(expr: &Expr, basis: &crate::source::Basis, max_size: usize) -> Result<synthesis::Synthesis, EvaluationError> {
+        synthesis::synthesize(expr, basis, max_size)
+    }
+    
+    /// Rewrite an expression to use only the operators in `basis`
+    pub fn rewrite_in_basis(expr: &Expr, basis: &crate::source::Basis) -> Result<Expr, EvaluationError> {
+        synthesis::rewrite_in_basis(expr, basis)
+    }
+    
+    /// Rewrite an expression to use only the operators of a basis, counting the gates it takes
+    pub fn convert(expr: &Expr, basis: &crate::source::Basis) -> Result<synthesis::Conversion, EvaluationError> {
+        synthesis::convert(expr, basis)
+    }
+    
+    /// Check whether an expression is a threshold function, finding integer weights and a threshold if it is
+    pub fn analyze_threshold(expr: &Expr) -> Result<threshold::ThresholdAnalysis, EvaluationError> {
+        threshold::analyze_threshold(expr)
+    }
+    
+    /// Non-fatal findings about an expression, such as variables that only appear negated
+    pub fn expression_warnings(expr: &Expr) -> Result<Vec<warnings::Warning>, EvaluationError> {
+        warnings::expression_warnings(expr)
+    }
+    
     /// Evaluate an expression with a given variable assignment (for testing)
     pub fn evaluate_with_assignment(expr: &Expr, assignment: &std::collections::HashMap<String, bool>) -> bool {
         truth_table::evaluate_expression(expr, assignment)
@@ -164,6 +546,35 @@ impl Evaluator {
 }
 
 // Re-export public types for backward compatibility
-pub use truth_table::{TruthTable, TruthTableRow};
+pub use truth_table::{TruthTable, TruthTableRow, RowRange, RowOrder, RowSort, TableSample};
 pub use equivalence::{EquivalenceCheck, EquivalenceDifference};
-pub use reduction::Reduction;
\ No newline at end of file
+pub use reduction::{Reduction, ReductionOptions, TermCoverage, SnippetRewrite, PrimeImplicant, PrimeImplicantReport};
+pub use table_diff::TableDiff;
+pub use subexpressions::{SubexpressionReport, SubexpressionOrder};
+pub use proof::{ChainProof, ProofLink};
+pub use merge::{TableMerge, MergeConflict};
+pub use expand::Expansion;
+pub use warnings::{Warning, WarningKind};
+pub use influence::{InfluenceReport, VariableInfluence};
+pub use threshold::{ThresholdAnalysis, VariableWeight};
+pub use explain::{Explanation, ExplanationReport};
+pub use abduction::Abduction;
+pub use propagate::{Propagation, ImpliedValue};
+pub use three_valued::{Ternary, ThreeValuedTable, ThreeValuedRow};
+pub use cutsets::{CutSet, CutSetReport};
+pub use group_by::{Group, GroupSummary};
+pub use spectrum::{SpectrumReport, WalshCoefficient};
+pub use bias::{BiasReport, ConditionalBias};
+pub use sbox::{SboxReport, ComponentFunction};
+pub use anf::{Anf, AnfReport};
+pub use immunity::{DegreeReport, Annihilator};
+pub use synthesis::{Conversion, Synthesis};
+pub use projection::{TableView, ViewRow, Projection, ProjectedRow, ProjectedResult};
+pub use trace::{Trace, TraceNode};
+pub use sat::Satisfiability;
+pub use classify::{Classification, ClassificationReport};
+pub use regress::{CorpusEntry, Regression, RegressionReport, RegressionStatus};
+pub use normal_forms::{Cnf, Definition, Dnf, Literal, Nnf};
+pub use options::{EvalOptions, EvalMethod};
+pub use engine::Engine;
+pub use report::{Report, Properties, KarnaughMap};
@@ -0,0 +1,517 @@
+use crate::source::{Expr, Quantifier};
+use crate::source::operator::resynthesize;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::aig::{self, Aig, negate};
+use crate::eval::truth_table::{assignment_from_index, evaluate_expression};
+use crate::eval::reduction::QuineMcCluskey;
+use crate::eval::options::{self, EvalMethod};
+use crate::config::{MAX_EXPANSION_SIZE, MAX_OPERATOR_ARITY, MAX_REDUCTION_STEPS};
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// A variable or its negation
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Literal {
+    pub variable: String,
+    pub negated: bool,
+}
+
+impl Literal {
+    pub fn to_expr(&self) -> Expr {
+        let variable = Expr::Identifier(self.variable.clone());
+        if self.negated { Expr::Not(Box::new(variable)) } else { variable }
+    }
+}
+
+/// A variable the Tseitin transform introduced, and the gate it stands for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Definition {
+    pub variable: String,
+    pub gate: Expr,
+}
+
+/// An expression in conjunctive normal form: an AND of clauses, each an OR of literals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cnf {
+    pub expression: Expr,
+    pub variables: Variables,
+    /// Whether the clauses are the Tseitin encoding, which is satisfiable exactly
+    /// when the expression is, rather than an equivalent formula
+    pub tseitin: bool,
+    pub definitions: Vec<Definition>,
+    /// No clauses is `true`; an empty clause is `false`
+    pub clauses: Vec<Vec<Literal>>,
+    /// The clauses as an expression
+    pub cnf: Expr,
+}
+
+impl Cnf {
+    pub fn literal_count(&self) -> usize {
+        self.clauses.iter().map(Vec::len).sum()
+    }
+}
+
+/// An expression in disjunctive normal form: an OR of terms, each an AND of literals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dnf {
+    pub expression: Expr,
+    pub variables: Variables,
+    /// Whether the terms are the expression's minterms, each naming every
+    /// variable, rather than a minimized sum of products
+    pub canonical: bool,
+    /// False when the minimizing search ran out of steps before proving its cover minimal
+    pub minimal: bool,
+    /// No terms is `false`; an empty term is `true`
+    pub terms: Vec<Vec<Literal>>,
+    /// The terms as an expression
+    pub dnf: Expr,
+}
+
+impl Dnf {
+    pub fn literal_count(&self) -> usize {
+        self.terms.iter().map(Vec::len).sum()
+    }
+}
+
+/// Convert `expr` to disjunctive normal form.
+///
+/// With `canonical`, the result is the sum of the expression's minterms in
+/// truth-table order, which stops with an error beyond [`MAX_EXPANSION_SIZE`]
+/// literals. Otherwise it is a minimal sum of products found with
+/// Quine-McCluskey, as `reduce` finds; if the search runs out of steps the
+/// best cover so far is returned with `minimal: false`, or `ReductionTimeout`
+/// under [`EvalMethod::Exact`].
+pub fn to_dnf(expr: &Expr, canonical: bool) -> Result<Dnf, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let names = variables.to_vec();
+    let (terms, minimal) = if canonical {
+        let mut terms = Vec::new();
+        for index in 0..1usize << names.len() {
+            options::check_row(index)?;
+            let assignment = assignment_from_index(&variables, index);
+            if !evaluate_expression(expr, &assignment) {
+                continue;
+            }
+            if (terms.len() + 1) * names.len() > MAX_EXPANSION_SIZE {
+                return Err(EvaluationError::ExpressionTooComplex {
+                    reason: format!("its canonical DNF has more than {} literals; the minimal DNF may be far smaller", MAX_EXPANSION_SIZE),
+                });
+            }
+            terms.push(names.iter().map(|name| Literal { variable: name.clone(), negated: !assignment[name] }).collect());
+        }
+        (terms, true)
+    } else {
+        let (cubes, minimal) = QuineMcCluskey::from_expression(expr)?.minimal_cubes();
+        if !minimal && options::current().method == EvalMethod::Exact {
+            return Err(EvaluationError::ReductionTimeout { max_iterations: MAX_REDUCTION_STEPS });
+        }
+        let terms = cubes
+            .iter()
+            .map(|cube| {
+                cube.chars()
+                    .zip(&names)
+                    .filter(|(bit, _)| *bit != '-')
+                    .map(|(bit, name)| Literal { variable: name.clone(), negated: bit == '0' })
+                    .collect()
+            })
+            .collect();
+        (terms, minimal)
+    };
+    Ok(Dnf { expression: expr.clone(), variables, canonical, minimal, dnf: terms_expr(&terms), terms })
+}
+
+/// `(l1 ∧ l2 ∧ ...) ∨ ...`, with no terms as `false` and an empty term as `true`
+fn terms_expr(terms: &[Vec<Literal>]) -> Expr {
+    terms
+        .iter()
+        .map(|term| {
+            term.iter()
+                .map(Literal::to_expr)
+                .reduce(|acc, next| Expr::And(Box::new(acc), Box::new(next)))
+                .unwrap_or(Expr::True)
+        })
+        .reduce(|acc, next| Expr::Or(Box::new(acc), Box::new(next)))
+        .unwrap_or(Expr::False)
+}
+
+/// An expression in negation normal form: only `and`, `or` and quantifiers
+/// over literals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Nnf {
+    pub expression: Expr,
+    pub nnf: Expr,
+}
+
+/// Convert `expr` to negation normal form.
+///
+/// Negations are pushed through `and`, `or` and quantifiers by De Morgan's
+/// laws until they rest on variables; the other operators are rewritten in
+/// terms of `and`, `or` and `not` on the way, and everything else keeps its
+/// shape. `xor`, `iff` and if-then-else copy their operands, so nesting them
+/// can grow the result exponentially; it stops with an error beyond
+/// [`MAX_EXPANSION_SIZE`] nodes.
+pub fn to_nnf(expr: &Expr) -> Result<Nnf, EvaluationError> {
+    let nnf = NnfBuilder { nodes: 0 }.build(expr, false)?;
+    Ok(Nnf { expression: expr.clone(), nnf })
+}
+
+/// Counts the nodes built so far, so that a blow-up stops early
+struct NnfBuilder {
+    nodes: usize,
+}
+
+impl NnfBuilder {
+    fn node(&mut self, expr: Expr) -> Result<Expr, EvaluationError> {
+        self.nodes += 1;
+        if self.nodes > MAX_EXPANSION_SIZE {
+            return Err(EvaluationError::ExpressionTooComplex {
+                reason: format!("its negation normal form has more than {} nodes", MAX_EXPANSION_SIZE),
+            });
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self, left: Expr, right: Expr) -> Result<Expr, EvaluationError> {
+        self.node(Expr::And(Box::new(left), Box::new(right)))
+    }
+
+    fn or(&mut self, left: Expr, right: Expr) -> Result<Expr, EvaluationError> {
+        self.node(Expr::Or(Box::new(left), Box::new(right)))
+    }
+
+    /// `(x ∧ y) ∨ (¬x ∧ z)` with `x`, `y` and `z` each negated as asked
+    fn choice(&mut self, x: &Expr, (y, y_negated): (&Expr, bool), (z, z_negated): (&Expr, bool)) -> Result<Expr, EvaluationError> {
+        let (x_then, then) = (self.build(x, false)?, self.build(y, y_negated)?);
+        let (x_else, otherwise) = (self.build(x, true)?, self.build(z, z_negated)?);
+        let (left, right) = (self.and(x_then, then)?, self.and(x_else, otherwise)?);
+        self.or(left, right)
+    }
+
+    /// The negation normal form of `expr`, or of `¬expr` when `negated`
+    fn build(&mut self, expr: &Expr, negated: bool) -> Result<Expr, EvaluationError> {
+        match expr {
+            Expr::Identifier(_) if negated => {
+                let variable = self.node(expr.clone())?;
+                self.node(Expr::Not(Box::new(variable)))
+            }
+            Expr::Identifier(_) => self.node(expr.clone()),
+            Expr::True | Expr::False => self.node(if (*expr == Expr::True) != negated { Expr::True } else { Expr::False }),
+            Expr::Not(inner) => self.build(inner, !negated),
+            Expr::And(left, right) | Expr::Nand(left, right) => {
+                // ¬(a ∧ b) = ¬a ∨ ¬b
+                let negated = negated != matches!(expr, Expr::Nand(..));
+                let (left, right) = (self.build(left, negated)?, self.build(right, negated)?);
+                if negated { self.or(left, right) } else { self.and(left, right) }
+            }
+            Expr::Or(left, right) | Expr::Nor(left, right) => {
+                let negated = negated != matches!(expr, Expr::Nor(..));
+                let (left, right) = (self.build(left, negated)?, self.build(right, negated)?);
+                if negated { self.and(left, right) } else { self.or(left, right) }
+            }
+            Expr::Implication(left, right) => {
+                // a → b = ¬a ∨ b, and ¬(a → b) = a ∧ ¬b
+                let (left, right) = (self.build(left, !negated)?, self.build(right, negated)?);
+                if negated { self.and(left, right) } else { self.or(left, right) }
+            }
+            // a ⊕ b = (a ∧ ¬b) ∨ (¬a ∧ b), and a ↔ b = (a ∧ b) ∨ (¬a ∧ ¬b)
+            Expr::Xor(left, right) | Expr::Iff(left, right) => {
+                let negated = negated != matches!(expr, Expr::Iff(..));
+                self.choice(left, (right, !negated), (right, negated))
+            }
+            Expr::Ite(cond, then, otherwise) => self.choice(cond, (then, negated), (otherwise, negated)),
+            Expr::Quantified(quantifier, variable, body) => {
+                // ¬∀x φ = ∃x ¬φ
+                let quantifier = match (quantifier, negated) {
+                    (Quantifier::ForAll, true) => Quantifier::Exists,
+                    (Quantifier::Exists, true) => Quantifier::ForAll,
+                    (quantifier, false) => *quantifier,
+                };
+                let body = self.build(body, negated)?;
+                self.node(Expr::Quantified(quantifier, variable.clone(), Box::new(body)))
+            }
+            Expr::Cardinality(kind, k, operands) => {
+                if operands.len() > MAX_OPERATOR_ARITY {
+                    return Err(EvaluationError::ExpressionTooComplex {
+                        reason: format!("expanding a cardinality constraint over more than {} operands", MAX_OPERATOR_ARITY),
+                    });
+                }
+                let results: Vec<bool> = (0..1usize << operands.len()).map(|row| kind.holds(*k, row.count_ones() as usize)).collect();
+                self.build(&resynthesize(operands, &results), negated)
+            }
+            Expr::Custom(..) => self.build(&expr.expand_custom(), negated),
+        }
+    }
+}
+
+/// Whether `expr` is in negation normal form
+pub fn is_nnf(expr: &Expr) -> bool {
+    match expr {
+        Expr::Identifier(_) | Expr::True | Expr::False => true,
+        Expr::Not(inner) => matches!(**inner, Expr::Identifier(_)),
+        Expr::And(left, right) | Expr::Or(left, right) => is_nnf(left) && is_nnf(right),
+        Expr::Quantified(_, _, body) => is_nnf(body),
+        _ => false,
+    }
+}
+
+/// A clause over graph literals, sorted with no literal twice
+type Clause = Vec<aig::Literal>;
+
+/// Convert `expr` to conjunctive normal form.
+///
+/// Without `tseitin`, the result is equivalent to `expr`: ORs are distributed
+/// over ANDs, clauses that contain a literal and its negation are dropped and
+/// so are clauses another one subsumes. Distribution can grow exponentially, so
+/// it stops with an error beyond [`MAX_EXPANSION_SIZE`] literals. With
+/// `tseitin`, every gate of the expression's and-inverter graph gets a variable
+/// `tN` defined by three clauses, which keeps the result linear in the size of
+/// the expression; it is satisfied by exactly the assignments that satisfy
+/// `expr`, extended with the gates' values.
+pub fn to_cnf(expr: &Expr, tseitin: bool) -> Result<Cnf, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let graph = Aig::from_expr(expr)?;
+    let output = graph.outputs()[0];
+    let inputs = graph.inputs().len();
+
+    let gates = if tseitin { reachable_gates(&graph, output) } else { Vec::new() };
+    let gate_names: HashMap<u32, String> = gates.iter().copied().zip(auxiliary_names(graph.inputs(), gates.len())).collect();
+    let clauses = if tseitin {
+        let mut clauses = match output {
+            aig::TRUE => vec![],
+            aig::FALSE => vec![vec![]],
+            _ => vec![vec![output]],
+        };
+        for &variable in &gates {
+            let gate = 2 * variable;
+            let (a, b) = graph.ands()[variable as usize - inputs - 1];
+            clauses.extend([sorted(vec![negate(gate), b]), sorted(vec![negate(gate), a]), sorted(vec![gate, negate(a), negate(b)])]);
+        }
+        clauses
+    } else {
+        remove_subsumed(distribute(&graph, output, &mut HashMap::new())?)
+    };
+
+    let literal = |lit: aig::Literal| {
+        let variable = lit / 2;
+        Literal {
+            variable: gate_names.get(&variable).cloned().unwrap_or_else(|| graph.inputs()[variable as usize - 1].clone()),
+            negated: lit & 1 == 1,
+        }
+    };
+    let definitions: Vec<Definition> = gates
+        .iter()
+        .map(|&variable| {
+            let (a, b) = graph.ands()[variable as usize - inputs - 1];
+            Definition { variable: gate_names[&variable].clone(), gate: Expr::And(Box::new(literal(b).to_expr()), Box::new(literal(a).to_expr())) }
+        })
+        .collect();
+
+    let clauses: Vec<Vec<Literal>> = clauses.into_iter().map(|clause| clause.into_iter().map(literal).collect()).collect();
+    Ok(Cnf { expression: expr.clone(), variables, tseitin, definitions, cnf: clauses_expr(&clauses), clauses })
+}
+
+/// `(l1 ∨ l2 ∨ ...) ∧ ...`, with no clauses as `true` and an empty clause as `false`
+fn clauses_expr(clauses: &[Vec<Literal>]) -> Expr {
+    clauses
+        .iter()
+        .map(|clause| {
+            clause
+                .iter()
+                .map(Literal::to_expr)
+                .reduce(|acc, next| Expr::Or(Box::new(acc), Box::new(next)))
+                .unwrap_or(Expr::False)
+        })
+        .reduce(|acc, next| Expr::And(Box::new(acc), Box::new(next)))
+        .unwrap_or(Expr::True)
+}
+
+/// The clauses of `lit`: a positive gate is the union of its inputs' clauses,
+/// and a negated one the OR of its negated inputs, distributed
+fn distribute(graph: &Aig, lit: aig::Literal, memo: &mut HashMap<aig::Literal, Vec<Clause>>) -> Result<Vec<Clause>, EvaluationError> {
+    if let Some(clauses) = memo.get(&lit) {
+        return Ok(clauses.clone());
+    }
+    let variable = (lit / 2) as usize;
+    let clauses = match lit {
+        aig::TRUE => vec![],
+        aig::FALSE => vec![vec![]],
+        _ if variable <= graph.inputs().len() => vec![vec![lit]],
+        _ => {
+            let (a, b) = graph.ands()[variable - graph.inputs().len() - 1];
+            if lit & 1 == 0 {
+                let mut clauses = distribute(graph, a, memo)?;
+                clauses.extend(distribute(graph, b, memo)?);
+                clauses.sort();
+                clauses.dedup();
+                clauses
+            } else {
+                let (left, right) = (distribute(graph, negate(a), memo)?, distribute(graph, negate(b), memo)?);
+                let literals = left.len() * right.iter().map(Vec::len).sum::<usize>() + right.len() * left.iter().map(Vec::len).sum::<usize>();
+                if literals > MAX_EXPANSION_SIZE {
+                    return Err(EvaluationError::ExpressionTooComplex {
+                        reason: format!("its CNF has more than {} literals; the Tseitin transform stays linear in the expression's size", MAX_EXPANSION_SIZE),
+                    });
+                }
+                let mut clauses: Vec<Clause> = left
+                    .iter()
+                    .flat_map(|l| right.iter().map(move |r| sorted(l.iter().chain(r).copied().collect())))
+                    .filter(|clause| !clause.windows(2).any(|pair| pair[0] == negate(pair[1])))
+                    .collect();
+                clauses.sort();
+                clauses.dedup();
+                clauses
+            }
+        }
+    };
+    memo.insert(lit, clauses.clone());
+    Ok(clauses)
+}
+
+fn sorted(mut clause: Clause) -> Clause {
+    clause.sort();
+    clause.dedup();
+    clause
+}
+
+/// Drop every clause that contains another clause, keeping the first of equal ones
+fn remove_subsumed(mut clauses: Vec<Clause>) -> Vec<Clause> {
+    clauses.sort_by_key(Vec::len);
+    let mut kept: Vec<Clause> = Vec::new();
+    for clause in clauses {
+        if !kept.iter().any(|smaller| smaller.iter().all(|lit| clause.binary_search(lit).is_ok())) {
+            kept.push(clause);
+        }
+    }
+    kept.sort();
+    kept
+}
+
+/// Gate variables the output depends on, in graph order
+fn reachable_gates(graph: &Aig, output: aig::Literal) -> Vec<u32> {
+    let inputs = graph.inputs().len() as u32;
+    let mut reached = vec![false; graph.ands().len()];
+    let mut pending = vec![output / 2];
+    while let Some(variable) = pending.pop() {
+        if variable <= inputs || reached[(variable - inputs - 1) as usize] {
+            continue;
+        }
+        reached[(variable - inputs - 1) as usize] = true;
+        let (a, b) = graph.ands()[(variable - inputs - 1) as usize];
+        pending.extend([a / 2, b / 2]);
+    }
+    (0..graph.ands().len() as u32).filter(|&gate| reached[gate as usize]).map(|gate| gate + inputs + 1).collect()
+}
+
+/// `count` names `t1`, `t2`, ... that aren't among `inputs`
+fn auxiliary_names(inputs: &[String], count: usize) -> Vec<String> {
+    (1..).map(|n| format!("t{}", n)).filter(|name| !inputs.contains(name)).take(count).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+    use crate::eval::equivalence::check_equivalence;
+    use crate::eval::sat::check_satisfiability;
+
+    fn cnf_str(input: &str, tseitin: bool) -> Cnf {
+        to_cnf(&Parser::new(input).parse().unwrap(), tseitin).unwrap()
+    }
+
+    #[test]
+    fn test_distributed_cnf_is_equivalent() {
+        for input in ["a or (b and c)", "(a and b) or (c and d)", "a xor b xor c", "a -> b", "atleast(2; a, b, c)", "not (a iff b)", "a ? b : c"] {
+            let cnf = cnf_str(input, false);
+            assert!(check_equivalence(&cnf.expression, &cnf.cnf).unwrap().equivalent, "{}", input);
+            assert!(cnf.definitions.is_empty());
+        }
+        let cnf = cnf_str("(a and b) or (c and d)", false);
+        assert_eq!(cnf.clauses.len(), 4);
+        assert_eq!(cnf.literal_count(), 8);
+        // a ∨ (a ∧ b) is subsumed by a
+        assert_eq!(cnf_str("a or (a and b)", false).cnf, Parser::new("a").parse().unwrap());
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(cnf_str("a or not a", false).cnf, Expr::True);
+        assert_eq!(cnf_str("a and not a", false).cnf, Expr::False);
+        assert_eq!(cnf_str("a and not a", true).clauses, vec![vec![]]);
+    }
+
+    #[test]
+    fn test_tseitin_is_linear_and_equisatisfiable() {
+        // Distributing ten two-literal terms makes 2^10 clauses of ten literals
+        let input = (0..10).map(|i| format!("(`x{}` and `y{}`)", i, i)).collect::<Vec<_>>().join(" or ");
+        let expr = Parser::new(&input).parse().unwrap();
+        assert!(matches!(to_cnf(&expr, false), Err(EvaluationError::ExpressionTooComplex { .. })));
+
+        let cnf = to_cnf(&expr, true).unwrap();
+        assert_eq!(cnf.definitions.len(), 19);
+        assert_eq!(cnf.clauses.len(), 1 + 3 * 19);
+
+        // Every satisfying assignment of the encoding agrees with the expression on the inputs
+        let cnf = cnf_str("(a and b) or not c", true);
+        let exists = cnf.definitions.iter().fold(cnf.cnf.clone(), |body, definition| {
+            Expr::Quantified(crate::source::Quantifier::Exists, definition.variable.clone(), Box::new(body))
+        });
+        assert!(check_equivalence(&cnf.expression, &exists).unwrap().equivalent);
+        assert!(!check_satisfiability(&cnf_str("(a or b) and not a and not b", true).cnf).unwrap().satisfiable);
+    }
+
+    #[test]
+    fn test_dnf() {
+        let dnf = |input: &str, canonical: bool| to_dnf(&Parser::new(input).parse().unwrap(), canonical).unwrap();
+        for input in ["a or (b and c)", "a xor b xor c", "a -> b", "atleast(2; a, b, c)", "a ? b : c"] {
+            for canonical in [true, false] {
+                let result = dnf(input, canonical);
+                assert!(check_equivalence(&result.expression, &result.dnf).unwrap().equivalent, "{}", input);
+            }
+        }
+
+        let canonical = dnf("a or (b and c)", true);
+        assert_eq!(canonical.terms.len(), 5);
+        assert!(canonical.terms.iter().all(|term| term.len() == 3));
+        let minimal = dnf("a or (b and c)", false);
+        assert!(minimal.minimal);
+        assert_eq!(minimal.literal_count(), 3);
+
+        assert_eq!(dnf("a and not a", false).dnf, Expr::False);
+        assert_eq!(dnf("a or not a", false).dnf, Expr::True);
+        assert_eq!(dnf("a and not a", true).terms.len(), 0);
+    }
+
+    #[test]
+    fn test_nnf() {
+        let inputs = [
+            "not (a and (b or not c))",
+            "not (a -> b) nand c",
+            "not (a xor b) nor (a iff c)",
+            "not (a ? b : c)",
+            "not atleast(2; a, b, c)",
+            "not forall x. (x or a)",
+            "not not a and not true",
+        ];
+        for input in inputs {
+            let nnf = to_nnf(&Parser::new(input).parse().unwrap()).unwrap();
+            assert!(is_nnf(&nnf.nnf), "{} gave {}", input, nnf.nnf);
+            assert!(check_equivalence(&nnf.expression, &nnf.nnf).unwrap().equivalent, "{}", input);
+        }
+
+        // Structure is kept apart from the negations
+        let nnf = to_nnf(&Parser::new("not (a and (b or not c))").parse().unwrap()).unwrap();
+        assert_eq!(nnf.nnf, Parser::new("not a or (not b and c)").parse().unwrap());
+        let nnf = to_nnf(&Parser::new("not forall x. (x or a)").parse().unwrap()).unwrap();
+        assert!(matches!(nnf.nnf, Expr::Quantified(Quantifier::Exists, ..)));
+
+        // Nested xors double at every level
+        let input = (0..16).map(|i| format!("`x{}`", i)).collect::<Vec<_>>().join(" xor ");
+        assert!(matches!(to_nnf(&Parser::new(&input).parse().unwrap()), Err(EvaluationError::ExpressionTooComplex { .. })));
+    }
+
+    #[test]
+    fn test_auxiliary_names_avoid_inputs() {
+        let cnf = cnf_str("(`t1` and b) or c", true);
+        assert!(cnf.definitions.iter().all(|definition| definition.variable != "t1"));
+    }
+}
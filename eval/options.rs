@@ -0,0 +1,190 @@
+use crate::eval::EvaluationError;
+use crate::config::{MAX_NESTING_DEPTH, MAX_VARIABLES};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// How reduction treats a search that runs out of budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvalMethod {
+    /// Return the best cover found so far, which may not be minimal (default)
+    #[default]
+    BestEffort,
+    /// Fail with `ReductionTimeout` rather than return a cover that may not be minimal
+    Exact,
+}
+
+/// Limits for the evaluations made inside [`Evaluator::with_options`](crate::eval::Evaluator::with_options),
+/// so that a server can give each request its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalOptions {
+    /// Most variables an expression may have; values above [`MAX_VARIABLES`] are capped to it
+    pub max_vars: usize,
+    /// Wall-clock time the whole scope may take, checked between truth-table
+    /// rows and reduction steps
+    pub time_budget: Option<Duration>,
+    /// Approximate bytes a whole truth table held in memory may take; larger
+    /// tables are refused before any row is evaluated
+    pub memory_budget_hint: Option<usize>,
+    /// Deepest expression evaluated, in levels as [`Expr::depth`](crate::source::Expr::depth)
+    /// counts them; every pass recurses once per level, so a deeper limit needs a bigger stack
+    pub max_depth: usize,
+    pub method: EvalMethod,
+}
+
+impl Default for EvalOptions {
+    fn default() -> Self {
+        Self {
+            max_vars: MAX_VARIABLES,
+            time_budget: None,
+            memory_budget_hint: None,
+            max_depth: MAX_NESTING_DEPTH,
+            method: EvalMethod::BestEffort,
+        }
+    }
+}
+
+/// The options in force on this thread, with the instant the time budget runs out
+#[derive(Debug, Clone, Copy)]
+struct Scope {
+    options: EvalOptions,
+    deadline: Option<Instant>,
+}
+
+thread_local! {
+    static SCOPE: Cell<Option<Scope>> = const { Cell::new(None) };
+}
+
+/// Restores the enclosing scope, even if the evaluation panics
+struct Restore(Option<Scope>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        SCOPE.with(|scope| scope.set(self.0));
+    }
+}
+
+/// Run `f` with `options` in force on this thread; an inner scope replaces an
+/// outer one until it ends
+pub(crate) fn with_options<T>(options: &EvalOptions, f: impl FnOnce() -> T) -> T {
+    let scope = Scope { options: *options, deadline: options.time_budget.map(|budget| Instant::now() + budget) };
+    let _restore = Restore(SCOPE.with(|current| current.replace(Some(scope))));
+    f()
+}
+
+/// The options in force on this thread
+pub(crate) fn current() -> EvalOptions {
+    SCOPE.with(|scope| scope.get()).map_or_else(EvalOptions::default, |scope| scope.options)
+}
+
+/// The variable limit in force
+pub(crate) fn max_vars() -> usize {
+    current().max_vars.min(MAX_VARIABLES)
+}
+
+/// Fail once the time budget in force has run out
+pub(crate) fn check_deadline() -> Result<(), EvaluationError> {
+    match SCOPE.with(|scope| scope.get()) {
+        Some(Scope { options: EvalOptions { time_budget: Some(budget), .. }, deadline: Some(deadline) }) if Instant::now() >= deadline => {
+            Err(EvaluationError::TimeBudgetExceeded { budget })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rows between deadline checks, so that enumeration doesn't read the clock for every row
+pub(crate) const ROWS_PER_DEADLINE_CHECK: usize = 1024;
+
+/// Check the deadline on every [`ROWS_PER_DEADLINE_CHECK`]th row
+pub(crate) fn check_row(row: usize) -> Result<(), EvaluationError> {
+    if row.is_multiple_of(ROWS_PER_DEADLINE_CHECK) { check_deadline() } else { Ok(()) }
+}
+
+/// Refuse to hold `rows` rows of `columns` values each when they would exceed the memory hint
+pub(crate) fn check_table_size(rows: usize, columns: usize) -> Result<(), EvaluationError> {
+    let Some(budget) = current().memory_budget_hint else {
+        return Ok(());
+    };
+    // A row is a map of names to values: a bucket, a string and a value per column
+    let estimated = rows.saturating_mul(columns.max(1).saturating_mul(48));
+    if estimated > budget {
+        return Err(EvaluationError::MemoryBudgetExceeded { estimated, budget });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Evaluator;
+    use crate::source::Parser;
+
+    fn parse(input: &str) -> crate::source::Expr {
+        Parser::new(input).parse().unwrap()
+    }
+
+    #[test]
+    fn test_limits_apply_inside_the_scope_only() {
+        let expr = parse("a and b and c");
+        let options = EvalOptions { max_vars: 2, ..EvalOptions::default() };
+        let result = Evaluator::with_options(&options, || Evaluator::generate_truth_table(&expr));
+        assert!(matches!(result, Err(EvaluationError::TooManyVariables { count: 3, max: 2 })));
+        assert!(Evaluator::generate_truth_table(&expr).is_ok());
+
+        // The inner scope wins, and the outer one is back after it
+        let relaxed = EvalOptions::default();
+        Evaluator::with_options(&options, || {
+            assert!(Evaluator::with_options(&relaxed, || Evaluator::classify(&expr)).is_ok());
+            assert!(Evaluator::classify(&expr).is_err());
+        });
+    }
+
+    #[test]
+    fn test_scope_stays_on_its_thread() {
+        let expr = parse("a and b and c");
+        let options = EvalOptions { max_vars: 2, ..EvalOptions::default() };
+        Evaluator::with_options(&options, || {
+            // A thread spawned inside the scope starts with the defaults
+            let spawned = std::thread::scope(|threads| threads.spawn(|| Evaluator::generate_truth_table(&expr).is_ok()).join().unwrap());
+            assert!(spawned);
+            assert!(Evaluator::generate_truth_table(&expr).is_err());
+        });
+
+        // Nor does a scope on another thread reach this one
+        let (entered, wait) = std::sync::mpsc::channel();
+        let (release, done) = std::sync::mpsc::channel::<()>();
+        std::thread::scope(|threads| {
+            threads.spawn(move || {
+                Evaluator::with_options(&options, || {
+                    entered.send(()).unwrap();
+                    done.recv().unwrap();
+                })
+            });
+            wait.recv().unwrap();
+            assert!(Evaluator::generate_truth_table(&expr).is_ok());
+            release.send(()).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_budgets() {
+        let wide = parse(&(0..16).map(|i| format!("`x{}`", i)).collect::<Vec<_>>().join(" xor "));
+        let expired = EvalOptions { time_budget: Some(Duration::ZERO), ..EvalOptions::default() };
+        let result = Evaluator::with_options(&expired, || Evaluator::check_equivalence(&wide, &wide));
+        assert!(matches!(result, Err(EvaluationError::TimeBudgetExceeded { .. })));
+
+        let small = EvalOptions { memory_budget_hint: Some(1 << 20), ..EvalOptions::default() };
+        let result = Evaluator::with_options(&small, || Evaluator::generate_truth_table(&wide));
+        assert!(matches!(result, Err(EvaluationError::MemoryBudgetExceeded { .. })));
+        assert!(Evaluator::with_options(&small, || Evaluator::generate_truth_table(&parse("a or b"))).is_ok());
+    }
+
+    #[test]
+    fn test_exact_method_refuses_a_non_minimal_cover() {
+        let expr = parse("(a and b) or (b and c) or (a and not c) or (not a and d)");
+        let exact = EvalOptions { method: EvalMethod::Exact, ..EvalOptions::default() };
+        let options = crate::eval::ReductionOptions { step_budget: 1, ..Default::default() };
+        assert!(Evaluator::reduce_expression_with_options(&expr, options).is_ok());
+        let result = Evaluator::with_options(&exact, || Evaluator::reduce_expression_with_options(&expr, options));
+        assert!(matches!(result, Err(EvaluationError::ReductionTimeout { .. })));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::eval::EvaluationError;
+use crate::eval::truth_table::{TruthTable, RowRange, TableSample};
+use std::collections::{HashMap, HashSet};
+use serde::{Serialize, Deserialize};
+
+/// One distinct row of a [`TableView`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewRow {
+    /// Values of the shown variables
+    pub assignment: HashMap<String, bool>,
+    /// The result, unless its column is hidden
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<bool>,
+}
+
+/// A truth table showing only some of its columns.
+///
+/// The table is still evaluated over every variable; rows that read the same
+/// once the hidden columns are gone are listed once, at their first position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableView {
+    /// Shown variables, in display order
+    pub variables: Vec<String>,
+    /// Whether the result column is shown
+    pub result: bool,
+    /// Variables the table was evaluated over but doesn't show
+    pub hidden: Vec<String>,
+    pub rows: Vec<ViewRow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<RowRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<TableSample>,
+}
+
+/// Show only `variables`, in that order, and the result if `result` is set
+pub fn select_columns(table: &TruthTable, variables: &[String], result: bool) -> Result<TableView, EvaluationError> {
+    let mut shown: Vec<String> = Vec::new();
+    for var in variables {
+        if !table.variables.contains(var) {
+            return Err(EvaluationError::InvalidTruthAssignment {
+                variable: var.clone(),
+                context: "column that is neither a variable of the table nor `result`".to_string(),
+            });
+        }
+        if !shown.contains(var) {
+            shown.push(var.clone());
+        }
+    }
+    let hidden: Vec<String> = table.variables.iter().filter(|var| !shown.iter().any(|name| name == var)).map(str::to_string).collect();
+
+    let mut seen = HashSet::new();
+    let rows = table
+        .rows
+        .iter()
+        .filter(|row| {
+            let key: Vec<bool> = shown.iter().map(|var| row.assignments.get(var).copied().unwrap_or(false)).collect();
+            seen.insert((key, result.then_some(row.result)))
+        })
+        .map(|row| ViewRow {
+            assignment: shown.iter().map(|var| (var.clone(), row.assignments.get(var).copied().unwrap_or(false))).collect(),
+            result: result.then_some(row.result),
+        })
+        .collect();
+
+    Ok(TableView {
+        variables: shown,
+        result,
+        hidden,
+        rows,
+        range: table.range,
+        sample: table.sample,
+    })
+}
+
+/// The result over every row a [`ProjectedRow`] stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectedResult {
+    True,
+    False,
+    /// Some hidden completions give true and others false
+    Mixed,
+}
+
+/// One assignment of the shown variables, standing for every row that has it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectedRow {
+    pub assignment: HashMap<String, bool>,
+    /// Rows of the table collapsed into this one
+    pub count: usize,
+    pub result: ProjectedResult,
+}
+
+/// A truth table projected onto some of its variables: the rows are grouped by
+/// their values of those variables, one row per group in order of first appearance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Projection {
+    /// Variables projected onto, in display order
+    pub variables: Vec<String>,
+    /// Variables projected away
+    pub hidden: Vec<String>,
+    pub rows: Vec<ProjectedRow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<RowRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<TableSample>,
+}
+
+/// Project the columns a [`TableView`] shows: rows with the same shown
+/// variables collapse into one, counting them and marking whether their
+/// results agree
+pub fn project(table: &TruthTable, view: &TableView) -> Projection {
+    let mut rows: Vec<ProjectedRow> = Vec::new();
+    let mut positions: HashMap<Vec<bool>, usize> = HashMap::new();
+    for row in &table.rows {
+        let key: Vec<bool> = view.variables.iter().map(|var| row.assignments.get(var).copied().unwrap_or(false)).collect();
+        let result = if row.result { ProjectedResult::True } else { ProjectedResult::False };
+        match positions.get(&key) {
+            Some(&position) => {
+                let projected = &mut rows[position];
+                projected.count += 1;
+                if projected.result != result {
+                    projected.result = ProjectedResult::Mixed;
+                }
+            }
+            None => {
+                positions.insert(key, rows.len());
+                rows.push(ProjectedRow {
+                    assignment: view.variables.iter().map(|var| (var.clone(), row.assignments.get(var).copied().unwrap_or(false))).collect(),
+                    count: 1,
+                    result,
+                });
+            }
+        }
+    }
+    Projection {
+        variables: view.variables.clone(),
+        hidden: view.hidden.clone(),
+        rows,
+        range: view.range,
+        sample: view.sample,
+    }
+}
+
+/// Show every column except the variables in `hidden`
+pub fn hide_columns(table: &TruthTable, hidden: &[String]) -> Result<TableView, EvaluationError> {
+    if let Some(unknown) = hidden.iter().find(|var| !table.variables.contains(var)) {
+        return Err(EvaluationError::InvalidTruthAssignment {
+            variable: unknown.clone(),
+            context: "hidden variable the table does not have".to_string(),
+        });
+    }
+    let shown: Vec<String> = table.variables.iter().filter(|var| !hidden.iter().any(|name| name == var)).map(str::to_string).collect();
+    select_columns(table, &shown, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::truth_table::generate_truth_table;
+    use crate::source::Parser;
+
+    fn table(input: &str) -> TruthTable {
+        generate_truth_table(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_hidden_columns_leave_distinct_rows() {
+        let view = hide_columns(&table("a and (b or c)"), &names(&["c"])).unwrap();
+        assert_eq!(view.variables, ["a", "b"]);
+        assert_eq!(view.hidden, ["c"]);
+        // a=T, b=F reads both T and F depending on c, so it appears twice
+        assert_eq!(view.rows.len(), 5);
+        let both: Vec<Option<bool>> = view.rows.iter()
+            .filter(|row| row.assignment["a"] && !row.assignment["b"])
+            .map(|row| row.result)
+            .collect();
+        assert_eq!(both, [Some(false), Some(true)]);
+    }
+
+    #[test]
+    fn test_selected_columns_keep_their_order() {
+        let view = select_columns(&table("a xor b xor c"), &names(&["c", "a"]), false).unwrap();
+        assert_eq!(view.variables, ["c", "a"]);
+        assert_eq!(view.rows.len(), 4);
+        assert!(view.rows.iter().all(|row| row.result.is_none()));
+        assert!(select_columns(&table("a"), &names(&["z"]), true).is_err());
+        assert!(hide_columns(&table("a"), &names(&["z"])).is_err());
+    }
+
+    #[test]
+    fn test_projection_counts_and_marks_results() {
+        let table = table("a and (b or c)");
+        let projection = project(&table, &hide_columns(&table, &names(&["b", "c"])).unwrap());
+        assert_eq!(projection.variables, ["a"]);
+        let rows: Vec<(bool, usize, ProjectedResult)> = projection.rows.iter()
+            .map(|row| (row.assignment["a"], row.count, row.result))
+            .collect();
+        assert_eq!(rows, [(false, 4, ProjectedResult::False), (true, 4, ProjectedResult::Mixed)]);
+
+        let projection = project(&table, &select_columns(&table, &names(&["b", "a"]), false).unwrap());
+        assert_eq!(projection.rows.len(), 4);
+        assert!(projection.rows.iter().all(|row| row.count == 2));
+        assert_eq!(projection.rows.iter().filter(|row| row.result == ProjectedResult::True).count(), 1);
+    }
+}
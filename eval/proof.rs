@@ -0,0 +1,82 @@
+use crate::source::Expr;
+use crate::eval::EvaluationError;
+use crate::eval::equivalence::{check_equivalence, EquivalenceCheck};
+use serde::{Serialize, Deserialize};
+
+/// One link `from ↔ to` of an equivalence chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofLink {
+    pub from: Expr,
+    pub to: Expr,
+    pub check: EquivalenceCheck,
+}
+
+/// Result of checking a chain of equivalences `e1 ↔ e2 ↔ … ↔ en` link by link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainProof {
+    pub valid: bool,
+    /// Index into `links` of the first link that does not hold
+    pub first_failure: Option<usize>,
+    pub links: Vec<ProofLink>,
+}
+
+/// Check that each adjacent pair of steps is equivalent, as in a textbook
+/// derivation. Every link is checked so all failures are reported.
+pub fn prove_chain(steps: &[Expr]) -> Result<ChainProof, EvaluationError> {
+    if steps.len() < 2 {
+        return Err(EvaluationError::UnsupportedOperation {
+            operation: format!("an equivalence chain needs at least two steps, got {}", steps.len()),
+        });
+    }
+
+    let links = steps
+        .windows(2)
+        .map(|pair| {
+            Ok(ProofLink {
+                from: pair[0].clone(),
+                to: pair[1].clone(),
+                check: check_equivalence(&pair[0], &pair[1])?,
+            })
+        })
+        .collect::<Result<Vec<_>, EvaluationError>>()?;
+    let first_failure = links.iter().position(|link| !link.check.equivalent);
+
+    Ok(ChainProof {
+        valid: first_failure.is_none(),
+        first_failure,
+        links,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn parse_all(steps: &[&str]) -> Vec<Expr> {
+        steps.iter().map(|s| Parser::new(s).parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_valid_chain() {
+        let steps = parse_all(&["not (a and b)", "not a or not b", "not b or not a"]);
+        let proof = prove_chain(&steps).unwrap();
+        assert!(proof.valid);
+        assert_eq!(proof.links.len(), 2);
+        assert_eq!(proof.first_failure, None);
+    }
+
+    #[test]
+    fn test_first_failing_link() {
+        let steps = parse_all(&["a and b", "b and a", "a or b", "b or a"]);
+        let proof = prove_chain(&steps).unwrap();
+        assert!(!proof.valid);
+        assert_eq!(proof.first_failure, Some(1));
+        assert!(proof.links[2].check.equivalent);
+    }
+
+    #[test]
+    fn test_single_step_rejected() {
+        assert!(prove_chain(&parse_all(&["a"])).is_err());
+    }
+}
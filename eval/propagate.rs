@@ -0,0 +1,299 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::evaluate_expression;
+use crate::eval::options;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// A value the expression requires once the known values are in place
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImpliedValue {
+    pub variable: String,
+    pub value: bool,
+    /// Propagation round (starting at 1) that found the value
+    pub round: usize,
+}
+
+/// The residual of an expression under a partial assignment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Propagation {
+    pub expression: Expr,
+    pub fixed: HashMap<String, bool>,
+    /// Unit literals split off the residual's top-level conjunction; the
+    /// expression is true exactly when these and the residual all hold
+    pub implied: Vec<ImpliedValue>,
+    /// Simplified expression over the remaining variables; `None` once it folds to a constant
+    pub residual: Option<Expr>,
+    /// Constant the expression folded to, if any
+    pub value: Option<bool>,
+    /// Unassigned variables that no longer affect the result
+    pub irrelevant: Vec<String>,
+}
+
+/// Partially evaluated expression
+enum Folded {
+    Constant(bool),
+    Residual(Expr),
+}
+
+/// Substitute the fixed values into `expr` and simplify, then repeatedly assign
+/// the unit literals of the residual's top-level conjunction until none remain.
+///
+/// A residual made only of literals is left as it is, since assigning them
+/// would fold it to true.
+pub fn propagate(expr: &Expr, fixed: &HashMap<String, bool>) -> Result<Propagation, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    if let Some(unknown) = fixed.keys().find(|var| !variables.contains(var)) {
+        return Err(EvaluationError::InvalidTruthAssignment {
+            variable: unknown.clone(),
+            context: "fixed value for a variable the expression does not use".to_string(),
+        });
+    }
+
+    let mut assignment = fixed.clone();
+    let mut implied = Vec::new();
+    let mut folded = fold(expr, &assignment);
+    let mut round = 0;
+    while let Folded::Residual(residual) = &folded {
+        let conjuncts = conjuncts(residual);
+        let units: Vec<(String, bool)> = conjuncts.iter().filter_map(|c| unit_literal(c)).collect();
+        if units.is_empty() || units.len() == conjuncts.len() {
+            break;
+        }
+        round += 1;
+        for (variable, value) in units {
+            if !assignment.contains_key(&variable) {
+                assignment.insert(variable.clone(), value);
+                implied.push(ImpliedValue { variable, value, round });
+            }
+        }
+        folded = fold(expr, &assignment);
+    }
+
+    let (residual, value) = match folded {
+        Folded::Constant(value) => (None, Some(value)),
+        Folded::Residual(residual) => (Some(residual), None),
+    };
+    let mut irrelevant = Vec::new();
+    for var in variables.iter().filter(|var| !assignment.contains_key(*var)) {
+        let matters = match &residual {
+            Some(residual) => depends_on(residual, var)?,
+            None => false,
+        };
+        if !matters {
+            irrelevant.push(var.to_string());
+        }
+    }
+
+    Ok(Propagation {
+        expression: expr.clone(),
+        fixed: fixed.clone(),
+        implied,
+        residual,
+        value,
+        irrelevant,
+    })
+}
+
+/// Fold constants through `expr`, with local simplifications such as `x ∧ ¬x = F`
+fn fold(expr: &Expr, assignment: &HashMap<String, bool>) -> Folded {
+    use Folded::{Constant, Residual};
+
+    match expr {
+        Expr::Identifier(name) => match assignment.get(name) {
+            Some(&value) => Constant(value),
+            None => Residual(expr.clone()),
+        },
+        Expr::True => Constant(true),
+        Expr::False => Constant(false),
+        Expr::Not(inner) => negate(fold(inner, assignment)),
+        Expr::And(left, right) => and(fold(left, assignment), fold(right, assignment)),
+        Expr::Or(left, right) => or(fold(left, assignment), fold(right, assignment)),
+        Expr::Nand(left, right) => negate(and(fold(left, assignment), fold(right, assignment))),
+        Expr::Nor(left, right) => negate(or(fold(left, assignment), fold(right, assignment))),
+        Expr::Xor(left, right) => match (fold(left, assignment), fold(right, assignment)) {
+            (Constant(a), Constant(b)) => Constant(a ^ b),
+            (Constant(c), other) | (other, Constant(c)) => if c { negate(other) } else { other },
+            (Residual(l), Residual(r)) if l == r => Constant(false),
+            (Residual(l), Residual(r)) => Residual(Expr::Xor(Box::new(l), Box::new(r))),
+        },
+        Expr::Implication(left, right) => or(negate(fold(left, assignment)), fold(right, assignment)),
+        Expr::Iff(left, right) => match (fold(left, assignment), fold(right, assignment)) {
+            (Constant(a), Constant(b)) => Constant(a == b),
+            (Constant(c), other) | (other, Constant(c)) => if c { other } else { negate(other) },
+            (Residual(l), Residual(r)) if l == r => Constant(true),
+            (Residual(l), Residual(r)) => Residual(Expr::Iff(Box::new(l), Box::new(r))),
+        },
+        Expr::Ite(cond, then, otherwise) => match fold(cond, assignment) {
+            Constant(c) => fold(if c { then } else { otherwise }, assignment),
+            Residual(c) => match (fold(then, assignment), fold(otherwise, assignment)) {
+                (Residual(t), Residual(e)) if t == e => Residual(t),
+                (Residual(t), Residual(e)) => Residual(Expr::Ite(Box::new(c), Box::new(t), Box::new(e))),
+                (Constant(true), e) => or(Residual(c), e),
+                (Constant(false), e) => and(negate(Residual(c)), e),
+                (t, Constant(true)) => or(negate(Residual(c)), t),
+                (t, Constant(false)) => and(Residual(c), t),
+            },
+        },
+        // Constant operands move the bound; the rest stay counted
+        Expr::Cardinality(kind, k, operands) => {
+            let mut known_true = 0;
+            let mut residuals = Vec::new();
+            for operand in operands {
+                match fold(operand, assignment) {
+                    Constant(value) => known_true += usize::from(value),
+                    Residual(residual) => residuals.push(residual),
+                }
+            }
+            let outcomes: Vec<bool> = (known_true..=known_true + residuals.len()).map(|count| kind.holds(*k, count)).collect();
+            if outcomes.iter().all(|&o| o == outcomes[0]) {
+                Constant(outcomes[0])
+            } else {
+                Residual(Expr::Cardinality(*kind, k.saturating_sub(known_true), residuals))
+            }
+        }
+        Expr::Quantified(..) => fold(&expr.eliminate_quantifiers(), assignment),
+        Expr::Custom(..) => fold(&expr.expand_custom(), assignment),
+    }
+}
+
+fn negate(folded: Folded) -> Folded {
+    match folded {
+        Folded::Constant(value) => Folded::Constant(!value),
+        Folded::Residual(Expr::Not(inner)) => Folded::Residual(*inner),
+        Folded::Residual(expr) => Folded::Residual(Expr::Not(Box::new(expr))),
+    }
+}
+
+fn complementary(a: &Expr, b: &Expr) -> bool {
+    matches!(a, Expr::Not(inner) if **inner == *b) || matches!(b, Expr::Not(inner) if **inner == *a)
+}
+
+fn and(left: Folded, right: Folded) -> Folded {
+    use Folded::{Constant, Residual};
+    match (left, right) {
+        (Constant(false), _) | (_, Constant(false)) => Constant(false),
+        (Constant(true), other) | (other, Constant(true)) => other,
+        (Residual(l), Residual(r)) if l == r => Residual(l),
+        (Residual(l), Residual(r)) if complementary(&l, &r) => Constant(false),
+        (Residual(l), Residual(r)) => Residual(Expr::And(Box::new(l), Box::new(r))),
+    }
+}
+
+fn or(left: Folded, right: Folded) -> Folded {
+    use Folded::{Constant, Residual};
+    match (left, right) {
+        (Constant(true), _) | (_, Constant(true)) => Constant(true),
+        (Constant(false), other) | (other, Constant(false)) => other,
+        (Residual(l), Residual(r)) if l == r => Residual(l),
+        (Residual(l), Residual(r)) if complementary(&l, &r) => Constant(true),
+        (Residual(l), Residual(r)) => Residual(Expr::Or(Box::new(l), Box::new(r))),
+    }
+}
+
+/// Operands of the top-level conjunction
+fn conjuncts(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::And(left, right) => {
+            let mut operands = conjuncts(left);
+            operands.extend(conjuncts(right));
+            operands
+        }
+        _ => vec![expr],
+    }
+}
+
+fn unit_literal(expr: &Expr) -> Option<(String, bool)> {
+    match expr {
+        Expr::Identifier(name) => Some((name.clone(), true)),
+        Expr::Not(inner) => match inner.as_ref() {
+            Expr::Identifier(name) => Some((name.clone(), false)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether flipping `variable` changes the value of `expr` under some assignment
+fn depends_on(expr: &Expr, variable: &str) -> Result<bool, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    if !variables.contains(variable) {
+        return Ok(false);
+    }
+    let others: Vec<&str> = variables.iter().filter(|var| *var != variable).collect();
+    let mut assignment = HashMap::new();
+    for i in 0..1usize << others.len() {
+        options::check_row(i)?;
+        for (j, var) in others.iter().enumerate() {
+            assignment.insert(var.to_string(), (i >> j) & 1 == 1);
+        }
+        assignment.insert(variable.to_string(), false);
+        let low = evaluate_expression(expr, &assignment);
+        assignment.insert(variable.to_string(), true);
+        if low != evaluate_expression(expr, &assignment) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn propagate_str(input: &str, fixed: &[(&str, bool)]) -> Propagation {
+        let expr = Parser::new(input).parse().unwrap();
+        let fixed = fixed.iter().map(|(var, value)| (var.to_string(), *value)).collect();
+        propagate(&expr, &fixed).unwrap()
+    }
+
+    #[test]
+    fn test_residual_and_irrelevant_variables() {
+        let result = propagate_str("(a and b) or (not a and c)", &[("a", true)]);
+        assert_eq!(result.residual.unwrap().to_string(), "b");
+        assert_eq!(result.irrelevant, ["c"]);
+    }
+
+    #[test]
+    fn test_units_propagate_to_fixpoint() {
+        // a fixes the first conjunct to b, then b reduces the second to c
+        let result = propagate_str("(not a or b) and (not b or c) and (d or e)", &[("a", true)]);
+        let implied: Vec<_> = result.implied.iter().map(|i| (i.variable.as_str(), i.value, i.round)).collect();
+        assert_eq!(implied, [("b", true, 1), ("c", true, 2)]);
+        assert_eq!(result.residual.unwrap().to_string(), "(d ∨ e)");
+        assert!(result.irrelevant.is_empty());
+    }
+
+    #[test]
+    fn test_folds_to_constant() {
+        let forced = propagate_str("a or b", &[("a", true)]);
+        assert_eq!(forced.value, Some(true));
+        assert_eq!(forced.irrelevant, ["b"]);
+
+        // Requires both b and not b once a is true
+        let conflict = propagate_str("(not a or b) and (not a or not b)", &[("a", true)]);
+        assert_eq!(conflict.value, Some(false));
+        assert!(conflict.residual.is_none());
+    }
+
+    #[test]
+    fn test_if_then_else_selects_branch() {
+        assert_eq!(propagate_str("s ? a and b : c", &[("s", false)]).residual.unwrap().to_string(), "c");
+        assert_eq!(propagate_str("s ? a : b", &[("a", true)]).residual.unwrap().to_string(), "(s ∨ b)");
+    }
+
+    #[test]
+    fn test_cardinality_bound_moves_with_known_operands() {
+        let result = propagate_str("atleast(2; a, b, c, d)", &[("a", true), ("b", false)]);
+        assert_eq!(result.residual.unwrap().to_string(), "atleast(1; c, d)");
+        assert_eq!(propagate_str("atmost(1; a, b, c)", &[("a", true), ("b", true)]).value, Some(false));
+    }
+
+    #[test]
+    fn test_unknown_fixed_variable_is_rejected() {
+        let expr = Parser::new("a and b").parse().unwrap();
+        let fixed = HashMap::from([("z".to_string(), true)]);
+        assert!(propagate(&expr, &fixed).is_err());
+    }
+}
@@ -1,6 +1,9 @@
-use crate::source::Expr;
+use crate::source::{Expr, StructuralOptions};
 use crate::eval::{Variables, EvaluationError};
 use crate::eval::truth_table::evaluate_expression;
+use crate::eval::options::{self as eval_options, EvalMethod};
+use crate::eval::classify::{classify, Classification};
+use crate::config::MAX_REDUCTION_STEPS;
 use std::collections::{HashMap, BTreeSet, BTreeMap};
 use serde::{Serialize, Deserialize};
 
@@ -10,6 +13,104 @@ pub struct Reduction {
     pub original: Expr,
     pub reduced: Expr,
     pub simplified: bool,
+    /// False when the search budget ran out and `reduced` is the best cover found so far
+    #[serde(default = "default_minimal")]
+    pub minimal: bool,
+    /// Which minterms and source terms each reduced term covers, when tracing was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<Vec<TermCoverage>>,
+}
+
+fn default_minimal() -> bool {
+    true
+}
+
+/// Options controlling expression reduction
+#[derive(Debug, Clone, Copy)]
+pub struct ReductionOptions {
+    /// Record which minterms and source terms each reduced term covers
+    pub trace: bool,
+    /// Fail with `ReductionTimeout` instead of returning a non-minimal cover when the budget runs out
+    pub strict: bool,
+    /// Maximum number of combine attempts and cover search steps
+    pub step_budget: usize,
+}
+
+impl Default for ReductionOptions {
+    fn default() -> Self {
+        Self {
+            trace: false,
+            strict: false,
+            step_budget: MAX_REDUCTION_STEPS,
+        }
+    }
+}
+
+/// Steps remaining for the Quine-McCluskey search
+#[derive(Debug)]
+struct StepBudget {
+    remaining: usize,
+    spent: usize,
+    exhausted: bool,
+}
+
+impl StepBudget {
+    fn new(steps: usize) -> Self {
+        Self { remaining: steps, spent: 0, exhausted: false }
+    }
+    
+    /// Consume one step, returning false once the budget or the time budget in force is spent
+    fn spend(&mut self) -> bool {
+        if self.remaining == 0 || eval_options::check_row(self.spent).is_err() {
+            self.exhausted = true;
+            return false;
+        }
+        self.remaining -= 1;
+        self.spent += 1;
+        true
+    }
+}
+
+/// Traceability of one product term of a reduced expression back to its origin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermCoverage {
+    pub term: Expr,
+    /// The implicant as a cube over the variables in order: `1`, `0`, or `-` (absent)
+    pub cube: String,
+    /// Minterm indices covered, reading the variables in order as a binary number (first variable most significant)
+    pub minterms: Vec<usize>,
+    /// Top-level disjuncts of the original expression that share at least one of those minterms
+    pub source_terms: Vec<Expr>,
+}
+
+/// A prime implicant of a function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrimeImplicant {
+    /// The implicant as a product term; absent when it has no literals (the function is a tautology)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term: Option<Expr>,
+    /// The implicant as a cube over the variables in order: `1`, `0`, or `-` (absent)
+    pub cube: String,
+    /// Minterm indices covered (first variable most significant)
+    pub minterms: Vec<usize>,
+}
+
+/// Prime implicants of an expression, computed without selecting a cover
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrimeImplicantReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub count: usize,
+    /// Empty when only the count was requested
+    pub implicants: Vec<PrimeImplicant>,
+}
+
+/// A code snippet whose condition was replaced by its reduced form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetRewrite {
+    pub original: String,
+    pub rewritten: String,
+    pub reduction: Reduction,
 }
 
 /// Represents a minterm or implicant in the Quine-McCluskey algorithm
@@ -73,6 +174,18 @@ impl Minterm {
         }
     }
     
+    /// Render as a cube: `1`/`0` for literals, `-` for eliminated variables
+    fn cube(&self) -> String {
+        self.bits
+            .iter()
+            .map(|bit| match bit {
+                Some(true) => '1',
+                Some(false) => '0',
+                None => '-',
+            })
+            .collect()
+    }
+    
     /// Convert minterm back to an expression
     fn to_expression(&self, variables: &Variables) -> Option<Expr> {
         let var_vec = variables.to_vec();
@@ -104,6 +217,7 @@ impl Minterm {
 pub struct QuineMcCluskey {
     variables: Variables,
     minterms: BTreeSet<usize>,
+    step_budget: usize,
 }
 
 impl QuineMcCluskey {
@@ -119,7 +233,7 @@ impl QuineMcCluskey {
             
             for (j, var) in variables.iter().enumerate() {
                 let value = (i >> (num_vars - 1 - j)) & 1 == 1;
-                assignment.insert(var.clone(), value);
+                assignment.insert(var.to_string(), value);
             }
             
             if evaluate_expression(expr, &assignment) {
@@ -127,42 +241,156 @@ impl QuineMcCluskey {
             }
         }
         
-        Ok(Self { variables, minterms })
+        Ok(Self { variables, minterms, step_budget: MAX_REDUCTION_STEPS })
+    }
+    
+    /// Create an instance for the function that is true on `minterms`, indexed
+    /// with the first variable as the most significant bit
+    pub fn from_minterms(variables: Variables, minterms: BTreeSet<usize>) -> Self {
+        Self { variables, minterms, step_budget: MAX_REDUCTION_STEPS }
+    }
+    
+    /// Limit the number of combine attempts and cover search steps
+    pub fn with_step_budget(mut self, steps: usize) -> Self {
+        self.step_budget = steps;
+        self
     }
     
     /// Run the Quine-McCluskey algorithm to find minimal sum-of-products
     pub fn minimize(&self) -> Option<Expr> {
+        self.minimize_with_status().0
+    }
+    
+    /// Like `minimize`, also reporting whether the result is proven minimal
+    /// (false when the step budget ran out first)
+    pub fn minimize_with_status(&self) -> (Option<Expr>, bool) {
         if self.minterms.is_empty() {
             // Expression is always false
-            return Some(Expr::And(
-                Box::new(Expr::Identifier("false".to_string())),
-                Box::new(Expr::Not(Box::new(Expr::Identifier("false".to_string()))))
-            ));
+            return (Some(Expr::False), true);
         }
         
         let num_vars = self.variables.len();
         if num_vars == 0 {
-            return None;
+            return (None, true);
         }
         
-        // Step 1: Generate initial minterms
-        let current_implicants: Vec<Minterm> = self.minterms
+        let (minimal_cover, minimal) = self.minimal_cover();
+        
+        // Step 4: Convert back to expression
+        (self.implicants_to_expression(&minimal_cover), minimal)
+    }
+    
+    /// The cubes of a minimal sum-of-products cover, one character per variable
+    /// as in [`PrimeImplicant::cube`], and whether the cover is proven minimal.
+    /// A function that is never true has no cubes.
+    pub fn minimal_cubes(&self) -> (Vec<String>, bool) {
+        if self.minterms.is_empty() {
+            return (Vec::new(), true);
+        }
+        if self.variables.is_empty() {
+            return (vec![String::new()], true);
+        }
+        let (cover, minimal) = self.minimal_cover();
+        let mut cubes: Vec<String> = cover.iter().map(Minterm::cube).collect();
+        cubes.sort_by(|a, b| b.cmp(a));
+        (cubes, minimal)
+    }
+    
+    /// Compute all prime implicants, skipping cover selection
+    fn all_prime_implicants(&self) -> Vec<Minterm> {
+        self.initial_implicants(&mut StepBudget::new(usize::MAX))
+    }
+    
+    /// Combine the minterms into prime implicants within the given budget
+    fn initial_implicants(&self, budget: &mut StepBudget) -> Vec<Minterm> {
+        let initial: Vec<Minterm> = self.minterms
             .iter()
-            .map(|&idx| Minterm::new(idx, num_vars))
+            .map(|&idx| Minterm::new(idx, self.variables.len()))
             .collect();
+        self.find_prime_implicants(initial, budget)
+    }
+    
+    /// Number of prime implicants of the function
+    pub fn count_prime_implicants(&self) -> usize {
+        self.all_prime_implicants().len()
+    }
+    
+    /// All prime implicants of the function, sorted by cube
+    pub fn prime_implicants(&self) -> Vec<PrimeImplicant> {
+        let mut implicants: Vec<PrimeImplicant> = self.all_prime_implicants()
+            .into_iter()
+            .map(|implicant| PrimeImplicant {
+                term: implicant.to_expression(&self.variables),
+                cube: implicant.cube(),
+                minterms: implicant.covered_minterms.into_iter().collect(),
+            })
+            .collect();
+        implicants.sort_by(|a, b| b.cube.cmp(&a.cube));
+        implicants
+    }
+    
+    /// Compute the selected implicants of a minimal sum-of-products cover,
+    /// and whether the search completed within the step budget
+    fn minimal_cover(&self) -> (Vec<Minterm>, bool) {
+        let mut budget = StepBudget::new(self.step_budget);
         
-        // Step 2: Find all prime implicants
-        let prime_implicants = self.find_prime_implicants(current_implicants);
+        // Steps 1-2: Generate initial minterms and find all prime implicants
+        let prime_implicants = self.initial_implicants(&mut budget);
         
         // Step 3: Find essential prime implicants and minimal cover
-        let minimal_cover = self.find_minimal_cover(&prime_implicants);
+        let cover = self.find_minimal_cover(&prime_implicants, &mut budget);
+        (cover, !budget.exhausted)
+    }
+    
+    /// Map each term of the minimal cover back to the minterms and source terms it covers
+    pub fn trace_coverage(&self, original: &Expr) -> Vec<TermCoverage> {
+        if self.minterms.is_empty() || self.variables.is_empty() {
+            return Vec::new();
+        }
         
-        // Step 4: Convert back to expression
-        self.implicants_to_expression(&minimal_cover)
+        let num_vars = self.variables.len();
+        let source_terms: Vec<(Expr, BTreeSet<usize>)> = disjuncts(original)
+            .into_iter()
+            .map(|term| {
+                let covered = (0..(1 << num_vars))
+                    .filter(|&i| {
+                        let mut assignment = HashMap::new();
+                        for (j, var) in self.variables.iter().enumerate() {
+                            assignment.insert(var.to_string(), (i >> (num_vars - 1 - j)) & 1 == 1);
+                        }
+                        evaluate_expression(term, &assignment)
+                    })
+                    .collect();
+                (term.clone(), covered)
+            })
+            .collect();
+        
+        self.minimal_cover()
+            .0
+            .into_iter()
+            .filter_map(|implicant| {
+                let term = implicant.to_expression(&self.variables)?;
+                let cube = implicant.cube();
+                let sources = source_terms
+                    .iter()
+                    .filter(|(_, covered)| !covered.is_disjoint(&implicant.covered_minterms))
+                    .map(|(source, _)| source.clone())
+                    .collect();
+                Some(TermCoverage {
+                    term,
+                    cube,
+                    minterms: implicant.covered_minterms.into_iter().collect(),
+                    source_terms: sources,
+                })
+            })
+            .collect()
     }
     
-    /// Find all prime implicants using iterative combining
-    fn find_prime_implicants(&self, mut current_implicants: Vec<Minterm>) -> Vec<Minterm> {
+    /// Find all prime implicants using iterative combining.
+    ///
+    /// If the budget runs out part way, the implicants of the unfinished round are
+    /// returned as they are: they still cover every minterm, but may not be prime.
+    fn find_prime_implicants(&self, mut current_implicants: Vec<Minterm>, budget: &mut StepBudget) -> Vec<Minterm> {
         let mut prime_implicants = Vec::new();
         
         while !current_implicants.is_empty() {
@@ -181,6 +409,10 @@ impl QuineMcCluskey {
                 if let Some(next_indices) = groups.get(&(ones_count + 1)) {
                     for &i in indices {
                         for &j in next_indices {
+                            if !budget.spend() {
+                                prime_implicants.extend(current_implicants);
+                                return prime_implicants;
+                            }
                             if let Some(combined) = current_implicants[i].combine(&current_implicants[j]) {
                                 next_implicants.push(combined);
                                 used[i] = true;
@@ -208,8 +440,9 @@ impl QuineMcCluskey {
         prime_implicants
     }
     
-    /// Find minimal cover using essential prime implicants and heuristics
-    fn find_minimal_cover(&self, prime_implicants: &[Minterm]) -> Vec<Minterm> {
+    /// Find minimal cover: essential prime implicants first, then a greedy cover
+    /// refined by branch-and-bound search until the budget runs out
+    fn find_minimal_cover(&self, prime_implicants: &[Minterm], budget: &mut StepBudget) -> Vec<Minterm> {
         if prime_implicants.is_empty() {
             return Vec::new();
         }
@@ -271,30 +504,43 @@ impl QuineMcCluskey {
             return selected_implicants;
         }
         
-        // Use greedy heuristic for remaining minterms
-        while !uncovered_minterms.is_empty() && !available_implicants.is_empty() {
+        // Use greedy heuristic for an initial cover of the remaining minterms
+        let mut greedy = Vec::new();
+        let mut greedy_uncovered = uncovered_minterms.clone();
+        let mut greedy_available = available_implicants.clone();
+        while !greedy_uncovered.is_empty() && !greedy_available.is_empty() {
             // Find implicant that covers the most uncovered minterms
-            let best_implicant = available_implicants
+            let best_implicant = greedy_available
                 .iter()
                 .enumerate()
                 .max_by_key(|(_, impl_)| {
-                    impl_.covered_minterms.intersection(&uncovered_minterms).count()
+                    impl_.covered_minterms.intersection(&greedy_uncovered).count()
                 });
             
             if let Some((idx, implicant)) = best_implicant {
-                selected_implicants.push(implicant.clone());
+                greedy.push(implicant.clone());
                 
                 // Remove covered minterms
                 for &covered in &implicant.covered_minterms {
-                    uncovered_minterms.remove(&covered);
+                    greedy_uncovered.remove(&covered);
                 }
                 
-                available_implicants.remove(idx);
+                greedy_available.remove(idx);
             } else {
                 break;
             }
         }
         
+        // Then search for a cheaper cover, keeping the greedy one if the budget runs out
+        let mut best = CoverSearch {
+            candidates: &available_implicants,
+            best_cost: cover_cost(&greedy),
+            best: greedy,
+            chosen: Vec::new(),
+        };
+        best.search(&uncovered_minterms, budget);
+        
+        selected_implicants.extend(best.best);
         selected_implicants
     }
     
@@ -327,129 +573,174 @@ impl QuineMcCluskey {
     }
 }
 
-/// Reduce/simplify a boolean expression using Quine-McCluskey algorithm
-pub fn reduce_expression(expr: &Expr) -> Result<Reduction, EvaluationError> {
-    // Handle special cases first
-    if is_tautology(expr) {
-        // Expression is always true
-        let true_expr = Expr::Or(
-            Box::new(Expr::Identifier("true".to_string())),
-            Box::new(Expr::Not(Box::new(Expr::Identifier("true".to_string()))))
-        );
-        return Ok(Reduction {
-            original: expr.clone(),
-            reduced: true_expr,
-            simplified: true,
-        });
-    }
-    
-    if is_contradiction(expr) {
-        // Expression is always false
-        let false_expr = Expr::And(
-            Box::new(Expr::Identifier("false".to_string())),
-            Box::new(Expr::Not(Box::new(Expr::Identifier("false".to_string()))))
-        );
-        return Ok(Reduction {
-            original: expr.clone(),
-            reduced: false_expr,
-            simplified: true,
-        });
-    }
-    
-    // Use Quine-McCluskey for general reduction
-    match QuineMcCluskey::from_expression(expr) {
-        Ok(qm) => {
-            if let Some(reduced_expr) = qm.minimize() {
-                // Check if the reduction actually simplified the expression
-                let simplified = !expressions_equivalent_structure(expr, &reduced_expr);
-                
-                Ok(Reduction {
-                    original: expr.clone(),
-                    reduced: reduced_expr,
-                    simplified,
-                })
-            } else {
-                // Could not minimize (e.g., no variables)
-                Ok(Reduction {
-                    original: expr.clone(),
-                    reduced: expr.clone(),
-                    simplified: false,
-                })
-            }
-        }
-        Err(e) => Err(e),
-    }
+/// Cost of a cover: number of terms, then number of literals
+fn cover_cost(cover: &[Minterm]) -> (usize, usize) {
+    let literals = cover
+        .iter()
+        .map(|implicant| implicant.bits.iter().filter(|bit| bit.is_some()).count())
+        .sum();
+    (cover.len(), literals)
 }
 
-/// Check if an expression is a tautology (always true)
-fn is_tautology(expr: &Expr) -> bool {
-    match Variables::from_expr(expr) {
-        Ok(variables) => {
-            let num_vars = variables.len();
-            if num_vars == 0 {
-                return false; // No variables, evaluate directly
+/// Branch-and-bound search for the cheapest cover of the non-essential minterms
+struct CoverSearch<'a> {
+    candidates: &'a [Minterm],
+    chosen: Vec<Minterm>,
+    best: Vec<Minterm>,
+    best_cost: (usize, usize),
+}
+
+impl CoverSearch<'_> {
+    fn search(&mut self, uncovered: &BTreeSet<usize>, budget: &mut StepBudget) {
+        if !budget.spend() {
+            return;
+        }
+        
+        let cost = cover_cost(&self.chosen);
+        if uncovered.is_empty() {
+            if cost < self.best_cost {
+                self.best = self.chosen.clone();
+                self.best_cost = cost;
             }
-            
-            // Check all possible truth assignments
-            for i in 0..(1 << num_vars) {
-                let mut assignment = HashMap::new();
-                
-                for (j, var) in variables.iter().enumerate() {
-                    let value = (i >> (num_vars - 1 - j)) & 1 == 1;
-                    assignment.insert(var.clone(), value);
-                }
-                
-                if !evaluate_expression(expr, &assignment) {
-                    return false; // Found an assignment that makes it false
-                }
+            return;
+        }
+        
+        // Any completion needs at least one more term
+        if (cost.0 + 1, cost.1) >= self.best_cost {
+            return;
+        }
+        
+        // Branch on the minterm with the fewest covering candidates
+        let Some(covering) = uncovered
+            .iter()
+            .map(|minterm| {
+                self.candidates
+                    .iter()
+                    .filter(|implicant| implicant.covered_minterms.contains(minterm))
+                    .collect::<Vec<_>>()
+            })
+            .min_by_key(|covering| covering.len())
+        else {
+            return;
+        };
+        
+        for implicant in covering {
+            let remaining = uncovered - &implicant.covered_minterms;
+            self.chosen.push(implicant.clone());
+            self.search(&remaining, budget);
+            self.chosen.pop();
+            if budget.exhausted {
+                return;
             }
-            
-            true // All assignments make it true
         }
-        Err(_) => false, // Error in expression, not a tautology
     }
 }
 
-/// Check if an expression is a contradiction (always false)
-fn is_contradiction(expr: &Expr) -> bool {
-    match Variables::from_expr(expr) {
-        Ok(variables) => {
-            let num_vars = variables.len();
-            if num_vars == 0 {
-                return false; // No variables, evaluate directly
-            }
-            
-            // Check all possible truth assignments
-            for i in 0..(1 << num_vars) {
-                let mut assignment = HashMap::new();
-                
-                for (j, var) in variables.iter().enumerate() {
-                    let value = (i >> (num_vars - 1 - j)) & 1 == 1;
-                    assignment.insert(var.clone(), value);
-                }
-                
-                if evaluate_expression(expr, &assignment) {
-                    return false; // Found an assignment that makes it true
-                }
-            }
-            
-            true // All assignments make it false
+/// Top-level operands of an `or` chain (or the expression itself)
+fn disjuncts(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Or(left, right) => {
+            let mut terms = disjuncts(left);
+            terms.extend(disjuncts(right));
+            terms
         }
-        Err(_) => false, // Error in expression, not a contradiction
+        _ => vec![expr],
     }
 }
 
-/// Compare two expressions for structural equivalence (not logical equivalence)
-fn expressions_equivalent_structure(left: &Expr, right: &Expr) -> bool {
-    match (left, right) {
-        (Expr::Identifier(a), Expr::Identifier(b)) => a == b,
-        (Expr::Not(a), Expr::Not(b)) => expressions_equivalent_structure(a, b),
-        (Expr::And(a1, a2), Expr::And(b1, b2)) |
-        (Expr::Or(a1, a2), Expr::Or(b1, b2)) |
-        (Expr::Xor(a1, a2), Expr::Xor(b1, b2)) |
-        (Expr::Implication(a1, a2), Expr::Implication(b1, b2)) => {
-            expressions_equivalent_structure(a1, b1) && expressions_equivalent_structure(a2, b2)
+/// Compute the prime implicants of an expression without minimizing it.
+///
+/// With `list` false only the count is reported, skipping conversion of each
+/// implicant to a term.
+pub fn prime_implicants(expr: &Expr, list: bool) -> Result<PrimeImplicantReport, EvaluationError> {
+    let qm = QuineMcCluskey::from_expression(expr)?;
+    let (count, implicants) = if list {
+        let implicants = qm.prime_implicants();
+        (implicants.len(), implicants)
+    } else {
+        (qm.count_prime_implicants(), Vec::new())
+    };
+    
+    Ok(PrimeImplicantReport {
+        expression: expr.clone(),
+        variables: qm.variables,
+        count,
+        implicants,
+    })
+}
+
+/// Reduce an expression and record, for each reduced term, which minterms and
+/// source terms of the original expression it covers
+pub fn reduce_expression_with_trace(expr: &Expr) -> Result<Reduction, EvaluationError> {
+    reduce_expression_with_options(expr, ReductionOptions { trace: true, ..Default::default() })
+}
+
+/// Reduce/simplify a boolean expression using Quine-McCluskey algorithm
+pub fn reduce_expression(expr: &Expr) -> Result<Reduction, EvaluationError> {
+    reduce_expression_with_options(expr, ReductionOptions::default())
+}
+
+/// Reduce an expression with explicit options.
+///
+/// When the step budget runs out, the best cover found so far is returned with
+/// `minimal: false`, or `ReductionTimeout` in strict mode.
+pub fn reduce_expression_with_options(expr: &Expr, options: ReductionOptions) -> Result<Reduction, EvaluationError> {
+    let classification = classify(expr)?.classification;
+    reduce_classified(expr, classification, || QuineMcCluskey::from_expression(expr), options)
+}
+
+/// Reduce an expression already classified, building its minterms with `qm`
+/// only when it isn't constant
+pub(crate) fn reduce_classified(
+    expr: &Expr,
+    classification: Classification,
+    qm: impl FnOnce() -> Result<QuineMcCluskey, EvaluationError>,
+    options: ReductionOptions,
+) -> Result<Reduction, EvaluationError> {
+    // Handle special cases first: an expression that is always true or always false
+    let constant = match classification {
+        Classification::Tautology => Some(Expr::True),
+        Classification::Contradiction => Some(Expr::False),
+        Classification::Contingent => None,
+    };
+    if let Some(reduced) = constant {
+        return Ok(Reduction {
+            original: expr.clone(),
+            simplified: *expr != reduced,
+            reduced,
+            minimal: true,
+            coverage: options.trace.then(Vec::new),
+        });
+    }
+    
+    // Use Quine-McCluskey for general reduction
+    let qm = qm()?.with_step_budget(options.step_budget);
+    let (reduced, minimal) = qm.minimize_with_status();
+    if !minimal && (options.strict || eval_options::current().method == EvalMethod::Exact) {
+        return Err(EvaluationError::ReductionTimeout { max_iterations: options.step_budget });
+    }
+    
+    let coverage = options.trace.then(|| qm.trace_coverage(expr));
+    match reduced {
+        Some(reduced_expr) => {
+            // Check if the reduction actually simplified the expression
+            let simplified = !expr.structurally_equal(&reduced_expr, StructuralOptions::modulo_ac());
+            
+            Ok(Reduction {
+                original: expr.clone(),
+                reduced: reduced_expr,
+                simplified,
+                minimal,
+                coverage,
+            })
         }
-        _ => false,
+        // Could not minimize (e.g., no variables)
+        None => Ok(Reduction {
+            original: expr.clone(),
+            reduced: expr.clone(),
+            simplified: false,
+            minimal,
+            coverage,
+        }),
     }
-}
\ No newline at end of file
+}
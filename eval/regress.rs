@@ -0,0 +1,149 @@
+use crate::source::Expr;
+use crate::eval::equivalence::check_equivalence;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// One line of a JSON-lines corpus such as `{"name": "checkout", "expression": "a and b"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusEntry {
+    /// Pairs the entry with its counterpart in the other corpus; without one,
+    /// the expression's fingerprint does
+    #[serde(default)]
+    pub name: Option<String>,
+    pub expression: String,
+}
+
+/// How an expression differs from its golden counterpart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegressionStatus {
+    /// The same tree
+    Unchanged,
+    /// A different tree with the same truth table
+    Syntactic,
+    /// A different truth table
+    Semantic,
+    /// Only in the new corpus
+    Added,
+    /// Only in the golden corpus
+    Removed,
+    /// The two couldn't be compared
+    Error,
+}
+
+/// One paired, added or removed expression
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Regression {
+    /// The entry's name, or its expression's fingerprint as 16 hex digits
+    pub key: String,
+    pub status: RegressionStatus,
+    pub golden: Option<Expr>,
+    pub input: Option<Expr>,
+    /// An assignment the two disagree on, for semantic changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counterexample: Option<HashMap<String, bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The entries of a new corpus compared with a golden one: golden entries in
+/// order, then the added ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegressionReport {
+    pub entries: Vec<Regression>,
+}
+
+impl RegressionReport {
+    pub fn count(&self, status: RegressionStatus) -> usize {
+        self.entries.iter().filter(|entry| entry.status == status).count()
+    }
+
+    /// No expression changed meaning and every pair could be compared
+    pub fn passed(&self) -> bool {
+        self.count(RegressionStatus::Semantic) == 0 && self.count(RegressionStatus::Error) == 0
+    }
+}
+
+/// The key an entry is paired by: its name, or its expression's fingerprint
+pub fn corpus_key(name: Option<&str>, expr: &Expr) -> String {
+    name.map_or_else(|| format!("{:016x}", expr.fingerprint()), str::to_string)
+}
+
+/// Pair keyed expressions of `input` with those of `golden` and check each
+/// pair whose trees differ for equivalence
+pub fn regress(golden: &[(String, Expr)], input: &[(String, Expr)]) -> RegressionReport {
+    let inputs: HashMap<&str, &Expr> = input.iter().map(|(key, expr)| (key.as_str(), expr)).collect();
+    let mut entries: Vec<Regression> = golden
+        .iter()
+        .map(|(key, old)| {
+            let entry = |status, new: Option<&Expr>| Regression {
+                key: key.clone(),
+                status,
+                golden: Some(old.clone()),
+                input: new.cloned(),
+                counterexample: None,
+                message: None,
+            };
+            match inputs.get(key.as_str()) {
+                None => entry(RegressionStatus::Removed, None),
+                Some(new) if old == *new => entry(RegressionStatus::Unchanged, Some(new)),
+                Some(new) => match check_equivalence(old, new) {
+                    Ok(check) if check.equivalent => entry(RegressionStatus::Syntactic, Some(new)),
+                    Ok(check) => Regression {
+                        counterexample: check.differences.into_iter().next().map(|difference| difference.assignment),
+                        ..entry(RegressionStatus::Semantic, Some(new))
+                    },
+                    Err(e) => Regression { message: Some(e.to_string()), ..entry(RegressionStatus::Error, Some(new)) },
+                },
+            }
+        })
+        .collect();
+
+    let golden_keys: Vec<&str> = golden.iter().map(|(key, _)| key.as_str()).collect();
+    entries.extend(input.iter().filter(|(key, _)| !golden_keys.contains(&key.as_str())).map(|(key, new)| Regression {
+        key: key.clone(),
+        status: RegressionStatus::Added,
+        golden: None,
+        input: Some(new.clone()),
+        counterexample: None,
+        message: None,
+    }));
+    RegressionReport { entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn keyed(entries: &[(Option<&str>, &str)]) -> Vec<(String, Expr)> {
+        entries
+            .iter()
+            .map(|(name, text)| {
+                let expr = Parser::new(text).parse().unwrap();
+                (corpus_key(*name, &expr), expr)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pairs_by_name_and_fingerprint() {
+        let golden = keyed(&[(Some("same"), "a and b"), (Some("refactored"), "not (a or b)"), (Some("broken"), "a or b"), (Some("dropped"), "c"), (None, "x xor y")]);
+        let input = keyed(&[(None, "x xor y"), (Some("broken"), "a and b"), (Some("same"), "a and b"), (Some("refactored"), "not a and not b"), (Some("new"), "d")]);
+        let report = regress(&golden, &input);
+
+        let statuses: Vec<(&str, RegressionStatus)> = report.entries.iter().map(|entry| (entry.key.as_str(), entry.status)).collect();
+        let fingerprint = corpus_key(None, &Parser::new("x xor y").parse().unwrap());
+        assert_eq!(statuses, [
+            ("same", RegressionStatus::Unchanged),
+            ("refactored", RegressionStatus::Syntactic),
+            ("broken", RegressionStatus::Semantic),
+            ("dropped", RegressionStatus::Removed),
+            (fingerprint.as_str(), RegressionStatus::Unchanged),
+            ("new", RegressionStatus::Added),
+        ]);
+        assert_eq!(report.entries[2].counterexample, Some(HashMap::from([("a".to_string(), true), ("b".to_string(), false)])));
+        assert!(!report.passed());
+        assert!(regress(&golden, &golden).passed());
+    }
+}
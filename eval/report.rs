@@ -0,0 +1,219 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::{TruthTable, TruthTableRow, assignment_from_index, evaluate_expression};
+use crate::eval::classify::{classify_results, ClassificationReport};
+use crate::eval::reduction::{reduce_classified, QuineMcCluskey, Reduction, ReductionOptions};
+use crate::eval::anf::moebius_transform;
+use crate::eval::options;
+use crate::config::MAX_KARNAUGH_VARIABLES;
+use std::collections::BTreeSet;
+use serde::{Serialize, Deserialize};
+
+/// Properties of a boolean function read off its truth table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Properties {
+    pub rows: usize,
+    pub true_rows: usize,
+    /// True on exactly half the rows
+    pub balanced: bool,
+    /// Never turns false when a variable turns true
+    pub monotone: bool,
+    /// Negating every variable negates the result
+    pub self_dual: bool,
+    /// An xor of variables, possibly negated: algebraic degree at most 1
+    pub affine: bool,
+    /// Depends only on how many variables are true
+    pub symmetric: bool,
+    /// Variables that change the result on some row
+    pub essential: Vec<String>,
+    /// Algebraic degree, the most variables in a monomial of the ANF
+    pub degree: usize,
+}
+
+/// A Karnaugh map: rows and columns in Gray-code order, so that neighbouring
+/// cells, wrapping around the edges, differ in one variable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KarnaughMap {
+    pub row_variables: Vec<String>,
+    pub column_variables: Vec<String>,
+    /// Values of the row variables for each row, such as `01`, first variable first
+    pub row_labels: Vec<String>,
+    pub column_labels: Vec<String>,
+    pub cells: Vec<Vec<bool>>,
+}
+
+/// The truth table, classification, reduction, properties and Karnaugh map of
+/// an expression, all computed from one evaluation of each row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub table: TruthTable,
+    pub classification: ClassificationReport,
+    pub reduction: Reduction,
+    pub properties: Properties,
+    /// Drawn for one to [`MAX_KARNAUGH_VARIABLES`] variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub karnaugh_map: Option<KarnaughMap>,
+}
+
+/// Evaluate every row of `expr` once and derive each part of the report from the results
+pub fn report(expr: &Expr) -> Result<Report, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let n = variables.len();
+    options::check_table_size(1 << n, n)?;
+    let mut results = Vec::with_capacity(1 << n);
+    for index in 0..1usize << n {
+        options::check_row(index)?;
+        results.push(evaluate_expression(expr, &assignment_from_index(&variables, index)));
+    }
+
+    let classification = classify_results(expr, variables.clone(), &results);
+    let reduction = reduce_classified(
+        expr,
+        classification.classification,
+        || {
+            // Quine-McCluskey numbers minterms with the first variable as the most significant bit
+            let minterms: BTreeSet<usize> = (0..results.len())
+                .filter(|&index| results[index])
+                .map(|index| (0..n).filter(|bit| index >> bit & 1 == 1).map(|bit| 1 << (n - 1 - bit)).sum())
+                .collect();
+            Ok(QuineMcCluskey::from_minterms(variables.clone(), minterms))
+        },
+        ReductionOptions::default(),
+    )?;
+    let table = TruthTable {
+        variables: variables.clone(),
+        rows: results
+            .iter()
+            .enumerate()
+            .map(|(index, &result)| TruthTableRow { assignments: assignment_from_index(&variables, index), result })
+            .collect(),
+        range: None,
+        sample: None,
+    };
+
+    Ok(Report {
+        expression: expr.clone(),
+        properties: properties(&variables, &results),
+        karnaugh_map: (1..=MAX_KARNAUGH_VARIABLES).contains(&n).then(|| karnaugh_map(&variables, &results)),
+        variables,
+        table,
+        classification,
+        reduction,
+    })
+}
+
+/// Properties of the function with `results` in truth-table order, variable `j` in bit `j`
+fn properties(variables: &Variables, results: &[bool]) -> Properties {
+    let n = variables.len();
+    let rows = results.len();
+    let true_rows = results.iter().filter(|&&result| result).count();
+    let all = rows - 1;
+    let degree = moebius_transform(results)
+        .iter()
+        .enumerate()
+        .filter(|(_, coefficient)| **coefficient)
+        .map(|(monomial, _)| monomial.count_ones() as usize)
+        .max()
+        .unwrap_or(0);
+    // The value at the first row with each number of true variables
+    let mut by_weight: Vec<Option<bool>> = vec![None; n + 1];
+
+    Properties {
+        rows,
+        true_rows,
+        balanced: 2 * true_rows == rows,
+        monotone: (0..rows).all(|x| (0..n).all(|bit| !results[x] || results[x | 1 << bit])),
+        self_dual: (0..rows).all(|x| results[x ^ all] != results[x]),
+        affine: degree <= 1,
+        symmetric: (0..rows).all(|x| *by_weight[x.count_ones() as usize].get_or_insert(results[x]) == results[x]),
+        essential: variables
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| (0..rows).any(|x| results[x] != results[x ^ 1 << bit]))
+            .map(|(_, name)| name.to_string())
+            .collect(),
+        degree,
+    }
+}
+
+/// The Karnaugh map with the first half of the variables, rounded down, on the rows
+fn karnaugh_map(variables: &Variables, results: &[bool]) -> KarnaughMap {
+    let names = variables.to_vec();
+    let (row_variables, column_variables) = names.split_at(names.len() / 2);
+    let gray = |i: usize| i ^ (i >> 1);
+    // A code's bits name the variables of a group with the first as the leftmost bit;
+    // `spread` moves them to the variables' bits in a row index, from `offset`
+    let label = |code: usize, width: usize| (0..width).map(|bit| if code >> (width - 1 - bit) & 1 == 1 { '1' } else { '0' }).collect::<String>();
+    let spread = |code: usize, width: usize, offset: usize| (0..width).filter(|bit| code >> (width - 1 - bit) & 1 == 1).map(|bit| 1 << (offset + bit)).sum::<usize>();
+
+    let (row_count, column_count) = (1 << row_variables.len(), 1 << column_variables.len());
+    KarnaughMap {
+        row_labels: (0..row_count).map(|r| label(gray(r), row_variables.len())).collect(),
+        column_labels: (0..column_count).map(|c| label(gray(c), column_variables.len())).collect(),
+        cells: (0..row_count)
+            .map(|r| {
+                let row = spread(gray(r), row_variables.len(), 0);
+                (0..column_count)
+                    .map(|c| results[row | spread(gray(c), column_variables.len(), row_variables.len())])
+                    .collect()
+            })
+            .collect(),
+        row_variables: row_variables.to_vec(),
+        column_variables: column_variables.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+    use crate::eval::classify::Classification;
+    use crate::eval::reduction::reduce_expression;
+    use crate::eval::truth_table::generate_truth_table;
+
+    fn report_str(input: &str) -> Report {
+        report(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parts_match_the_separate_analyses() {
+        for input in ["(a and b) or (not a and c)", "a xor b", "a or not a", "atleast(2; a, b, c, d)"] {
+            let expr = Parser::new(input).parse().unwrap();
+            let report = report(&expr).unwrap();
+            assert_eq!(report.table.rows, generate_truth_table(&expr).unwrap().rows, "{}", input);
+            assert_eq!(report.reduction.reduced, reduce_expression(&expr).unwrap().reduced, "{}", input);
+        }
+        assert_eq!(report_str("a or not a").classification.classification, Classification::Tautology);
+    }
+
+    #[test]
+    fn test_properties() {
+        let majority = report_str("atleast(2; a, b, c)").properties;
+        assert!(majority.monotone && majority.self_dual && majority.symmetric && majority.balanced);
+        assert!(!majority.affine);
+        assert_eq!(majority.degree, 2);
+
+        let parity = report_str("a xor b xor c").properties;
+        assert!(parity.affine && parity.symmetric && !parity.monotone);
+        assert_eq!(parity.degree, 1);
+
+        let implication = report_str("a -> b").properties;
+        assert!(!implication.symmetric && !implication.balanced);
+        assert_eq!(implication.true_rows, 3);
+        assert_eq!(report_str("a and (b or not b)").properties.essential, ["a"]);
+    }
+
+    #[test]
+    fn test_karnaugh_map() {
+        // Rows a, columns bc in the order 00 01 11 10
+        let map = report_str("a and c or b and c").karnaugh_map.unwrap();
+        assert_eq!(map.row_variables, ["a"]);
+        assert_eq!(map.column_variables, ["b", "c"]);
+        assert_eq!(map.column_labels, ["00", "01", "11", "10"]);
+        assert_eq!(map.cells, [vec![false, false, true, false], vec![false, true, true, false]]);
+        assert!(report_str("true").karnaugh_map.is_none());
+        assert!(report_str("a and b and c and d and e").karnaugh_map.is_none());
+    }
+}
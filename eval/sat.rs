@@ -0,0 +1,71 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::{assignment_from_index, evaluate_expression};
+use crate::eval::options;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// Whether an expression can be true, and an assignment that makes it so
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Satisfiability {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub satisfiable: bool,
+    /// The first satisfying row in truth-table order; `None` when unsatisfiable
+    pub assignment: Option<HashMap<String, bool>>,
+    /// Rows evaluated before the search stopped
+    pub rows_checked: usize,
+    pub total: usize,
+}
+
+/// Evaluate the rows of `expr` in truth-table order, stopping at the first true one
+pub fn check_satisfiability(expr: &Expr) -> Result<Satisfiability, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let total = 1usize << variables.len();
+    let mut found = None;
+    for index in 0..total {
+        options::check_row(index)?;
+        let assignment = assignment_from_index(&variables, index);
+        if evaluate_expression(expr, &assignment) {
+            found = Some((index, assignment));
+            break;
+        }
+    }
+
+    Ok(Satisfiability {
+        expression: expr.clone(),
+        satisfiable: found.is_some(),
+        rows_checked: found.as_ref().map_or(total, |(index, _)| index + 1),
+        assignment: found.map(|(_, assignment)| assignment),
+        variables,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn sat_str(input: &str) -> Satisfiability {
+        check_satisfiability(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_stops_at_first_satisfying_row() {
+        let report = sat_str("a and not b");
+        assert!(report.satisfiable);
+        assert_eq!(report.assignment, Some(HashMap::from([("a".to_string(), true), ("b".to_string(), false)])));
+        assert_eq!((report.rows_checked, report.total), (2, 4));
+    }
+
+    #[test]
+    fn test_unsatisfiable() {
+        let report = sat_str("(a or b) and not a and not b");
+        assert!(!report.satisfiable);
+        assert_eq!(report.assignment, None);
+        assert_eq!(report.rows_checked, 4);
+        assert!(sat_str("true").satisfiable);
+        assert!(!sat_str("false").satisfiable);
+    }
+}
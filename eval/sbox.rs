@@ -0,0 +1,150 @@
+use crate::eval::EvaluationError;
+use crate::eval::spectrum::walsh_transform;
+use crate::eval::anf::moebius_transform;
+use crate::eval::options;
+use crate::config::MAX_SBOX_BITS;
+use serde::{Serialize, Deserialize};
+
+/// Properties of one component function `v·S(x)`, the xor of the output bits in `mask`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentFunction {
+    /// Output bits combined, bit 0 for the lowest output bit
+    pub mask: usize,
+    /// Degree of the algebraic normal form
+    pub degree: usize,
+    pub nonlinearity: usize,
+    pub balanced: bool,
+}
+
+/// Analysis of a lookup table as a vectorial Boolean function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SboxReport {
+    pub input_bits: usize,
+    pub output_bits: usize,
+    pub table: Vec<usize>,
+    /// The coordinate functions, one per output bit
+    pub coordinates: Vec<ComponentFunction>,
+    /// Smallest nonlinearity over every nonzero component function
+    pub nonlinearity: usize,
+    /// Largest degree over the coordinates
+    pub degree: usize,
+    /// Smallest degree over every nonzero component function
+    pub min_degree: usize,
+    /// Most inputs mapping any input difference to the same output difference
+    pub differential_uniformity: usize,
+    /// Largest Walsh coefficient magnitude over the nonzero component functions
+    pub linearity: usize,
+    /// Every nonzero component function is balanced (for n → n S-boxes, a permutation)
+    pub balanced: bool,
+    pub fixed_points: Vec<usize>,
+}
+
+/// Analyze a lookup table of `2^n` entries of `output_bits` bits each.
+///
+/// Input `x` is the row index, its lowest bit first, as in a truth table.
+pub fn analyze_sbox(table: &[usize], output_bits: usize) -> Result<SboxReport, EvaluationError> {
+    if !table.len().is_power_of_two() {
+        return Err(EvaluationError::UnsupportedOperation {
+            operation: format!("a lookup table of {} entries; S-boxes have a power of two", table.len()),
+        });
+    }
+    let input_bits = table.len().trailing_zeros() as usize;
+    let widest = input_bits.max(output_bits);
+    if widest > MAX_SBOX_BITS {
+        return Err(EvaluationError::TooManyVariables { count: widest, max: MAX_SBOX_BITS });
+    }
+    if let Some(&value) = table.iter().find(|&&value| value >> output_bits != 0) {
+        return Err(EvaluationError::UnsupportedOperation {
+            operation: format!("S-box output {:#x} is wider than {} bits", value, output_bits),
+        });
+    }
+
+    let mut components = Vec::new();
+    let mut linearity = 0;
+    for mask in 1..1usize << output_bits {
+        options::check_deadline()?;
+        let results: Vec<bool> = table.iter().map(|&y| (y & mask).count_ones() % 2 == 1).collect();
+        let spectrum = walsh_transform(&results);
+        let max_magnitude = spectrum.iter().map(|w| w.unsigned_abs() as usize).max().unwrap_or(0);
+        linearity = linearity.max(max_magnitude);
+        components.push(ComponentFunction {
+            mask,
+            degree: algebraic_degree(&results),
+            nonlinearity: (table.len() - max_magnitude) / 2,
+            balanced: spectrum[0] == 0,
+        });
+    }
+
+    let coordinates: Vec<ComponentFunction> = components.iter().filter(|c| c.mask.is_power_of_two()).cloned().collect();
+    Ok(SboxReport {
+        input_bits,
+        output_bits,
+        table: table.to_vec(),
+        nonlinearity: components.iter().map(|c| c.nonlinearity).min().unwrap_or(0),
+        degree: coordinates.iter().map(|c| c.degree).max().unwrap_or(0),
+        min_degree: components.iter().map(|c| c.degree).min().unwrap_or(0),
+        differential_uniformity: differential_uniformity(table, output_bits)?,
+        linearity,
+        balanced: components.iter().all(|c| c.balanced),
+        fixed_points: (0..table.len()).filter(|&x| table[x] == x).collect(),
+        coordinates,
+    })
+}
+
+/// Degree of the algebraic normal form of a truth table
+fn algebraic_degree(results: &[bool]) -> usize {
+    let anf = moebius_transform(results);
+    (0..anf.len()).filter(|&monomial| anf[monomial]).map(|monomial| monomial.count_ones() as usize).max().unwrap_or(0)
+}
+
+/// Largest entry of the difference distribution table outside the trivial `0 → 0`
+fn differential_uniformity(table: &[usize], output_bits: usize) -> Result<usize, EvaluationError> {
+    let mut uniformity = 0;
+    let mut counts = vec![0usize; 1 << output_bits];
+    for dx in 1..table.len() {
+        options::check_deadline()?;
+        counts.fill(0);
+        for x in 0..table.len() {
+            counts[table[x] ^ table[x ^ dx]] += 1;
+        }
+        uniformity = uniformity.max(counts.iter().copied().max().unwrap_or(0));
+    }
+    Ok(uniformity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The PRESENT cipher's S-box
+    const PRESENT: [usize; 16] = [0xc, 0x5, 0x6, 0xb, 0x9, 0x0, 0xa, 0xd, 0x3, 0xe, 0xf, 0x8, 0x4, 0x7, 0x1, 0x2];
+
+    #[test]
+    fn test_present_sbox_is_optimal() {
+        let report = analyze_sbox(&PRESENT, 4).unwrap();
+        assert_eq!(report.input_bits, 4);
+        assert_eq!(report.coordinates.len(), 4);
+        assert_eq!(report.nonlinearity, 4);
+        assert_eq!(report.differential_uniformity, 4);
+        assert_eq!(report.linearity, 8);
+        assert_eq!(report.degree, 3);
+        assert!(report.balanced);
+        assert!(report.fixed_points.is_empty());
+    }
+
+    #[test]
+    fn test_affine_sbox_is_linear() {
+        // x ↦ x ⊕ 5 on three bits
+        let table: Vec<usize> = (0..8).map(|x| x ^ 5).collect();
+        let report = analyze_sbox(&table, 3).unwrap();
+        assert_eq!(report.nonlinearity, 0);
+        assert_eq!(report.degree, 1);
+        assert_eq!(report.differential_uniformity, 8);
+    }
+
+    #[test]
+    fn test_invalid_tables() {
+        assert!(analyze_sbox(&[0, 1, 2], 2).is_err());
+        assert!(analyze_sbox(&[0, 4], 2).is_err());
+    }
+}
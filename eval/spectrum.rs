@@ -0,0 +1,131 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::evaluate_results;
+use serde::{Serialize, Deserialize};
+
+/// One Walsh–Hadamard coefficient, the correlation with the xor of `mask`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalshCoefficient {
+    /// Variables of the linear function, empty for the constant
+    pub mask: Vec<String>,
+    /// Rows where the function agrees with the linear function minus rows where it doesn't
+    pub value: i64,
+}
+
+/// Walsh–Hadamard spectrum of a function and the properties read off it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpectrumReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    /// Coefficients in truth-table order of their masks
+    pub coefficients: Vec<WalshCoefficient>,
+    /// Distance to the nearest affine function
+    pub nonlinearity: usize,
+    /// True on exactly half of the rows
+    pub balanced: bool,
+    /// Largest `m` such that every coefficient with 1 to `m` mask variables is zero
+    pub correlation_immunity: usize,
+    /// `correlation_immunity` when the function is also balanced
+    pub resiliency: Option<usize>,
+    /// Every coefficient has magnitude 2^(n/2), the maximum possible nonlinearity
+    pub bent: bool,
+}
+
+/// Compute the spectrum with the fast Walsh–Hadamard transform, O(n·2^n)
+pub fn walsh_spectrum(expr: &Expr) -> Result<SpectrumReport, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let n = variables.len();
+    let rows = 1usize << n;
+
+    let results = evaluate_results(expr, &variables)?;
+    let spectrum = walsh_transform(&results);
+
+    let max_magnitude = spectrum.iter().map(|w| w.unsigned_abs() as usize).max().unwrap_or(0);
+    let nonlinearity = (rows - max_magnitude) / 2;
+    let balanced = spectrum[0] == 0;
+    let correlation_immunity = (1..=n)
+        .take_while(|&m| (0..rows).filter(|w| w.count_ones() as usize == m).all(|w| spectrum[w] == 0))
+        .last()
+        .unwrap_or(0);
+    let bent = n % 2 == 0 && spectrum.iter().all(|w| w.unsigned_abs() == 1 << (n / 2));
+
+    let names = variables.to_vec();
+    let coefficients = spectrum
+        .iter()
+        .enumerate()
+        .map(|(w, &value)| WalshCoefficient {
+            mask: names.iter().enumerate().filter(|(i, _)| (w >> i) & 1 == 1).map(|(_, name)| name.clone()).collect(),
+            value,
+        })
+        .collect();
+
+    Ok(SpectrumReport {
+        expression: expr.clone(),
+        variables,
+        coefficients,
+        nonlinearity,
+        balanced,
+        correlation_immunity,
+        resiliency: balanced.then_some(correlation_immunity),
+        bent,
+    })
+}
+
+/// Walsh–Hadamard transform of a truth table in row order: coefficient `w` is
+/// the sum over rows `x` of `(-1)^(f(x) ⊕ w·x)`
+pub(crate) fn walsh_transform(results: &[bool]) -> Vec<i64> {
+    let rows = results.len();
+    let mut spectrum: Vec<i64> = results.iter().map(|&r| if r { -1 } else { 1 }).collect();
+    let mut half = 1;
+    while half < rows {
+        for block in (0..rows).step_by(2 * half) {
+            for i in block..block + half {
+                let (a, b) = (spectrum[i], spectrum[i + half]);
+                spectrum[i] = a + b;
+                spectrum[i + half] = a - b;
+            }
+        }
+        half *= 2;
+    }
+    spectrum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn spectrum_str(input: &str) -> SpectrumReport {
+        walsh_spectrum(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_linear_function_has_one_peak() {
+        let report = spectrum_str("a xor b xor c");
+        let peaks: Vec<&WalshCoefficient> = report.coefficients.iter().filter(|c| c.value != 0).collect();
+        assert_eq!(peaks.len(), 1);
+        assert_eq!(peaks[0].mask, ["a", "b", "c"]);
+        assert_eq!(peaks[0].value, 8);
+        assert_eq!(report.nonlinearity, 0);
+        // Balanced and uncorrelated with any two inputs
+        assert_eq!(report.resiliency, Some(2));
+    }
+
+    #[test]
+    fn test_bent_function() {
+        let report = spectrum_str("(a and b) xor (c and d)");
+        assert!(report.bent);
+        assert!(!report.balanced);
+        assert_eq!(report.nonlinearity, 6);
+    }
+
+    #[test]
+    fn test_and_is_neither_balanced_nor_immune() {
+        let report = spectrum_str("a and b");
+        assert_eq!(report.coefficients.iter().map(|c| c.value).collect::<Vec<_>>(), [2, 2, 2, -2]);
+        assert_eq!(report.nonlinearity, 1);
+        assert_eq!(report.correlation_immunity, 0);
+        assert_eq!(report.resiliency, None);
+        assert!(report.bent);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::{assignment_from_index, evaluate_expression};
+use serde::{Serialize, Deserialize};
+
+/// Ordering for the subexpression listing
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubexpressionOrder {
+    /// Order of first appearance, innermost first (default)
+    #[default]
+    Appearance,
+    /// Largest subexpressions first
+    Size,
+    /// Most frequently repeated subexpressions first
+    Count,
+}
+
+/// A distinct subexpression and how it behaves over the whole expression's inputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subexpression {
+    pub expr: Expr,
+    /// Number of nodes in the subexpression
+    pub size: usize,
+    /// How many times the subexpression occurs in the expression
+    pub occurrences: usize,
+    /// Number of assignments for which the subexpression is true
+    pub true_count: usize,
+    /// Fraction of assignments for which the subexpression is true
+    pub density: f64,
+}
+
+/// Every distinct subexpression of an expression
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubexpressionReport {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub subexpressions: Vec<Subexpression>,
+}
+
+/// List the distinct subexpressions of `expr` with occurrence counts and truth densities.
+///
+/// Subexpressions are distinct when they differ structurally; densities are
+/// computed over all assignments of the full expression's variables.
+pub fn analyze_subexpressions(expr: &Expr, order: SubexpressionOrder) -> Result<SubexpressionReport, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    
+    let mut distinct: Vec<(Expr, usize)> = Vec::new();
+    collect_subexpressions(expr, &mut distinct);
+    
+    let num_combinations = 1usize << variables.len();
+    let mut true_counts = vec![0; distinct.len()];
+    for i in 0..num_combinations {
+        let assignment = assignment_from_index(&variables, i);
+        for (count, (sub, _)) in true_counts.iter_mut().zip(&distinct) {
+            if evaluate_expression(sub, &assignment) {
+                *count += 1;
+            }
+        }
+    }
+    
+    let mut subexpressions: Vec<Subexpression> = distinct
+        .into_iter()
+        .zip(true_counts)
+        .map(|((expr, occurrences), true_count)| Subexpression {
+            size: expr.node_count(),
+            expr,
+            occurrences,
+            true_count,
+            density: true_count as f64 / num_combinations as f64,
+        })
+        .collect();
+    
+    match order {
+        SubexpressionOrder::Appearance => {}
+        SubexpressionOrder::Size => subexpressions.sort_by_key(|sub| std::cmp::Reverse(sub.size)),
+        SubexpressionOrder::Count => subexpressions.sort_by_key(|sub| std::cmp::Reverse(sub.occurrences)),
+    }
+    
+    Ok(SubexpressionReport {
+        expression: expr.clone(),
+        variables,
+        subexpressions,
+    })
+}
+
+/// Post-order walk recording each distinct subexpression with its occurrence count
+fn collect_subexpressions(expr: &Expr, distinct: &mut Vec<(Expr, usize)>) {
+    match expr {
+        Expr::Identifier(_) | Expr::True | Expr::False => {}
+        Expr::Not(inner) => collect_subexpressions(inner, distinct),
+        Expr::And(left, right)
+        | Expr::Or(left, right)
+        | Expr::Nand(left, right)
+        | Expr::Nor(left, right)
+        | Expr::Xor(left, right)
+        | Expr::Implication(left, right)
+        | Expr::Iff(left, right) => {
+            collect_subexpressions(left, distinct);
+            collect_subexpressions(right, distinct);
+        }
+        Expr::Ite(cond, then, otherwise) => {
+            collect_subexpressions(cond, distinct);
+            collect_subexpressions(then, distinct);
+            collect_subexpressions(otherwise, distinct);
+        }
+        Expr::Cardinality(_, _, operands) | Expr::Custom(_, operands) => {
+            for operand in operands {
+                collect_subexpressions(operand, distinct);
+            }
+        }
+        Expr::Quantified(_, _, body) => collect_subexpressions(body, distinct),
+    }
+    
+    match distinct.iter_mut().find(|(seen, _)| seen == expr) {
+        Some((_, count)) => *count += 1,
+        None => distinct.push((expr.clone(), 1)),
+    }
+}
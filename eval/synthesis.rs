@@ -0,0 +1,470 @@
+use crate::source::{Expr, BinaryOp, Basis, Cardinality, Operator, Quantifier, ExprFolder};
+use crate::source::operator::resynthesize;
+use crate::source::parser::substitute;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::reduction::reduce_expression;
+use crate::eval::truth_table::evaluate_results;
+use crate::eval::options;
+use crate::config::{MAX_SYNTHESIS_VARIABLES, MAX_SYNTHESIS_STEPS, MAX_OPERATOR_ARITY};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+
+/// The smallest expression found for a function over a basis
+#[derive(Debug, Clone, Serialize)]
+pub struct Synthesis {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub basis: Basis,
+    pub max_size: usize,
+    /// An equivalent expression over the basis of at most `max_size` nodes, if one was found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Expr>,
+    /// Node count of `result`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    /// Whether `result` is proven to be the smallest, or its absence proves
+    /// there is none within `max_size`
+    pub exact: bool,
+}
+
+/// How a function was first reached, by the truth tables of its operands
+enum Recipe {
+    Leaf(Expr),
+    Not(u64),
+    Binary(BinaryOp, u64, u64),
+}
+
+/// Search for the smallest expression over `basis` with the same truth table
+/// as `expr`, by node count, up to `max_size` nodes.
+///
+/// Functions are enumerated by increasing size, each kept only at the size it
+/// is first reached: an expression built from smallest operands is never larger
+/// than one built from others, so the first hit is a smallest expression.
+/// Truth tables are bit masks, row `i` in bit `i`.
+pub fn synthesize_exact(expr: &Expr, basis: &Basis, max_size: usize) -> Result<Synthesis, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    if variables.len() > MAX_SYNTHESIS_VARIABLES {
+        return Err(EvaluationError::TooManyVariables { count: variables.len(), max: MAX_SYNTHESIS_VARIABLES });
+    }
+    let rows = 1usize << variables.len();
+    let mask = if rows == 64 { u64::MAX } else { (1u64 << rows) - 1 };
+    let target = evaluate_results(expr, &variables)?
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, result)| result)
+        .fold(0u64, |table, (i, _)| table | (1 << i));
+
+    let mut recipes: HashMap<u64, Recipe> = HashMap::new();
+    // by_size[s] holds the functions whose smallest expression has s nodes
+    let mut by_size: Vec<Vec<u64>> = vec![Vec::new(), Vec::new()];
+    let leaves = [(0, Expr::False), (mask, Expr::True)].into_iter().chain(variables.iter().enumerate().map(|(bit, name)| {
+        let table = (0..rows).filter(|i| (i >> bit) & 1 == 1).fold(0u64, |table, i| table | (1 << i));
+        (table, Expr::Identifier(name.to_string()))
+    }));
+    for (table, leaf) in leaves {
+        if let Entry::Vacant(entry) = recipes.entry(table) {
+            entry.insert(Recipe::Leaf(leaf));
+            by_size[1].push(table);
+        }
+    }
+
+    let mut steps = 0usize;
+    let mut size = 1;
+    while !recipes.contains_key(&target) && size < max_size {
+        size += 1;
+        let mut reached = Vec::new();
+        let mut reach = |table: u64, recipe: Recipe, reached: &mut Vec<u64>| {
+            if let Entry::Vacant(entry) = recipes.entry(table & mask) {
+                entry.insert(recipe);
+                reached.push(table & mask);
+            }
+        };
+        if basis.allows_not() {
+            for &g in &by_size[size - 1] {
+                reach(!g, Recipe::Not(g), &mut reached);
+            }
+        }
+        for &op in basis.binary() {
+            let commutative = op.is_commutative();
+            for left_size in 1..size - 1 {
+                let right_size = size - 1 - left_size;
+                if commutative && left_size > right_size {
+                    break;
+                }
+                for (i, &g) in by_size[left_size].iter().enumerate() {
+                    let start = if commutative && left_size == right_size { i } else { 0 };
+                    for &h in &by_size[right_size][start..] {
+                        steps += 1;
+                        options::check_row(steps)?;
+                        if steps > MAX_SYNTHESIS_STEPS {
+                            return Err(EvaluationError::ExpressionTooComplex {
+                                reason: format!("no expression of fewer than {} nodes exists over {}, and the search budget ran out at that size", size, basis),
+                            });
+                        }
+                        reach(apply(op, g, h), Recipe::Binary(op, g, h), &mut reached);
+                    }
+                }
+            }
+        }
+        by_size.push(reached);
+    }
+
+    let result = recipes.contains_key(&target).then(|| rebuild(&recipes, target));
+    Ok(Synthesis {
+        expression: expr.clone(),
+        variables,
+        basis: basis.clone(),
+        max_size,
+        size: result.as_ref().map(Expr::node_count),
+        result,
+        exact: true,
+    })
+}
+
+/// The reduced sum of products, rewritten into `basis`, when it has at most
+/// `max_size` nodes; it is not proven the smallest
+pub fn synthesize(expr: &Expr, basis: &Basis, max_size: usize) -> Result<Synthesis, EvaluationError> {
+    let reduced = rewrite_in_basis(&reduce_expression(expr)?.reduced, basis)?;
+    let result = (reduced.node_count() <= max_size).then_some(reduced);
+    Ok(Synthesis {
+        expression: expr.clone(),
+        variables: Variables::from_expr(expr)?,
+        basis: basis.clone(),
+        max_size,
+        size: result.as_ref().map(Expr::node_count),
+        result,
+        exact: false,
+    })
+}
+
+/// Largest replacement for a single operator that [`rewrite_in_basis`] searches for
+const MAX_GADGET_SIZE: usize = 15;
+
+/// An equivalent expression that uses only the operators in `basis`.
+///
+/// Each node outside the basis is replaced by the smallest expression over
+/// the basis for its operator, found with [`synthesize_exact`]; cardinality
+/// constraints, custom operators and quantifiers are first expanded into
+/// `∧`, `∨`, `¬` and `? :`. Nodes already in the basis are kept, and double
+/// negations the replacements leave behind are removed.
+pub fn rewrite_in_basis(expr: &Expr, basis: &Basis) -> Result<Expr, EvaluationError> {
+    if basis.permits(expr) {
+        return Ok(expr.clone());
+    }
+    let mut rewriter = BasisRewriter { basis, gadgets: HashMap::new(), error: None };
+    let rewritten = rewriter.fold_expr(expr);
+    // Rebuilding the result cancels negations that met when replacements were nested
+    let rewritten = rewriter.fold_expr(&rewritten);
+    match rewriter.error {
+        Some(error) => Err(error),
+        None => Ok(rewritten),
+    }
+}
+
+/// An expression rewritten to use only the operators of a basis
+#[derive(Debug, Clone, Serialize)]
+pub struct Conversion {
+    pub expression: Expr,
+    pub basis: Basis,
+    pub converted: Expr,
+    /// Operator nodes of the converted expression, one per occurrence
+    pub gates: usize,
+    /// Operator nodes when identical subexpressions share one gate, as in a circuit
+    pub distinct_gates: usize,
+    /// Operator nodes of the original expression
+    pub original_gates: usize,
+}
+
+/// Rewrite `expr` into `basis` with [`rewrite_in_basis`] and count its gates
+pub fn convert(expr: &Expr, basis: &Basis) -> Result<Conversion, EvaluationError> {
+    let converted = rewrite_in_basis(expr, basis)?;
+    let mut distinct = HashSet::new();
+    let gates = count_gates(&converted, &mut distinct);
+    Ok(Conversion {
+        expression: expr.clone(),
+        basis: basis.clone(),
+        gates,
+        distinct_gates: distinct.len(),
+        original_gates: count_gates(expr, &mut HashSet::new()),
+        converted,
+    })
+}
+
+/// Operator nodes of `expr`, recording each distinct one in `distinct`
+fn count_gates<'e>(expr: &'e Expr, distinct: &mut HashSet<&'e Expr>) -> usize {
+    let operands: Vec<&Expr> = match expr {
+        Expr::Identifier(_) | Expr::True | Expr::False => return 0,
+        Expr::Not(inner) | Expr::Quantified(_, _, inner) => vec![inner],
+        Expr::Ite(cond, then, otherwise) => vec![cond, then, otherwise],
+        Expr::Cardinality(_, _, operands) | Expr::Custom(_, operands) => operands.iter().collect(),
+        _ => {
+            let (_, left, right) = expr.as_binary().expect("every other node is binary");
+            vec![left, right]
+        }
+    };
+    distinct.insert(expr);
+    1 + operands.into_iter().map(|operand| count_gates(operand, distinct)).sum::<usize>()
+}
+
+/// An operator that may need replacing
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Gate {
+    Not,
+    Binary(BinaryOp),
+    Ite,
+}
+
+impl Gate {
+    /// The gate applied to the template parameters `x`, `y` and `z`
+    fn template(self) -> Expr {
+        let param = |name: &str| Expr::Identifier(name.to_string());
+        match self {
+            Gate::Not => Expr::Not(Box::new(param("x"))),
+            Gate::Binary(op) => op.build(param("x"), param("y")),
+            Gate::Ite => Expr::Ite(Box::new(param("x")), Box::new(param("y")), Box::new(param("z"))),
+        }
+    }
+}
+
+/// Rebuilds an expression bottom-up, replacing each operator outside the basis
+struct BasisRewriter<'a> {
+    basis: &'a Basis,
+    /// Replacement for each gate over the parameters `x`, `y` and `z`
+    gadgets: HashMap<Gate, Option<Expr>>,
+    /// The first gate the basis cannot express
+    error: Option<EvaluationError>,
+}
+
+impl BasisRewriter<'_> {
+    /// The smallest expression over the basis for `gate`, if there is one
+    fn gadget(&mut self, gate: Gate) -> Option<&Expr> {
+        let basis = self.basis;
+        self.gadgets
+            .entry(gate)
+            .or_insert_with(|| {
+                synthesize_exact(&gate.template(), basis, MAX_GADGET_SIZE).ok().and_then(|synthesis| synthesis.result)
+            })
+            .as_ref()
+    }
+
+    fn apply_gate(&mut self, gate: Gate, operands: Vec<Expr>) -> Expr {
+        match self.gadget(gate) {
+            Some(gadget) => {
+                let bindings: Vec<(String, Expr)> = ["x", "y", "z"].iter().map(|p| p.to_string()).zip(operands).collect();
+                let replaced = substitute(gadget, &bindings);
+                self.cancel_double_negation(replaced)
+            }
+            None => {
+                let symbol = match gate {
+                    Gate::Not => "¬",
+                    Gate::Binary(op) => op.symbol(),
+                    Gate::Ite => "? :",
+                };
+                self.error.get_or_insert(EvaluationError::UnsupportedOperation {
+                    operation: format!("writing {} with only {}", symbol, self.basis),
+                });
+                Expr::False
+            }
+        }
+    }
+
+    /// `e` for a node that negates the negation of `e`, otherwise the node
+    fn cancel_double_negation(&mut self, node: Expr) -> Expr {
+        let inverter = if self.basis.allows_not() { Gate::Not.template() } else {
+            match self.gadget(Gate::Not) {
+                Some(inverter) => inverter.clone(),
+                None => return node,
+            }
+        };
+        match negated(&inverter, &node).and_then(|inner| negated(&inverter, inner)) {
+            Some(operand) => operand.clone(),
+            None => node,
+        }
+    }
+}
+
+/// The operand of `expr` when it is `inverter` applied to something, where
+/// `inverter` negates its parameter `x`
+fn negated<'e>(inverter: &Expr, expr: &'e Expr) -> Option<&'e Expr> {
+    let mut operand = None;
+    matches_template(inverter, expr, &mut operand).then_some(operand).flatten()
+}
+
+/// Whether `expr` is `template` with one expression in place of every `x`
+fn matches_template<'e>(template: &Expr, expr: &'e Expr, operand: &mut Option<&'e Expr>) -> bool {
+    match (template, expr) {
+        (Expr::Identifier(name), _) if name == "x" => match operand {
+            Some(bound) => *bound == expr,
+            None => {
+                *operand = Some(expr);
+                true
+            }
+        },
+        (Expr::Not(t), Expr::Not(e)) => matches_template(t, e, operand),
+        _ => match (template.as_binary(), expr.as_binary()) {
+            (Some((top, tl, tr)), Some((op, l, r))) => {
+                top == op && matches_template(tl, l, operand) && matches_template(tr, r, operand)
+            }
+            _ => template == expr,
+        },
+    }
+}
+
+impl ExprFolder for BasisRewriter<'_> {
+    type Output = Expr;
+
+    fn fold_identifier(&mut self, name: &str) -> Expr {
+        Expr::Identifier(name.to_string())
+    }
+
+    fn fold_constant(&mut self, value: bool) -> Expr {
+        if value { Expr::True } else { Expr::False }
+    }
+
+    fn fold_not(&mut self, inner: Expr) -> Expr {
+        if self.basis.allows_not() {
+            self.cancel_double_negation(Expr::Not(Box::new(inner)))
+        } else {
+            self.apply_gate(Gate::Not, vec![inner])
+        }
+    }
+
+    fn fold_binary(&mut self, op: BinaryOp, left: Expr, right: Expr) -> Expr {
+        if self.basis.binary().contains(&op) {
+            self.cancel_double_negation(op.build(left, right))
+        } else {
+            self.apply_gate(Gate::Binary(op), vec![left, right])
+        }
+    }
+
+    fn fold_ite(&mut self, cond: Expr, then: Expr, otherwise: Expr) -> Expr {
+        self.apply_gate(Gate::Ite, vec![cond, then, otherwise])
+    }
+
+    // Operands already in the basis fold to themselves, so the expansion can be folded whole
+    fn fold_cardinality(&mut self, kind: Cardinality, k: usize, operands: Vec<Expr>) -> Expr {
+        if operands.len() > MAX_OPERATOR_ARITY {
+            self.error.get_or_insert(EvaluationError::ExpressionTooComplex {
+                reason: format!("expanding a cardinality constraint over more than {} operands", MAX_OPERATOR_ARITY),
+            });
+            return Expr::False;
+        }
+        let results: Vec<bool> = (0..1usize << operands.len()).map(|row| kind.holds(k, row.count_ones() as usize)).collect();
+        self.fold_expr(&resynthesize(&operands, &results))
+    }
+
+    fn fold_custom(&mut self, operator: &Operator, operands: Vec<Expr>) -> Expr {
+        self.fold_expr(&operator.expand(&operands))
+    }
+
+    fn fold_quantified(&mut self, quantifier: Quantifier, var: &str, body: &Expr) -> Expr {
+        let quantified = Expr::Quantified(quantifier, var.to_string(), Box::new(body.clone()));
+        self.fold_expr(&quantified.eliminate_quantifiers())
+    }
+}
+
+/// The truth table of `op` applied to two truth tables, with bits past the
+/// last row left for the caller to mask off
+fn apply(op: BinaryOp, g: u64, h: u64) -> u64 {
+    match op {
+        BinaryOp::And => g & h,
+        BinaryOp::Or => g | h,
+        BinaryOp::Nand => !(g & h),
+        BinaryOp::Nor => !(g | h),
+        BinaryOp::Xor => g ^ h,
+        BinaryOp::Implication => !g | h,
+        BinaryOp::Iff => !(g ^ h),
+    }
+}
+
+fn rebuild(recipes: &HashMap<u64, Recipe>, table: u64) -> Expr {
+    match &recipes[&table] {
+        Recipe::Leaf(leaf) => leaf.clone(),
+        Recipe::Not(inner) => Expr::Not(Box::new(rebuild(recipes, *inner))),
+        Recipe::Binary(op, left, right) => op.build(rebuild(recipes, *left), rebuild(recipes, *right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr;
+    use crate::eval::{Evaluator, EvalOptions};
+    use std::time::{Duration, Instant};
+
+    fn exact(expr: &Expr, basis: &str, max_size: usize) -> Synthesis {
+        synthesize_exact(expr, &basis.parse().unwrap(), max_size).unwrap()
+    }
+
+    #[test]
+    fn test_time_budget_stops_the_search() {
+        let parity = expr!(a xor b xor c xor d);
+        let options = EvalOptions { time_budget: Some(Duration::from_millis(10)), ..EvalOptions::default() };
+        let started = Instant::now();
+        let result = Evaluator::with_options(&options, || synthesize_exact(&parity, &"nand".parse().unwrap(), 25));
+        assert!(matches!(result, Err(EvaluationError::TimeBudgetExceeded { .. })), "{:?}", result.map(|s| s.size));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_smallest_beats_sum_of_products() {
+        // The minimal SOP of majority has 11 nodes; factoring saves two
+        let majority = expr!((a and b) or (a and c) or (b and c));
+        let synthesis = exact(&majority, "and,or,not", 15);
+        assert_eq!(synthesis.size, Some(9));
+        let result = synthesis.result.unwrap();
+        assert!(Evaluator::check_equivalence(&majority, &result).unwrap().equivalent);
+        assert!(Basis::default().permits(&result));
+    }
+
+    #[test]
+    fn test_other_bases() {
+        assert_eq!(exact(&expr!(a xor b), "xor", 5).result, Some(expr!(a xor b)));
+        // Constants are always available: not a = true nand a
+        assert_eq!(exact(&expr!(not a), "nand", 5).result, Some(expr!(true nand a)));
+        assert_eq!(exact(&expr!(a and b), "nand", 7).size, Some(5));
+        assert_eq!(exact(&expr!(a or not a), "and", 5).result, Some(Expr::True));
+    }
+
+    #[test]
+    fn test_rewrite_in_basis() {
+        let expr = expr!(a and not b or (c ? d : atleast(2; a, b, d)) or (e xor a));
+        for basis in ["nand", "nor", "xor,and", "implies,not", "and,or,not"] {
+            let basis: Basis = basis.parse().unwrap();
+            let rewritten = rewrite_in_basis(&expr, &basis).unwrap();
+            assert!(basis.permits(&rewritten), "{} is not over {}", rewritten, basis);
+            assert!(Evaluator::check_equivalence(&expr, &rewritten).unwrap().equivalent);
+        }
+        assert_eq!(rewrite_in_basis(&expr!(a nand b), &"nand".parse().unwrap()).unwrap(), expr!(a nand b));
+        // Or over and in nand would negate the conjunction twice
+        let nand = rewrite_in_basis(&expr!(a and b or c), &"nand".parse().unwrap()).unwrap();
+        assert_eq!(nand, expr!((a nand b) nand (true nand c)));
+        assert!(rewrite_in_basis(&expr!(not a), &"and,or".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_convert_counts_gates() {
+        let expr = expr!(a xor b);
+        let conversion = convert(&expr, &"nand".parse().unwrap()).unwrap();
+        assert!(Evaluator::check_equivalence(&expr, &conversion.converted).unwrap().equivalent);
+        assert_eq!(conversion.original_gates, 1);
+        assert_eq!(conversion.gates, 5);
+
+        // A repeated subexpression is one gate in a circuit
+        let conversion = convert(&expr!((a nor b) or ((a nor b) and c)), &"nor".parse().unwrap()).unwrap();
+        assert_eq!((conversion.gates, conversion.distinct_gates), (7, 6));
+
+        let conversion = convert(&expr!(not (a or b)), &"nor".parse().unwrap()).unwrap();
+        assert_eq!(conversion.converted, expr!(a nor b));
+        assert_eq!((conversion.gates, conversion.original_gates), (1, 2));
+    }
+
+    #[test]
+    fn test_nothing_within_max_size() {
+        let synthesis = exact(&expr!(a xor b), "and,or,not", 6);
+        assert_eq!(synthesis.result, None);
+        assert!(synthesis.exact);
+        assert_eq!(exact(&expr!(a xor b), "and,or,not", 10).size, Some(8));
+    }
+}
@@ -0,0 +1,103 @@
+use crate::eval::Variables;
+use crate::eval::truth_table::TruthTable;
+use crate::eval::equivalence::EquivalenceDifference;
+use std::collections::{HashMap, HashSet};
+use serde::{Serialize, Deserialize};
+
+/// Structural and semantic differences between two truth tables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDiff {
+    /// True when both tables have the same variables, rows, and results
+    pub identical: bool,
+    pub left_variables: Variables,
+    pub right_variables: Variables,
+    pub left_row_count: usize,
+    pub right_row_count: usize,
+    /// Assignments (over the shared variables) present only in the left table
+    pub left_only_rows: Vec<HashMap<String, bool>>,
+    /// Assignments (over the shared variables) present only in the right table
+    pub right_only_rows: Vec<HashMap<String, bool>>,
+    /// Matching rows whose results disagree
+    pub differences: Vec<EquivalenceDifference>,
+}
+
+impl TableDiff {
+    /// Variables that appear in the left table but not the right one
+    pub fn left_only_variables(&self) -> Variables {
+        self.left_variables.difference(&self.right_variables)
+    }
+    
+    /// Variables that appear in the right table but not the left one
+    pub fn right_only_variables(&self) -> Variables {
+        self.right_variables.difference(&self.left_variables)
+    }
+}
+
+/// Compare two truth tables, matching rows on their shared variables.
+///
+/// Column order is irrelevant. When the variable sets differ, every pair of rows
+/// that agrees on the shared variables is compared, so a difference means the
+/// tables disagree for some completion of the extra variables.
+pub fn diff_truth_tables(left: &TruthTable, right: &TruthTable) -> TableDiff {
+    let shared = left.variables.intersection(&right.variables);
+    let key = |assignments: &HashMap<String, bool>| -> Vec<bool> {
+        shared.iter().map(|var| assignments.get(var).copied().unwrap_or(false)).collect()
+    };
+    let project = |assignments: &HashMap<String, bool>| -> HashMap<String, bool> {
+        shared.iter().map(|var| (var.to_string(), assignments.get(var).copied().unwrap_or(false))).collect()
+    };
+    
+    let mut right_by_key: HashMap<Vec<bool>, Vec<usize>> = HashMap::new();
+    for (idx, row) in right.rows.iter().enumerate() {
+        right_by_key.entry(key(&row.assignments)).or_default().push(idx);
+    }
+    
+    let mut left_only_rows = Vec::new();
+    let mut differences = Vec::new();
+    let mut left_keys = HashSet::new();
+    
+    for row in &left.rows {
+        let row_key = key(&row.assignments);
+        match right_by_key.get(&row_key) {
+            None => left_only_rows.push(project(&row.assignments)),
+            Some(indices) => {
+                for &idx in indices {
+                    let other = &right.rows[idx];
+                    if other.result != row.result {
+                        let mut assignment = other.assignments.clone();
+                        assignment.extend(row.assignments.iter().map(|(k, v)| (k.clone(), *v)));
+                        differences.push(EquivalenceDifference {
+                            assignment,
+                            left_value: row.result,
+                            right_value: other.result,
+                        });
+                    }
+                }
+            }
+        }
+        left_keys.insert(row_key);
+    }
+    
+    let right_only_rows: Vec<_> = right.rows
+        .iter()
+        .filter(|row| !left_keys.contains(&key(&row.assignments)))
+        .map(|row| project(&row.assignments))
+        .collect();
+    
+    let identical = left.variables == right.variables
+        && left.rows.len() == right.rows.len()
+        && left_only_rows.is_empty()
+        && right_only_rows.is_empty()
+        && differences.is_empty();
+    
+    TableDiff {
+        identical,
+        left_variables: left.variables.clone(),
+        right_variables: right.variables.clone(),
+        left_row_count: left.rows.len(),
+        right_row_count: right.rows.len(),
+        left_only_rows,
+        right_only_rows,
+        differences,
+    }
+}
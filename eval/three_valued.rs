@@ -0,0 +1,263 @@
+use crate::source::{Expr, Quantifier, Cardinality, Operator, BinaryOp, ExprFolder};
+use crate::eval::{Variables, EvaluationError};
+use crate::config::MAX_THREE_VALUED_VARIABLES;
+use crate::eval::options;
+use std::collections::HashMap;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+/// A truth value in Kleene's strong three-valued logic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ternary {
+    False,
+    True,
+    Unknown,
+}
+
+impl Ternary {
+    /// All values, in table order
+    pub const ALL: [Ternary; 3] = [Ternary::False, Ternary::True, Ternary::Unknown];
+
+    /// The boolean value, if known
+    pub fn known(self) -> Option<bool> {
+        match self {
+            Ternary::False => Some(false),
+            Ternary::True => Some(true),
+            Ternary::Unknown => None,
+        }
+    }
+
+    /// False if either side is false, unknown if either side is unknown
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Ternary::False, _) | (_, Ternary::False) => Ternary::False,
+            (Ternary::True, Ternary::True) => Ternary::True,
+            _ => Ternary::Unknown,
+        }
+    }
+
+    /// True if either side is true, unknown if either side is unknown
+    pub fn or(self, other: Self) -> Self {
+        !(!self).and(!other)
+    }
+
+    /// Unknown unless both sides are known
+    pub fn xor(self, other: Self) -> Self {
+        match (self.known(), other.known()) {
+            (Some(a), Some(b)) => Ternary::from(a ^ b),
+            _ => Ternary::Unknown,
+        }
+    }
+}
+
+impl std::ops::Not for Ternary {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Ternary::False => Ternary::True,
+            Ternary::True => Ternary::False,
+            Ternary::Unknown => Ternary::Unknown,
+        }
+    }
+}
+
+impl From<bool> for Ternary {
+    fn from(value: bool) -> Self {
+        if value { Ternary::True } else { Ternary::False }
+    }
+}
+
+impl fmt::Display for Ternary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ternary::False => write!(f, "F"),
+            Ternary::True => write!(f, "T"),
+            Ternary::Unknown => write!(f, "U"),
+        }
+    }
+}
+
+/// One row of a three-valued truth table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreeValuedRow {
+    pub assignments: HashMap<String, Ternary>,
+    pub result: Ternary,
+}
+
+/// A truth table over true, false and unknown inputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreeValuedTable {
+    pub variables: Variables,
+    pub rows: Vec<ThreeValuedRow>,
+}
+
+/// Evaluate an expression in Kleene logic. Variables without a value are unknown.
+pub fn evaluate_three_valued(expr: &Expr, assignments: &HashMap<String, Ternary>) -> Ternary {
+    KleeneEvaluation { assignments }.fold_expr(expr)
+}
+
+/// Evaluates in Kleene logic, where an operator's result is known once every
+/// way of settling its unknown operands gives the same answer
+struct KleeneEvaluation<'a> {
+    assignments: &'a HashMap<String, Ternary>,
+}
+
+impl ExprFolder for KleeneEvaluation<'_> {
+    type Output = Ternary;
+
+    fn fold_identifier(&mut self, name: &str) -> Ternary {
+        self.assignments.get(name).copied().unwrap_or(Ternary::Unknown)
+    }
+
+    fn fold_constant(&mut self, value: bool) -> Ternary {
+        Ternary::from(value)
+    }
+
+    fn fold_not(&mut self, inner: Ternary) -> Ternary {
+        !inner
+    }
+
+    fn fold_binary(&mut self, op: BinaryOp, left: Ternary, right: Ternary) -> Ternary {
+        match op {
+            BinaryOp::And => left.and(right),
+            BinaryOp::Or => left.or(right),
+            BinaryOp::Nand => !left.and(right),
+            BinaryOp::Nor => !left.or(right),
+            BinaryOp::Xor => left.xor(right),
+            BinaryOp::Implication => (!left).or(right),
+            BinaryOp::Iff => !left.xor(right),
+        }
+    }
+
+    // An unknown condition still decides the result when both branches agree
+    fn fold_ite(&mut self, cond: Ternary, then: Ternary, otherwise: Ternary) -> Ternary {
+        match cond {
+            Ternary::True => then,
+            Ternary::False => otherwise,
+            Ternary::Unknown if then == otherwise => then,
+            Ternary::Unknown => Ternary::Unknown,
+        }
+    }
+
+    fn fold_cardinality(&mut self, kind: Cardinality, k: usize, values: Vec<Ternary>) -> Ternary {
+        let known_true = values.iter().filter(|&&v| v == Ternary::True).count();
+        let unknown = values.iter().filter(|&&v| v == Ternary::Unknown).count();
+        let outcomes: Vec<bool> = (known_true..=known_true + unknown).map(|count| kind.holds(k, count)).collect();
+        if outcomes.iter().all(|&o| o == outcomes[0]) { Ternary::from(outcomes[0]) } else { Ternary::Unknown }
+    }
+
+    fn fold_custom(&mut self, operator: &Operator, values: Vec<Ternary>) -> Ternary {
+        let unknown: Vec<usize> = (0..values.len()).filter(|&i| values[i] == Ternary::Unknown).collect();
+        let outcomes: Vec<bool> = (0..1usize << unknown.len())
+            .map(|settled| {
+                let mut args: Vec<bool> = values.iter().map(|v| v.known().unwrap_or(false)).collect();
+                for (bit, &i) in unknown.iter().enumerate() {
+                    args[i] = (settled >> bit) & 1 == 1;
+                }
+                operator.apply(&args)
+            })
+            .collect();
+        if outcomes.iter().all(|&o| o == outcomes[0]) { Ternary::from(outcomes[0]) } else { Ternary::Unknown }
+    }
+
+    fn fold_quantified(&mut self, quantifier: Quantifier, var: &str, body: &Expr) -> Ternary {
+        let mut assignments = self.assignments.clone();
+        let mut case = |value: bool| {
+            assignments.insert(var.to_string(), Ternary::from(value));
+            evaluate_three_valued(body, &assignments)
+        };
+        let (low, high) = (case(false), case(true));
+        match quantifier {
+            Quantifier::ForAll => low.and(high),
+            Quantifier::Exists => low.or(high),
+        }
+    }
+}
+
+/// Generate every row over false, true and unknown, the first variable varying fastest
+pub fn generate_three_valued_table(expr: &Expr) -> Result<ThreeValuedTable, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    if variables.len() > MAX_THREE_VALUED_VARIABLES {
+        return Err(EvaluationError::TooManyVariables {
+            count: variables.len(),
+            max: MAX_THREE_VALUED_VARIABLES,
+        });
+    }
+
+    let count = 3usize.pow(variables.len() as u32);
+    options::check_table_size(count, variables.len())?;
+    let rows = (0..count)
+        .map(|index| {
+            options::check_row(index)?;
+            let assignments: HashMap<String, Ternary> = variables
+                .iter()
+                .enumerate()
+                .map(|(i, var)| (var.to_string(), Ternary::ALL[index / 3usize.pow(i as u32) % 3]))
+                .collect();
+            let result = evaluate_three_valued(expr, &assignments);
+            Ok(ThreeValuedRow { assignments, result })
+        })
+        .collect::<Result<_, EvaluationError>>()?;
+
+    Ok(ThreeValuedTable { variables, rows })
+}
+
+/// Evaluate an expression for each (possibly partial) assignment in turn
+pub fn evaluate_rows_three_valued(expr: &Expr, assignments: Vec<HashMap<String, Ternary>>) -> Result<ThreeValuedTable, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let rows = assignments
+        .into_iter()
+        .map(|mut assignments| {
+            for var in variables.iter() {
+                assignments.entry(var.to_string()).or_insert(Ternary::Unknown);
+            }
+            let result = evaluate_three_valued(expr, &assignments);
+            ThreeValuedRow { assignments, result }
+        })
+        .collect();
+    Ok(ThreeValuedTable { variables, rows })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn eval_str(input: &str, values: &[(&str, Ternary)]) -> Ternary {
+        let expr = Parser::new(input).parse().unwrap();
+        let assignments = values.iter().map(|(var, value)| (var.to_string(), *value)).collect();
+        evaluate_three_valued(&expr, &assignments)
+    }
+
+    #[test]
+    fn test_known_values_decide_despite_unknowns() {
+        assert_eq!(eval_str("a and b", &[("a", Ternary::False)]), Ternary::False);
+        assert_eq!(eval_str("a or b", &[("a", Ternary::True)]), Ternary::True);
+        assert_eq!(eval_str("a -> b", &[("b", Ternary::True)]), Ternary::True);
+        assert_eq!(eval_str("a and b", &[("a", Ternary::True)]), Ternary::Unknown);
+    }
+
+    #[test]
+    fn test_unknown_is_not_excluded_middle() {
+        // Kleene logic does not know that a or not a holds
+        assert_eq!(eval_str("a or not a", &[]), Ternary::Unknown);
+        assert_eq!(eval_str("a xor b", &[("a", Ternary::True)]), Ternary::Unknown);
+        // An unknown condition with agreeing branches is known
+        assert_eq!(eval_str("s ? a : b", &[("a", Ternary::True), ("b", Ternary::True)]), Ternary::True);
+        assert_eq!(eval_str("s ? a : b", &[("a", Ternary::True)]), Ternary::Unknown);
+        assert_eq!(eval_str("atleast(2; a, b, c)", &[("a", Ternary::True), ("b", Ternary::True)]), Ternary::True);
+        assert_eq!(eval_str("exactly(1; a, b, c)", &[("a", Ternary::True)]), Ternary::Unknown);
+    }
+
+    #[test]
+    fn test_table_has_three_rows_per_variable_value() {
+        let expr = Parser::new("a and b").parse().unwrap();
+        let table = generate_three_valued_table(&expr).unwrap();
+        assert_eq!(table.rows.len(), 9);
+        let unknown = table.rows.iter().filter(|row| row.result == Ternary::Unknown).count();
+        // (T,U), (U,T) and (U,U)
+        assert_eq!(unknown, 3);
+    }
+}
@@ -0,0 +1,361 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::evaluate_results;
+use crate::eval::options;
+use crate::config::MAX_THRESHOLD_VARIABLES;
+use serde::{Serialize, Deserialize};
+
+/// The integer weight of one variable in a threshold function
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableWeight {
+    pub variable: String,
+    pub weight: i64,
+}
+
+/// Whether an expression is a threshold function `w1·x1 + … + wn·xn ≥ T`,
+/// with true counting as 1 and false as 0
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdAnalysis {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub is_threshold: bool,
+    /// Weights in variable order, present when the expression is a threshold function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<Vec<VariableWeight>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<i64>,
+    /// Why the expression is not a threshold function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Tolerance for the floating point simplex
+const EPSILON: f64 = 1e-9;
+
+/// Decide whether `expr` is linearly separable and, if so, find small integer
+/// weights and a threshold for it.
+///
+/// A threshold function is unate, so each variable is first oriented to make
+/// the function monotone. Weights for the monotone function then come from a
+/// linear program over its minimal true and maximal false points, and are
+/// checked against every assignment before they are reported.
+pub fn analyze_threshold(expr: &Expr) -> Result<ThresholdAnalysis, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let n = variables.len();
+    let results = evaluate_results(expr, &variables)?;
+    let not_threshold = |reason: String| ThresholdAnalysis {
+        expression: expr.clone(),
+        variables: variables.clone(),
+        is_threshold: false,
+        weights: None,
+        threshold: None,
+        reason: Some(reason),
+    };
+
+    // Orient each variable: flipped when the function decreases in it
+    let mut flip = 0usize;
+    let mut relevant = Vec::new();
+    for (bit, name) in variables.iter().enumerate() {
+        options::check_deadline()?;
+        let mask = 1 << bit;
+        let (mut rises, mut falls) = (false, false);
+        for without in (0..results.len()).filter(|i| i & mask == 0) {
+            match (results[without], results[without | mask]) {
+                (false, true) => rises = true,
+                (true, false) => falls = true,
+                _ => {}
+            }
+        }
+        if rises && falls {
+            return Ok(not_threshold(format!("not unate: the result both rises and falls with `{}`", name)));
+        }
+        if falls {
+            flip |= mask;
+        }
+        if rises || falls {
+            relevant.push(bit);
+        }
+    }
+    if relevant.len() > MAX_THRESHOLD_VARIABLES {
+        return Err(EvaluationError::ExpressionTooComplex {
+            reason: format!("threshold analysis supports at most {} relevant variables, found {}", MAX_THRESHOLD_VARIABLES, relevant.len()),
+        });
+    }
+
+    // The monotone function g(y) = f(y ⊕ flip)
+    let monotone = |y: usize| results[y ^ flip];
+    let (weights, threshold) = match monotone_weights(&monotone, &relevant, n)? {
+        Some(solution) => solution,
+        None => return Ok(not_threshold("not linearly separable".to_string())),
+    };
+
+    // Undo the orientation: a flipped variable contributes w·(1 - x)
+    let mut signed = vec![0i64; n];
+    let mut threshold = threshold;
+    for (&bit, &weight) in relevant.iter().zip(&weights) {
+        if flip & (1 << bit) != 0 {
+            signed[bit] = -weight;
+            threshold -= weight;
+        } else {
+            signed[bit] = weight;
+        }
+    }
+
+    let holds = (0..results.len()).all(|i| {
+        let sum: i64 = (0..n).filter(|bit| i & (1 << bit) != 0).map(|bit| signed[bit]).sum();
+        (sum >= threshold) == results[i]
+    });
+    if !holds {
+        return Err(EvaluationError::UnsupportedOperation {
+            operation: "recovering integer weights for a linearly separable function".to_string(),
+        });
+    }
+
+    Ok(ThresholdAnalysis {
+        expression: expr.clone(),
+        weights: Some(variables
+            .iter()
+            .zip(signed)
+            .map(|(name, weight)| VariableWeight { variable: name.to_string(), weight })
+            .collect()),
+        variables,
+        is_threshold: true,
+        threshold: Some(threshold),
+        reason: None,
+    })
+}
+
+/// Non-negative integer weights (one per relevant bit) and a threshold for a
+/// monotone function, or `None` if it is not linearly separable
+fn monotone_weights(g: &dyn Fn(usize) -> bool, relevant: &[usize], n: usize) -> Result<Option<(Vec<i64>, i64)>, EvaluationError> {
+    let all = 1usize << n;
+    let relevant_mask: usize = relevant.iter().map(|bit| 1 << bit).sum();
+    if g(0) {
+        return Ok(Some((vec![0; relevant.len()], 0)));
+    }
+    if !g(all - 1) {
+        return Ok(Some((vec![0; relevant.len()], 1)));
+    }
+
+    // Only points over the relevant variables matter
+    let points = (0..all).filter(|y| y & !relevant_mask == 0);
+    let mut constraints = Vec::new();
+    for y in points {
+        options::check_row(y)?;
+        let coefficients: Vec<f64> = relevant.iter().map(|bit| ((y >> bit) & 1) as f64).collect();
+        if g(y) && relevant.iter().all(|bit| y & (1 << bit) == 0 || !g(y & !(1 << bit))) {
+            // Minimal true point: w·y ≥ T, i.e. w·y - t' ≥ 1 with T = t' + 1
+            constraints.push(Constraint { coefficients, at_least: true, rhs: 1.0 });
+        } else if !g(y) && relevant.iter().all(|bit| y & (1 << bit) != 0 || g(y | (1 << bit))) {
+            // Maximal false point: w·y ≤ T - 1, i.e. w·y - t' ≤ 0
+            constraints.push(Constraint { coefficients, at_least: false, rhs: 0.0 });
+        }
+    }
+    for constraint in &mut constraints {
+        constraint.coefficients.push(-1.0);
+    }
+
+    let Some(solution) = minimize_sum(&constraints, relevant.len() + 1) else {
+        return Ok(None);
+    };
+    let (weights, offset) = solution.split_at(relevant.len());
+    let Some((mut weights, mut threshold)) = round_solution(weights, offset[0] + 1.0) else {
+        return Ok(None);
+    };
+
+    // Smallest equivalent form: divide by the weights' gcd, rounding the threshold up
+    let divisor = weights.iter().fold(0, |acc, &w| gcd(acc, w));
+    if divisor > 1 {
+        weights.iter_mut().for_each(|w| *w /= divisor);
+        threshold = (threshold + divisor - 1) / divisor;
+    }
+    Ok(Some((weights, threshold)))
+}
+
+/// Scale a rational solution to integers, trying small common denominators
+fn round_solution(weights: &[f64], threshold: f64) -> Option<(Vec<i64>, i64)> {
+    (1..=1000).find_map(|scale| {
+        let scale = scale as f64;
+        let near = |x: f64| ((x * scale).round() - x * scale).abs() < 1e-6;
+        (weights.iter().all(|&w| near(w)) && near(threshold)).then(|| {
+            let weights = weights.iter().map(|&w| (w * scale).round() as i64).collect();
+            (weights, (threshold * scale).round() as i64)
+        })
+    })
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// `coefficients · x ≥ rhs` (or `≤` when `at_least` is false), with `rhs ≥ 0`
+struct Constraint {
+    coefficients: Vec<f64>,
+    at_least: bool,
+    rhs: f64,
+}
+
+/// Minimize the sum of `vars` non-negative variables subject to `constraints`
+/// with a two-phase tableau simplex using Bland's rule. `None` when infeasible.
+fn minimize_sum(constraints: &[Constraint], vars: usize) -> Option<Vec<f64>> {
+    let m = constraints.len();
+    let artificial: Vec<usize> = (0..m).filter(|&i| constraints[i].at_least).collect();
+    // Columns: variables, one slack or surplus per row, artificials, right-hand side
+    let width = vars + m + artificial.len() + 1;
+    let rhs = width - 1;
+    let mut tableau = vec![vec![0.0; width]; m + 1];
+    let mut basis = vec![0; m];
+    for (i, constraint) in constraints.iter().enumerate() {
+        tableau[i][..vars].copy_from_slice(&constraint.coefficients);
+        tableau[i][rhs] = constraint.rhs;
+        if constraint.at_least {
+            tableau[i][vars + i] = -1.0;
+        } else {
+            tableau[i][vars + i] = 1.0;
+            basis[i] = vars + i;
+        }
+    }
+    for (k, &i) in artificial.iter().enumerate() {
+        tableau[i][vars + m + k] = 1.0;
+        basis[i] = vars + m + k;
+    }
+
+    // Phase 1: drive the artificials to zero
+    let first_artificial = vars + m;
+    let phase_one: Vec<f64> = (0..width - 1).map(|j| if j >= first_artificial { 1.0 } else { 0.0 }).collect();
+    set_objective(&mut tableau, &basis, &phase_one);
+    optimize(&mut tableau, &mut basis, width - 1);
+    if -tableau[m][rhs] > EPSILON {
+        return None;
+    }
+
+    // Pivot artificials left in the basis at zero onto real columns; rows
+    // where none is available are redundant
+    for row in 0..m {
+        if basis[row] >= first_artificial
+            && let Some(column) = (0..first_artificial).find(|&j| tableau[row][j].abs() > EPSILON)
+        {
+            pivot(&mut tableau, row, column);
+            basis[row] = column;
+        }
+    }
+
+    // Phase 2: minimize the sum of the variables, keeping artificials out
+    let phase_two: Vec<f64> = (0..width - 1).map(|j| if j < vars { 1.0 } else { 0.0 }).collect();
+    set_objective(&mut tableau, &basis, &phase_two);
+    optimize(&mut tableau, &mut basis, first_artificial);
+
+    let mut solution = vec![0.0; vars];
+    for (i, &column) in basis.iter().enumerate() {
+        if column < vars {
+            solution[column] = tableau[i][rhs];
+        }
+    }
+    Some(solution)
+}
+
+/// Fill the objective row with the reduced costs of `costs` for the current basis
+fn set_objective(tableau: &mut [Vec<f64>], basis: &[usize], costs: &[f64]) {
+    let m = basis.len();
+    let width = tableau[0].len();
+    let mut row = costs.to_vec();
+    row.push(0.0);
+    for (i, &column) in basis.iter().enumerate() {
+        let cost = costs[column];
+        if cost != 0.0 {
+            for (j, value) in row.iter_mut().enumerate().take(width) {
+                *value -= cost * tableau[i][j];
+            }
+        }
+    }
+    tableau[m] = row;
+}
+
+/// Pivot until no column before `enterable` has a negative reduced cost
+fn optimize(tableau: &mut [Vec<f64>], basis: &mut [usize], enterable: usize) {
+    let m = basis.len();
+    let rhs = tableau[0].len() - 1;
+    while let Some(column) = (0..enterable).find(|&j| tableau[m][j] < -EPSILON) {
+        let leaving = (0..m)
+            .filter(|&i| tableau[i][column] > EPSILON)
+            .min_by(|&a, &b| {
+                let ratio = |i: usize| tableau[i][rhs] / tableau[i][column];
+                ratio(a).total_cmp(&ratio(b)).then(basis[a].cmp(&basis[b]))
+            });
+        // The objective is bounded below by zero, so there is always a leaving row
+        let Some(row) = leaving else { return };
+        pivot(tableau, row, column);
+        basis[row] = column;
+    }
+}
+
+fn pivot(tableau: &mut [Vec<f64>], row: usize, column: usize) {
+    let factor = tableau[row][column];
+    tableau[row].iter_mut().for_each(|value| *value /= factor);
+    let pivot_row = tableau[row].clone();
+    for (i, other) in tableau.iter_mut().enumerate() {
+        let scale = other[column];
+        if i != row && scale != 0.0 {
+            for (value, &p) in other.iter_mut().zip(&pivot_row) {
+                *value -= scale * p;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn analyze(input: &str) -> ThresholdAnalysis {
+        analyze_threshold(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    fn weights(analysis: &ThresholdAnalysis) -> Vec<i64> {
+        analysis.weights.as_ref().unwrap().iter().map(|w| w.weight).collect()
+    }
+
+    #[test]
+    fn test_majority_is_threshold() {
+        let analysis = analyze("(a and b) or (a and c) or (b and c)");
+        assert!(analysis.is_threshold);
+        assert_eq!(weights(&analysis), [1, 1, 1]);
+        assert_eq!(analysis.threshold, Some(2));
+    }
+
+    #[test]
+    fn test_weighted_vote() {
+        // a alone, or b and c together
+        let analysis = analyze("a or (b and c)");
+        assert_eq!(weights(&analysis), [2, 1, 1]);
+        assert_eq!(analysis.threshold, Some(2));
+    }
+
+    #[test]
+    fn test_negative_weights() {
+        let analysis = analyze("a and not b");
+        assert_eq!(weights(&analysis), [1, -1]);
+        assert_eq!(analysis.threshold, Some(1));
+    }
+
+    #[test]
+    fn test_not_threshold() {
+        let xor = analyze("a xor b");
+        assert!(!xor.is_threshold);
+        assert!(xor.reason.unwrap().contains("not unate"));
+
+        // Unate but not separable
+        let split = analyze("(a and b) or (c and d)");
+        assert!(!split.is_threshold);
+        assert_eq!(split.reason.as_deref(), Some("not linearly separable"));
+    }
+
+    #[test]
+    fn test_constants() {
+        let tautology = analyze("a or not a");
+        assert!(tautology.is_threshold);
+        assert_eq!(tautology.threshold, Some(0));
+        assert_eq!(analyze("a and not a").threshold, Some(1));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::eval::{Variables, EvaluationError};
+use crate::eval::truth_table::evaluate_expression;
+use crate::source::{Expr, ExprVisitor, Quantifier, BinaryOp};
+use crate::source::visit::walk_expr;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use serde::{Serialize, Deserialize};
+
+/// One subexpression of a [`Trace`] and its value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceNode {
+    pub expr: Expr,
+    /// Nesting below the whole expression, which is at depth 0
+    pub depth: usize,
+    pub value: bool,
+    /// Whether the node is on the chain of operands that force the result
+    pub deciding: bool,
+}
+
+/// The value of every subexpression under one assignment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trace {
+    pub expression: Expr,
+    pub variables: Variables,
+    pub assignment: HashMap<String, bool>,
+    pub result: bool,
+    /// Subexpressions in preorder; quantifier bodies are not traced, since
+    /// their value depends on the bound variable
+    pub nodes: Vec<TraceNode>,
+    /// The innermost subexpression that forces the result: every operator
+    /// above it has it (or the node containing it) as an operand whose value
+    /// alone decides the operator's. `None` when the root needs all its operands.
+    pub forced_by: Option<Expr>,
+}
+
+/// Evaluate `expr` and each of its subexpressions under `assignment`, which
+/// must give a value to every variable of the expression
+pub fn trace(expr: &Expr, assignment: &HashMap<String, bool>) -> Result<Trace, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    if let Some(missing) = variables.iter().find(|var| !assignment.contains_key(*var)) {
+        return Err(EvaluationError::InvalidTruthAssignment {
+            variable: missing.to_string(),
+            context: "no value provided".to_string(),
+        });
+    }
+    let mut assignment = assignment.clone();
+    assignment.retain(|name, _| variables.contains(name));
+
+    let mut chain: Vec<&Expr> = Vec::new();
+    let mut node = expr;
+    while let Some(operand) = deciding_operand(node, &assignment) {
+        chain.push(operand);
+        node = operand;
+    }
+
+    let mut collector = Nodes { assignment: &assignment, chain: &chain, depth: 0, nodes: Vec::new() };
+    let _ = collector.visit_expr(expr);
+    let mut nodes = collector.nodes;
+    nodes[0].deciding = true;
+
+    Ok(Trace {
+        expression: expr.clone(),
+        variables,
+        result: evaluate_expression(expr, &assignment),
+        forced_by: chain.last().map(|&operand| operand.clone()),
+        assignment,
+        nodes,
+    })
+}
+
+/// The operand of `expr` whose value alone decides `expr`'s value under `assignment`
+fn deciding_operand<'e>(expr: &'e Expr, assignment: &HashMap<String, bool>) -> Option<&'e Expr> {
+    if let Expr::Not(inner) = expr {
+        return Some(inner);
+    }
+    let (op, left, right) = expr.as_binary()?;
+    let value = |operand: &Expr| evaluate_expression(operand, assignment);
+    match op {
+        // false decides and/nand, true decides or/nor; the first such operand is named
+        BinaryOp::And | BinaryOp::Nand => [left, right].into_iter().find(|operand| !value(operand)),
+        BinaryOp::Or | BinaryOp::Nor => [left, right].into_iter().find(|operand| value(operand)),
+        BinaryOp::Implication if !value(left) => Some(left),
+        BinaryOp::Implication if value(right) => Some(right),
+        BinaryOp::Implication | BinaryOp::Xor | BinaryOp::Iff => None,
+    }
+}
+
+struct Nodes<'a> {
+    assignment: &'a HashMap<String, bool>,
+    chain: &'a [&'a Expr],
+    depth: usize,
+    nodes: Vec<TraceNode>,
+}
+
+impl ExprVisitor for Nodes<'_> {
+    type Break = ();
+
+    fn visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        self.nodes.push(TraceNode {
+            expr: expr.clone(),
+            depth: self.depth,
+            value: evaluate_expression(expr, self.assignment),
+            deciding: self.chain.iter().any(|&operand| std::ptr::eq(operand, expr)),
+        });
+        self.depth += 1;
+        walk_expr(self, expr)?;
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn visit_quantified(&mut self, _quantifier: Quantifier, _var: &str, _body: &Expr) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr;
+
+    fn assignment(values: &[(&str, bool)]) -> HashMap<String, bool> {
+        values.iter().map(|&(name, value)| (name.to_string(), value)).collect()
+    }
+
+    #[test]
+    fn test_trace_follows_deciding_operands() {
+        let expr = expr!(not (a and (b or c)) xor d);
+        let traced = trace(&expr, &assignment(&[("a", true), ("b", false), ("c", true), ("d", false)])).unwrap();
+        assert!(!traced.result);
+        // xor needs both operands, so nothing below the root forces the result
+        assert_eq!(traced.forced_by, None);
+        assert_eq!(traced.nodes.len(), 8);
+        assert_eq!(traced.nodes.iter().map(|node| node.depth).collect::<Vec<_>>(), [0, 1, 2, 3, 3, 4, 4, 1]);
+
+        let expr = expr!(d or not (a and (b or c)));
+        let traced = trace(&expr, &assignment(&[("a", true), ("b", false), ("c", false), ("d", false)])).unwrap();
+        assert!(traced.result);
+        // b ∨ c is false only because both are, so the chain stops there
+        assert_eq!(traced.forced_by, Some(expr!(b or c)));
+        let deciding: Vec<String> = traced.nodes.iter().filter(|node| node.deciding).map(|node| node.expr.to_string()).collect();
+        assert_eq!(deciding, ["(d ∨ ¬(a ∧ (b ∨ c)))", "¬(a ∧ (b ∨ c))", "(a ∧ (b ∨ c))", "(b ∨ c)"]);
+    }
+
+    #[test]
+    fn test_trace_needs_every_variable() {
+        assert!(trace(&expr!(a and b), &assignment(&[("a", true)])).is_err());
+        let traced = trace(&expr!(forall x. x or a), &assignment(&[("a", true)])).unwrap();
+        assert_eq!(traced.nodes.len(), 1);
+    }
+}
@@ -1,5 +1,8 @@
-use crate::source::Expr;
+use crate::source::{Expr, Quantifier, Cardinality, Operator, BinaryOp, ExprFolder};
 use crate::eval::{Variables, EvaluationError};
+use crate::eval::rng::SplitMix64;
+use crate::eval::options;
+use crate::config::MAX_SAMPLE_ROWS;
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 
@@ -8,14 +11,104 @@ use serde::{Serialize, Deserialize};
 pub struct TruthTable {
     pub variables: Variables,
     pub rows: Vec<TruthTableRow>,
+    /// The slice of the assignment space these rows cover, when only part of it was generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<RowRange>,
+    /// Set when the rows are random assignments rather than the table in order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<TableSample>,
+}
+
+/// A slice `start..end` of the `total` rows of a truth table, in row order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowRange {
+    pub start: usize,
+    pub end: usize,
+    pub total: usize,
+}
+
+/// How the rows of a sampled table were drawn: independently and uniformly from
+/// all `2^variables` assignments, so a row may repeat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSample {
+    pub seed: u64,
+    /// Assignments drawn
+    pub rows: usize,
+    /// Variables of the expression, so `2^variables` assignments in all
+    pub variables: usize,
+}
+
+/// Row order for reading a column out of a truth table
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RowOrder {
+    /// The table's own order: the first variable is the least significant bit of the row index (default)
+    #[default]
+    Lsb,
+    /// The first variable is the most significant bit, as in the usual binary numbering of inputs
+    Msb,
+}
+
+/// Row orders for reading a table by importance rather than by row number
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowSort {
+    /// True rows first, each group in row order
+    Result,
+    /// Count with the most influential variable as the most significant bit,
+    /// so rows that agree on the variables that matter most sit together
+    Influence,
 }
 
 impl TruthTable {
+    /// Move the true rows before the false ones, keeping their order otherwise
+    pub fn sort_by_result(&mut self) {
+        self.rows.sort_by_key(|row| !row.result);
+    }
+    
+    /// Order rows by their values of `ranking`, read as a binary number with the
+    /// first variable as the most significant bit; ties keep their order
+    pub fn sort_by_variables(&mut self, ranking: &[String]) {
+        self.rows.sort_by_cached_key(|row| {
+            ranking.iter().map(|var| row.assignments.get(var).copied().unwrap_or(false)).collect::<Vec<_>>()
+        });
+    }
+    
+    /// Values of `column`, a variable or `result`, in the given row order.
+    ///
+    /// Reordering needs the whole table, so `Msb` is rejected for a table slice.
+    pub fn column(&self, column: &str, order: RowOrder) -> Result<Vec<bool>, EvaluationError> {
+        if column != "result" && !self.variables.contains(column) {
+            return Err(EvaluationError::InvalidTruthAssignment {
+                variable: column.to_string(),
+                context: "column that is neither a variable of the table nor `result`".to_string(),
+            });
+        }
+        let value = |row: &TruthTableRow| if column == "result" { row.result } else { row.assignments.get(column).copied().unwrap_or(false) };
+        match order {
+            RowOrder::Lsb => Ok(self.rows.iter().map(value).collect()),
+            RowOrder::Msb => {
+                if self.range.is_some() || self.sample.is_some() {
+                    return Err(EvaluationError::UnsupportedOperation {
+                        operation: "reordering the rows of a table slice or sample".to_string(),
+                    });
+                }
+                let n = self.variables.len();
+                Ok((0..self.rows.len())
+                    .map(|index| {
+                        let reversed = (0..n).fold(0, |acc, bit| (acc << 1) | ((index >> bit) & 1));
+                        value(&self.rows[reversed])
+                    })
+                    .collect())
+            }
+        }
+    }
+    
     /// Create a new empty truth table
     pub fn new(variables: Variables) -> Self {
         Self {
             variables,
             rows: Vec::new(),
+            range: None,
+            sample: None,
         }
     }
     
@@ -69,6 +162,8 @@ impl TruthTableBuilder {
         Ok(TruthTable {
             variables,
             rows: self.rows,
+            range: None,
+            sample: None,
         })
     }
 }
@@ -79,7 +174,7 @@ impl Default for TruthTableBuilder {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TruthTableRow {
     pub assignments: HashMap<String, bool>,
     pub result: bool,
@@ -87,30 +182,42 @@ pub struct TruthTableRow {
 
 /// Generate a truth table from a boolean expression
 pub fn generate_truth_table(expr: &Expr) -> Result<TruthTable, EvaluationError> {
+    generate_rows(expr, None)
+}
+
+/// Generate rows `start..end` of a truth table (`end` defaults to the last row),
+/// recording the slice in the table so shards can be merged later
+pub fn generate_truth_table_range(expr: &Expr, start: usize, end: Option<usize>) -> Result<TruthTable, EvaluationError> {
+    generate_rows(expr, Some((start, end)))
+}
+
+fn generate_rows(expr: &Expr, slice: Option<(usize, Option<usize>)>) -> Result<TruthTable, EvaluationError> {
     let variables = Variables::from_expr(expr)?;
     let num_vars = variables.len();
-    
-    if num_vars == 0 {
-        // Handle expressions with no variables (like constants)
-        return Ok(TruthTable {
-            variables,
-            rows: vec![TruthTableRow {
-                assignments: HashMap::new(),
-                result: evaluate_expression(expr, &HashMap::new()),
-            }],
-        });
-    }
-    
-    let mut rows = Vec::new();
     let num_combinations = 1 << num_vars; // 2^num_vars
     
-    for i in 0..num_combinations {
+    let range = match slice {
+        None => None,
+        Some((start, end)) => {
+            let end = end.unwrap_or(num_combinations);
+            if start > end || end > num_combinations {
+                return Err(EvaluationError::RowRangeOutOfBounds { start, end, rows: num_combinations });
+            }
+            Some(RowRange { start, end, total: num_combinations })
+        }
+    };
+    let (start, end) = range.map_or((0, num_combinations), |r| (r.start, r.end));
+    options::check_table_size(end - start, num_vars)?;
+    
+    let mut rows = Vec::with_capacity(end - start);
+    for i in start..end {
+        options::check_row(i - start)?;
         let mut assignments = HashMap::new();
         
         // Create assignment from bit pattern
         for (var_idx, var_name) in variables.iter().enumerate() {
             let bit_value = (i >> var_idx) & 1 == 1;
-            assignments.insert(var_name.clone(), bit_value);
+            assignments.insert(var_name.to_string(), bit_value);
         }
         
         let result = evaluate_expression(expr, &assignments);
@@ -124,29 +231,140 @@ pub fn generate_truth_table(expr: &Expr) -> Result<TruthTable, EvaluationError>
     Ok(TruthTable {
         variables,
         rows,
+        range,
+        sample: None,
     })
 }
 
+/// Evaluate `rows` assignments drawn uniformly at random, with no limit on the
+/// number of variables; the same seed draws the same rows
+pub fn sample_truth_table(expr: &Expr, rows: usize, seed: u64) -> Result<TruthTable, EvaluationError> {
+    if rows > MAX_SAMPLE_ROWS {
+        return Err(EvaluationError::ExpressionTooComplex {
+            reason: format!("a sample of {} rows is more than the {} allowed", rows, MAX_SAMPLE_ROWS),
+        });
+    }
+    let variables = Variables::from_expr_unbounded(expr)?;
+    let sample = TableSample { seed, rows, variables: variables.len() };
+    let mut rng = SplitMix64(seed);
+    let rows = (0..rows)
+        .map(|_| {
+            // The top bit: the lowest bit of successive draws nearly alternates
+            let assignments: HashMap<String, bool> = variables.iter().map(|var| (var.to_string(), rng.next() >> 63 == 1)).collect();
+            let result = evaluate_expression(expr, &assignments);
+            TruthTableRow { assignments, result }
+        })
+        .collect();
+    Ok(TruthTable {
+        variables,
+        rows,
+        range: None,
+        sample: Some(sample),
+    })
+}
+
+/// Evaluate an expression for each of the given assignments, producing one row per assignment
+///
+/// Every variable of the expression must be present in every assignment; extra
+/// entries are ignored.
+pub fn evaluate_rows(expr: &Expr, assignments: Vec<HashMap<String, bool>>) -> Result<TruthTable, EvaluationError> {
+    let variables = Variables::from_expr(expr)?;
+    let mut rows = Vec::with_capacity(assignments.len());
+    
+    for (row_idx, mut assignment) in assignments.into_iter().enumerate() {
+        if let Some(missing) = variables.iter().find(|var| !assignment.contains_key(*var)) {
+            return Err(EvaluationError::InvalidTruthAssignment {
+                variable: missing.to_string(),
+                context: format!("no value provided in row {}", row_idx + 1),
+            });
+        }
+        assignment.retain(|name, _| variables.contains(name));
+        
+        let result = evaluate_expression(expr, &assignment);
+        rows.push(TruthTableRow {
+            assignments: assignment,
+            result,
+        });
+    }
+    
+    Ok(TruthTable {
+        variables,
+        rows,
+        range: None,
+        sample: None,
+    })
+}
+
+/// The result on every row, row `i` under [`assignment_from_index`], checking
+/// the time budget in force as the rows are evaluated
+pub(crate) fn evaluate_results(expr: &Expr, variables: &Variables) -> Result<Vec<bool>, EvaluationError> {
+    (0..1usize << variables.len())
+        .map(|index| {
+            options::check_row(index)?;
+            Ok(evaluate_expression(expr, &assignment_from_index(variables, index)))
+        })
+        .collect()
+}
+
+/// Build the assignment for row `index` of a truth table (the first variable is the lowest bit)
+pub fn assignment_from_index(variables: &Variables, index: usize) -> HashMap<String, bool> {
+    variables
+        .iter()
+        .enumerate()
+        .map(|(var_idx, var_name)| (var_name.to_string(), (index >> var_idx) & 1 == 1))
+        .collect()
+}
+
 /// Evaluate a boolean expression with given variable assignments
 pub fn evaluate_expression(expr: &Expr, assignments: &HashMap<String, bool>) -> bool {
-    match expr {
-        Expr::Identifier(name) => {
-            assignments.get(name).copied().unwrap_or(false)
-        }
-        Expr::Not(inner) => {
-            !evaluate_expression(inner, assignments)
-        }
-        Expr::And(left, right) => {
-            evaluate_expression(left, assignments) && evaluate_expression(right, assignments)
-        }
-        Expr::Or(left, right) => {
-            evaluate_expression(left, assignments) || evaluate_expression(right, assignments)
-        }
-        Expr::Xor(left, right) => {
-            evaluate_expression(left, assignments) ^ evaluate_expression(right, assignments)
-        }
-        Expr::Implication(left, right) => {
-            !evaluate_expression(left, assignments) || evaluate_expression(right, assignments)
+    BooleanEvaluation { assignments }.fold_expr(expr)
+}
+
+/// Evaluates in two-valued logic; unassigned variables are false
+struct BooleanEvaluation<'a> {
+    assignments: &'a HashMap<String, bool>,
+}
+
+impl ExprFolder for BooleanEvaluation<'_> {
+    type Output = bool;
+
+    fn fold_identifier(&mut self, name: &str) -> bool {
+        self.assignments.get(name).copied().unwrap_or(false)
+    }
+
+    fn fold_constant(&mut self, value: bool) -> bool {
+        value
+    }
+
+    fn fold_not(&mut self, inner: bool) -> bool {
+        !inner
+    }
+
+    fn fold_binary(&mut self, op: BinaryOp, left: bool, right: bool) -> bool {
+        op.apply(left, right)
+    }
+
+    fn fold_ite(&mut self, cond: bool, then: bool, otherwise: bool) -> bool {
+        if cond { then } else { otherwise }
+    }
+
+    fn fold_cardinality(&mut self, kind: Cardinality, k: usize, operands: Vec<bool>) -> bool {
+        kind.holds(k, operands.into_iter().filter(|&operand| operand).count())
+    }
+
+    fn fold_custom(&mut self, operator: &Operator, operands: Vec<bool>) -> bool {
+        operator.apply(&operands)
+    }
+
+    fn fold_quantified(&mut self, quantifier: Quantifier, var: &str, body: &Expr) -> bool {
+        let mut assignments = self.assignments.clone();
+        let mut case = |value: bool| {
+            assignments.insert(var.to_string(), value);
+            evaluate_expression(body, &assignments)
+        };
+        match quantifier {
+            Quantifier::ForAll => case(false) && case(true),
+            Quantifier::Exists => case(false) || case(true),
         }
     }
-}
\ No newline at end of file
+}
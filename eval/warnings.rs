@@ -0,0 +1,180 @@
+use crate::source::{Expr, Cardinality};
+use crate::eval::{options, Variables, EvaluationError};
+use crate::eval::truth_table::{assignment_from_index, evaluate_expression};
+use std::collections::HashSet;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+/// Kind of a non-fatal finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningKind {
+    /// A variable only ever occurs under an odd number of negations
+    OnlyNegated,
+    /// The expression has the same value for every assignment
+    ConstantExpression,
+    /// Merged tables agreed on rows they both contain
+    OverlappingRows,
+}
+
+/// Something worth pointing out about an input or result that does not prevent
+/// the command from producing its output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Check an expression for variables used only negated and for being constant
+pub fn expression_warnings(expr: &Expr) -> Result<Vec<Warning>, EvaluationError> {
+    // Only the constant check enumerates rows, so it alone is bounded by the variable limit
+    let variables = Variables::from_expr_unbounded(expr)?;
+    let mut warnings = Vec::new();
+
+    let polarities = polarities(expr);
+    for var in variables.iter() {
+        if polarities.negative.contains(var) && !polarities.positive.contains(var) {
+            warnings.push(Warning::new(
+                WarningKind::OnlyNegated,
+                format!("variable `{}` appears only negated", var),
+            ));
+        }
+    }
+
+    // A bare constant is constant on purpose
+    if !matches!(expr, Expr::True | Expr::False)
+        && variables.len() <= options::max_vars()
+        && let Some(value) = constant_value(expr, &variables)
+    {
+        warnings.push(Warning::new(
+            WarningKind::ConstantExpression,
+            format!("expression is constant (always {})", value),
+        ));
+    }
+
+    Ok(warnings)
+}
+
+/// The variables occurring positively and negatively in a subtree
+#[derive(Default)]
+struct Polarities<'a> {
+    positive: HashSet<&'a str>,
+    negative: HashSet<&'a str>,
+}
+
+impl<'a> Polarities<'a> {
+    /// The polarities of the subtree under one more negation
+    fn flipped(self) -> Self {
+        Self { positive: self.negative, negative: self.positive }
+    }
+
+    /// Every variable of the subtree in both polarities
+    fn both(mut self) -> Self {
+        self.positive.extend(self.negative.iter().copied());
+        self.negative.clone_from(&self.positive);
+        self
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.positive.extend(other.positive);
+        self.negative.extend(other.negative);
+        self
+    }
+}
+
+/// The variables of `expr` that occur positively and negatively, each
+/// subtree computed once and flipped where it sits under a negation.
+/// Xor and iff operands, if-then-else conditions and implication antecedents
+/// count as both polarities: `a -> b` is a rule about `a`, not a use of `not a`.
+fn polarities(expr: &Expr) -> Polarities<'_> {
+    match expr {
+        Expr::Identifier(name) => Polarities { positive: HashSet::from([name.as_str()]), negative: HashSet::new() },
+        Expr::True | Expr::False => Polarities::default(),
+        Expr::Not(inner) => polarities(inner).flipped(),
+        Expr::And(left, right) | Expr::Or(left, right) => polarities(left).merge(polarities(right)),
+        Expr::Nand(left, right) | Expr::Nor(left, right) => polarities(left).merge(polarities(right)).flipped(),
+        Expr::Implication(left, right) => polarities(left).both().merge(polarities(right)),
+        Expr::Xor(left, right) | Expr::Iff(left, right) => polarities(left).merge(polarities(right)).both(),
+        Expr::Ite(cond, then, otherwise) => polarities(cond).both().merge(polarities(then)).merge(polarities(otherwise)),
+        // More true operands help `atleast`, hurt `atmost`, and can do either for `exactly`
+        Expr::Cardinality(kind, _, operands) => {
+            let operands = operands.iter().fold(Polarities::default(), |acc, operand| acc.merge(polarities(operand)));
+            match kind {
+                Cardinality::AtLeast => operands,
+                Cardinality::AtMost => operands.flipped(),
+                Cardinality::Exactly => operands.both(),
+            }
+        }
+        Expr::Quantified(_, _, body) => polarities(body),
+        // Nothing is known about how a custom operator uses its operands
+        Expr::Custom(_, operands) => operands.iter().fold(Polarities::default(), |acc, operand| acc.merge(polarities(operand))).both(),
+    }
+}
+
+/// The expression's value if it is the same for every assignment
+fn constant_value(expr: &Expr, variables: &Variables) -> Option<bool> {
+    let first = evaluate_expression(expr, &assignment_from_index(variables, 0));
+    (1..1usize << variables.len())
+        .all(|index| evaluate_expression(expr, &assignment_from_index(variables, index)) == first)
+        .then_some(first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn warnings(input: &str) -> Vec<Warning> {
+        expression_warnings(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_only_negated_variable() {
+        let found = warnings("a and not temp or not (b -> temp)");
+        assert_eq!(found, vec![Warning::new(WarningKind::OnlyNegated, "variable `temp` appears only negated")]);
+        // Antecedents and xor operands are not reported
+        assert!(warnings("(not a -> b) and (not c xor b)").is_empty());
+        assert!(warnings("a -> b").is_empty());
+        assert!(warnings("(a -> b) and (b -> c)").is_empty());
+    }
+
+    #[test]
+    fn test_constant_expression() {
+        let found = warnings("a or not a");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, WarningKind::ConstantExpression);
+        assert_eq!(found[0].message, "expression is constant (always true)");
+        assert!(warnings("a and b").is_empty());
+    }
+
+    #[test]
+    fn test_nested_xors_take_one_pass() {
+        // Every level doubled the walk when each operand was visited once per polarity
+        let var = |name: String| Box::new(Expr::Identifier(name));
+        let nested = (0..200).fold(Expr::Not(var("a".into())), |acc, i| Expr::Xor(Box::new(acc), var(format!("x{}", i))));
+        let expr = Expr::And(Box::new(nested), Box::new(Expr::Not(var("b".into()))));
+        let found = expression_warnings(&expr).unwrap();
+        assert_eq!(found, vec![Warning::new(WarningKind::OnlyNegated, "variable `b` appears only negated")]);
+    }
+
+    #[test]
+    fn test_more_variables_than_a_table_allows() {
+        let operands = (0..=crate::config::MAX_VARIABLES).map(|i| Expr::Identifier(format!("v{}", i)));
+        let disjunction = operands.reduce(|acc, next| Expr::Or(Box::new(acc), Box::new(next))).unwrap();
+        let found = expression_warnings(&Expr::Not(Box::new(disjunction))).unwrap();
+        assert_eq!(found.len(), crate::config::MAX_VARIABLES + 1);
+        assert!(found.iter().all(|warning| warning.kind == WarningKind::OnlyNegated));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::eval::aig::{Aig, Literal};
+use std::collections::HashMap;
+use miette::Result;
+
+/// Write a graph in the ASCII AIGER format (`aag`), including an input symbol table
+pub fn write_ascii(aig: &Aig) -> String {
+    let inputs = aig.inputs().len();
+    let mut output = format!("aag {} {} 0 {} {}\n", aig.max_variable(), inputs, aig.outputs().len(), aig.ands().len());
+    for i in 0..inputs {
+        output.push_str(&format!("{}\n", aig.input(i)));
+    }
+    for lit in aig.outputs() {
+        output.push_str(&format!("{}\n", lit));
+    }
+    for (i, (rhs0, rhs1)) in aig.ands().iter().enumerate() {
+        output.push_str(&format!("{} {} {}\n", gate_literal(aig, i), rhs0, rhs1));
+    }
+    push_symbols(&mut output, aig);
+    output
+}
+
+/// Write a graph in the binary AIGER format (`aig`): gates are delta-encoded
+pub fn write_binary(aig: &Aig) -> Vec<u8> {
+    let mut bytes = format!("aig {} {} 0 {} {}\n", aig.max_variable(), aig.inputs().len(), aig.outputs().len(), aig.ands().len())
+        .into_bytes();
+    for lit in aig.outputs() {
+        bytes.extend(format!("{}\n", lit).bytes());
+    }
+    for (i, &(rhs0, rhs1)) in aig.ands().iter().enumerate() {
+        encode_delta(&mut bytes, gate_literal(aig, i) - rhs0);
+        encode_delta(&mut bytes, rhs0 - rhs1);
+    }
+    let mut symbols = String::new();
+    push_symbols(&mut symbols, aig);
+    bytes.extend(symbols.bytes());
+    bytes
+}
+
+/// A comment section to append to either format, after which readers stop
+pub fn comment_section(lines: &[String]) -> String {
+    let mut section = String::from("c\n");
+    for line in lines {
+        section.push_str(line);
+        section.push('\n');
+    }
+    section
+}
+
+fn gate_literal(aig: &Aig, index: usize) -> Literal {
+    2 * (aig.inputs().len() + 1 + index) as Literal
+}
+
+fn push_symbols(output: &mut String, aig: &Aig) {
+    for (i, name) in aig.inputs().iter().enumerate() {
+        output.push_str(&format!("i{} {}\n", i, name));
+    }
+}
+
+fn encode_delta(bytes: &mut Vec<u8>, mut delta: u32) {
+    while delta >= 0x80 {
+        bytes.push((delta & 0x7f) as u8 | 0x80);
+        delta >>= 7;
+    }
+    bytes.push(delta as u8);
+}
+
+/// Parse an AIGER file in either the ASCII (`aag`) or binary (`aig`) format.
+///
+/// Latches are not supported. Unnamed inputs are called `i0`, `i1`, ….
+pub fn parse(bytes: &[u8]) -> Result<Aig> {
+    let mut reader = LineReader { bytes, pos: 0, line: 0 };
+    let header = reader.next_line()?.ok_or_else(|| miette::miette!("AIGER input is empty"))?;
+    let fields: Vec<&str> = header.split_whitespace().collect();
+    let (binary, counts) = match fields.as_slice() {
+        ["aag", counts @ ..] if counts.len() == 5 => (false, counts),
+        ["aig", counts @ ..] if counts.len() == 5 => (true, counts),
+        _ => return Err(miette::miette!("Expected an AIGER header `aag M I L O A` or `aig M I L O A`, found '{}'", header)),
+    };
+    let counts = counts
+        .iter()
+        .map(|field| field.parse::<u32>().map_err(|_| miette::miette!("Invalid number '{}' in AIGER header", field)))
+        .collect::<Result<Vec<_>>>()?;
+    let (max_variable, inputs, latches, outputs, ands) = (counts[0], counts[1], counts[2], counts[3], counts[4]);
+    if latches > 0 {
+        return Err(miette::miette!("AIGER latches are not supported (found {}); only combinational circuits can be read", latches));
+    }
+
+    let input_literals: Vec<Literal> = if binary {
+        (1..=inputs).map(|var| 2 * var).collect()
+    } else {
+        reader.single_literals(inputs)?
+    };
+    let output_literals = reader.single_literals(outputs)?;
+
+    let mut gates: HashMap<u32, (Literal, Literal)> = HashMap::new();
+    for i in 0..ands {
+        let (lhs, rhs0, rhs1) = if binary {
+            let lhs = 2 * (inputs + latches + i + 1);
+            let (delta0, delta1) = (reader.delta()?, reader.delta()?);
+            let rhs0 = lhs.checked_sub(delta0);
+            match rhs0.and_then(|rhs0| Some((rhs0, rhs0.checked_sub(delta1)?))) {
+                Some((rhs0, rhs1)) => (lhs, rhs0, rhs1),
+                None => return Err(miette::miette!("Invalid delta encoding for AND gate {}", i)),
+            }
+        } else {
+            match reader.literal_line(3)?.as_slice() {
+                &[lhs, rhs0, rhs1] => (lhs, rhs0, rhs1),
+                _ => unreachable!("literal_line returns the requested count"),
+            }
+        };
+        if lhs & 1 == 1 || lhs / 2 > max_variable {
+            return Err(miette::miette!("Invalid AND gate literal {}", lhs));
+        }
+        gates.insert(lhs / 2, (rhs0, rhs1));
+    }
+
+    // Symbol table: `i<index> <name>` lines until a `c` comment section
+    let mut names: Vec<String> = (0..inputs).map(|i| format!("i{}", i)).collect();
+    while let Some(line) = reader.next_line()? {
+        if line == "c" {
+            break;
+        }
+        if let Some((kind, name)) = line.split_once(' ')
+            && let Some(index) = kind.strip_prefix('i').and_then(|i| i.parse::<usize>().ok())
+            && index < names.len()
+        {
+            names[index] = name.to_string();
+        }
+    }
+
+    let mut aig = Aig::new(names);
+    let mut mapped: HashMap<u32, Literal> = HashMap::new();
+    mapped.insert(0, 0);
+    for (i, lit) in input_literals.iter().enumerate() {
+        mapped.insert(lit / 2, aig.input(i));
+    }
+    for lit in output_literals {
+        let output = map_literal(lit, &gates, &mut mapped, &mut aig, &mut Vec::new())?;
+        aig.add_output(output);
+    }
+    Ok(aig)
+}
+
+/// Translate a file literal into the rebuilt graph, adding its gates first
+fn map_literal(
+    lit: Literal,
+    gates: &HashMap<u32, (Literal, Literal)>,
+    mapped: &mut HashMap<u32, Literal>,
+    aig: &mut Aig,
+    visiting: &mut Vec<u32>,
+) -> Result<Literal> {
+    let variable = lit / 2;
+    if let Some(&base) = mapped.get(&variable) {
+        return Ok(base ^ (lit & 1));
+    }
+    let &(rhs0, rhs1) = gates
+        .get(&variable)
+        .ok_or_else(|| miette::miette!("AIGER literal {} refers to an undefined variable", lit))?;
+    if visiting.contains(&variable) {
+        return Err(miette::miette!("AIGER AND gates form a cycle through variable {}", variable));
+    }
+    visiting.push(variable);
+    let a = map_literal(rhs0, gates, mapped, aig, visiting)?;
+    let b = map_literal(rhs1, gates, mapped, aig, visiting)?;
+    visiting.pop();
+    let base = aig.and(a, b);
+    mapped.insert(variable, base);
+    Ok(base ^ (lit & 1))
+}
+
+struct LineReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl LineReader<'_> {
+    fn next_line(&mut self) -> Result<Option<String>> {
+        if self.pos >= self.bytes.len() {
+            return Ok(None);
+        }
+        let rest = &self.bytes[self.pos..];
+        let len = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+        self.pos += len + 1;
+        self.line += 1;
+        let text = std::str::from_utf8(&rest[..len])
+            .map_err(|_| miette::miette!("AIGER line {} is not valid UTF-8", self.line))?;
+        Ok(Some(text.trim_end_matches('\r').to_string()))
+    }
+
+    /// Read a line of exactly `count` literals
+    fn literal_line(&mut self, count: usize) -> Result<Vec<Literal>> {
+        let line = self.next_line()?.ok_or_else(|| miette::miette!("AIGER input ends early after line {}", self.line))?;
+        let literals = line
+            .split_whitespace()
+            .map(|field| field.parse::<Literal>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .ok()
+            .filter(|literals| literals.len() == count);
+        literals.ok_or_else(|| miette::miette!("AIGER line {}: expected {} literal(s), found '{}'", self.line, count, line))
+    }
+
+    /// Read `count` lines of one literal each
+    fn single_literals(&mut self, count: u32) -> Result<Vec<Literal>> {
+        (0..count).map(|_| Ok(self.literal_line(1)?[0])).collect()
+    }
+
+    fn delta(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+        let mut shift = 0;
+        loop {
+            let byte = *self.bytes.get(self.pos).ok_or_else(|| miette::miette!("AIGER binary section ends early"))?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u32).checked_shl(shift).ok_or_else(|| miette::miette!("AIGER delta is too large"))?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+    use crate::expr;
+    use crate::eval::equivalence::check_equivalence;
+
+    #[test]
+    fn test_write_ascii() {
+        let expr = Parser::new("a and not b").parse().unwrap();
+        let aig = Aig::from_expr(&expr).unwrap();
+        assert_eq!(write_ascii(&aig), "aag 3 2 0 1 1\n2\n4\n6\n6 5 2\ni0 a\ni1 b\n");
+    }
+
+    #[test]
+    fn test_round_trip_both_formats() {
+        let expr = Parser::new("(a xor b) or (c -> a)").parse().unwrap();
+        let aig = Aig::from_expr(&expr).unwrap();
+        for bytes in [write_ascii(&aig).into_bytes(), write_binary(&aig)] {
+            let mut bytes = bytes;
+            bytes.extend(comment_section(&["i0 not a symbol".to_string()]).bytes());
+            let parsed = parse(&bytes).unwrap();
+            assert_eq!(parsed.inputs(), aig.inputs());
+            let back = parsed.to_expr(0).unwrap();
+            assert!(check_equivalence(&expr, &back).unwrap().equivalent);
+        }
+    }
+
+    #[test]
+    fn test_parse_unordered_ascii_without_symbols() {
+        // Gate defined after its use, inputs at arbitrary literals
+        let parsed = parse(b"aag 5 2 0 1 2\n8\n4\n11\n10 6 8\n6 8 4\n").unwrap();
+        assert_eq!(parsed.inputs(), ["i0", "i1"]);
+        let expected = expr!(not (i0 and i1));
+        assert!(check_equivalence(&expected, &parsed.to_expr(0).unwrap()).unwrap().equivalent);
+    }
+
+    #[test]
+    fn test_rejects_latches() {
+        assert!(parse(b"aag 1 0 1 0 0\n2 3\n").is_err());
+    }
+}
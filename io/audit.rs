@@ -0,0 +1,113 @@
+use crate::source::Expr;
+use crate::config::VERSION;
+use crate::io::input::InputError;
+use serde::{Serialize, Deserialize};
+use std::cell::RefCell;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditStatus {
+    Ok,
+    Error,
+}
+
+/// One invocation, written as a line of the NDJSON audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the invocation started, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub version: String,
+    pub command: String,
+    /// [`Expr::fingerprint`] of each expression read, as 16 hex digits
+    pub fingerprints: Vec<String>,
+    pub duration_ms: f64,
+    pub status: AuditStatus,
+    /// What the command found, for commands that report one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The error, when the command's input was at fault
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_error: Option<InputError>,
+}
+
+impl AuditRecord {
+    /// Append the record as one line of `path`, creating the file if needed
+    pub fn append_to(&self, path: &Path) -> io::Result<()> {
+        let mut line = serde_json::to_string(self).map_err(io::Error::other)?;
+        line.push('\n');
+        // A single write keeps lines from concurrent invocations whole
+        OpenOptions::new().create(true).append(true).open(path)?.write_all(line.as_bytes())
+    }
+}
+
+/// Collects what an invocation read and found while it runs
+#[derive(Debug, Default)]
+pub struct Audit {
+    fingerprints: RefCell<Vec<u64>>,
+    summary: RefCell<Option<String>>,
+}
+
+impl Audit {
+    /// Note an expression the command read
+    pub fn expression(&self, expr: &Expr) {
+        self.fingerprints.borrow_mut().push(expr.fingerprint());
+    }
+
+    /// Set the result summary
+    pub fn summarize(&self, summary: impl Into<String>) {
+        *self.summary.borrow_mut() = Some(summary.into());
+    }
+
+    /// The record of an invocation of `command` that started at `started`,
+    /// took `elapsed` and failed with `error` if it failed
+    pub fn finish(self, command: &str, started: SystemTime, elapsed: Duration, error: Option<&miette::Report>) -> AuditRecord {
+        AuditRecord {
+            timestamp_ms: started.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64),
+            version: VERSION.to_string(),
+            command: command.to_string(),
+            fingerprints: self.fingerprints.into_inner().iter().map(|fingerprint| format!("{:016x}", fingerprint)).collect(),
+            duration_ms: elapsed.as_secs_f64() * 1000.0,
+            status: if error.is_some() { AuditStatus::Error } else { AuditStatus::Ok },
+            summary: self.summary.into_inner(),
+            error: error.map(|e| e.to_string()),
+            input_error: error.and_then(|e| e.downcast_ref::<InputError>()).cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    #[test]
+    fn test_records_append_as_lines() {
+        let path = std::env::temp_dir().join(format!("ttt-audit-test-{}.jsonl", std::process::id()));
+        let audit = Audit::default();
+        audit.expression(&Parser::new("a").parse().unwrap());
+        audit.summarize("equivalent");
+        let record = audit.finish("eq", UNIX_EPOCH + Duration::from_millis(1500), Duration::from_micros(2500), None);
+        assert_eq!(record.fingerprints, vec!["e4981b2632418b44"]);
+        assert_eq!(record.timestamp_ms, 1500);
+        assert_eq!(record.duration_ms, 2.5);
+
+        let failed = Audit::default().finish("table", SystemTime::now(), Duration::ZERO, Some(&miette::miette!("Parse error")));
+        let empty = Audit::default().finish("table", SystemTime::now(), Duration::ZERO, Some(&InputError::EmptyStdin.into()));
+        assert_eq!(empty.input_error, Some(InputError::EmptyStdin));
+        record.append_to(&path).unwrap();
+        failed.append_to(&path).unwrap();
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines: Vec<AuditRecord> = log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines, vec![record, failed]);
+        assert_eq!(lines[1].status, AuditStatus::Error);
+        assert!(log.lines().next().unwrap().contains(r#""status":"ok","summary":"equivalent""#));
+    }
+}
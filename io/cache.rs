@@ -0,0 +1,140 @@
+use crate::source::{Expr, FINGERPRINT_VERSION};
+use crate::source::fingerprint::fnv1a;
+use crate::config::VERSION;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Identifies one command's output for one expression: the expression's
+/// fingerprint plus everything else that shapes the output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    fingerprint: u64,
+    command: String,
+    /// Written at the top of the entry and checked on lookup, so a hash
+    /// collision or an entry from another version is a miss
+    description: String,
+}
+
+impl CacheKey {
+    /// `options` should render every setting the output depends on
+    pub fn new(expr: &Expr, command: &str, options: &str) -> Self {
+        Self {
+            fingerprint: expr.fingerprint(),
+            command: command.to_string(),
+            description: format!("ttt {} fingerprint v{} {} {:?}", VERSION, FINGERPRINT_VERSION, command, options),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        format!("{:016x}-{}-{:016x}", self.fingerprint, self.command, fnv1a(self.description.as_bytes()))
+    }
+}
+
+/// Formatted command output stored on disk, one file per key.
+///
+/// The cache is best effort: entries that can't be read count as misses and
+/// failed writes are ignored, so a broken cache never fails a command.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `$XDG_CACHE_HOME/ttt`, or `~/.cache/ttt`
+    pub fn default_dir() -> Option<PathBuf> {
+        let non_empty = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+        non_empty("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|home| Path::new(&home).join(".cache")))
+            .map(|dir| dir.join("ttt"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<String> {
+        let contents = fs::read_to_string(self.dir.join(key.file_name())).ok()?;
+        let (description, output) = contents.split_once('\n')?;
+        (description == key.description).then(|| output.to_string())
+    }
+
+    pub fn put(&self, key: &CacheKey, output: &str) {
+        let path = self.dir.join(key.file_name());
+        // Write beside the entry and rename, so concurrent runs never see half an entry
+        let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+        let written = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&partial, format!("{}\n{}", key.description, output)))
+            .and_then(|_| fs::rename(&partial, &path));
+        if written.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+    }
+
+    /// The cached output for `key`, or `compute`'s, which is stored for next time
+    pub fn get_or_insert_with<E>(&self, key: &CacheKey, compute: impl FnOnce() -> Result<String, E>) -> Result<String, E> {
+        if let Some(output) = self.get(key) {
+            return Ok(output);
+        }
+        let output = compute()?;
+        self.put(key, &output);
+        Ok(output)
+    }
+
+    /// Remove every entry, returning how many there were
+    pub fn clear(&self) -> io::Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                fs::remove_file(entry.path())?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn key(input: &str, options: &str) -> CacheKey {
+        CacheKey::new(&Parser::new(input).parse().unwrap(), "table", options)
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let cache = Cache::new(std::env::temp_dir().join(format!("ttt-cache-test-{}", std::process::id())));
+        assert_eq!(cache.get(&key("a and b", "")), None);
+
+        let mut calls = 0;
+        for _ in 0..2 {
+            let output = cache.get_or_insert_with(&key("a and b", ""), || {
+                calls += 1;
+                Ok::<_, ()>("a b result\n".to_string())
+            });
+            assert_eq!(output, Ok("a b result\n".to_string()));
+        }
+        assert_eq!(calls, 1);
+        // Equal trees share entries; other options don't
+        assert!(cache.get(&key("(a) and (b)", "")).is_some());
+        assert_eq!(cache.get(&key("a and b", "json")), None);
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.get(&key("a and b", "")), None);
+        fs::remove_dir(cache.dir()).unwrap();
+        assert_eq!(cache.clear().unwrap(), 0);
+    }
+}
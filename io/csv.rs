@@ -0,0 +1,224 @@
+use crate::eval::{TruthTable, TruthTableRow, Ternary, Variables};
+use std::collections::HashMap;
+use miette::Result;
+
+/// A CSV document split into a header row and data rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvDocument {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CsvDocument {
+    /// Parse CSV text. Blank lines and `#` comment lines are skipped and double-quoted fields are supported.
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'));
+        
+        let headers = match lines.next() {
+            Some((_, line)) => split_record(line),
+            None => return Err(miette::miette!("CSV input is empty; expected a header row")),
+        };
+        
+        let mut rows = Vec::new();
+        for (line_idx, line) in lines {
+            let record = split_record(line);
+            if record.len() != headers.len() {
+                return Err(miette::miette!(
+                    "CSV line {} has {} fields, but the header has {}",
+                    line_idx + 1,
+                    record.len(),
+                    headers.len()
+                ));
+            }
+            rows.push(record);
+        }
+        
+        Ok(Self { headers, rows })
+    }
+    
+    /// Index of a column by (trimmed, exact) name
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == name)
+    }
+    
+    /// Interpret the given columns as boolean values, one assignment per row
+    pub fn assignments(&self, columns: &[usize]) -> Result<Vec<HashMap<String, bool>>> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(row_idx, row)| {
+                columns
+                    .iter()
+                    .map(|&col| {
+                        let value = parse_bool(&row[col]).ok_or_else(|| miette::miette!(
+                            "Invalid boolean value '{}' for column '{}' in data row {}",
+                            row[col],
+                            self.headers[col],
+                            row_idx + 1
+                        ))?;
+                        Ok((self.headers[col].clone(), value))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+    
+    /// Like `assignments`, but cells may also be unknown (`?`, `u`, `unknown` or empty)
+    pub fn ternary_assignments(&self, columns: &[usize]) -> Result<Vec<HashMap<String, Ternary>>> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(row_idx, row)| {
+                columns
+                    .iter()
+                    .map(|&col| {
+                        let value = parse_ternary(&row[col]).ok_or_else(|| miette::miette!(
+                            "Invalid value '{}' for column '{}' in data row {}; expected true, false or unknown",
+                            row[col],
+                            self.headers[col],
+                            row_idx + 1
+                        ))?;
+                        Ok((self.headers[col].clone(), value))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+    
+    /// Interpret the document as an exported truth table: one column per variable plus a `result` column
+    pub fn to_truth_table(&self) -> Result<TruthTable> {
+        let result_col = self.headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case("result"))
+            .ok_or_else(|| miette::miette!("Truth table CSV has no 'result' column"))?;
+        let var_cols: Vec<usize> = (0..self.headers.len()).filter(|&col| col != result_col).collect();
+        let variables = Variables::from_names(var_cols.iter().map(|&col| self.headers[col].clone()))
+            .map_err(|e| miette::miette!("Invalid truth table header: {}", e))?;
+        
+        let assignments = self.assignments(&var_cols)?;
+        let results = self.assignments(&[result_col])?;
+        let rows = assignments
+            .into_iter()
+            .zip(results)
+            .map(|(assignments, result)| TruthTableRow {
+                assignments,
+                result: result.values().all(|&v| v),
+            })
+            .collect();
+        
+        Ok(TruthTable { variables, rows, range: None, sample: None })
+    }
+}
+
+/// Parse a boolean cell value (`true`/`false`, `T`/`F`, `1`/`0`, case-insensitive)
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "t" | "1" => Some(true),
+        "false" | "f" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a three-valued cell: a boolean, or `?`/`u`/`unknown`/empty for unknown
+pub fn parse_ternary(value: &str) -> Option<Ternary> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "?" | "u" | "unknown" | "" => Some(Ternary::Unknown),
+        other => parse_bool(other).map(Ternary::from),
+    }
+}
+
+/// Write fields as one CSV line, quoting those that contain a comma or a quote
+/// so that [`CsvDocument::parse`] reads them back unchanged
+pub fn join_record<'a>(fields: impl IntoIterator<Item = &'a str>) -> String {
+    fields
+        .into_iter()
+        .map(|field| {
+            if field.contains([',', '"']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn split_record(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(ch),
+        }
+    }
+    fields.push(field.trim().to_string());
+    
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_document() {
+        let doc = CsvDocument::parse("a,b,result\ntrue,false,false\n\n1,1,1\n").unwrap();
+        assert_eq!(doc.headers, vec!["a", "b", "result"]);
+        assert_eq!(doc.rows.len(), 2);
+        assert_eq!(doc.rows[1], vec!["1", "1", "1"]);
+    }
+
+    #[test]
+    fn test_quoted_fields() {
+        let doc = CsvDocument::parse("name,value\n\"x, y\",\"say \"\"hi\"\"\"\n").unwrap();
+        assert_eq!(doc.rows[0], vec!["x, y", "say \"hi\""]);
+    }
+
+    #[test]
+    fn test_join_record_round_trips() {
+        let fields = ["x, y", "say \"hi\"", "plain"];
+        let line = join_record(fields);
+        assert_eq!(line, "\"x, y\",\"say \"\"hi\"\"\",plain");
+        assert_eq!(split_record(&line), fields);
+    }
+
+    #[test]
+    fn test_ragged_rows_rejected() {
+        assert!(CsvDocument::parse("a,b\ntrue\n").is_err());
+        assert!(CsvDocument::parse("").is_err());
+    }
+
+    #[test]
+    fn test_to_truth_table() {
+        let doc = CsvDocument::parse("b,a,result\nfalse,false,false\ntrue,false,true\n").unwrap();
+        let table = doc.to_truth_table().unwrap();
+        assert_eq!(table.variables.to_vec(), vec!["a", "b"]);
+        assert_eq!(table.rows.len(), 2);
+        assert!(table.rows[1].assignments["b"]);
+        assert!(table.rows[1].result);
+        
+        assert!(CsvDocument::parse("a,b\n1,0\n").unwrap().to_truth_table().is_err());
+    }
+
+    #[test]
+    fn test_assignments() {
+        let doc = CsvDocument::parse("id,a,b\n7,T,0\n8,f,TRUE\n").unwrap();
+        let rows = doc.assignments(&[1, 2]).unwrap();
+        assert!(rows[0]["a"]);
+        assert!(!rows[0]["b"]);
+        assert!(rows[1]["b"]);
+        assert!(doc.assignments(&[0]).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::eval::{Evaluator, EvaluationError};
+use crate::eval::aig::Aig;
+use crate::eval::normal_forms::Cnf;
+use crate::io::aiger;
+use crate::io::provenance::CommentSyntax;
+use crate::source::Expr;
+use std::collections::HashMap;
+
+/// File formats `export` writes an expression in
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// ASCII AIGER (`aag`)
+    #[default]
+    Aag,
+    /// Binary AIGER (`aig`)
+    Aig,
+    /// DIMACS CNF of the Tseitin encoding, satisfiable exactly when the expression is
+    Cnf,
+}
+
+impl ExportFormat {
+    /// Where the format keeps comments, such as a provenance header
+    pub fn comments(self) -> CommentSyntax {
+        match self {
+            ExportFormat::Aag | ExportFormat::Aig => CommentSyntax::Aiger,
+            ExportFormat::Cnf => CommentSyntax::Dimacs,
+        }
+    }
+}
+
+/// The expression as a file in `format`
+pub fn export(expr: &Expr, format: ExportFormat) -> Result<Vec<u8>, EvaluationError> {
+    Ok(match format {
+        ExportFormat::Aag => aiger::write_ascii(&Aig::from_expr(expr)?).into_bytes(),
+        ExportFormat::Aig => aiger::write_binary(&Aig::from_expr(expr)?),
+        ExportFormat::Cnf => write_dimacs(&Evaluator::to_cnf(expr, true)?).into_bytes(),
+    })
+}
+
+/// Write clauses in the DIMACS format SAT solvers read, numbering the
+/// expression's variables first and then the Tseitin variables, each named
+/// in a `c` line before the problem line
+pub fn write_dimacs(cnf: &Cnf) -> String {
+    let names: Vec<&str> = cnf.variables.iter().chain(cnf.definitions.iter().map(|definition| definition.variable.as_str())).collect();
+    let numbers: HashMap<&str, usize> = names.iter().enumerate().map(|(index, name)| (*name, index + 1)).collect();
+    let mut output = String::new();
+    for (index, name) in names.iter().enumerate() {
+        output.push_str(&format!("c {} {}\n", index + 1, name));
+    }
+    output.push_str(&format!("p cnf {} {}\n", names.len(), cnf.clauses.len()));
+    for clause in &cnf.clauses {
+        for literal in clause {
+            let number = numbers[literal.variable.as_str()];
+            output.push_str(&if literal.negated { format!("-{} ", number) } else { format!("{} ", number) });
+        }
+        output.push_str("0\n");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    #[test]
+    fn test_dimacs_numbers_inputs_then_gates() {
+        let cnf = Evaluator::to_cnf(&Parser::new("a and not b").parse().unwrap(), true).unwrap();
+        let dimacs = write_dimacs(&cnf);
+        let mut lines = dimacs.lines();
+        assert_eq!(lines.by_ref().take(3).collect::<Vec<_>>(), ["c 1 a", "c 2 b", "c 3 t1"]);
+        assert_eq!(lines.next(), Some(format!("p cnf 3 {}", cnf.clauses.len()).as_str()));
+        assert!(lines.all(|line| line.ends_with(" 0") || line == "0"));
+        // `false` is one empty clause
+        let never = Evaluator::to_cnf(&Parser::new("a and not a").parse().unwrap(), true).unwrap();
+        assert!(write_dimacs(&never).ends_with("p cnf 1 1\n0\n"));
+    }
+}
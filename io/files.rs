@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+use miette::{IntoDiagnostic, Result};
+
+/// Expand a path pattern with `*`, `?`, and `**` (any number of directories).
+///
+/// Patterns without wildcards are returned as-is so missing files are reported
+/// when they are read. Matches are sorted for deterministic output.
+pub fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>> {
+    if !pattern.contains(['*', '?']) {
+        return Ok(vec![PathBuf::from(pattern)]);
+    }
+    
+    let absolute = pattern.starts_with('/');
+    let components: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty() && *c != ".").collect();
+    let root = if absolute { PathBuf::from("/") } else { PathBuf::from(".") };
+    
+    let mut matches = Vec::new();
+    expand_components(&root, &components, &mut matches)?;
+    
+    if !absolute {
+        matches = matches
+            .into_iter()
+            .map(|path| path.strip_prefix(".").map(Path::to_path_buf).unwrap_or(path))
+            .collect();
+    }
+    matches.sort();
+    matches.dedup();
+    Ok(matches)
+}
+
+fn expand_components(base: &Path, components: &[&str], matches: &mut Vec<PathBuf>) -> Result<()> {
+    let Some((first, rest)) = components.split_first() else {
+        if base.is_file() {
+            matches.push(base.to_path_buf());
+        }
+        return Ok(());
+    };
+    
+    if *first == "**" {
+        // Zero directories...
+        expand_components(base, rest, matches)?;
+        // ...or descend into every subdirectory and keep the `**`
+        for entry in read_dir_sorted(base)? {
+            if entry.is_dir() {
+                expand_components(&entry, components, matches)?;
+            }
+        }
+    } else if first.contains(['*', '?']) {
+        for entry in read_dir_sorted(base)? {
+            let name = entry.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            if wildcard_match(first, &name) {
+                expand_components(&entry, rest, matches)?;
+            }
+        }
+    } else {
+        let next = base.join(first);
+        if next.exists() {
+            expand_components(&next, rest, matches)?;
+        }
+    }
+    
+    Ok(())
+}
+
+fn read_dir_sorted(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries = std::fs::read_dir(dir)
+        .into_diagnostic()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect::<Vec<_>>();
+    entries.sort();
+    Ok(entries)
+}
+
+/// Match a single path component against a pattern with `*` and `?`
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.rs", "main.rs"));
+        assert!(wildcard_match("m?in.*", "main.rs"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("*.rs", "main.c"));
+        assert!(!wildcard_match("a?", "a"));
+    }
+
+    #[test]
+    fn test_plain_path_passthrough() {
+        assert_eq!(expand_glob("does/not/exist.rs").unwrap(), vec![PathBuf::from("does/not/exist.rs")]);
+    }
+}
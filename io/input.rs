@@ -1,5 +1,84 @@
 use std::io::{self, Read};
-use miette::{IntoDiagnostic, Result};
+use std::path::Path;
+use crate::eval::TruthTable;
+use crate::io::csv::CsvDocument;
+use miette::{Diagnostic, IntoDiagnostic, Result, WrapErr};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use thiserror::Error;
+
+/// How the expression given to a command is encoded
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Text in the language chosen by `--dialect`
+    #[default]
+    Text,
+    /// An expression encoded by `Expr::to_bytes`
+    #[cfg(feature = "binary")]
+    Bin,
+}
+
+/// Where a command's expressions were read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputOrigin {
+    Arguments,
+    Stdin,
+}
+
+impl fmt::Display for InputOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputOrigin::Arguments => write!(f, "as arguments"),
+            InputOrigin::Stdin => write!(f, "on stdin"),
+        }
+    }
+}
+
+/// Input that doesn't hold what the command reads
+#[derive(Error, Debug, Diagnostic, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InputError {
+    #[error("Expected {expected} expression(s) {origin}, got {found}")]
+    #[diagnostic(code(ttt::input::expression_count))]
+    ExpressionCount {
+        expected: usize,
+        found: usize,
+        origin: InputOrigin,
+        #[help]
+        help: String,
+    },
+    
+    #[error("No expression given")]
+    #[diagnostic(
+        code(ttt::input::empty_stdin),
+        help("Pass the expression as an argument, or pipe it to stdin")
+    )]
+    EmptyStdin,
+    
+    #[error("Input is not valid UTF-8: byte {byte:#04x} at offset {offset} (line {line})")]
+    #[diagnostic(
+        code(ttt::input::not_utf8),
+        help("Text input must be UTF-8; re-encode it (e.g. from Latin-1 or UTF-16), or read Expr::to_bytes output with --input-format bin")
+    )]
+    NotUtf8 {
+        /// The first byte that isn't part of a UTF-8 character
+        byte: u8,
+        offset: usize,
+        line: usize,
+    },
+}
+
+impl InputError {
+    fn expression_count(expected: usize, found: usize, origin: InputOrigin) -> Self {
+        let help = match (origin, expected) {
+            (InputOrigin::Stdin, _) => "Write one expression per line".to_string(),
+            (InputOrigin::Arguments, 2) => "Quote each expression as one argument, e.g. ttt eq \"a and b\" \"b and a\"".to_string(),
+            (InputOrigin::Arguments, _) => "Quote each expression as one argument".to_string(),
+        };
+        InputError::ExpressionCount { expected, found, origin, help }
+    }
+}
 
 /// Generic input handler for CLI arguments and stdin
 pub struct InputHandler;
@@ -14,6 +93,26 @@ impl InputHandler {
         }
     }
     
+    /// Get the bytes of a binary expression from the file named in args, or stdin
+    pub fn get_single_binary(args: Vec<String>) -> Result<Vec<u8>> {
+        match args.as_slice() {
+            [] => {
+                let mut bytes = Vec::new();
+                io::stdin().read_to_end(&mut bytes).into_diagnostic()?;
+                Ok(bytes)
+            }
+            [path] => std::fs::read(path)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("Failed to read '{}'", path)),
+            _ => Err(InputError::ExpressionCount {
+                expected: 1,
+                found: args.len(),
+                origin: InputOrigin::Arguments,
+                help: "Binary input is read from a single file".to_string(),
+            }.into()),
+        }
+    }
+    
     /// Get exactly two expressions for equivalence checking
     pub fn get_expression_pair(expressions: Vec<String>) -> Result<(String, String)> {
         match expressions.len() {
@@ -23,17 +122,11 @@ impl InputHandler {
                 let input = Self::read_from_stdin()?;
                 let lines: Vec<&str> = input.trim().lines().collect();
                 if lines.len() != 2 {
-                    return Err(miette::miette!(
-                        "Expected exactly two expressions for equivalence check (one per line), got {}.\nPlease provide two expressions separated by newlines or as separate arguments.", 
-                        lines.len()
-                    ));
+                    return Err(InputError::expression_count(2, lines.len(), InputOrigin::Stdin).into());
                 }
                 Ok((lines[0].to_string(), lines[1].to_string()))
             }
-            _ => Err(miette::miette!(
-                "Equivalence check requires exactly two expressions as arguments, got {}.\nUsage: ttt eq \"expr1\" \"expr2\"", 
-                expressions.len()
-            )),
+            found => Err(InputError::expression_count(2, found, InputOrigin::Arguments).into()),
         }
     }
     
@@ -47,36 +140,98 @@ impl InputHandler {
                 .map(|line| line.to_string())
                 .collect();
             
-            if let Some(count) = expected_count {
-                if expressions.len() != count {
-                    return Err(miette::miette!(
-                        "Expected exactly {} expressions, got {}", 
-                        count, 
-                        expressions.len()
-                    ));
-                }
+            if let Some(count) = expected_count
+                && expressions.len() != count
+            {
+                return Err(InputError::expression_count(count, expressions.len(), InputOrigin::Stdin).into());
             }
             
             Ok(expressions)
         } else {
-            if let Some(count) = expected_count {
-                if args.len() != count {
-                    return Err(miette::miette!(
-                        "Expected exactly {} expressions as arguments, got {}", 
-                        count, 
-                        args.len()
-                    ));
-                }
+            if let Some(count) = expected_count
+                && args.len() != count
+            {
+                return Err(InputError::expression_count(count, args.len(), InputOrigin::Arguments).into());
             }
             Ok(args)
         }
     }
     
-    /// Read input from stdin
+    /// Split an equivalence chain `e1 <-> e2 <-> …` (or `↔`) into its steps.
+    ///
+    /// Only separators outside parentheses split the chain.
+    pub fn split_chain(input: &str) -> Vec<&str> {
+        let mut steps = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        let mut chars = input.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                '↔' if depth == 0 => {
+                    steps.push(&input[start..i]);
+                    start = i + c.len_utf8();
+                }
+                '<' if depth == 0 && input[i..].starts_with("<->") => {
+                    steps.push(&input[start..i]);
+                    start = i + 3;
+                    chars.next();
+                    chars.next();
+                }
+                _ => {}
+            }
+        }
+        steps.push(&input[start..]);
+        steps
+    }
+    
+    /// Read the full contents of a text file, as [`InputHandler::decode`] reads it
+    pub fn read_file(path: &Path) -> Result<String> {
+        let bytes = std::fs::read(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to read '{}'", path.display()))?;
+        Self::decode(bytes).map_err(|e| miette::Report::new(e).wrap_err(format!("Failed to read '{}'", path.display())))
+    }
+    
+    /// Load a previously exported truth table (JSON by `.json` extension, CSV otherwise)
+    pub fn read_truth_table(path: &Path) -> Result<TruthTable> {
+        let contents = Self::read_file(path)?;
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            serde_json::from_str(&contents)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("'{}' is not a JSON truth table", path.display()))
+        } else {
+            CsvDocument::parse(&contents)?
+                .to_truth_table()
+                .wrap_err_with(|| format!("'{}' is not a CSV truth table", path.display()))
+        }
+    }
+    
+    /// Read input from stdin, which must hold some text
     fn read_from_stdin() -> Result<String> {
-        let mut input = String::new();
-        io::stdin().read_to_string(&mut input).into_diagnostic()?;
-        Ok(input.trim().to_string())
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes).into_diagnostic()?;
+        match Self::decode(bytes)?.trim() {
+            "" => Err(InputError::EmptyStdin.into()),
+            trimmed => Ok(trimmed.to_string()),
+        }
+    }
+    
+    /// Text from UTF-8 bytes, dropping a leading byte order mark and reading
+    /// CRLF line endings as LF, as files saved by Windows tools have them
+    pub fn decode(bytes: Vec<u8>) -> Result<String, InputError> {
+        let text = String::from_utf8(bytes).map_err(|e| {
+            let offset = e.utf8_error().valid_up_to();
+            let bytes = e.as_bytes();
+            InputError::NotUtf8 {
+                byte: bytes[offset],
+                offset,
+                line: bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1,
+            }
+        })?;
+        let text = text.strip_prefix('\u{FEFF}').unwrap_or(&text);
+        Ok(text.replace("\r\n", "\n"))
     }
 }
 
@@ -124,6 +279,37 @@ mod tests {
     fn test_multiple_expressions_count_mismatch() {
         let args = vec!["expr1".to_string(), "expr2".to_string()];
         let result = InputHandler::get_multiple_expressions(args, Some(3));
-        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<InputError>(),
+            Some(InputError::ExpressionCount { expected: 3, found: 2, origin: InputOrigin::Arguments, .. })
+        ));
+    }
+    
+    #[test]
+    fn test_decode() {
+        assert_eq!(InputHandler::decode(b"\xef\xbb\xbfa,b\r\n1,0\r\n".to_vec()).unwrap(), "a,b\n1,0\n");
+        let error = InputHandler::decode(b"a and b\r\nc \xe9 d".to_vec()).unwrap_err();
+        assert_eq!(error, InputError::NotUtf8 { byte: 0xe9, offset: 11, line: 2 });
+        assert_eq!(error.to_string(), "Input is not valid UTF-8: byte 0xe9 at offset 11 (line 2)");
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(json, r#"{"kind":"not_utf8","byte":233,"offset":11,"line":2}"#);
+    }
+    
+    #[test]
+    fn test_read_file_decodes_windows_text() {
+        let path = std::env::temp_dir().join(format!("ttt-input-test-{}.csv", std::process::id()));
+        std::fs::write(&path, b"\xef\xbb\xbfa,result\r\n0,1\r\n1,0\r\n").unwrap();
+        let table = InputHandler::read_truth_table(&path);
+        std::fs::remove_file(&path).unwrap();
+        let table = table.unwrap();
+        assert_eq!((table.variables.len(), table.rows.len()), (1, 2));
+    }
+
+    #[test]
+    fn test_split_chain_outside_parentheses() {
+        let steps = InputHandler::split_chain("a <-> (b <-> c) ↔ d");
+        let texts: Vec<&str> = steps.iter().map(|s| s.trim()).collect();
+        assert_eq!(texts, vec!["a", "(b <-> c)", "d"]);
     }
-}
\ No newline at end of file
+}
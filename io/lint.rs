@@ -0,0 +1,200 @@
+use crate::source::{Parser, Expr};
+use crate::eval::Evaluator;
+use serde::{Serialize, Deserialize};
+
+/// Marker for a comment that states an expression which must parse
+pub const EXPRESSION_MARKER: &str = "ttt:";
+/// Marker for a comment that states an equivalence, written `A == B`
+pub const EQUIVALENCE_MARKER: &str = "ttt-eq:";
+
+/// Outcome of checking one annotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintStatus {
+    Ok,
+    ParseError,
+    NotEquivalent,
+    EvaluationError,
+}
+
+/// One `ttt:` or `ttt-eq:` annotation found in a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintFinding {
+    pub path: String,
+    pub line: usize,
+    pub annotation: String,
+    pub status: LintStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Results of linting a set of files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LintReport {
+    pub files_scanned: usize,
+    pub findings: Vec<LintFinding>,
+}
+
+impl LintReport {
+    pub fn failures(&self) -> usize {
+        self.findings.iter().filter(|f| f.status != LintStatus::Ok).count()
+    }
+    
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+}
+
+/// Scan source text for annotated comment lines and check each one.
+///
+/// Annotations may follow any common line-comment marker (`//`, `#`, `--`, `;`, `/*`, `*`).
+/// When `check_equivalences` is false, `ttt-eq:` annotations are only parsed.
+pub fn lint_source(path: &str, text: &str, check_equivalences: bool) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    
+    for (idx, line) in text.lines().enumerate() {
+        let Some(body) = comment_body(line) else {
+            continue;
+        };
+        
+        let (annotation, status, message) = if let Some(rest) = body.strip_prefix(EQUIVALENCE_MARKER) {
+            let rest = strip_block_end(rest);
+            let (status, message) = check_equivalence_annotation(rest, check_equivalences);
+            (format!("{} {}", EQUIVALENCE_MARKER, rest), status, message)
+        } else if let Some(rest) = body.strip_prefix(EXPRESSION_MARKER) {
+            let rest = strip_block_end(rest);
+            let (status, message) = match parse(rest) {
+                Ok(_) => (LintStatus::Ok, None),
+                Err(message) => (LintStatus::ParseError, Some(message)),
+            };
+            (format!("{} {}", EXPRESSION_MARKER, rest), status, message)
+        } else {
+            continue;
+        };
+        
+        findings.push(LintFinding {
+            path: path.to_string(),
+            line: idx + 1,
+            annotation,
+            status,
+            message,
+        });
+    }
+    
+    findings
+}
+
+fn comment_body(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    ["//", "/*", "#", "--", ";", "*"]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+        .map(|rest| rest.trim_start_matches(['/', '*', '!', '#', ';', '-']).trim_start())
+}
+
+fn strip_block_end(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_suffix("*/").unwrap_or(text).trim()
+}
+
+fn parse(input: &str) -> Result<Expr, String> {
+    Parser::new(input).parse().map_err(|e| format!("{}", e))
+}
+
+/// The sides of the one `==` outside parentheses and quoted names, if there is exactly one
+fn split_equivalence(text: &str) -> Option<(&str, &str)> {
+    let (mut depth, mut quote, mut found) = (0usize, None, Vec::new());
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '`' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, '=') if depth == 0 && chars.next_if(|&(_, next)| next == '=').is_some() => found.push(index),
+            _ => {}
+        }
+    }
+    match found[..] {
+        [index] => Some((&text[..index], &text[index + 2..])),
+        _ => None,
+    }
+}
+
+fn check_equivalence_annotation(text: &str, check: bool) -> (LintStatus, Option<String>) {
+    let Some((left, right)) = split_equivalence(text) else {
+        return (LintStatus::ParseError, Some("expected `A == B` with one `==` outside parentheses".to_string()));
+    };
+    
+    let (left, right) = match (parse(left.trim()), parse(right.trim())) {
+        (Ok(l), Ok(r)) => (l, r),
+        (Err(e), _) => return (LintStatus::ParseError, Some(format!("left side: {}", e))),
+        (_, Err(e)) => return (LintStatus::ParseError, Some(format!("right side: {}", e))),
+    };
+    
+    if !check {
+        return (LintStatus::Ok, None);
+    }
+    
+    match Evaluator::check_equivalence(&left, &right) {
+        Ok(result) if result.equivalent => (LintStatus::Ok, None),
+        Ok(result) => {
+            let counterexample = result.differences.first().map(|diff| {
+                result.variables
+                    .iter()
+                    .map(|var| format!("{}={}", var, if diff.assignment[var] { "T" } else { "F" }))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            });
+            (LintStatus::NotEquivalent, Some(format!(
+                "not equivalent (e.g. {})",
+                counterexample.unwrap_or_default()
+            )))
+        }
+        Err(e) => (LintStatus::EvaluationError, Some(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "\
+fn guard() {
+    // ttt: a and (b or c)
+    let x = 1; // not an annotation: ttt: a
+    # ttt-eq: not (a and b) == not a or not b
+    /* ttt-eq: a or b == a and b */
+    -- ttt: a and
+}
+";
+
+    #[test]
+    fn test_lint_source() {
+        let findings = lint_source("guard.rs", SOURCE, true);
+        let statuses: Vec<_> = findings.iter().map(|f| (f.line, f.status)).collect();
+        assert_eq!(statuses, vec![
+            (2, LintStatus::Ok),
+            (4, LintStatus::Ok),
+            (5, LintStatus::NotEquivalent),
+            (6, LintStatus::ParseError),
+        ]);
+        assert_eq!(findings[2].annotation, "ttt-eq: a or b == a and b");
+    }
+
+    #[test]
+    fn test_parse_only() {
+        let findings = lint_source("guard.rs", SOURCE, false);
+        assert_eq!(findings[2].status, LintStatus::Ok);
+        assert_eq!(findings[3].status, LintStatus::ParseError);
+    }
+
+    #[test]
+    fn test_equivalence_inside_a_side() {
+        let findings = lint_source("eq.rs", "// ttt-eq: (a == b) == (b == a)\n// ttt-eq: `x==y` == `x==y`\n", true);
+        assert!(findings.iter().all(|finding| finding.status == LintStatus::Ok), "{:?}", findings);
+        let findings = lint_source("eq.rs", "// ttt-eq: a == b == c\n// ttt-eq: (a == b)\n", true);
+        assert!(findings.iter().all(|finding| finding.status == LintStatus::ParseError), "{:?}", findings);
+    }
+}
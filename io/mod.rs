@@ -1,2 +1,15 @@
 pub mod input;
 pub mod output;
+pub mod csv;
+pub mod files;
+pub mod lint;
+pub mod aiger;
+pub mod export;
+pub mod vector;
+pub mod cache;
+pub mod audit;
+pub mod repl;
+pub mod terminal;
+pub mod notebook;
+pub mod report;
+pub mod provenance;
@@ -0,0 +1,190 @@
+use crate::eval::Evaluator;
+use crate::eval::truth_table::TruthTable;
+use crate::io::output::{OutputFormat, format_truth_table};
+use crate::source::{Expr, Library};
+use miette::{GraphicalReportHandler, GraphicalTheme, Result};
+use serde::{Serialize, Deserialize};
+
+/// One cell to run, read from a line of the server's NDJSON input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    /// Echoed in the response so the client can match them up
+    #[serde(default)]
+    pub id: serde_json::Value,
+    pub code: String,
+}
+
+/// What running a cell produced, written as a line of NDJSON
+#[derive(Debug, Clone, Serialize)]
+pub struct Response {
+    pub id: serde_json::Value,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+/// The fields of a Jupyter `execute_reply`, so a kernel can pass them on as they are
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Outcome {
+    /// One display bundle per expression in the cell
+    Ok { outputs: Vec<DisplayData> },
+    Error {
+        /// The diagnostic code, such as `ttt::parser::unexpected_token`
+        ename: String,
+        evalue: String,
+        /// The rendered diagnostic, one line per entry
+        traceback: Vec<String>,
+    },
+}
+
+/// A Jupyter MIME bundle showing one truth table
+#[derive(Debug, Clone, Serialize)]
+pub struct DisplayData {
+    #[serde(rename = "text/plain")]
+    pub plain: String,
+    #[serde(rename = "text/html")]
+    pub html: String,
+    #[serde(rename = "application/json")]
+    pub json: TruthTable,
+}
+
+/// The state notebook cells share: the definitions made so far.
+///
+/// A cell holds `def` and `alias` lines, as in a library file, and
+/// expressions, one per line, each shown as a truth table. Blank lines and `#`
+/// comments are skipped.
+pub struct Kernel {
+    library: Library,
+}
+
+impl Kernel {
+    /// Start from `library`'s definitions
+    pub fn new(library: Library) -> Self {
+        Self { library }
+    }
+
+    /// Run one cell, reading expressions with `parse` and the definitions so
+    /// far; a failing line stops the cell, keeping the definitions before it
+    pub fn execute(&mut self, request: Request, parse: &impl Fn(&str, &Library) -> Result<Expr>) -> Response {
+        let outcome = match self.run(&request.code, parse) {
+            Ok(outputs) => Outcome::Ok { outputs },
+            Err(report) => error_outcome(&report),
+        };
+        Response { id: request.id, outcome }
+    }
+
+    /// The response to an input line that isn't a request
+    pub fn malformed(error: &serde_json::Error) -> Response {
+        let report = miette::miette!("Expected a request such as {{\"id\": 1, \"code\": \"a and b\"}}: {}", error);
+        Response { id: serde_json::Value::Null, outcome: error_outcome(&report) }
+    }
+
+    fn run(&mut self, code: &str, parse: &impl Fn(&str, &Library) -> Result<Expr>) -> Result<Vec<DisplayData>> {
+        let mut outputs = Vec::new();
+        for (idx, line) in code.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with("def ") || line.starts_with("alias ") {
+                let definitions = Library::parse(line).map_err(|e| miette::miette!("Line {}: {}", idx + 1, e))?;
+                self.library.extend(definitions).map_err(|name| miette::miette!("Line {}: '{}' is already defined", idx + 1, name))?;
+                continue;
+            }
+            let expr = parse(line, &self.library)?;
+            let table = Evaluator::generate_truth_table(&expr).map_err(|e| miette::miette!("{}", e))?;
+            outputs.push(DisplayData {
+                plain: format_truth_table(&table, &OutputFormat::Table),
+                html: html_truth_table(&expr, &table),
+                json: table,
+            });
+        }
+        Ok(outputs)
+    }
+}
+
+fn error_outcome(report: &miette::Report) -> Outcome {
+    let mut rendered = String::new();
+    let handler = GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor());
+    if handler.render_report(&mut rendered, report.as_ref()).is_err() {
+        rendered = report.to_string();
+    }
+    Outcome::Error {
+        ename: report.code().map_or_else(|| "Error".to_string(), |code| code.to_string()),
+        evalue: report.to_string(),
+        traceback: rendered.lines().map(str::to_string).collect(),
+    }
+}
+
+/// The truth table as an HTML table captioned with its expression, true
+/// results marked with the `ttt-true` class
+pub fn html_truth_table(expr: &Expr, table: &TruthTable) -> String {
+    let mut html = String::from("<table class=\"ttt-truth-table\">\n");
+    html.push_str(&format!("<caption>{}</caption>\n<thead><tr>", escape_html(&expr.to_string())));
+    for var in table.variables.iter() {
+        html.push_str(&format!("<th>{}</th>", escape_html(var)));
+    }
+    html.push_str("<th>Result</th></tr></thead>\n<tbody>\n");
+    for row in &table.rows {
+        html.push_str("<tr>");
+        for var in table.variables.iter() {
+            html.push_str(if row.assignments.get(var).copied().unwrap_or(false) { "<td>T</td>" } else { "<td>F</td>" });
+        }
+        html.push_str(if row.result { "<td class=\"ttt-true\">T</td>" } else { "<td>F</td>" });
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn parse(input: &str, library: &Library) -> Result<Expr> {
+        Ok(Parser::new(input).with_library(library.clone()).parse()?)
+    }
+
+    fn request(code: &str) -> Request {
+        Request { id: serde_json::json!(7), code: code.to_string() }
+    }
+
+    #[test]
+    fn test_cells_share_definitions() {
+        let mut kernel = Kernel::new(Library::new());
+        let response = kernel.execute(request("# majority\ndef maj(x, y, z) := (x and y) or (x and z) or (y and z)"), &parse);
+        assert!(matches!(&response.outcome, Outcome::Ok { outputs } if outputs.is_empty()));
+
+        let response = kernel.execute(request("maj(a, b, c)\n\nnot a"), &parse);
+        let Outcome::Ok { outputs } = &response.outcome else {
+            panic!("expected outputs, got {:?}", response.outcome);
+        };
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].json.rows.len(), 8);
+        assert_eq!(outputs[0].html.matches("class=\"ttt-true\"").count(), 4);
+        assert!(outputs[1].html.starts_with("<table class=\"ttt-truth-table\">\n<caption>¬a</caption>\n<thead><tr><th>a</th><th>Result</th>"));
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["id"], 7);
+        assert_eq!(json["status"], "ok");
+        assert!(json["outputs"][1]["text/plain"].as_str().unwrap().contains("Result"));
+    }
+
+    #[test]
+    fn test_errors_follow_jupyter() {
+        let mut kernel = Kernel::new(Library::new());
+        let json = serde_json::to_value(kernel.execute(request("a and"), &parse)).unwrap();
+        assert_eq!(json["status"], "error");
+        assert_eq!(json["ename"], "ttt::parser::unexpected_eof");
+        assert!(json["traceback"].as_array().unwrap().len() > 1);
+
+        let json = serde_json::to_value(Kernel::malformed(&serde_json::from_str::<Request>("a and b").unwrap_err())).unwrap();
+        assert_eq!((json["id"].clone(), json["status"].clone()), (serde_json::Value::Null, serde_json::json!("error")));
+        assert_eq!(escape_html("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
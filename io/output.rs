@@ -1,5 +1,10 @@
-use crate::eval::{TruthTable, EquivalenceCheck, Reduction, EquivalenceDifference};
-use crate::config::MAX_DIFFERENCES_TO_SHOW;
+use crate::eval::{TruthTable, EquivalenceCheck, Reduction, EquivalenceDifference, TableDiff, SubexpressionReport, SnippetRewrite, PrimeImplicantReport, ChainProof, Expansion, InfluenceReport, ThresholdAnalysis, Explanation, ExplanationReport, VariableWeight, Abduction, Propagation, Ternary, ThreeValuedTable, CutSet, CutSetReport, GroupSummary, SpectrumReport, WalshCoefficient, BiasReport, Satisfiability, ClassificationReport, Cnf, Dnf, Nnf, AnfReport, Conversion, Report, Properties, KarnaughMap, SboxReport, DegreeReport, Synthesis, TableSample, RowRange, TableView, Projection, ProjectedResult, Trace, Variables, Warning};
+use crate::source::{Expr, Precedence, Rewrite, quote_identifier};
+use crate::io::lint::{LintReport, LintStatus};
+use crate::io::csv::{CsvDocument, join_record};
+use crate::eval::regress::{RegressionReport, RegressionStatus};
+use std::collections::{BTreeSet, HashMap};
+use crate::config::{MAX_DIFFERENCES_TO_SHOW, Capabilities, Limits};
 use serde_json;
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -12,22 +17,318 @@ pub enum OutputFormat {
     Csv,
     /// Nuon format
     Nuon,
+    /// Deterministic ASCII-only table format with `0`/`1` values, for snapshot tests
+    Plain,
+}
+
+/// Encoding for a single column of a truth table
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColumnFormat {
+    /// The values as one string of `0`s and `1`s (default)
+    #[default]
+    Bits,
+    /// One `0` or `1` per line
+    Lines,
 }
 
 pub trait Formatter {
     fn format_truth_table(&self, table: &TruthTable) -> String;
     fn format_equivalence_result(&self, check: &EquivalenceCheck, left_str: &str, right_str: &str) -> String;
     fn format_reduction_result(&self, reduction: &Reduction) -> String;
+    fn format_table_diff(&self, diff: &TableDiff, left_name: &str, right_name: &str) -> String;
+    fn format_subexpressions(&self, report: &SubexpressionReport) -> String;
+    fn format_snippet_rewrite(&self, rewrite: &SnippetRewrite) -> String;
+    fn format_lint_report(&self, report: &LintReport) -> String;
+    fn format_prime_implicants(&self, report: &PrimeImplicantReport) -> String;
+    fn format_chain_proof(&self, proof: &ChainProof) -> String;
+    fn format_expansion(&self, expansion: &Expansion) -> String;
+    fn format_influence(&self, report: &InfluenceReport) -> String;
+    fn format_threshold(&self, analysis: &ThresholdAnalysis) -> String;
+    fn format_explanations(&self, report: &ExplanationReport) -> String;
+    fn format_abduction(&self, abduction: &Abduction) -> String;
+    fn format_propagation(&self, propagation: &Propagation) -> String;
+    fn format_three_valued_table(&self, table: &ThreeValuedTable) -> String;
+    fn format_vectors(&self, document: &CsvDocument, results: &[bool]) -> String;
+    fn format_cut_sets(&self, report: &CutSetReport) -> String;
+    fn format_group_summary(&self, summary: &GroupSummary) -> String;
+    fn format_spectrum(&self, report: &SpectrumReport) -> String;
+    fn format_bias(&self, report: &BiasReport) -> String;
+    fn format_sbox(&self, report: &SboxReport) -> String;
+    fn format_degree(&self, report: &DegreeReport) -> String;
+    fn format_capabilities(&self, info: &Capabilities) -> String;
+    fn format_synthesis(&self, synthesis: &Synthesis) -> String;
+    fn format_rewrite(&self, rewrite: &Rewrite) -> String;
+    fn format_table_view(&self, view: &TableView) -> String;
+    fn format_projection(&self, projection: &Projection) -> String;
+    fn format_trace(&self, trace: &Trace) -> String;
+    fn format_satisfiability(&self, report: &Satisfiability) -> String;
+    fn format_classification(&self, report: &ClassificationReport) -> String;
+    fn format_regression(&self, report: &RegressionReport) -> String;
+    fn format_cnf(&self, cnf: &Cnf) -> String;
+    fn format_dnf(&self, dnf: &Dnf) -> String;
+    fn format_nnf(&self, nnf: &Nnf) -> String;
+    fn format_anf(&self, report: &AnfReport) -> String;
+    fn format_conversion(&self, conversion: &Conversion) -> String;
+    fn format_report(&self, report: &Report) -> String;
+}
+
+/// The named lists of a capability report, labelled for display
+fn capability_lists(info: &Capabilities) -> [(&'static str, &[String]); 6] {
+    [
+        ("features", &info.features),
+        ("commands", &info.commands),
+        ("dialects", &info.dialects),
+        ("notations", &info.notations),
+        ("input formats", &info.input_formats),
+        ("output formats", &info.output_formats),
+    ]
+}
+
+fn limit_entries(limits: &Limits) -> [(&'static str, usize); 12] {
+    [
+        ("max_variables", limits.max_variables),
+        ("max_variable_name_length", limits.max_variable_name_length),
+        ("max_nesting_depth", limits.max_nesting_depth),
+        ("max_three_valued_variables", limits.max_three_valued_variables),
+        ("max_threshold_variables", limits.max_threshold_variables),
+        ("max_immunity_variables", limits.max_immunity_variables),
+        ("max_sbox_bits", limits.max_sbox_bits),
+        ("max_operator_arity", limits.max_operator_arity),
+        ("max_expansion_size", limits.max_expansion_size),
+        ("max_reduction_steps", limits.max_reduction_steps),
+        ("max_synthesis_variables", limits.max_synthesis_variables),
+        ("max_sample_rows", limits.max_sample_rows),
+    ]
+}
+
+/// "N rows of 2^n assignments, seed S" for a sampled table
+fn sample_note(sample: &TableSample) -> String {
+    format!("{} row(s) of 2^{} assignments, seed {}", sample.rows, sample.variables, sample.seed)
+}
+
+fn projected_result_word(result: ProjectedResult) -> &'static str {
+    match result {
+        ProjectedResult::True => "true",
+        ProjectedResult::False => "false",
+        ProjectedResult::Mixed => "mixed",
+    }
+}
+
+fn bool_symbol(value: bool) -> &'static str {
+    if value { "T" } else { "F" }
+}
+
+fn bool_word(value: bool) -> &'static str {
+    if value { "true" } else { "false" }
+}
+
+fn ternary_word(value: Ternary) -> &'static str {
+    value.known().map_or("unknown", bool_word)
+}
+
+fn format_assignment(variables: &Variables, assignment: &HashMap<String, bool>) -> String {
+    variables
+        .iter()
+        .filter_map(|var| assignment.get(var).map(|&value| format!("{}={}", quote_identifier(var), bool_symbol(value))))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An assignment with its variables in alphabetical order, as `a=T b=F`
+fn sorted_assignment(assignment: &HashMap<String, bool>) -> String {
+    let mut names: Vec<&String> = assignment.keys().collect();
+    names.sort();
+    names.iter().map(|name| format!("{}={}", quote_identifier(name), bool_symbol(assignment[*name]))).collect::<Vec<_>>().join(" ")
+}
+
+/// The statuses of a regression report, in the order they are summarized
+const REGRESSION_STATUSES: [RegressionStatus; 6] = [
+    RegressionStatus::Unchanged,
+    RegressionStatus::Syntactic,
+    RegressionStatus::Semantic,
+    RegressionStatus::Added,
+    RegressionStatus::Removed,
+    RegressionStatus::Error,
+];
+
+fn regression_status_word(status: RegressionStatus) -> &'static str {
+    match status {
+        RegressionStatus::Unchanged => "unchanged",
+        RegressionStatus::Syntactic => "syntactic",
+        RegressionStatus::Semantic => "semantic",
+        RegressionStatus::Added => "added",
+        RegressionStatus::Removed => "removed",
+        RegressionStatus::Error => "error",
+    }
+}
+
+/// The responsible literals of an explanation, as `a=T b=F`
+fn format_reason(explanation: &Explanation) -> String {
+    explanation.reason
+        .iter()
+        .map(|var| format!("{}={}", quote_identifier(var), bool_symbol(explanation.assignment.get(var).copied().unwrap_or(false))))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Events of a cut set followed by the negated absent events
+fn cut_set_literals(cut_set: &CutSet) -> Vec<String> {
+    cut_set.events
+        .iter()
+        .cloned()
+        .chain(cut_set.absent.iter().map(|var| format!("¬{}", var)))
+        .collect()
+}
+
+/// The linear function of a Walsh coefficient, as `a⊕c` or `0` for the constant
+fn walsh_mask(coefficient: &WalshCoefficient) -> String {
+    if coefficient.mask.is_empty() { "0".to_string() } else { coefficient.mask.join("⊕") }
+}
+
+/// Render weights as `2a + b - c`, leaving out zero weights
+fn linear_form(weights: &[VariableWeight]) -> String {
+    let mut output = String::new();
+    for w in weights.iter().filter(|w| w.weight != 0) {
+        let magnitude = w.weight.abs();
+        let sign = match (output.is_empty(), w.weight < 0) {
+            (true, false) => "",
+            (true, true) => "-",
+            (false, false) => " + ",
+            (false, true) => " - ",
+        };
+        let coefficient = if magnitude == 1 { String::new() } else { magnitude.to_string() };
+        output.push_str(&format!("{}{}{}", sign, coefficient, w.variable));
+    }
+    if output.is_empty() { "0".to_string() } else { output }
+}
+
+/// `1` or `0`, the plain formatter's values
+fn plain_bit(value: bool) -> &'static str {
+    if value { "1" } else { "0" }
+}
+
+fn plain_ternary(value: Ternary) -> &'static str {
+    value.known().map_or("x", plain_bit)
+}
+
+/// The range and sample lines that head a table, as the table formatter writes them
+fn plain_notes(range: Option<&RowRange>, sample: Option<&TableSample>) -> String {
+    let mut output = String::new();
+    if let Some(range) = range {
+        output.push_str(&format!("Rows {}..{} of {}\n", range.start, range.end, range.total));
+    }
+    if let Some(sample) = sample {
+        output.push_str(&format!("Random sample: {}\n", sample_note(sample)));
+    }
+    output
+}
+
+/// Left-aligned columns as wide as their widest entry, separated by one space,
+/// with no trailing whitespace
+fn plain_grid(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(String::len).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut output = String::new();
+    for line in std::iter::once(header).chain(rows.iter().map(Vec::as_slice)) {
+        let cells: Vec<String> = line.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+        output.push_str(cells.join(" ").trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// Table output rewritten in ASCII: operators as `ttt pretty --style ascii`
+/// writes them, `·` as `*`, `✓`/`✗` as `+`/`-`, standalone `T`/`F` values as `1`/`0`, and
+/// any other non-ASCII character, such as one in a variable name, as `\u{...}`
+fn plain_text(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    let mut previous: Option<char> = None;
+    while let Some(c) = chars.next() {
+        let identifier = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+        match c {
+            '`' => {
+                quoted = !quoted;
+                output.push(c);
+            }
+            'T' | 'F' if !quoted && !identifier(previous.as_ref()) && !identifier(chars.peek()) => {
+                output.push(if c == 'T' { '1' } else { '0' });
+            }
+            '∧' => output.push_str("&&"),
+            '∨' => output.push_str("||"),
+            '¬' => output.push('!'),
+            '↑' => output.push_str("nand"),
+            '↓' => output.push_str("nor"),
+            '⊕' => output.push('^'),
+            '→' | '⟶' => output.push_str("->"),
+            '↔' => output.push_str("<->"),
+            '∀' => output.push_str("forall "),
+            '∃' => output.push_str("exists "),
+            '⊤' => output.push_str("true"),
+            '≥' => output.push_str(">="),
+            '≤' => output.push_str("<="),
+            '✓' => output.push('+'),
+            '✗' => output.push('-'),
+            '▸' => output.push('>'),
+            '—' => output.push_str("--"),
+            '·' => output.push('*'),
+            '\r' => {}
+            c if c.is_ascii() => output.push(c),
+            c => output.push_str(&format!("\\u{{{:04x}}}", c as u32)),
+        }
+        previous = Some(c);
+    }
+    output
+}
+
+/// The properties of a report with their labels, in the order they are listed
+pub(crate) fn property_entries(properties: &Properties) -> [(&'static str, String); 8] {
+    [
+        ("true rows", format!("{} of {}", properties.true_rows, properties.rows)),
+        ("balanced", bool_word(properties.balanced).to_string()),
+        ("monotone", bool_word(properties.monotone).to_string()),
+        ("self-dual", bool_word(properties.self_dual).to_string()),
+        ("affine", bool_word(properties.affine).to_string()),
+        ("symmetric", bool_word(properties.symmetric).to_string()),
+        ("degree", properties.degree.to_string()),
+        ("essential", properties.essential.iter().map(|name| quote_identifier(name)).collect::<Vec<_>>().join(" ")),
+    ]
+}
+
+/// A Karnaugh map as rows of cells, headed by `rows\columns` and the column labels
+pub(crate) fn karnaugh_cells(map: &KarnaughMap) -> Vec<Vec<String>> {
+    let compact = map.row_variables.iter().chain(&map.column_variables).all(|name| name.chars().count() == 1);
+    let group = |names: &[String]| names.iter().map(|name| quote_identifier(name)).collect::<Vec<_>>().join(if compact { "" } else { "," });
+    let corner = format!("{}\\{}", group(&map.row_variables), group(&map.column_variables));
+    std::iter::once(std::iter::once(corner).chain(map.column_labels.iter().cloned()).collect())
+        .chain(map.row_labels.iter().zip(&map.cells).map(|(label, cells)| {
+            std::iter::once(label.clone()).chain(cells.iter().map(|&cell| plain_bit(cell).to_string())).collect()
+        }))
+        .collect()
 }
 
 pub struct TableFormatter;
 pub struct JsonFormatter;
 pub struct CsvFormatter;
 pub struct NuonFormatter;
+/// The table output in ASCII only, with `0`/`1` for values and columns as wide
+/// as their widest entry, for golden-file snapshots
+pub struct PlainFormatter;
 
 impl Formatter for TableFormatter {
     fn format_truth_table(&self, table: &TruthTable) -> String {
         let mut output = String::new();
+        if let Some(range) = &table.range {
+            output.push_str(&format!("Rows {}..{} of {}\n", range.start, range.end, range.total));
+        }
+        if let Some(sample) = &table.sample {
+            output.push_str(&format!("Random sample: {}\n", sample_note(sample)));
+        }
         
         // Header
         for var in table.variables.iter() {
@@ -60,10 +361,16 @@ impl Formatter for TableFormatter {
             output.push_str("✓ Expressions are equivalent\n");
             output.push_str(&format!("  Left:  {}\n", left_str));
             output.push_str(&format!("  Right: {}\n", right_str));
+            if check.ignored > 0 {
+                output.push_str(&format!("  Ignored: {} don't-care assignment(s)\n", check.ignored));
+            }
         } else {
             output.push_str("✗ Expressions are not equivalent\n");
             output.push_str(&format!("  Left:  {}\n", left_str));
             output.push_str(&format!("  Right: {}\n", right_str));
+            if check.ignored > 0 {
+                output.push_str(&format!("  Ignored: {} don't-care assignment(s)\n", check.ignored));
+            }
             output.push_str("\nDifferences:\n");
             
             for diff in check.differences.iter().take(MAX_DIFFERENCES_TO_SHOW) {
@@ -88,191 +395,2476 @@ impl Formatter for TableFormatter {
     fn format_reduction_result(&self, reduction: &Reduction) -> String {
         let mut output = String::new();
         output.push_str(&format!("Expression: {}\n", reduction.original));
-        if reduction.simplified {
+        if !reduction.minimal {
+            output.push_str(&format!("Reduced form: {} (not proven minimal: search budget exhausted)\n", reduction.reduced));
+        } else if reduction.simplified {
             output.push_str(&format!("Reduced form: {}\n", reduction.reduced));
         } else {
             output.push_str(&format!("Reduced form: {} (already minimal)\n", reduction.reduced));
         }
+        
+        if let Some(coverage) = &reduction.coverage {
+            output.push_str("\nCoverage:\n");
+            for term in coverage {
+                let minterms: Vec<String> = term.minterms.iter().map(|m| m.to_string()).collect();
+                output.push_str(&format!("  {} [{}] covers minterms {}\n", term.term, term.cube, minterms.join(", ")));
+                for source in &term.source_terms {
+                    output.push_str(&format!("    from {}\n", source));
+                }
+            }
+        }
         output
     }
-}
-
-impl Formatter for JsonFormatter {
-    fn format_truth_table(&self, table: &TruthTable) -> String {
-        serde_json::to_string_pretty(table).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
-    }
 
-    fn format_equivalence_result(&self, check: &EquivalenceCheck, left_str: &str, right_str: &str) -> String {
-        #[derive(serde::Serialize)]
-        struct EquivalenceOutput {
-            equivalent: bool,
-            left_expression: String,
-            right_expression: String,
-            differences: Vec<EquivalenceDifference>,
+    fn format_table_diff(&self, diff: &TableDiff, left_name: &str, right_name: &str) -> String {
+        let mut output = String::new();
+        let all_vars = diff.left_variables.union(&diff.right_variables);
+        
+        if diff.identical {
+            output.push_str("✓ Truth tables are identical\n");
+        } else {
+            output.push_str("✗ Truth tables differ\n");
         }
+        output.push_str(&format!("  Left:  {} ({} variables, {} rows)\n", left_name, diff.left_variables.len(), diff.left_row_count));
+        output.push_str(&format!("  Right: {} ({} variables, {} rows)\n", right_name, diff.right_variables.len(), diff.right_row_count));
         
-        let output = EquivalenceOutput {
-            equivalent: check.equivalent,
-            left_expression: left_str.to_string(),
-            right_expression: right_str.to_string(),
-            differences: check.differences.clone(),
-        };
+        let left_only_vars = diff.left_only_variables();
+        if !left_only_vars.is_empty() {
+            output.push_str(&format!("\nVariables only in left: {}\n", left_only_vars.to_vec().join(", ")));
+        }
+        let right_only_vars = diff.right_only_variables();
+        if !right_only_vars.is_empty() {
+            output.push_str(&format!("\nVariables only in right: {}\n", right_only_vars.to_vec().join(", ")));
+        }
         
-        serde_json::to_string_pretty(&output).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+        for (label, rows) in [("left", &diff.left_only_rows), ("right", &diff.right_only_rows)] {
+            if rows.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\nRows only in {}:\n", label));
+            for assignment in rows.iter().take(MAX_DIFFERENCES_TO_SHOW) {
+                output.push_str(&format!("  {}\n", format_assignment(&all_vars, assignment)));
+            }
+            if rows.len() > MAX_DIFFERENCES_TO_SHOW {
+                output.push_str(&format!("  ... and {} more rows\n", rows.len() - MAX_DIFFERENCES_TO_SHOW));
+            }
+        }
+        
+        if !diff.differences.is_empty() {
+            output.push_str("\nDifferences:\n");
+            for d in diff.differences.iter().take(MAX_DIFFERENCES_TO_SHOW) {
+                output.push_str(&format!("  {} → Left={}, Right={}\n",
+                    format_assignment(&all_vars, &d.assignment),
+                    bool_symbol(d.left_value),
+                    bool_symbol(d.right_value)));
+            }
+            if diff.differences.len() > MAX_DIFFERENCES_TO_SHOW {
+                output.push_str(&format!("  ... and {} more differences\n", diff.differences.len() - MAX_DIFFERENCES_TO_SHOW));
+            }
+        }
+        
+        output
     }
 
-    fn format_reduction_result(&self, reduction: &Reduction) -> String {
-        serde_json::to_string_pretty(reduction).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    fn format_subexpressions(&self, report: &SubexpressionReport) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("expression: {}\n", report.expression));
+        output.push_str(&format!("{:>6}{:>6}{:>9}  {}\n", "Count", "Size", "Density", "Subexpression"));
+        output.push_str(&format!("{}\n", "-".repeat(36)));
+        for sub in &report.subexpressions {
+            output.push_str(&format!("{:>6}{:>6}{:>8.1}%  {}\n", sub.occurrences, sub.size, sub.density * 100.0, sub.expr));
+        }
+        output
     }
-}
 
-impl Formatter for CsvFormatter {
-    fn format_truth_table(&self, table: &TruthTable) -> String {
+    fn format_snippet_rewrite(&self, rewrite: &SnippetRewrite) -> String {
+        format!("{}\n", rewrite.rewritten)
+    }
+
+    fn format_lint_report(&self, report: &LintReport) -> String {
         let mut output = String::new();
-        
-        // Header
-        for var in table.variables.iter() {
-            output.push_str(&format!("{},", var));
+        for finding in &report.findings {
+            let mark = if finding.status == LintStatus::Ok { "✓" } else { "✗" };
+            output.push_str(&format!("{}:{}: {} {}", finding.path, finding.line, mark, finding.annotation));
+            if let Some(message) = &finding.message {
+                output.push_str(&format!(" — {}", message));
+            }
+            output.push('\n');
         }
-        output.push_str("result\n");
-        
-        // Rows
-        for row in &table.rows {
-            for var in table.variables.iter() {
-                let value = row.assignments.get(var).copied().unwrap_or(false);
-                output.push_str(&format!("{},", if value { "true" } else { "false" }));
+        output.push_str(&format!("{} annotation(s) in {} file(s), {} failed\n",
+            report.findings.len(), report.files_scanned, report.failures()));
+        output
+    }
+
+    fn format_prime_implicants(&self, report: &PrimeImplicantReport) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("expression: {}\n", report.expression));
+        output.push_str(&format!("Prime implicants: {}\n", report.count));
+        if !report.implicants.is_empty() {
+            output.push_str(&format!("variables: {}\n", report.variables.to_vec().join(" ")));
+            for implicant in &report.implicants {
+                let term = implicant.term.as_ref().map_or("⊤".to_string(), |t| t.to_string());
+                output.push_str(&format!("  {}  {}\n", implicant.cube, term));
             }
-            output.push_str(&format!("{}\n", if row.result { "true" } else { "false" }));
         }
-        
         output
     }
 
-    fn format_equivalence_result(&self, check: &EquivalenceCheck, left_str: &str, right_str: &str) -> String {
+    fn format_chain_proof(&self, proof: &ChainProof) -> String {
         let mut output = String::new();
-        output.push_str("equivalent,left_expression,right_expression\n");
-        output.push_str(&format!("{},{},{}\n", check.equivalent, left_str, right_str));
+        for (i, link) in proof.links.iter().enumerate() {
+            let mark = if link.check.equivalent { "✓" } else { "✗" };
+            output.push_str(&format!("{} {}. {} ↔ {}\n", mark, i + 1, link.from, link.to));
+        }
         
-        if !check.differences.is_empty() {
-            output.push_str("\nDifferences:\n");
-            // Header for differences
-            for var in check.variables.iter() {
-                output.push_str(&format!("{},", var));
-            }
-            output.push_str("left_value,right_value\n");
-            
-            // Each difference
-            for diff in &check.differences {
-                for var in check.variables.iter() {
-                    let value = diff.assignment.get(var).copied().unwrap_or(false);
-                    output.push_str(&format!("{},", if value { "true" } else { "false" }));
+        match proof.first_failure {
+            None => output.push_str("\n✓ Chain holds\n"),
+            Some(index) => {
+                let link = &proof.links[index];
+                output.push_str(&format!("\n✗ Link {} fails: {} ↔ {}\n", index + 1, link.from, link.to));
+                if let Some(diff) = link.check.differences.first() {
+                    output.push_str(&format!("  Counterexample: {} → Left={}, Right={}\n",
+                        format_assignment(&link.check.variables, &diff.assignment),
+                        bool_symbol(diff.left_value), bool_symbol(diff.right_value)));
                 }
-                output.push_str(&format!("{},{}\n", 
-                    if diff.left_value { "true" } else { "false" },
-                    if diff.right_value { "true" } else { "false" }));
             }
         }
-        
         output
     }
 
-    fn format_reduction_result(&self, reduction: &Reduction) -> String {
-        format!("original,reduced,simplified\n\"{}\",\"{}\",{}\n", 
-            reduction.original, reduction.reduced, reduction.simplified)
+    fn format_expansion(&self, expansion: &Expansion) -> String {
+        format!("Expression: {}\nExpanded form ({} nodes, {} rewrites, seed {}): {}\n",
+            expansion.original, expansion.size, expansion.steps, expansion.seed, expansion.expanded)
     }
-}
 
-impl Formatter for NuonFormatter {
-    fn format_truth_table(&self, table: &TruthTable) -> String {
+    fn format_influence(&self, report: &InfluenceReport) -> String {
         let mut output = String::new();
-        output.push_str("[\n");
-        for (i, row) in table.rows.iter().enumerate() {
-            output.push_str("  {");
-            
-            // Variable assignments
-            for (j, var) in table.variables.iter().enumerate() {
-                let value = row.assignments.get(var).copied().unwrap_or(false);
-                output.push_str(&format!("{}: {}", var, if value { "true" } else { "false" }));
-                if j < table.variables.len() - 1 {
-                    output.push_str(", ");
-                }
+        output.push_str(&format!("expression: {}\n", report.expression));
+        let shapley = report.influences.iter().any(|i| i.shapley.is_some());
+        output.push_str(&format!("{:>10}", "Banzhaf"));
+        if shapley {
+            output.push_str(&format!("{:>10}", "Shapley"));
+        }
+        output.push_str("  Variable\n");
+        output.push_str(&format!("{}\n", "-".repeat(if shapley { 30 } else { 20 })));
+        for influence in &report.influences {
+            output.push_str(&format!("{:>10.4}", influence.banzhaf));
+            if let Some(value) = influence.shapley {
+                output.push_str(&format!("{:>10.4}", value));
             }
-            
-            // Result
-            output.push_str(&format!(", result: {}", if row.result { "true" } else { "false" }));
-            output.push('}');
-            
-            if i < table.rows.len() - 1 {
-                output.push_str(",\n");
-            } else {
-                output.push('\n');
+            output.push_str(&format!("  {}\n", influence.variable));
+        }
+        output
+    }
+
+    fn format_threshold(&self, analysis: &ThresholdAnalysis) -> String {
+        let mut output = format!("expression: {}\n", analysis.expression);
+        match (&analysis.weights, analysis.threshold) {
+            (Some(weights), Some(threshold)) => {
+                output.push_str(&format!("✓ Threshold function: {} ≥ {}\n", linear_form(weights), threshold));
+            }
+            _ => {
+                output.push_str(&format!("✗ Not a threshold function: {}\n", analysis.reason.as_deref().unwrap_or("unknown reason")));
             }
         }
-        output.push_str("]\n");
         output
     }
 
-    fn format_equivalence_result(&self, check: &EquivalenceCheck, left_str: &str, right_str: &str) -> String {
+    fn format_explanations(&self, report: &ExplanationReport) -> String {
         let mut output = String::new();
-        output.push_str("{\n");
-        output.push_str(&format!("  equivalent: {},\n", if check.equivalent { "true" } else { "false" }));
-        output.push_str(&format!("  left_expression: \"{}\",\n", left_str));
-        output.push_str(&format!("  right_expression: \"{}\",\n", right_str));
-        output.push_str("  differences: [\n");
-        
-        for (i, diff) in check.differences.iter().enumerate() {
-            output.push_str("    {");
-            
-            // Variable assignments
-            for (j, var) in check.variables.iter().enumerate() {
-                let value = diff.assignment.get(var).copied().unwrap_or(false);
-                output.push_str(&format!("{}: {}", var, if value { "true" } else { "false" }));
-                if j < check.variables.len() - 1 {
-                    output.push_str(", ");
-                }
-            }
-            
-            // Left and right values
-            output.push_str(&format!(", left_value: {}, right_value: {}", 
-                if diff.left_value { "true" } else { "false" },
-                if diff.right_value { "true" } else { "false" }));
-            output.push('}');
-            
-            if i < check.differences.len() - 1 {
-                output.push_str(",\n");
+        for explanation in &report.explanations {
+            output.push_str(&format!("{} → {}",
+                format_assignment(&report.variables, &explanation.assignment), bool_symbol(explanation.result)));
+            if explanation.reason.is_empty() {
+                output.push_str(" (expression is constant)\n");
             } else {
-                output.push('\n');
+                output.push_str(&format!(" because {}\n", format_reason(explanation)));
             }
         }
-        
-        output.push_str("  ]\n");
-        output.push_str("}\n");
         output
     }
 
-    fn format_reduction_result(&self, reduction: &Reduction) -> String {
-        format!("{{\n  original: \"{}\",\n  reduced: \"{}\",\n  simplified: {}\n}}\n", 
-            reduction.original, reduction.reduced, if reduction.simplified { "true" } else { "false" })
+    fn format_abduction(&self, abduction: &Abduction) -> String {
+        let mut output = format!("expression: {}\n", abduction.expression);
+        if !abduction.fixed.is_empty() {
+            let fixed: Vec<String> = abduction.fixed.keys().collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(|var| format!("{}={}", quote_identifier(var), bool_symbol(abduction.fixed[var])))
+                .collect();
+            output.push_str(&format!("fixed: {}\n", fixed.join(" ")));
+        }
+        if abduction.already_forced() {
+            output.push_str(&format!("✓ The fixed values already force {}\n", bool_symbol(abduction.target)));
+        } else if abduction.assumptions.is_empty() {
+            output.push_str(&format!("✗ No assumptions can force {}\n", bool_symbol(abduction.target)));
+        } else {
+            output.push_str(&format!("Assumptions that force {}:\n", bool_symbol(abduction.target)));
+            for assumption in &abduction.assumptions {
+                let term = assumption.term.as_ref().map_or(String::new(), |t| t.to_string());
+                output.push_str(&format!("  {}\n", term));
+            }
+        }
+        output
     }
-}
 
-pub fn get_formatter(format: &OutputFormat) -> Box<dyn Formatter> {
-    match format {
-        OutputFormat::Table => Box::new(TableFormatter),
-        OutputFormat::Json => Box::new(JsonFormatter),
-        OutputFormat::Csv => Box::new(CsvFormatter),
-        OutputFormat::Nuon => Box::new(NuonFormatter),
+    fn format_propagation(&self, propagation: &Propagation) -> String {
+        let mut output = format!("expression: {}\n", propagation.expression);
+        if !propagation.fixed.is_empty() {
+            let fixed: Vec<String> = propagation.fixed.keys().collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(|var| format!("{}={}", quote_identifier(var), bool_symbol(propagation.fixed[var])))
+                .collect();
+            output.push_str(&format!("fixed: {}\n", fixed.join(" ")));
+        }
+        if !propagation.implied.is_empty() {
+            let implied: Vec<String> = propagation.implied
+                .iter()
+                .map(|i| format!("{}={} (round {})", i.variable, bool_symbol(i.value), i.round))
+                .collect();
+            output.push_str(&format!("implied: {}\n", implied.join(", ")));
+        }
+        match (&propagation.residual, propagation.value) {
+            (Some(residual), _) => output.push_str(&format!("residual: {}\n", residual)),
+            (None, Some(true)) => output.push_str("✓ The expression is forced to T\n"),
+            (None, _) => output.push_str("✗ The expression is forced to F\n"),
+        }
+        if !propagation.irrelevant.is_empty() {
+            output.push_str(&format!("irrelevant: {}\n", propagation.irrelevant.join(" ")));
+        }
+        output
     }
-}
 
-pub fn format_truth_table(table: &TruthTable, format: &OutputFormat) -> String {
-    get_formatter(format).format_truth_table(table)
-}
+    fn format_three_valued_table(&self, table: &ThreeValuedTable) -> String {
+        let mut output = String::new();
+        for var in table.variables.iter() {
+            output.push_str(&format!("{:>4}", var));
+        }
+        output.push_str(&format!("{:>8}\n", "Result"));
+        output.push_str(&"----".repeat(table.variables.len()));
+        output.push_str("--------\n");
+        for row in &table.rows {
+            for var in table.variables.iter() {
+                let value = row.assignments.get(var).copied().unwrap_or(Ternary::Unknown);
+                output.push_str(&format!("{:>4}", value.to_string()));
+            }
+            output.push_str(&format!("{:>8}\n", row.result.to_string()));
+        }
+        output
+    }
 
-pub fn format_equivalence_result(check: &EquivalenceCheck, left_str: &str, right_str: &str, format: &OutputFormat) -> String {
-    get_formatter(format).format_equivalence_result(check, left_str, right_str)
-}
+    fn format_vectors(&self, document: &CsvDocument, results: &[bool]) -> String {
+        let widths: Vec<usize> = document.headers
+            .iter()
+            .enumerate()
+            .map(|(col, header)| {
+                let longest = document.rows.iter().map(|row| row[col].chars().count()).fold(header.chars().count(), usize::max);
+                (longest + 1).max(4)
+            })
+            .collect();
+        let mut output = String::new();
+        for (header, width) in document.headers.iter().zip(&widths) {
+            output.push_str(&format!("{:>width$}", header, width = width));
+        }
+        output.push_str(&format!("{:>8}\n", "Result"));
+        output.push_str(&"-".repeat(widths.iter().sum::<usize>() + 8));
+        output.push('\n');
+        for (row, &result) in document.rows.iter().zip(results) {
+            for (cell, width) in row.iter().zip(&widths) {
+                output.push_str(&format!("{:>width$}", cell, width = width));
+            }
+            output.push_str(&format!("{:>8}\n", bool_symbol(result)));
+        }
+        output
+    }
+
+    fn format_cut_sets(&self, report: &CutSetReport) -> String {
+        let mut output = format!("expression: {}\n", report.expression);
+        if report.cut_sets.is_empty() {
+            output.push_str("✗ The expression is never true, so it has no cut sets\n");
+            return output;
+        }
+        output.push_str(&format!("cut sets: {}\n", report.cut_sets.len()));
+        for (i, cut_set) in report.cut_sets.iter().enumerate() {
+            let line = format!("  {}. {{{}}}", i + 1, cut_set_literals(cut_set).join(", "));
+            match cut_set.probability {
+                Some(p) => output.push_str(&format!("{:<40} p={:.3e}\n", line, p)),
+                None => output.push_str(&format!("{}\n", line)),
+            }
+        }
+        if let Some(p) = report.rare_event_approximation {
+            output.push_str(&format!("rare-event approximation: {:.3e}\n", p));
+        }
+        if let Some(p) = report.probability {
+            output.push_str(&format!("probability: {:.3e}\n", p));
+        }
+        output
+    }
+
+    fn format_group_summary(&self, summary: &GroupSummary) -> String {
+        let mut output = String::new();
+        for var in &summary.variables {
+            output.push_str(&format!("{:>4}", var));
+        }
+        output.push_str(&format!("{:>10}{:>10}\n", "True", "Fraction"));
+        output.push_str(&"----".repeat(summary.variables.len()));
+        output.push_str("--------------------\n");
+        for group in &summary.groups {
+            for var in &summary.variables {
+                output.push_str(&format!("{:>4}", bool_symbol(group.assignment[var])));
+            }
+            let count = format!("{}/{}", group.true_count, group.total);
+            output.push_str(&format!("{:>10}{:>10.3}\n", count, group.fraction()));
+        }
+        if !summary.collapsed.is_empty() {
+            output.push_str(&format!("collapsed: {}\n", summary.collapsed.join(" ")));
+        }
+        output
+    }
+
+    fn format_spectrum(&self, report: &SpectrumReport) -> String {
+        let mut output = format!("expression: {}\n", report.expression);
+        output.push_str("spectrum:\n");
+        for coefficient in &report.coefficients {
+            output.push_str(&format!("  W({}) = {}\n", walsh_mask(coefficient), coefficient.value));
+        }
+        output.push_str(&format!("nonlinearity: {}\n", report.nonlinearity));
+        output.push_str(&format!("correlation immunity: {}\n", report.correlation_immunity));
+        match report.resiliency {
+            Some(m) => output.push_str(&format!("✓ Balanced ({}-resilient)\n", m)),
+            None => output.push_str("✗ Not balanced\n"),
+        }
+        if report.bent {
+            output.push_str("✓ Bent\n");
+        } else {
+            output.push_str("✗ Not bent\n");
+        }
+        output
+    }
+
+    fn format_bias(&self, report: &BiasReport) -> String {
+        let mut output = format!("expression: {}\n", report.expression);
+        output.push_str(&format!("true: {}/{} (bias {:+.3})\n", report.true_count, report.total, report.bias));
+        if report.balanced() {
+            output.push_str("✓ Balanced\n");
+        } else {
+            output.push_str("✗ Not balanced\n");
+        }
+        if !report.conditionals.is_empty() {
+            let half = report.total / 2;
+            output.push_str("conditional biases:\n");
+            for c in &report.conditionals {
+                output.push_str(&format!(
+                    "  {}=T: {}/{} ({:+.3})  {}=F: {}/{} ({:+.3})\n",
+                    c.variable, c.true_when_set, half, c.bias_when_set,
+                    c.variable, c.true_when_clear, half, c.bias_when_clear
+                ));
+            }
+        }
+        output
+    }
+
+    fn format_sbox(&self, report: &SboxReport) -> String {
+        let mut output = format!("S-box: {} → {} bits\n", report.input_bits, report.output_bits);
+        output.push_str(&format!("{:>6}{:>8}{:>14}{:>10}\n", "Bit", "Degree", "Nonlinearity", "Balanced"));
+        output.push_str(&format!("{}\n", "-".repeat(38)));
+        for (bit, coordinate) in report.coordinates.iter().enumerate() {
+            output.push_str(&format!(
+                "{:>6}{:>8}{:>14}{:>10}\n",
+                bit,
+                coordinate.degree,
+                coordinate.nonlinearity,
+                if coordinate.balanced { "✓" } else { "✗" }
+            ));
+        }
+        let components = (1usize << report.output_bits) - 1;
+        output.push_str(&format!("nonlinearity: {} (over all {} components)\n", report.nonlinearity, components));
+        output.push_str(&format!("algebraic degree: {} (at least {} for every component)\n", report.degree, report.min_degree));
+        output.push_str(&format!("differential uniformity: {}\n", report.differential_uniformity));
+        output.push_str(&format!("linearity: {}\n", report.linearity));
+        if report.fixed_points.is_empty() {
+            output.push_str("fixed points: none\n");
+        } else {
+            let points: Vec<String> = report.fixed_points.iter().map(|x| format!("{:x}", x)).collect();
+            output.push_str(&format!("fixed points: {}\n", points.join(" ")));
+        }
+        if report.balanced {
+            output.push_str("✓ Balanced\n");
+        } else {
+            output.push_str("✗ Not balanced\n");
+        }
+        output
+    }
+
+    fn format_degree(&self, report: &DegreeReport) -> String {
+        let mut output = format!("expression: {}\n", report.expression);
+        output.push_str(&format!("anf: {}\n", report.anf));
+        output.push_str(&format!("algebraic degree: {}\n", report.degree));
+        if let Some(immunity) = &report.immunity {
+            let target = if immunity.of_complement { "the complement" } else { "the expression" };
+            output.push_str(&format!("algebraic immunity: {}\n", immunity.degree));
+            output.push_str(&format!(
+                "annihilator of {}: {}\n",
+                target,
+                Precedence::Standard.render(&immunity.annihilator)
+            ));
+        }
+        output
+    }
+
+    fn format_capabilities(&self, info: &Capabilities) -> String {
+        let mut output = format!("{} {}\n", info.name, info.version);
+        for (label, values) in capability_lists(info) {
+            output.push_str(&format!("{}: {}\n", label, values.join(", ")));
+        }
+        output.push_str("limits:\n");
+        for (name, value) in limit_entries(&info.limits) {
+            output.push_str(&format!("  {}: {}\n", name, value));
+        }
+        output
+    }
+
+    fn format_synthesis(&self, synthesis: &Synthesis) -> String {
+        let mut output = format!("Expression: {}\nBasis: {}\n", synthesis.expression, synthesis.basis);
+        match (&synthesis.result, synthesis.size) {
+            (Some(result), Some(size)) if synthesis.exact => {
+                output.push_str(&format!("Smallest form ({} nodes): {}\n", size, result));
+            }
+            (Some(result), Some(size)) => {
+                output.push_str(&format!("Reduced form ({} nodes, not proven smallest): {}\n", size, result));
+            }
+            _ if synthesis.exact => {
+                output.push_str(&format!("No equivalent expression of at most {} nodes\n", synthesis.max_size));
+            }
+            _ => {
+                output.push_str(&format!("The reduced form has more than {} nodes\n", synthesis.max_size));
+            }
+        }
+        output
+    }
+
+    fn format_rewrite(&self, rewrite: &Rewrite) -> String {
+        let mut output = format!("Expression: {}\n", rewrite.original);
+        if rewrite.steps.is_empty() {
+            output.push_str(&format!("Simplified: {} (no rule applies)\n", rewrite.rewritten));
+            return output;
+        }
+        output.push_str(&format!("Simplified: {}\n\nSteps:\n", rewrite.rewritten));
+        for step in &rewrite.steps {
+            output.push_str(&format!("  {}: {} ⟶ {}\n", step.rule, step.before, step.after));
+        }
+        if !rewrite.complete {
+            output.push_str("(stopped: step budget exhausted)\n");
+        }
+        output
+    }
+
+    fn format_table_view(&self, view: &TableView) -> String {
+        let mut output = String::new();
+        if let Some(range) = &view.range {
+            output.push_str(&format!("Rows {}..{} of {}\n", range.start, range.end, range.total));
+        }
+        if let Some(sample) = &view.sample {
+            output.push_str(&format!("Random sample: {}\n", sample_note(sample)));
+        }
+        for var in &view.variables {
+            output.push_str(&format!("{:>4}", var));
+        }
+        if view.result {
+            output.push_str(&format!("{:>8}", "Result"));
+        }
+        output.push('\n');
+        output.push_str(&"----".repeat(view.variables.len()));
+        output.push_str(if view.result { "--------\n" } else { "\n" });
+        for row in &view.rows {
+            for var in &view.variables {
+                output.push_str(&format!("{:>4}", bool_symbol(row.assignment[var])));
+            }
+            if let Some(result) = row.result {
+                output.push_str(&format!("{:>8}", bool_symbol(result)));
+            }
+            output.push('\n');
+        }
+        if !view.hidden.is_empty() {
+            output.push_str(&format!("hidden: {}\n", view.hidden.join(" ")));
+        }
+        output
+    }
+
+    fn format_projection(&self, projection: &Projection) -> String {
+        let mut output = String::new();
+        if let Some(range) = &projection.range {
+            output.push_str(&format!("Rows {}..{} of {}\n", range.start, range.end, range.total));
+        }
+        if let Some(sample) = &projection.sample {
+            output.push_str(&format!("Random sample: {}\n", sample_note(sample)));
+        }
+        for var in &projection.variables {
+            output.push_str(&format!("{:>4}", var));
+        }
+        output.push_str(&format!("{:>8}{:>8}\n", "Rows", "Result"));
+        output.push_str(&"----".repeat(projection.variables.len()));
+        output.push_str("----------------\n");
+        for row in &projection.rows {
+            for var in &projection.variables {
+                output.push_str(&format!("{:>4}", bool_symbol(row.assignment[var])));
+            }
+            let result = match row.result {
+                ProjectedResult::True => "T",
+                ProjectedResult::False => "F",
+                ProjectedResult::Mixed => "mixed",
+            };
+            output.push_str(&format!("{:>8}{:>8}\n", row.count, result));
+        }
+        if !projection.hidden.is_empty() {
+            output.push_str(&format!("projected away: {}\n", projection.hidden.join(" ")));
+        }
+        output
+    }
+
+    fn format_trace(&self, trace: &Trace) -> String {
+        let mut output = format!("{} → {}\n", format_assignment(&trace.variables, &trace.assignment), bool_symbol(trace.result));
+        for node in &trace.nodes {
+            let marker = if node.deciding { '▸' } else { ' ' };
+            output.push_str(&format!("{} {}  {}{}\n",
+                marker, bool_symbol(node.value), "  ".repeat(node.depth), Precedence::Standard.render(&node.expr)));
+        }
+        match &trace.forced_by {
+            Some(expr) => output.push_str(&format!("Forced by: {}\n", Precedence::Standard.render(expr))),
+            None => output.push_str("No single operand forces the result\n"),
+        }
+        output
+    }
+
+    fn format_satisfiability(&self, report: &Satisfiability) -> String {
+        let mut output = format!("expression: {}\n", report.expression);
+        match &report.assignment {
+            Some(assignment) if assignment.is_empty() => output.push_str("✓ Satisfiable\n"),
+            Some(assignment) => output.push_str(&format!("✓ Satisfiable: {}\n", format_assignment(&report.variables, assignment))),
+            None => output.push_str(&format!("✗ Unsatisfiable (all {} rows are false)\n", report.total)),
+        }
+        output
+    }
+
+    fn format_classification(&self, report: &ClassificationReport) -> String {
+        let mut output = format!("expression: {}\n", report.expression);
+        let rows = 1usize << report.variables.len();
+        match (&report.true_example, &report.false_example) {
+            (Some(when_true), Some(when_false)) => {
+                output.push_str("~ Contingent\n");
+                output.push_str(&format!("  true:  {}\n", format_assignment(&report.variables, when_true)));
+                output.push_str(&format!("  false: {}\n", format_assignment(&report.variables, when_false)));
+            }
+            (Some(_), None) => output.push_str(&format!("✓ Tautology: true on all {} rows\n", rows)),
+            _ => output.push_str(&format!("✗ Contradiction: false on all {} rows\n", rows)),
+        }
+        output
+    }
+
+    fn format_regression(&self, report: &RegressionReport) -> String {
+        let mut output = String::new();
+        let shown = |expr: &Option<Expr>| expr.as_ref().map_or(String::new(), |expr| expr.to_string());
+        for entry in &report.entries {
+            let line = match entry.status {
+                RegressionStatus::Unchanged => continue,
+                RegressionStatus::Syntactic => format!("~ {}: {} → {} (same truth table)", entry.key, shown(&entry.golden), shown(&entry.input)),
+                RegressionStatus::Semantic => {
+                    let counterexample = entry.counterexample.as_ref().map_or(String::new(), |assignment| format!(", differs at {}", sorted_assignment(assignment)));
+                    format!("✗ {}: {} → {}{}", entry.key, shown(&entry.golden), shown(&entry.input), counterexample)
+                }
+                RegressionStatus::Added => format!("+ {}: {}", entry.key, shown(&entry.input)),
+                RegressionStatus::Removed => format!("- {}: {}", entry.key, shown(&entry.golden)),
+                RegressionStatus::Error => format!("! {}: {}", entry.key, entry.message.as_deref().unwrap_or_default()),
+            };
+            output.push_str(&line);
+            output.push('\n');
+        }
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str("status      count\n");
+        output.push_str("-----------------\n");
+        for status in REGRESSION_STATUSES {
+            output.push_str(&format!("{:<10} {:>6}\n", regression_status_word(status), report.count(status)));
+        }
+        output
+    }
+
+    fn format_cnf(&self, cnf: &Cnf) -> String {
+        let mut output = format!("expression: {}\n", cnf.expression);
+        if cnf.tseitin {
+            output.push_str("tseitin variables:\n");
+            for definition in &cnf.definitions {
+                output.push_str(&format!("  {} ↔ {}\n", quote_identifier(&definition.variable), definition.gate));
+            }
+        }
+        output.push_str(&format!("cnf: {}\n", cnf.cnf));
+        output.push_str(&format!("{} clause(s), {} literal(s)\n", cnf.clauses.len(), cnf.literal_count()));
+        output
+    }
+
+    fn format_dnf(&self, dnf: &Dnf) -> String {
+        let mut output = format!("expression: {}\n", dnf.expression);
+        if dnf.minimal {
+            output.push_str(&format!("dnf: {}\n", dnf.dnf));
+        } else {
+            output.push_str(&format!("dnf: {} (not proven minimal: search budget exhausted)\n", dnf.dnf));
+        }
+        output.push_str(&format!("{} term(s), {} literal(s)\n", dnf.terms.len(), dnf.literal_count()));
+        output
+    }
+
+    fn format_nnf(&self, nnf: &Nnf) -> String {
+        format!(
+            "expression: {}\nnnf: {}\n{} node(s), from {}\n",
+            nnf.expression,
+            nnf.nnf,
+            nnf.nnf.node_count(),
+            nnf.expression.node_count()
+        )
+    }
+
+    fn format_anf(&self, report: &AnfReport) -> String {
+        format!(
+            "expression: {}\nanf: {}\ndegree {}, {} monomial(s)\n",
+            report.expression,
+            report.polynomial,
+            report.degree,
+            report.monomials.len()
+        )
+    }
+
+    fn format_conversion(&self, conversion: &Conversion) -> String {
+        format!(
+            "expression: {}\nbasis: {}\nconverted: {}\ngates: {} ({} distinct), from {}\n",
+            conversion.expression,
+            conversion.basis,
+            conversion.converted,
+            conversion.gates,
+            conversion.distinct_gates,
+            conversion.original_gates
+        )
+    }
+
+    fn format_report(&self, report: &Report) -> String {
+        let mut output = format!("expression: {}\n\ntruth table:\n", report.expression);
+        output.push_str(&self.format_truth_table(&report.table));
+        output.push_str(&format!("\nclassification: {}\n", report.classification.classification));
+        let minimal = if report.reduction.minimal { "" } else { " (not proven minimal: search budget exhausted)" };
+        output.push_str(&format!("reduced form: {}{}\n\nproperties:\n", report.reduction.reduced, minimal));
+        for (label, value) in property_entries(&report.properties) {
+            output.push_str(&format!("  {}: {}\n", label, value));
+        }
+        if let Some(map) = &report.karnaugh_map {
+            let cells = karnaugh_cells(map);
+            output.push_str("\nkarnaugh map:\n");
+            output.push_str(&plain_grid(&cells[0], &cells[1..]));
+        }
+        output
+    }
+}
+
+impl Formatter for JsonFormatter {
+    fn format_truth_table(&self, table: &TruthTable) -> String {
+        serde_json::to_string_pretty(table).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_equivalence_result(&self, check: &EquivalenceCheck, left_str: &str, right_str: &str) -> String {
+        #[derive(serde::Serialize)]
+        struct EquivalenceOutput {
+            equivalent: bool,
+            left_expression: String,
+            right_expression: String,
+            differences: Vec<EquivalenceDifference>,
+            ignored: usize,
+        }
+        
+        let output = EquivalenceOutput {
+            equivalent: check.equivalent,
+            left_expression: left_str.to_string(),
+            right_expression: right_str.to_string(),
+            differences: check.differences.clone(),
+            ignored: check.ignored,
+        };
+        
+        serde_json::to_string_pretty(&output).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_reduction_result(&self, reduction: &Reduction) -> String {
+        serde_json::to_string_pretty(reduction).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_table_diff(&self, diff: &TableDiff, left_name: &str, right_name: &str) -> String {
+        #[derive(serde::Serialize)]
+        struct TableDiffOutput<'a> {
+            left_table: &'a str,
+            right_table: &'a str,
+            left_only_variables: Vec<String>,
+            right_only_variables: Vec<String>,
+            #[serde(flatten)]
+            diff: &'a TableDiff,
+        }
+        
+        let output = TableDiffOutput {
+            left_table: left_name,
+            right_table: right_name,
+            left_only_variables: diff.left_only_variables().to_vec(),
+            right_only_variables: diff.right_only_variables().to_vec(),
+            diff,
+        };
+        
+        serde_json::to_string_pretty(&output).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_subexpressions(&self, report: &SubexpressionReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_snippet_rewrite(&self, rewrite: &SnippetRewrite) -> String {
+        serde_json::to_string_pretty(rewrite).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_lint_report(&self, report: &LintReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_prime_implicants(&self, report: &PrimeImplicantReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_chain_proof(&self, proof: &ChainProof) -> String {
+        serde_json::to_string_pretty(proof).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_expansion(&self, expansion: &Expansion) -> String {
+        serde_json::to_string_pretty(expansion).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_influence(&self, report: &InfluenceReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_threshold(&self, analysis: &ThresholdAnalysis) -> String {
+        serde_json::to_string_pretty(analysis).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_explanations(&self, report: &ExplanationReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_abduction(&self, abduction: &Abduction) -> String {
+        serde_json::to_string_pretty(abduction).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_propagation(&self, propagation: &Propagation) -> String {
+        serde_json::to_string_pretty(propagation).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_three_valued_table(&self, table: &ThreeValuedTable) -> String {
+        serde_json::to_string_pretty(table).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_vectors(&self, document: &CsvDocument, results: &[bool]) -> String {
+        // Cells stay strings, as they were read; only the result is typed
+        let rows: Vec<serde_json::Map<String, serde_json::Value>> = document.rows
+            .iter()
+            .zip(results)
+            .map(|(row, &result)| {
+                document.headers
+                    .iter()
+                    .zip(row)
+                    .map(|(header, cell)| (header.clone(), serde_json::Value::from(cell.as_str())))
+                    .chain([("result".to_string(), serde_json::Value::from(result))])
+                    .collect()
+            })
+            .collect();
+        serde_json::to_string_pretty(&rows).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_cut_sets(&self, report: &CutSetReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_group_summary(&self, summary: &GroupSummary) -> String {
+        serde_json::to_string_pretty(summary).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_spectrum(&self, report: &SpectrumReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_bias(&self, report: &BiasReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_sbox(&self, report: &SboxReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_degree(&self, report: &DegreeReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_capabilities(&self, info: &Capabilities) -> String {
+        serde_json::to_string_pretty(info).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_synthesis(&self, synthesis: &Synthesis) -> String {
+        serde_json::to_string_pretty(synthesis).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_rewrite(&self, rewrite: &Rewrite) -> String {
+        serde_json::to_string_pretty(rewrite).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_table_view(&self, view: &TableView) -> String {
+        serde_json::to_string_pretty(view).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_projection(&self, projection: &Projection) -> String {
+        serde_json::to_string_pretty(projection).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_trace(&self, trace: &Trace) -> String {
+        serde_json::to_string_pretty(trace).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_satisfiability(&self, report: &Satisfiability) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_classification(&self, report: &ClassificationReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_regression(&self, report: &RegressionReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_cnf(&self, cnf: &Cnf) -> String {
+        serde_json::to_string_pretty(cnf).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_dnf(&self, dnf: &Dnf) -> String {
+        serde_json::to_string_pretty(dnf).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_nnf(&self, nnf: &Nnf) -> String {
+        serde_json::to_string_pretty(nnf).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_anf(&self, report: &AnfReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_conversion(&self, conversion: &Conversion) -> String {
+        serde_json::to_string_pretty(conversion).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+
+    fn format_report(&self, report: &Report) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e))
+    }
+}
+
+impl Formatter for CsvFormatter {
+    fn format_truth_table(&self, table: &TruthTable) -> String {
+        let mut output = String::new();
+        if let Some(range) = &table.range {
+            output.push_str(&format!("# rows {}..{} of {}\n", range.start, range.end, range.total));
+        }
+        if let Some(sample) = &table.sample {
+            output.push_str(&format!("# random sample: {}\n", sample_note(sample)));
+        }
+        
+        // Header
+        for var in table.variables.iter() {
+            output.push_str(&format!("{},", var));
+        }
+        output.push_str("result\n");
+        
+        // Rows
+        for row in &table.rows {
+            for var in table.variables.iter() {
+                let value = row.assignments.get(var).copied().unwrap_or(false);
+                output.push_str(&format!("{},", if value { "true" } else { "false" }));
+            }
+            output.push_str(&format!("{}\n", if row.result { "true" } else { "false" }));
+        }
+        
+        output
+    }
+
+    fn format_equivalence_result(&self, check: &EquivalenceCheck, left_str: &str, right_str: &str) -> String {
+        let mut output = String::new();
+        output.push_str("equivalent,left_expression,right_expression\n");
+        output.push_str(&format!("{},{},{}\n", check.equivalent, left_str, right_str));
+        
+        if !check.differences.is_empty() {
+            output.push_str("\nDifferences:\n");
+            // Header for differences
+            for var in check.variables.iter() {
+                output.push_str(&format!("{},", var));
+            }
+            output.push_str("left_value,right_value\n");
+            
+            // Each difference
+            for diff in &check.differences {
+                for var in check.variables.iter() {
+                    let value = diff.assignment.get(var).copied().unwrap_or(false);
+                    output.push_str(&format!("{},", if value { "true" } else { "false" }));
+                }
+                output.push_str(&format!("{},{}\n", 
+                    if diff.left_value { "true" } else { "false" },
+                    if diff.right_value { "true" } else { "false" }));
+            }
+        }
+        
+        output
+    }
+
+    fn format_reduction_result(&self, reduction: &Reduction) -> String {
+        let mut output = format!("original,reduced,simplified,minimal\n\"{}\",\"{}\",{},{}\n", 
+            reduction.original, reduction.reduced, reduction.simplified, reduction.minimal);
+        
+        if let Some(coverage) = &reduction.coverage {
+            output.push_str("\nCoverage:\n");
+            output.push_str("term,cube,minterms,source_terms\n");
+            for term in coverage {
+                let minterms: Vec<String> = term.minterms.iter().map(|m| m.to_string()).collect();
+                let sources: Vec<String> = term.source_terms.iter().map(|e| e.to_string()).collect();
+                output.push_str(&format!("\"{}\",{},\"{}\",\"{}\"\n", term.term, term.cube, minterms.join(" "), sources.join("; ")));
+            }
+        }
+        output
+    }
+
+    fn format_table_diff(&self, diff: &TableDiff, _left_name: &str, _right_name: &str) -> String {
+        let all_vars = diff.left_variables.union(&diff.right_variables);
+        let mut output = String::new();
+        
+        // One line per finding: kind, every variable (blank when absent), then the two results
+        output.push_str("kind,");
+        for var in all_vars.iter() {
+            output.push_str(&format!("{},", var));
+        }
+        output.push_str("left_value,right_value\n");
+        
+        let mut push_row = |kind: &str, assignment: &HashMap<String, bool>, left: Option<bool>, right: Option<bool>| {
+            output.push_str(&format!("{},", kind));
+            for var in all_vars.iter() {
+                output.push_str(&format!("{},", assignment.get(var).map(|&v| bool_word(v)).unwrap_or("")));
+            }
+            output.push_str(&format!("{},{}\n", left.map(bool_word).unwrap_or(""), right.map(bool_word).unwrap_or("")));
+        };
+        
+        for assignment in &diff.left_only_rows {
+            push_row("left_only", assignment, None, None);
+        }
+        for assignment in &diff.right_only_rows {
+            push_row("right_only", assignment, None, None);
+        }
+        for d in &diff.differences {
+            push_row("different", &d.assignment, Some(d.left_value), Some(d.right_value));
+        }
+        
+        output
+    }
+
+    fn format_subexpressions(&self, report: &SubexpressionReport) -> String {
+        let mut output = String::from("subexpression,size,occurrences,true_count,density\n");
+        for sub in &report.subexpressions {
+            output.push_str(&format!("\"{}\",{},{},{},{}\n", sub.expr, sub.size, sub.occurrences, sub.true_count, sub.density));
+        }
+        output
+    }
+
+    fn format_snippet_rewrite(&self, rewrite: &SnippetRewrite) -> String {
+        format!("original,rewritten,simplified\n\"{}\",\"{}\",{}\n",
+            rewrite.original.replace('"', "\"\""), rewrite.rewritten.replace('"', "\"\""), rewrite.reduction.simplified)
+    }
+
+    fn format_lint_report(&self, report: &LintReport) -> String {
+        let mut output = String::from("path,line,status,annotation,message\n");
+        for finding in &report.findings {
+            let status = serde_json::to_value(finding.status).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+            output.push_str(&format!("\"{}\",{},{},\"{}\",\"{}\"\n",
+                finding.path,
+                finding.line,
+                status,
+                finding.annotation.replace('"', "\"\""),
+                finding.message.as_deref().unwrap_or("").replace('"', "\"\"")));
+        }
+        output
+    }
+
+    fn format_prime_implicants(&self, report: &PrimeImplicantReport) -> String {
+        if report.implicants.is_empty() {
+            return format!("expression,count\n\"{}\",{}\n", report.expression, report.count);
+        }
+        let mut output = String::from("cube,term,minterms\n");
+        for implicant in &report.implicants {
+            let term = implicant.term.as_ref().map_or(String::new(), |t| t.to_string());
+            let minterms: Vec<String> = implicant.minterms.iter().map(|m| m.to_string()).collect();
+            output.push_str(&format!("{},\"{}\",\"{}\"\n", implicant.cube, term, minterms.join(" ")));
+        }
+        output
+    }
+
+    fn format_chain_proof(&self, proof: &ChainProof) -> String {
+        let mut output = String::from("link,from,to,equivalent,counterexample\n");
+        for (i, link) in proof.links.iter().enumerate() {
+            let counterexample = link.check.differences
+                .first()
+                .map(|diff| format_assignment(&link.check.variables, &diff.assignment))
+                .unwrap_or_default();
+            output.push_str(&format!("{},\"{}\",\"{}\",{},{}\n", i + 1, link.from, link.to, link.check.equivalent, counterexample));
+        }
+        output
+    }
+
+    fn format_expansion(&self, expansion: &Expansion) -> String {
+        format!("original,expanded,size,steps,seed\n\"{}\",\"{}\",{},{},{}\n",
+            expansion.original, expansion.expanded, expansion.size, expansion.steps, expansion.seed)
+    }
+
+    fn format_influence(&self, report: &InfluenceReport) -> String {
+        let mut output = String::from("variable,banzhaf,shapley\n");
+        for influence in &report.influences {
+            let shapley = influence.shapley.map_or(String::new(), |value| value.to_string());
+            output.push_str(&format!("{},{},{}\n", influence.variable, influence.banzhaf, shapley));
+        }
+        output
+    }
+
+    fn format_threshold(&self, analysis: &ThresholdAnalysis) -> String {
+        let mut output = String::from("is_threshold,threshold");
+        for var in analysis.variables.iter() {
+            output.push_str(&format!(",{}", var));
+        }
+        output.push_str(",reason\n");
+        output.push_str(&format!("{},{}", analysis.is_threshold, analysis.threshold.map_or(String::new(), |t| t.to_string())));
+        for var in analysis.variables.iter() {
+            let weight = analysis.weights
+                .iter()
+                .flatten()
+                .find(|w| w.variable == var)
+                .map_or(String::new(), |w| w.weight.to_string());
+            output.push_str(&format!(",{}", weight));
+        }
+        output.push_str(&format!(",\"{}\"\n", analysis.reason.as_deref().unwrap_or("")));
+        output
+    }
+
+    fn format_explanations(&self, report: &ExplanationReport) -> String {
+        let mut output = String::new();
+        for var in report.variables.iter() {
+            output.push_str(&format!("{},", var));
+        }
+        output.push_str("result,reason\n");
+        for explanation in &report.explanations {
+            for var in report.variables.iter() {
+                let value = explanation.assignment.get(var).copied().unwrap_or(false);
+                output.push_str(&format!("{},", bool_word(value)));
+            }
+            output.push_str(&format!("{},\"{}\"\n", bool_word(explanation.result), format_reason(explanation)));
+        }
+        output
+    }
+
+    fn format_abduction(&self, abduction: &Abduction) -> String {
+        let mut output = String::from("cube,assumption\n");
+        for assumption in &abduction.assumptions {
+            let term = assumption.term.as_ref().map_or(String::new(), |t| t.to_string());
+            output.push_str(&format!("{},\"{}\"\n", assumption.cube, term));
+        }
+        output
+    }
+
+    fn format_propagation(&self, propagation: &Propagation) -> String {
+        let mut output = match (&propagation.residual, propagation.value) {
+            (Some(residual), _) => format!("# residual: {}\n", residual),
+            (None, value) => format!("# residual: {}\n", bool_word(value.unwrap_or(false))),
+        };
+        output.push_str("variable,state,value,round\n");
+        for var in propagation.fixed.keys().collect::<BTreeSet<_>>() {
+            output.push_str(&format!("{},fixed,{},\n", var, bool_word(propagation.fixed[var])));
+        }
+        for implied in &propagation.implied {
+            output.push_str(&format!("{},implied,{},{}\n", implied.variable, bool_word(implied.value), implied.round));
+        }
+        for var in &propagation.irrelevant {
+            output.push_str(&format!("{},irrelevant,,\n", var));
+        }
+        output
+    }
+
+    fn format_three_valued_table(&self, table: &ThreeValuedTable) -> String {
+        let mut output = String::new();
+        for var in table.variables.iter() {
+            output.push_str(&format!("{},", var));
+        }
+        output.push_str("result\n");
+        for row in &table.rows {
+            for var in table.variables.iter() {
+                let value = row.assignments.get(var).copied().unwrap_or(Ternary::Unknown);
+                output.push_str(&format!("{},", ternary_word(value)));
+            }
+            output.push_str(&format!("{}\n", ternary_word(row.result)));
+        }
+        output
+    }
+
+    fn format_vectors(&self, document: &CsvDocument, results: &[bool]) -> String {
+        let mut output = join_record(document.headers.iter().map(String::as_str).chain(["result"]));
+        output.push('\n');
+        for (row, &result) in document.rows.iter().zip(results) {
+            output.push_str(&join_record(row.iter().map(String::as_str).chain([bool_word(result)])));
+            output.push('\n');
+        }
+        output
+    }
+
+    fn format_cut_sets(&self, report: &CutSetReport) -> String {
+        let mut output = String::from("order,cut_set,probability\n");
+        for cut_set in &report.cut_sets {
+            output.push_str(&format!(
+                "{},{},{}\n",
+                cut_set.order(),
+                cut_set_literals(cut_set).join(" "),
+                cut_set.probability.map_or(String::new(), |p| p.to_string())
+            ));
+        }
+        output
+    }
+
+    fn format_group_summary(&self, summary: &GroupSummary) -> String {
+        let mut output = String::new();
+        for var in &summary.variables {
+            output.push_str(&format!("{},", var));
+        }
+        output.push_str("true_count,total,fraction\n");
+        for group in &summary.groups {
+            for var in &summary.variables {
+                output.push_str(&format!("{},", bool_word(group.assignment[var])));
+            }
+            output.push_str(&format!("{},{},{}\n", group.true_count, group.total, group.fraction()));
+        }
+        output
+    }
+
+    fn format_spectrum(&self, report: &SpectrumReport) -> String {
+        let mut output = format!(
+            "# nonlinearity: {}, correlation immunity: {}, balanced: {}, bent: {}\n",
+            report.nonlinearity,
+            report.correlation_immunity,
+            bool_word(report.balanced),
+            bool_word(report.bent)
+        );
+        output.push_str("mask,coefficient\n");
+        for coefficient in &report.coefficients {
+            output.push_str(&format!("{},{}\n", coefficient.mask.join(" "), coefficient.value));
+        }
+        output
+    }
+
+    fn format_bias(&self, report: &BiasReport) -> String {
+        let mut output = format!("# true: {}/{}, bias: {}\n", report.true_count, report.total, report.bias);
+        output.push_str("variable,true_when_set,true_when_clear,bias_when_set,bias_when_clear\n");
+        for c in &report.conditionals {
+            output.push_str(&format!(
+                "{},{},{},{},{}\n",
+                c.variable, c.true_when_set, c.true_when_clear, c.bias_when_set, c.bias_when_clear
+            ));
+        }
+        output
+    }
+
+    fn format_sbox(&self, report: &SboxReport) -> String {
+        let mut output = format!(
+            "# nonlinearity: {}, degree: {}, differential uniformity: {}, linearity: {}, balanced: {}\n",
+            report.nonlinearity,
+            report.degree,
+            report.differential_uniformity,
+            report.linearity,
+            bool_word(report.balanced)
+        );
+        output.push_str("bit,degree,nonlinearity,balanced\n");
+        for (bit, coordinate) in report.coordinates.iter().enumerate() {
+            output.push_str(&format!("{},{},{},{}\n", bit, coordinate.degree, coordinate.nonlinearity, bool_word(coordinate.balanced)));
+        }
+        output
+    }
+
+    fn format_degree(&self, report: &DegreeReport) -> String {
+        let mut output = String::from("degree,immunity,annihilates,annihilator\n");
+        match &report.immunity {
+            Some(immunity) => output.push_str(&format!(
+                "{},{},{},\"{}\"\n",
+                report.degree,
+                immunity.degree,
+                if immunity.of_complement { "complement" } else { "expression" },
+                immunity.annihilator
+            )),
+            None => output.push_str(&format!("{},,,\n", report.degree)),
+        }
+        output
+    }
+
+    fn format_capabilities(&self, info: &Capabilities) -> String {
+        let mut output = String::from("key,value\n");
+        output.push_str(&format!("name,{}\nversion,{}\n", info.name, info.version));
+        for (label, values) in capability_lists(info) {
+            output.push_str(&format!("{},{}\n", label.replace(' ', "_"), values.join(";")));
+        }
+        for (name, value) in limit_entries(&info.limits) {
+            output.push_str(&format!("{},{}\n", name, value));
+        }
+        output
+    }
+
+    fn format_synthesis(&self, synthesis: &Synthesis) -> String {
+        format!("expression,basis,max_size,size,exact,result\n\"{}\",\"{}\",{},{},{},\"{}\"\n",
+            synthesis.expression,
+            synthesis.basis,
+            synthesis.max_size,
+            synthesis.size.map_or(String::new(), |size| size.to_string()),
+            synthesis.exact,
+            synthesis.result.as_ref().map_or(String::new(), |result| result.to_string()))
+    }
+
+    fn format_rewrite(&self, rewrite: &Rewrite) -> String {
+        let mut output = format!("original,simplified,complete\n\"{}\",\"{}\",{}\n",
+            rewrite.original, rewrite.rewritten, rewrite.complete);
+        if !rewrite.steps.is_empty() {
+            output.push_str("\nSteps:\nrule,before,after\n");
+            for step in &rewrite.steps {
+                output.push_str(&format!("{},\"{}\",\"{}\"\n", step.rule, step.before, step.after));
+            }
+        }
+        output
+    }
+
+    fn format_table_view(&self, view: &TableView) -> String {
+        let mut output = String::new();
+        if let Some(range) = &view.range {
+            output.push_str(&format!("# rows {}..{} of {}\n", range.start, range.end, range.total));
+        }
+        if let Some(sample) = &view.sample {
+            output.push_str(&format!("# random sample: {}\n", sample_note(sample)));
+        }
+        if !view.hidden.is_empty() {
+            output.push_str(&format!("# hidden: {}\n", view.hidden.join(" ")));
+        }
+        let mut header = view.variables.clone();
+        if view.result {
+            header.push("result".to_string());
+        }
+        output.push_str(&format!("{}\n", header.join(",")));
+        for row in &view.rows {
+            let mut fields: Vec<&str> = view.variables.iter().map(|var| bool_word(row.assignment[var])).collect();
+            fields.extend(row.result.map(bool_word));
+            output.push_str(&format!("{}\n", fields.join(",")));
+        }
+        output
+    }
+
+    fn format_projection(&self, projection: &Projection) -> String {
+        let mut output = String::new();
+        if let Some(range) = &projection.range {
+            output.push_str(&format!("# rows {}..{} of {}\n", range.start, range.end, range.total));
+        }
+        if let Some(sample) = &projection.sample {
+            output.push_str(&format!("# random sample: {}\n", sample_note(sample)));
+        }
+        if !projection.hidden.is_empty() {
+            output.push_str(&format!("# projected away: {}\n", projection.hidden.join(" ")));
+        }
+        let mut header = projection.variables.clone();
+        header.extend(["count".to_string(), "result".to_string()]);
+        output.push_str(&format!("{}\n", header.join(",")));
+        for row in &projection.rows {
+            let mut fields: Vec<String> = projection.variables.iter().map(|var| bool_word(row.assignment[var]).to_string()).collect();
+            fields.push(row.count.to_string());
+            fields.push(projected_result_word(row.result).to_string());
+            output.push_str(&format!("{}\n", fields.join(",")));
+        }
+        output
+    }
+
+    fn format_trace(&self, trace: &Trace) -> String {
+        let mut output = format!("# {} -> {}\n", format_assignment(&trace.variables, &trace.assignment), bool_word(trace.result));
+        if let Some(expr) = &trace.forced_by {
+            output.push_str(&format!("# forced by: {}\n", expr));
+        }
+        output.push_str("depth,expression,value,deciding\n");
+        for node in &trace.nodes {
+            output.push_str(&format!("{},\"{}\",{},{}\n", node.depth, node.expr, bool_word(node.value), bool_word(node.deciding)));
+        }
+        output
+    }
+
+    fn format_satisfiability(&self, report: &Satisfiability) -> String {
+        let mut output = format!("# satisfiable: {}, rows checked: {}/{}\n", bool_word(report.satisfiable), report.rows_checked, report.total);
+        output.push_str("variable,value\n");
+        if let Some(assignment) = &report.assignment {
+            for var in report.variables.iter() {
+                output.push_str(&format!("{},{}\n", var, bool_word(assignment[var])));
+            }
+        }
+        output
+    }
+
+    fn format_classification(&self, report: &ClassificationReport) -> String {
+        let mut output = format!("# classification: {}\n", report.classification);
+        let header: Vec<&str> = report.variables.iter().collect();
+        output.push_str(&format!("result,{}\n", header.join(",")));
+        for (result, example) in [(true, &report.true_example), (false, &report.false_example)] {
+            if let Some(assignment) = example {
+                let values: Vec<&str> = report.variables.iter().map(|var| bool_word(assignment[var])).collect();
+                output.push_str(&format!("{},{}\n", bool_word(result), values.join(",")));
+            }
+        }
+        output
+    }
+
+    fn format_regression(&self, report: &RegressionReport) -> String {
+        let mut output = String::from("key,status,golden,input,counterexample,message\n");
+        let shown = |expr: &Option<Expr>| expr.as_ref().map_or(String::new(), |expr| expr.to_string().replace('"', "\"\""));
+        for entry in &report.entries {
+            output.push_str(&format!("\"{}\",{},\"{}\",\"{}\",\"{}\",\"{}\"\n",
+                entry.key.replace('"', "\"\""),
+                regression_status_word(entry.status),
+                shown(&entry.golden),
+                shown(&entry.input),
+                entry.counterexample.as_ref().map_or(String::new(), sorted_assignment),
+                entry.message.as_deref().unwrap_or("").replace('"', "\"\"")));
+        }
+        output
+    }
+
+    fn format_cnf(&self, cnf: &Cnf) -> String {
+        let mut output = format!("# cnf of {}\n", cnf.expression);
+        for definition in &cnf.definitions {
+            output.push_str(&format!("# {} = {}\n", definition.variable, definition.gate));
+        }
+        output.push_str("clause,variable,negated\n");
+        for (index, clause) in cnf.clauses.iter().enumerate() {
+            for literal in clause {
+                output.push_str(&format!("{},\"{}\",{}\n", index + 1, literal.variable.replace('"', "\"\""), bool_word(literal.negated)));
+            }
+        }
+        output
+    }
+
+    fn format_dnf(&self, dnf: &Dnf) -> String {
+        let mut output = format!("# {} dnf of {}\n", if dnf.canonical { "canonical" } else { "minimal" }, dnf.expression);
+        output.push_str("term,variable,negated\n");
+        for (index, term) in dnf.terms.iter().enumerate() {
+            for literal in term {
+                output.push_str(&format!("{},\"{}\",{}\n", index + 1, literal.variable.replace('"', "\"\""), bool_word(literal.negated)));
+            }
+        }
+        output
+    }
+
+    fn format_nnf(&self, nnf: &Nnf) -> String {
+        format!("expression,nnf\n\"{}\",\"{}\"\n", nnf.expression, nnf.nnf)
+    }
+
+    fn format_anf(&self, report: &AnfReport) -> String {
+        let mut output = format!("# anf of {}\n", report.expression);
+        output.push_str("monomial,degree\n");
+        for monomial in &report.monomials {
+            let term = if monomial.is_empty() { "1".to_string() } else { monomial.join(" ") };
+            output.push_str(&format!("\"{}\",{}\n", term.replace('"', "\"\""), monomial.len()));
+        }
+        output
+    }
+
+    fn format_conversion(&self, conversion: &Conversion) -> String {
+        format!("expression,basis,converted,gates,distinct_gates,original_gates\n\"{}\",\"{}\",\"{}\",{},{},{}\n",
+            conversion.expression,
+            conversion.basis,
+            conversion.converted,
+            conversion.gates,
+            conversion.distinct_gates,
+            conversion.original_gates)
+    }
+
+    fn format_report(&self, report: &Report) -> String {
+        let mut output = format!("# report of {}\n# classification: {}\n# reduced: {}\n", report.expression, report.classification.classification, report.reduction.reduced);
+        for (label, value) in property_entries(&report.properties) {
+            output.push_str(&format!("# {}: {}\n", label, value));
+        }
+        output.push_str(&self.format_truth_table(&report.table));
+        output
+    }
+}
+
+impl Formatter for NuonFormatter {
+    fn format_truth_table(&self, table: &TruthTable) -> String {
+        let mut output = String::new();
+        if let Some(sample) = &table.sample {
+            output.push_str(&format!("# random sample: {}\n", sample_note(sample)));
+        }
+        output.push_str("[\n");
+        for (i, row) in table.rows.iter().enumerate() {
+            output.push_str("  {");
+            
+            // Variable assignments
+            for (j, var) in table.variables.iter().enumerate() {
+                let value = row.assignments.get(var).copied().unwrap_or(false);
+                output.push_str(&format!("{}: {}", var, if value { "true" } else { "false" }));
+                if j < table.variables.len() - 1 {
+                    output.push_str(", ");
+                }
+            }
+            
+            // Result
+            output.push_str(&format!(", result: {}", if row.result { "true" } else { "false" }));
+            output.push('}');
+            
+            if i < table.rows.len() - 1 {
+                output.push_str(",\n");
+            } else {
+                output.push('\n');
+            }
+        }
+        output.push_str("]\n");
+        output
+    }
+
+    fn format_equivalence_result(&self, check: &EquivalenceCheck, left_str: &str, right_str: &str) -> String {
+        let mut output = String::new();
+        output.push_str("{\n");
+        output.push_str(&format!("  equivalent: {},\n", if check.equivalent { "true" } else { "false" }));
+        output.push_str(&format!("  left_expression: \"{}\",\n", left_str));
+        output.push_str(&format!("  right_expression: \"{}\",\n", right_str));
+        output.push_str("  differences: [\n");
+        
+        for (i, diff) in check.differences.iter().enumerate() {
+            output.push_str("    {");
+            
+            // Variable assignments
+            for (j, var) in check.variables.iter().enumerate() {
+                let value = diff.assignment.get(var).copied().unwrap_or(false);
+                output.push_str(&format!("{}: {}", var, if value { "true" } else { "false" }));
+                if j < check.variables.len() - 1 {
+                    output.push_str(", ");
+                }
+            }
+            
+            // Left and right values
+            output.push_str(&format!(", left_value: {}, right_value: {}", 
+                if diff.left_value { "true" } else { "false" },
+                if diff.right_value { "true" } else { "false" }));
+            output.push('}');
+            
+            if i < check.differences.len() - 1 {
+                output.push_str(",\n");
+            } else {
+                output.push('\n');
+            }
+        }
+        
+        output.push_str("  ],\n");
+        output.push_str(&format!("  ignored: {}\n", check.ignored));
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_reduction_result(&self, reduction: &Reduction) -> String {
+        let coverage = match &reduction.coverage {
+            Some(coverage) => {
+                let terms: Vec<String> = coverage
+                    .iter()
+                    .map(|term| {
+                        let minterms: Vec<String> = term.minterms.iter().map(|m| m.to_string()).collect();
+                        let sources: Vec<String> = term.source_terms.iter().map(|e| format!("\"{}\"", e)).collect();
+                        format!("{{term: \"{}\", cube: \"{}\", minterms: [{}], source_terms: [{}]}}",
+                            term.term, term.cube, minterms.join(", "), sources.join(", "))
+                    })
+                    .collect();
+                format!(",\n  coverage: [{}]", terms.join(", "))
+            }
+            None => String::new(),
+        };
+        format!("{{\n  original: \"{}\",\n  reduced: \"{}\",\n  simplified: {},\n  minimal: {}{}\n}}\n", 
+            reduction.original, reduction.reduced, if reduction.simplified { "true" } else { "false" }, bool_word(reduction.minimal), coverage)
+    }
+
+    fn format_table_diff(&self, diff: &TableDiff, left_name: &str, right_name: &str) -> String {
+        let all_vars = diff.left_variables.union(&diff.right_variables);
+        let record = |assignment: &HashMap<String, bool>| -> String {
+            all_vars
+                .iter()
+                .filter_map(|var| assignment.get(var).map(|&v| format!("{}: {}", var, bool_word(v))))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let names = |vars: &Variables| -> String {
+            vars.iter().map(|v| format!("\"{}\"", v)).collect::<Vec<_>>().join(", ")
+        };
+        
+        let mut output = String::new();
+        output.push_str("{\n");
+        output.push_str(&format!("  identical: {},\n", bool_word(diff.identical)));
+        output.push_str(&format!("  left_table: \"{}\",\n", left_name));
+        output.push_str(&format!("  right_table: \"{}\",\n", right_name));
+        output.push_str(&format!("  left_row_count: {},\n", diff.left_row_count));
+        output.push_str(&format!("  right_row_count: {},\n", diff.right_row_count));
+        output.push_str(&format!("  left_only_variables: [{}],\n", names(&diff.left_only_variables())));
+        output.push_str(&format!("  right_only_variables: [{}],\n", names(&diff.right_only_variables())));
+        for (label, rows) in [("left_only_rows", &diff.left_only_rows), ("right_only_rows", &diff.right_only_rows)] {
+            let rendered: Vec<String> = rows.iter().map(|a| format!("{{{}}}", record(a))).collect();
+            output.push_str(&format!("  {}: [{}],\n", label, rendered.join(", ")));
+        }
+        let rendered: Vec<String> = diff.differences
+            .iter()
+            .map(|d| format!("{{{}, left_value: {}, right_value: {}}}", record(&d.assignment), bool_word(d.left_value), bool_word(d.right_value)))
+            .collect();
+        output.push_str(&format!("  differences: [{}]\n", rendered.join(", ")));
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_subexpressions(&self, report: &SubexpressionReport) -> String {
+        let mut output = String::from("[\n");
+        let rows: Vec<String> = report.subexpressions
+            .iter()
+            .map(|sub| format!("  {{subexpression: \"{}\", size: {}, occurrences: {}, true_count: {}, density: {}}}",
+                sub.expr, sub.size, sub.occurrences, sub.true_count, sub.density))
+            .collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_snippet_rewrite(&self, rewrite: &SnippetRewrite) -> String {
+        format!("{{\n  original: {:?},\n  rewritten: {:?},\n  simplified: {}\n}}\n",
+            rewrite.original, rewrite.rewritten, bool_word(rewrite.reduction.simplified))
+    }
+
+    fn format_lint_report(&self, report: &LintReport) -> String {
+        let mut output = String::from("[\n");
+        let rows: Vec<String> = report.findings
+            .iter()
+            .map(|finding| {
+                let status = serde_json::to_value(finding.status).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+                format!("  {{path: {:?}, line: {}, status: {}, annotation: {:?}, message: {:?}}}",
+                    finding.path, finding.line, status, finding.annotation, finding.message.as_deref().unwrap_or(""))
+            })
+            .collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_prime_implicants(&self, report: &PrimeImplicantReport) -> String {
+        let implicants: Vec<String> = report.implicants
+            .iter()
+            .map(|implicant| {
+                let term = implicant.term.as_ref().map_or(String::new(), |t| t.to_string());
+                let minterms: Vec<String> = implicant.minterms.iter().map(|m| m.to_string()).collect();
+                format!("{{cube: \"{}\", term: \"{}\", minterms: [{}]}}", implicant.cube, term, minterms.join(", "))
+            })
+            .collect();
+        format!("{{\n  expression: \"{}\",\n  count: {},\n  implicants: [{}]\n}}\n",
+            report.expression, report.count, implicants.join(", "))
+    }
+
+    fn format_chain_proof(&self, proof: &ChainProof) -> String {
+        let links: Vec<String> = proof.links
+            .iter()
+            .map(|link| format!("{{from: \"{}\", to: \"{}\", equivalent: {}}}",
+                link.from, link.to, bool_word(link.check.equivalent)))
+            .collect();
+        let first_failure = proof.first_failure.map_or("null".to_string(), |index| index.to_string());
+        format!("{{\n  valid: {},\n  first_failure: {},\n  links: [{}]\n}}\n",
+            bool_word(proof.valid), first_failure, links.join(", "))
+    }
+
+    fn format_expansion(&self, expansion: &Expansion) -> String {
+        format!("{{\n  original: \"{}\",\n  expanded: \"{}\",\n  size: {},\n  steps: {},\n  seed: {}\n}}\n",
+            expansion.original, expansion.expanded, expansion.size, expansion.steps, expansion.seed)
+    }
+
+    fn format_influence(&self, report: &InfluenceReport) -> String {
+        let mut output = String::from("[\n");
+        let rows: Vec<String> = report.influences
+            .iter()
+            .map(|influence| {
+                let shapley = influence.shapley.map_or("null".to_string(), |value| value.to_string());
+                format!("  {{variable: \"{}\", banzhaf: {}, shapley: {}}}", influence.variable, influence.banzhaf, shapley)
+            })
+            .collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_threshold(&self, analysis: &ThresholdAnalysis) -> String {
+        let weights = match &analysis.weights {
+            Some(weights) => {
+                let fields: Vec<String> = weights.iter().map(|w| format!("{}: {}", w.variable, w.weight)).collect();
+                format!("{{{}}}", fields.join(", "))
+            }
+            None => "null".to_string(),
+        };
+        format!("{{is_threshold: {}, threshold: {}, weights: {}, reason: {}}}\n",
+            bool_word(analysis.is_threshold),
+            analysis.threshold.map_or("null".to_string(), |t| t.to_string()),
+            weights,
+            analysis.reason.as_ref().map_or("null".to_string(), |r| format!("{:?}", r)))
+    }
+
+    fn format_explanations(&self, report: &ExplanationReport) -> String {
+        let mut output = String::from("[\n");
+        let rows: Vec<String> = report.explanations
+            .iter()
+            .map(|explanation| {
+                let mut fields: Vec<String> = report.variables
+                    .iter()
+                    .map(|var| format!("{}: {}", var, bool_word(explanation.assignment.get(var).copied().unwrap_or(false))))
+                    .collect();
+                fields.push(format!("result: {}", bool_word(explanation.result)));
+                let reason: Vec<String> = explanation.reason.iter().map(|var| format!("{:?}", var)).collect();
+                fields.push(format!("reason: [{}]", reason.join(", ")));
+                format!("  {{{}}}", fields.join(", "))
+            })
+            .collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_abduction(&self, abduction: &Abduction) -> String {
+        let mut output = String::from("[\n");
+        let rows: Vec<String> = abduction.assumptions
+            .iter()
+            .map(|assumption| {
+                let term = assumption.term.as_ref().map_or(String::new(), |t| t.to_string());
+                format!("  {{cube: \"{}\", assumption: \"{}\"}}", assumption.cube, term)
+            })
+            .collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_propagation(&self, propagation: &Propagation) -> String {
+        let implied: Vec<String> = propagation.implied
+            .iter()
+            .map(|i| format!("{{variable: \"{}\", value: {}, round: {}}}", i.variable, bool_word(i.value), i.round))
+            .collect();
+        let irrelevant: Vec<String> = propagation.irrelevant.iter().map(|var| format!("\"{}\"", var)).collect();
+        format!("{{residual: {}, value: {}, implied: [{}], irrelevant: [{}]}}\n",
+            propagation.residual.as_ref().map_or("null".to_string(), |r| format!("\"{}\"", r)),
+            propagation.value.map_or("null", bool_word),
+            implied.join(", "),
+            irrelevant.join(", "))
+    }
+
+    fn format_three_valued_table(&self, table: &ThreeValuedTable) -> String {
+        let nuon_value = |value: Ternary| value.known().map_or("null", bool_word);
+        let mut output = String::from("[\n");
+        let rows: Vec<String> = table.rows
+            .iter()
+            .map(|row| {
+                let mut fields: Vec<String> = table.variables
+                    .iter()
+                    .map(|var| format!("{}: {}", var, nuon_value(row.assignments.get(var).copied().unwrap_or(Ternary::Unknown))))
+                    .collect();
+                fields.push(format!("result: {}", nuon_value(row.result)));
+                format!("  {{{}}}", fields.join(", "))
+            })
+            .collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_vectors(&self, document: &CsvDocument, results: &[bool]) -> String {
+        let rows: Vec<String> = document.rows
+            .iter()
+            .zip(results)
+            .map(|(row, result)| {
+                let cells: Vec<String> = document.headers
+                    .iter()
+                    .zip(row)
+                    .map(|(header, cell)| format!("{:?}: {:?}", header, cell))
+                    .collect();
+                format!("  {{{}, result: {}}}", cells.join(", "), result)
+            })
+            .collect();
+        format!("[\n{}\n]\n", rows.join(",\n"))
+    }
+
+    fn format_cut_sets(&self, report: &CutSetReport) -> String {
+        let quoted = |names: &[String]| names.iter().map(|n| format!("\"{}\"", n)).collect::<Vec<_>>().join(", ");
+        let cut_sets: Vec<String> = report.cut_sets
+            .iter()
+            .map(|c| format!(
+                "  {{events: [{}], absent: [{}], probability: {}}}",
+                quoted(&c.events),
+                quoted(&c.absent),
+                c.probability.map_or("null".to_string(), |p| p.to_string())
+            ))
+            .collect();
+        if cut_sets.is_empty() {
+            return "[]\n".to_string();
+        }
+        format!("[\n{}\n]\n", cut_sets.join(",\n"))
+    }
+
+    fn format_group_summary(&self, summary: &GroupSummary) -> String {
+        let rows: Vec<String> = summary.groups
+            .iter()
+            .map(|group| {
+                let mut fields: Vec<String> = summary.variables
+                    .iter()
+                    .map(|var| format!("{}: {}", var, bool_word(group.assignment[var])))
+                    .collect();
+                fields.push(format!("true_count: {}, total: {}, fraction: {}", group.true_count, group.total, group.fraction()));
+                format!("  {{{}}}", fields.join(", "))
+            })
+            .collect();
+        format!("[\n{}\n]\n", rows.join(",\n"))
+    }
+
+    fn format_spectrum(&self, report: &SpectrumReport) -> String {
+        let coefficients: Vec<String> = report.coefficients
+            .iter()
+            .map(|c| {
+                let mask: Vec<String> = c.mask.iter().map(|var| format!("\"{}\"", var)).collect();
+                format!("{{mask: [{}], value: {}}}", mask.join(", "), c.value)
+            })
+            .collect();
+        format!(
+            "{{nonlinearity: {}, correlation_immunity: {}, balanced: {}, bent: {}, coefficients: [{}]}}\n",
+            report.nonlinearity,
+            report.correlation_immunity,
+            bool_word(report.balanced),
+            bool_word(report.bent),
+            coefficients.join(", ")
+        )
+    }
+
+    fn format_bias(&self, report: &BiasReport) -> String {
+        let conditionals: Vec<String> = report.conditionals
+            .iter()
+            .map(|c| format!(
+                "{{variable: \"{}\", true_when_set: {}, true_when_clear: {}, bias_when_set: {}, bias_when_clear: {}}}",
+                c.variable, c.true_when_set, c.true_when_clear, c.bias_when_set, c.bias_when_clear
+            ))
+            .collect();
+        format!(
+            "{{true_count: {}, total: {}, bias: {}, balanced: {}, conditionals: [{}]}}\n",
+            report.true_count,
+            report.total,
+            report.bias,
+            bool_word(report.balanced()),
+            conditionals.join(", ")
+        )
+    }
+
+    fn format_sbox(&self, report: &SboxReport) -> String {
+        let coordinates: Vec<String> = report.coordinates
+            .iter()
+            .enumerate()
+            .map(|(bit, c)| format!(
+                "{{bit: {}, degree: {}, nonlinearity: {}, balanced: {}}}",
+                bit, c.degree, c.nonlinearity, bool_word(c.balanced)
+            ))
+            .collect();
+        let fixed_points: Vec<String> = report.fixed_points.iter().map(usize::to_string).collect();
+        format!(
+            "{{input_bits: {}, output_bits: {}, nonlinearity: {}, degree: {}, min_degree: {}, differential_uniformity: {}, linearity: {}, balanced: {}, fixed_points: [{}], coordinates: [{}]}}\n",
+            report.input_bits,
+            report.output_bits,
+            report.nonlinearity,
+            report.degree,
+            report.min_degree,
+            report.differential_uniformity,
+            report.linearity,
+            bool_word(report.balanced),
+            fixed_points.join(", "),
+            coordinates.join(", ")
+        )
+    }
+
+    fn format_degree(&self, report: &DegreeReport) -> String {
+        let monomials: Vec<String> = report.anf.monomials.iter().map(usize::to_string).collect();
+        let immunity = match &report.immunity {
+            Some(immunity) => format!(
+                ", immunity: {}, annihilates: \"{}\", annihilator: \"{}\"",
+                immunity.degree,
+                if immunity.of_complement { "complement" } else { "expression" },
+                immunity.annihilator
+            ),
+            None => String::new(),
+        };
+        format!("{{degree: {}, monomials: [{}]{}}}\n", report.degree, monomials.join(", "), immunity)
+    }
+
+    fn format_capabilities(&self, info: &Capabilities) -> String {
+        let mut fields = vec![format!("name: \"{}\"", info.name), format!("version: \"{}\"", info.version)];
+        for (label, values) in capability_lists(info) {
+            let values: Vec<String> = values.iter().map(|value| format!("\"{}\"", value)).collect();
+            fields.push(format!("{}: [{}]", label.replace(' ', "_"), values.join(", ")));
+        }
+        let limits: Vec<String> = limit_entries(&info.limits).iter().map(|(name, value)| format!("{}: {}", name, value)).collect();
+        fields.push(format!("limits: {{{}}}", limits.join(", ")));
+        format!("{{{}}}\n", fields.join(", "))
+    }
+
+    fn format_synthesis(&self, synthesis: &Synthesis) -> String {
+        let result = match (&synthesis.result, synthesis.size) {
+            (Some(result), Some(size)) => format!(", size: {}, result: \"{}\"", size, result),
+            _ => String::new(),
+        };
+        format!("{{expression: \"{}\", basis: \"{}\", max_size: {}, exact: {}{}}}\n",
+            synthesis.expression, synthesis.basis, synthesis.max_size, synthesis.exact, result)
+    }
+
+    fn format_rewrite(&self, rewrite: &Rewrite) -> String {
+        let steps: Vec<String> = rewrite.steps
+            .iter()
+            .map(|step| format!("{{rule: \"{}\", before: \"{}\", after: \"{}\"}}", step.rule, step.before, step.after))
+            .collect();
+        format!("{{original: \"{}\", simplified: \"{}\", complete: {}, steps: [{}]}}\n",
+            rewrite.original, rewrite.rewritten, rewrite.complete, steps.join(", "))
+    }
+
+    fn format_table_view(&self, view: &TableView) -> String {
+        let mut output = String::new();
+        if let Some(sample) = &view.sample {
+            output.push_str(&format!("# random sample: {}\n", sample_note(sample)));
+        }
+        if !view.hidden.is_empty() {
+            output.push_str(&format!("# hidden: {}\n", view.hidden.join(" ")));
+        }
+        output.push_str("[\n");
+        let rows: Vec<String> = view.rows.iter().map(|row| {
+            let mut fields: Vec<String> = view.variables.iter().map(|var| format!("{}: {}", var, bool_word(row.assignment[var]))).collect();
+            fields.extend(row.result.map(|result| format!("result: {}", bool_word(result))));
+            format!("  {{{}}}", fields.join(", "))
+        }).collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_projection(&self, projection: &Projection) -> String {
+        let mut output = String::new();
+        if let Some(sample) = &projection.sample {
+            output.push_str(&format!("# random sample: {}\n", sample_note(sample)));
+        }
+        if !projection.hidden.is_empty() {
+            output.push_str(&format!("# projected away: {}\n", projection.hidden.join(" ")));
+        }
+        output.push_str("[\n");
+        let rows: Vec<String> = projection.rows.iter().map(|row| {
+            let mut fields: Vec<String> = projection.variables.iter().map(|var| format!("{}: {}", var, bool_word(row.assignment[var]))).collect();
+            fields.push(format!("count: {}", row.count));
+            fields.push(format!("result: \"{}\"", projected_result_word(row.result)));
+            format!("  {{{}}}", fields.join(", "))
+        }).collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_trace(&self, trace: &Trace) -> String {
+        let mut output = String::from("[\n");
+        let rows: Vec<String> = trace.nodes
+            .iter()
+            .map(|node| format!("  {{depth: {}, expression: \"{}\", value: {}, deciding: {}}}",
+                node.depth, node.expr, bool_word(node.value), bool_word(node.deciding)))
+            .collect();
+        output.push_str(&rows.join(",\n"));
+        output.push_str("\n]\n");
+        output
+    }
+
+    fn format_satisfiability(&self, report: &Satisfiability) -> String {
+        let assignment = report.assignment.as_ref().map_or("null".to_string(), |assignment| {
+            let fields: Vec<String> = report.variables.iter().map(|var| format!("\"{}\": {}", var, bool_word(assignment[var]))).collect();
+            format!("{{{}}}", fields.join(", "))
+        });
+        format!(
+            "{{satisfiable: {}, assignment: {}, rows_checked: {}, total: {}}}\n",
+            bool_word(report.satisfiable),
+            assignment,
+            report.rows_checked,
+            report.total
+        )
+    }
+
+    fn format_classification(&self, report: &ClassificationReport) -> String {
+        let example = |example: &Option<HashMap<String, bool>>| example.as_ref().map_or("null".to_string(), |assignment| {
+            let fields: Vec<String> = report.variables.iter().map(|var| format!("\"{}\": {}", var, bool_word(assignment[var]))).collect();
+            format!("{{{}}}", fields.join(", "))
+        });
+        format!(
+            "{{classification: \"{}\", true_example: {}, false_example: {}}}\n",
+            report.classification,
+            example(&report.true_example),
+            example(&report.false_example)
+        )
+    }
+
+    fn format_regression(&self, report: &RegressionReport) -> String {
+        let shown = |expr: &Option<Expr>| expr.as_ref().map_or("null".to_string(), |expr| format!("{:?}", expr.to_string()));
+        let rows: Vec<String> = report.entries
+            .iter()
+            .map(|entry| {
+                format!("  {{key: {:?}, status: {}, golden: {}, input: {}, counterexample: {:?}, message: {:?}}}",
+                    entry.key,
+                    regression_status_word(entry.status),
+                    shown(&entry.golden),
+                    shown(&entry.input),
+                    entry.counterexample.as_ref().map_or(String::new(), sorted_assignment),
+                    entry.message.as_deref().unwrap_or(""))
+            })
+            .collect();
+        format!("[\n{}\n]\n", rows.join(",\n"))
+    }
+
+    fn format_cnf(&self, cnf: &Cnf) -> String {
+        let clauses: Vec<String> = cnf.clauses
+            .iter()
+            .map(|clause| {
+                let literals: Vec<String> = clause.iter().map(|literal| format!("{{variable: {:?}, negated: {}}}", literal.variable, bool_word(literal.negated))).collect();
+                format!("[{}]", literals.join(", "))
+            })
+            .collect();
+        let definitions: Vec<String> = cnf.definitions
+            .iter()
+            .map(|definition| format!("{{variable: {:?}, gate: {:?}}}", definition.variable, definition.gate.to_string()))
+            .collect();
+        format!(
+            "{{tseitin: {}, cnf: {:?}, definitions: [{}], clauses: [{}]}}\n",
+            bool_word(cnf.tseitin),
+            cnf.cnf.to_string(),
+            definitions.join(", "),
+            clauses.join(", ")
+        )
+    }
+
+    fn format_dnf(&self, dnf: &Dnf) -> String {
+        let terms: Vec<String> = dnf.terms
+            .iter()
+            .map(|term| {
+                let literals: Vec<String> = term.iter().map(|literal| format!("{{variable: {:?}, negated: {}}}", literal.variable, bool_word(literal.negated))).collect();
+                format!("[{}]", literals.join(", "))
+            })
+            .collect();
+        format!(
+            "{{canonical: {}, minimal: {}, dnf: {:?}, terms: [{}]}}\n",
+            bool_word(dnf.canonical),
+            bool_word(dnf.minimal),
+            dnf.dnf.to_string(),
+            terms.join(", ")
+        )
+    }
+
+    fn format_nnf(&self, nnf: &Nnf) -> String {
+        format!("{{expression: {:?}, nnf: {:?}}}\n", nnf.expression.to_string(), nnf.nnf.to_string())
+    }
+
+    fn format_anf(&self, report: &AnfReport) -> String {
+        let monomials: Vec<String> = report.monomials
+            .iter()
+            .map(|monomial| format!("[{}]", monomial.iter().map(|name| format!("{:?}", name)).collect::<Vec<_>>().join(", ")))
+            .collect();
+        format!(
+            "{{expression: {:?}, anf: {:?}, degree: {}, monomials: [{}]}}\n",
+            report.expression.to_string(),
+            report.polynomial,
+            report.degree,
+            monomials.join(", ")
+        )
+    }
+
+    fn format_conversion(&self, conversion: &Conversion) -> String {
+        format!(
+            "{{expression: {:?}, basis: {:?}, converted: {:?}, gates: {}, distinct_gates: {}, original_gates: {}}}\n",
+            conversion.expression.to_string(),
+            conversion.basis.to_string(),
+            conversion.converted.to_string(),
+            conversion.gates,
+            conversion.distinct_gates,
+            conversion.original_gates
+        )
+    }
+
+    fn format_report(&self, report: &Report) -> String {
+        let properties = &report.properties;
+        let essential: Vec<String> = properties.essential.iter().map(|name| format!("{:?}", name)).collect();
+        let karnaugh_map = report.karnaugh_map.as_ref().map_or(String::new(), |map| {
+            let cells: Vec<String> = map.cells
+                .iter()
+                .map(|row| format!("[{}]", row.iter().map(|&cell| bool_word(cell)).collect::<Vec<_>>().join(", ")))
+                .collect();
+            format!(", karnaugh_map: {{rows: {:?}, columns: {:?}, cells: [{}]}}", map.row_labels, map.column_labels, cells.join(", "))
+        });
+        format!(
+            "{{expression: {:?}, classification: {:?}, reduced: {:?}, minimal: {}, properties: {{true_rows: {}, rows: {}, balanced: {}, monotone: {}, self_dual: {}, affine: {}, symmetric: {}, degree: {}, essential: [{}]}}, table: {}{}}}\n",
+            report.expression.to_string(),
+            report.classification.classification.to_string(),
+            report.reduction.reduced.to_string(),
+            bool_word(report.reduction.minimal),
+            properties.true_rows,
+            properties.rows,
+            bool_word(properties.balanced),
+            bool_word(properties.monotone),
+            bool_word(properties.self_dual),
+            bool_word(properties.affine),
+            bool_word(properties.symmetric),
+            properties.degree,
+            essential.join(", "),
+            self.format_truth_table(&report.table).trim_end(),
+            karnaugh_map
+        )
+    }
+}
+
+impl Formatter for PlainFormatter {
+    fn format_truth_table(&self, table: &TruthTable) -> String {
+        let mut output = plain_notes(table.range.as_ref(), table.sample.as_ref());
+        let mut header: Vec<String> = table.variables.iter().map(plain_text).collect();
+        header.push("result".to_string());
+        let rows: Vec<Vec<String>> = table.rows
+            .iter()
+            .map(|row| {
+                table.variables
+                    .iter()
+                    .map(|var| plain_bit(row.assignments.get(var).copied().unwrap_or(false)).to_string())
+                    .chain([plain_bit(row.result).to_string()])
+                    .collect()
+            })
+            .collect();
+        output.push_str(&plain_grid(&header, &rows));
+        output
+    }
+
+    fn format_three_valued_table(&self, table: &ThreeValuedTable) -> String {
+        let mut header: Vec<String> = table.variables.iter().map(plain_text).collect();
+        header.push("result".to_string());
+        let rows: Vec<Vec<String>> = table.rows
+            .iter()
+            .map(|row| {
+                table.variables
+                    .iter()
+                    .map(|var| plain_ternary(row.assignments.get(var).copied().unwrap_or(Ternary::Unknown)).to_string())
+                    .chain([plain_ternary(row.result).to_string()])
+                    .collect()
+            })
+            .collect();
+        plain_grid(&header, &rows)
+    }
+
+    fn format_group_summary(&self, summary: &GroupSummary) -> String {
+        let mut header: Vec<String> = summary.variables.iter().map(|var| plain_text(var)).collect();
+        header.extend(["true".to_string(), "fraction".to_string()]);
+        let rows: Vec<Vec<String>> = summary.groups
+            .iter()
+            .map(|group| {
+                summary.variables
+                    .iter()
+                    .map(|var| plain_bit(group.assignment[var]).to_string())
+                    .chain([format!("{}/{}", group.true_count, group.total), format!("{:.3}", group.fraction())])
+                    .collect()
+            })
+            .collect();
+        let mut output = plain_grid(&header, &rows);
+        if !summary.collapsed.is_empty() {
+            output.push_str(&format!("collapsed: {}\n", plain_text(&summary.collapsed.join(" "))));
+        }
+        output
+    }
+
+    fn format_table_view(&self, view: &TableView) -> String {
+        let mut output = plain_notes(view.range.as_ref(), view.sample.as_ref());
+        let mut header: Vec<String> = view.variables.iter().map(|var| plain_text(var)).collect();
+        if view.result {
+            header.push("result".to_string());
+        }
+        let rows: Vec<Vec<String>> = view.rows
+            .iter()
+            .map(|row| {
+                view.variables
+                    .iter()
+                    .map(|var| plain_bit(row.assignment[var]).to_string())
+                    .chain(row.result.map(|result| plain_bit(result).to_string()))
+                    .collect()
+            })
+            .collect();
+        output.push_str(&plain_grid(&header, &rows));
+        if !view.hidden.is_empty() {
+            output.push_str(&format!("hidden: {}\n", plain_text(&view.hidden.join(" "))));
+        }
+        output
+    }
+
+    fn format_projection(&self, projection: &Projection) -> String {
+        let mut output = plain_notes(projection.range.as_ref(), projection.sample.as_ref());
+        let mut header: Vec<String> = projection.variables.iter().map(|var| plain_text(var)).collect();
+        header.extend(["rows".to_string(), "result".to_string()]);
+        let rows: Vec<Vec<String>> = projection.rows
+            .iter()
+            .map(|row| {
+                let result = match row.result {
+                    ProjectedResult::True => "1",
+                    ProjectedResult::False => "0",
+                    ProjectedResult::Mixed => "mixed",
+                };
+                projection.variables
+                    .iter()
+                    .map(|var| plain_bit(row.assignment[var]).to_string())
+                    .chain([row.count.to_string(), result.to_string()])
+                    .collect()
+            })
+            .collect();
+        output.push_str(&plain_grid(&header, &rows));
+        if !projection.hidden.is_empty() {
+            output.push_str(&format!("projected away: {}\n", plain_text(&projection.hidden.join(" "))));
+        }
+        output
+    }
+
+    fn format_equivalence_result(&self, check: &EquivalenceCheck, left_str: &str, right_str: &str) -> String {
+        plain_text(&TableFormatter.format_equivalence_result(check, left_str, right_str))
+    }
+
+    fn format_reduction_result(&self, reduction: &Reduction) -> String {
+        plain_text(&TableFormatter.format_reduction_result(reduction))
+    }
+
+    fn format_table_diff(&self, diff: &TableDiff, left_name: &str, right_name: &str) -> String {
+        plain_text(&TableFormatter.format_table_diff(diff, left_name, right_name))
+    }
+
+    fn format_subexpressions(&self, report: &SubexpressionReport) -> String {
+        plain_text(&TableFormatter.format_subexpressions(report))
+    }
+
+    fn format_snippet_rewrite(&self, rewrite: &SnippetRewrite) -> String {
+        plain_text(&TableFormatter.format_snippet_rewrite(rewrite))
+    }
+
+    fn format_lint_report(&self, report: &LintReport) -> String {
+        plain_text(&TableFormatter.format_lint_report(report))
+    }
+
+    fn format_prime_implicants(&self, report: &PrimeImplicantReport) -> String {
+        plain_text(&TableFormatter.format_prime_implicants(report))
+    }
+
+    fn format_chain_proof(&self, proof: &ChainProof) -> String {
+        plain_text(&TableFormatter.format_chain_proof(proof))
+    }
+
+    fn format_expansion(&self, expansion: &Expansion) -> String {
+        plain_text(&TableFormatter.format_expansion(expansion))
+    }
+
+    fn format_influence(&self, report: &InfluenceReport) -> String {
+        plain_text(&TableFormatter.format_influence(report))
+    }
+
+    fn format_threshold(&self, analysis: &ThresholdAnalysis) -> String {
+        plain_text(&TableFormatter.format_threshold(analysis))
+    }
+
+    fn format_explanations(&self, report: &ExplanationReport) -> String {
+        plain_text(&TableFormatter.format_explanations(report))
+    }
+
+    fn format_abduction(&self, abduction: &Abduction) -> String {
+        plain_text(&TableFormatter.format_abduction(abduction))
+    }
+
+    fn format_propagation(&self, propagation: &Propagation) -> String {
+        plain_text(&TableFormatter.format_propagation(propagation))
+    }
+
+    fn format_vectors(&self, document: &CsvDocument, results: &[bool]) -> String {
+        let mut header: Vec<String> = document.headers.iter().map(|h| plain_text(h)).collect();
+        header.push("result".to_string());
+        let rows: Vec<Vec<String>> = document.rows
+            .iter()
+            .zip(results)
+            .map(|(row, &result)| row.iter().map(|cell| plain_text(cell)).chain([plain_bit(result).to_string()]).collect())
+            .collect();
+        plain_grid(&header, &rows)
+    }
+
+    fn format_cut_sets(&self, report: &CutSetReport) -> String {
+        plain_text(&TableFormatter.format_cut_sets(report))
+    }
+
+    fn format_spectrum(&self, report: &SpectrumReport) -> String {
+        plain_text(&TableFormatter.format_spectrum(report))
+    }
+
+    fn format_bias(&self, report: &BiasReport) -> String {
+        plain_text(&TableFormatter.format_bias(report))
+    }
+
+    fn format_sbox(&self, report: &SboxReport) -> String {
+        plain_text(&TableFormatter.format_sbox(report))
+    }
+
+    fn format_degree(&self, report: &DegreeReport) -> String {
+        plain_text(&TableFormatter.format_degree(report))
+    }
+
+    fn format_capabilities(&self, info: &Capabilities) -> String {
+        plain_text(&TableFormatter.format_capabilities(info))
+    }
+
+    fn format_synthesis(&self, synthesis: &Synthesis) -> String {
+        plain_text(&TableFormatter.format_synthesis(synthesis))
+    }
+
+    fn format_rewrite(&self, rewrite: &Rewrite) -> String {
+        plain_text(&TableFormatter.format_rewrite(rewrite))
+    }
+
+    fn format_trace(&self, trace: &Trace) -> String {
+        plain_text(&TableFormatter.format_trace(trace))
+    }
+
+    fn format_satisfiability(&self, report: &Satisfiability) -> String {
+        plain_text(&TableFormatter.format_satisfiability(report))
+    }
+
+    fn format_classification(&self, report: &ClassificationReport) -> String {
+        plain_text(&TableFormatter.format_classification(report))
+    }
+
+    fn format_regression(&self, report: &RegressionReport) -> String {
+        plain_text(&TableFormatter.format_regression(report))
+    }
+
+    fn format_cnf(&self, cnf: &Cnf) -> String {
+        plain_text(&TableFormatter.format_cnf(cnf))
+    }
+
+    fn format_dnf(&self, dnf: &Dnf) -> String {
+        plain_text(&TableFormatter.format_dnf(dnf))
+    }
+
+    fn format_nnf(&self, nnf: &Nnf) -> String {
+        plain_text(&TableFormatter.format_nnf(nnf))
+    }
+
+    fn format_anf(&self, report: &AnfReport) -> String {
+        plain_text(&TableFormatter.format_anf(report))
+    }
+
+    fn format_conversion(&self, conversion: &Conversion) -> String {
+        plain_text(&TableFormatter.format_conversion(conversion))
+    }
+
+    fn format_report(&self, report: &Report) -> String {
+        let table = TableFormatter.format_report(report);
+        // The table formatter's truth table sits between the first two blank lines
+        let start = table.find("truth table:\n").map_or(0, |i| i + "truth table:\n".len());
+        let end = table[start..].find("\n\n").map_or(table.len(), |i| start + i + 1);
+        format!("{}{}{}", plain_text(&table[..start]), self.format_truth_table(&report.table), plain_text(&table[end..]))
+    }
+}
+
+pub fn get_formatter(format: &OutputFormat) -> Box<dyn Formatter> {
+    match format {
+        OutputFormat::Table => Box::new(TableFormatter),
+        OutputFormat::Json => Box::new(JsonFormatter),
+        OutputFormat::Csv => Box::new(CsvFormatter),
+        OutputFormat::Nuon => Box::new(NuonFormatter),
+        OutputFormat::Plain => Box::new(PlainFormatter),
+    }
+}
+
+pub fn format_truth_table(table: &TruthTable, format: &OutputFormat) -> String {
+    get_formatter(format).format_truth_table(table)
+}
+
+pub fn format_equivalence_result(check: &EquivalenceCheck, left_str: &str, right_str: &str, format: &OutputFormat) -> String {
+    get_formatter(format).format_equivalence_result(check, left_str, right_str)
+}
 
 pub fn format_reduction_result(reduction: &Reduction, format: &OutputFormat) -> String {
     get_formatter(format).format_reduction_result(reduction)
-}
\ No newline at end of file
+}
+
+pub fn format_table_diff(diff: &TableDiff, left_name: &str, right_name: &str, format: &OutputFormat) -> String {
+    get_formatter(format).format_table_diff(diff, left_name, right_name)
+}
+
+pub fn format_subexpressions(report: &SubexpressionReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_subexpressions(report)
+}
+
+pub fn format_snippet_rewrite(rewrite: &SnippetRewrite, format: &OutputFormat) -> String {
+    get_formatter(format).format_snippet_rewrite(rewrite)
+}
+
+pub fn format_lint_report(report: &LintReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_lint_report(report)
+}
+
+pub fn format_prime_implicants(report: &PrimeImplicantReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_prime_implicants(report)
+}
+
+pub fn format_chain_proof(proof: &ChainProof, format: &OutputFormat) -> String {
+    get_formatter(format).format_chain_proof(proof)
+}
+
+pub fn format_expansion(expansion: &Expansion, format: &OutputFormat) -> String {
+    get_formatter(format).format_expansion(expansion)
+}
+
+pub fn format_influence(report: &InfluenceReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_influence(report)
+}
+
+pub fn format_threshold(analysis: &ThresholdAnalysis, format: &OutputFormat) -> String {
+    get_formatter(format).format_threshold(analysis)
+}
+
+pub fn format_explanations(report: &ExplanationReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_explanations(report)
+}
+
+pub fn format_abduction(abduction: &Abduction, format: &OutputFormat) -> String {
+    get_formatter(format).format_abduction(abduction)
+}
+
+pub fn format_propagation(propagation: &Propagation, format: &OutputFormat) -> String {
+    get_formatter(format).format_propagation(propagation)
+}
+
+pub fn format_three_valued_table(table: &ThreeValuedTable, format: &OutputFormat) -> String {
+    get_formatter(format).format_three_valued_table(table)
+}
+
+/// The rows of a `--vectors` file as they were read, each followed by its result
+pub fn format_vectors(document: &CsvDocument, results: &[bool], format: &OutputFormat) -> String {
+    get_formatter(format).format_vectors(document, results)
+}
+
+pub fn format_cut_sets(report: &CutSetReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_cut_sets(report)
+}
+
+pub fn format_group_summary(summary: &GroupSummary, format: &OutputFormat) -> String {
+    get_formatter(format).format_group_summary(summary)
+}
+
+/// Render one column of values without headers, for piping into other tools
+pub fn format_column(values: &[bool], format: ColumnFormat) -> String {
+    let bits: Vec<&str> = values.iter().map(|&value| if value { "1" } else { "0" }).collect();
+    match format {
+        ColumnFormat::Bits => format!("{}\n", bits.concat()),
+        ColumnFormat::Lines => bits.iter().map(|bit| format!("{}\n", bit)).collect(),
+    }
+}
+
+pub fn format_spectrum(report: &SpectrumReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_spectrum(report)
+}
+
+pub fn format_bias(report: &BiasReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_bias(report)
+}
+
+pub fn format_sbox(report: &SboxReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_sbox(report)
+}
+
+pub fn format_degree(report: &DegreeReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_degree(report)
+}
+
+pub fn format_capabilities(info: &Capabilities, format: &OutputFormat) -> String {
+    get_formatter(format).format_capabilities(info)
+}
+
+pub fn format_synthesis(synthesis: &Synthesis, format: &OutputFormat) -> String {
+    get_formatter(format).format_synthesis(synthesis)
+}
+
+pub fn format_rewrite(rewrite: &Rewrite, format: &OutputFormat) -> String {
+    get_formatter(format).format_rewrite(rewrite)
+}
+
+pub fn format_table_view(view: &TableView, format: &OutputFormat) -> String {
+    get_formatter(format).format_table_view(view)
+}
+
+pub fn format_projection(projection: &Projection, format: &OutputFormat) -> String {
+    get_formatter(format).format_projection(projection)
+}
+
+pub fn format_trace(trace: &Trace, format: &OutputFormat) -> String {
+    get_formatter(format).format_trace(trace)
+}
+
+pub fn format_satisfiability(report: &Satisfiability, format: &OutputFormat) -> String {
+    get_formatter(format).format_satisfiability(report)
+}
+
+pub fn format_classification(report: &ClassificationReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_classification(report)
+}
+
+pub fn format_regression(report: &RegressionReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_regression(report)
+}
+
+pub fn format_cnf(cnf: &Cnf, format: &OutputFormat) -> String {
+    get_formatter(format).format_cnf(cnf)
+}
+
+pub fn format_dnf(dnf: &Dnf, format: &OutputFormat) -> String {
+    get_formatter(format).format_dnf(dnf)
+}
+
+pub fn format_nnf(nnf: &Nnf, format: &OutputFormat) -> String {
+    get_formatter(format).format_nnf(nnf)
+}
+
+pub fn format_anf(report: &AnfReport, format: &OutputFormat) -> String {
+    get_formatter(format).format_anf(report)
+}
+
+pub fn format_conversion(conversion: &Conversion, format: &OutputFormat) -> String {
+    get_formatter(format).format_conversion(conversion)
+}
+
+pub fn format_report(report: &Report, format: &OutputFormat) -> String {
+    get_formatter(format).format_report(report)
+}
+
+/// Add `warnings` to JSON output as a top-level `warnings` array.
+///
+/// Other formats are returned unchanged; their warnings belong on stderr.
+pub fn attach_warnings(output: String, warnings: &[Warning], format: &OutputFormat) -> String {
+    if warnings.is_empty() || !matches!(format, OutputFormat::Json) {
+        return output;
+    }
+    // Splice the array in before the closing brace so the existing key order is kept
+    let Ok(serde_json::Value::Object(_)) = serde_json::from_str(&output) else {
+        return output;
+    };
+    let Some(body) = output.trim_end().strip_suffix('}') else {
+        return output;
+    };
+    let array = serde_json::to_string_pretty(warnings).unwrap_or_else(|e| format!("Error serializing to JSON: {}", e));
+    let body = body.trim_end();
+    let separator = if body.ends_with('{') { "" } else { "," };
+    format!("{}{}\n  \"warnings\": {}\n}}", body, separator, array.replace('\n', "\n  "))
+}
@@ -0,0 +1,115 @@
+use crate::config::{APP_NAME, VERSION};
+use crate::io::aiger;
+use crate::source::{Expr, FINGERPRINT_VERSION};
+
+/// How a generated file was produced, written into it as a header comment so
+/// that a checked-in export can be traced back to the command that made it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    /// The expression as it was given, before parsing
+    pub expression: String,
+    /// The options given on the command line, such as `--format aig`
+    pub options: Vec<String>,
+    /// [`Expr::fingerprint`] of the parsed expression
+    pub fingerprint: u64,
+}
+
+impl Provenance {
+    pub fn new(expression: &str, expr: &Expr, options: Vec<String>) -> Self {
+        Self { expression: expression.to_string(), options, fingerprint: expr.fingerprint() }
+    }
+
+    /// The header, one line per field, without comment markers
+    pub fn lines(&self) -> Vec<String> {
+        let options = if self.options.is_empty() { "(defaults)".to_string() } else { self.options.join(" ") };
+        vec![
+            format!("generated by {} {}", APP_NAME, VERSION),
+            // A multi-line expression would end a line comment early
+            format!("expression: {}", self.expression.split_whitespace().collect::<Vec<_>>().join(" ")),
+            format!("options: {}", options),
+            format!("fingerprint: {:016x} (v{})", self.fingerprint, FINGERPRINT_VERSION),
+        ]
+    }
+}
+
+/// Where a file format keeps comments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentSyntax {
+    /// An AIGER comment section after the graph, in either encoding
+    Aiger,
+    /// `c` lines before a DIMACS problem line
+    Dimacs,
+    /// An HTML comment, which Markdown renderers also hide
+    Html,
+}
+
+impl Provenance {
+    /// `document` with the header in a comment where `syntax` keeps one: a
+    /// comment section at the end of an AIGER file, `c` lines at the start of
+    /// a DIMACS file, or an HTML comment before the `<html>` element of a page
+    /// and at the start of a Markdown document
+    pub fn stamp(&self, mut document: Vec<u8>, syntax: CommentSyntax) -> Vec<u8> {
+        let lines = self.lines();
+        match syntax {
+            CommentSyntax::Aiger => {
+                document.extend(aiger::comment_section(&lines).bytes());
+                document
+            }
+            CommentSyntax::Dimacs => {
+                let header: String = lines.iter().map(|line| format!("c {}\n", line)).collect();
+                [header.into_bytes(), document].concat()
+            }
+            CommentSyntax::Html => {
+                let mut comment = String::from("<!--\n");
+                for line in &lines {
+                    // `-->` in an expression would end the comment early
+                    comment.push_str(&line.replace("-->", "-- >"));
+                    comment.push('\n');
+                }
+                comment.push_str("-->\n");
+                // Before the doctype, a comment would put browsers in quirks mode
+                let at = document.windows(7).position(|window| window == b"<html>\n").unwrap_or(0);
+                document.splice(at..at, comment.into_bytes());
+                document
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    #[test]
+    fn test_lines_name_every_field() {
+        let expr = Parser::new("(a and b) or not c").parse().unwrap();
+        let provenance = Provenance::new("(a and b)\n  or not c", &expr, vec!["--format".to_string(), "aig".to_string()]);
+        assert_eq!(provenance.lines(), [
+            format!("generated by {} {}", APP_NAME, VERSION),
+            "expression: (a and b) or not c".to_string(),
+            "options: --format aig".to_string(),
+            "fingerprint: 5f7c178a60956d4c (v1)".to_string(),
+        ]);
+        assert_eq!(Provenance::new("a", &expr, Vec::new()).lines()[2], "options: (defaults)");
+    }
+
+    #[test]
+    fn test_stamp_puts_the_header_where_each_format_keeps_comments() {
+        // ttt syntax has no `-->`, but other dialects' text could
+        let provenance = Provenance { expression: "a --> b".to_string(), options: Vec::new(), fingerprint: 0 };
+        let stamp = |document: &str, syntax| String::from_utf8(provenance.stamp(document.as_bytes().to_vec(), syntax)).unwrap();
+
+        let aag = stamp("aag 0 0 0 1 0\n0\n", CommentSyntax::Aiger);
+        assert!(aag.starts_with("aag 0 0 0 1 0\n0\nc\ngenerated by "));
+        let dimacs = stamp("p cnf 0 0\n", CommentSyntax::Dimacs);
+        assert!(dimacs.starts_with("c generated by "));
+        assert!(dimacs.lines().take(4).all(|line| line.starts_with("c ")));
+        assert!(dimacs.ends_with("\np cnf 0 0\n"));
+        let html = stamp("<!DOCTYPE html>\n<html>\n</html>\n", CommentSyntax::Html);
+        assert!(html.starts_with("<!DOCTYPE html>\n<!--\ngenerated by "));
+        assert!(html.contains("expression: a -- > b\n"));
+        assert!(html.contains("(v1)\n-->\n<html>"));
+        assert!(stamp("# Report\n", CommentSyntax::Html).starts_with("<!--\n"));
+    }
+}
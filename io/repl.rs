@@ -0,0 +1,413 @@
+use crate::eval::{Evaluator, Variables};
+use crate::io::csv::parse_bool;
+use crate::io::output::{OutputFormat, format_trace};
+use crate::io::input::InputHandler;
+use crate::io::terminal::{self, Key, RawMode};
+use crate::source::{Expr, Library, Precedence};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use miette::{IntoDiagnostic, Result, WrapErr};
+
+const HELP: &str = "\
+<expression>        explore an expression; variables start false, or keep their value
+:toggle VAR...      flip variables (:t)
+:set VAR=VALUE...   set variables to true/false, T/F or 1/0 (:s)
+:show               show the current trace again
+:explore            flip variables with the arrow keys, q to come back (:x)
+def NAME := EXPR    define a name for later expressions, as in a --library file
+:save FILE          write the definitions and lines so far to FILE
+:load FILE          replay a saved session
+:help               this list (:h)
+:quit               leave (:q)
+";
+
+/// What the session does after a line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reply {
+    /// Text to show, possibly empty
+    Output(String),
+    /// Hand the terminal to [`run_explorer`]
+    Explore,
+    Quit,
+}
+
+/// An interactive exploration of one expression: the user edits an assignment
+/// and each change re-evaluates every subexpression, marking the operands that
+/// force the result.
+pub struct Session {
+    format: OutputFormat,
+    expression: Option<(Expr, Variables)>,
+    assignment: HashMap<String, bool>,
+    /// The library expressions are read with, and the definitions made since
+    library: Library,
+    /// Lines that changed the session, in order; `:save` writes them out
+    history: Vec<String>,
+    /// Set while replaying a file, which may not load another
+    loading: bool,
+}
+
+impl Session {
+    pub fn new(format: OutputFormat) -> Self {
+        Self::with_library(format, Library::new())
+    }
+
+    /// Start from `library`'s definitions; a saved session holds only those made in it
+    pub fn with_library(format: OutputFormat, library: Library) -> Self {
+        Self { format, expression: None, assignment: HashMap::new(), library, history: Vec::new(), loading: false }
+    }
+
+    /// Handle one line: a `:` command, a definition, or an expression to
+    /// explore, read with `parse` and the session's library the way the
+    /// command line reads expressions
+    pub fn handle(&mut self, line: &str, parse: &impl Fn(&str, &Library) -> Result<Expr>) -> Result<Reply> {
+        let line = line.trim();
+        let reply = self.dispatch(line, parse)?;
+        let changes_state = !line.is_empty() && !["q", "quit", "h", "help", "show", "x", "explore", "save", "load"].contains(&command_name(line));
+        if changes_state {
+            self.history.push(line.to_string());
+        }
+        Ok(reply)
+    }
+
+    /// Run each line of a saved session in turn, returning every line with its
+    /// output; blank lines and `#` comments are skipped, and `:quit` ends the replay
+    pub fn replay(&mut self, script: &str, parse: &impl Fn(&str, &Library) -> Result<Expr>) -> Result<Vec<(String, String)>> {
+        let loading = std::mem::replace(&mut self.loading, true);
+        let transcript = self.replay_lines(script, parse);
+        self.loading = loading;
+        transcript
+    }
+
+    fn replay_lines(&mut self, script: &str, parse: &impl Fn(&str, &Library) -> Result<Expr>) -> Result<Vec<(String, String)>> {
+        let mut transcript = Vec::new();
+        for (idx, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match self.handle(line, parse).wrap_err_with(|| format!("Line {}: {}", idx + 1, line))? {
+                Reply::Output(text) => transcript.push((line.to_string(), text)),
+                Reply::Explore => return Err(miette::miette!("Line {}: :explore needs a terminal", idx + 1)),
+                Reply::Quit => break,
+            }
+        }
+        Ok(transcript)
+    }
+
+    fn dispatch(&mut self, line: &str, parse: &impl Fn(&str, &Library) -> Result<Expr>) -> Result<Reply> {
+        if line.starts_with("def ") || line.starts_with("alias ") {
+            return self.define(line);
+        }
+        let Some(command) = line.strip_prefix(':') else {
+            if line.is_empty() {
+                return Ok(Reply::Output(String::new()));
+            }
+            return self.explore(parse(line, &self.library)?);
+        };
+        let (name, args) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        let args = args.trim();
+        match name {
+            "q" | "quit" => Ok(Reply::Quit),
+            "h" | "help" => Ok(Reply::Output(HELP.to_string())),
+            "show" => self.show(),
+            "x" | "explore" if self.expression.is_none() => Err(miette::miette!("No expression to explore yet; enter one first")),
+            "x" | "explore" => Ok(Reply::Explore),
+            "save" => self.save(Path::new(args)),
+            "load" => {
+                if self.loading {
+                    return Err(miette::miette!("A session file can't load another"));
+                }
+                let script = InputHandler::read_file(Path::new(args))?;
+                let mut transcript = self.replay(&script, parse)
+                    .wrap_err_with(|| format!("Failed to load session '{}'", args))?;
+                // The state the session was left in, as its last line showed it
+                Ok(Reply::Output(transcript.pop().map(|(_, text)| text).unwrap_or_default()))
+            }
+            "t" | "toggle" => {
+                let vars: Vec<&str> = args.split_whitespace().collect();
+                self.check_variables(&vars)?;
+                for var in vars {
+                    let value = self.assignment.entry(var.to_string()).or_default();
+                    *value = !*value;
+                }
+                self.show()
+            }
+            "s" | "set" => {
+                let mut values = Vec::new();
+                for pair in args.split_whitespace() {
+                    let (var, value) = pair.split_once('=')
+                        .ok_or_else(|| miette::miette!("Expected VAR=VALUE, got '{}'", pair))?;
+                    let value = parse_bool(value)
+                        .ok_or_else(|| miette::miette!("Invalid value '{}' for '{}': expected true/false, T/F or 1/0", value, var))?;
+                    values.push((var, value));
+                }
+                self.check_variables(&values.iter().map(|&(var, _)| var).collect::<Vec<_>>())?;
+                for (var, value) in values {
+                    self.assignment.insert(var.to_string(), value);
+                }
+                self.show()
+            }
+            _ => Err(miette::miette!("Unknown command ':{}'; :help lists the commands", name)),
+        }
+    }
+
+    fn define(&mut self, line: &str) -> Result<Reply> {
+        let definitions = Library::parse(line).map_err(|e| miette::miette!("{}", e))?;
+        self.library.extend(definitions).map_err(|name| miette::miette!("'{}' is already defined", name))?;
+        Ok(Reply::Output(String::new()))
+    }
+
+    fn save(&self, path: &Path) -> Result<Reply> {
+        if path.as_os_str().is_empty() {
+            return Err(miette::miette!("Name the file to save to"));
+        }
+        let mut text = String::from("# ttt repl session; replay with `ttt repl --script FILE` or `:load FILE`\n");
+        for line in &self.history {
+            text.push_str(line);
+            text.push('\n');
+        }
+        std::fs::write(path, text)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to write session '{}'", path.display()))?;
+        Ok(Reply::Output(format!("Saved {} lines to '{}'\n", self.history.len(), path.display())))
+    }
+
+    fn explore(&mut self, expr: Expr) -> Result<Reply> {
+        let variables = Variables::from_expr(&expr).map_err(|e| miette::miette!("{}", e))?;
+        self.assignment = variables
+            .iter()
+            .map(|var| (var.to_string(), self.assignment.get(var).copied().unwrap_or(false)))
+            .collect();
+        self.expression = Some((expr, variables));
+        self.show()
+    }
+
+    fn check_variables(&self, vars: &[&str]) -> Result<()> {
+        let Some((_, variables)) = &self.expression else {
+            return Err(miette::miette!("No expression to explore yet; enter one first"));
+        };
+        if vars.is_empty() {
+            return Err(miette::miette!("Name the variables to change"));
+        }
+        match vars.iter().find(|var| !variables.contains(var)) {
+            Some(var) => Err(miette::miette!("'{}' is not a variable of the expression", var)),
+            None => Ok(()),
+        }
+    }
+
+    fn show(&self) -> Result<Reply> {
+        let Some((expr, _)) = &self.expression else {
+            return Err(miette::miette!("No expression to explore yet; enter one first"));
+        };
+        let trace = Evaluator::trace(expr, &self.assignment).map_err(|e| miette::miette!("{}", e))?;
+        Ok(Reply::Output(format_trace(&trace, &self.format)))
+    }
+}
+
+/// The session's expression as a screen where the arrow keys choose and flip
+/// variables, redrawn after every key with the operands that force the result
+/// in bold and the innermost of them highlighted
+pub struct Explorer<'s> {
+    session: &'s mut Session,
+    selected: usize,
+}
+
+impl<'s> Explorer<'s> {
+    pub fn new(session: &'s mut Session) -> Result<Self> {
+        if session.expression.is_none() {
+            return Err(miette::miette!("No expression to explore yet; enter one first"));
+        }
+        Ok(Self { session, selected: 0 })
+    }
+
+    fn variables(&self) -> &Variables {
+        &self.session.expression.as_ref().expect("checked in new").1
+    }
+
+    /// Act on `key`: left and right choose a variable, up, down and Enter flip
+    /// it. Returns false once the user leaves.
+    pub fn press(&mut self, key: Key) -> bool {
+        let count = self.variables().len();
+        match key {
+            Key::Quit => return false,
+            _ if count == 0 => {}
+            Key::Left => self.selected = (self.selected + count - 1) % count,
+            Key::Right => self.selected = (self.selected + 1) % count,
+            Key::Up | Key::Down | Key::Select => {
+                let var = self.variables().iter().nth(self.selected).expect("selection within the variables").to_string();
+                let value = self.session.assignment.entry(var.clone()).or_default();
+                *value = !*value;
+                // Saved as the line that makes the same change
+                self.session.history.push(format!(":t {}", var));
+            }
+            Key::Other => {}
+        }
+        true
+    }
+
+    /// The whole screen, starting by clearing it
+    pub fn render(&self) -> Result<String> {
+        let (expr, variables) = self.session.expression.as_ref().expect("checked in new");
+        let trace = Evaluator::trace(expr, &self.session.assignment).map_err(|e| miette::miette!("{}", e))?;
+        let symbol = |value: bool| if value { "T" } else { "F" };
+
+        let mut screen = format!("{}←/→ choose a variable   ↑/↓ flip it   q back to the prompt\n\n", terminal::CLEAR);
+        let cells: Vec<String> = variables
+            .iter()
+            .enumerate()
+            .map(|(idx, var)| {
+                let cell = format!("{}={}", var, symbol(trace.assignment[var]));
+                if idx == self.selected { format!("{}{}{}", terminal::REVERSE, cell, terminal::RESET) } else { cell }
+            })
+            .collect();
+        screen.push_str(&format!("{} → {}\n\n", cells.join(" "), symbol(trace.result)));
+
+        // The chain of deciding operands is nested, so its innermost node comes last
+        let forcing = trace.forced_by.as_ref().and_then(|_| trace.nodes.iter().rposition(|node| node.deciding));
+        for (idx, node) in trace.nodes.iter().enumerate() {
+            let line = format!("{} {}  {}{}", if node.deciding { '▸' } else { ' ' }, symbol(node.value), "  ".repeat(node.depth), Precedence::Standard.render(&node.expr));
+            match (Some(idx) == forcing, node.deciding) {
+                (true, _) => screen.push_str(&format!("{}{}{}\n", terminal::HIGHLIGHT, line, terminal::RESET)),
+                (false, true) => screen.push_str(&format!("{}{}{}\n", terminal::BOLD, line, terminal::RESET)),
+                (false, false) => screen.push_str(&format!("{}\n", line)),
+            }
+        }
+        match &trace.forced_by {
+            Some(expr) => screen.push_str(&format!("\nForced by: {}\n", Precedence::Standard.render(expr))),
+            None => screen.push_str("\nNo single operand forces the result\n"),
+        }
+        Ok(screen)
+    }
+}
+
+/// Explore `session`'s expression on the terminal until the user leaves, then
+/// reply with the trace as the line view shows it
+pub fn run_explorer(session: &mut Session) -> Result<Reply> {
+    let mut explorer = Explorer::new(session)?;
+    let raw = RawMode::enable()?;
+    let mut stdout = std::io::stdout();
+    let mut stdin = std::io::stdin().lock();
+    let mut buffer = [0; 64];
+    print!("{}", terminal::ENTER_SCREEN);
+    let explored = (|| -> Result<()> {
+        loop {
+            print!("{}", explorer.render()?);
+            stdout.flush().into_diagnostic()?;
+            let read = stdin.read(&mut buffer).into_diagnostic()?;
+            if read == 0 || !Key::decode(&buffer[..read]).into_iter().all(|key| explorer.press(key)) {
+                return Ok(());
+            }
+        }
+    })();
+    print!("{}", terminal::LEAVE_SCREEN);
+    let _ = stdout.flush();
+    drop(raw);
+    explored?;
+    session.show()
+}
+
+/// The name of a `:` command, or nothing for any other line
+fn command_name(line: &str) -> &str {
+    line.strip_prefix(':').map_or("", |command| command.split_whitespace().next().unwrap_or(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn run(session: &mut Session, line: &str) -> Result<Reply> {
+        session.handle(line, &parse)
+    }
+
+    fn parse(input: &str, library: &Library) -> Result<Expr> {
+        Ok(Parser::new(input).with_library(library.clone()).parse()?)
+    }
+
+    fn output(session: &mut Session, line: &str) -> String {
+        match run(session, line).unwrap() {
+            Reply::Output(text) => text,
+            reply => panic!("unexpected {:?}", reply),
+        }
+    }
+
+    #[test]
+    fn test_toggling_reevaluates() {
+        let mut session = Session::new(OutputFormat::Table);
+        assert!(output(&mut session, "a and (b or c)").starts_with("a=F b=F c=F → F\n"));
+        let text = output(&mut session, ":t a c");
+        assert!(text.starts_with("a=T b=F c=T → T\n"));
+        assert!(text.ends_with("No single operand forces the result\n"));
+        assert!(output(&mut session, ":set a=0").ends_with("Forced by: a\n"));
+        // Values carry over to the next expression where the names match
+        assert!(output(&mut session, "c or d").starts_with("c=T d=F → T\n"));
+        assert_eq!(run(&mut session, ":quit").unwrap(), Reply::Quit);
+    }
+
+    #[test]
+    fn test_session_errors() {
+        let mut session = Session::new(OutputFormat::Table);
+        assert!(run(&mut session, ":t a").is_err());
+        assert!(run(&mut session, "a and").is_err());
+        output(&mut session, "a");
+        assert!(run(&mut session, ":t z").is_err());
+        assert!(run(&mut session, ":set a=maybe").is_err());
+        assert!(run(&mut session, ":frobnicate").is_err());
+    }
+
+    #[test]
+    fn test_explorer_flips_the_chosen_variable() {
+        let mut session = Session::new(OutputFormat::Table);
+        assert!(run(&mut session, ":explore").is_err());
+        output(&mut session, "a and (b or c)");
+        assert_eq!(run(&mut session, ":x").unwrap(), Reply::Explore);
+
+        let mut explorer = Explorer::new(&mut session).unwrap();
+        assert!(explorer.press(Key::Up));
+        let screen = explorer.render().unwrap();
+        assert!(screen.contains(&format!("{}a=T{} b=F c=F → F\n", terminal::REVERSE, terminal::RESET)));
+        // b ∨ c forces the result, and the conjunction above it is on the chain
+        assert!(screen.contains(&format!("{}▸ F    b ∨ c{}\n", terminal::HIGHLIGHT, terminal::RESET)));
+        assert!(screen.contains(&format!("{}▸ F  a ∧ (b ∨ c){}\n", terminal::BOLD, terminal::RESET)));
+        for key in [Key::Left, Key::Select] {
+            explorer.press(key);
+        }
+        let screen = explorer.render().unwrap();
+        assert!(screen.contains(&format!("a=T b=F {}c=T{} → T\n", terminal::REVERSE, terminal::RESET)));
+        assert!(!screen.contains(terminal::HIGHLIGHT));
+        assert!(!explorer.press(Key::Quit));
+
+        // The flips are saved as the lines that make them
+        assert_eq!(session.history, ["a and (b or c)", ":t a", ":t c"]);
+        assert!(output(&mut session, ":show").starts_with("a=T b=F c=T → T\n"));
+        assert!(session.replay(":x\n", &parse).is_err());
+    }
+
+    #[test]
+    fn test_saved_session_replays() {
+        let path = std::env::temp_dir().join(format!("ttt-repl-test-{}.ttt", std::process::id()));
+        let mut session = Session::new(OutputFormat::Table);
+        output(&mut session, "def guard(x) := x and enabled");
+        output(&mut session, "guard(a) or b");
+        output(&mut session, ":t enabled a");
+        output(&mut session, ":show");
+        assert!(run(&mut session, "undefined(a)").is_err());
+        assert_eq!(output(&mut session, &format!(":save {}", path.display())), format!("Saved 3 lines to '{}'\n", path.display()));
+
+        let script = std::fs::read_to_string(&path).unwrap();
+        assert!(script.ends_with("def guard(x) := x and enabled\nguard(a) or b\n:t enabled a\n"));
+        let mut replayed = Session::new(OutputFormat::Table);
+        let transcript = replayed.replay(&script, &parse).unwrap();
+        assert_eq!(transcript.len(), 3);
+        assert!(transcript[2].1.starts_with("a=T b=F enabled=T → T\n"));
+
+        let mut loaded = Session::new(OutputFormat::Table);
+        assert!(output(&mut loaded, &format!(":load {}", path.display())).starts_with("a=T b=F enabled=T → T\n"));
+        assert!(run(&mut loaded, "def guard := c").is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(Session::new(OutputFormat::Table).replay("a\n:t b\n", &parse).is_err());
+        assert!(Session::new(OutputFormat::Table).replay(":load other.ttt", &parse).is_err());
+    }
+}
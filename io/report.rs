@@ -0,0 +1,174 @@
+use crate::eval::Report;
+use crate::io::notebook::{escape_html, html_truth_table};
+use crate::io::output::{karnaugh_cells, property_entries};
+use crate::io::provenance::CommentSyntax;
+
+/// Document formats a report can be written as, in place of `--output`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A Markdown document with one section per analysis
+    Markdown,
+    /// A standalone HTML page with one section per analysis
+    Html,
+}
+
+impl ReportFormat {
+    /// Where the format keeps comments, such as a provenance header
+    pub fn comments(self) -> CommentSyntax {
+        CommentSyntax::Html
+    }
+}
+
+/// The report as a document
+pub fn render_report(report: &Report, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Markdown => render_markdown(report),
+        ReportFormat::Html => render_html(report),
+    }
+}
+
+/// Inline code, fenced with more backticks than the text holds in a row
+fn markdown_code(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+    format!("{fence}{pad}{text}{pad}{fence}")
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn markdown_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut table = markdown_row(header);
+    table.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+    for row in rows {
+        table.push_str(&markdown_row(row));
+    }
+    table
+}
+
+fn bit(value: bool) -> String {
+    if value { "1" } else { "0" }.to_string()
+}
+
+pub fn render_markdown(report: &Report) -> String {
+    let mut doc = format!("# Report: {}\n\n## Truth table\n\n", markdown_code(&report.expression.to_string()));
+    let header: Vec<String> = report.table.variables.iter().map(str::to_string).chain(["result".to_string()]).collect();
+    let rows: Vec<Vec<String>> = report.table.rows
+        .iter()
+        .map(|row| {
+            report.table.variables
+                .iter()
+                .map(|var| bit(row.assignments.get(var).copied().unwrap_or(false)))
+                .chain([bit(row.result)])
+                .collect()
+        })
+        .collect();
+    doc.push_str(&markdown_table(&header, &rows));
+
+    doc.push_str(&format!("\n## Classification\n\n{}\n", report.classification.classification));
+    doc.push_str(&format!("\n## Reduction\n\n{}", markdown_code(&report.reduction.reduced.to_string())));
+    if !report.reduction.minimal {
+        doc.push_str(" (not proven minimal: search budget exhausted)");
+    }
+    doc.push_str("\n\n## Properties\n\n");
+    let properties: Vec<Vec<String>> = property_entries(&report.properties)
+        .into_iter()
+        .map(|(label, value)| vec![label.to_string(), value])
+        .collect();
+    doc.push_str(&markdown_table(&["property".to_string(), "value".to_string()], &properties));
+
+    if let Some(map) = &report.karnaugh_map {
+        let cells = karnaugh_cells(map);
+        doc.push_str("\n## Karnaugh map\n\n");
+        doc.push_str(&markdown_table(&cells[0], &cells[1..]));
+    }
+    doc
+}
+
+pub fn render_html(report: &Report) -> String {
+    let expression = escape_html(&report.expression.to_string());
+    let mut doc = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Report: {expression}</title>\n</head>\n<body>\n<h1>Report: <code>{expression}</code></h1>\n"
+    );
+    doc.push_str("<h2>Truth table</h2>\n");
+    doc.push_str(&html_truth_table(&report.expression, &report.table));
+    doc.push_str(&format!("<h2>Classification</h2>\n<p>{}</p>\n", report.classification.classification));
+    doc.push_str(&format!("<h2>Reduction</h2>\n<p><code>{}</code>", escape_html(&report.reduction.reduced.to_string())));
+    if !report.reduction.minimal {
+        doc.push_str(" (not proven minimal: search budget exhausted)");
+    }
+    doc.push_str("</p>\n<h2>Properties</h2>\n<table class=\"ttt-properties\">\n<tbody>\n");
+    for (label, value) in property_entries(&report.properties) {
+        doc.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, escape_html(&value)));
+    }
+    doc.push_str("</tbody>\n</table>\n");
+
+    if let Some(map) = &report.karnaugh_map {
+        let cells = karnaugh_cells(map);
+        doc.push_str("<h2>Karnaugh map</h2>\n<table class=\"ttt-karnaugh-map\">\n<thead><tr>");
+        for label in &cells[0] {
+            doc.push_str(&format!("<th>{}</th>", escape_html(label)));
+        }
+        doc.push_str("</tr></thead>\n<tbody>\n");
+        for row in &cells[1..] {
+            doc.push_str(&format!("<tr><th>{}</th>", escape_html(&row[0])));
+            for cell in &row[1..] {
+                doc.push_str(&format!("<td>{}</td>", cell));
+            }
+            doc.push_str("</tr>\n");
+        }
+        doc.push_str("</tbody>\n</table>\n");
+    }
+    doc.push_str("</body>\n</html>\n");
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::provenance::Provenance;
+    use crate::eval::Evaluator;
+    use crate::source::Parser;
+
+    fn report(input: &str) -> Report {
+        Evaluator::report(&Parser::new(input).parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_markdown_report_has_every_section() {
+        let doc = render_markdown(&report("a and b"));
+        for heading in ["# Report", "## Truth table", "## Classification", "## Reduction", "## Properties", "## Karnaugh map"] {
+            assert!(doc.contains(heading), "missing {heading}:\n{doc}");
+        }
+        assert!(doc.contains("| a | b | result |\n|---|---|---|\n"));
+        assert!(doc.contains("| a\\b | 0 | 1 |\n"));
+        assert!(doc.contains("| 1 | 0 | 1 |\n"));
+        assert_eq!(markdown_code("x`y"), "``x`y``");
+    }
+
+    #[test]
+    fn test_html_report_escapes_and_closes() {
+        let doc = render_html(&report("a -> b"));
+        assert!(doc.starts_with("<!DOCTYPE html>"));
+        assert!(doc.ends_with("</body>\n</html>\n"));
+        assert!(doc.contains("<h2>Karnaugh map</h2>"));
+        assert!(!doc.contains("<td><"));
+    }
+
+    #[test]
+    fn test_provenance_header_is_a_comment() {
+        let report = report("a xor b");
+        let provenance = Provenance::new("a xor b", &report.expression, vec!["--format".to_string(), "html".to_string()]);
+        let stamped = |format: ReportFormat| {
+            String::from_utf8(provenance.stamp(render_report(&report, format).into_bytes(), format.comments())).unwrap()
+        };
+        let doc = stamped(ReportFormat::Html);
+        assert!(doc.starts_with("<!DOCTYPE html>\n<!--\ngenerated by "), "{doc}");
+        assert!(doc.contains("expression: a xor b\noptions: --format html\n"));
+        let doc = stamped(ReportFormat::Markdown);
+        assert!(doc.starts_with("<!--\n") && doc.contains("-->\n# Report"), "{doc}");
+    }
+}
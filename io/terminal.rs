@@ -0,0 +1,114 @@
+use std::process::{Command, Stdio};
+use miette::{IntoDiagnostic, Result, WrapErr};
+
+/// Switch to the alternate screen, clear it and hide the cursor
+pub const ENTER_SCREEN: &str = "\x1b[?1049h\x1b[?25l";
+/// Show the cursor and return to the screen as it was
+pub const LEAVE_SCREEN: &str = "\x1b[?25h\x1b[?1049l";
+/// Clear the screen and move to its top left corner
+pub const CLEAR: &str = "\x1b[2J\x1b[H";
+pub const BOLD: &str = "\x1b[1m";
+pub const REVERSE: &str = "\x1b[7m";
+/// Bold yellow, for the one thing the eye should land on
+pub const HIGHLIGHT: &str = "\x1b[1;33m";
+pub const RESET: &str = "\x1b[0m";
+
+/// The terminal on stdin reading key by key, without echo, until dropped.
+///
+/// Uses `stty`, so it works wherever a POSIX shell does without a terminal
+/// library. Signals are off as well: Ctrl-C arrives as a key, so the settings
+/// are always restored.
+pub struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    pub fn enable() -> Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "1"])?;
+        Ok(Self { saved: saved.trim().to_string() })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .into_diagnostic()
+        .wrap_err("Failed to run stty")?;
+    if !output.status.success() {
+        return Err(miette::miette!("stty failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    String::from_utf8(output.stdout).into_diagnostic()
+}
+
+/// A key pressed in raw mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Space or Enter
+    Select,
+    /// `q`, Escape, Ctrl-C or Ctrl-D
+    Quit,
+    Other,
+}
+
+impl Key {
+    /// The keys in `bytes` as one read from a raw terminal returns them.
+    ///
+    /// Arrows arrive as `ESC [ A` through `ESC [ D`, or with `O` for `[` in
+    /// application mode; `h`, `j`, `k` and `l` stand in for them as in vi.
+    pub fn decode(bytes: &[u8]) -> Vec<Key> {
+        let mut keys = Vec::new();
+        let mut rest = bytes;
+        while let Some(&byte) = rest.first() {
+            let (key, length) = match (byte, rest.get(1), rest.get(2)) {
+                (0x1b, Some(b'[' | b'O'), Some(arrow)) => {
+                    let key = match arrow {
+                        b'A' => Key::Up,
+                        b'B' => Key::Down,
+                        b'C' => Key::Right,
+                        b'D' => Key::Left,
+                        _ => Key::Other,
+                    };
+                    (key, 3)
+                }
+                (0x1b, _, _) => (Key::Quit, 1),
+                (b'k', _, _) => (Key::Up, 1),
+                (b'j', _, _) => (Key::Down, 1),
+                (b'l', _, _) => (Key::Right, 1),
+                (b'h', _, _) => (Key::Left, 1),
+                (b' ' | b'\r' | b'\n', _, _) => (Key::Select, 1),
+                (b'q' | 0x03 | 0x04, _, _) => (Key::Quit, 1),
+                _ => (Key::Other, 1),
+            };
+            keys.push(key);
+            rest = &rest[length..];
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_arrows_and_letters() {
+        assert_eq!(Key::decode(b"\x1b[A\x1b[B\x1bOC\x1b[D"), [Key::Up, Key::Down, Key::Right, Key::Left]);
+        assert_eq!(Key::decode(b"kjlh \r"), [Key::Up, Key::Down, Key::Right, Key::Left, Key::Select, Key::Select]);
+        assert_eq!(Key::decode(b"q\x03x\x1b"), [Key::Quit, Key::Quit, Key::Other, Key::Quit]);
+        // Keys other than arrows with the same prefix are skipped whole
+        assert_eq!(Key::decode(b"\x1b[Hq"), [Key::Other, Key::Quit]);
+    }
+}
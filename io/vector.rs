@@ -0,0 +1,125 @@
+use crate::source::Expr;
+use crate::source::operator::resynthesize;
+use crate::config::MAX_VARIABLES;
+use std::path::Path;
+use miette::{IntoDiagnostic, Result, WrapErr};
+
+/// The result column of a truth table, as read from a lookup table or S-box dump
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruthVector {
+    /// Generated variable names, `a` for the lowest bit of the row index
+    pub variables: Vec<String>,
+    /// One result per row, in truth-table order (first variable varying fastest)
+    pub results: Vec<bool>,
+}
+
+impl TruthVector {
+    /// Read a vector over `vars` variables from a file.
+    ///
+    /// Files ending in `.hex` or `.txt` hold hex text, anything else raw bytes.
+    /// Row `i` is bit `i % 8` of byte `i / 8`, so the majority of three is `e8`.
+    pub fn read(path: &Path, vars: usize) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to read '{}'", path.display()))?;
+        let hex = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("hex") || ext.eq_ignore_ascii_case("txt"));
+        let bytes = if hex { decode_hex(&bytes)? } else { bytes };
+        Self::from_bytes(&bytes, vars).wrap_err_with(|| format!("'{}' is not a truth vector over {} variables", path.display(), vars))
+    }
+
+    /// Unpack `2^vars` results from bytes, lowest bit first
+    pub fn from_bytes(bytes: &[u8], vars: usize) -> Result<Self> {
+        if vars > MAX_VARIABLES.min(26) {
+            return Err(miette::miette!("A truth vector can have at most {} variables, got {}", MAX_VARIABLES.min(26), vars));
+        }
+        let rows = 1usize << vars;
+        let expected = rows.div_ceil(8);
+        if bytes.len() != expected {
+            return Err(miette::miette!("Expected {} byte(s) for {} rows, found {}", expected, rows, bytes.len()));
+        }
+        if rows < 8 && bytes[0] >> rows != 0 {
+            return Err(miette::miette!("Bits beyond the {} rows of a {}-variable vector are set", rows, vars));
+        }
+
+        let variables = (0..vars).map(|i| char::from(b'a' + i as u8).to_string()).collect();
+        let results = (0..rows).map(|row| (bytes[row / 8] >> (row % 8)) & 1 == 1).collect();
+        Ok(Self { variables, results })
+    }
+
+    /// An expression with this truth table, built by Shannon expansion on the last
+    /// variable first so that evaluating a row walks a single path
+    pub fn to_expr(&self) -> Expr {
+        let variables: Vec<Expr> = self.variables.iter().cloned().map(Expr::Identifier).collect();
+        resynthesize(&variables, &self.results)
+    }
+}
+
+/// Read an S-box or other lookup table: hex entries (an optional `0x` prefix)
+/// separated by whitespace or commas, in input order
+pub fn parse_lookup_table(text: &str) -> Result<Vec<usize>> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let digits = entry.strip_prefix("0x").unwrap_or(entry);
+            usize::from_str_radix(digits, 16).map_err(|_| miette::miette!("Invalid lookup table entry '{}'; expected a hex number", entry))
+        })
+        .collect()
+}
+
+/// Hex digits to bytes, ignoring whitespace and `0x` prefixes
+fn decode_hex(text: &[u8]) -> Result<Vec<u8>> {
+    let text = std::str::from_utf8(text).into_diagnostic().wrap_err("Hex truth vector is not text")?;
+    let digits: Vec<u8> = text
+        .split_whitespace()
+        .flat_map(|word| word.strip_prefix("0x").unwrap_or(word).chars())
+        .map(|c| c.to_digit(16).map(|d| d as u8).ok_or_else(|| miette::miette!("Invalid hex digit '{}'", c)))
+        .collect::<Result<_>>()?;
+    // A lone digit is the low nibble, for vectors of up to four rows
+    if digits.len() == 1 {
+        return Ok(digits);
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(miette::miette!("Hex truth vector has an odd number of digits"));
+    }
+    Ok(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Evaluator;
+
+    fn results(expr: &Expr) -> Vec<bool> {
+        Evaluator::generate_truth_table(expr).unwrap().rows.iter().map(|row| row.result).collect()
+    }
+
+    #[test]
+    fn test_majority_from_hex() {
+        let vector = TruthVector::from_bytes(&decode_hex(b"0xe8\n").unwrap(), 3).unwrap();
+        assert_eq!(vector.results, [false, false, false, true, false, true, true, true]);
+        assert_eq!(results(&vector.to_expr()), vector.results);
+    }
+
+    #[test]
+    fn test_expression_skips_irrelevant_variables() {
+        // a xor b, with c ignored
+        let vector = TruthVector::from_bytes(&[0b0110_0110], 3).unwrap();
+        assert_eq!(vector.to_expr().to_string(), "(b ? ¬a : a)");
+        // Multi-byte vectors read the lowest bit of the first byte first
+        let vector = TruthVector::from_bytes(&[0x00, 0x80], 4).unwrap();
+        assert_eq!(vector.to_expr().to_string(), "(d ∧ (c ∧ (b ∧ a)))");
+    }
+
+    #[test]
+    fn test_lookup_table() {
+        assert_eq!(parse_lookup_table("c 5, 0x6\nB").unwrap(), [0xc, 0x5, 0x6, 0xb]);
+        assert!(parse_lookup_table("1 g").is_err());
+    }
+
+    #[test]
+    fn test_length_is_checked() {
+        assert!(TruthVector::from_bytes(&[0xe8, 0x00], 3).is_err());
+        assert!(TruthVector::from_bytes(&[0x10], 2).is_err());
+        assert!(decode_hex(b"e8f").is_err());
+    }
+}
@@ -1,4 +1,6 @@
+pub mod macros;
 pub mod source;
 pub mod eval;
 pub mod io;
 pub mod config;
+pub mod oracle;
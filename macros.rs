@@ -0,0 +1,203 @@
+//! Macros for building expressions in Rust code
+
+/// Build an [`Expr`](crate::source::Expr) from ttt syntax at compile time,
+/// without the parser or nested `Box::new` calls:
+///
+/// ```
+/// use ttt::expr;
+/// use ttt::source::Expr;
+///
+/// let expr = expr!(a and (not b or c));
+/// assert_eq!(expr.to_string(), "(a ∧ (¬b ∨ c))");
+///
+/// // Rust values are spliced in from braces
+/// let guard = expr!(x xor y);
+/// assert_eq!(expr!({guard.clone()} -> "out ok"), Expr::Implication(Box::new(guard), Box::new(expr!("out ok"))));
+/// ```
+///
+/// Expressions group as the standard precedence profile and right-associative
+/// implication do. The macro accepts:
+///
+/// - the word operators, `!`, `&&`, `||`, `^`, `->` and `<->`
+/// - `true`, `false`, `1` and `0`
+/// - identifiers, and string literals for names that need quoting
+/// - `c ? t : e` and `ite(c, t, e)`
+/// - `atleast(k; ...)`, `atmost(k; ...)` and `exactly(k; ...)`
+/// - `forall x. body` and `exists x. body`
+/// - `{ value }` for any Rust expression of type `Expr`
+///
+/// Unicode operators and custom operators are not Rust tokens and must go
+/// through the [`Parser`](crate::source::Parser). Very long expressions may
+/// need a higher `#![recursion_limit]`.
+#[macro_export]
+macro_rules! expr {
+    // Operator precedence parsing over a stack of operands `(expr)` and
+    // operators `Variant level`, top first. Levels run from 1 (`↔`) to 5 (`∧`).
+
+    // An operand, after any number of `not`s
+    (@operand [$($stack:tt)*] [$($nots:tt)*] not $($rest:tt)+) => { $crate::expr!(@operand [$($stack)*] [! $($nots)*] $($rest)+) };
+    (@operand [$($stack:tt)*] [$($nots:tt)*] ! $($rest:tt)+) => { $crate::expr!(@operand [$($stack)*] [! $($nots)*] $($rest)+) };
+    // A quantifier's body extends to the end
+    (@operand [$($stack:tt)*] $nots:tt forall $($rest:tt)+) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::expr!(@quantified ForAll $($rest)+)))) $($stack)*])
+    };
+    (@operand [$($stack:tt)*] $nots:tt exists $($rest:tt)+) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::expr!(@quantified Exists $($rest)+)))) $($stack)*])
+    };
+    (@operand [$($stack:tt)*] $nots:tt ite ($($args:tt)+) $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::expr!(@split [ite] [] [] $($args)+)))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt atleast ($k:literal ; $($args:tt)+) $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::expr!(@split [cardinality AtLeast $k] [] [] $($args)+)))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt atmost ($k:literal ; $($args:tt)+) $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::expr!(@split [cardinality AtMost $k] [] [] $($args)+)))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt exactly ($k:literal ; $($args:tt)+) $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::expr!(@split [cardinality Exactly $k] [] [] $($args)+)))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt true $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::source::Expr::True))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt false $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::source::Expr::False))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt 1 $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::source::Expr::True))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt 0 $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::source::Expr::False))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt { $value:expr } $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($value))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt ($($inner:tt)+) $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::expr!($($inner)+)))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt $name:ident $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::source::Expr::Identifier(::std::string::String::from(stringify!($name)))))) $($stack)*] $($rest)*)
+    };
+    (@operand [$($stack:tt)*] $nots:tt $name:literal $($rest:tt)*) => {
+        $crate::expr!(@operator [($crate::expr!(@not $nots ($crate::source::Expr::Identifier(::std::string::String::from($name))))) $($stack)*] $($rest)*)
+    };
+    (@not [] ($($operand:tt)+)) => { $($operand)+ };
+    (@not [! $($nots:tt)*] ($($operand:tt)+)) => {
+        $crate::source::Expr::Not(::std::boxed::Box::new($crate::expr!(@not [$($nots)*] ($($operand)+))))
+    };
+
+    // After an operand: the end, `? :`, or a binary operator
+    (@operator [$($stack:tt)+]) => { $crate::expr!(@reduce [$($stack)+]) };
+    (@operator [$($stack:tt)+] ? $($rest:tt)+) => { $crate::expr!(@branches [$($stack)+] [] [] $($rest)+) };
+    (@operator [$($stack:tt)+] iff $($rest:tt)+) => { $crate::expr!(@shift Iff 1 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] xnor $($rest:tt)+) => { $crate::expr!(@shift Iff 1 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] <-> $($rest:tt)+) => { $crate::expr!(@shift Iff 1 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] -> $($rest:tt)+) => { $crate::expr!(@shift Implication 2 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] or $($rest:tt)+) => { $crate::expr!(@shift Or 3 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] || $($rest:tt)+) => { $crate::expr!(@shift Or 3 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] nor $($rest:tt)+) => { $crate::expr!(@shift Nor 3 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] xor $($rest:tt)+) => { $crate::expr!(@shift Xor 4 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] ^ $($rest:tt)+) => { $crate::expr!(@shift Xor 4 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] and $($rest:tt)+) => { $crate::expr!(@shift And 5 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] && $($rest:tt)+) => { $crate::expr!(@shift And 5 [$($stack)+] $($rest)+) };
+    (@operator [$($stack:tt)+] nand $($rest:tt)+) => { $crate::expr!(@shift Nand 5 [$($stack)+] $($rest)+) };
+
+    // Apply the operators on top that bind at least as tightly as the incoming
+    // one (strictly tighter for right-associative implication), then push it
+    (@shift $op:ident 1 [$r:tt $top:ident $level:tt $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 1 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 2 [$r:tt $top:ident 3 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 2 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 2 [$r:tt $top:ident 4 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 2 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 2 [$r:tt $top:ident 5 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 2 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 3 [$r:tt $top:ident 3 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 3 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 3 [$r:tt $top:ident 4 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 3 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 3 [$r:tt $top:ident 5 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 3 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 4 [$r:tt $top:ident 4 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 4 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 4 [$r:tt $top:ident 5 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 4 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident 5 [$r:tt $top:ident 5 $l:tt $($stack:tt)*] $($rest:tt)+) => { $crate::expr!(@shift $op 5 [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*] $($rest)+) };
+    (@shift $op:ident $level:tt [$($stack:tt)+] $($rest:tt)+) => { $crate::expr!(@operand [$op $level $($stack)+] [] $($rest)+) };
+
+    (@reduce [$operand:tt]) => { $crate::expr!(@unwrap $operand) };
+    (@reduce [$r:tt $top:ident $level:tt $l:tt $($stack:tt)*]) => { $crate::expr!(@reduce [($crate::source::Expr::$top(::std::boxed::Box::new $l, ::std::boxed::Box::new $r)) $($stack)*]) };
+    (@unwrap ($($operand:tt)+)) => { $($operand)+ };
+
+    // `c ? t : e`, where `t` runs to the `:` that matches the `?`
+    (@branches [$($stack:tt)+] [$($open:tt)*] [$($then:tt)*] ? $($rest:tt)+) => { $crate::expr!(@branches [$($stack)+] [? $($open)*] [$($then)* ?] $($rest)+) };
+    (@branches [$($stack:tt)+] [? $($open:tt)*] [$($then:tt)*] : $($rest:tt)+) => { $crate::expr!(@branches [$($stack)+] [$($open)*] [$($then)* :] $($rest)+) };
+    (@branches [$($stack:tt)+] [] [$($then:tt)+] : $($otherwise:tt)+) => {
+        $crate::source::Expr::Ite(
+            ::std::boxed::Box::new($crate::expr!(@reduce [$($stack)+])),
+            ::std::boxed::Box::new($crate::expr!($($then)+)),
+            ::std::boxed::Box::new($crate::expr!($($otherwise)+)),
+        )
+    };
+    (@branches [$($stack:tt)+] $open:tt [$($then:tt)*] $next:tt $($rest:tt)*) => { $crate::expr!(@branches [$($stack)+] $open [$($then)* $next] $($rest)*) };
+
+    // `x, y. body` after a quantifier
+    (@quantified $quantifier:ident $var:ident , $($rest:tt)+) => {
+        $crate::source::Expr::Quantified(
+            $crate::source::Quantifier::$quantifier,
+            ::std::string::String::from(stringify!($var)),
+            ::std::boxed::Box::new($crate::expr!(@quantified $quantifier $($rest)+)),
+        )
+    };
+    (@quantified $quantifier:ident $var:ident . $($body:tt)+) => {
+        $crate::source::Expr::Quantified(
+            $crate::source::Quantifier::$quantifier,
+            ::std::string::String::from(stringify!($var)),
+            ::std::boxed::Box::new($crate::expr!($($body)+)),
+        )
+    };
+
+    // Split call arguments at commas, then hand them to `@$callback` as groups
+    (@split [$($callback:tt)+] [$($done:tt)*] [$($arg:tt)+] , $($rest:tt)+) => { $crate::expr!(@split [$($callback)+] [$($done)* ($($arg)+)] [] $($rest)+) };
+    (@split [$($callback:tt)+] [$($done:tt)*] [$($arg:tt)*] $next:tt $($rest:tt)*) => { $crate::expr!(@split [$($callback)+] [$($done)*] [$($arg)* $next] $($rest)*) };
+    (@split [$($callback:tt)+] [$($done:tt)*] [$($arg:tt)+]) => { $crate::expr!(@ $($callback)+ $($done)* ($($arg)+)) };
+    (@ite ($($cond:tt)+) ($($then:tt)+) ($($otherwise:tt)+)) => {
+        $crate::source::Expr::Ite(
+            ::std::boxed::Box::new($crate::expr!($($cond)+)),
+            ::std::boxed::Box::new($crate::expr!($($then)+)),
+            ::std::boxed::Box::new($crate::expr!($($otherwise)+)),
+        )
+    };
+    (@cardinality $kind:ident $k:literal $(($($operand:tt)+))+) => {
+        $crate::source::Expr::Cardinality(
+            $crate::source::Cardinality::$kind,
+            $k,
+            ::std::vec![$($crate::expr!($($operand)+)),+],
+        )
+    };
+
+    ($($tokens:tt)+) => { $crate::expr!(@operand [] [] $($tokens)+) };
+}
+
+pub use crate::expr;
+
+#[cfg(test)]
+mod tests {
+    use crate::source::{Expr, Parser};
+
+    fn parsed(input: &str) -> Expr {
+        Parser::new(input).parse().unwrap()
+    }
+
+    #[test]
+    fn test_macro_matches_parser() {
+        assert_eq!(expr!(a and (not b or c)), parsed("a and (not b or c)"));
+        assert_eq!(expr!(a or b and c xor d), parsed("a or b and c xor d"));
+        assert_eq!(expr!(a && !b || c ^ d), parsed("a && !b || c ^ d"));
+        assert_eq!(expr!(a -> b -> c <-> d iff e), parsed("a -> b -> c <-> d iff e"));
+        assert_eq!(expr!(a nand b nor c or d), parsed("a nand b nor c or d"));
+        assert_eq!(expr!(s ? a : b ? 1 : false), parsed("s ? a : b ? 1 : false"));
+        assert_eq!(expr!(s ? t ? a : b : c), parsed("s ? t ? a : b : c"));
+        assert_eq!(expr!(ite(s, a or b, not c)), parsed("ite(s, a or b, not c)"));
+        assert_eq!(expr!(atleast(2; a, b and c, d)), parsed("atleast(2; a, b and c, d)"));
+        assert_eq!(expr!(not a and forall x, y. x or y ? a : b), parsed("not a and forall x, y. x or y ? a : b"));
+        assert_eq!(expr!("input valid" and exists x. x), parsed("`input valid` and exists x. x"));
+    }
+
+    #[test]
+    fn test_splices_values() {
+        let inner = expr!(a or b);
+        assert_eq!(expr!(not {inner.clone()} and c), parsed("not (a or b) and c"));
+        assert_eq!(super::expr!({ Expr::True }), Expr::True);
+    }
+}
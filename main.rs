@@ -1,9 +1,29 @@
-use ttt::source::{Parser, Expr};
-use ttt::eval::Evaluator;
-use ttt::io::output::{OutputFormat, format_truth_table, format_equivalence_result, format_reduction_result};
-use ttt::io::input::InputHandler;
-use miette::{Result, NamedSource};
-use clap::{Parser as ClapParser, Subcommand};
+use ttt::source::{Parser, Syntax, Aliases, ParseError, ParseErrors, ImplicitAndHint, Expr, Dialect, Notation, Precedence, Associativity, Snippet, Library, Basis, Rewriter, PrettyPrinter, SymbolStyle, parse_smtlib, parse_sexpr, parse_fault_tree, parse_spreadsheet, parse_sql, parse_feature_flags, parse_structured_text};
+use ttt::source::dialect::parse_c;
+use ttt::eval::{Evaluator, EvalOptions, EvaluationError, Classification, CorpusEntry, RegressionStatus, SubexpressionOrder, SnippetRewrite, ReductionOptions, Warning, WarningKind, Ternary, RowOrder, RowSort};
+use ttt::io::output::{OutputFormat, format_truth_table, format_equivalence_result, format_reduction_result, format_table_diff, format_subexpressions, format_snippet_rewrite, format_lint_report, format_prime_implicants, format_chain_proof, format_expansion, format_influence, format_threshold, format_explanations, format_abduction, format_propagation, format_three_valued_table, format_vectors, format_cut_sets, format_group_summary, format_column, ColumnFormat, format_spectrum, format_bias, format_sbox, format_degree, format_capabilities, format_synthesis, format_rewrite, format_table_view, format_projection, format_satisfiability, format_classification, format_regression, format_cnf, format_dnf, format_nnf, format_anf, format_conversion, format_report, attach_warnings};
+use ttt::eval::regress::corpus_key;
+use ttt::io::files::expand_glob;
+use ttt::io::lint::{lint_source, LintReport};
+use ttt::io::input::{InputHandler, InputFormat};
+use ttt::io::csv::{CsvDocument, parse_bool};
+use ttt::io::aiger;
+use ttt::io::export::{export, ExportFormat};
+use ttt::io::vector::{TruthVector, parse_lookup_table};
+use ttt::io::cache::{Cache, CacheKey};
+use ttt::io::audit::Audit;
+use ttt::io::repl::{Session, Reply, run_explorer};
+use ttt::io::notebook::{Kernel, Request};
+use ttt::io::report::{ReportFormat, render_report};
+use ttt::io::provenance::{CommentSyntax, Provenance};
+use ttt::config::Capabilities;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Instant, SystemTime};
+use miette::{Result, NamedSource, IntoDiagnostic, WrapErr};
+use clap::{Parser as ClapParser, CommandFactory, FromArgMatches, Subcommand};
 
 
 #[derive(ClapParser)]
@@ -12,11 +32,73 @@ use clap::{Parser as ClapParser, Subcommand};
 #[command(version = ttt::config::VERSION)]
 struct Cli {
     /// Output format
-    #[arg(short = 'o', long = "output", value_enum, default_value_t = OutputFormat::Table)]
+    #[arg(short = 'o', long = "output", value_enum, global = true, default_value_t = OutputFormat::Table)]
     output: OutputFormat,
     
+    /// Encoding of the expression read by single-expression commands; `bin` reads
+    /// `Expr::to_bytes` output from a file argument or stdin
+    #[arg(long = "input-format", value_enum, global = true, default_value_t = InputFormat::Text)]
+    input_format: InputFormat,
+    
+    /// Sort operands of commutative operators and flatten associative chains before display
+    #[arg(long = "normalize", global = true)]
+    normalize: bool,
+    
+    /// Input language of expressions; for `reduce`, the simplified snippet is emitted in the same language
+    #[arg(long = "dialect", value_enum, global = true, default_value_t = Dialect::Ttt)]
+    dialect: Dialect,
+    
+    /// Extra operator notation for ttt expressions, e.g. `algebra` for `a*b' + c`
+    #[arg(long = "notation", value_enum, global = true, default_value_t = Notation::Standard)]
+    notation: Notation,
+    /// How the binary operators rank against each other in ttt expressions
+    #[arg(long = "precedence", value_enum, global = true, default_value_t = Precedence::Standard)]
+    precedence: Precedence,
+    /// Whether `a -> b -> c` groups as `a -> (b -> c)` or `(a -> b) -> c`
+    #[arg(long = "implication-assoc", value_enum, global = true, default_value_t = Associativity::Right)]
+    implication_assoc: Associativity,
+    /// Whether operators go between their operands or before them, e.g. `prefix` for `-> and a b c`
+    #[arg(long = "syntax", value_enum, global = true, default_value_t = Syntax::Infix)]
+    syntax: Syntax,
+    
+    /// Read adjacent operands as `and` and words as single-letter variables, e.g. `AB + C'D`
+    #[arg(long = "implicit-and", global = true)]
+    implicit_and: bool,
+    
+    /// Read keywords in any case, e.g. `a AND NOT b`
+    #[arg(long = "case-insensitive", global = true)]
+    case_insensitive: bool,
+    
+    /// Deepest expression read and evaluated, in levels of parentheses, `not`s, operators and chained operands
+    #[arg(long = "max-nesting", value_name = "N", global = true, default_value_t = ttt::config::MAX_NESTING_DEPTH)]
+    max_nesting: usize,
+    
+    /// With `--dialect structured-text`, read the value assigned to NAME instead of the last assignment
+    #[arg(long = "assignment", value_name = "NAME", global = true)]
+    assignment: Option<String>,
+    
+    /// File of `def name(params) := expr` definitions that expressions may use
+    #[arg(long = "library", global = true)]
+    library: Option<PathBuf>,
+    
+    /// Read WORD as an operator in ttt expressions, e.g. `--alias et=and --alias non=not`
+    #[arg(long = "alias", value_name = "WORD=OPERATOR", global = true, value_parser = parse_alias)]
+    aliases: Vec<(String, String)>,
+    
+    /// Recompute `table` and `reduce` output instead of reusing cached results
+    #[arg(long = "no-cache", global = true)]
+    no_cache: bool,
+    
+    /// Append a JSON line recording the command, expression fingerprints, timing and result to this file
+    #[arg(long = "log-file", value_name = "FILE", global = true)]
+    log_file: Option<PathBuf>,
+    
     #[command(subcommand)]
     command: Commands,
+    
+    /// The options given on the command line, for provenance headers
+    #[arg(skip)]
+    invocation: Vec<String>,
 }
 
 #[derive(Subcommand)]
@@ -26,58 +108,1299 @@ enum Commands {
     Table {
         /// Boolean expression (if not provided, reads from stdin)
         expression: Vec<String>,
+        /// Only generate rows START..END (END may be omitted to run to the last row)
+        #[arg(long = "range", value_name = "START..END", value_parser = parse_row_range)]
+        range: Option<(usize, Option<usize>)>,
+        /// Also give each variable an unknown value, evaluating in Kleene logic
+        #[arg(long = "three-valued", conflicts_with = "range")]
+        three_valued: bool,
+        /// Collapse the table over the other variables, counting true completions per combination of these
+        #[arg(long = "group-by", value_name = "VARS", value_delimiter = ',', conflicts_with_all = ["range", "three_valued"])]
+        group_by: Vec<String>,
+        /// Print only this column (`result` or a variable), ignoring `--output`
+        #[arg(long = "column", value_name = "NAME", conflicts_with_all = ["three_valued", "group_by"])]
+        column: Option<String>,
+        /// Encoding of the `--column` values
+        #[arg(long = "format", value_enum, default_value_t = ColumnFormat::Bits, requires = "column")]
+        format: ColumnFormat,
+        /// Row order of the `--column` values
+        #[arg(long = "row-order", value_enum, default_value_t = RowOrder::Lsb, requires = "column")]
+        row_order: RowOrder,
+        /// Evaluate N uniformly random assignments instead of every row, allowing any number of variables
+        #[arg(long = "sample", value_name = "N", conflicts_with_all = ["range", "three_valued", "group_by"])]
+        sample: Option<usize>,
+        /// Seed for `--sample`; the same seed draws the same rows
+        #[arg(long = "seed", default_value_t = 0, requires = "sample")]
+        seed: u64,
+        /// List true rows first, or count with the most influential variables as the high bits
+        #[arg(long = "sort-by", value_enum, conflicts_with_all = ["three_valued", "group_by", "column"])]
+        sort_by: Option<RowSort>,
+        /// Leave these variables out of the output, still evaluating over them; rows that then read the same are listed once
+        #[arg(long = "hide-vars", value_name = "VARS", value_delimiter = ',', conflicts_with_all = ["three_valued", "group_by", "column"])]
+        hide_vars: Vec<String>,
+        /// Show only these columns (variables and `result`), in this order; rows that then read the same are listed once
+        #[arg(long = "columns", value_name = "COLUMNS", value_delimiter = ',', conflicts_with_all = ["three_valued", "group_by", "column", "hide_vars"])]
+        columns: Vec<String>,
+        /// With `--hide-vars` or `--columns`, collapse rows that agree on the shown variables, counting them and marking the result true, false or mixed
+        #[arg(long = "project", conflicts_with_all = ["three_valued", "group_by", "column"])]
+        project: bool,
     },
     /// Check expression equivalency
     #[command(name = "eq")]
     Equivalence {
         /// Two boolean expressions to compare (if not provided, reads from stdin)
         expressions: Vec<String>,
+        /// Don't-care condition; assignments satisfying it are not compared
+        #[arg(long = "dont-care")]
+        dont_care: Option<String>,
     },
     /// Reduce/simplify an expression
     #[command(name = "reduce")]
     Reduce {
         /// Boolean expression to reduce (if not provided, reads from stdin)
         expression: Vec<String>,
+        /// Read the function from a truth vector file (raw bytes, or hex text for `.hex`/`.txt`) instead of an expression
+        #[arg(long = "from-vector", value_name = "FILE", requires = "vars", conflicts_with = "expression")]
+        from_vector: Option<PathBuf>,
+        /// Number of variables in the `--from-vector` file, named a, b, c, ...
+        #[arg(long = "vars", value_name = "N", requires = "from_vector")]
+        vars: Option<usize>,
+        /// Report which minterms and source terms each reduced term covers
+        #[arg(long = "trace")]
+        trace: bool,
+        /// Fail instead of returning a possibly non-minimal result when the search budget runs out
+        #[arg(long = "strict")]
+        strict: bool,
+        /// Rewrite the reduced form to use only these operators, e.g. `and,or,not`, `nand` or `xor,and`
+        #[arg(long = "basis", value_name = "OPS")]
+        basis: Option<Basis>,
+    },
+    /// Simplify with rewrite rules (De Morgan, absorption, double negation, ...), keeping the expression's structure
+    #[command(name = "simplify")]
+    Simplify {
+        /// Boolean expression to simplify (if not provided, reads from stdin)
+        expression: Vec<String>,
+    },
+    /// Print an expression with only the parentheses `--precedence` needs, ignoring `--output`
+    #[command(name = "pretty")]
+    Pretty {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Operator symbols to write
+        #[arg(long = "style", value_enum, default_value_t = SymbolStyle::Unicode)]
+        style: SymbolStyle,
+    },
+    /// Explore expressions interactively: toggle variables and watch every
+    /// subexpression re-evaluate, with the operands that force the result marked
+    #[command(name = "repl")]
+    Repl {
+        /// Replay a session saved with `:save` instead of reading stdin, echoing each line
+        #[arg(long = "script")]
+        script: Option<PathBuf>,
+    },
+    /// Run notebook cells sent as NDJSON requests on stdin, answering each with
+    /// a line of plain-text, HTML and JSON truth tables for a Jupyter kernel to show
+    #[command(name = "notebook-server")]
+    NotebookServer,
+    /// Evaluate an expression under one assignment, or each assignment read from a file
+    #[command(name = "eval")]
+    Eval {
+        /// Boolean expression, optionally followed by `name=value` assignments (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// CSV file whose columns are variable values, one assignment per row
+        #[arg(long = "vectors", conflicts_with = "set")]
+        vectors: Option<PathBuf>,
+        /// Evaluate a single assignment given as `name=value` (repeatable)
+        #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_assignment)]
+        set: Vec<(String, bool)>,
+        /// Show the literals of each assignment that are responsible for its result
+        #[arg(long = "why")]
+        why: bool,
+        /// Evaluate in Kleene logic: variables without a value, and `?`/`unknown` cells, are unknown
+        #[arg(long = "three-valued", conflicts_with = "why")]
+        three_valued: bool,
+    },
+    /// Compare two exported truth tables (CSV or JSON)
+    #[command(name = "tablediff")]
+    TableDiff {
+        /// Left truth table file
+        left: PathBuf,
+        /// Right truth table file
+        right: PathBuf,
+    },
+    /// Combine partial truth tables (CSV or JSON) into one complete table
+    #[command(name = "merge")]
+    Merge {
+        /// Partial truth table files, all over the same variables
+        #[arg(required = true)]
+        tables: Vec<PathBuf>,
+        /// Output the merged rows even if some assignments are missing
+        #[arg(long = "allow-partial")]
+        allow_partial: bool,
+    },
+    /// List every distinct subexpression with occurrence count and truth density
+    #[command(name = "subexprs")]
+    Subexpressions {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Order of the listing
+        #[arg(long = "sort", value_enum, default_value_t = SubexpressionOrder::Appearance)]
+        sort: SubexpressionOrder,
+    },
+    /// Check `ttt:` and `ttt-eq:` annotations in source file comments
+    #[command(name = "lint-files")]
+    LintFiles {
+        /// Files or glob patterns (`*`, `?`, `**`) to scan
+        #[arg(required = true)]
+        patterns: Vec<String>,
+        /// Only check that annotations parse; don't verify stated equivalences
+        #[arg(long = "parse-only")]
+        parse_only: bool,
+    },
+    /// Compare a JSON-lines corpus of expressions with a golden one; exits 1 if any changed meaning
+    #[command(name = "regress")]
+    Regress {
+        /// Corpus of known-good expressions, one `{"name": ..., "expression": ...}` per line
+        #[arg(long = "golden")]
+        golden: PathBuf,
+        /// Corpus to check against it
+        #[arg(long = "input")]
+        input: PathBuf,
+    },
+    /// Check a chain of equivalences `e1 <-> e2 <-> ...` link by link
+    #[command(name = "prove")]
+    Prove {
+        /// Equivalence chain (if not provided, reads from stdin)
+        chain: Vec<String>,
+    },
+    /// Grow an expression with equivalence-preserving rewrites (the inverse of reduce)
+    #[command(name = "expand")]
+    Expand {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Number of nodes in the result
+        #[arg(long = "target-size")]
+        target_size: usize,
+        /// Seed for choosing rewrites; the same seed gives the same result
+        #[arg(long = "seed", default_value_t = 0)]
+        seed: u64,
+    },
+    /// Convert an expression to conjunctive normal form
+    #[command(name = "cnf")]
+    Cnf {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Name each gate with a new variable instead of distributing, which keeps
+        /// the result linear in size; it is satisfiable exactly when the expression is
+        #[arg(long = "tseitin")]
+        tseitin: bool,
+    },
+    /// Convert an expression to disjunctive normal form
+    #[command(name = "dnf")]
+    Dnf {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// List every minterm, each naming every variable, instead of a minimal sum of products
+        #[arg(long = "canonical")]
+        canonical: bool,
+    },
+    /// Convert an expression to negation normal form
+    #[command(name = "nnf")]
+    Nnf {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+    },
+    /// Write an expression as an xor of conjunctions (algebraic normal form)
+    #[command(name = "anf")]
+    Anf {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+    },
+    /// Rewrite an expression with only the operators of a basis, such as NAND or NOR gates
+    #[command(name = "convert")]
+    Convert {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Operators the result may use, e.g. `nand`, `nor` or `and,or,not`
+        #[arg(long = "basis", value_name = "OPS")]
+        basis: Basis,
+    },
+    /// Show the truth table, classification, reduced form, properties and Karnaugh map of an expression at once
+    #[command(name = "report")]
+    Report {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Write the report as a Markdown or HTML document, ignoring `--output`
+        #[arg(long = "format", value_enum)]
+        format: Option<ReportFormat>,
+        /// Leave out the comment recording the expression, version and options that produced the document
+        #[arg(long = "no-provenance", requires = "format")]
+        no_provenance: bool,
+    },
+    /// Write an expression as an and-inverter graph in AIGER format, or as DIMACS CNF
+    #[command(name = "export")]
+    Export {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// File format
+        #[arg(long = "format", value_enum, default_value_t = ExportFormat::Aag)]
+        format: ExportFormat,
+        /// Leave out the comment recording the expression, version and options that produced the file
+        #[arg(long = "no-provenance")]
+        no_provenance: bool,
+    },
+    /// List the prime implicants of an expression without minimizing it
+    #[command(name = "implicants")]
+    Implicants {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Only report how many prime implicants there are
+        #[arg(long = "count-only")]
+        count_only: bool,
+    },
+    /// Rank variables by influence: how often flipping each one changes the result
+    #[command(name = "influence")]
+    Influence {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Also compute Shapley-Shubik values
+        #[arg(long = "shapley")]
+        shapley: bool,
+    },
+    /// Find minimal sets of assumptions that force an expression to a value
+    #[command(name = "abduce")]
+    Abduce {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Value the expression must be forced to
+        #[arg(long = "target", action = clap::ArgAction::Set, value_parser = parse_bool_value, default_value = "true")]
+        target: bool,
+        /// Known value `name=value` (repeatable)
+        #[arg(long = "fixed", value_name = "NAME=VALUE", value_parser = parse_assignment)]
+        fixed: Vec<(String, bool)>,
+    },
+    /// Classify an expression as a tautology, a contradiction or contingent; exits 0, 3 or 2 respectively
+    #[command(name = "check")]
+    Check {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+    },
+    /// Report whether an expression can be true, with the first assignment that makes it so
+    #[command(name = "sat")]
+    Sat {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+    },
+    /// Simplify an expression under known values, propagating forced literals until nothing changes
+    #[command(name = "propagate")]
+    Propagate {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Known value `name=value` (repeatable)
+        #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_assignment)]
+        set: Vec<(String, bool)>,
+    },
+    /// List the minimal satisfying cubes (minimal cut sets), smallest first
+    #[command(name = "cutsets")]
+    Cutsets {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Probability `name=p` that an event occurs (repeatable); overrides `prob=` in a fault tree
+        #[arg(long = "prob", value_name = "NAME=P", value_parser = parse_probability)]
+        prob: Vec<(String, f64)>,
+    },
+    /// Walsh–Hadamard spectrum, nonlinearity, correlation immunity and whether the function is bent or balanced
+    #[command(name = "spectrum")]
+    Spectrum {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Read the function from a truth vector file (raw bytes, or hex text for `.hex`/`.txt`) instead of an expression
+        #[arg(long = "from-vector", value_name = "FILE", requires = "vars", conflicts_with = "expression")]
+        from_vector: Option<PathBuf>,
+        /// Number of variables in the `--from-vector` file, named a, b, c, ...
+        #[arg(long = "vars", value_name = "N", requires = "from_vector")]
+        vars: Option<usize>,
+    },
+    /// Count of true rows and the bias from 1/2, overall and with each variable fixed
+    #[command(name = "bias")]
+    Bias {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Read the function from a truth vector file (raw bytes, or hex text for `.hex`/`.txt`) instead of an expression
+        #[arg(long = "from-vector", value_name = "FILE", requires = "vars", conflicts_with = "expression")]
+        from_vector: Option<PathBuf>,
+        /// Number of variables in the `--from-vector` file, named a, b, c, ...
+        #[arg(long = "vars", value_name = "N", requires = "from_vector")]
+        vars: Option<usize>,
+    },
+    /// Algebraic degree of the expression's ANF, and optionally its algebraic immunity
+    #[command(name = "degree")]
+    Degree {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Also find a lowest-degree annihilator of the expression or its complement
+        #[arg(long = "immunity")]
+        immunity: bool,
     },
+    /// Analyze an S-box given as a lookup table: each component function and the joint properties
+    #[command(name = "sbox")]
+    Sbox {
+        /// Hex table entries in input order, e.g. `c 5 6 b ...` (if not provided, reads from stdin)
+        values: Vec<String>,
+        /// Width of the outputs in bits (defaults to the input width)
+        #[arg(long = "output-bits", value_name = "M")]
+        output_bits: Option<usize>,
+    },
+    /// Check whether an expression is a weighted vote, reporting integer weights and a threshold
+    #[command(name = "threshold")]
+    Threshold {
+        /// Boolean expression (if not provided, reads from stdin)
+        expression: Vec<String>,
+    },
+    /// Find the smallest expression over a set of operators with the same truth table
+    #[command(name = "synth")]
+    Synth {
+        /// Boolean expression giving the truth table (if not provided, reads from stdin)
+        expression: Vec<String>,
+        /// Read the function from a truth vector file (raw bytes, or hex text for `.hex`/`.txt`) instead of an expression
+        #[arg(long = "from-vector", value_name = "FILE", requires = "vars", conflicts_with = "expression")]
+        from_vector: Option<PathBuf>,
+        /// Number of variables in the `--from-vector` file, named a, b, c, ...
+        #[arg(long = "vars", value_name = "N", requires = "from_vector")]
+        vars: Option<usize>,
+        /// Search every expression by size instead of using the reduced sum of products (up to 5 variables)
+        #[arg(long = "exact")]
+        exact: bool,
+        /// Largest expression to consider, in nodes
+        #[arg(long = "max-size", value_name = "N", default_value_t = 15)]
+        max_size: usize,
+        /// Operators the result may use, e.g. `and,or,not`, `nand` or `xor,and`
+        #[arg(long = "basis", value_name = "OPS", default_value = "and,or,not")]
+        basis: Basis,
+    },
+    /// Report version, features, limits and supported formats, e.g. `ttt info -o json`
+    #[command(name = "info")]
+    Info,
+    /// Manage the cache of `table` and `reduce` results
+    #[command(name = "cache")]
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Remove every cached result
+    #[command(name = "clear")]
+    Clear,
+}
+
+fn main() -> Result<ExitCode> {
+    // Parsing and every pass over an expression recurse once per level
+    std::thread::Builder::new()
+        .stack_size(ttt::config::CLI_STACK_SIZE)
+        .spawn(run_cli)
+        .into_diagnostic()?
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+fn run_cli() -> Result<ExitCode> {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    cli.invocation = explicit_options(&matches);
+    // Evaluation goes as deep as parsing, on the stack this thread has for it
+    let options = EvalOptions { max_depth: cli.max_nesting, ..EvalOptions::default() };
+    let Some(log_file) = cli.log_file.clone() else {
+        return Evaluator::with_options(&options, || run(cli, &Audit::default()));
+    };
+    let (started, timer) = (SystemTime::now(), Instant::now());
+    let audit = Audit::default();
+    let result = Evaluator::with_options(&options, || run(cli, &audit));
+    let record = audit.finish(matches.subcommand_name().unwrap_or_default(), started, timer.elapsed(), result.as_ref().err());
+    let logged = record.append_to(&log_file)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("Failed to write audit log '{}'", log_file.display()));
+    result.and_then(|code| logged.map(|()| code))
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Run the command, returning the status it exits with: 1 for errors and for
+/// the differences and failures that commands such as `tablediff` and `lint-files` find
+fn run(cli: Cli, audit: &Audit) -> Result<ExitCode> {
+    let library = match &cli.library {
+        Some(path) => Some(Library::parse(&InputHandler::read_file(path)?).map_err(|e| {
+            miette::Report::new(e).wrap_err(format!("Failed to load library '{}'", path.display()))
+        })?),
+        None => None,
+    };
+    let mut aliases = Aliases::new();
+    for (word, operator) in &cli.aliases {
+        aliases.add(word, operator).map_err(|e| miette::miette!("Invalid alias '{}': {}", word, e))?;
+    }
+    let settings = ParseSettings {
+        input_format: cli.input_format,
+        normalize: cli.normalize,
+        dialect: cli.dialect,
+        notation: cli.notation,
+        precedence: cli.precedence,
+        implication_assoc: cli.implication_assoc,
+        syntax: cli.syntax,
+        implicit_and: cli.implicit_and,
+        case_insensitive: cli.case_insensitive,
+        max_nesting: cli.max_nesting,
+        assignment: cli.assignment.as_deref(),
+        library,
+        aliases,
+        audit,
+    };
+    let cache = Cache::default_dir().filter(|_| !cli.no_cache).map(Cache::new);
     
     match cli.command {
-        Commands::Table { expression } => {
-            let expr_str = InputHandler::get_single_expression(expression)?;
-            let expr = parse_expression_with_error_handling(&expr_str)?;
-            let table = Evaluator::generate_truth_table(&expr)
-                .map_err(|e| miette::miette!("Truth table generation failed: {}", e))?;
-            print!("{}", format_truth_table(&table, &cli.output));
+        Commands::Table { expression, three_valued: true, .. } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let output = cached(cache.as_ref(), audit, &expr, "table", format!("{:?} three-valued", cli.output), || {
+                let table = Evaluator::generate_three_valued_table(&expr)
+                    .map_err(|e| miette::miette!("Truth table generation failed: {}", e))?;
+                Ok(format_three_valued_table(&table, &cli.output))
+            })?;
+            emit(output, &expression_warnings(&expr, None)?, &cli.output);
         }
-        Commands::Equivalence { expressions } => {
+        Commands::Table { expression, group_by, .. } if !group_by.is_empty() => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let group_by: Vec<String> = group_by.iter().map(|var| var.trim().to_string()).collect();
+            let output = cached(cache.as_ref(), audit, &expr, "table", format!("{:?} group-by {:?}", cli.output, group_by), || {
+                let summary = Evaluator::group_truth_table(&expr, &group_by)
+                    .map_err(|e| miette::miette!("Grouping failed: {}", e))?;
+                Ok(format_group_summary(&summary, &cli.output))
+            })?;
+            emit(output, &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Table { expression, range, column, format, row_order, sample, seed, sort_by, hide_vars, columns, project, .. } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let hide_vars: Vec<String> = hide_vars.iter().map(|var| var.trim().to_string()).collect();
+            let columns: Vec<String> = columns.iter().map(|var| var.trim().to_string()).collect();
+            let options = format!("{:?} range {:?} sample {:?} seed {} sort {:?} hide {:?} columns {:?} project {} column {:?} {:?} {:?}", cli.output, range, sample, seed, sort_by, hide_vars, columns, project, column, format, row_order);
+            let output = cached(cache.as_ref(), audit, &expr, "table", options, || {
+                let mut table = match (range, sample) {
+                    (Some((start, end)), _) => Evaluator::generate_truth_table_range(&expr, start, end),
+                    (None, Some(rows)) => Evaluator::sample_truth_table(&expr, rows, seed),
+                    (None, None) => Evaluator::generate_truth_table(&expr),
+                }.map_err(|e| miette::miette!("Truth table generation failed: {}", e))?;
+                if let Some(sort) = sort_by {
+                    Evaluator::sort_truth_table(&expr, &mut table, sort)
+                        .map_err(|e| miette::miette!("Sorting the table failed: {}", e))?;
+                }
+                audit.summarize(format!("{} rows, {} true", table.rows.len(), table.rows.iter().filter(|row| row.result).count()));
+                Ok(match column {
+                    Some(column) => {
+                        let values = table.column(&column, row_order).map_err(|e| miette::miette!("Column extraction failed: {}", e))?;
+                        format_column(&values, format)
+                    }
+                    None if !columns.is_empty() || !hide_vars.is_empty() || project => {
+                        let view = if columns.is_empty() {
+                            Evaluator::hide_columns(&table, &hide_vars)
+                        } else {
+                            let variables: Vec<String> = columns.iter().filter(|name| *name != "result").cloned().collect();
+                            Evaluator::select_columns(&table, &variables, columns.iter().any(|name| name == "result"))
+                        }.map_err(|e| miette::miette!("Column selection failed: {}", e))?;
+                        if project {
+                            format_projection(&Evaluator::project(&table, &view), &cli.output)
+                        } else {
+                            format_table_view(&view, &cli.output)
+                        }
+                    }
+                    None => format_truth_table(&table, &cli.output),
+                })
+            })?;
+            emit(output, &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Equivalence { expressions, dont_care } => {
             let (left_expr, right_expr) = InputHandler::get_expression_pair(expressions)?;
-            let left_parsed = parse_expression_with_error_handling(&left_expr)?;
-            let right_parsed = parse_expression_with_error_handling(&right_expr)?;
-            let result = Evaluator::check_equivalence(&left_parsed, &right_parsed)
-                .map_err(|e| miette::miette!("Equivalence check failed: {}", e))?;
-            print!("{}", format_equivalence_result(&result, &left_expr, &right_expr, &cli.output));
+            let left_parsed = parse_expression(&left_expr, &settings)?;
+            let right_parsed = parse_expression(&right_expr, &settings)?;
+            let result = match dont_care {
+                Some(dc) => {
+                    let dc_parsed = parse_expression(&dc, &settings)?;
+                    Evaluator::check_equivalence_with_dont_care(&left_parsed, &right_parsed, &dc_parsed)
+                }
+                None => Evaluator::check_equivalence(&left_parsed, &right_parsed),
+            }.map_err(|e| miette::miette!("Equivalence check failed: {}", e))?;
+            audit.summarize(if result.equivalent { "equivalent" } else { "not equivalent" });
+            let (left_display, right_display) = if cli.normalize {
+                (left_parsed.to_string(), right_parsed.to_string())
+            } else {
+                (left_expr, right_expr)
+            };
+            let mut warnings = expression_warnings(&left_parsed, Some("left"))?;
+            warnings.extend(expression_warnings(&right_parsed, Some("right"))?);
+            emit(format_equivalence_result(&result, &left_display, &right_display, &cli.output), &warnings, &cli.output);
         }
-        Commands::Reduce { expression } => {
-            let expr_str = InputHandler::get_single_expression(expression)?;
-            let expr = parse_expression_with_error_handling(&expr_str)?;
-            let result = Evaluator::reduce_expression(&expr)
-                .map_err(|e| miette::miette!("Expression reduction failed: {}", e))?;
-            print!("{}", format_reduction_result(&result, &cli.output));
+        Commands::Reduce { expression, trace, strict, from_vector, vars, basis } => {
+            let (expr_str, expr) = analysis_input(expression, from_vector, vars, &settings)?;
+            let warnings = expression_warnings(&expr, None)?;
+            // A rewritten C snippet keeps the text around the condition
+            let source = if cli.dialect == Dialect::C { expr_str.as_str() } else { "" };
+            let basis_name = basis.as_ref().map(Basis::to_string);
+            let options = format!("{:?} trace {} strict {} normalize {} basis {:?} {:?} {:?}", cli.output, trace, strict, cli.normalize, basis_name, cli.dialect, source);
+            let output = cached(cache.as_ref(), audit, &expr, "reduce", options, || {
+                let options = ReductionOptions { trace, strict, ..Default::default() };
+                let mut result = Evaluator::reduce_expression_with_options(&expr, options)
+                    .map_err(|e| miette::miette!("Expression reduction failed: {}", e))?;
+                if cli.normalize {
+                    result.reduced = result.reduced.normalize();
+                    result.simplified = result.reduced != result.original;
+                }
+                if let Some(basis) = &basis {
+                    result.reduced = Evaluator::rewrite_in_basis(&result.reduced, basis)
+                        .map_err(|e| miette::miette!("Rewriting into basis {} failed: {}", basis, e))?;
+                    result.simplified = result.reduced != result.original;
+                }
+                audit.summarize(format!("reduced to {}", result.reduced));
+                Ok(match cli.dialect {
+                    Dialect::Ttt | Dialect::Smtlib | Dialect::Sexpr | Dialect::Aiger | Dialect::FaultTree | Dialect::Spreadsheet | Dialect::Sql | Dialect::FeatureFlags | Dialect::StructuredText => {
+                        format_reduction_result(&result, &cli.output)
+                    }
+                    Dialect::C => {
+                        let rewrite = SnippetRewrite {
+                            rewritten: Snippet::extract(&expr_str).rewrite(&result.reduced),
+                            original: expr_str.clone(),
+                            reduction: result,
+                        };
+                        format_snippet_rewrite(&rewrite, &cli.output)
+                    }
+                })
+            })?;
+            emit(output, &warnings, &cli.output);
+        }
+        Commands::Eval { expression, vectors, mut set, three_valued: true, .. } => {
+            let (expression, positional) = split_assignments(expression);
+            if vectors.is_some() && !positional.is_empty() {
+                return Err(miette::miette!("`name=value` assignments can't be combined with --vectors"));
+            }
+            let (_, expr) = read_expression(expression, &settings)?;
+            set.extend(positional);
+            check_assigned_variables(&expr, &set, false)?;
+            let assignments = match vectors {
+                Some(vectors) => {
+                    let document = CsvDocument::parse(&InputHandler::read_file(&vectors)?)?;
+                    // Variables without a column stay unknown
+                    let columns: Vec<usize> = Evaluator::collect_expression_variables(&expr)
+                        .map_err(|e| miette::miette!("Evaluation failed: {}", e))?
+                        .iter()
+                        .filter_map(|var| document.column(var))
+                        .collect();
+                    document.ternary_assignments(&columns)?
+                }
+                None => vec![set.into_iter().map(|(var, value)| (var, Ternary::from(value))).collect()],
+            };
+            let table = Evaluator::evaluate_rows_three_valued(&expr, assignments)
+                .map_err(|e| miette::miette!("Evaluation failed: {}", e))?;
+            emit(format_three_valued_table(&table, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Eval { expression, vectors, mut set, why, three_valued: false } => {
+            let (expression, positional) = split_assignments(expression);
+            if vectors.is_some() && !positional.is_empty() {
+                return Err(miette::miette!("`name=value` assignments can't be combined with --vectors"));
+            }
+            let (_, expr) = read_expression(expression, &settings)?;
+            set.extend(positional);
+            check_assigned_variables(&expr, &set, vectors.is_none())?;
+            let document = match &vectors {
+                Some(vectors) => Some(CsvDocument::parse(&InputHandler::read_file(vectors)?)?),
+                None => None,
+            };
+            let assignments = match (&vectors, &document) {
+                (Some(vectors), Some(document)) => {
+                    let variables = Evaluator::collect_expression_variables(&expr)
+                        .map_err(|e| miette::miette!("Evaluation failed: {}", e))?;
+                    let columns = variables
+                        .iter()
+                        .map(|var| document.column(var).ok_or_else(|| miette::miette!(
+                            "Variable '{}' has no column in '{}'", var, vectors.display()
+                        )))
+                        .collect::<Result<Vec<_>>>()?;
+                    document.assignments(&columns)?
+                }
+                _ => vec![set.into_iter().collect()],
+            };
+            let output = if why {
+                let report = Evaluator::explain_rows(&expr, assignments)
+                    .map_err(|e| miette::miette!("Evaluation failed: {}", e))?;
+                format_explanations(&report, &cli.output)
+            } else {
+                let table = Evaluator::evaluate_rows(&expr, assignments)
+                    .map_err(|e| miette::miette!("Evaluation failed: {}", e))?;
+                match (&document, &cli.output) {
+                    // Every input row comes back as it was read, with its result
+                    (Some(document), _) => {
+                        let results: Vec<bool> = table.rows.iter().map(|row| row.result).collect();
+                        format_vectors(document, &results, &cli.output)
+                    }
+                    // One assignment in a terminal: just the result
+                    (None, OutputFormat::Table) => format!("{}\n", table.rows[0].result),
+                    (None, OutputFormat::Plain) => format!("{}\n", if table.rows[0].result { 1 } else { 0 }),
+                    (None, _) => format_truth_table(&table, &cli.output),
+                }
+            };
+            emit(output, &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::TableDiff { left, right } => {
+            let left_table = InputHandler::read_truth_table(&left)?;
+            let right_table = InputHandler::read_truth_table(&right)?;
+            let diff = Evaluator::diff_truth_tables(&left_table, &right_table);
+            print!("{}", format_table_diff(&diff, &left.display().to_string(), &right.display().to_string(), &cli.output));
+            if !diff.identical {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Commands::Merge { tables, allow_partial } => {
+            let partials = tables
+                .iter()
+                .map(|path| InputHandler::read_truth_table(path))
+                .collect::<Result<Vec<_>>>()?;
+            let merge = Evaluator::merge_truth_tables(&partials).map_err(|e| match e {
+                EvaluationError::MismatchedVariables { table, .. } => {
+                    miette::miette!("Cannot merge '{}': {}", tables[table].display(), e)
+                }
+                e => miette::miette!("Merge failed: {}", e),
+            })?;
+            if let Some(conflict) = merge.conflicts.first() {
+                let assignment = merge.table.variables
+                    .iter()
+                    .map(|var| format!("{}={}", var, if conflict.assignment.get(var).copied().unwrap_or(false) { "T" } else { "F" }))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                return Err(miette::miette!(
+                    "{} conflicting row(s); first at {}: '{}' gives {}, '{}' gives {}",
+                    merge.conflicts.len(),
+                    assignment,
+                    tables[conflict.first_table].display(),
+                    conflict.first_value,
+                    tables[conflict.second_table].display(),
+                    conflict.second_value,
+                ));
+            }
+            if merge.missing_rows > 0 && !allow_partial {
+                return Err(miette::miette!(
+                    help = "Pass --allow-partial to output the rows that are present",
+                    "Merged table is missing {} of {} row(s)",
+                    merge.missing_rows,
+                    1usize << merge.table.variables.len()
+                ));
+            }
+            let mut warnings = Vec::new();
+            if merge.overlapping_rows > 0 {
+                warnings.push(Warning::new(
+                    WarningKind::OverlappingRows,
+                    format!("{} row(s) appear in more than one table with the same result", merge.overlapping_rows),
+                ));
+            }
+            emit(format_truth_table(&merge.table, &cli.output), &warnings, &cli.output);
+        }
+        Commands::Subexpressions { expression, sort } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let report = Evaluator::analyze_subexpressions(&expr, sort)
+                .map_err(|e| miette::miette!("Subexpression analysis failed: {}", e))?;
+            emit(format_subexpressions(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::LintFiles { patterns, parse_only } => {
+            let mut report = LintReport::default();
+            for pattern in &patterns {
+                let paths = expand_glob(pattern)?;
+                // Otherwise a mistyped pattern in a hook would pass by checking nothing
+                if paths.is_empty() {
+                    return Err(miette::miette!("'{}' matched no files", pattern));
+                }
+                for path in paths {
+                    let text = InputHandler::read_file(&path)?;
+                    report.files_scanned += 1;
+                    report.findings.extend(lint_source(&path.display().to_string(), &text, !parse_only));
+                }
+            }
+            print!("{}", format_lint_report(&report, &cli.output));
+            if !report.passed() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Commands::Prove { chain } => {
+            let chain_str = InputHandler::get_single_expression(chain)?;
+            let steps = InputHandler::split_chain(&chain_str)
+                .into_iter()
+                .map(|step| parse_expression(step.trim(), &settings))
+                .collect::<Result<Vec<_>>>()?;
+            let proof = Evaluator::prove_chain(&steps)
+                .map_err(|e| miette::miette!("Proof check failed: {}", e))?;
+            print!("{}", format_chain_proof(&proof, &cli.output));
+            if !proof.valid {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Commands::Expand { expression, target_size, seed } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let expansion = Evaluator::expand_expression(&expr, target_size, seed)
+                .map_err(|e| miette::miette!("Expansion failed: {}", e))?;
+            emit(format_expansion(&expansion, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Cnf { expression, tseitin } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let cnf = Evaluator::to_cnf(&expr, tseitin)
+                .map_err(|e| miette::miette!("CNF conversion failed: {}", e))?;
+            emit(format_cnf(&cnf, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Dnf { expression, canonical } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let dnf = Evaluator::to_dnf(&expr, canonical)
+                .map_err(|e| miette::miette!("DNF conversion failed: {}", e))?;
+            emit(format_dnf(&dnf, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Nnf { expression } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let nnf = Evaluator::to_nnf(&expr)
+                .map_err(|e| miette::miette!("NNF conversion failed: {}", e))?;
+            emit(format_nnf(&nnf, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Anf { expression } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let report = Evaluator::algebraic_normal_form(&expr)
+                .map_err(|e| miette::miette!("ANF conversion failed: {}", e))?;
+            emit(format_anf(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Convert { expression, basis } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let conversion = Evaluator::convert(&expr, &basis)
+                .map_err(|e| miette::miette!("Rewriting into basis {} failed: {}", basis, e))?;
+            emit(format_conversion(&conversion, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Report { expression, format, no_provenance } => {
+            let (source, expr) = read_expression(expression, &settings)?;
+            let report = Evaluator::report(&expr)
+                .map_err(|e| miette::miette!("Report failed: {}", e))?;
+            let warnings = expression_warnings(&expr, None)?;
+            match format {
+                Some(format) => {
+                    for warning in &warnings {
+                        eprintln!("warning: {}", warning);
+                    }
+                    let provenance = (!no_provenance).then(|| Provenance::new(&source, &expr, cli.invocation.clone()));
+                    write_artifact(render_report(&report, format).into_bytes(), format.comments(), provenance)?;
+                }
+                None => emit(format_report(&report, &cli.output), &warnings, &cli.output),
+            }
+        }
+        Commands::Export { expression, format, no_provenance } => {
+            let (source, expr) = read_expression(expression, &settings)?;
+            let bytes = export(&expr, format)
+                .map_err(|e| miette::miette!("Export failed: {}", e))?;
+            let provenance = (!no_provenance).then(|| Provenance::new(&source, &expr, cli.invocation));
+            write_artifact(bytes, format.comments(), provenance)?;
+        }
+        Commands::Implicants { expression, count_only } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let report = Evaluator::prime_implicants(&expr, !count_only)
+                .map_err(|e| miette::miette!("Prime implicant computation failed: {}", e))?;
+            emit(format_prime_implicants(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Influence { expression, shapley } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let report = Evaluator::analyze_influence(&expr, shapley)
+                .map_err(|e| miette::miette!("Influence analysis failed: {}", e))?;
+            emit(format_influence(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Abduce { expression, target, fixed } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let abduction = Evaluator::abduce(&expr, &fixed.into_iter().collect(), target)
+                .map_err(|e| miette::miette!("Abduction failed: {}", e))?;
+            emit(format_abduction(&abduction, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Check { expression } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let report = Evaluator::classify(&expr)
+                .map_err(|e| miette::miette!("Classification failed: {}", e))?;
+            audit.summarize(report.classification.to_string());
+            emit(format_classification(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+            // 1 is left for errors
+            match report.classification {
+                Classification::Tautology => {}
+                Classification::Contingent => return Ok(ExitCode::from(2)),
+                Classification::Contradiction => return Ok(ExitCode::from(3)),
+            }
+        }
+        Commands::Regress { golden, input } => {
+            let report = Evaluator::regress(&read_corpus(&golden, &settings)?, &read_corpus(&input, &settings)?);
+            audit.summarize(format!(
+                "{} semantic, {} syntactic change(s)",
+                report.count(RegressionStatus::Semantic),
+                report.count(RegressionStatus::Syntactic)
+            ));
+            print!("{}", format_regression(&report, &cli.output));
+            if !report.passed() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Commands::Sat { expression } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let report = Evaluator::check_satisfiability(&expr)
+                .map_err(|e| miette::miette!("Satisfiability check failed: {}", e))?;
+            audit.summarize(if report.satisfiable { "satisfiable" } else { "unsatisfiable" });
+            emit(format_satisfiability(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Propagate { expression, set } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let propagation = Evaluator::propagate(&expr, &set.into_iter().collect())
+                .map_err(|e| miette::miette!("Propagation failed: {}", e))?;
+            emit(format_propagation(&propagation, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Cutsets { expression, prob } => {
+            let (expr_str, expr) = read_expression(expression, &settings)?;
+            let mut probabilities: HashMap<String, f64> = match cli.dialect {
+                Dialect::FaultTree => parse_fault_tree(&expr_str).map(|tree| tree.probabilities.into_iter().collect()).unwrap_or_default(),
+                _ => HashMap::new(),
+            };
+            probabilities.extend(prob);
+            let report = Evaluator::minimal_cut_sets(&expr, &probabilities)
+                .map_err(|e| miette::miette!("Cut set extraction failed: {}", e))?;
+            emit(format_cut_sets(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Spectrum { expression, from_vector, vars } => {
+            let (_, expr) = analysis_input(expression, from_vector, vars, &settings)?;
+            let report = Evaluator::walsh_spectrum(&expr)
+                .map_err(|e| miette::miette!("Spectrum analysis failed: {}", e))?;
+            emit(format_spectrum(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Bias { expression, from_vector, vars } => {
+            let (_, expr) = analysis_input(expression, from_vector, vars, &settings)?;
+            let report = Evaluator::analyze_bias(&expr)
+                .map_err(|e| miette::miette!("Bias analysis failed: {}", e))?;
+            emit(format_bias(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Degree { expression, immunity } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let report = Evaluator::analyze_degree(&expr, immunity)
+                .map_err(|e| miette::miette!("Degree analysis failed: {}", e))?;
+            emit(format_degree(&report, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Sbox { values, output_bits } => {
+            let table = parse_lookup_table(&InputHandler::get_single_expression(values)?)?;
+            let output_bits = output_bits.unwrap_or(table.len().max(1).ilog2() as usize);
+            let report = Evaluator::analyze_sbox(&table, output_bits)
+                .map_err(|e| miette::miette!("S-box analysis failed: {}", e))?;
+            print!("{}", format_sbox(&report, &cli.output));
+        }
+        Commands::Threshold { expression } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let analysis = Evaluator::analyze_threshold(&expr)
+                .map_err(|e| miette::miette!("Threshold analysis failed: {}", e))?;
+            emit(format_threshold(&analysis, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Simplify { expression } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let rewrite = Rewriter::default().rewrite(&expr);
+            audit.summarize(format!("simplified to {}", rewrite.rewritten));
+            emit(format_rewrite(&rewrite, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Pretty { expression, style } => {
+            let (_, expr) = read_expression(expression, &settings)?;
+            let printer = PrettyPrinter::new().with_precedence(cli.precedence).with_style(style);
+            println!("{}", printer.print(&expr));
+        }
+        Commands::Repl { script } => {
+            let library = settings.library.clone().unwrap_or_default();
+            let parse = |input: &str, library: &Library| {
+                parse_expression(input, &ParseSettings { library: Some(library.clone()), aliases: settings.aliases.clone(), ..settings })
+            };
+            let mut session = Session::with_library(cli.output, library);
+            if let Some(path) = script {
+                let script = InputHandler::read_file(&path)?;
+                for (line, output) in session.replay(&script, &parse)? {
+                    println!("ttt> {}", line);
+                    print!("{}", output);
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+            let interactive = std::io::stdin().is_terminal();
+            let prompt = || {
+                if interactive {
+                    print!("ttt> ");
+                    let _ = std::io::stdout().flush();
+                }
+            };
+            prompt();
+            // A line at a time rather than `lines()`, which would keep stdin
+            // locked while the explorer reads keys from it
+            let mut line = String::new();
+            while std::io::stdin().read_line(&mut line).into_diagnostic()? > 0 {
+                let reply = session.handle(&line, &parse).and_then(|reply| match reply {
+                    Reply::Explore if interactive => run_explorer(&mut session),
+                    Reply::Explore => Err(miette::miette!(":explore needs a terminal")),
+                    reply => Ok(reply),
+                });
+                match reply {
+                    Ok(Reply::Output(text)) => print!("{}", text),
+                    Ok(Reply::Quit) => break,
+                    Ok(Reply::Explore) => {}
+                    Err(e) => eprintln!("{:?}", e),
+                }
+                line.clear();
+                prompt();
+            }
+        }
+        Commands::NotebookServer => {
+            let parse = |input: &str, library: &Library| {
+                parse_expression(input, &ParseSettings { library: Some(library.clone()), aliases: settings.aliases.clone(), ..settings })
+            };
+            let mut kernel = Kernel::new(settings.library.clone().unwrap_or_default());
+            for line in std::io::stdin().lines() {
+                let line = line.into_diagnostic()?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let response = match serde_json::from_str::<Request>(&line) {
+                    Ok(request) => kernel.execute(request, &parse),
+                    Err(e) => Kernel::malformed(&e),
+                };
+                println!("{}", serde_json::to_string(&response).into_diagnostic()?);
+                let _ = std::io::stdout().flush();
+            }
+        }
+        Commands::Synth { expression, from_vector, vars, exact, max_size, basis } => {
+            let (_, expr) = analysis_input(expression, from_vector, vars, &settings)?;
+            let synthesis = if exact {
+                Evaluator::synthesize_exact(&expr, &basis, max_size)
+            } else {
+                Evaluator::synthesize(&expr, &basis, max_size)
+            }
+            .map_err(|e| miette::miette!("Synthesis failed: {}", e))?;
+            if let (Some(result), true) = (&synthesis.result, synthesis.exact) {
+                audit.summarize(format!("smallest form {}", result));
+            }
+            emit(format_synthesis(&synthesis, &cli.output), &expression_warnings(&expr, None)?, &cli.output);
+        }
+        Commands::Info => {
+            let commands = Cli::command().get_subcommands().map(|command| command.get_name().to_string()).collect();
+            print!("{}", format_capabilities(&Capabilities::new(commands), &cli.output));
+        }
+        Commands::Cache { action: CacheAction::Clear } => {
+            let dir = Cache::default_dir().ok_or_else(|| miette::miette!("No cache directory: neither XDG_CACHE_HOME nor HOME is set"))?;
+            let removed = Cache::new(&dir).clear().into_diagnostic()?;
+            println!("Removed {} cached result{} from {}", removed, if removed == 1 { "" } else { "s" }, dir.display());
         }
     }
     
+    Ok(ExitCode::SUCCESS)
+}
+
+
+/// Options that control how command-line expressions are read
+struct ParseSettings<'a> {
+    input_format: InputFormat,
+    normalize: bool,
+    dialect: Dialect,
+    notation: Notation,
+    precedence: Precedence,
+    implication_assoc: Associativity,
+    syntax: Syntax,
+    implicit_and: bool,
+    case_insensitive: bool,
+    max_nesting: usize,
+    /// The Structured Text variable whose value is read
+    assignment: Option<&'a str>,
+    library: Option<Library>,
+    /// Words read as operators, besides the library's
+    aliases: Aliases,
+    /// Notes every expression read, for the audit log
+    audit: &'a Audit,
+}
+
+fn parse_expression(input: &str, settings: &ParseSettings) -> Result<Expr> {
+    if settings.input_format != InputFormat::Text {
+        return Err(miette::miette!("Binary input is only read by commands that take a single expression"));
+    }
+    let expr = match settings.dialect {
+        Dialect::Ttt => {
+            let mut parser = Parser::new_with_notation(input, settings.notation).with_precedence(settings.precedence)
+                .with_implication_associativity(settings.implication_assoc).with_syntax(settings.syntax)
+                .with_max_nesting(settings.max_nesting);
+            if let Some(library) = &settings.library {
+                parser = parser.with_library(library.clone());
+            }
+            if !settings.aliases.is_empty() {
+                parser = parser.with_aliases(settings.aliases.clone());
+            }
+            if settings.implicit_and {
+                parser = parser.with_implicit_and();
+            }
+            if settings.case_insensitive {
+                parser = parser.with_case_insensitive_keywords();
+            }
+            parse_with_source(parser, input)?
+        }
+        Dialect::C => {
+            let snippet = Snippet::extract(input);
+            parse_c(&snippet.condition, snippet.prefix.len()).map_err(|e| {
+                let named_source = NamedSource::new("snippet", input.to_string());
+                miette::Report::new(e).with_source_code(named_source)
+            })?
+        }
+        Dialect::Sexpr => parse_sexpr(input).map_err(|e| {
+            let named_source = NamedSource::new("expression", input.to_string());
+            miette::Report::new(e).with_source_code(named_source)
+        })?,
+        Dialect::Smtlib => parse_smtlib(input).map_err(|e| {
+            let named_source = NamedSource::new("script", input.to_string());
+            miette::Report::new(e).with_source_code(named_source)
+        })?,
+        Dialect::Aiger => {
+            let aig = aiger::parse(input.as_bytes())?;
+            if aig.outputs().len() != 1 {
+                return Err(miette::miette!("Expected an AIGER circuit with exactly one output, found {}", aig.outputs().len()));
+            }
+            aig.to_expr(0).map_err(|e| miette::miette!("AIGER conversion failed: {}", e))?
+        }
+        Dialect::FaultTree => parse_fault_tree(input).map_err(|e| {
+            let named_source = NamedSource::new("fault tree", input.to_string());
+            miette::Report::new(e).with_source_code(named_source)
+        })?.expr,
+        Dialect::Spreadsheet => parse_spreadsheet(input).map_err(|e| {
+            let named_source = NamedSource::new("formula", input.to_string());
+            miette::Report::new(e).with_source_code(named_source)
+        })?,
+        Dialect::Sql => parse_sql(input).map_err(|e| {
+            let named_source = NamedSource::new("predicate", input.to_string());
+            miette::Report::new(e).with_source_code(named_source)
+        })?,
+        Dialect::FeatureFlags => parse_feature_flags(input).map_err(|e| {
+            let named_source = NamedSource::new("rule", input.to_string());
+            miette::Report::new(e).with_source_code(named_source)
+        })?,
+        Dialect::StructuredText => parse_structured_text(input)
+            .and_then(|program| program.output(settings.assignment).cloned())
+            .map_err(|e| {
+                let named_source = NamedSource::new("program", input.to_string());
+                miette::Report::new(e).with_source_code(named_source)
+            })?,
+    };
+    let expr = if settings.normalize { expr.normalize() } else { expr };
+    settings.audit.expression(&expr);
+    Ok(expr)
+}
+
+/// The expression an analysis command works on, with the text it was read from:
+/// a truth vector file when one is given, otherwise the arguments or stdin
+fn analysis_input(expression: Vec<String>, from_vector: Option<PathBuf>, vars: Option<usize>, settings: &ParseSettings) -> Result<(String, Expr)> {
+    if let (Some(path), Some(vars)) = (from_vector, vars) {
+        let expr = TruthVector::read(&path, vars)?.to_expr();
+        settings.audit.expression(&expr);
+        return Ok((expr.to_string(), expr));
+    }
+    read_expression(expression, settings)
+}
+
+/// The expression of a command that reads one, with the text it was read from;
+/// binary input is shown as the decoded expression
+fn read_expression(expression: Vec<String>, settings: &ParseSettings) -> Result<(String, Expr)> {
+    match settings.input_format {
+        InputFormat::Text => {
+            let expr_str = InputHandler::get_single_expression(expression)?;
+            let expr = parse_expression(&expr_str, settings)?;
+            Ok((expr_str, expr))
+        }
+        #[cfg(feature = "binary")]
+        InputFormat::Bin => {
+            let expr = Expr::from_bytes(&InputHandler::get_single_binary(expression)?)?;
+            let expr = if settings.normalize { expr.normalize() } else { expr };
+            settings.audit.expression(&expr);
+            Ok((expr.to_string(), expr))
+        }
+    }
+}
+
+/// `compute`'s output for `command` on `expr`, reused from the cache when an
+/// earlier run had the same `options`
+fn cached(cache: Option<&Cache>, audit: &Audit, expr: &Expr, command: &str, options: String, compute: impl FnOnce() -> Result<String>) -> Result<String> {
+    let Some(cache) = cache else {
+        return compute();
+    };
+    let mut computed = false;
+    let output = cache.get_or_insert_with(&CacheKey::new(expr, command, &options), || {
+        computed = true;
+        compute()
+    })?;
+    if !computed {
+        audit.summarize("reused cached output");
+    }
+    Ok(output)
+}
+
+/// Warnings about an expression, prefixed with `label` when a command reads more than one
+fn expression_warnings(expr: &Expr, label: Option<&str>) -> Result<Vec<Warning>> {
+    let mut warnings = Evaluator::expression_warnings(expr)
+        .map_err(|e| miette::miette!("Checking for warnings failed: {}", e))?;
+    if let Some(label) = label {
+        for warning in &mut warnings {
+            warning.message = format!("{} expression: {}", label, warning.message);
+        }
+    }
+    Ok(warnings)
+}
+
+/// Write a generated file to stdout, with a comment saying how it was produced unless `provenance` is `None`
+fn write_artifact(document: Vec<u8>, comments: CommentSyntax, provenance: Option<Provenance>) -> Result<()> {
+    let document = match provenance {
+        Some(provenance) => provenance.stamp(document, comments),
+        None => document,
+    };
+    std::io::stdout().write_all(&document).into_diagnostic()
+}
+
+/// Print a command's output, reporting warnings on stderr and in JSON output
+fn emit(output: String, warnings: &[Warning], format: &OutputFormat) {
+    for warning in warnings {
+        eprintln!("warning: {}", warning);
+    }
+    print!("{}", attach_warnings(output, warnings, format));
+}
+
+/// The options given on the command line, global and the subcommand's, as
+/// `--name value` in the order `--help` lists them; positional arguments and
+/// defaults are left out
+fn explicit_options(matches: &clap::ArgMatches) -> Vec<String> {
+    let Some((name, matches)) = matches.subcommand() else {
+        return Vec::new();
+    };
+    let mut command = Cli::command();
+    command.build();
+    let Some(command) = command.find_subcommand(name) else {
+        return Vec::new();
+    };
+    let mut options = Vec::new();
+    for arg in command.get_arguments().filter(|arg| !arg.is_positional()) {
+        let id = arg.get_id().as_str();
+        if matches.value_source(id) != Some(clap::parser::ValueSource::CommandLine) {
+            continue;
+        }
+        let flag = arg.get_long().map_or_else(|| format!("-{}", arg.get_short().unwrap_or_default()), |long| format!("--{}", long));
+        if !arg.get_action().takes_values() {
+            options.push(flag);
+            continue;
+        }
+        for value in matches.get_raw(id).into_iter().flatten() {
+            let value = value.to_string_lossy();
+            options.push(flag.clone());
+            options.push(if value.is_empty() || value.contains(char::is_whitespace) { format!("{:?}", value) } else { value.into_owned() });
+        }
+    }
+    options
+}
+
+/// Parse a boolean option value such as `true`, `0` or `F`
+fn parse_bool_value(value: &str) -> std::result::Result<bool, String> {
+    parse_bool(value).ok_or_else(|| format!("invalid value '{}': expected true/false, T/F or 1/0", value))
+}
+
+/// Parse `name=value` with a boolean value such as `1`, `false` or `T`
+fn parse_assignment(value: &str) -> std::result::Result<(String, bool), String> {
+    let (name, flag) = value.split_once('=').ok_or("expected NAME=VALUE")?;
+    let flag = parse_bool(flag).ok_or_else(|| format!("invalid value '{}' for '{}': expected true/false, T/F or 1/0", flag, name.trim()))?;
+    Ok((name.trim().to_string(), flag))
+}
+
+/// The entries of a JSON-lines corpus, keyed by name or fingerprint; blank lines are skipped
+fn read_corpus(path: &Path, settings: &ParseSettings) -> Result<Vec<(String, Expr)>> {
+    let mut entries: Vec<(String, Expr)> = Vec::new();
+    for (idx, line) in InputHandler::read_file(path)?.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let location = || format!("line {} of '{}'", idx + 1, path.display());
+        let entry: CorpusEntry = serde_json::from_str(line).map_err(|e| miette::miette!("Malformed entry on {}: {}", location(), e))?;
+        let expr = parse_expression(&entry.expression, settings).map_err(|e| e.wrap_err(format!("Failed to parse the expression on {}", location())))?;
+        let key = corpus_key(entry.name.as_deref(), &expr);
+        match entries.iter().find(|(existing, _)| *existing == key) {
+            // The same unnamed expression twice is one entry
+            Some(_) if entry.name.is_none() => {}
+            Some(_) => return Err(miette::miette!("'{}' appears twice; second on {}", key, location())),
+            None => entries.push((key, expr)),
+        }
+    }
+    Ok(entries)
+}
+
+/// Split trailing `name=value` arguments, as in `ttt eval "a and b" a=1 b=0`,
+/// from the words of the expression before them
+fn split_assignments(mut args: Vec<String>) -> (Vec<String>, Vec<(String, bool)>) {
+    let mut assignments = Vec::new();
+    while let Some(assignment) = args.last().and_then(|arg| parse_assignment(arg).ok()).filter(|(name, _)| !name.is_empty()) {
+        assignments.push(assignment);
+        args.pop();
+    }
+    assignments.reverse();
+    (args, assignments)
+}
+
+/// Refuse values for names that aren't variables of `expr`, and with
+/// `require_all`, a missing value for one that is
+fn check_assigned_variables(expr: &Expr, assignments: &[(String, bool)], require_all: bool) -> Result<()> {
+    let variables = Evaluator::collect_expression_variables(expr).map_err(|e| miette::miette!("Evaluation failed: {}", e))?;
+    if let Some((name, _)) = assignments.iter().find(|(name, _)| !variables.contains(name)) {
+        let known: Vec<&str> = variables.iter().collect();
+        return Err(miette::miette!(
+            help = format!("The expression's variables are {}", if known.is_empty() { "none".to_string() } else { known.join(", ") }),
+            "Unknown variable '{}'", name
+        ));
+    }
+    let missing: Vec<&str> = variables.iter().filter(|var| !assignments.iter().any(|(name, _)| name == var)).collect();
+    if require_all && !missing.is_empty() {
+        return Err(miette::miette!(
+            help = "Give assignments as `name=value` arguments or with `--set`, or a CSV file of them with `--vectors`",
+            "No value given for {}", missing.join(", ")
+        ));
+    }
     Ok(())
 }
 
+/// Parse `word=operator`, checking the alias is one `--alias` accepts
+fn parse_alias(value: &str) -> std::result::Result<(String, String), String> {
+    let (word, operator) = value.split_once('=').ok_or("expected WORD=OPERATOR")?;
+    Aliases::new().add(word.trim(), operator)?;
+    Ok((word.trim().to_string(), operator.trim().to_string()))
+}
 
+/// Parse `name=p` with a probability between 0 and 1
+fn parse_probability(value: &str) -> std::result::Result<(String, f64), String> {
+    let (name, p) = value.split_once('=').ok_or("expected NAME=P")?;
+    let p = p.trim().parse::<f64>().ok().filter(|p| (0.0..=1.0).contains(p))
+        .ok_or_else(|| format!("invalid probability '{}' for '{}': expected a number between 0 and 1", p, name.trim()))?;
+    Ok((name.trim().to_string(), p))
+}
+
+/// Parse `START..END` or `START..` into a row range
+fn parse_row_range(value: &str) -> std::result::Result<(usize, Option<usize>), String> {
+    let (start, end) = value.split_once("..").ok_or("expected START..END")?;
+    let start = start.trim().parse().map_err(|_| format!("invalid start row '{}'", start))?;
+    let end = match end.trim() {
+        "" => None,
+        end => Some(end.parse().map_err(|_| format!("invalid end row '{}'", end))?),
+    };
+    Ok((start, end))
+}
+
+#[cfg(test)]
 fn parse_expression_with_error_handling(input: &str) -> Result<Expr> {
-    let mut parser = Parser::new(input);
-    parser.parse().map_err(|e| {
+    parse_with_source(Parser::new(input), input)
+}
+
+fn parse_with_source(mut parser: Parser, input: &str) -> Result<Expr> {
+    parser.parse_recovering().map_err(|mut errors| {
+        for error in &mut errors {
+            // The parser's hint names its library method; here the switch is a flag
+            if let ParseError::MissingOperator { help, .. } = error {
+                *help = ImplicitAndHint::Flag;
+            }
+        }
         let named_source = NamedSource::new("expression", input.to_string());
-        miette::Report::new(e).with_source_code(named_source)
+        if errors.len() == 1 {
+            miette::Report::new(errors.remove(0)).with_source_code(named_source)
+        } else {
+            miette::Report::new(ParseErrors { errors }).with_source_code(named_source)
+        }
     })
 }
 
@@ -87,6 +1410,7 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
     use ttt::eval::{EquivalenceCheck, Reduction, TruthTable};
+    use ttt::expr;
     
     #[test]
     fn test_input_handler_single_expression() {
@@ -137,6 +1461,20 @@ mod tests {
         assert!(result.is_err());
     }
     
+    #[test]
+    fn test_split_assignments() {
+        let args = |words: &[&str]| words.iter().map(|word| word.to_string()).collect::<Vec<_>>();
+        let (expression, assignments) = split_assignments(args(&["a and (b or c)", "a=true", "b=0", "c=T"]));
+        assert_eq!(expression, args(&["a and (b or c)"]));
+        assert_eq!(assignments, vec![("a".to_string(), true), ("b".to_string(), false), ("c".to_string(), true)]);
+        // `==` and `=>` are operators, not assignments
+        assert_eq!(split_assignments(args(&["a", "==", "b"])).1, vec![]);
+        assert_eq!(split_assignments(args(&["a", "=>b"])).1, vec![]);
+        assert!(check_assigned_variables(&expr!(a and b), &[("a".to_string(), true), ("c".to_string(), true)], false).is_err());
+        assert!(check_assigned_variables(&expr!(a and b), &[("a".to_string(), true)], true).is_err());
+        assert!(check_assigned_variables(&expr!(a and b), &[("a".to_string(), true)], false).is_ok());
+    }
+
     #[test]
     fn test_display_functions_dont_panic() {
         // These tests verify that display functions don't panic
@@ -144,10 +1482,7 @@ mod tests {
         
         // Test truth table display
         use ttt::eval::Variables;
-        let variables = Variables::from_expr(&Expr::And(
-            Box::new(Expr::Identifier("a".to_string())),
-            Box::new(Expr::Identifier("b".to_string()))
-        )).unwrap();
+        let variables = Variables::from_expr(&expr!(a and b)).unwrap();
         
         let table = TruthTable {
             variables,
@@ -162,26 +1497,44 @@ mod tests {
                     result: false,
                 }
             ],
+            range: None,
+            sample: None,
         };
         let _result = format_truth_table(&table, &OutputFormat::Table); // Should not panic
         
         // Test equivalence display
-        let variables = Variables::from_expr(&Expr::Identifier("a".to_string())).unwrap();
+        let variables = Variables::from_expr(&expr!(a)).unwrap();
         let check = EquivalenceCheck {
             equivalent: false,
             variables,
             differences: vec![],
+            ignored: 0,
         };
         let _result = format_equivalence_result(&check, "a", "not a", &OutputFormat::Table); // Should not panic
         
         // Test reduction display
-        use ttt::source::Expr;
         use ttt::eval::TruthTableRow;
         let reduction = Reduction {
-            original: Expr::Identifier("a".to_string()),
-            reduced: Expr::Identifier("a".to_string()),
+            original: expr!(a),
+            reduced: expr!(a),
             simplified: false,
+            minimal: true,
+            coverage: None,
         };
         let _result = format_reduction_result(&reduction, &OutputFormat::Table); // Should not panic
     }
+
+    #[test]
+    fn test_info_reports_every_command() {
+        let cli = Cli::try_parse_from(["ttt", "info", "-o", "json"]).unwrap();
+        assert!(matches!(cli.command, Commands::Info));
+        assert!(matches!(cli.output, OutputFormat::Json));
+
+        let commands: Vec<String> = Cli::command().get_subcommands().map(|c| c.get_name().to_string()).collect();
+        let info = Capabilities::new(commands);
+        assert!(info.commands.iter().any(|c| c == "info"));
+        assert!(info.output_formats.iter().any(|f| f == "nuon"));
+        let parsed: Capabilities = serde_json::from_str(&format_capabilities(&info, &OutputFormat::Json)).unwrap();
+        assert_eq!(parsed, info);
+    }
 }
@@ -0,0 +1,144 @@
+use crate::source::Expr;
+use crate::eval::{Variables, EvaluationError, EquivalenceDifference};
+use crate::eval::truth_table::{assignment_from_index, evaluate_expression};
+use crate::eval::rng::SplitMix64;
+use std::collections::HashMap;
+
+/// Outcome of comparing a callback against an expression
+#[derive(Debug, Clone)]
+pub struct OracleReport {
+    pub variables: Variables,
+    /// Number of assignments the callback was called with
+    pub checked: usize,
+    /// Assignments where they disagree; `left_value` is the expression's result,
+    /// `right_value` the callback's
+    pub counterexamples: Vec<EquivalenceDifference>,
+}
+
+impl OracleReport {
+    pub fn passed(&self) -> bool {
+        self.counterexamples.is_empty()
+    }
+}
+
+/// Compare `callback` against `expr` over every assignment of the expression's variables.
+///
+/// ```
+/// use ttt::source::Parser;
+/// use ttt::oracle::check_equiv_callback;
+///
+/// let spec = Parser::new("a xor b").parse().unwrap();
+/// let report = check_equiv_callback(&spec, |v| v["a"] != v["b"]).unwrap();
+/// assert!(report.passed());
+/// ```
+pub fn check_equiv_callback<F>(expr: &Expr, callback: F) -> Result<OracleReport, EvaluationError>
+where
+    F: FnMut(&HashMap<String, bool>) -> bool,
+{
+    let variables = Variables::from_expr(expr)?;
+    let rows = (0..1usize << variables.len()).map(|index| assignment_from_index(&variables, index));
+    let (checked, counterexamples) = compare(expr, rows, callback);
+    Ok(OracleReport { variables, checked, counterexamples })
+}
+
+/// Compare `callback` against `expr` on `samples` assignments drawn at random
+/// (with replacement) from a generator seeded with `seed`, with no limit on
+/// the number of variables
+pub fn check_equiv_callback_sampled<F>(expr: &Expr, samples: usize, seed: u64, callback: F) -> Result<OracleReport, EvaluationError>
+where
+    F: FnMut(&HashMap<String, bool>) -> bool,
+{
+    let variables = Variables::from_expr_unbounded(expr)?;
+    let mut rng = SplitMix64(seed);
+    // One draw per variable, as `table --sample` does, rather than a row
+    // index that would overflow past 64 variables
+    let rows = (0..samples).map(|_| variables.iter().map(|var| (var.to_string(), rng.next() >> 63 == 1)).collect());
+    let (checked, counterexamples) = compare(expr, rows, callback);
+    Ok(OracleReport { variables, checked, counterexamples })
+}
+
+/// The number of assignments checked, and those where `callback` disagrees with `expr`
+fn compare<F>(expr: &Expr, rows: impl Iterator<Item = HashMap<String, bool>>, mut callback: F) -> (usize, Vec<EquivalenceDifference>)
+where
+    F: FnMut(&HashMap<String, bool>) -> bool,
+{
+    let mut checked = 0;
+    let mut counterexamples = Vec::new();
+    for assignment in rows {
+        let expected = evaluate_expression(expr, &assignment);
+        let actual = callback(&assignment);
+        checked += 1;
+        if expected != actual {
+            counterexamples.push(EquivalenceDifference {
+                assignment,
+                left_value: expected,
+                right_value: actual,
+            });
+        }
+    }
+    (checked, counterexamples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    #[test]
+    fn test_bit_twiddling_matches_spec() {
+        // Majority of three, computed with bit operations
+        let spec = Parser::new("(a and b) or (a and c) or (b and c)").parse().unwrap();
+        let report = check_equiv_callback(&spec, |v| {
+            let (a, b, c) = (v["a"] as u8, v["b"] as u8, v["c"] as u8);
+            (a & b | a & c | b & c) == 1
+        }).unwrap();
+        assert!(report.passed());
+        assert_eq!(report.checked, 8);
+    }
+
+    #[test]
+    fn test_counterexamples_reported() {
+        let spec = Parser::new("a or b").parse().unwrap();
+        let report = check_equiv_callback(&spec, |v| v["a"] ^ v["b"]).unwrap();
+        assert_eq!(report.counterexamples.len(), 1);
+        let diff = &report.counterexamples[0];
+        assert!(diff.assignment["a"] && diff.assignment["b"]);
+        assert!(diff.left_value && !diff.right_value);
+    }
+
+    #[test]
+    fn test_sampled_is_reproducible() {
+        let spec = Parser::new("a and b and c and d").parse().unwrap();
+        let mut seen = Vec::new();
+        let report = check_equiv_callback_sampled(&spec, 5, 9, |v| {
+            seen.push(v.clone());
+            v.values().all(|&x| x)
+        }).unwrap();
+        assert!(report.passed());
+        assert_eq!(report.checked, 5);
+
+        let mut again = Vec::new();
+        check_equiv_callback_sampled(&spec, 5, 9, |v| {
+            again.push(v.clone());
+            true
+        }).unwrap();
+        assert_eq!(seen, again);
+    }
+
+    #[test]
+    fn test_sampled_takes_more_variables_than_a_table() {
+        // Parity of 100 variables, far more than an exhaustive check allows
+        let names: Vec<String> = (0..100).map(|i| format!("x{}", i)).collect();
+        let spec = names[1..].iter().fold(Expr::Identifier(names[0].clone()), |parity, name| {
+            Expr::Xor(Box::new(parity), Box::new(Expr::Identifier(name.clone())))
+        });
+        assert!(check_equiv_callback(&spec, |_| true).is_err());
+
+        let parity = |v: &HashMap<String, bool>| v.values().filter(|&&x| x).count() % 2 == 1;
+        let report = check_equiv_callback_sampled(&spec, 50, 3, parity).unwrap();
+        assert!(report.passed());
+        assert_eq!((report.variables.len(), report.checked), (100, 50));
+        let report = check_equiv_callback_sampled(&spec, 50, 3, |v| v["x0"]).unwrap();
+        assert!(!report.passed());
+    }
+}
@@ -0,0 +1,142 @@
+use crate::source::{Expr, BinaryOp, Cardinality, Quantifier};
+use proptest::prelude::*;
+
+const BINARY_OPS: [BinaryOp; 7] = [
+    BinaryOp::And,
+    BinaryOp::Nand,
+    BinaryOp::Or,
+    BinaryOp::Nor,
+    BinaryOp::Xor,
+    BinaryOp::Implication,
+    BinaryOp::Iff,
+];
+
+/// The shape of the expressions `Expr`'s [`Arbitrary`] impl generates.
+///
+/// ```
+/// use proptest::prelude::*;
+/// use ttt::source::{Expr, ExprParams};
+///
+/// proptest!(|(expr in any_with::<Expr>(ExprParams::new().with_depth(3).with_variables(["x", "y"])))| {
+///     prop_assert_eq!(expr.clone(), expr);
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprParams {
+    depth: u32,
+    variables: Vec<String>,
+    quantifiers: bool,
+}
+
+impl Default for ExprParams {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            variables: ["a", "b", "c", "d", "e"].map(String::from).to_vec(),
+            quantifiers: true,
+        }
+    }
+}
+
+impl ExprParams {
+    /// Up to four levels of operators over the variables `a` to `e`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nest at most `depth` operators above any leaf
+    pub fn with_depth(mut self, depth: u32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Draw variable names, including those quantifiers bind, from `variables`;
+    /// with none, the leaves are all constants
+    pub fn with_variables<S: Into<String>>(mut self, variables: impl IntoIterator<Item = S>) -> Self {
+        self.variables = variables.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Leave out `∀` and `∃`, for code that only takes quantifier-free expressions
+    pub fn without_quantifiers(mut self) -> Self {
+        self.quantifiers = false;
+        self
+    }
+}
+
+/// Random expressions over every operator except library calls, shrinking
+/// towards their operands
+impl Arbitrary for Expr {
+    type Parameters = ExprParams;
+    type Strategy = BoxedStrategy<Expr>;
+
+    fn arbitrary_with(params: ExprParams) -> Self::Strategy {
+        let ExprParams { depth, variables, quantifiers } = params;
+        let constant = prop_oneof![Just(Expr::True), Just(Expr::False)];
+        let leaf = if variables.is_empty() {
+            constant.boxed()
+        } else {
+            prop_oneof![
+                6 => prop::sample::select(variables.clone()).prop_map(Expr::Identifier),
+                1 => constant,
+            ]
+            .boxed()
+        };
+        leaf.prop_recursive(depth, 1 << depth.min(8), 3, move |inner| {
+            let binary = (prop::sample::select(&BINARY_OPS[..]), inner.clone(), inner.clone())
+                .prop_map(|(op, left, right)| op.build(left, right));
+            let not = inner.clone().prop_map(|operand| Expr::Not(Box::new(operand)));
+            let ite = (inner.clone(), inner.clone(), inner.clone())
+                .prop_map(|(cond, then, otherwise)| Expr::Ite(Box::new(cond), Box::new(then), Box::new(otherwise)));
+            let cardinality = (
+                prop::sample::select(&[Cardinality::AtLeast, Cardinality::AtMost, Cardinality::Exactly][..]),
+                prop::collection::vec(inner.clone(), 1..4),
+            )
+                .prop_flat_map(|(kind, operands)| (Just(kind), 0..=operands.len(), Just(operands)))
+                .prop_map(|(kind, k, operands)| Expr::Cardinality(kind, k, operands));
+            if !quantifiers || variables.is_empty() {
+                return prop_oneof![4 => binary, 2 => not, 1 => ite, 1 => cardinality].boxed();
+            }
+            let quantified = (
+                prop::sample::select(&[Quantifier::ForAll, Quantifier::Exists][..]),
+                prop::sample::select(variables.clone()),
+                inner,
+            )
+                .prop_map(|(quantifier, var, body)| Expr::Quantified(quantifier, var, Box::new(body)));
+            prop_oneof![4 => binary, 2 => not, 1 => ite, 1 => cardinality, 1 => quantified].boxed()
+        })
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{Parser, Precedence, PrettyPrinter, SymbolStyle, parse_sexpr, to_sexpr};
+    use crate::eval::Evaluator;
+
+    proptest! {
+        #[test]
+        fn test_printed_expressions_parse_back(expr in any::<Expr>(), precedence in prop::sample::select(&[Precedence::Standard, Precedence::Flat][..])) {
+            let printed = PrettyPrinter::new().with_precedence(precedence).with_style(SymbolStyle::Ascii).print(&expr);
+            prop_assert_eq!(Parser::new(&printed).with_precedence(precedence).parse().unwrap(), expr.clone());
+            prop_assert_eq!(parse_sexpr(&to_sexpr(&expr)).unwrap(), expr);
+        }
+
+        #[test]
+        fn test_reduced_expressions_are_equivalent(expr in any_with::<Expr>(ExprParams::new().with_depth(3).without_quantifiers())) {
+            let reduction = Evaluator::reduce_expression(&expr).unwrap();
+            prop_assert!(Evaluator::check_equivalence(&expr, &reduction.reduced).unwrap().equivalent);
+        }
+    }
+
+    #[test]
+    fn test_params_shape_expressions() {
+        let mut runner = proptest::test_runner::TestRunner::deterministic();
+        let strategy = any_with::<Expr>(ExprParams::new().with_depth(0).with_variables(["x"]));
+        for _ in 0..20 {
+            let leaf = strategy.new_tree(&mut runner).unwrap().current();
+            assert!(matches!(leaf, Expr::Identifier(ref name) if name == "x") || matches!(leaf, Expr::True | Expr::False));
+        }
+    }
+}
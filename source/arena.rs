@@ -0,0 +1,217 @@
+use crate::source::{Expr, Cardinality, Quantifier, Operator, BinaryOp, ExprFolder};
+use std::collections::HashMap;
+
+/// Handle to a node in an [`ExprArena`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExprId(u32);
+
+impl ExprId {
+    /// Position of the node in the arena, counting from 0 in creation order
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// One node of an interned expression, with its children as handles
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExprNode {
+    Identifier(String),
+    True,
+    False,
+    Not(ExprId),
+    Binary(BinaryOp, ExprId, ExprId),
+    Ite(ExprId, ExprId, ExprId),
+    Cardinality(Cardinality, usize, Vec<ExprId>),
+    Quantified(Quantifier, String, ExprId),
+    Custom(Box<Operator>, Vec<ExprId>),
+}
+
+/// Expressions stored as a DAG of interned nodes.
+///
+/// Structurally equal subexpressions get the same [`ExprId`], so a term shared
+/// many times over, as in the output of a Tseitin transform or of synthesis, is
+/// stored once and copying a handle is free. A node is always created after its
+/// children, so [`iter`](Self::iter) yields them bottom-up.
+#[derive(Debug, Clone, Default)]
+pub struct ExprArena {
+    nodes: Vec<ExprNode>,
+    ids: HashMap<ExprNode, ExprId>,
+}
+
+impl ExprArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The handle for `node`, adding it unless an equal node exists.
+    ///
+    /// Panics if a child handle is not from this arena.
+    pub fn intern(&mut self, node: ExprNode) -> ExprId {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = ExprId(u32::try_from(self.nodes.len()).expect("arena holds at most u32::MAX nodes"));
+        assert!(node.children().iter().all(|child| child.index() < self.nodes.len()), "child handle from another arena");
+        self.nodes.push(node.clone());
+        self.ids.insert(node, id);
+        id
+    }
+
+    pub fn get(&self, id: ExprId) -> &ExprNode {
+        &self.nodes[id.index()]
+    }
+
+    /// Every node with its handle, children before parents
+    pub fn iter(&self) -> impl Iterator<Item = (ExprId, &ExprNode)> {
+        self.nodes.iter().enumerate().map(|(index, node)| (ExprId(index as u32), node))
+    }
+
+    pub fn identifier(&mut self, name: impl Into<String>) -> ExprId {
+        self.intern(ExprNode::Identifier(name.into()))
+    }
+
+    pub fn constant(&mut self, value: bool) -> ExprId {
+        self.intern(if value { ExprNode::True } else { ExprNode::False })
+    }
+
+    pub fn not(&mut self, inner: ExprId) -> ExprId {
+        self.intern(ExprNode::Not(inner))
+    }
+
+    pub fn binary(&mut self, op: BinaryOp, left: ExprId, right: ExprId) -> ExprId {
+        self.intern(ExprNode::Binary(op, left, right))
+    }
+
+    /// Intern every node of `expr`
+    pub fn insert(&mut self, expr: &Expr) -> ExprId {
+        Interner(self).fold_expr(expr)
+    }
+
+    /// The tree for `id`, with every shared node written out in full
+    pub fn to_expr(&self, id: ExprId) -> Expr {
+        let operands = |ids: &[ExprId]| ids.iter().map(|&id| self.to_expr(id)).collect();
+        let boxed = |id: ExprId| Box::new(self.to_expr(id));
+        match self.get(id) {
+            ExprNode::Identifier(name) => Expr::Identifier(name.clone()),
+            ExprNode::True => Expr::True,
+            ExprNode::False => Expr::False,
+            ExprNode::Not(inner) => Expr::Not(boxed(*inner)),
+            ExprNode::Binary(op, left, right) => op.build(self.to_expr(*left), self.to_expr(*right)),
+            ExprNode::Ite(cond, then, otherwise) => Expr::Ite(boxed(*cond), boxed(*then), boxed(*otherwise)),
+            ExprNode::Cardinality(kind, k, list) => Expr::Cardinality(*kind, *k, operands(list)),
+            ExprNode::Quantified(quantifier, var, body) => Expr::Quantified(*quantifier, var.clone(), boxed(*body)),
+            ExprNode::Custom(operator, list) => Expr::Custom(operator.clone(), operands(list)),
+        }
+    }
+
+    /// Number of nodes in the tree [`to_expr`](Self::to_expr) would build,
+    /// saturating at `u64::MAX`
+    pub fn tree_size(&self, id: ExprId) -> u64 {
+        let mut sizes: Vec<u64> = Vec::with_capacity(id.index() + 1);
+        for node in &self.nodes[..=id.index()] {
+            let size = node.children().iter().fold(1u64, |total, child| total.saturating_add(sizes[child.index()]));
+            sizes.push(size);
+        }
+        sizes[id.index()]
+    }
+}
+
+impl ExprNode {
+    /// Handles of the direct children, in order
+    pub fn children(&self) -> Vec<ExprId> {
+        match self {
+            ExprNode::Identifier(_) | ExprNode::True | ExprNode::False => Vec::new(),
+            ExprNode::Not(inner) | ExprNode::Quantified(_, _, inner) => vec![*inner],
+            ExprNode::Binary(_, left, right) => vec![*left, *right],
+            ExprNode::Ite(cond, then, otherwise) => vec![*cond, *then, *otherwise],
+            ExprNode::Cardinality(_, _, operands) | ExprNode::Custom(_, operands) => operands.clone(),
+        }
+    }
+}
+
+/// Interns an expression's nodes bottom-up
+struct Interner<'a>(&'a mut ExprArena);
+
+impl ExprFolder for Interner<'_> {
+    type Output = ExprId;
+
+    fn fold_identifier(&mut self, name: &str) -> ExprId {
+        self.0.identifier(name)
+    }
+
+    fn fold_constant(&mut self, value: bool) -> ExprId {
+        self.0.constant(value)
+    }
+
+    fn fold_not(&mut self, inner: ExprId) -> ExprId {
+        self.0.not(inner)
+    }
+
+    fn fold_binary(&mut self, op: BinaryOp, left: ExprId, right: ExprId) -> ExprId {
+        self.0.binary(op, left, right)
+    }
+
+    fn fold_ite(&mut self, cond: ExprId, then: ExprId, otherwise: ExprId) -> ExprId {
+        self.0.intern(ExprNode::Ite(cond, then, otherwise))
+    }
+
+    fn fold_cardinality(&mut self, kind: Cardinality, k: usize, operands: Vec<ExprId>) -> ExprId {
+        self.0.intern(ExprNode::Cardinality(kind, k, operands))
+    }
+
+    fn fold_custom(&mut self, operator: &Operator, operands: Vec<ExprId>) -> ExprId {
+        self.0.intern(ExprNode::Custom(Box::new(operator.clone()), operands))
+    }
+
+    fn fold_quantified(&mut self, quantifier: Quantifier, var: &str, body: &Expr) -> ExprId {
+        let body = self.fold_expr(body);
+        self.0.intern(ExprNode::Quantified(quantifier, var.to_string(), body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr;
+
+    #[test]
+    fn test_equal_subtrees_are_interned_once() {
+        let mut arena = ExprArena::new();
+        let root = arena.insert(&expr!((a and b) or not (a and b)));
+        // a, b, a ∧ b, ¬(a ∧ b) and the disjunction
+        assert_eq!(arena.len(), 5);
+        assert_eq!(arena.insert(&expr!(a and b)), arena.get(root).children()[0]);
+        assert_eq!(arena.tree_size(root), 8);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let expr = expr!(forall x. atleast(2; a, x, b xor c) -> (a ? x : not c));
+        let mut arena = ExprArena::new();
+        let root = arena.insert(&expr);
+        assert_eq!(arena.to_expr(root), expr);
+        assert!(arena.iter().all(|(id, node)| node.children().iter().all(|child| *child < id)));
+    }
+
+    #[test]
+    fn test_sharing_keeps_size_linear() {
+        let mut arena = ExprArena::new();
+        let mut term = arena.identifier("a");
+        for i in 0..100 {
+            let x = arena.identifier(format!("x{}", i));
+            let left = arena.binary(BinaryOp::And, term, x);
+            term = arena.binary(BinaryOp::Or, left, term);
+        }
+        assert_eq!(arena.len(), 301);
+        assert_eq!(arena.tree_size(term), u64::MAX);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::source::{Expr, BinaryOp, ExprVisitor, visit::walk_expr};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::ops::ControlFlow;
+use std::str::FromStr;
+
+/// The operators an expression may be built from, written as a comma-separated
+/// list such as `and,or,not`, `nand` or `xor,and`.
+///
+/// Identifiers and the constants are always allowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basis {
+    not: bool,
+    binary: Vec<BinaryOp>,
+}
+
+impl Default for Basis {
+    fn default() -> Self {
+        Self { not: true, binary: vec![BinaryOp::And, BinaryOp::Or] }
+    }
+}
+
+impl Basis {
+    pub fn new(not: bool, binary: Vec<BinaryOp>) -> Self {
+        Self { not, binary }
+    }
+
+    pub fn allows_not(&self) -> bool {
+        self.not
+    }
+
+    /// The binary operators, in the order given
+    pub fn binary(&self) -> &[BinaryOp] {
+        &self.binary
+    }
+
+    /// Whether `expr` uses only operators from this basis
+    pub fn permits(&self, expr: &Expr) -> bool {
+        OutsideBasis(self).visit_expr(expr).is_continue()
+    }
+}
+
+/// Stops at the first node whose operator is not in the basis
+struct OutsideBasis<'a>(&'a Basis);
+
+impl ExprVisitor for OutsideBasis<'_> {
+    type Break = ();
+
+    fn visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        let permitted = match expr {
+            Expr::Identifier(_) | Expr::True | Expr::False => true,
+            Expr::Not(_) => self.0.not,
+            Expr::Ite(..) | Expr::Cardinality(..) | Expr::Quantified(..) | Expr::Custom(..) => false,
+            _ => expr.as_binary().is_some_and(|(op, _, _)| self.0.binary.contains(&op)),
+        };
+        if !permitted {
+            return ControlFlow::Break(());
+        }
+        walk_expr(self, expr)
+    }
+}
+
+fn op_name(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::And => "and",
+        BinaryOp::Or => "or",
+        BinaryOp::Nand => "nand",
+        BinaryOp::Nor => "nor",
+        BinaryOp::Xor => "xor",
+        BinaryOp::Implication => "implies",
+        BinaryOp::Iff => "iff",
+    }
+}
+
+impl FromStr for Basis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut basis = Basis { not: false, binary: Vec::new() };
+        for name in s.split(',').map(str::trim) {
+            let op = match name.to_ascii_lowercase().as_str() {
+                "not" => {
+                    basis.not = true;
+                    continue;
+                }
+                "and" => BinaryOp::And,
+                "or" => BinaryOp::Or,
+                "nand" => BinaryOp::Nand,
+                "nor" => BinaryOp::Nor,
+                "xor" => BinaryOp::Xor,
+                "implies" => BinaryOp::Implication,
+                "iff" | "xnor" => BinaryOp::Iff,
+                _ => return Err(format!("unknown operator '{}', expected one of not, and, or, nand, nor, xor, implies, iff", name)),
+            };
+            if !basis.binary.contains(&op) {
+                basis.binary.push(op);
+            }
+        }
+        Ok(basis)
+    }
+}
+
+impl fmt::Display for Basis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&str> = self.binary.iter().map(|&op| op_name(op)).collect();
+        if self.not {
+            names.push("not");
+        }
+        write!(f, "{}", names.join(","))
+    }
+}
+
+impl Serialize for Basis {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr;
+
+    #[test]
+    fn test_basis_parses_and_displays() {
+        let basis: Basis = "not, and ,or".parse().unwrap();
+        assert_eq!(basis, Basis::default());
+        assert_eq!(basis.to_string(), "and,or,not");
+        assert_eq!("nand,NAND".parse::<Basis>().unwrap().binary(), [BinaryOp::Nand]);
+        assert!("and,ite".parse::<Basis>().is_err());
+    }
+
+    #[test]
+    fn test_basis_permits() {
+        let nand: Basis = "nand".parse().unwrap();
+        assert!(nand.permits(&expr!((a nand b) nand true)));
+        assert!(!nand.permits(&expr!(not (a nand b))));
+        assert!(Basis::default().permits(&expr!(a and not (b or c))));
+        assert!(!Basis::default().permits(&expr!(a and (b ? c : d))));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::source::Expr;
+use bincode::Options;
+use thiserror::Error;
+use miette::Diagnostic;
+
+/// Marks encoded expressions and the version of the encoding
+const MAGIC: &[u8; 4] = b"ttt\x01";
+
+#[derive(Error, Debug, Diagnostic)]
+pub enum BinaryError {
+    #[error("Not a binary ttt expression")]
+    #[diagnostic(
+        code(ttt::binary::header),
+        help("Binary input must be written by Expr::to_bytes; use text input for ttt expressions")
+    )]
+    Header,
+
+    #[error("Malformed binary expression: {0}")]
+    #[diagnostic(code(ttt::binary::decode))]
+    Decode(String),
+}
+
+/// Variable-length integers keep tags and name lengths to a byte each
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+impl Expr {
+    /// Encode the expression compactly, a few bytes per node; the encoding is
+    /// read back by [`Expr::from_bytes`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        options().serialize_into(&mut bytes, self).expect("expressions always serialize");
+        bytes
+    }
+
+    /// Decode an expression written by [`Expr::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Expr, BinaryError> {
+        let body = bytes.strip_prefix(MAGIC).ok_or(BinaryError::Header)?;
+        options().deserialize(body).map_err(|e| BinaryError::Decode(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    #[test]
+    fn test_round_trip() {
+        let expr = Parser::new("atleast(2; a, b, `c d`) -> forall x. x xor (s ? a : not b)").parse().unwrap();
+        let bytes = expr.to_bytes();
+        assert_eq!(Expr::from_bytes(&bytes).unwrap(), expr);
+        // Much smaller than the JSON form
+        assert!(bytes.len() * 3 < serde_json::to_vec(&expr).unwrap().len());
+    }
+
+    #[test]
+    fn test_rejects_other_input() {
+        assert!(matches!(Expr::from_bytes(b"a and b"), Err(BinaryError::Header)));
+        let mut bytes = Parser::new("a and b").parse().unwrap().to_bytes();
+        bytes.pop();
+        assert!(matches!(Expr::from_bytes(&bytes), Err(BinaryError::Decode(_))));
+        bytes.extend([0, 0]);
+        assert!(Expr::from_bytes(&bytes).is_err());
+    }
+}
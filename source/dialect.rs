@@ -0,0 +1,676 @@
+use crate::config::MAX_NESTING_DEPTH;
+use crate::source::{Expr, Cardinality, ParseError};
+use crate::source::sql::condition_variable;
+use std::collections::HashMap;
+use miette::SourceSpan;
+use serde::{Serialize, Deserialize};
+
+/// Input language for expressions
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    /// ttt's own grammar (default)
+    #[default]
+    Ttt,
+    /// C-family conditions (`&&`, `||`, `!`, `^`, `?:`), optionally inside an `if (...)`/`while (...)` snippet;
+    /// each other condition such as `p->len > 0` is a variable
+    C,
+    /// SMT-LIB2 scripts over Bool constants (`declare-const`, `assert`); assertions are conjoined
+    Smtlib,
+    /// One s-expression such as `(and a (not b))`, as `pretty --style sexpr` writes them
+    Sexpr,
+    /// Single-output combinational AIGER circuits in the ASCII (`aag`) format
+    Aiger,
+    /// Fault trees in a subset of the Galileo format; the expression is the top event
+    FaultTree,
+    /// Excel or Google Sheets logical formulas such as `=AND(A1, NOT(B1))`; cells are variables
+    Spreadsheet,
+    /// The boolean skeleton of an SQL predicate; each condition such as `a > 1` is a variable
+    Sql,
+    /// JSON feature-flag rules: `all`, `any`, `none` and `not` over named conditions
+    FeatureFlags,
+    /// IEC 61131-3 Structured Text assignments such as `Pump := Run AND NOT Fault;`; the last assigned value is read
+    StructuredText,
+}
+
+/// A code snippet split around the boolean condition it contains
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub prefix: String,
+    pub condition: String,
+    pub suffix: String,
+}
+
+impl Snippet {
+    /// Locate the condition in a snippet.
+    ///
+    /// The parenthesized condition of the first `if`/`while` is used when present;
+    /// otherwise the whole snippet, minus a leading `return` and trailing `;`.
+    pub fn extract(text: &str) -> Self {
+        if let Some((open, close)) = find_keyword_condition(text) {
+            return Self {
+                prefix: text[..=open].to_string(),
+                condition: text[open + 1..close].to_string(),
+                suffix: text[close..].to_string(),
+            };
+        }
+        
+        let trimmed_end = text.trim_end();
+        let body_end = trimmed_end.strip_suffix(';').map_or(trimmed_end.len(), str::len);
+        let leading = text.len() - text.trim_start().len();
+        let body_start = match text[leading..].strip_prefix("return") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => text.len() - rest.trim_start().len(),
+            _ => leading,
+        };
+        let body_start = body_start.min(body_end);
+        
+        Self {
+            prefix: text[..body_start].to_string(),
+            condition: text[body_start..body_end].to_string(),
+            suffix: text[body_end..].to_string(),
+        }
+    }
+    
+    /// Rebuild the snippet around a replacement condition
+    pub fn with_condition(&self, condition: &str) -> String {
+        format!("{}{}{}", self.prefix, condition, self.suffix)
+    }
+    
+    /// Rebuild the snippet around `expr` written in C, with the conditions
+    /// read from this snippet written out in full even where their variables
+    /// were shortened
+    pub fn rewrite(&self, expr: &Expr) -> String {
+        let conditions = parse_c_conditions(&self.condition, 0).map(|(_, conditions)| conditions).unwrap_or_default();
+        self.with_condition(&render_c(expr, 0, &conditions))
+    }
+}
+
+/// Byte offsets of the parentheses around the first `if (...)` or `while (...)` condition
+fn find_keyword_condition(text: &str) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    for keyword in ["if", "while"] {
+        let mut search_from = 0;
+        while let Some(found) = text[search_from..].find(keyword) {
+            let start = search_from + found;
+            let end = start + keyword.len();
+            search_from = end;
+            
+            let standalone_before = start == 0 || !is_ident_byte(bytes[start - 1]);
+            let open = end + text[end..].len() - text[end..].trim_start().len();
+            if !standalone_before || bytes.get(open) != Some(&b'(') {
+                continue;
+            }
+            
+            let mut depth = 0;
+            for (offset, ch) in text[open..].char_indices() {
+                match ch {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some((open, open + offset));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    None
+}
+
+fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Parse a C condition into an expression. `offset` shifts reported spans, so
+/// errors point into the enclosing snippet.
+///
+/// `&&`, `||`, `!`, `^` and `?:` become operators and `0`, `1`, `true` and
+/// `false` constants. Any other operand, such as `p->len > 0`, `(flags & MASK)`
+/// or `is_ready(dev)`, becomes a variable named after its text, as C would
+/// evaluate it: `!a == b` compares `!a` with `b`, and `a | b ^ c` is one
+/// condition because `|` binds more loosely than `^`. `^` is read as exclusive
+/// or, which is what it computes for `bool`s and comparisons.
+pub fn parse_c(input: &str, offset: usize) -> Result<Expr, ParseError> {
+    parse_c_conditions(input, offset).map(|(expr, _)| expr)
+}
+
+/// Parse a C condition, with the full text of each condition whose variable
+/// had to be shortened
+fn parse_c_conditions(input: &str, offset: usize) -> Result<(Expr, HashMap<String, String>), ParseError> {
+    let tokens = lex_c(input, offset)?;
+    let mut closes = vec![None; tokens.len()];
+    let mut open = Vec::new();
+    for (index, (token, _)) in tokens.iter().enumerate() {
+        match token {
+            CToken::LeftParen | CToken::Operator("[") => open.push(index),
+            CToken::RightParen | CToken::Operator("]") => {
+                if let Some(start) = open.pop() {
+                    closes[start] = Some(index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut parser = CParser { tokens, closes, current: 0, end: offset + input.len(), depth: 0, conditions: HashMap::new() };
+    let expr = parser.parse_conditional()?;
+    match parser.peek() {
+        None => Ok((expr, parser.conditions)),
+        Some((token, span)) => Err(ParseError::UnexpectedToken {
+            expected: "'&&', '||', '^', '?' or the end of the condition".to_string(),
+            found: token.to_string(),
+            span: span.clone().into(),
+        }),
+    }
+}
+
+/// Render an expression as a C condition, using only the parentheses C precedence requires
+pub fn to_c(expr: &Expr) -> String {
+    render_c(expr, 0, &HashMap::new())
+}
+
+// C binding strengths: ?: < || < && < a condition with an operator < ^ < unary !
+fn render_c(expr: &Expr, min_prec: u8, conditions: &HashMap<String, String>) -> String {
+    let render = |expr: &Expr, min_prec: u8| render_c(expr, min_prec, conditions);
+    let (text, prec) = match expr {
+        Expr::Identifier(name) => {
+            let text = conditions.get(name).unwrap_or(name);
+            (text.clone(), if is_c_operand(text) { 5 } else { 3 })
+        }
+        Expr::True => ("1".to_string(), 5),
+        Expr::False => ("0".to_string(), 5),
+        Expr::Not(inner) => (format!("!{}", render(inner, 5)), 5),
+        Expr::Xor(left, right) => (format!("{} ^ {}", render(left, 4), render(right, 5)), 4),
+        Expr::And(left, right) => (format!("{} && {}", render(left, 2), render(right, 3)), 2),
+        Expr::Or(left, right) => (format!("{} || {}", render(left, 1), render(right, 2)), 1),
+        Expr::Implication(left, right) => (format!("!{} || {}", render(left, 5), render(right, 2)), 1),
+        Expr::Nand(left, right) => (format!("!({} && {})", render(left, 2), render(right, 3)), 5),
+        Expr::Nor(left, right) => (format!("!({} || {})", render(left, 1), render(right, 2)), 5),
+        // a ↔ b is !(a ^ b)
+        Expr::Iff(left, right) => (format!("!({} ^ {})", render(left, 4), render(right, 5)), 5),
+        Expr::Ite(cond, then, otherwise) => {
+            (format!("{} ? {} : {}", render(cond, 1), render(then, 0), render(otherwise, 0)), 0)
+        }
+        // Sum the operands as 0/1 values; `!!` turns any truthy value into 1
+        Expr::Cardinality(kind, k, operands) => {
+            let sum: Vec<String> = operands.iter().map(|operand| format!("!!{}", render(operand, 5))).collect();
+            let comparison = match kind {
+                Cardinality::AtLeast => ">=",
+                Cardinality::AtMost => "<=",
+                Cardinality::Exactly => "==",
+            };
+            (format!("({} {} {})", sum.join(" + "), comparison, k), 5)
+        }
+        // C has no quantifiers, so write out both cases
+        Expr::Quantified(..) => return render(&expr.eliminate_quantifiers(), min_prec),
+        Expr::Custom(..) => return render(&expr.expand_custom(), min_prec),
+    };
+    if prec < min_prec { format!("({})", text) } else { text }
+}
+
+/// Whether a condition's text is a name, call, member access or subscript,
+/// which `!` and `^` bind to without parentheses
+fn is_c_operand(text: &str) -> bool {
+    let mut depth = 0usize;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth > 0 => {}
+            '-' if chars.next_if_eq(&'>').is_some() => {}
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '.' => {}
+            _ => return false,
+        }
+    }
+    !text.is_empty()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CToken {
+    Identifier(String),
+    /// A number, string or character literal
+    Literal(String),
+    And,
+    Or,
+    Not,
+    Xor,
+    Question,
+    Colon,
+    LeftParen,
+    RightParen,
+    /// Any other punctuator, such as `==`, `&`, `->` or `[`
+    Operator(&'static str),
+}
+
+impl std::fmt::Display for CToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CToken::Identifier(text) | CToken::Literal(text) => write!(f, "{}", text),
+            CToken::And => write!(f, "&&"),
+            CToken::Or => write!(f, "||"),
+            CToken::Not => write!(f, "!"),
+            CToken::Xor => write!(f, "^"),
+            CToken::Question => write!(f, "?"),
+            CToken::Colon => write!(f, ":"),
+            CToken::LeftParen => write!(f, "("),
+            CToken::RightParen => write!(f, ")"),
+            CToken::Operator(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Punctuators other than the logical ones, longest first
+const C_OPERATORS: &[&str] = &[
+    "<<=", ">>=", "->", "++", "--", "==", "!=", "<=", ">=", "<<", ">>", "+=", "-=", "*=", "/=", "%=", "&=", "|=",
+    "^=", "<", ">", "+", "-", "*", "/", "%", "&", "|", "~", "=", ".", "[", "]", ",",
+];
+
+fn lex_c(input: &str, offset: usize) -> Result<Vec<(CToken, std::ops::Range<usize>)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    
+    while let Some(ch) = input[pos..].chars().next() {
+        let rest = &input[pos..];
+        let (token, len) = match ch {
+            c if c.is_whitespace() => {
+                pos += c.len_utf8();
+                continue;
+            }
+            '(' => (CToken::LeftParen, 1),
+            ')' => (CToken::RightParen, 1),
+            '?' => (CToken::Question, 1),
+            ':' => (CToken::Colon, 1),
+            _ if rest.starts_with("&&") => (CToken::And, 2),
+            _ if rest.starts_with("||") => (CToken::Or, 2),
+            '!' if !rest.starts_with("!=") => (CToken::Not, 1),
+            '^' if !rest.starts_with("^=") => (CToken::Xor, 1),
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                // Numbers run on through suffixes, hex digits and exponents
+                let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || (ch.is_ascii_digit() && c == '.'))).unwrap_or(rest.len());
+                let text = rest[..len].to_string();
+                (if ch.is_ascii_digit() { CToken::Literal(text) } else { CToken::Identifier(text) }, len)
+            }
+            '"' | '\'' => {
+                let mut escaped = false;
+                let close = rest.char_indices().skip(1).find(|&(_, c)| {
+                    let closes = c == ch && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    closes
+                });
+                let Some((close, _)) = close else {
+                    return Err(ParseError::UnexpectedEof { span: SourceSpan::from(offset + input.len()..offset + input.len()) });
+                };
+                (CToken::Literal(rest[..=close].to_string()), close + 1)
+            }
+            other => match C_OPERATORS.iter().find(|operator| rest.starts_with(**operator)) {
+                Some(operator) => (CToken::Operator(operator), operator.len()),
+                None => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "a C condition".to_string(),
+                        found: other.to_string(),
+                        span: SourceSpan::from(offset + pos..offset + pos + other.len_utf8()),
+                    });
+                }
+            },
+        };
+        tokens.push((token, offset + pos..offset + pos + len));
+        pos += len;
+    }
+    
+    Ok(tokens)
+}
+
+struct CParser {
+    tokens: Vec<(CToken, std::ops::Range<usize>)>,
+    /// For each opening bracket, the index just past the one that closes it
+    closes: Vec<Option<usize>>,
+    current: usize,
+    end: usize,
+    /// Levels open around the operand being read
+    depth: usize,
+    /// The full text of each condition whose variable is shortened
+    conditions: HashMap<String, String>,
+}
+
+impl CParser {
+    fn peek(&self) -> Option<&(CToken, std::ops::Range<usize>)> {
+        self.tokens.get(self.current)
+    }
+    
+    fn eat(&mut self, token: &CToken) -> bool {
+        if self.peek().is_some_and(|(t, _)| t == token) {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+    
+    fn parse_conditional(&mut self) -> Result<Expr, ParseError> {
+        let cond = self.parse_or()?;
+        if !self.eat(&CToken::Question) {
+            return Ok(cond);
+        }
+        self.deeper()?;
+        let then = self.parse_conditional()?;
+        if !self.eat(&CToken::Colon) {
+            self.depth -= 1;
+            return Err(self.expected("':'"));
+        }
+        let otherwise = self.parse_conditional()?;
+        self.depth -= 1;
+        Ok(Expr::Ite(Box::new(cond), Box::new(then), Box::new(otherwise)))
+    }
+    
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut left = self.parse_and()?;
+        while self.eat(&CToken::Or) {
+            self.deeper()?;
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+    
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut left = self.parse_xor()?;
+        while self.eat(&CToken::And) {
+            self.deeper()?;
+            let right = self.parse_xor()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+    
+    fn parse_xor(&mut self) -> Result<Expr, ParseError> {
+        let end = self.condition_end(self.current, false);
+        // `|` binds more loosely than `^`, so neither splits the condition
+        if self.outside_brackets(self.current).take_while(|&index| index < end).any(|index| self.tokens[index].0 == CToken::Operator("|")) {
+            return self.parse_unary(end);
+        }
+        let depth = self.depth;
+        let mut left = self.parse_unary(self.condition_end(self.current, true))?;
+        while self.eat(&CToken::Xor) {
+            self.deeper()?;
+            let right = self.parse_unary(self.condition_end(self.current, true))?;
+            left = Expr::Xor(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+    
+    /// Indices from `start` on, skipping what is inside brackets; an
+    /// unclosed bracket holds the rest of the tokens
+    fn outside_brackets(&self, start: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(start), |&index| Some(self.matching(index).unwrap_or(index + 1)))
+            .take_while(|&index| index < self.tokens.len())
+    }
+    
+    /// Index of the first token after `start` outside brackets that ends an
+    /// operand: `&&`, `||`, `?`, `:`, `,`, an unmatched `)`, and `^` if `at_xor`
+    fn condition_end(&self, start: usize, at_xor: bool) -> usize {
+        self.outside_brackets(start)
+            .find(|&index| {
+                let token = &self.tokens[index].0;
+                matches!(token, CToken::And | CToken::Or | CToken::Question | CToken::Colon | CToken::RightParen | CToken::Operator(","))
+                    || (at_xor && *token == CToken::Xor)
+            })
+            .unwrap_or(self.tokens.len())
+    }
+    
+    /// Index just past the `)` or `]` matching the bracket at `open`, or the
+    /// end of the tokens if it is never closed
+    fn matching(&self, open: usize) -> Option<usize> {
+        match self.tokens.get(open) {
+            Some((CToken::LeftParen | CToken::Operator("["), _)) => Some(self.closes[open].unwrap_or(self.tokens.len())),
+            _ => None,
+        }
+    }
+    
+    /// Whether `start..end` is one name, literal, call, member access or
+    /// subscript, which `!` applies to as a whole
+    fn is_operand(&self, start: usize, end: usize) -> bool {
+        if !matches!(self.tokens.get(start), Some((CToken::Identifier(_) | CToken::Literal(_), _))) {
+            return false;
+        }
+        let mut index = start + 1;
+        while index < end {
+            index = match &self.tokens[index].0 {
+                CToken::Operator("." | "->") if matches!(self.tokens.get(index + 1), Some((CToken::Identifier(_), _))) => index + 2,
+                CToken::Operator("++" | "--") => index + 1,
+                CToken::LeftParen | CToken::Operator("[") => match self.matching(index) {
+                    Some(close) => close,
+                    None => return false,
+                },
+                _ => return false,
+            };
+        }
+        index == end
+    }
+    
+    /// Go one level deeper, for the operand after a `!`, `(` or chained
+    /// operator just read, refusing to go deeper than [`MAX_NESTING_DEPTH`]
+    fn deeper(&mut self) -> Result<(), ParseError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            let (_, span) = &self.tokens[self.current - 1];
+            return Err(ParseError::TooDeep { limit: MAX_NESTING_DEPTH, span: span.clone().into() });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+    
+    /// The error for a missing `expected` at the current token
+    fn expected(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some((token, span)) => ParseError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: token.to_string(),
+                span: span.clone().into(),
+            },
+            None => ParseError::UnexpectedEof {
+                span: SourceSpan::from(self.end..self.end),
+            },
+        }
+    }
+    
+    /// Read the operand of `^`, `&&` or `||` that ends at `end`
+    fn parse_unary(&mut self, end: usize) -> Result<Expr, ParseError> {
+        if self.current == end {
+            return Err(self.expected("a condition"));
+        }
+        let inner = (self.current..end).find(|&index| self.tokens[index].0 != CToken::Not).unwrap_or(end);
+        if inner == end {
+            self.current = end;
+            return Err(self.expected("a condition"));
+        }
+        let group = self.tokens.get(inner).is_some_and(|(token, _)| *token == CToken::LeftParen)
+            && self.matching(inner) == Some(end);
+        if !group && !self.is_operand(inner, end) {
+            return Ok(self.condition(end));
+        }
+        self.parse_negated(end)
+    }
+    
+    /// Read the `!`s in front of a parenthesized condition or an operand
+    fn parse_negated(&mut self, end: usize) -> Result<Expr, ParseError> {
+        if self.eat(&CToken::Not) {
+            self.deeper()?;
+            let operand = self.parse_negated(end)?;
+            self.depth -= 1;
+            return Ok(Expr::Not(Box::new(operand)));
+        }
+        if self.eat(&CToken::LeftParen) {
+            self.deeper()?;
+            let expr = self.parse_conditional()?;
+            self.depth -= 1;
+            if !self.eat(&CToken::RightParen) {
+                return Err(self.expected("')'"));
+            }
+            return Ok(expr);
+        }
+        if end == self.current + 1 {
+            let constant = match &self.tokens[self.current].0 {
+                CToken::Identifier(name) if name == "true" => Some(Expr::True),
+                CToken::Identifier(name) if name == "false" => Some(Expr::False),
+                CToken::Literal(number) if number == "0" => Some(Expr::False),
+                CToken::Literal(number) if number == "1" => Some(Expr::True),
+                _ => None,
+            };
+            if let Some(constant) = constant {
+                self.current += 1;
+                return Ok(constant);
+            }
+        }
+        Ok(self.condition(end))
+    }
+    
+    /// The tokens up to `end` as one condition, a variable named after its text
+    fn condition(&mut self, end: usize) -> Expr {
+        let text = render_condition(&self.tokens[self.current..end]);
+        self.current = end;
+        let name = condition_variable(text.clone());
+        if name != text {
+            self.conditions.insert(name.clone(), text);
+        }
+        Expr::Identifier(name)
+    }
+}
+
+/// A condition's tokens written out the same way whatever the spacing, so
+/// conditions written differently share a variable
+fn render_condition(tokens: &[(CToken, std::ops::Range<usize>)]) -> String {
+    let mut text = String::new();
+    // Whether the next token starts an operand, as after `(` or `==`
+    let mut operand_next = true;
+    let mut after_prefix = false;
+    for (token, _) in tokens {
+        let prefix = operand_next && matches!(token, CToken::Not | CToken::Operator("~" | "-" | "+" | "*" | "&" | "++" | "--"));
+        let joined = text.is_empty()
+            || after_prefix
+            || text.ends_with(['(', '[', '.'])
+            || text.ends_with("->")
+            || matches!(token, CToken::RightParen | CToken::Operator("]" | "," | "." | "->" | "["))
+            || (!operand_next && matches!(token, CToken::LeftParen | CToken::Operator("++" | "--")));
+        if !joined {
+            text.push(' ');
+        }
+        text.push_str(&token.to_string());
+        after_prefix = prefix;
+        operand_next = match token {
+            CToken::Identifier(_) | CToken::Literal(_) | CToken::RightParen | CToken::Operator("]") => false,
+            CToken::Operator("++" | "--") => operand_next,
+            _ => true,
+        };
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    #[test]
+    fn test_extract_if_condition() {
+        let snippet = Snippet::extract("if (isReady && (x || !x)) {");
+        assert_eq!(snippet.prefix, "if (");
+        assert_eq!(snippet.condition, "isReady && (x || !x)");
+        assert_eq!(snippet.suffix, ") {");
+        assert_eq!(snippet.with_condition("isReady"), "if (isReady) {");
+    }
+
+    #[test]
+    fn test_extract_return_and_bare() {
+        let snippet = Snippet::extract("return a && b;");
+        assert_eq!(snippet.condition, "a && b");
+        assert_eq!(snippet.with_condition("a"), "return a;");
+        
+        let snippet = Snippet::extract("a || b");
+        assert_eq!(snippet.prefix, "");
+        assert_eq!(snippet.condition, "a || b");
+    }
+
+    #[test]
+    fn test_parse_c_precedence_and_casing() {
+        let expr = parse_c("!Ready || Flag_A && flagB2", 0).unwrap();
+        assert_eq!(expr.to_string(), "(¬Ready ∨ (Flag_A ∧ `flagB2`))");
+    }
+
+    #[test]
+    fn test_parse_c_errors_point_into_snippet() {
+        let err = parse_c("a @ b", 4).unwrap_err();
+        match err {
+            ParseError::UnexpectedToken { span, .. } => assert_eq!(span.offset(), 6),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(parse_c("(a && b", 0).is_err());
+        assert!(parse_c("a && ", 0).is_err());
+        assert!(parse_c("a ? b", 0).is_err());
+        assert!(matches!(parse_c("!! && a", 0), Err(ParseError::UnexpectedToken { found, .. }) if found == "&&"));
+    }
+
+    #[test]
+    fn test_parse_c_conditions_are_variables() {
+        let variables = |input: &str| -> Vec<String> {
+            crate::eval::Variables::from_expr(&parse_c(input, 0).unwrap()).unwrap().to_vec()
+        };
+        assert_eq!(
+            variables("p != NULL && p->len > 0 && (flags & FLAG_DIRTY)"),
+            ["flags & FLAG_DIRTY", "p != NULL", "p->len > 0"]
+        );
+        assert_eq!(variables("is_ready(dev, 2) || !cfg.enabled[i] || x==y"), ["cfg.enabled[i]", "is_ready(dev, 2)", "x == y"]);
+        // Operators bind as C binds them
+        assert_eq!(parse_c("!a == b", 0).unwrap(), Expr::Identifier("!a == b".into()));
+        assert_eq!(parse_c("a | b ^ c", 0).unwrap(), Expr::Identifier("a | b ^ c".into()));
+        assert_eq!(parse_c("~mask", 0).unwrap(), Expr::Identifier("~mask".into()));
+        assert_eq!(parse_c("(a + b) * 2 > limit", 0).unwrap(), Expr::Identifier("(a + b) * 2 > limit".into()));
+        assert_eq!(parse_c("(int)x", 0).unwrap(), Expr::Identifier("(int) x".into()));
+        assert_eq!(parse_c("a == 1 ^ b < 2", 0).unwrap().to_string(), "(`a == 1` ⊕ `b < 2`)");
+        assert_eq!(parse_c("s == \"a && b\" || c", 0).unwrap().to_string(), "(`s == \"a && b\"` ∨ c)");
+        assert_eq!(parse_c("1 && !0", 0).unwrap(), Expr::And(Box::new(Expr::True), Box::new(Expr::Not(Box::new(Expr::False)))));
+        assert_eq!(parse_c("ready ? a > 0 : b", 0).unwrap().to_string(), "(ready ? `a > 0` : b)");
+    }
+
+    #[test]
+    fn test_rewrite_keeps_conditions_as_written() {
+        let snippet = Snippet::extract("if (p != NULL && p->len > 0 || p != NULL && !(p->len > 0)) {");
+        let expr = parse_c(&snippet.condition, 0).unwrap();
+        let reduced = crate::eval::Evaluator::reduce_expression(&expr).unwrap().reduced;
+        assert_eq!(snippet.rewrite(&reduced), "if (p != NULL) {");
+        // Negated conditions with operators keep their parentheses
+        assert_eq!(to_c(&parse_c("!(x & MASK) ^ !(a == b)", 0).unwrap()), "!(x & MASK) ^ !(a == b)");
+        // A condition too long for a name is written back in full
+        let long = "config->retry_policy.max_attempts_before_giving_up > attempts_so_far";
+        let snippet = Snippet::extract(&format!("while ({} && {}) {{", long, long));
+        let expr = parse_c(&snippet.condition, 0).unwrap();
+        let reduced = crate::eval::Evaluator::reduce_expression(&expr).unwrap().reduced;
+        assert_eq!(snippet.rewrite(&reduced), format!("while ({}) {{", long));
+    }
+
+    #[test]
+    fn test_parse_c_refuses_deep_conditions() {
+        let deep = format!("{}a{}", "!(".repeat(100_000), ")".repeat(100_000));
+        assert!(matches!(parse_c(&deep, 0), Err(ParseError::TooDeep { .. })));
+        let chain = format!("a{}", " && b".repeat(1000));
+        assert!(matches!(parse_c(&chain, 0), Err(ParseError::TooDeep { .. })));
+        assert!(parse_c(&format!("a{}", " && b".repeat(MAX_NESTING_DEPTH)), 0).is_ok());
+    }
+
+    #[test]
+    fn test_to_c_minimal_parentheses() {
+        let expr = parse_c("(a || b) && !(c && d) || e ^ f", 0).unwrap();
+        assert_eq!(to_c(&expr), "(a || b) && !(c && d) || e ^ f");
+        
+        let ite = Parser::new("(s or t ? a : b) and c").parse().unwrap();
+        assert_eq!(to_c(&ite), "(s || t ? a : b) && c");
+        
+        let vote = Parser::new("atleast(2; a, b or c, d)").parse().unwrap();
+        assert_eq!(to_c(&vote), "(!!a + !!(b || c) + !!d >= 2)");
+    }
+}
@@ -0,0 +1,317 @@
+use crate::config::MAX_NESTING_DEPTH;
+use crate::source::{Expr, Cardinality};
+use crate::source::parser::fold_chain;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use thiserror::Error;
+use miette::{Diagnostic, SourceSpan};
+
+/// Errors in a fault-tree description
+#[derive(Error, Debug, Diagnostic)]
+pub enum FaultTreeError {
+    #[error("Malformed fault tree: {message}")]
+    #[diagnostic(code(ttt::fault_tree::syntax))]
+    Syntax {
+        message: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[error("Unknown gate type '{gate}'")]
+    #[diagnostic(
+        code(ttt::fault_tree::unknown_gate),
+        help("Supported gates are `and`, `or` and k-of-n voting gates such as `2of3`")
+    )]
+    UnknownGate {
+        gate: String,
+        #[label("unknown gate")]
+        span: SourceSpan,
+    },
+
+    #[error("Voting gate {gate} has {found} input(s)")]
+    #[diagnostic(code(ttt::fault_tree::voting_inputs), help("A `KofN` gate needs exactly N inputs and 1 ≤ K ≤ N"))]
+    VotingInputs {
+        gate: String,
+        found: usize,
+        #[label("defined here")]
+        span: SourceSpan,
+    },
+
+    #[error("'{name}' is defined more than once")]
+    #[diagnostic(code(ttt::fault_tree::duplicate_definition))]
+    DuplicateDefinition {
+        name: String,
+        #[label("defined again here")]
+        span: SourceSpan,
+    },
+
+    #[error("'{name}' is not a valid ttt variable name")]
+    #[diagnostic(
+        code(ttt::fault_tree::invalid_name),
+        help("Basic event names may only contain letters, digits, and underscores")
+    )]
+    InvalidName {
+        name: String,
+        #[label("used here")]
+        span: SourceSpan,
+    },
+
+    #[error("Invalid probability '{value}' for '{name}'")]
+    #[diagnostic(code(ttt::fault_tree::invalid_probability), help("Probabilities must be numbers between 0 and 1"))]
+    InvalidProbability {
+        name: String,
+        value: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[error("Gate '{name}' depends on itself")]
+    #[diagnostic(code(ttt::fault_tree::cycle))]
+    Cycle {
+        name: String,
+        #[label("defined here")]
+        span: SourceSpan,
+    },
+
+    #[error("The fault tree has no top event")]
+    #[diagnostic(code(ttt::fault_tree::no_top_event), help("Add a `toplevel NAME;` statement"))]
+    NoTopEvent,
+}
+
+/// A fault tree compiled to the expression for its top event
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultTree {
+    pub top: String,
+    /// True exactly when the top event occurs; variables are the basic events
+    pub expr: Expr,
+    /// Failure probabilities given for basic events
+    pub probabilities: BTreeMap<String, f64>,
+}
+
+/// A name or attribute with the byte range it was read from
+type Word = (String, Range<usize>);
+
+#[derive(Debug, Clone)]
+enum GateKind {
+    And,
+    Or,
+    Voting(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Gate {
+    kind: GateKind,
+    inputs: Vec<Word>,
+    span: Range<usize>,
+}
+
+/// Parse a fault tree in a subset of the Galileo format.
+///
+/// Statements end with `;` and names may be quoted:
+///
+/// ```text
+/// toplevel System;
+/// System or Power Cooling;
+/// Cooling and PumpA PumpB;
+/// Power 2of3 GenA GenB GenC;
+/// PumpA prob=0.001;
+/// ```
+///
+/// Names that are not defined as gates are basic events. `//` starts a comment.
+pub fn parse_fault_tree(input: &str) -> Result<FaultTree, FaultTreeError> {
+    let mut top = None;
+    let mut gates: HashMap<String, Gate> = HashMap::new();
+    let mut probabilities = BTreeMap::new();
+
+    for statement in statements(input)? {
+        let (name, name_span) = &statement[0];
+        let Some((second, second_span)) = statement.get(1) else {
+            return Err(syntax("expected a gate type or an attribute after the name", name_span));
+        };
+
+        if name == "toplevel" {
+            if statement.len() != 2 {
+                return Err(syntax("`toplevel` takes a single event name", second_span));
+            }
+            top = Some(second.clone());
+            continue;
+        }
+
+        if let Some(value) = second.strip_prefix("prob=") {
+            if statement.len() != 2 {
+                return Err(syntax("expected a single `prob=` attribute", &statement[2].1));
+            }
+            let probability = value.parse::<f64>().ok().filter(|p| (0.0..=1.0).contains(p)).ok_or_else(|| {
+                FaultTreeError::InvalidProbability { name: name.clone(), value: value.to_string(), span: span(second_span) }
+            })?;
+            if probabilities.insert(name.clone(), probability).is_some() {
+                return Err(FaultTreeError::DuplicateDefinition { name: name.clone(), span: span(name_span) });
+            }
+            continue;
+        }
+
+        let kind = match second.to_ascii_lowercase().as_str() {
+            "and" => GateKind::And,
+            "or" => GateKind::Or,
+            voting => match voting.split_once("of").map(|(k, n)| (k.parse::<usize>(), n.parse::<usize>())) {
+                Some((Ok(k), Ok(n))) => {
+                    let found = statement.len() - 2;
+                    if found != n || k == 0 || k > n {
+                        return Err(FaultTreeError::VotingInputs { gate: second.clone(), found, span: span(name_span) });
+                    }
+                    GateKind::Voting(k)
+                }
+                _ => return Err(FaultTreeError::UnknownGate { gate: second.clone(), span: span(second_span) }),
+            },
+        };
+        if statement.len() < 3 {
+            return Err(syntax("a gate needs at least one input", second_span));
+        }
+        let gate = Gate { kind, inputs: statement[2..].to_vec(), span: name_span.clone() };
+        if gates.insert(name.clone(), gate).is_some() {
+            return Err(FaultTreeError::DuplicateDefinition { name: name.clone(), span: span(name_span) });
+        }
+    }
+
+    let top = top.ok_or(FaultTreeError::NoTopEvent)?;
+    if let Some(name) = probabilities.keys().find(|name| gates.contains_key(*name)) {
+        return Err(FaultTreeError::DuplicateDefinition { name: name.clone(), span: span(&gates[name].span) });
+    }
+    let expr = compile(&top, &(0..0), &gates, &mut Vec::new())?;
+    Ok(FaultTree { top, expr, probabilities })
+}
+
+/// Build the expression for `name`, expanding gates and checking for cycles
+fn compile(name: &str, used_at: &Range<usize>, gates: &HashMap<String, Gate>, visiting: &mut Vec<String>) -> Result<Expr, FaultTreeError> {
+    let Some(gate) = gates.get(name) else {
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(FaultTreeError::InvalidName { name: name.to_string(), span: span(used_at) });
+        }
+        return Ok(Expr::Identifier(name.to_string()));
+    };
+    if visiting.iter().any(|v| v == name) {
+        return Err(FaultTreeError::Cycle { name: name.to_string(), span: span(&gate.span) });
+    }
+    let too_deep = || syntax(&format!("nested more than {} levels deep", MAX_NESTING_DEPTH), &gate.span);
+    if visiting.len() >= MAX_NESTING_DEPTH {
+        return Err(too_deep());
+    }
+
+    visiting.push(name.to_string());
+    let inputs = gate.inputs
+        .iter()
+        .map(|(input, input_span)| compile(input, input_span, gates, visiting))
+        .collect::<Result<Vec<_>, _>>()?;
+    visiting.pop();
+
+    let make = match gate.kind {
+        GateKind::And => Expr::And,
+        GateKind::Or => Expr::Or,
+        GateKind::Voting(k) => return bounded(Expr::Cardinality(Cardinality::AtLeast, k, inputs)).ok_or_else(too_deep),
+    };
+    let expr = fold_chain(inputs, make, MAX_NESTING_DEPTH).map_err(|_| too_deep())?;
+    Ok(expr.expect("gates have at least one input"))
+}
+
+/// `expr` unless it nests more than [`MAX_NESTING_DEPTH`] levels deep, as
+/// gates feeding gates can
+fn bounded(expr: Expr) -> Option<Expr> {
+    (expr.depth() <= MAX_NESTING_DEPTH).then_some(expr)
+}
+
+/// Split the input into `;`-terminated statements of words with their byte ranges
+fn statements(input: &str) -> Result<Vec<Vec<Word>>, FaultTreeError> {
+    let mut statements = Vec::new();
+    let mut current: Vec<Word> = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        match ch {
+            c if c.is_whitespace() => {}
+            '/' if chars.peek().map(|&(_, c)| c) == Some('/') => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            ';' => {
+                if !current.is_empty() {
+                    statements.push(std::mem::take(&mut current));
+                }
+            }
+            '"' => {
+                let mut word = String::new();
+                let end = loop {
+                    match chars.next() {
+                        Some((end, '"')) => break end + 1,
+                        Some((_, c)) => word.push(c),
+                        None => return Err(syntax("unterminated quoted name", &(start..input.len()))),
+                    }
+                };
+                current.push((word, start..end));
+            }
+            c => {
+                // A bare word, which may contain a quoted attribute value
+                let mut word = c.to_string();
+                let mut end = start + c.len_utf8();
+                while let Some((pos, c)) = chars.next_if(|&(_, c)| !c.is_whitespace() && c != ';') {
+                    if c != '"' {
+                        word.push(c);
+                    }
+                    end = pos + c.len_utf8();
+                }
+                current.push((word, start..end));
+            }
+        }
+    }
+    if let Some((_, last)) = current.last() {
+        return Err(syntax("expected ';' at the end of the statement", last));
+    }
+    Ok(statements)
+}
+
+fn span(range: &Range<usize>) -> SourceSpan {
+    (range.start, range.len()).into()
+}
+
+fn syntax(message: &str, range: &Range<usize>) -> FaultTreeError {
+    FaultTreeError::Syntax { message: message.to_string(), span: span(range) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::equivalence::check_equivalence;
+    use crate::source::Parser;
+
+    const TREE: &str = r#"
+        toplevel "System";
+        "System" or Power Cooling;   // either subsystem failing brings the system down
+        Cooling and pump_a pump_b;
+        Power 2of3 gen_a gen_b gen_c;
+        pump_a prob=0.001;
+        gen_a prob=1e-4;
+    "#;
+
+    #[test]
+    fn test_compiles_gates() {
+        let tree = parse_fault_tree(TREE).unwrap();
+        assert_eq!(tree.top, "System");
+        let expected = Parser::new(
+            "(pump_a and pump_b) or (gen_a and gen_b) or (gen_a and gen_c) or (gen_b and gen_c)",
+        ).parse().unwrap();
+        assert!(check_equivalence(&tree.expr, &expected).unwrap().equivalent);
+        assert_eq!(tree.probabilities["pump_a"], 0.001);
+        assert_eq!(tree.probabilities.len(), 2);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse_fault_tree("a and b c;"), Err(FaultTreeError::NoTopEvent)));
+        assert!(matches!(parse_fault_tree("toplevel a; a xor b c;"), Err(FaultTreeError::UnknownGate { .. })));
+        assert!(matches!(parse_fault_tree("toplevel a; a 2of3 b c;"), Err(FaultTreeError::VotingInputs { .. })));
+        assert!(matches!(parse_fault_tree("toplevel a; a or b; b and a c;"), Err(FaultTreeError::Cycle { .. })));
+        assert!(matches!(parse_fault_tree("toplevel a; a or b; b prob=2;"), Err(FaultTreeError::InvalidProbability { .. })));
+        assert!(matches!(parse_fault_tree("toplevel a; a or b-c;"), Err(FaultTreeError::InvalidName { .. })));
+        assert!(matches!(parse_fault_tree("toplevel a; a or b"), Err(FaultTreeError::Syntax { .. })));
+        let gates: String = (0..1000).map(|i| format!("g{} or g{} x;", i, i + 1)).collect();
+        assert!(matches!(parse_fault_tree(&format!("toplevel g0; {}", gates)), Err(FaultTreeError::Syntax { .. })));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::config::MAX_NESTING_DEPTH;
+use crate::source::Expr;
+use crate::source::parser::fold_chain;
+use crate::source::sql::condition_variable;
+use serde_json::{Map, Value};
+use thiserror::Error;
+use miette::{Diagnostic, SourceSpan};
+
+/// Errors in a JSON feature-flag rule
+#[derive(Error, Debug, Diagnostic)]
+pub enum FeatureFlagError {
+    #[error("Malformed JSON: {message}")]
+    #[diagnostic(code(ttt::feature_flags::json))]
+    Json {
+        message: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[error("Invalid rule at {path}: {message}")]
+    #[diagnostic(
+        code(ttt::feature_flags::invalid_rule),
+        help("A rule is a condition name, true or false, or an object with one of `all`, `any`, `none`, `not`, `condition` or `fact`")
+    )]
+    InvalidRule { path: String, message: String },
+}
+
+/// The keys that make an object a rule, one per object
+const RULE_KEYS: [&str; 6] = ["all", "any", "none", "not", "condition", "fact"];
+
+/// Parse a feature-flag targeting rule written as JSON.
+///
+/// `{"all": [...]}`, `{"any": [...]}` and `{"none": [...]}` hold when every,
+/// some or none of their rules do, and `{"not": rule}` negates one rule. A
+/// string, or `{"condition": "name"}` as json-rules-engine writes shared
+/// conditions, is a named condition and becomes a variable of that name.
+/// Inline conditions such as `{"fact": "country", "operator": "in", "value":
+/// ["US", "CA"]}` become a variable named after the fact, path, operator and
+/// value, so the same test written twice is one variable. `true` and `false`
+/// are constants. A whole json-rules-engine rule is accepted too: its
+/// `conditions` are read and the rest ignored.
+///
+/// ```
+/// use ttt::source::parse_feature_flags;
+///
+/// let rule = r#"{"all": ["beta", {"any": ["us", "ca"]}, {"none": ["internal"]}]}"#;
+/// assert_eq!(parse_feature_flags(rule).unwrap().to_string(), "((beta ∧ (us ∨ ca)) ∧ ¬internal)");
+/// ```
+pub fn parse_feature_flags(input: &str) -> Result<Expr, FeatureFlagError> {
+    // serde_json refuses documents nested more than 128 levels deep, so the
+    // rules in one can't nest deeper than that
+    let document: Value = serde_json::from_str(input).map_err(|e| json_error(input, &e))?;
+    let (value, path) = match &document {
+        Value::Object(object) if object.contains_key("conditions") && !RULE_KEYS.iter().any(|key| object.contains_key(*key)) => {
+            (&object["conditions"], "$.conditions")
+        }
+        _ => (&document, "$"),
+    };
+    let expr = rule(value, path)?;
+    if expr.depth() > MAX_NESTING_DEPTH {
+        return Err(too_deep(path));
+    }
+    Ok(expr)
+}
+
+/// The expression for the rule at `path`
+fn rule(value: &Value, path: &str) -> Result<Expr, FeatureFlagError> {
+    match value {
+        Value::Bool(true) => Ok(Expr::True),
+        Value::Bool(false) => Ok(Expr::False),
+        Value::String(name) => condition(name, path),
+        Value::Object(object) => object_rule(object, path),
+        _ => Err(invalid(path, format!("expected a rule, found {}", kind(value)))),
+    }
+}
+
+fn object_rule(object: &Map<String, Value>, path: &str) -> Result<Expr, FeatureFlagError> {
+    let keys: Vec<&str> = RULE_KEYS.into_iter().filter(|key| object.contains_key(*key)).collect();
+    let key = match keys.as_slice() {
+        [key] => *key,
+        [] => return Err(invalid(path, "the object is not a rule".to_string())),
+        _ => return Err(invalid(path, format!("expected one of {}, found several", keys.join(", ")))),
+    };
+    let value = &object[key];
+    let path = format!("{}.{}", path, key);
+
+    match key {
+        "not" => Ok(Expr::Not(Box::new(rule(value, &path)?))),
+        "condition" => match value {
+            Value::String(name) => condition(name, &path),
+            _ => Err(invalid(&path, format!("expected a condition name, found {}", kind(value)))),
+        },
+        "fact" => fact(object, &path),
+        _ => {
+            let Value::Array(items) = value else {
+                return Err(invalid(&path, format!("expected a list of rules, found {}", kind(value))));
+            };
+            let operands = items
+                .iter()
+                .enumerate()
+                .map(|(idx, item)| rule(item, &format!("{}[{}]", path, idx)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let combine = |make| fold_chain(operands, make, MAX_NESTING_DEPTH).map_err(|_| too_deep(&path));
+            Ok(match key {
+                "all" => combine(Expr::And)?.unwrap_or(Expr::True),
+                "any" => combine(Expr::Or)?.unwrap_or(Expr::False),
+                _ => combine(Expr::Or)?.map_or(Expr::True, |any| Expr::Not(Box::new(any))),
+            })
+        }
+    }
+}
+
+/// `{"fact": ..., "operator": ..., "value": ...}`, with an optional `path` into the fact
+fn fact(object: &Map<String, Value>, path: &str) -> Result<Expr, FeatureFlagError> {
+    let text = |key: &str| match object.get(key) {
+        Some(Value::String(text)) => Ok(Some(text.as_str())),
+        None => Ok(None),
+        Some(other) => Err(invalid(path, format!("expected `{}` to be a string, found {}", key, kind(other)))),
+    };
+    let fact = text("fact")?.unwrap_or_default();
+    let operator = text("operator")?.ok_or_else(|| invalid(path, "the condition has no `operator`".to_string()))?;
+    let value = object.get("value").ok_or_else(|| invalid(path, "the condition has no `value`".to_string()))?;
+    let name = format!("{}{} {} {}", fact, text("path")?.unwrap_or_default(), operator, value);
+    Ok(Expr::Identifier(condition_variable(name)))
+}
+
+fn condition(name: &str, path: &str) -> Result<Expr, FeatureFlagError> {
+    if name.trim().is_empty() {
+        return Err(invalid(path, "condition names can't be empty".to_string()));
+    }
+    Ok(Expr::Identifier(name.to_string()))
+}
+
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn invalid(path: &str, message: String) -> FeatureFlagError {
+    FeatureFlagError::InvalidRule { path: path.to_string(), message }
+}
+
+/// The rule at `path` nests more than [`MAX_NESTING_DEPTH`] levels deep,
+/// counting each operand of a list as a level
+fn too_deep(path: &str) -> FeatureFlagError {
+    invalid(path, format!("nested more than {} levels deep", MAX_NESTING_DEPTH))
+}
+
+/// Point at the line and column serde_json reports
+fn json_error(input: &str, error: &serde_json::Error) -> FeatureFlagError {
+    let line_start: usize = input.split_inclusive('\n').take(error.line().saturating_sub(1)).map(str::len).sum();
+    let offset = (line_start + error.column().saturating_sub(1)).min(input.len());
+    let message = error.to_string();
+    // serde_json appends the position, which the label shows
+    let message = message.split(" at line ").next().unwrap_or(&message).to_string();
+    FeatureFlagError::Json { message, span: (offset, 0).into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn equivalent(json: &str, ttt: &str) {
+        let parsed = parse_feature_flags(json).unwrap();
+        let expected = Parser::new(ttt).parse().unwrap();
+        assert_eq!(parsed, expected, "{}", json);
+    }
+
+    #[test]
+    fn test_nested_rules() {
+        equivalent(r#"{"all": ["beta", {"any": ["us", "ca"]}, {"none": ["internal", "banned"]}]}"#, "beta and (us or ca) and not (internal or banned)");
+        equivalent(r#"{"any": [{"not": {"condition": "paying"}}, true]}"#, "not paying or true");
+        equivalent(r#"{"all": []}"#, "true");
+        equivalent(r#"{"any": []}"#, "false");
+        equivalent(r#"{"none": []}"#, "true");
+        equivalent(r#""opted in""#, "`opted in`");
+    }
+
+    #[test]
+    fn test_rules_engine_documents() {
+        let rule = r#"{
+            "conditions": {"any": [
+                {"fact": "account", "path": "$.country", "operator": "in", "value": ["US", "CA"]},
+                {"all": [{"condition": "employee"}, {"fact": "account", "path": "$.country", "operator": "in", "value": ["US","CA"]}]}
+            ]},
+            "event": {"type": "enable-checkout-v2"}
+        }"#;
+        let country = "`account$.country in [\"US\",\"CA\"]`";
+        equivalent(rule, &format!("{} or (employee and {})", country, country));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse_feature_flags("{\"all\": [\"a\",\n  ]}").unwrap_err();
+        assert!(matches!(error, FeatureFlagError::Json { span, .. } if span.offset() == 16));
+        assert!(matches!(parse_feature_flags(r#"{"all": "a"}"#), Err(FeatureFlagError::InvalidRule { path, .. }) if path == "$.all"));
+        assert!(matches!(parse_feature_flags(r#"{"any": ["a", 3]}"#), Err(FeatureFlagError::InvalidRule { path, .. }) if path == "$.any[1]"));
+        assert!(matches!(parse_feature_flags(r#"{"all": [], "any": []}"#), Err(FeatureFlagError::InvalidRule { .. })));
+        assert!(matches!(parse_feature_flags(r#"{"enabled": true}"#), Err(FeatureFlagError::InvalidRule { .. })));
+        assert!(matches!(parse_feature_flags(r#"{"fact": "age", "value": 21}"#), Err(FeatureFlagError::InvalidRule { .. })));
+        assert!(matches!(parse_feature_flags(r#"{"not": ""}"#), Err(FeatureFlagError::InvalidRule { .. })));
+        let long = format!(r#"{{"all": [{}"a"]}}"#, r#""a", "#.repeat(1000));
+        assert!(matches!(parse_feature_flags(&long), Err(FeatureFlagError::InvalidRule { path, .. }) if path == "$.all"));
+    }
+}
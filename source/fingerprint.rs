@@ -0,0 +1,177 @@
+use crate::source::{Expr, Cardinality, Quantifier};
+
+/// Version of the encoding [`Expr::fingerprint`] hashes
+pub const FINGERPRINT_VERSION: u32 = 1;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn number(&mut self, n: usize) {
+        self.bytes(&(n as u64).to_le_bytes());
+    }
+
+    fn string(&mut self, s: &str) {
+        self.number(s.len());
+        self.bytes(s.as_bytes());
+    }
+}
+
+/// 64-bit FNV-1a of `bytes`, for keys derived alongside fingerprints
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv(FNV_OFFSET);
+    hasher.bytes(bytes);
+    hasher.0
+}
+
+impl Expr {
+    /// A 64-bit structural fingerprint, the same on every platform and in every
+    /// process, suitable as a cache or database key.
+    ///
+    /// The expression is written out in pre-order and hashed with 64-bit FNV-1a.
+    /// Each node is a tag byte followed by its fields:
+    ///
+    /// | Node            | Tag       | Fields                                           |
+    /// |-----------------|-----------|--------------------------------------------------|
+    /// | identifier      | 0x01      | name                                             |
+    /// | `true`          | 0x02      |                                                  |
+    /// | `false`         | 0x03      |                                                  |
+    /// | `¬`             | 0x04      | operand                                          |
+    /// | `∧ ∨ ↑ ↓ ⊕ → ↔` | 0x05–0x0b | left, right                                      |
+    /// | `? :`           | 0x0c      | condition, then, else                            |
+    /// | cardinality     | 0x0d      | kind byte (atleast, atmost, exactly), bound, operands |
+    /// | quantifier      | 0x0e      | kind byte (∀, ∃), variable, body                 |
+    /// | custom          | 0x0f      | name, symbol (byte 0, or 1 and the string), arity, truth table, operands |
+    ///
+    /// Strings are their UTF-8 length then bytes; numbers are unsigned 64-bit
+    /// little-endian; operand lists are their length then each operand; kind bytes
+    /// count from 0 in the order listed; truth tables are packed eight rows to
+    /// a byte, lowest row in the lowest bit. Trees
+    /// that are equal (`==`) have the same fingerprint. The encoding is fixed:
+    /// a change to it is a breaking change.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv(FNV_OFFSET);
+        write_node(self, &mut hasher);
+        hasher.0
+    }
+}
+
+fn write_node(expr: &Expr, h: &mut Fnv) {
+    let binary = |tag: u8, left: &Expr, right: &Expr, h: &mut Fnv| {
+        h.bytes(&[tag]);
+        write_node(left, h);
+        write_node(right, h);
+    };
+    let operands = |operands: &[Expr], h: &mut Fnv| {
+        h.number(operands.len());
+        for operand in operands {
+            write_node(operand, h);
+        }
+    };
+    match expr {
+        Expr::Identifier(name) => {
+            h.bytes(&[0x01]);
+            h.string(name);
+        }
+        Expr::True => h.bytes(&[0x02]),
+        Expr::False => h.bytes(&[0x03]),
+        Expr::Not(inner) => {
+            h.bytes(&[0x04]);
+            write_node(inner, h);
+        }
+        Expr::And(l, r) => binary(0x05, l, r, h),
+        Expr::Or(l, r) => binary(0x06, l, r, h),
+        Expr::Nand(l, r) => binary(0x07, l, r, h),
+        Expr::Nor(l, r) => binary(0x08, l, r, h),
+        Expr::Xor(l, r) => binary(0x09, l, r, h),
+        Expr::Implication(l, r) => binary(0x0a, l, r, h),
+        Expr::Iff(l, r) => binary(0x0b, l, r, h),
+        Expr::Ite(c, t, e) => {
+            h.bytes(&[0x0c]);
+            write_node(c, h);
+            write_node(t, h);
+            write_node(e, h);
+        }
+        Expr::Cardinality(kind, k, list) => {
+            let kind = match kind {
+                Cardinality::AtLeast => 0,
+                Cardinality::AtMost => 1,
+                Cardinality::Exactly => 2,
+            };
+            h.bytes(&[0x0d, kind]);
+            h.number(*k);
+            operands(list, h);
+        }
+        Expr::Quantified(quantifier, var, body) => {
+            let kind = match quantifier {
+                Quantifier::ForAll => 0,
+                Quantifier::Exists => 1,
+            };
+            h.bytes(&[0x0e, kind]);
+            h.string(var);
+            write_node(body, h);
+        }
+        Expr::Custom(operator, list) => {
+            h.bytes(&[0x0f]);
+            h.string(&operator.name);
+            match &operator.symbol {
+                Some(symbol) => {
+                    h.bytes(&[1]);
+                    h.string(symbol);
+                }
+                None => h.bytes(&[0]),
+            }
+            h.number(operator.arity);
+            let packed: Vec<u8> = operator
+                .truth_table()
+                .chunks(8)
+                .map(|chunk| chunk.iter().enumerate().fold(0u8, |byte, (i, &bit)| byte | (u8::from(bit) << i)))
+                .collect();
+            h.bytes(&packed);
+            operands(list, h);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::Parser;
+
+    fn fingerprint(input: &str) -> u64 {
+        Parser::new(input).parse().unwrap().fingerprint()
+    }
+
+    #[test]
+    fn test_fingerprints_are_pinned() {
+        // These values are part of the format; they must never change
+        assert_eq!(fingerprint("a"), 0xe498_1b26_3241_8b44);
+        assert_eq!(fingerprint("(a and b) or not c"), 0x5f7c_178a_6095_6d4c);
+        assert_eq!(fingerprint("atleast(2; a, b, c) -> forall x. x xor s ? a : b"), 0x545e_3cd2_990b_4217);
+    }
+
+    #[test]
+    fn test_structure_distinguishes_fingerprints() {
+        assert_eq!(fingerprint("a and b"), fingerprint("(a) and (b)"));
+        assert_ne!(fingerprint("a and b"), fingerprint("b and a"));
+        assert_ne!(fingerprint("a and b"), fingerprint("a or b"));
+        // Length prefixes keep names from running together
+        assert_ne!(fingerprint("ab and c"), fingerprint("a and bc"));
+    }
+
+    #[test]
+    fn test_expressions_are_hashable() {
+        let set: std::collections::HashSet<_> = ["a and b", "(a and b)", "b and a"]
+            .iter()
+            .map(|input| Parser::new(input).parse().unwrap())
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+}
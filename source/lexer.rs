@@ -1,5 +1,18 @@
+use crate::source::parser::{Cardinality, Quantifier};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt;
 
+/// Operator notation accepted on top of the standard operators
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Notation {
+    /// Word, symbol and Unicode operators only
+    #[default]
+    Standard,
+    /// Boolean-algebra notation: `*` for and, `+` for or, postfix `'` for not
+    Algebra,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Span {
     pub start: usize,
@@ -17,75 +30,305 @@ impl Span {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct SpannedToken {
-    pub token: Token,
+pub struct SpannedToken<'a> {
+    pub token: Token<'a>,
     pub span: Span,
 }
 
+/// A token of the input, borrowing identifiers from it
 #[derive(Debug, Clone, PartialEq)]
-pub enum Token {
+pub enum Token<'a> {
     // Unary operators
     Not,
+    /// Postfix not (`'`) in algebra notation
+    Prime,
     
     // Binary operators
     And,
     Or,
+    Nand,
+    Nor,
     Xor,
     Implication,
+    Iff,
+    /// `ite` keyword introducing `ite(cond, then, else)`
+    Ite,
+    Question,
+    Colon,
+    /// `atleast`, `atmost` or `exactly`, introducing `name(k; operands...)`
+    Cardinality(Cardinality),
+    /// `forall`/`∀` or `exists`/`∃`, introducing `q x. body`
+    Quantifier(Quantifier),
     
-    // Identifiers
-    Identifier(String),
+    // Identifiers and constants
+    Identifier(&'a str),
+    True,
+    False,
+    /// A count other than `0` or `1`, which read as constants
+    Number(usize),
     
     // Delimiters
     LeftParen,
     RightParen,
+    Comma,
+    Semicolon,
+    Dot,
+    
+    /// A character that starts no token, kept so the parser can point at it
+    Unknown(char),
     
     // End of input
     Eof,
 }
 
-impl fmt::Display for Token {
+impl fmt::Display for Token<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Token::Not => write!(f, "NOT"),
+            Token::Prime => write!(f, "'"),
             Token::And => write!(f, "AND"),
             Token::Or => write!(f, "OR"),
+            Token::Nand => write!(f, "NAND"),
+            Token::Nor => write!(f, "NOR"),
             Token::Xor => write!(f, "XOR"),
             Token::Implication => write!(f, "IMPL"),
-            Token::Identifier(name) => write!(f, "{}", name),
+            Token::Iff => write!(f, "IFF"),
+            Token::Ite => write!(f, "ITE"),
+            Token::Question => write!(f, "?"),
+            Token::Colon => write!(f, ":"),
+            Token::Cardinality(kind) => write!(f, "{}", kind),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Semicolon => write!(f, ";"),
+            Token::Quantifier(quantifier) => write!(f, "{}", quantifier),
+            Token::Dot => write!(f, "."),
+            Token::True => write!(f, "TRUE"),
+            Token::False => write!(f, "FALSE"),
+            Token::Identifier(name) => write!(f, "{}", quote_identifier(name)),
             Token::LeftParen => write!(f, "("),
             Token::RightParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+            Token::Unknown(ch) => write!(f, "'{}'", ch),
             Token::Eof => write!(f, "EOF"),
         }
     }
 }
 
-#[derive(Debug)]
-pub struct Lexer {
-    input: Vec<char>,
+/// The token a reserved word stands for, if `word` is one
+pub(crate) fn keyword(word: &str) -> Option<Token<'static>> {
+    Some(match word {
+        "and" => Token::And,
+        "or" => Token::Or,
+        "not" => Token::Not,
+        "nand" => Token::Nand,
+        "nor" => Token::Nor,
+        "xor" => Token::Xor,
+        "iff" | "xnor" => Token::Iff,
+        "ite" => Token::Ite,
+        "atleast" => Token::Cardinality(Cardinality::AtLeast),
+        "atmost" => Token::Cardinality(Cardinality::AtMost),
+        "exactly" => Token::Cardinality(Cardinality::Exactly),
+        "forall" => Token::Quantifier(Quantifier::ForAll),
+        "exists" => Token::Quantifier(Quantifier::Exists),
+        "true" => Token::True,
+        "false" => Token::False,
+        _ => return None,
+    })
+}
+
+/// Every reserved word, for suggestions
+const KEYWORDS: [&str; 16] = [
+    "and", "or", "not", "nand", "nor", "xor", "iff", "xnor", "ite",
+    "atleast", "atmost", "exactly", "forall", "exists", "true", "false",
+];
+
+/// The keyword `word` was most likely meant to be, if it is a typo of one:
+/// one edit away for words of up to four letters, two for longer words
+pub fn suggest_keyword(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    let allowed = if word.chars().count() <= 4 { 1 } else { 2 };
+    KEYWORDS
+        .iter()
+        .map(|&keyword| (edit_distance(&word, keyword), keyword))
+        .filter(|&(distance, _)| distance <= allowed)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, keyword)| keyword)
+}
+
+/// Insertions, deletions, substitutions and swaps of adjacent characters
+/// needed to turn `a` into `b` (optimal string alignment distance)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Extra words for existing operators, such as `et` for `and` or `ou` for `or`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Aliases {
+    words: HashMap<String, Token<'static>>,
+}
+
+impl Aliases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Read `word` as `operator`, a keyword or operator symbol such as `and`,
+    /// `->` or `forall`. Aliases can't redefine keywords; a later alias for the
+    /// same word replaces the earlier one.
+    pub fn add(&mut self, word: &str, operator: &str) -> Result<(), String> {
+        let mut chars = word.chars();
+        if !(chars.next().is_some_and(char::is_alphabetic) && chars.all(|c| c.is_alphabetic() || c == '_')) {
+            return Err(format!("alias '{}' must be a word of letters and underscores", word));
+        }
+        if keyword(word).is_some() {
+            return Err(format!("'{}' is already a keyword", word));
+        }
+        let mut lexer = Lexer::new(operator.trim());
+        let token = match (lexer.next_spanned_token().token, lexer.next_spanned_token().token) {
+            (token, Token::Eof) => owned_operator(&token),
+            _ => None,
+        };
+        let token = token.ok_or_else(|| format!("'{}' is not an operator or keyword", operator.trim()))?;
+        self.words.insert(word.to_string(), token);
+        Ok(())
+    }
+    
+    /// The token `word` stands for, if it is an alias
+    pub fn get(&self, word: &str) -> Option<Token<'static>> {
+        self.words.get(word).cloned()
+    }
+    
+    /// Add every alias of `other`, replacing aliases for the same words
+    pub fn extend(&mut self, other: &Aliases) {
+        self.words.extend(other.words.iter().map(|(word, token)| (word.clone(), token.clone())));
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// An operator or keyword token, detached from the input it was read from
+fn owned_operator(token: &Token) -> Option<Token<'static>> {
+    Some(match token {
+        Token::Not => Token::Not,
+        Token::And => Token::And,
+        Token::Or => Token::Or,
+        Token::Nand => Token::Nand,
+        Token::Nor => Token::Nor,
+        Token::Xor => Token::Xor,
+        Token::Implication => Token::Implication,
+        Token::Iff => Token::Iff,
+        Token::Ite => Token::Ite,
+        Token::Cardinality(kind) => Token::Cardinality(*kind),
+        Token::Quantifier(quantifier) => Token::Quantifier(*quantifier),
+        Token::True => Token::True,
+        Token::False => Token::False,
+        _ => return None,
+    })
+}
+
+/// Write a variable name so that it reads back as the same identifier: as is
+/// when it is a plain word, otherwise in backticks, or in double quotes if the
+/// name itself contains a backtick
+pub fn quote_identifier(name: &str) -> Cow<'_, str> {
+    let mut chars = name.chars();
+    let plain = chars.next().is_some_and(char::is_alphabetic)
+        && chars.all(|c| c.is_alphabetic() || c == '_')
+        && keyword(name).is_none();
+    if plain {
+        Cow::Borrowed(name)
+    } else if name.contains('`') {
+        Cow::Owned(format!("\"{}\"", name))
+    } else {
+        Cow::Owned(format!("`{}`", name))
+    }
+}
+
+/// Reads tokens on demand; as an iterator it yields every token up to and
+/// including [`Token::Eof`]
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a str,
+    /// Byte offset of the current character
     position: usize,
     current_char: Option<char>,
+    notation: Notation,
+    aliases: Aliases,
+    /// Read keywords in any case, e.g. `AND` or `Not`
+    case_insensitive: bool,
+    /// Whether the iterator has yielded `Eof`
+    finished: bool,
 }
 
-impl Lexer {
-    pub fn new(input: &str) -> Self {
-        let chars: Vec<char> = input.chars().collect();
-        let current_char = chars.first().copied();
-        
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
         Self {
-            input: chars,
+            input,
             position: 0,
-            current_char,
+            current_char: input.chars().next(),
+            notation: Notation::Standard,
+            aliases: Aliases::new(),
+            case_insensitive: false,
+            finished: false,
         }
     }
     
+    /// Also accept the operators of `notation`
+    pub fn with_notation(mut self, notation: Notation) -> Self {
+        self.notation = notation;
+        self
+    }
+    
+    /// Read the words of `aliases` as the operators they stand for
+    pub fn with_aliases(mut self, aliases: Aliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+    
+    pub fn aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+    
+    /// Read keywords whatever their case, so `AND`, `Or` and `NOT` are
+    /// operators; identifiers that only contain a keyword are unaffected
+    pub fn with_case_insensitive_keywords(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+    
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+    
     fn advance(&mut self) {
-        self.position += 1;
-        self.current_char = self.input.get(self.position).copied();
+        if let Some(ch) = self.current_char {
+            self.position += ch.len_utf8();
+            self.current_char = self.input[self.position..].chars().next();
+        }
+    }
+    
+    /// The character `n` places after the current one
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.input[self.position..].chars().nth(n)
     }
     
     fn peek(&self) -> Option<char> {
-        self.input.get(self.position + 1).copied()
+        self.peek_nth(1)
     }
     
     fn skip_whitespace(&mut self) {
@@ -98,23 +341,37 @@ impl Lexer {
         }
     }
     
-    fn read_identifier(&mut self) -> (String, Span) {
+    fn read_identifier(&mut self) -> (&'a str, Span) {
         let start = self.position;
-        let mut result = String::new();
         
         while let Some(ch) = self.current_char {
             if ch.is_alphabetic() || ch == '_' {
-                result.push(ch);
                 self.advance();
             } else {
                 break;
             }
         }
         
-        (result, Span::new(start, self.position))
+        (&self.input[start..self.position], Span::new(start, self.position))
+    }
+    
+    /// A name between two `quote` characters, taken verbatim. An unterminated or
+    /// empty name consumes only the opening quote.
+    fn read_quoted(&mut self, quote: char) -> Option<&'a str> {
+        let start = self.position + quote.len_utf8();
+        let close = self.input[start..].find(quote).filter(|&len| len > 0);
+        let Some(len) = close else {
+            self.advance();
+            return None;
+        };
+        let name = &self.input[start..start + len];
+        self.position = start + len;
+        self.current_char = Some(quote);
+        self.advance();
+        Some(name)
     }
     
-    fn read_symbol(&mut self) -> Option<(Token, Span)> {
+    fn read_symbol(&mut self) -> Option<(Token<'a>, Span)> {
         let start = self.position;
         match self.current_char? {
             '-' if self.peek() == Some('>') => {
@@ -122,16 +379,48 @@ impl Lexer {
                 self.advance(); // consume '>'
                 Some((Token::Implication, Span::new(start, self.position)))
             }
+            '=' if self.peek() == Some('>') => {
+                self.advance(); // consume '='
+                self.advance(); // consume '>'
+                Some((Token::Implication, Span::new(start, self.position)))
+            }
+            '=' if self.peek() == Some('=') => {
+                self.advance(); // consume first '='
+                self.advance(); // consume second '='
+                Some((Token::Iff, Span::new(start, self.position)))
+            }
             // Unicode arrow: →
             '\u{2192}' => {
                 self.advance();
                 Some((Token::Implication, Span::new(start, self.position)))
             }
+            '<' if self.peek() == Some('-') && self.peek_nth(2) == Some('>') => {
+                self.advance(); // consume '<'
+                self.advance(); // consume '-'
+                self.advance(); // consume '>'
+                Some((Token::Iff, Span::new(start, self.position)))
+            }
+            '<' if self.peek() == Some('=') && self.peek_nth(2) == Some('>') => {
+                self.advance(); // consume '<'
+                self.advance(); // consume '='
+                self.advance(); // consume '>'
+                Some((Token::Iff, Span::new(start, self.position)))
+            }
+            // Unicode double arrow: ↔
+            '\u{2194}' => {
+                self.advance();
+                Some((Token::Iff, Span::new(start, self.position)))
+            }
             '&' if self.peek() == Some('&') => {
                 self.advance(); // consume first '&'
                 self.advance(); // consume second '&'
                 Some((Token::And, Span::new(start, self.position)))
             }
+            // C's bitwise and, which agrees with '&&' on booleans
+            '&' => {
+                self.advance();
+                Some((Token::And, Span::new(start, self.position)))
+            }
             // Unicode and: ∧
             '\u{2227}' => {
                 self.advance();
@@ -142,22 +431,41 @@ impl Lexer {
                 self.advance(); // consume second '|'
                 Some((Token::Or, Span::new(start, self.position)))
             }
+            '|' => {
+                self.advance();
+                Some((Token::Or, Span::new(start, self.position)))
+            }
             // Unicode or: ∨
             '\u{2228}' => {
                 self.advance();
                 Some((Token::Or, Span::new(start, self.position)))
             }
+            // Sheffer stroke: ↑
+            '\u{2191}' => {
+                self.advance();
+                Some((Token::Nand, Span::new(start, self.position)))
+            }
+            // Peirce arrow: ↓
+            '\u{2193}' => {
+                self.advance();
+                Some((Token::Nor, Span::new(start, self.position)))
+            }
+            '!' if self.peek() == Some('=') => {
+                self.advance(); // consume '!'
+                self.advance(); // consume '='
+                Some((Token::Xor, Span::new(start, self.position)))
+            }
             '!' => {
                 self.advance();
                 Some((Token::Not, Span::new(start, self.position)))
             }
-            // Unicode not: ¬
-            '\u{00AC}' => {
+            // Unicode not ¬, and C's bitwise not
+            '\u{00AC}' | '~' => {
                 self.advance();
                 Some((Token::Not, Span::new(start, self.position)))
             }
-            // Unicode xor: ⊻ or ⊕
-            c if c == '\u{22BB}' || c == '\u{2295}' => {
+            // Unicode xor ⊻ or ⊕, and C's ^
+            c if c == '\u{22BB}' || c == '\u{2295}' || c == '^' => {
                 self.advance();
                 Some((Token::Xor, Span::new(start, self.position)))
             }
@@ -169,11 +477,52 @@ impl Lexer {
                 self.advance();
                 Some((Token::RightParen, Span::new(start, self.position)))
             }
+            ',' => {
+                self.advance();
+                Some((Token::Comma, Span::new(start, self.position)))
+            }
+            '?' => {
+                self.advance();
+                Some((Token::Question, Span::new(start, self.position)))
+            }
+            ':' => {
+                self.advance();
+                Some((Token::Colon, Span::new(start, self.position)))
+            }
+            ';' => {
+                self.advance();
+                Some((Token::Semicolon, Span::new(start, self.position)))
+            }
+            '.' => {
+                self.advance();
+                Some((Token::Dot, Span::new(start, self.position)))
+            }
+            '\u{2200}' => {
+                self.advance();
+                Some((Token::Quantifier(Quantifier::ForAll), Span::new(start, self.position)))
+            }
+            '\u{2203}' => {
+                self.advance();
+                Some((Token::Quantifier(Quantifier::Exists), Span::new(start, self.position)))
+            }
+            '*' if self.notation == Notation::Algebra => {
+                self.advance();
+                Some((Token::And, Span::new(start, self.position)))
+            }
+            '+' if self.notation == Notation::Algebra => {
+                self.advance();
+                Some((Token::Or, Span::new(start, self.position)))
+            }
+            '\'' if self.notation == Notation::Algebra => {
+                self.advance();
+                Some((Token::Prime, Span::new(start, self.position)))
+            }
             _ => None,
         }
     }
     
-    pub fn next_spanned_token(&mut self) -> SpannedToken {
+    /// The next token, with its span in bytes
+    pub fn next_spanned_token(&mut self) -> SpannedToken<'a> {
         loop {
             self.skip_whitespace();
             
@@ -184,62 +533,71 @@ impl Lexer {
                 },
                 Some(ch) if ch.is_alphabetic() => {
                     let (identifier, span) = self.read_identifier();
-                    let token = match identifier.as_str() {
-                        "and" => Token::And,
-                        "or" => Token::Or,
-                        "not" => Token::Not,
-                        "xor" => Token::Xor,
-                        _ => Token::Identifier(identifier),
-                    };
+                    let token = keyword(identifier)
+                        .or_else(|| self.case_insensitive.then(|| keyword(&identifier.to_lowercase())).flatten())
+                        .or_else(|| self.aliases.get(identifier))
+                        .unwrap_or(Token::Identifier(identifier));
                     return SpannedToken { token, span };
                 }
-                Some(_) => {
+                Some(quote @ ('`' | '"')) => {
+                    let start = self.position;
+                    let token = match self.read_quoted(quote) {
+                        Some(name) => Token::Identifier(name),
+                        None => Token::Unknown(quote),
+                    };
+                    return SpannedToken { token, span: Span::new(start, self.position) };
+                }
+                Some(ch) if ch.is_ascii_digit() => {
+                    let start = self.position;
+                    while self.current_char.is_some_and(|ch| ch.is_ascii_digit()) {
+                        self.advance();
+                    }
+                    let token = match &self.input[start..self.position] {
+                        "1" => Token::True,
+                        "0" => Token::False,
+                        digits => match digits.parse() {
+                            Ok(n) => Token::Number(n),
+                            // Skip numbers too large to count with
+                            Err(_) => continue,
+                        },
+                    };
+                    return SpannedToken { token, span: Span::new(start, self.position) };
+                }
+                Some(ch) => {
                     if let Some((token, span)) = self.read_symbol() {
                         return SpannedToken { token, span };
-                    } else {
-                        // Skip unknown character and continue
-                        self.advance();
-                        continue;
                     }
+                    let start = self.position;
+                    self.advance();
+                    return SpannedToken { token: Token::Unknown(ch), span: Span::new(start, self.position) };
                 }
             }
         }
     }
     
-    pub fn next_token(&mut self) -> Token {
+    pub fn next_token(&mut self) -> Token<'a> {
         self.next_spanned_token().token
     }
     
-    pub fn tokenize(&mut self) -> Vec<Token> {
-        let mut tokens = Vec::new();
-        
-        loop {
-            let token = self.next_token();
-            let is_eof = matches!(token, Token::Eof);
-            tokens.push(token);
-            
-            if is_eof {
-                break;
-            }
-        }
-        
-        tokens
+    pub fn tokenize(&mut self) -> Vec<Token<'a>> {
+        self.map(|spanned| spanned.token).collect()
     }
     
-    pub fn tokenize_spanned(&mut self) -> Vec<SpannedToken> {
-        let mut tokens = Vec::new();
-        
-        loop {
-            let spanned_token = self.next_spanned_token();
-            let is_eof = matches!(spanned_token.token, Token::Eof);
-            tokens.push(spanned_token);
-            
-            if is_eof {
-                break;
-            }
+    pub fn tokenize_spanned(&mut self) -> Vec<SpannedToken<'a>> {
+        self.collect()
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = SpannedToken<'a>;
+    
+    fn next(&mut self) -> Option<SpannedToken<'a>> {
+        if self.finished {
+            return None;
         }
-        
-        tokens
+        let spanned = self.next_spanned_token();
+        self.finished = matches!(spanned.token, Token::Eof);
+        Some(spanned)
     }
 }
 
@@ -254,6 +612,11 @@ mod tests {
             ("or", vec![Token::Or, Token::Eof]),
             ("not", vec![Token::Not, Token::Eof]),
             ("xor", vec![Token::Xor, Token::Eof]),
+            ("iff", vec![Token::Iff, Token::Eof]),
+            ("xnor", vec![Token::Iff, Token::Eof]),
+            ("nand", vec![Token::Nand, Token::Eof]),
+            ("nor", vec![Token::Nor, Token::Eof]),
+            ("atleast", vec![Token::Cardinality(Cardinality::AtLeast), Token::Eof]),
         ];
         
         for (input, expected) in test_cases {
@@ -270,6 +633,17 @@ mod tests {
             ("||", vec![Token::Or, Token::Eof]),
             ("!", vec![Token::Not, Token::Eof]),
             ("->", vec![Token::Implication, Token::Eof]),
+            ("<->", vec![Token::Iff, Token::Eof]),
+            ("=>", vec![Token::Implication, Token::Eof]),
+            ("<=>", vec![Token::Iff, Token::Eof]),
+            ("==", vec![Token::Iff, Token::Eof]),
+            ("!=", vec![Token::Xor, Token::Eof]),
+            ("^", vec![Token::Xor, Token::Eof]),
+            ("!!", vec![Token::Not, Token::Not, Token::Eof]),
+            ("&", vec![Token::And, Token::Eof]),
+            ("|", vec![Token::Or, Token::Eof]),
+            ("~", vec![Token::Not, Token::Eof]),
+            ("& &&|", vec![Token::And, Token::And, Token::Or, Token::Eof]),
         ];
         
         for (input, expected) in test_cases {
@@ -288,6 +662,9 @@ mod tests {
             ("→", vec![Token::Implication, Token::Eof]),
             ("⊻", vec![Token::Xor, Token::Eof]),
             ("⊕", vec![Token::Xor, Token::Eof]),
+            ("↔", vec![Token::Iff, Token::Eof]),
+            ("↑", vec![Token::Nand, Token::Eof]),
+            ("↓", vec![Token::Nor, Token::Eof]),
         ];
         
         for (input, expected) in test_cases {
@@ -300,9 +677,9 @@ mod tests {
     #[test]
     fn test_identifiers() {
         let test_cases = [
-            ("a", vec![Token::Identifier("a".to_string()), Token::Eof]),
-            ("variable", vec![Token::Identifier("variable".to_string()), Token::Eof]),
-            ("var_name", vec![Token::Identifier("var_name".to_string()), Token::Eof]),
+            ("a", vec![Token::Identifier("a"), Token::Eof]),
+            ("variable", vec![Token::Identifier("variable"), Token::Eof]),
+            ("var_name", vec![Token::Identifier("var_name"), Token::Eof]),
         ];
         
         for (input, expected) in test_cases {
@@ -312,6 +689,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_constants() {
+        let mut lexer = Lexer::new("true or false and 1 xor 0");
+        assert_eq!(
+            lexer.tokenize(),
+            vec![Token::True, Token::Or, Token::False, Token::And, Token::True, Token::Xor, Token::False, Token::Eof]
+        );
+    }
+
+    #[test]
+    fn test_algebra_notation() {
+        let a = || Token::Identifier("a");
+        let mut lexer = Lexer::new("a' * a + a").with_notation(Notation::Algebra);
+        assert_eq!(lexer.tokenize(), vec![a(), Token::Prime, Token::And, a(), Token::Or, a(), Token::Eof]);
+        
+        // Not operators in the standard notation
+        let mut lexer = Lexer::new("a * a");
+        assert_eq!(lexer.tokenize(), vec![a(), Token::Unknown('*'), a(), Token::Eof]);
+    }
+
+    #[test]
+    fn test_spans_are_byte_offsets() {
+        let mut lexer = Lexer::new("¬a € b");
+        let spans: Vec<(Token, usize, usize)> = lexer
+            .tokenize_spanned()
+            .into_iter()
+            .map(|t| (t.token, t.span.start, t.span.end))
+            .collect();
+        assert_eq!(spans[1], (Token::Identifier("a"), 2, 3));
+        assert_eq!(spans[2], (Token::Unknown('€'), 4, 7));
+        assert_eq!(spans[3].1, 8);
+    }
+
     #[test]
     fn test_complex_expression() {
         let mut lexer = Lexer::new("a and b or not c");
@@ -319,12 +729,12 @@ mod tests {
         assert_eq!(
             tokens,
             vec![
-                Token::Identifier("a".to_string()),
+                Token::Identifier("a"),
                 Token::And,
-                Token::Identifier("b".to_string()),
+                Token::Identifier("b"),
                 Token::Or,
                 Token::Not,
-                Token::Identifier("c".to_string()),
+                Token::Identifier("c"),
                 Token::Eof
             ]
         );
@@ -339,9 +749,9 @@ mod tests {
         ];
         
         let expected = vec![
-            Token::Identifier("a".to_string()),
+            Token::Identifier("a"),
             Token::And,
-            Token::Identifier("b".to_string()),
+            Token::Identifier("b"),
             Token::Eof,
         ];
         
@@ -351,4 +761,79 @@ mod tests {
             assert_eq!(tokens, expected, "Failed for input: {:?}", input);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_quoted_identifiers() {
+        let mut lexer = Lexer::new("`input valid` and \"rst-n\" or `and`");
+        assert_eq!(
+            lexer.tokenize(),
+            vec![
+                Token::Identifier("input valid"),
+                Token::And,
+                Token::Identifier("rst-n"),
+                Token::Or,
+                Token::Identifier("and"),
+                Token::Eof,
+            ]
+        );
+        // An unterminated quote is reported on its own
+        assert_eq!(Lexer::new("`a b").tokenize()[0], Token::Unknown('`'));
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("valid_name"), "valid_name");
+        // A digit would read back as a constant
+        assert_eq!(quote_identifier("x0"), "`x0`");
+        assert_eq!(quote_identifier("input valid"), "`input valid`");
+        assert_eq!(quote_identifier("or"), "`or`");
+        assert_eq!(quote_identifier("a`b"), "\"a`b\"");
+    }
+
+    #[test]
+    fn test_lexer_is_an_iterator() {
+        let mut lexer = Lexer::new("a and b");
+        assert_eq!(lexer.next().map(|t| t.token), Some(Token::Identifier("a")));
+        let rest: Vec<Token> = lexer.by_ref().map(|t| t.token).collect();
+        assert_eq!(rest, vec![Token::And, Token::Identifier("b"), Token::Eof]);
+        // Nothing follows the end of input
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let mut aliases = Aliases::new();
+        aliases.add("et", "and").unwrap();
+        aliases.add("implique", "->").unwrap();
+        let tokens = Lexer::new("a et b implique etc").with_aliases(aliases.clone()).tokenize();
+        assert_eq!(tokens, vec![
+            Token::Identifier("a"), Token::And, Token::Identifier("b"),
+            Token::Implication, Token::Identifier("etc"), Token::Eof,
+        ]);
+        assert!(aliases.add("or", "and").is_err());
+        assert!(aliases.add("x1", "and").is_err());
+        assert!(aliases.add("plus", "+").is_err());
+        assert!(aliases.add("both", "a and").is_err());
+    }
+    
+    #[test]
+    fn test_case_insensitive_keywords() {
+        let tokens = Lexer::new("a AND Not b Or ANDROID").with_case_insensitive_keywords().tokenize();
+        assert_eq!(tokens, vec![
+            Token::Identifier("a"), Token::And, Token::Not, Token::Identifier("b"),
+            Token::Or, Token::Identifier("ANDROID"), Token::Eof,
+        ]);
+        assert_eq!(Lexer::new("AND").tokenize()[0], Token::Identifier("AND"));
+        assert_eq!(Lexer::new("`AND`").with_case_insensitive_keywords().tokenize()[0], Token::Identifier("AND"));
+    }
+    
+    #[test]
+    fn test_identifiers_borrow_from_input() {
+        let input = "größe and `ü b`";
+        let tokens = Lexer::new(input).tokenize_spanned();
+        let Token::Identifier(name) = tokens[0].token else { panic!("expected an identifier") };
+        assert!(std::ptr::eq(name, &input[..7]));
+        assert_eq!(tokens[2].token, Token::Identifier("ü b"));
+        assert_eq!((tokens[2].span.start, tokens[2].span.end), (12, input.len()));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::source::operator::Operator;
+use crate::source::lexer::Aliases;
+use std::collections::HashMap;
+use thiserror::Error;
+use miette::Diagnostic;
+
+/// A named, possibly parameterized expression definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub name: String,
+    pub params: Vec<String>,
+    /// Unparsed body text; parsed on each instantiation so definitions may refer to each other in any order
+    pub body: String,
+}
+
+/// Errors in a library file
+#[derive(Error, Debug, Diagnostic)]
+pub enum LibraryError {
+    #[error("Line {line}: expected `def name := expr` or `def name(a, b) := expr`")]
+    #[diagnostic(code(ttt::library::syntax))]
+    Syntax { line: usize },
+    
+    #[error("Line {line}: invalid name '{name}'")]
+    #[diagnostic(
+        code(ttt::library::invalid_name),
+        help("Names must start with a letter and contain only letters and underscores")
+    )]
+    InvalidName { line: usize, name: String },
+    
+    #[error("Line {line}: '{name}' is already defined")]
+    #[diagnostic(code(ttt::library::duplicate_definition))]
+    DuplicateDefinition { line: usize, name: String },
+    
+    #[error("Line {line}: parameter '{param}' appears more than once in '{name}'")]
+    #[diagnostic(code(ttt::library::duplicate_parameter))]
+    DuplicateParameter { line: usize, name: String, param: String },
+    
+    #[error("Line {line}: {reason}")]
+    #[diagnostic(
+        code(ttt::library::invalid_alias),
+        help("Write `alias word = operator`, e.g. `alias et = and`")
+    )]
+    InvalidAlias { line: usize, reason: String },
+}
+
+/// A set of named definitions that the parser expands during parsing, and of
+/// custom operators that it keeps as calls
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Library {
+    templates: HashMap<String, Template>,
+    operators: HashMap<String, Operator>,
+    aliases: Aliases,
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Parse a library file: one `def` or `alias word = operator` per line, `#`
+    /// starts a comment
+    pub fn parse(text: &str) -> Result<Self, LibraryError> {
+        let mut library = Self::new();
+        
+        for (idx, raw) in text.lines().enumerate() {
+            let line_no = idx + 1;
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            
+            if let Some(alias) = line.strip_prefix("alias").filter(|rest| rest.starts_with(char::is_whitespace)) {
+                let (word, operator) = alias.split_once('=')
+                    .ok_or_else(|| LibraryError::InvalidAlias { line: line_no, reason: "expected `=` after the alias".to_string() })?;
+                library.aliases.add(word.trim(), operator)
+                    .map_err(|reason| LibraryError::InvalidAlias { line: line_no, reason })?;
+                continue;
+            }
+            
+            let rest = line
+                .strip_prefix("def")
+                .filter(|rest| rest.starts_with(char::is_whitespace))
+                .ok_or(LibraryError::Syntax { line: line_no })?;
+            let (head, body) = rest.split_once(":=").ok_or(LibraryError::Syntax { line: line_no })?;
+            let head = head.trim();
+            let body = body.trim();
+            if body.is_empty() {
+                return Err(LibraryError::Syntax { line: line_no });
+            }
+            
+            let (name, params) = match head.split_once('(') {
+                Some((name, params)) => {
+                    let params = params.trim().strip_suffix(')').ok_or(LibraryError::Syntax { line: line_no })?;
+                    let params: Vec<String> = if params.trim().is_empty() {
+                        Vec::new()
+                    } else {
+                        params.split(',').map(|p| p.trim().to_string()).collect()
+                    };
+                    (name.trim().to_string(), params)
+                }
+                None => (head.to_string(), Vec::new()),
+            };
+            
+            for candidate in std::iter::once(&name).chain(&params) {
+                if !is_valid_name(candidate) {
+                    return Err(LibraryError::InvalidName { line: line_no, name: candidate.clone() });
+                }
+            }
+            for (i, param) in params.iter().enumerate() {
+                if params[..i].contains(param) {
+                    return Err(LibraryError::DuplicateParameter { line: line_no, name, param: param.clone() });
+                }
+            }
+            
+            library.define(Template { name, params, body: body.to_string() })
+                .map_err(|name| LibraryError::DuplicateDefinition { line: line_no, name })?;
+        }
+        
+        Ok(library)
+    }
+    
+    /// Add a definition; returns the name back if it is already defined
+    pub fn define(&mut self, template: Template) -> Result<(), String> {
+        if self.templates.contains_key(&template.name) || self.operators.contains_key(&template.name) {
+            return Err(template.name);
+        }
+        self.templates.insert(template.name.clone(), template);
+        Ok(())
+    }
+    
+    /// Add a custom operator; returns its name back if the name is already taken
+    pub fn register(&mut self, operator: Operator) -> Result<(), String> {
+        if self.templates.contains_key(&operator.name) || self.operators.contains_key(&operator.name) {
+            return Err(operator.name);
+        }
+        self.operators.insert(operator.name.clone(), operator);
+        Ok(())
+    }
+    
+    /// Add every definition, operator and alias of `other`; returns the first
+    /// name already taken, leaving the definitions before it added
+    pub fn extend(&mut self, other: Library) -> Result<(), String> {
+        for template in other.templates.into_values() {
+            self.define(template)?;
+        }
+        for operator in other.operators.into_values() {
+            self.register(operator)?;
+        }
+        self.aliases.extend(&other.aliases);
+        Ok(())
+    }
+    
+    /// Words the parser reads as operators
+    pub fn aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+    
+    pub fn operator(&self, name: &str) -> Option<&Operator> {
+        self.operators.get(name)
+    }
+    
+    pub fn get(&self, name: &str) -> Option<&Template> {
+        self.templates.get(name)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.templates.len() + self.operators.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty() && self.operators.is_empty()
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic())
+        && chars.all(|c| c.is_alphabetic() || c == '_')
+        && !matches!(name, "and" | "or" | "not" | "xor")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_library() {
+        let library = Library::parse("\
+# adders
+def carry(a, b, cin) := (a and b) or (cin and (a xor b))
+def enabled := power and not fault
+").unwrap();
+        assert_eq!(library.len(), 2);
+        assert_eq!(library.get("carry").unwrap().params, vec!["a", "b", "cin"]);
+        assert!(library.get("enabled").unwrap().params.is_empty());
+    }
+
+    #[test]
+    fn test_library_errors() {
+        assert!(matches!(Library::parse("carry := a"), Err(LibraryError::Syntax { line: 1 })));
+        assert!(matches!(Library::parse("def f(a, a) := a"), Err(LibraryError::DuplicateParameter { .. })));
+        assert!(matches!(Library::parse("def f := a\ndef f := b"), Err(LibraryError::DuplicateDefinition { line: 2, .. })));
+        assert!(matches!(Library::parse("def and := a"), Err(LibraryError::InvalidName { .. })));
+        assert!(matches!(Library::parse("def f := a\nalias et and"), Err(LibraryError::InvalidAlias { line: 2, .. })));
+        assert!(matches!(Library::parse("alias et = plus"), Err(LibraryError::InvalidAlias { .. })));
+        assert!(matches!(Library::parse("alias or = and"), Err(LibraryError::InvalidAlias { .. })));
+    }
+}
@@ -1,5 +1,50 @@
 pub mod lexer;
 pub mod parser;
+pub mod normalize;
+pub mod structural;
+pub mod dialect;
+pub mod library;
+pub mod smtlib;
+pub mod sexpr;
+pub mod fault_tree;
+pub mod spreadsheet;
+pub mod sql;
+pub mod feature_flags;
+pub mod structured_text;
+pub mod precedence;
+pub mod operator;
+pub mod fingerprint;
+pub mod visit;
+pub mod arena;
+pub mod basis;
+pub mod rewrite;
+pub mod pretty;
+#[cfg(feature = "binary")]
+pub mod binary;
+#[cfg(any(test, feature = "proptest"))]
+pub mod arbitrary;
 
-pub use lexer::{Lexer, Token, SpannedToken, Span};
-pub use parser::{Parser, Expr, ParseError};
\ No newline at end of file
+pub use lexer::{Lexer, Aliases, Token, SpannedToken, Span, Notation, quote_identifier};
+pub use parser::{Parser, Syntax, Expr, Cardinality, Quantifier, ParseError, ParseErrors, ImplicitAndHint};
+pub use structural::StructuralOptions;
+pub use dialect::{Dialect, Snippet};
+pub use library::{Library, Template};
+pub use smtlib::{parse_smtlib, SmtLibError};
+pub use sexpr::{parse_sexpr, to_sexpr, SExprError};
+pub use fault_tree::{parse_fault_tree, FaultTree, FaultTreeError};
+pub use spreadsheet::{parse_spreadsheet, SpreadsheetError};
+pub use sql::{parse_sql, SqlError};
+pub use feature_flags::{parse_feature_flags, FeatureFlagError};
+pub use structured_text::{parse_structured_text, StructuredText, StructuredTextError};
+pub use precedence::{Precedence, Associativity, BinaryOp};
+pub use operator::Operator;
+pub use fingerprint::FINGERPRINT_VERSION;
+pub use visit::{ExprVisitor, ExprFolder};
+pub use arena::{ExprArena, ExprId, ExprNode};
+pub use basis::Basis;
+pub use rewrite::{Rule, Rewriter, Rewrite, RewriteStep};
+pub use pretty::{PrettyPrinter, SymbolStyle};
+#[cfg(feature = "binary")]
+pub use binary::BinaryError;
+#[cfg(any(test, feature = "proptest"))]
+pub use arbitrary::ExprParams;
@@ -0,0 +1,121 @@
+use crate::source::Expr;
+use crate::source::structural::chain_operands;
+use std::cmp::Ordering;
+
+impl Expr {
+    /// Return a canonical form of the expression.
+    ///
+    /// Chains of the associative, commutative operators (`and`, `or`, `xor`, `iff`) are
+    /// flattened, their operands sorted, and rebuilt left-associatively, so
+    /// `c or (b or a)` and `(a or b) or c` both normalize to `((a ∨ b) ∨ c)`.
+    /// The operands of `nand` and `nor`, which commute but don't associate, are
+    /// sorted without flattening, as are the operands of a cardinality constraint.
+    /// Implication and if-then-else keep their operand order.
+    pub fn normalize(&self) -> Expr {
+        match self {
+            Expr::Identifier(_) | Expr::True | Expr::False => self.clone(),
+            Expr::Not(inner) => Expr::Not(Box::new(inner.normalize())),
+            Expr::And(..) => rebuild_sorted(self, Expr::And),
+            Expr::Or(..) => rebuild_sorted(self, Expr::Or),
+            Expr::Xor(..) => rebuild_sorted(self, Expr::Xor),
+            Expr::Iff(..) => rebuild_sorted(self, Expr::Iff),
+            Expr::Nand(left, right) => sorted_pair(left, right, Expr::Nand),
+            Expr::Nor(left, right) => sorted_pair(left, right, Expr::Nor),
+            Expr::Implication(left, right) => {
+                Expr::Implication(Box::new(left.normalize()), Box::new(right.normalize()))
+            }
+            Expr::Ite(cond, then, otherwise) => Expr::Ite(
+                Box::new(cond.normalize()),
+                Box::new(then.normalize()),
+                Box::new(otherwise.normalize()),
+            ),
+            Expr::Cardinality(kind, k, operands) => {
+                let mut operands: Vec<Expr> = operands.iter().map(Expr::normalize).collect();
+                operands.sort_by(compare_operands);
+                Expr::Cardinality(*kind, *k, operands)
+            }
+            Expr::Quantified(quantifier, var, body) => Expr::Quantified(*quantifier, var.clone(), Box::new(body.normalize())),
+            // Custom operators need not be symmetric
+            Expr::Custom(operator, operands) => Expr::Custom(operator.clone(), operands.iter().map(Expr::normalize).collect()),
+        }
+    }
+}
+
+fn rebuild_sorted(expr: &Expr, make: fn(Box<Expr>, Box<Expr>) -> Expr) -> Expr {
+    let mut operands: Vec<Expr> = chain_operands(expr).into_iter().map(Expr::normalize).collect();
+    operands.sort_by(compare_operands);
+    
+    let mut iter = operands.into_iter();
+    let first = iter.next().expect("binary chain has at least two operands");
+    iter.fold(first, |acc, operand| make(Box::new(acc), Box::new(operand)))
+}
+
+fn sorted_pair(left: &Expr, right: &Expr, make: fn(Box<Expr>, Box<Expr>) -> Expr) -> Expr {
+    let (left, right) = (left.normalize(), right.normalize());
+    if compare_operands(&left, &right) == Ordering::Greater {
+        make(Box::new(right), Box::new(left))
+    } else {
+        make(Box::new(left), Box::new(right))
+    }
+}
+
+/// Order operands by their first variable, then by size, then textually,
+/// so literals read alphabetically (`¬a ∧ b`) and simple terms precede compound ones.
+fn compare_operands(left: &Expr, right: &Expr) -> Ordering {
+    first_identifier(left)
+        .cmp(first_identifier(right))
+        .then_with(|| left.node_count().cmp(&right.node_count()))
+        .then_with(|| left.to_string().cmp(&right.to_string()))
+}
+
+fn first_identifier(expr: &Expr) -> &str {
+    match expr {
+        Expr::Identifier(name) => name,
+        // Constants sort before every variable
+        Expr::True | Expr::False => "",
+        Expr::Not(inner) => first_identifier(inner),
+        Expr::And(left, _)
+        | Expr::Or(left, _)
+        | Expr::Nand(left, _)
+        | Expr::Nor(left, _)
+        | Expr::Xor(left, _)
+        | Expr::Implication(left, _)
+        | Expr::Iff(left, _)
+        | Expr::Ite(left, _, _) => {
+            first_identifier(left)
+        }
+        Expr::Cardinality(_, _, operands) | Expr::Custom(_, operands) => operands.first().map_or("", first_identifier),
+        Expr::Quantified(_, _, body) => first_identifier(body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::Parser;
+
+    fn normalized(input: &str) -> String {
+        Parser::new(input).parse().unwrap().normalize().to_string()
+    }
+
+    #[test]
+    fn test_commutative_sorting() {
+        assert_eq!(normalized("b and a"), "(a ∧ b)");
+        assert_eq!(normalized("b or not a"), "(¬a ∨ b)");
+        assert_eq!(normalized("b xor a"), "(a ⊕ b)");
+        assert_eq!(normalized("(c nand b) nand a"), "(a ↑ (b ↑ c))");
+    }
+
+    #[test]
+    fn test_associative_flattening() {
+        assert_eq!(normalized("c or (b or a)"), "((a ∨ b) ∨ c)");
+        assert_eq!(normalized("(a or b) or c"), normalized("a or (c or b)"));
+        // Different operators are not merged
+        assert_eq!(normalized("c and (b or a)"), "((a ∨ b) ∧ c)");
+    }
+
+    #[test]
+    fn test_implication_order_preserved() {
+        assert_eq!(normalized("b -> a"), "(b → a)");
+        assert_eq!(normalized("(b and a) -> c"), "((a ∧ b) → c)");
+    }
+}
@@ -0,0 +1,134 @@
+use crate::source::Expr;
+use crate::config::MAX_OPERATOR_ARITY;
+use serde::{Serialize, Deserialize};
+
+/// A user-defined n-ary operator, registered in a [`Library`](crate::source::Library)
+/// and called like a function, e.g. `vote(a, b, c)`.
+///
+/// The evaluation closure is tabulated once when the operator is built, so
+/// operators can be cloned, compared and serialized along with expressions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Operator {
+    pub name: String,
+    /// Shown instead of the name when displaying calls
+    pub symbol: Option<String>,
+    pub arity: usize,
+    /// Result for each combination of arguments, the first argument varying fastest
+    table: Vec<bool>,
+}
+
+impl Operator {
+    /// Tabulate `eval` over every combination of `arity` arguments.
+    ///
+    /// Panics if `arity` is larger than [`MAX_OPERATOR_ARITY`].
+    pub fn new(name: impl Into<String>, arity: usize, eval: impl Fn(&[bool]) -> bool) -> Self {
+        assert!(arity <= MAX_OPERATOR_ARITY, "operators take at most {} arguments", MAX_OPERATOR_ARITY);
+        let table = (0..1usize << arity)
+            .map(|row| {
+                let args: Vec<bool> = (0..arity).map(|i| (row >> i) & 1 == 1).collect();
+                eval(&args)
+            })
+            .collect();
+        Self { name: name.into(), symbol: None, arity, table }
+    }
+
+    /// Display calls as `symbol(args)` instead of `name(args)`
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    /// The operator's value on `args`, which must have `arity` entries
+    pub fn apply(&self, args: &[bool]) -> bool {
+        debug_assert_eq!(args.len(), self.arity);
+        let row = args.iter().enumerate().fold(0, |row, (i, &arg)| row | (usize::from(arg) << i));
+        self.table[row]
+    }
+
+    /// Results in row order, the first argument varying fastest
+    pub fn truth_table(&self) -> &[bool] {
+        &self.table
+    }
+
+    /// How calls are displayed
+    pub fn display_name(&self) -> &str {
+        self.symbol.as_deref().unwrap_or(&self.name)
+    }
+
+    /// An expression over the built-in operators equal to calling this operator
+    /// on `operands`, rebuilt from its truth table
+    pub fn expand(&self, operands: &[Expr]) -> Expr {
+        resynthesize(operands, &self.table)
+    }
+}
+
+/// Build an expression with the truth table `results` over `operands` by
+/// Shannon expansion on the last operand first, so that evaluating a row walks
+/// a single path
+pub(crate) fn resynthesize(operands: &[Expr], results: &[bool]) -> Expr {
+    let Some((x, rest)) = operands.split_last() else {
+        return if results[0] { Expr::True } else { Expr::False };
+    };
+    let (low, high) = results.split_at(results.len() / 2);
+    let (low, high) = (resynthesize(rest, low), resynthesize(rest, high));
+    let x = x.clone();
+    let not_x = || Expr::Not(Box::new(x.clone()));
+    match (low, high) {
+        (low, high) if low == high => low,
+        (Expr::False, Expr::True) => x,
+        (Expr::True, Expr::False) => not_x(),
+        (Expr::False, high) => Expr::And(Box::new(x), Box::new(high)),
+        (low, Expr::False) => Expr::And(Box::new(not_x()), Box::new(low)),
+        (Expr::True, high) => Expr::Or(Box::new(not_x()), Box::new(high)),
+        (low, Expr::True) => Expr::Or(Box::new(x), Box::new(low)),
+        (low, high) => Expr::Ite(Box::new(x), Box::new(high), Box::new(low)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{Library, Parser};
+    use crate::eval::Evaluator;
+
+    fn library() -> Library {
+        let mut library = Library::new();
+        let vote = Operator::new("vote", 3, |args| args.iter().filter(|&&a| a).count() >= 2);
+        library.register(vote).unwrap();
+        let consensus = Operator::new("consensus", 2, |args| args[0] == args[1]).with_symbol("≡");
+        library.register(consensus).unwrap();
+        library
+    }
+
+    fn parse(input: &str) -> Expr {
+        Parser::new(input).with_library(library()).parse().unwrap()
+    }
+
+    #[test]
+    fn test_operator_table() {
+        let vote = Operator::new("vote", 3, |args| args.iter().filter(|&&a| a).count() >= 2);
+        assert!(vote.apply(&[true, false, true]));
+        assert!(!vote.apply(&[false, false, true]));
+    }
+
+    #[test]
+    fn test_custom_operators_parse_and_evaluate() {
+        let expr = parse("vote(a, b and c, not d) or consensus(a, e)");
+        assert_eq!(expr.to_string(), "(vote(a, (b ∧ c), ¬d) ∨ ≡(a, e))");
+        let table = Evaluator::generate_truth_table(&expr).unwrap();
+        let expanded = Evaluator::generate_truth_table(&expr.expand_custom()).unwrap();
+        assert_eq!(table.rows.len(), 32);
+        for (row, expected) in table.rows.iter().zip(&expanded.rows) {
+            assert_eq!(row.result, expected.result);
+        }
+    }
+
+    #[test]
+    fn test_expansion_is_built_in() {
+        assert_eq!(parse("vote(a, b, c)").expand_custom().to_string(), "(c ? (b ∨ a) : (b ∧ a))");
+        assert!(matches!(
+            Parser::new("vote(a, b)").with_library(library()).parse(),
+            Err(crate::source::ParseError::ArityMismatch { expected: 3, found: 2, .. })
+        ));
+    }
+}
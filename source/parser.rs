@@ -1,29 +1,219 @@
-use crate::source::lexer::{Lexer, Token, SpannedToken, Span};
+use crate::source::lexer::{Lexer, Aliases, Token, SpannedToken, Span, Notation, quote_identifier, suggest_keyword, keyword};
+use crate::source::library::{Library, Template};
+use crate::source::operator::Operator;
+use crate::source::precedence::{Precedence, Associativity, BinaryOp};
+use crate::source::visit::ExprVisitor;
+use crate::config::MAX_NESTING_DEPTH;
 use std::fmt;
-use std::str::FromStr;
+use std::ops::ControlFlow;
 use thiserror::Error;
 use miette::{Diagnostic, SourceSpan};
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Expr {
     Identifier(String),
+    True,
+    False,
     Not(Box<Expr>),
     And(Box<Expr>, Box<Expr>),
     Or(Box<Expr>, Box<Expr>),
+    Nand(Box<Expr>, Box<Expr>),
+    Nor(Box<Expr>, Box<Expr>),
     Xor(Box<Expr>, Box<Expr>),
     Implication(Box<Expr>, Box<Expr>),
+    Iff(Box<Expr>, Box<Expr>),
+    /// If-then-else: the second operand when the first is true, otherwise the third
+    Ite(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// Counting constraint: how many of the operands are true compared with the bound
+    Cardinality(Cardinality, usize, Vec<Expr>),
+    /// Quantified boolean variable, bound within the body
+    Quantified(Quantifier, String, Box<Expr>),
+    /// Call of an operator registered in the library
+    Custom(Box<Operator>, Vec<Expr>),
+}
+
+/// Whether a quantified body must hold for every value of its variable or for some value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quantifier {
+    ForAll,
+    Exists,
+}
+
+impl Quantifier {
+    /// The word the parser reads for this quantifier
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Quantifier::ForAll => "forall",
+            Quantifier::Exists => "exists",
+        }
+    }
+}
+
+impl fmt::Display for Quantifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quantifier::ForAll => write!(f, "∀"),
+            Quantifier::Exists => write!(f, "∃"),
+        }
+    }
+}
+
+/// The comparison a cardinality constraint makes between its count and bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cardinality {
+    AtLeast,
+    AtMost,
+    Exactly,
+}
+
+impl Cardinality {
+    /// Whether `count` true operands satisfy the constraint with bound `k`
+    pub fn holds(self, k: usize, count: usize) -> bool {
+        match self {
+            Cardinality::AtLeast => count >= k,
+            Cardinality::AtMost => count <= k,
+            Cardinality::Exactly => count == k,
+        }
+    }
+}
+
+impl fmt::Display for Cardinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cardinality::AtLeast => write!(f, "atleast"),
+            Cardinality::AtMost => write!(f, "atmost"),
+            Cardinality::Exactly => write!(f, "exactly"),
+        }
+    }
+}
+
+impl Expr {
+    /// Number of nodes (operators and identifiers) in the expression tree
+    pub fn node_count(&self) -> usize {
+        match self {
+            Expr::Identifier(_) | Expr::True | Expr::False => 1,
+            Expr::Not(inner) => 1 + inner.node_count(),
+            Expr::And(left, right)
+            | Expr::Or(left, right)
+            | Expr::Nand(left, right)
+            | Expr::Nor(left, right)
+            | Expr::Xor(left, right)
+            | Expr::Implication(left, right)
+            | Expr::Iff(left, right) => 1 + left.node_count() + right.node_count(),
+            Expr::Ite(cond, then, otherwise) => 1 + cond.node_count() + then.node_count() + otherwise.node_count(),
+            Expr::Cardinality(_, _, operands) | Expr::Custom(_, operands) => 1 + operands.iter().map(Expr::node_count).sum::<usize>(),
+            Expr::Quantified(_, _, body) => 1 + body.node_count(),
+        }
+    }
+    
+    /// Operators on the longest path from the root to a leaf, measured without
+    /// recursion so that an expression of any depth can be checked
+    pub fn depth(&self) -> usize {
+        let mut deepest = 0;
+        let mut pending = vec![(self, 0)];
+        while let Some((expr, depth)) = pending.pop() {
+            deepest = deepest.max(depth);
+            match expr {
+                Expr::Identifier(_) | Expr::True | Expr::False => {}
+                Expr::Not(inner) | Expr::Quantified(_, _, inner) => pending.push((inner, depth + 1)),
+                Expr::Ite(cond, then, otherwise) => pending.extend([cond, then, otherwise].map(|operand| (&**operand, depth + 1))),
+                Expr::Cardinality(_, _, operands) | Expr::Custom(_, operands) => {
+                    pending.extend(operands.iter().map(|operand| (operand, depth + 1)));
+                }
+                _ => {
+                    let (_, left, right) = expr.as_binary().expect("every other node is binary");
+                    pending.extend([(left, depth + 1), (right, depth + 1)]);
+                }
+            }
+        }
+        deepest
+    }
+    
+    /// Expand every quantifier over its two cases, `∀x. e` to `e[x:=false] ∧ e[x:=true]`
+    /// and `∃x. e` to `e[x:=false] ∨ e[x:=true]`
+    pub fn eliminate_quantifiers(&self) -> Expr {
+        match self {
+            Expr::Quantified(quantifier, var, body) => {
+                let body = body.eliminate_quantifiers();
+                let case = |value: Expr| substitute(&body, &[(var.clone(), value)]);
+                let (low, high) = (Box::new(case(Expr::False)), Box::new(case(Expr::True)));
+                match quantifier {
+                    Quantifier::ForAll => Expr::And(low, high),
+                    Quantifier::Exists => Expr::Or(low, high),
+                }
+            }
+            Expr::Identifier(_) | Expr::True | Expr::False => self.clone(),
+            Expr::Not(inner) => Expr::Not(Box::new(inner.eliminate_quantifiers())),
+            Expr::And(l, r) => Expr::And(Box::new(l.eliminate_quantifiers()), Box::new(r.eliminate_quantifiers())),
+            Expr::Or(l, r) => Expr::Or(Box::new(l.eliminate_quantifiers()), Box::new(r.eliminate_quantifiers())),
+            Expr::Nand(l, r) => Expr::Nand(Box::new(l.eliminate_quantifiers()), Box::new(r.eliminate_quantifiers())),
+            Expr::Nor(l, r) => Expr::Nor(Box::new(l.eliminate_quantifiers()), Box::new(r.eliminate_quantifiers())),
+            Expr::Xor(l, r) => Expr::Xor(Box::new(l.eliminate_quantifiers()), Box::new(r.eliminate_quantifiers())),
+            Expr::Implication(l, r) => Expr::Implication(Box::new(l.eliminate_quantifiers()), Box::new(r.eliminate_quantifiers())),
+            Expr::Iff(l, r) => Expr::Iff(Box::new(l.eliminate_quantifiers()), Box::new(r.eliminate_quantifiers())),
+            Expr::Ite(c, t, e) => Expr::Ite(
+                Box::new(c.eliminate_quantifiers()),
+                Box::new(t.eliminate_quantifiers()),
+                Box::new(e.eliminate_quantifiers()),
+            ),
+            Expr::Cardinality(kind, k, operands) => {
+                Expr::Cardinality(*kind, *k, operands.iter().map(Expr::eliminate_quantifiers).collect())
+            }
+            Expr::Custom(operator, operands) => Expr::Custom(operator.clone(), operands.iter().map(Expr::eliminate_quantifiers).collect()),
+        }
+    }
+    
+    /// Replace every custom operator call with an equivalent expression over the
+    /// built-in operators, for the algorithms that only know those
+    pub fn expand_custom(&self) -> Expr {
+        match self {
+            Expr::Custom(operator, operands) => {
+                let operands: Vec<Expr> = operands.iter().map(Expr::expand_custom).collect();
+                operator.expand(&operands)
+            }
+            Expr::Identifier(_) | Expr::True | Expr::False => self.clone(),
+            Expr::Not(inner) => Expr::Not(Box::new(inner.expand_custom())),
+            Expr::And(l, r) => Expr::And(Box::new(l.expand_custom()), Box::new(r.expand_custom())),
+            Expr::Or(l, r) => Expr::Or(Box::new(l.expand_custom()), Box::new(r.expand_custom())),
+            Expr::Nand(l, r) => Expr::Nand(Box::new(l.expand_custom()), Box::new(r.expand_custom())),
+            Expr::Nor(l, r) => Expr::Nor(Box::new(l.expand_custom()), Box::new(r.expand_custom())),
+            Expr::Xor(l, r) => Expr::Xor(Box::new(l.expand_custom()), Box::new(r.expand_custom())),
+            Expr::Implication(l, r) => Expr::Implication(Box::new(l.expand_custom()), Box::new(r.expand_custom())),
+            Expr::Iff(l, r) => Expr::Iff(Box::new(l.expand_custom()), Box::new(r.expand_custom())),
+            Expr::Ite(c, t, e) => Expr::Ite(Box::new(c.expand_custom()), Box::new(t.expand_custom()), Box::new(e.expand_custom())),
+            Expr::Cardinality(kind, k, operands) => Expr::Cardinality(*kind, *k, operands.iter().map(Expr::expand_custom).collect()),
+            Expr::Quantified(quantifier, var, body) => Expr::Quantified(*quantifier, var.clone(), Box::new(body.expand_custom())),
+        }
+    }
 }
 
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Expr::Identifier(name) => write!(f, "{}", name),
+            Expr::Identifier(name) => write!(f, "{}", quote_identifier(name)),
+            Expr::True => write!(f, "true"),
+            Expr::False => write!(f, "false"),
             Expr::Not(expr) => write!(f, "¬{}", expr),
             Expr::And(left, right) => write!(f, "({} ∧ {})", left, right),
             Expr::Or(left, right) => write!(f, "({} ∨ {})", left, right),
+            Expr::Nand(left, right) => write!(f, "({} ↑ {})", left, right),
+            Expr::Nor(left, right) => write!(f, "({} ↓ {})", left, right),
             Expr::Xor(left, right) => write!(f, "({} ⊕ {})", left, right),
             Expr::Implication(left, right) => write!(f, "({} → {})", left, right),
+            Expr::Iff(left, right) => write!(f, "({} ↔ {})", left, right),
+            Expr::Ite(cond, then, otherwise) => write!(f, "({} ? {} : {})", cond, then, otherwise),
+            Expr::Cardinality(kind, k, operands) => {
+                let operands: Vec<String> = operands.iter().map(Expr::to_string).collect();
+                write!(f, "{}({}; {})", kind, k, operands.join(", "))
+            }
+            Expr::Quantified(quantifier, var, body) => write!(f, "({}{}. {})", quantifier, quote_identifier(var), body),
+            Expr::Custom(operator, operands) => {
+                let operands: Vec<String> = operands.iter().map(Expr::to_string).collect();
+                write!(f, "{}({})", operator.display_name(), operands.join(", "))
+            }
         }
     }
 }
@@ -42,6 +232,40 @@ pub enum ParseError {
         span: SourceSpan,
     },
     
+    #[error("Missing operator between `{left}` and `{right}`")]
+    #[diagnostic(code(ttt::parser::missing_operator))]
+    MissingOperator {
+        left: String,
+        right: String,
+        /// Names [`Parser::with_implicit_and`]; a front end can point to its own switch instead
+        #[help]
+        help: ImplicitAndHint,
+        #[label("operator expected here")]
+        span: SourceSpan,
+    },
+    
+    #[error("Unknown character{} '{text}'", if text.chars().count() > 1 { "s" } else { "" })]
+    #[diagnostic(code(ttt::lexer::unknown_character))]
+    UnknownCharacter {
+        text: String,
+        #[help]
+        help: String,
+        #[label("not part of any operator")]
+        span: SourceSpan,
+    },
+    
+    #[error("'{word}' is not a keyword")]
+    #[diagnostic(
+        code(ttt::parser::misspelled_keyword),
+        help("Did you mean `{suggestion}`?")
+    )]
+    MisspelledKeyword {
+        word: String,
+        suggestion: String,
+        #[label("read as a variable")]
+        span: SourceSpan,
+    },
+    
     #[error("Unexpected end of input")]
     #[diagnostic(
         code(ttt::parser::unexpected_eof),
@@ -52,6 +276,59 @@ pub enum ParseError {
         span: SourceSpan,
     },
     
+    #[error("'{name}' expects {expected} argument(s), found {found}")]
+    #[diagnostic(code(ttt::parser::arity_mismatch))]
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+        #[label("called here")]
+        span: SourceSpan,
+    },
+    
+    #[error("'{name}' is defined in terms of itself")]
+    #[diagnostic(
+        code(ttt::parser::recursive_definition),
+        help("Expansion chain: {chain}")
+    )]
+    RecursiveDefinition {
+        name: String,
+        chain: String,
+        #[label("expanded here")]
+        span: SourceSpan,
+    },
+    
+    #[error("Invalid definition of '{name}': {reason}")]
+    #[diagnostic(code(ttt::parser::invalid_definition))]
+    InvalidDefinition {
+        name: String,
+        reason: String,
+        #[label("used here")]
+        span: SourceSpan,
+    },
+    
+    #[error("'{name}' is ambiguous with implicit and")]
+    #[diagnostic(
+        code(ttt::parser::ambiguous_juxtaposition),
+        help("Words are split into single-letter variables, which isn't possible here; write the letters apart, or drop --implicit-and to use '{name}' as one variable")
+    )]
+    AmbiguousJuxtaposition {
+        name: String,
+        #[label("cannot be split into letters")]
+        span: SourceSpan,
+    },
+    
+    #[error("Expression nests more than {limit} levels deep")]
+    #[diagnostic(
+        code(ttt::parser::too_deep),
+        help("Deeper expressions could overflow the stack; flatten the expression or split it into library definitions")
+    )]
+    TooDeep {
+        limit: usize,
+        #[label("limit reached here")]
+        span: SourceSpan,
+    },
+    
     #[error("Invalid expression")]
     #[diagnostic(code(ttt::parser::invalid_expression))]
     InvalidExpression {
@@ -60,110 +337,599 @@ pub enum ParseError {
     },
 }
 
-pub struct Parser {
-    tokens: Vec<SpannedToken>,
-    current: usize,
+/// The help of [`ParseError::MissingOperator`], naming the switch that reads
+/// adjacent operands as `and` the way the caller offers it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImplicitAndHint {
+    /// [`Parser::with_implicit_and`]
+    Library,
+    /// The command line's `--implicit-and`
+    Flag,
 }
 
-impl Parser {
-    pub fn new(input: &str) -> Self {
-        let mut lexer = Lexer::new(input);
-        let tokens = lexer.tokenize_spanned();
-        Self { tokens, current: 0 }
+impl fmt::Display for ImplicitAndHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let switch = match self {
+            ImplicitAndHint::Library => "build the parser with `Parser::with_implicit_and`",
+            ImplicitAndHint::Flag => "pass --implicit-and",
+        };
+        write!(f, "Join them with one of: and, or, xor, nand, nor, ->, <->; or {} to read adjacent operands as `and`", switch)
+    }
+}
+
+impl ParseError {
+    /// Where in the input the error was found
+    pub fn span(&self) -> SourceSpan {
+        match self {
+            ParseError::UnexpectedToken { span, .. }
+            | ParseError::MissingOperator { span, .. }
+            | ParseError::UnknownCharacter { span, .. }
+            | ParseError::MisspelledKeyword { span, .. }
+            | ParseError::UnexpectedEof { span }
+            | ParseError::ArityMismatch { span, .. }
+            | ParseError::RecursiveDefinition { span, .. }
+            | ParseError::InvalidDefinition { span, .. }
+            | ParseError::AmbiguousJuxtaposition { span, .. }
+            | ParseError::TooDeep { span, .. }
+            | ParseError::InvalidExpression { span } => *span,
+        }
+    }
+}
+
+/// Every error found in one expression, each rendered with its own label
+#[derive(Error, Debug, Diagnostic)]
+#[error("Found {} errors in the expression", errors.len())]
+#[diagnostic(code(ttt::parser::multiple_errors))]
+pub struct ParseErrors {
+    #[related]
+    pub errors: Vec<ParseError>,
+}
+
+/// How operators are placed relative to their operands
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Syntax {
+    /// Binary operators between their operands, e.g. `(a and b) -> c`
+    #[default]
+    Infix,
+    /// Polish notation: every operator before its operands, e.g. `-> and a b c`,
+    /// which needs no parentheses
+    Prefix,
+}
+
+/// Levels of nesting parsed on the calling thread, which may have little stack
+/// to spare; deeper input is parsed on a thread of its own
+const INLINE_NESTING: usize = 32;
+
+/// Stack such a thread gets for each level, with room to spare for the
+/// frames of an unoptimized build
+const STACK_PER_LEVEL: usize = 16 << 10;
+
+/// Stack such a thread gets on top of that
+const INLINE_STACK: usize = 256 << 10;
+
+pub struct Parser<'a> {
+    /// Tokens after the current one, read as parsing reaches them
+    lexer: Lexer<'a>,
+    current: SpannedToken<'a>,
+    previous: Option<SpannedToken<'a>>,
+    library: Option<Library>,
+    /// Definitions currently being expanded, outermost first (for recursion checks)
+    expanding: Vec<String>,
+    /// Template parameters in scope, which shadow library definitions of the same name
+    shadowed: Vec<String>,
+    /// Adjacent operands are conjoined and words split into single-letter variables
+    implicit_and: bool,
+    precedence: Precedence,
+    implication: Associativity,
+    syntax: Syntax,
+    /// Most levels of nesting read before giving up, and the levels open now,
+    /// counting the outermost operator as the first
+    max_nesting: usize,
+    nesting: usize,
+    /// Parentheses open now, which nest the expression no deeper but take
+    /// stack all the same; at most `max_nesting` of them
+    parens: usize,
+    /// Record errors and keep parsing instead of stopping at the first one
+    recovering: bool,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::new_with_notation(input, Notation::Standard)
+    }
+    
+    /// Create a parser that also accepts the operators of `notation`
+    pub fn new_with_notation(input: &'a str, notation: Notation) -> Self {
+        let mut lexer = Lexer::new(input).with_notation(notation);
+        let current = lexer.next().expect("the lexer yields at least Eof");
+        Self {
+            lexer,
+            current,
+            previous: None,
+            library: None,
+            expanding: Vec::new(),
+            shadowed: Vec::new(),
+            implicit_and: false,
+            precedence: Precedence::Standard,
+            implication: Associativity::Right,
+            syntax: Syntax::Infix,
+            max_nesting: MAX_NESTING_DEPTH,
+            nesting: 0,
+            parens: 0,
+            recovering: false,
+            errors: Vec::new(),
+        }
     }
     
+    /// Expand references to the library's definitions while parsing, and read
+    /// its aliases as operators
+    pub fn with_library(mut self, library: Library) -> Self {
+        let aliases = library.aliases().clone();
+        self.library = Some(library);
+        self.with_aliases(aliases)
+    }
+    
+    /// Read the words of `aliases` as the operators they stand for, on top of
+    /// any aliases already in use
+    pub fn with_aliases(mut self, aliases: Aliases) -> Self {
+        let mut combined = self.lexer.aliases().clone();
+        combined.extend(&aliases);
+        // The first token was read before the aliases were known
+        if let Token::Identifier(word) = self.current.token
+            && !is_quoted(&self.current)
+            && let Some(token) = combined.get(word)
+        {
+            self.current.token = token;
+        }
+        self.lexer = self.lexer.with_aliases(combined);
+        self
+    }
+    
+    /// Read adjacent operands as a conjunction, so `AB + C'D` is `(A ∧ B) ∨ (¬C ∧ D)`.
+    ///
+    /// Identifiers are split into single-letter variables unless they name a
+    /// library definition.
+    pub fn with_implicit_and(mut self) -> Self {
+        self.implicit_and = true;
+        self
+    }
+    
+    /// Read keywords in any case, e.g. `a AND NOT b`
+    pub fn with_case_insensitive_keywords(mut self) -> Self {
+        self.lexer = self.lexer.with_case_insensitive_keywords();
+        // The first token was read as it was written
+        if let Token::Identifier(word) = self.current.token
+            && !is_quoted(&self.current)
+            && let Some(token) = keyword(&word.to_lowercase())
+        {
+            self.current.token = token;
+        }
+        self
+    }
     
-    fn current_token(&self) -> SpannedToken {
-        self.tokens.get(self.current).cloned().unwrap_or_else(|| {
-            // Create EOF token at the end of input
-            let end_pos = self.tokens.last()
-                .map(|t| t.span.end)
-                .unwrap_or(0);
-            SpannedToken {
-                token: Token::Eof,
-                span: Span::single(end_pos),
+    /// Rank the binary operators by `precedence` instead of the standard profile
+    pub fn with_precedence(mut self, precedence: Precedence) -> Self {
+        self.precedence = precedence;
+        self
+    }
+    
+    /// Group chains of implications as `associativity` says instead of to the right
+    pub fn with_implication_associativity(mut self, associativity: Associativity) -> Self {
+        self.implication = associativity;
+        self
+    }
+    
+    /// Read operators in `syntax` instead of between their operands
+    pub fn with_syntax(mut self, syntax: Syntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+    
+    /// Read expressions at most `max_nesting` levels deep, as [`Expr::depth`]
+    /// counts them, and as many parentheses inside one another, instead of
+    /// [`MAX_NESTING_DEPTH`]
+    pub fn with_max_nesting(mut self, max_nesting: usize) -> Self {
+        self.max_nesting = max_nesting;
+        self
+    }
+    
+    /// Whether the current token can begin an operand of an implicit and
+    fn at_operand(&self) -> bool {
+        self.implicit_and && matches!(
+            self.current_token().token,
+            Token::Identifier(_) | Token::True | Token::False | Token::LeftParen | Token::Not | Token::Ite | Token::Cardinality(_) | Token::Quantifier(_)
+        )
+    }
+    
+    fn template(&self, name: &str) -> Option<Template> {
+        if self.shadowed.iter().any(|p| p == name) {
+            return None;
+        }
+        self.library.as_ref()?.get(name).cloned()
+    }
+    
+    fn operator(&self, name: &str) -> Option<Operator> {
+        if self.shadowed.iter().any(|p| p == name) {
+            return None;
+        }
+        self.library.as_ref()?.operator(name).cloned()
+    }
+    
+    /// Parenthesized, comma-separated arguments of a call; none if no `(` follows
+    fn parse_arguments(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut args = Vec::new();
+        if matches!(self.current_token().token, Token::LeftParen) {
+            self.advance();
+            if !matches!(self.current_token().token, Token::RightParen) {
+                args.push(self.parse_conditional()?);
+                while matches!(self.current_token().token, Token::Comma) {
+                    self.advance();
+                    args.push(self.parse_conditional()?);
+                }
             }
-        })
+            self.expect(Token::RightParen)?;
+        }
+        Ok(args)
+    }
+    
+    /// Call a custom operator; the current token is the identifier naming it
+    fn parse_custom(&mut self, operator: Operator, span: Span) -> Result<Expr, ParseError> {
+        self.advance();
+        let args = self.parse_arguments()?;
+        if args.len() != operator.arity {
+            return Err(ParseError::ArityMismatch {
+                name: operator.name,
+                expected: operator.arity,
+                found: args.len(),
+                span: SourceSpan::from(span.start..span.end),
+            });
+        }
+        Ok(Expr::Custom(Box::new(operator), args))
+    }
+    
+    /// Instantiate a definition; the current token is the identifier naming it
+    fn expand_template(&mut self, template: Template, span: Span) -> Result<Expr, ParseError> {
+        let call_span = SourceSpan::from(span.start..span.end);
+        self.advance();
+        let args = self.parse_arguments()?;
+        
+        if args.len() != template.params.len() {
+            return Err(ParseError::ArityMismatch {
+                name: template.name,
+                expected: template.params.len(),
+                found: args.len(),
+                span: call_span,
+            });
+        }
+        
+        if self.expanding.contains(&template.name) {
+            let mut chain = self.expanding.clone();
+            chain.push(template.name.clone());
+            return Err(ParseError::RecursiveDefinition {
+                name: template.name,
+                chain: chain.join(" → "),
+                span: call_span,
+            });
+        }
+        
+        let mut body_parser = Parser::new(&template.body).with_aliases(self.lexer.aliases().clone());
+        if self.lexer.is_case_insensitive() {
+            body_parser = body_parser.with_case_insensitive_keywords();
+        }
+        body_parser.library = self.library.clone();
+        body_parser.precedence = self.precedence;
+        body_parser.implication = self.implication;
+        body_parser.max_nesting = self.room();
+        body_parser.parens = self.parens;
+        body_parser.expanding = self.expanding.clone();
+        body_parser.expanding.push(template.name.clone());
+        body_parser.shadowed = template.params.clone();
+        
+        let body = body_parser.parse().map_err(|e| match e {
+            // Keep the outermost call site as the label but preserve the recursion chain
+            ParseError::RecursiveDefinition { name, chain, .. } => ParseError::RecursiveDefinition { name, chain, span: call_span },
+            ParseError::ArityMismatch { name, expected, found, .. } => ParseError::ArityMismatch { name, expected, found, span: call_span },
+            ParseError::InvalidDefinition { name, reason, .. } => ParseError::InvalidDefinition { name, reason, span: call_span },
+            other => ParseError::InvalidDefinition {
+                name: template.name.clone(),
+                reason: other.to_string(),
+                span: call_span,
+            },
+        })?;
+        
+        let bindings: Vec<(String, Expr)> = template.params.into_iter().zip(args).collect();
+        Ok(substitute(&body, &bindings))
     }
     
+    
+    fn current_token(&self) -> SpannedToken<'a> {
+        self.current.clone()
+    }
+    
+    /// Move to the next token, staying on `Eof` once it is reached
     fn advance(&mut self) {
-        if self.current < self.tokens.len().saturating_sub(1) {
-            self.current += 1;
+        if let Some(next) = self.lexer.next() {
+            self.previous = Some(std::mem::replace(&mut self.current, next));
         }
     }
     
+    /// Consume `expected`; in recovery mode a missing token is reported and
+    /// parsing carries on as if it had been there
     fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
         let current = self.current_token();
         if std::mem::discriminant(&current.token) == std::mem::discriminant(&expected) {
             self.advance();
             Ok(())
         } else {
-            Err(ParseError::UnexpectedToken {
-                expected: format!("{}", expected),
-                found: format!("{}", current.token),
-                span: SourceSpan::from(current.span.start..current.span.end),
-            })
+            self.report(self.unexpected(&expected.to_string()))
         }
     }
     
-    pub fn parse(&mut self) -> Result<Expr, ParseError> {
-        let expr = self.parse_implication()?;
-        
+    /// The error for finding the current token where `expected` should be,
+    /// pointing at the whole run when it is a character no token starts with
+    fn unexpected(&self, expected: &str) -> ParseError {
         let current = self.current_token();
-        if !matches!(current.token, Token::Eof) {
-            return Err(ParseError::UnexpectedToken {
-                expected: "end of input".to_string(),
+        // A misspelled operator reads as a variable, so the error lands on it or,
+        // when it stood where an operand belongs, on the token after it
+        let starts_operand = matches!(
+            current.token,
+            Token::Identifier(_) | Token::True | Token::False | Token::LeftParen | Token::Not | Token::Ite | Token::Cardinality(_) | Token::Quantifier(_)
+        );
+        let previous = self.previous.as_ref().filter(|_| starts_operand);
+        for candidate in std::iter::once(&current).chain(previous) {
+            if let Token::Identifier(word) = &candidate.token
+                && !is_quoted(candidate)
+                && let Some(suggestion) = suggest_keyword(word)
+            {
+                return ParseError::MisspelledKeyword {
+                    word: word.to_string(),
+                    suggestion: suggestion.to_string(),
+                    span: SourceSpan::from(candidate.span.start..candidate.span.end),
+                };
+            }
+        }
+        
+        // Adjacent operands are expected in prefix syntax, so there they are merely one too many
+        if let (Some(previous), Token::Identifier(right)) = (&self.previous, &current.token)
+            && self.syntax == Syntax::Infix
+            && let Token::Identifier(left) = &previous.token
+        {
+            return ParseError::MissingOperator {
+                left: left.to_string(),
+                right: right.to_string(),
+                help: ImplicitAndHint::Library,
+                span: SourceSpan::from(previous.span.end..current.span.start),
+            };
+        }
+        
+        if !matches!(current.token, Token::Unknown(_)) {
+            return ParseError::UnexpectedToken {
+                expected: expected.to_string(),
                 found: format!("{}", current.token),
                 span: SourceSpan::from(current.span.start..current.span.end),
-            });
+            };
         }
         
-        Ok(expr)
+        let mut text = String::new();
+        let mut end = current.span.start;
+        // Look ahead on a copy of the lexer; this only happens once parsing has failed
+        for token in std::iter::once(current.clone()).chain(self.lexer.clone()) {
+            match token.token {
+                Token::Unknown(ch) if token.span.start == end => {
+                    text.push(ch);
+                    end = token.span.end;
+                }
+                _ => break,
+            }
+        }
+        ParseError::UnknownCharacter {
+            help: unknown_character_help(&text),
+            text,
+            span: SourceSpan::from(current.span.start..end),
+        }
     }
     
-    fn parse_implication(&mut self) -> Result<Expr, ParseError> {
-        let mut left = self.parse_or()?;
-        
-        while matches!(self.current_token().token, Token::Implication) {
-            self.advance();
-            let right = self.parse_or()?;
-            left = Expr::Implication(Box::new(left), Box::new(right));
+    /// Run `parse` one level of nesting deeper, failing at the limit rather
+    /// than recursing until the stack overflows
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, ParseError>) -> Result<T, ParseError> {
+        if self.room() == 0 {
+            return Err(self.too_deep(self.max_nesting));
         }
-        
-        Ok(left)
+        self.nesting += 1;
+        let result = parse(self);
+        self.nesting -= 1;
+        result
     }
     
-    fn parse_or(&mut self) -> Result<Expr, ParseError> {
-        let mut left = self.parse_xor()?;
-        
-        while matches!(self.current_token().token, Token::Or) {
-            self.advance();
-            let right = self.parse_xor()?;
-            left = Expr::Or(Box::new(left), Box::new(right));
+    /// Levels the expression may still nest below the one being read
+    fn room(&self) -> usize {
+        (self.max_nesting + 1).saturating_sub(self.nesting)
+    }
+    
+    /// Run `parse` inside one more pair of parentheses
+    fn parenthesized<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, ParseError>) -> Result<T, ParseError> {
+        if self.parens >= self.max_nesting {
+            return Err(self.too_deep(self.max_nesting));
         }
-        
-        Ok(left)
+        self.parens += 1;
+        let result = parse(self);
+        self.parens -= 1;
+        result
     }
     
-    fn parse_xor(&mut self) -> Result<Expr, ParseError> {
-        let mut left = self.parse_and()?;
-        
-        while matches!(self.current_token().token, Token::Xor) {
+    /// The error for reaching `limit` levels of nesting at the current token
+    fn too_deep(&self, limit: usize) -> ParseError {
+        let current = self.current_token();
+        ParseError::TooDeep {
+            limit,
+            span: SourceSpan::from(current.span.start..current.span.end),
+        }
+    }
+    
+    /// Record `error` in recovery mode, or return it
+    fn report(&mut self, error: ParseError) -> Result<(), ParseError> {
+        if !self.recovering {
+            return Err(error);
+        }
+        // One mistake often trips several checks at the same place; keep the first
+        let offset = error.span().offset();
+        if self.errors.last().is_none_or(|last| last.span().offset() != offset) {
+            self.errors.push(error);
+        }
+        Ok(())
+    }
+    
+    /// Report an operand that failed to parse, skip ahead to the next operator or
+    /// closing delimiter outside any parentheses, and stand in `false` for it
+    fn recover(&mut self, error: ParseError) -> Result<Expr, ParseError> {
+        // Every enclosing level would hit the limit again
+        if matches!(error, ParseError::TooDeep { .. }) {
+            return Err(error);
+        }
+        self.report(error)?;
+        let mut depth = 0usize;
+        loop {
+            match self.current_token().token {
+                Token::Eof => break,
+                Token::LeftParen => depth += 1,
+                Token::RightParen if depth > 0 => depth -= 1,
+                Token::RightParen | Token::Comma | Token::Semicolon | Token::Colon | Token::Question
+                | Token::And | Token::Or | Token::Nand | Token::Nor | Token::Xor | Token::Implication | Token::Iff
+                    if depth == 0 => break,
+                _ => {}
+            }
             self.advance();
-            let right = self.parse_and()?;
-            left = Expr::Xor(Box::new(left), Box::new(right));
         }
+        Ok(Expr::False)
+    }
+    
+    pub fn parse(&mut self) -> Result<Expr, ParseError> {
+        // Each level of nesting takes several frames, several kilobytes in an
+        // unoptimized build, so deep input gets a stack sized for it
+        let levels = self.nesting_bound();
+        if levels <= INLINE_NESTING {
+            return self.parse_here();
+        }
+        let builder = std::thread::Builder::new().stack_size(levels * STACK_PER_LEVEL + INLINE_STACK);
+        let spawned = std::thread::scope(|scope| builder.spawn_scoped(scope, || self.parse_here()).ok().map(|handle| handle.join()));
+        match spawned {
+            Some(Ok(result)) => result,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            // No thread to be had; the limit still stops the recursion
+            None => self.parse_here(),
+        }
+    }
+    
+    /// The most levels the rest of the input could nest, up to the limit: one
+    /// for each token that opens a level
+    fn nesting_bound(&self) -> usize {
+        let prefix = self.syntax == Syntax::Prefix;
+        std::iter::once(self.current.clone())
+            .chain(self.lexer.clone())
+            .filter(|token| match token.token {
+                Token::LeftParen | Token::Not | Token::Ite | Token::Cardinality(_) | Token::Quantifier(_)
+                | Token::Comma | Token::Question | Token::Implication => true,
+                _ => prefix && BinaryOp::from_token(&token.token).is_some(),
+            })
+            .take(self.room() + self.max_nesting.saturating_sub(self.parens))
+            .count()
+    }
+    
+    fn parse_here(&mut self) -> Result<Expr, ParseError> {
+        let expr = match self.syntax {
+            Syntax::Infix => self.parse_conditional()?,
+            Syntax::Prefix => self.parse_prefix()?,
+        };
         
-        Ok(left)
+        if !matches!(self.current_token().token, Token::Eof) {
+            self.report(self.unexpected("end of input"))?;
+        }
+        // Expanded definitions can nest deeper than what was read
+        if expr.depth() > self.max_nesting {
+            return Err(ParseError::TooDeep {
+                limit: self.max_nesting,
+                span: SourceSpan::from(0..self.current.span.end),
+            });
+        }
+        Ok(expr)
     }
     
-    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+    /// Parse the whole input, resynchronizing after each error so that every
+    /// error in the expression is reported rather than only the first
+    pub fn parse_recovering(&mut self) -> Result<Expr, Vec<ParseError>> {
+        self.recovering = true;
+        let result = self.parse();
+        self.recovering = false;
+        let mut errors = std::mem::take(&mut self.errors);
+        match result {
+            Ok(expr) if errors.is_empty() => Ok(expr),
+            Ok(_) => Err(errors),
+            Err(error) => {
+                errors.push(error);
+                Err(errors)
+            }
+        }
+    }
+    
+    /// An operand one level deeper than the one being read
+    fn parse_conditional(&mut self) -> Result<Expr, ParseError> {
+        self.nested(Self::parse_group)
+    }
+    
+    /// `cond ? then : else`, binding looser than every other operator and
+    /// nesting to the right
+    fn parse_group(&mut self) -> Result<Expr, ParseError> {
+        let cond = self.parse_binary(0)?;
+        if !matches!(self.current_token().token, Token::Question) {
+            return Ok(cond);
+        }
+        self.advance();
+        let then = self.parse_conditional()?;
+        self.expect(Token::Colon)?;
+        let otherwise = self.parse_conditional()?;
+        Ok(Expr::Ite(Box::new(cond), Box::new(then), Box::new(otherwise)))
+    }
+    
+    /// Binary operators at `level` and tighter, by precedence climbing so that
+    /// reading an operand costs one call whatever the number of levels; operators
+    /// group to the left except for right-associative implication, and the
+    /// precedence profile decides which operators share a level
+    fn parse_binary(&mut self, level: usize) -> Result<Expr, ParseError> {
+        let nesting = self.nesting;
+        let result = self.parse_chain(level);
+        self.nesting = nesting;
+        result
+    }
+    
+    fn parse_chain(&mut self, level: usize) -> Result<Expr, ParseError> {
         let mut left = self.parse_unary()?;
         
-        while matches!(self.current_token().token, Token::And) {
-            self.advance();
-            let right = self.parse_unary()?;
-            left = Expr::And(Box::new(left), Box::new(right));
+        loop {
+            let (op, juxtaposed) = match BinaryOp::from_token(&self.current_token().token) {
+                Some(op) => (op, false),
+                // Juxtaposed operands are conjoined at the level of and
+                None if self.at_operand() => (BinaryOp::And, true),
+                None => break,
+            };
+            let op_level = self.precedence.level(op);
+            if op_level < level {
+                break;
+            }
+            // Each operand read here nests the ones before it a level deeper
+            if self.room() == 0 {
+                return Err(self.too_deep(self.max_nesting));
+            }
+            self.nesting += 1;
+            if !juxtaposed {
+                self.advance();
+            }
+            let right = if op == BinaryOp::Implication && self.implication == Associativity::Right {
+                self.parse_binary(op_level)?
+            } else {
+                self.parse_binary(op_level + 1)?
+            };
+            left = op.build(left, right);
         }
         
         Ok(left)
@@ -174,10 +940,94 @@ impl Parser {
         match current.token {
             Token::Not => {
                 self.advance();
-                let expr = self.parse_unary()?;
+                let expr = self.nested(Self::parse_unary)?;
                 Ok(Expr::Not(Box::new(expr)))
             }
-            _ => self.parse_primary(),
+            Token::Quantifier(quantifier) => {
+                self.advance();
+                self.parse_quantified(quantifier).or_else(|e| self.recover(e))
+            }
+            _ => {
+                let mut expr = self.parse_primary().or_else(|e| self.recover(e))?;
+                let mut primes = 0;
+                while matches!(self.current_token().token, Token::Prime) {
+                    // Read in a loop, but each prime nests a `not` like a prefix one
+                    primes += 1;
+                    if primes > self.room() {
+                        return Err(self.too_deep(self.max_nesting));
+                    }
+                    self.advance();
+                    expr = Expr::Not(Box::new(expr));
+                }
+                Ok(expr)
+            }
+        }
+    }
+    
+    /// `x, y. body` after a quantifier; the body extends as far right as possible
+    fn parse_quantified(&mut self, quantifier: Quantifier) -> Result<Expr, ParseError> {
+        let current = self.current_token();
+        let Token::Identifier(var) = current.token else {
+            return Err(self.unexpected("a variable to quantify"));
+        };
+        self.advance();
+        let body = if matches!(self.current_token().token, Token::Comma) {
+            self.advance();
+            self.nested(|parser| parser.parse_quantified(quantifier))?
+        } else {
+            self.expect(Token::Dot)?;
+            self.parse_conditional()?
+        };
+        Ok(Expr::Quantified(quantifier, var.to_string(), Box::new(body)))
+    }
+    
+    /// An operator followed by as many operands as it takes: two for binary
+    /// operators, one for `not`, three for `ite`, and a variable and a body for
+    /// quantifiers, whose `.` is optional. Parentheses may group an operand.
+    fn parse_prefix(&mut self) -> Result<Expr, ParseError> {
+        self.nested(Self::parse_prefix_operator)
+    }
+    
+    fn parse_prefix_operator(&mut self) -> Result<Expr, ParseError> {
+        let current = self.current_token();
+        if let Some(op) = BinaryOp::from_token(&current.token) {
+            self.advance();
+            let left = self.parse_prefix()?;
+            let right = self.parse_prefix()?;
+            return Ok(op.build(left, right));
+        }
+        match current.token {
+            Token::Not => {
+                self.advance();
+                Ok(Expr::Not(Box::new(self.parse_prefix()?)))
+            }
+            Token::Ite => {
+                self.advance();
+                let cond = self.parse_prefix()?;
+                let then = self.parse_prefix()?;
+                let otherwise = self.parse_prefix()?;
+                Ok(Expr::Ite(Box::new(cond), Box::new(then), Box::new(otherwise)))
+            }
+            Token::Quantifier(quantifier) => {
+                self.advance();
+                let Token::Identifier(var) = self.current_token().token else {
+                    return Err(self.unexpected("a variable to quantify"));
+                };
+                self.advance();
+                if matches!(self.current_token().token, Token::Dot) {
+                    self.advance();
+                }
+                let body = self.parse_prefix()?;
+                Ok(Expr::Quantified(quantifier, var.to_string(), Box::new(body)))
+            }
+            Token::LeftParen => {
+                self.advance();
+                let expr = self.parenthesized(Self::parse_prefix_operator)?;
+                self.expect(Token::RightParen)?;
+                Ok(expr)
+            }
+            Token::Identifier(_) | Token::True | Token::False | Token::Eof => self.parse_primary(),
+            _ => Err(self.unexpected("an operator, identifier or constant")),
         }
     }
     
@@ -185,94 +1035,258 @@ impl Parser {
         let current = self.current_token();
         match &current.token {
             Token::Identifier(name) => {
-                let name = name.clone();
+                if let Some(template) = self.template(name) {
+                    return self.expand_template(template, current.span);
+                }
+                if let Some(operator) = self.operator(name) {
+                    return self.parse_custom(operator, current.span);
+                }
+                let name = *name;
+                self.advance();
+                // A quoted name is never split
+                if self.implicit_and && !is_quoted(&current) && name.chars().count() > 1 {
+                    let letters = letters(name, current.span)?;
+                    let conjunction = fold_chain(letters, Expr::And, self.room()).map_err(|_| self.too_deep(self.max_nesting))?;
+                    return Ok(conjunction.expect("identifiers are not empty"));
+                }
+                Ok(Expr::Identifier(name.to_string()))
+            }
+            Token::True => {
+                self.advance();
+                Ok(Expr::True)
+            }
+            Token::False => {
                 self.advance();
-                Ok(Expr::Identifier(name))
+                Ok(Expr::False)
             }
             Token::LeftParen => {
                 self.advance();
-                let expr = self.parse_implication()?;
+                let expr = self.parenthesized(Self::parse_group)?;
                 self.expect(Token::RightParen)?;
                 Ok(expr)
             }
+            Token::Ite => {
+                self.advance();
+                self.parse_ite()
+            }
+            Token::Cardinality(kind) => {
+                let kind = *kind;
+                self.advance();
+                self.parse_cardinality(kind)
+            }
             Token::Eof => Err(ParseError::UnexpectedEof {
                 span: SourceSpan::from(current.span.start..current.span.end),
             }),
-            _ => Err(ParseError::UnexpectedToken {
-                expected: "identifier or '('".to_string(),
-                found: format!("{}", current.token),
-                span: SourceSpan::from(current.span.start..current.span.end),
-            }),
+            _ => Err(self.unexpected("identifier, constant or '('")),
+        }
+    }
+    
+    /// `(cond, then, else)` after `ite`
+    fn parse_ite(&mut self) -> Result<Expr, ParseError> {
+        self.expect(Token::LeftParen)?;
+        let cond = self.parse_conditional()?;
+        self.expect(Token::Comma)?;
+        let then = self.parse_conditional()?;
+        self.expect(Token::Comma)?;
+        let otherwise = self.parse_conditional()?;
+        self.expect(Token::RightParen)?;
+        Ok(Expr::Ite(Box::new(cond), Box::new(then), Box::new(otherwise)))
+    }
+    
+    /// `(k; operands)` after a cardinality keyword
+    fn parse_cardinality(&mut self, kind: Cardinality) -> Result<Expr, ParseError> {
+        self.expect(Token::LeftParen)?;
+        let bound = self.current_token();
+        let k = match bound.token {
+            Token::Number(n) => n,
+            Token::True => 1,
+            Token::False => 0,
+            _ => {
+                self.report(self.unexpected("a count"))?;
+                0
+            }
+        };
+        // A missing count leaves the `;` for `expect`
+        if !matches!(bound.token, Token::Semicolon) {
+            self.advance();
+        }
+        self.expect(Token::Semicolon)?;
+        let mut operands = vec![self.parse_conditional()?];
+        while matches!(self.current_token().token, Token::Comma) {
+            self.advance();
+            operands.push(self.parse_conditional()?);
         }
+        self.expect(Token::RightParen)?;
+        Ok(Expr::Cardinality(kind, k, operands))
     }
 }
 
-impl FromStr for Parser {
-    type Err = ();
-    
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self::new(s))
+/// Whether an identifier was written in quotes, which makes it span more than its text
+fn is_quoted(token: &SpannedToken) -> bool {
+    matches!(&token.token, Token::Identifier(name) if token.span.end - token.span.start != name.len())
+}
+
+/// Suggest the operator an unknown character was probably meant to be
+fn unknown_character_help(text: &str) -> String {
+    match text {
+        "=" => "Write '==' or 'iff' for iff".to_string(),
+        "`" | "\"" => format!("Quoted names need a closing {} and at least one character", text),
+        _ if text.chars().all(|c| matches!(c, '+' | '*' | '\'')) => {
+            "'+', '*' and postfix ' are operators in algebra notation (--notation algebra)".to_string()
+        }
+        _ => "Operators are words such as 'and', 'or' and 'not', or symbols such as '&&', '||', '!', '^' and '->'".to_string(),
+    }
+}
+
+/// Split a word into its letters, which implicit and conjoins as it reads `AB`
+fn letters(name: &str, span: Span) -> Result<Vec<Expr>, ParseError> {
+    if !name.chars().all(char::is_alphabetic) {
+        return Err(ParseError::AmbiguousJuxtaposition {
+            name: name.to_string(),
+            span: SourceSpan::from(span.start..span.end),
+        });
+    }
+    Ok(name.chars().map(|letter| Expr::Identifier(letter.to_string())).collect())
+}
+
+/// `operands` grouped from the left with `make`, as `a and b and c` is read,
+/// or none if there are none; fails with the depth the chain would reach once
+/// it nests more than `max` levels deep, before building any more of it
+pub(crate) fn fold_chain(
+    operands: impl IntoIterator<Item = Expr>,
+    make: fn(Box<Expr>, Box<Expr>) -> Expr,
+    max: usize,
+) -> Result<Option<Expr>, usize> {
+    let mut chain: Option<(Expr, usize)> = None;
+    for operand in operands {
+        let depth = operand.depth();
+        chain = Some(match chain {
+            None => (operand, depth),
+            Some((left, left_depth)) => {
+                let joined = left_depth.max(depth) + 1;
+                if joined > max {
+                    return Err(joined);
+                }
+                (make(Box::new(left), Box::new(operand)), joined)
+            }
+        });
+    }
+    Ok(chain.map(|(expr, _)| expr))
+}
+
+/// Replace identifiers bound to template parameters with the argument expressions.
+/// A quantifier whose variable occurs free in an argument is renamed first, so
+/// that the argument's variable isn't captured by it.
+pub(crate) fn substitute(expr: &Expr, bindings: &[(String, Expr)]) -> Expr {
+    match expr {
+        Expr::Identifier(name) => bindings
+            .iter()
+            .find(|(param, _)| param == name)
+            .map_or_else(|| expr.clone(), |(_, arg)| arg.clone()),
+        Expr::True | Expr::False => expr.clone(),
+        Expr::Not(inner) => Expr::Not(Box::new(substitute(inner, bindings))),
+        Expr::And(l, r) => Expr::And(Box::new(substitute(l, bindings)), Box::new(substitute(r, bindings))),
+        Expr::Or(l, r) => Expr::Or(Box::new(substitute(l, bindings)), Box::new(substitute(r, bindings))),
+        Expr::Nand(l, r) => Expr::Nand(Box::new(substitute(l, bindings)), Box::new(substitute(r, bindings))),
+        Expr::Nor(l, r) => Expr::Nor(Box::new(substitute(l, bindings)), Box::new(substitute(r, bindings))),
+        Expr::Xor(l, r) => Expr::Xor(Box::new(substitute(l, bindings)), Box::new(substitute(r, bindings))),
+        Expr::Implication(l, r) => Expr::Implication(Box::new(substitute(l, bindings)), Box::new(substitute(r, bindings))),
+        Expr::Iff(l, r) => Expr::Iff(Box::new(substitute(l, bindings)), Box::new(substitute(r, bindings))),
+        Expr::Ite(c, t, e) => Expr::Ite(
+            Box::new(substitute(c, bindings)),
+            Box::new(substitute(t, bindings)),
+            Box::new(substitute(e, bindings)),
+        ),
+        Expr::Cardinality(kind, k, operands) => {
+            Expr::Cardinality(*kind, *k, operands.iter().map(|operand| substitute(operand, bindings)).collect())
+        }
+        Expr::Custom(operator, operands) => {
+            Expr::Custom(operator.clone(), operands.iter().map(|operand| substitute(operand, bindings)).collect())
+        }
+        // The bound variable shadows any binding of the same name
+        Expr::Quantified(quantifier, var, body) => {
+            let bindings: Vec<(String, Expr)> = bindings
+                .iter()
+                .filter(|(param, _)| param != var && occurs_free(body, param))
+                .cloned()
+                .collect();
+            if !bindings.iter().any(|(_, arg)| occurs_free(arg, var)) {
+                return Expr::Quantified(*quantifier, var.clone(), Box::new(substitute(body, &bindings)));
+            }
+            // Digits don't continue an identifier, so the new name takes underscores
+            let fresh = (1..)
+                .map(|length| format!("{}{}", var, "_".repeat(length)))
+                .find(|name| !occurs_free(body, name) && !bindings.iter().any(|(_, arg)| occurs_free(arg, name)))
+                .expect("an expression mentions finitely many names");
+            let renamed = substitute(body, &[(var.clone(), Expr::Identifier(fresh.clone()))]);
+            Expr::Quantified(*quantifier, fresh, Box::new(substitute(&renamed, &bindings)))
+        }
+    }
+}
+
+/// Whether `name` occurs in `expr` outside any quantifier binding it
+fn occurs_free(expr: &Expr, name: &str) -> bool {
+    struct Occurrence<'a>(&'a str);
+
+    impl ExprVisitor for Occurrence<'_> {
+        type Break = ();
+
+        fn visit_identifier(&mut self, name: &str) -> ControlFlow<()> {
+            if name == self.0 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        }
+
+        fn visit_quantified(&mut self, _quantifier: Quantifier, var: &str, body: &Expr) -> ControlFlow<()> {
+            if var == self.0 { ControlFlow::Continue(()) } else { self.visit_expr(body) }
+        }
+    }
+
+    Occurrence(name).visit_expr(expr).is_break()
+}
+
+impl<'a> From<&'a str> for Parser<'a> {
+    fn from(input: &'a str) -> Self {
+        Self::new(input)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::expr;
     
     #[test]
     fn test_parse_simple_identifier() {
         let mut parser = Parser::new("a");
         let result = parser.parse().unwrap();
-        assert_eq!(result, Expr::Identifier("a".to_string()));
+        assert_eq!(result, expr!(a));
     }
     
     #[test]
     fn test_parse_not() {
         let mut parser = Parser::new("not a");
         let result = parser.parse().unwrap();
-        assert_eq!(result, Expr::Not(Box::new(Expr::Identifier("a".to_string()))));
+        assert_eq!(result, expr!(not a));
     }
     
     #[test]
     fn test_parse_and() {
         let mut parser = Parser::new("a and b");
         let result = parser.parse().unwrap();
-        assert_eq!(
-            result,
-            Expr::And(
-                Box::new(Expr::Identifier("a".to_string())),
-                Box::new(Expr::Identifier("b".to_string()))
-            )
-        );
+        assert_eq!(result, expr!(a and b));
     }
     
     #[test]
     fn test_parse_complex() {
         let mut parser = Parser::new("a or not b");
         let result = parser.parse().unwrap();
-        assert_eq!(
-            result,
-            Expr::Or(
-                Box::new(Expr::Identifier("a".to_string())),
-                Box::new(Expr::Not(Box::new(Expr::Identifier("b".to_string()))))
-            )
-        );
+        assert_eq!(result, expr!(a or (not b)));
     }
     
     #[test]
     fn test_parse_with_parentheses() {
         let mut parser = Parser::new("(a or b) and c");
         let result = parser.parse().unwrap();
-        assert_eq!(
-            result,
-            Expr::And(
-                Box::new(Expr::Or(
-                    Box::new(Expr::Identifier("a".to_string())),
-                    Box::new(Expr::Identifier("b".to_string()))
-                )),
-                Box::new(Expr::Identifier("c".to_string()))
-            )
-        );
+        assert_eq!(result, expr!((a or b) and c));
     }
     
     #[test]
@@ -280,28 +1294,301 @@ mod tests {
         let mut parser = Parser::new("a or b and c");
         let result = parser.parse().unwrap();
         // Should parse as: a or (b and c)
-        assert_eq!(
-            result,
-            Expr::Or(
-                Box::new(Expr::Identifier("a".to_string())),
-                Box::new(Expr::And(
-                    Box::new(Expr::Identifier("b".to_string())),
-                    Box::new(Expr::Identifier("c".to_string()))
-                ))
-            )
-        );
+        assert_eq!(result, expr!(a or (b and c)));
     }
     
     #[test]
     fn test_implication() {
         let mut parser = Parser::new("a -> b");
         let result = parser.parse().unwrap();
-        assert_eq!(
-            result,
-            Expr::Implication(
-                Box::new(Expr::Identifier("a".to_string())),
-                Box::new(Expr::Identifier("b".to_string()))
-            )
-        );
+        assert_eq!(result, expr!(a -> b));
+    }
+
+    #[test]
+    fn test_implication_associativity() {
+        assert_eq!(Parser::new("a -> b -> c").parse().unwrap().to_string(), "(a → (b → c))");
+        let mut parser = Parser::new("a -> b -> c").with_implication_associativity(Associativity::Left);
+        assert_eq!(parser.parse().unwrap().to_string(), "((a → b) → c)");
+        // Other operators still group to the left either way
+        let mut parser = Parser::new("a or b or c -> d").with_implication_associativity(Associativity::Right);
+        assert_eq!(parser.parse().unwrap().to_string(), "(((a ∨ b) ∨ c) → d)");
+    }
+
+    #[test]
+    fn test_iff_binds_loosest() {
+        let mut parser = Parser::new("a -> b iff c or d");
+        let result = parser.parse().unwrap();
+        assert_eq!(result.to_string(), "((a → b) ↔ (c ∨ d))");
+        
+        for input in ["a <-> b", "a ↔ b", "a xnor b"] {
+            assert_eq!(Parser::new(input).parse().unwrap().to_string(), "(a ↔ b)", "{}", input);
+        }
+    }
+    
+    #[test]
+    fn test_if_then_else() {
+        let parse = |input| Parser::new(input).parse().unwrap().to_string();
+        assert_eq!(parse("ite(s, a, b)"), "(s ? a : b)");
+        assert_eq!(parse("s ? a : b"), "(s ? a : b)");
+        // Binds loosest and nests to the right
+        assert_eq!(parse("s -> t ? a or b : c ? d : e"), "((s → t) ? (a ∨ b) : (c ? d : e))");
+        assert_eq!(parse("ite(s, a ? b : c, d) and e"), "((s ? (a ? b : c) : d) ∧ e)");
+        assert!(Parser::new("s ? a").parse().is_err());
+    }
+    
+    #[test]
+    fn test_cardinality() {
+        let parse = |input| Parser::new(input).parse();
+        assert_eq!(parse("atleast(2; a, b, c or d) and e").unwrap().to_string(), "(atleast(2; a, b, (c ∨ d)) ∧ e)");
+        assert_eq!(parse("exactly(1; a, b)").unwrap(), expr!(exactly(1; a, b)));
+        assert!(parse("atmost(a; b)").is_err());
+        assert!(parse("atmost(1, a, b)").is_err());
+    }
+    
+    #[test]
+    fn test_quantifiers() {
+        let parse = |input| Parser::new(input).parse().unwrap().to_string();
+        // The body extends as far right as possible
+        assert_eq!(parse("forall s. a and s -> b"), "(∀s. ((a ∧ s) → b))");
+        assert_eq!(parse("exists x, y. x xor y"), "(∃x. (∃y. (x ⊕ y)))");
+        assert_eq!(parse("a and ∀x. x or b"), "(a ∧ (∀x. (x ∨ b)))");
+        assert!(Parser::new("forall . a").parse().is_err());
+        
+        let expr = Parser::new("forall x. x or a").parse().unwrap();
+        assert_eq!(expr.eliminate_quantifiers().to_string(), "((false ∨ a) ∧ (true ∨ a))");
+        // Inner binders shadow outer ones
+        let shadowed = Parser::new("exists x. forall x. x").parse().unwrap();
+        assert_eq!(shadowed.eliminate_quantifiers().to_string(), "((false ∧ true) ∨ (false ∧ true))");
+    }
+    
+    #[test]
+    fn test_algebra_notation() {
+        let parse = |input| Parser::new_with_notation(input, Notation::Algebra).parse().unwrap().to_string();
+        assert_eq!(parse("a*b' + c"), "((a ∧ ¬b) ∨ c)");
+        assert_eq!(parse("(a + b)'' * c"), "(¬¬(a ∨ b) ∧ c)");
+        assert_eq!(parse("not a'"), "¬¬a");
+    }
+
+    #[test]
+    fn test_implicit_and() {
+        let parse = |input| Parser::new_with_notation(input, Notation::Algebra).with_implicit_and().parse();
+        assert_eq!(parse("AB + C'D").unwrap().to_string(), "((A ∧ B) ∨ (¬C ∧ D))");
+        assert_eq!(parse("a (b + c) not d").unwrap().to_string(), "((a ∧ (b ∨ c)) ∧ ¬d)");
+        assert!(matches!(parse("x_y + z"), Err(ParseError::AmbiguousJuxtaposition { .. })));
+        // Without the mode, adjacent operands are still an error
+        assert!(Parser::new("a b").parse().is_err());
+    }
+
+    #[test]
+    fn test_constants() {
+        let result = Parser::new("a and true or 0").parse().unwrap();
+        assert_eq!(result, expr!((a and true) or false));
+        assert_eq!(result.to_string(), "((a ∧ true) ∨ false)");
+    }
+
+    #[test]
+    fn test_nand_nor_share_and_or_precedence() {
+        let result = Parser::new("a nand b or c ↓ d").parse().unwrap();
+        assert_eq!(result.to_string(), "(((a ↑ b) ∨ c) ↓ d)");
+        assert_eq!(Parser::new("a ↑ b and c").parse().unwrap().to_string(), "((a ↑ b) ∧ c)");
+    }
+    
+    fn parse_with(library: &str, input: &str) -> Result<Expr, ParseError> {
+        let library = Library::parse(library).unwrap();
+        Parser::new(input).with_library(library).parse()
+    }
+    
+    #[test]
+    fn test_template_instantiation() {
+        let library = "def carry(a, b, cin) := (a and b) or (cin and (a xor b))\ndef ok := not fault";
+        let result = parse_with(library, "carry(x, not y, ok)").unwrap();
+        assert_eq!(result.to_string(), "((x ∧ ¬y) ∨ (¬fault ∧ (x ⊕ ¬y)))");
+        
+        // Parameters shadow definitions of the same name
+        let result = parse_with("def a := z\ndef f(a) := a or b", "f(c)").unwrap();
+        assert_eq!(result.to_string(), "(c ∨ b)");
+    }
+    
+    #[test]
+    fn test_substitution_avoids_capture() {
+        // The argument's `x` is free, so the definition's bound `x` is renamed
+        let result = parse_with("def f(p) := exists x. p and x", "f(x or y)").unwrap();
+        assert_eq!(result.to_string(), "(∃x_. ((x ∨ y) ∧ x_))");
+        assert_eq!(result.eliminate_quantifiers().to_string(), "(((x ∨ y) ∧ false) ∨ ((x ∨ y) ∧ true))");
+        // The fresh name avoids the body's own variables too
+        let result = parse_with("def g(p) := forall x. p or x or x_", "g(x)").unwrap();
+        assert_eq!(result.to_string(), "(∀x__. ((x ∨ x__) ∨ x_))");
+        // No rename when nothing would be captured
+        let result = parse_with("def f(p) := exists x. p and x", "f(y)").unwrap();
+        assert_eq!(result.to_string(), "(∃x. (y ∧ x))");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_template_arity_and_recursion() {
+        let library = "def f(a, b) := a and b\ndef loop := g\ndef g := loop or x";
+        assert!(matches!(parse_with(library, "f(x)"), Err(ParseError::ArityMismatch { expected: 2, found: 1, .. })));
+        assert!(matches!(parse_with(library, "f"), Err(ParseError::ArityMismatch { found: 0, .. })));
+        match parse_with(library, "y and loop") {
+            Err(ParseError::RecursiveDefinition { chain, span, .. }) => {
+                assert_eq!(chain, "loop → g → loop");
+                assert_eq!(span.offset(), 6);
+            }
+            other => panic!("expected recursion error, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_aliases() {
+        let mut aliases = Aliases::new();
+        for (word, operator) in [("et", "and"), ("ou", "or"), ("non", "not"), ("vrai", "true")] {
+            aliases.add(word, operator).unwrap();
+        }
+        let result = Parser::new("non a et b ou vrai").with_aliases(aliases).parse().unwrap();
+        assert_eq!(result.to_string(), "((¬a ∧ b) ∨ true)");
+        
+        // Library aliases also apply inside definitions; quoting keeps the word a variable
+        let result = parse_with("alias et = &&\ndef f(x) := x et y", "f(`et`)").unwrap();
+        assert_eq!(result.to_string(), "(et ∧ y)");
+        let result = parse_with("alias et = &&", "`et` et b").unwrap();
+        assert_eq!(result.to_string(), "(et ∧ b)");
+    }
+    
+    #[test]
+    fn test_case_insensitive_keywords() {
+        let result = Parser::new("NOT a AND b OR Exists x. x").with_case_insensitive_keywords().parse().unwrap();
+        assert_eq!(result.to_string(), "((¬a ∧ b) ∨ (∃x. x))");
+        // Words that only contain a keyword stay variables, as do quoted keywords
+        let result = Parser::new("`NOT` and notable or ORACLE").with_case_insensitive_keywords().parse().unwrap();
+        assert_eq!(result.to_string(), "((NOT ∧ notable) ∨ ORACLE)");
+        assert!(matches!(Parser::new("a AND b").parse(), Err(ParseError::MisspelledKeyword { .. })));
+    }
+    
+    #[test]
+    fn test_missing_operator() {
+        match Parser::new("a and (b   c)").parse() {
+            Err(ParseError::MissingOperator { left, right, help, span }) => {
+                assert_eq!((left.as_str(), right.as_str()), ("b", "c"));
+                assert_eq!((span.offset(), span.len()), (8, 3));
+                assert_eq!(help, ImplicitAndHint::Library);
+                assert!(help.to_string().contains("Parser::with_implicit_and"));
+                assert!(ImplicitAndHint::Flag.to_string().contains("or pass --implicit-and to"));
+            }
+            other => panic!("expected missing operator error, got {:?}", other),
+        }
+        assert!(matches!(Parser::new("a b").parse(), Err(ParseError::MissingOperator { .. })));
+        // A misspelled operator between them is still reported as such
+        assert!(matches!(Parser::new("a adn b").parse(), Err(ParseError::MisspelledKeyword { .. })));
+        assert!(matches!(Parser::new("(a) (b)").parse(), Err(ParseError::UnexpectedToken { .. })));
+    }
+    
+    #[test]
+    fn test_prefix_syntax() {
+        let parse = |input: &str| Parser::new(input).with_syntax(Syntax::Prefix).parse();
+        assert_eq!(parse("-> and a b or c d").unwrap(), Parser::new("(a and b) -> (c or d)").parse().unwrap());
+        assert_eq!(parse("! && a ite b c true").unwrap(), Parser::new("not (a and (b ? c : true))").parse().unwrap());
+        assert_eq!(parse("forall x. or x (nand y x)").unwrap(), Parser::new("forall x. x or (y nand x)").parse().unwrap());
+        assert!(matches!(parse("and a"), Err(ParseError::UnexpectedEof { .. })));
+        // One operand too many is not a missing operator
+        assert!(matches!(parse("and a b c"), Err(ParseError::UnexpectedToken { .. })));
+        assert!(parse("a and b").is_err());
+    }
+    
+    #[test]
+    fn test_deep_nesting_is_an_error() {
+        let depth = 100_000;
+        let inputs = [
+            format!("{}a{}", "(".repeat(depth), ")".repeat(depth)),
+            "!".repeat(depth) + "a",
+            format!("a{}", " -> a".repeat(depth)),
+            format!("a{}", "'".repeat(depth)),
+            format!("{}b{}", "ite(a, ".repeat(depth), ", c)".repeat(depth)),
+        ];
+        for input in &inputs {
+            let result = Parser::new_with_notation(input, Notation::Algebra).parse();
+            assert!(matches!(result, Err(ParseError::TooDeep { .. })), "{}...", &input[..20]);
+        }
+        let prefix = "and a ".repeat(depth) + "b";
+        assert!(matches!(Parser::new(&prefix).with_syntax(Syntax::Prefix).parse(), Err(ParseError::TooDeep { .. })));
+        // Recovery doesn't try again at every level
+        assert!(matches!(Parser::new(&inputs[0]).parse_recovering(), Err(errors) if errors.len() == 1));
+        
+        let nested = format!("{}a{}", "(".repeat(MAX_NESTING_DEPTH - 1), ")".repeat(MAX_NESTING_DEPTH - 1));
+        assert!(Parser::new(&nested).parse().is_ok());
+        assert!(matches!(Parser::new(&nested).with_max_nesting(10).parse(), Err(ParseError::TooDeep { limit: 10, .. })));
+    }
+    
+    #[test]
+    fn test_chains_nest_a_level_per_operand() {
+        // Each operand nests the ones before it, as every pass over the expression sees
+        let chain = format!("a{}", " and b".repeat(1_000));
+        assert!(matches!(Parser::new(&chain).parse(), Err(ParseError::TooDeep { limit: MAX_NESTING_DEPTH, .. })));
+        assert_eq!(Parser::new(&chain).with_max_nesting(1_000).parse().unwrap().depth(), 1_000);
+        let juxtaposed = "ab".repeat(500);
+        assert!(matches!(Parser::new(&juxtaposed).with_implicit_and().parse(), Err(ParseError::TooDeep { .. })));
+        let expr = Parser::new(&juxtaposed).with_implicit_and().with_max_nesting(999).parse().unwrap();
+        assert_eq!(expr.depth(), 999);
+        // Parentheses nest the expression no deeper
+        assert_eq!(Parser::new("(a) and (b)").with_max_nesting(1).parse().unwrap().depth(), 1);
+        assert!(Parser::new_with_notation("a''", Notation::Algebra).with_max_nesting(2).parse().is_ok());
+        assert!(matches!(Parser::new_with_notation("a'''", Notation::Algebra).with_max_nesting(2).parse(), Err(ParseError::TooDeep { limit: 2, .. })));
+    }
+    
+    #[test]
+    fn test_unknown_characters() {
+        match Parser::new("a +++ b").parse() {
+            Err(ParseError::UnknownCharacter { text, span, .. }) => {
+                assert_eq!(text, "+++");
+                assert_eq!((span.offset(), span.len()), (2, 3));
+            }
+            other => panic!("expected unknown character error, got {:?}", other),
+        }
+        // Also where an operand or a closing parenthesis should be
+        assert!(matches!(Parser::new("a and $b").parse(), Err(ParseError::UnknownCharacter { .. })));
+        assert!(matches!(Parser::new("(a @ b)").parse(), Err(ParseError::UnknownCharacter { .. })));
+    }
+    
+    #[test]
+    fn test_quoted_identifiers_round_trip() {
+        let expr = Parser::new("`input valid` -> not \"rst-n\" and exists `x y`. `x y`").parse().unwrap();
+        assert_eq!(expr.to_string(), "(`input valid` → (¬`rst-n` ∧ (∃`x y`. `x y`)))");
+        assert_eq!(Parser::new(&expr.to_string()).parse().unwrap(), expr);
+        // Quoted words are never split by implicit and
+        let expr = Parser::new("`ab` c").with_implicit_and().parse().unwrap();
+        assert_eq!(expr.to_string(), "(ab ∧ c)");
+    }
+    
+    #[test]
+    fn test_misspelled_keywords() {
+        let suggestion = |input: &str| match Parser::new(input).parse() {
+            Err(ParseError::MisspelledKeyword { word, suggestion, .. }) => Some((word, suggestion)),
+            _ => None,
+        };
+        assert_eq!(suggestion("a andd b"), Some(("andd".to_string(), "and".to_string())));
+        assert_eq!(suggestion("a nto b"), Some(("nto".to_string(), "not".to_string())));
+        assert_eq!(suggestion("nto a"), Some(("nto".to_string(), "not".to_string())));
+        assert_eq!(suggestion("forall x. x or exsts y. y").map(|(_, s)| s), Some("exists".to_string()));
+        // Unrelated words and quoted names get no suggestion
+        assert_eq!(suggestion("a bee c"), None);
+        assert_eq!(suggestion("a `andd` b"), None);
+    }
+    
+    #[test]
+    fn test_recovery_reports_every_error() {
+        let errors = Parser::new("(a and ) or (b xor ) or ite(c, d").parse_recovering().unwrap_err();
+        let offsets: Vec<usize> = errors.iter().map(|e| e.span().offset()).collect();
+        // Two missing operands, then the unfinished ite reported once at the end
+        assert_eq!(offsets, [7, 19, 32]);
+        
+        let errors = Parser::new("atleast(x; a, b) or (c").parse_recovering().unwrap_err();
+        assert!(matches!(&errors[0], ParseError::UnexpectedToken { expected, .. } if expected == "a count"));
+        assert_eq!(errors.len(), 2);
+    }
+    
+    #[test]
+    fn test_recovery_matches_parse_on_valid_input() {
+        let input = "forall x. (x or y) -> atleast(1; y, z)";
+        assert_eq!(Parser::new(input).parse_recovering().unwrap(), Parser::new(input).parse().unwrap());
+        // Without recovery the first error is still the only one
+        assert!(matches!(Parser::new("a and ) or b").parse(), Err(ParseError::UnexpectedToken { span, .. }) if span.offset() == 6));
+    }
+}
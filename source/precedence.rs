@@ -0,0 +1,172 @@
+use crate::source::{Expr, Token, PrettyPrinter};
+
+/// How tightly the binary operators bind relative to each other.
+///
+/// `¬` always binds tightest and `? :` loosest; the profiles differ in where
+/// `⊕` sits and whether `∧` binds tighter than `∨`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precedence {
+    /// `∧ ↑` > `⊕` > `∨ ↓` > `→` > `↔`, as C ranks `&`, `^` and `|`
+    #[default]
+    Standard,
+    /// `∧ ↑` > `∨ ↓ ⊕` > `→` > `↔`, with xor as another kind of sum
+    XorAsOr,
+    /// `∧ ↑` > `∨ ↓` > `→` > `↔ ⊕`, with xor as negated equivalence
+    XorAsIff,
+    /// `∧ ↑ ∨ ↓ ⊕` > `→` > `↔`, grouping and, or and xor left to right
+    Flat,
+}
+
+/// How a chain of implications groups
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a → b → c` is `(a → b) → c`
+    Left,
+    /// `a → b → c` is `a → (b → c)`, the usual convention in logic
+    #[default]
+    Right,
+}
+
+/// The binary operators, which the profiles rank
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    And,
+    Nand,
+    Or,
+    Nor,
+    Xor,
+    Implication,
+    Iff,
+}
+
+impl BinaryOp {
+    pub(crate) fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::And => Some(BinaryOp::And),
+            Token::Nand => Some(BinaryOp::Nand),
+            Token::Or => Some(BinaryOp::Or),
+            Token::Nor => Some(BinaryOp::Nor),
+            Token::Xor => Some(BinaryOp::Xor),
+            Token::Implication => Some(BinaryOp::Implication),
+            Token::Iff => Some(BinaryOp::Iff),
+            _ => None,
+        }
+    }
+
+    /// The node applying this operator to `left` and `right`
+    pub fn build(self, left: Expr, right: Expr) -> Expr {
+        let (left, right) = (Box::new(left), Box::new(right));
+        match self {
+            BinaryOp::And => Expr::And(left, right),
+            BinaryOp::Nand => Expr::Nand(left, right),
+            BinaryOp::Or => Expr::Or(left, right),
+            BinaryOp::Nor => Expr::Nor(left, right),
+            BinaryOp::Xor => Expr::Xor(left, right),
+            BinaryOp::Implication => Expr::Implication(left, right),
+            BinaryOp::Iff => Expr::Iff(left, right),
+        }
+    }
+
+    /// Whether swapping the operands never changes the value
+    pub fn is_commutative(self) -> bool {
+        self != BinaryOp::Implication
+    }
+
+    /// The operator's value on two operands
+    pub fn apply(self, left: bool, right: bool) -> bool {
+        match self {
+            BinaryOp::And => left && right,
+            BinaryOp::Nand => !(left && right),
+            BinaryOp::Or => left || right,
+            BinaryOp::Nor => !(left || right),
+            BinaryOp::Xor => left ^ right,
+            BinaryOp::Implication => !left || right,
+            BinaryOp::Iff => left == right,
+        }
+    }
+
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::And => "∧",
+            BinaryOp::Nand => "↑",
+            BinaryOp::Or => "∨",
+            BinaryOp::Nor => "↓",
+            BinaryOp::Xor => "⊕",
+            BinaryOp::Implication => "→",
+            BinaryOp::Iff => "↔",
+        }
+    }
+}
+
+impl Precedence {
+    /// Number of binary levels
+    pub(crate) fn levels(self) -> usize {
+        match self {
+            Precedence::Standard => 5,
+            Precedence::XorAsOr | Precedence::XorAsIff => 4,
+            Precedence::Flat => 3,
+        }
+    }
+
+    /// Level of `op`, from 0 for the loosest binary operator to `levels() - 1`
+    pub(crate) fn level(self, op: BinaryOp) -> usize {
+        use BinaryOp::*;
+        match (self, op) {
+            (_, Iff) => 0,
+            (Precedence::XorAsIff, Xor) => 0,
+            (_, Implication) => 1,
+            (Precedence::Flat, _) => 2,
+            (_, Or | Nor) => 2,
+            (Precedence::XorAsOr, Xor) => 2,
+            (Precedence::Standard, Xor) => 3,
+            (_, And | Nand) => self.levels() - 1,
+        }
+    }
+
+    /// Write `expr` with only the parentheses this profile needs to read it back
+    pub fn render(self, expr: &Expr) -> String {
+        PrettyPrinter::new().with_precedence(self).print(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn parse(input: &str, precedence: Precedence) -> Expr {
+        Parser::new(input).with_precedence(precedence).parse().unwrap()
+    }
+
+    #[test]
+    fn test_profiles_group_xor_differently() {
+        let input = "a or b xor c and d";
+        assert_eq!(parse(input, Precedence::Standard).to_string(), "(a ∨ (b ⊕ (c ∧ d)))");
+        assert_eq!(parse(input, Precedence::XorAsOr).to_string(), "((a ∨ b) ⊕ (c ∧ d))");
+        assert_eq!(parse(input, Precedence::XorAsIff).to_string(), "((a ∨ b) ⊕ (c ∧ d))");
+        assert_eq!(parse(input, Precedence::Flat).to_string(), "(((a ∨ b) ⊕ c) ∧ d)");
+        assert_eq!(parse("a xor b -> c", Precedence::XorAsIff).to_string(), "(a ⊕ (b → c))");
+    }
+
+    #[test]
+    fn test_render_uses_minimal_parentheses() {
+        let expr = parse("(a or b) and not (c xor d) or e", Precedence::Standard);
+        assert_eq!(Precedence::Standard.render(&expr), "(a ∨ b) ∧ ¬(c ⊕ d) ∨ e");
+        assert_eq!(Precedence::Flat.render(&expr), "a ∨ b ∧ ¬(c ⊕ d) ∨ e");
+        assert_eq!(Precedence::Flat.render(&parse("a or b and c", Precedence::Standard)), "a ∨ (b ∧ c)");
+        assert_eq!(Precedence::Standard.render(&parse("s ? a : b or c", Precedence::Standard)), "s ? a : b ∨ c");
+        assert_eq!(Precedence::Standard.render(&parse("a -> b -> c", Precedence::Standard)), "a → (b → c)");
+    }
+
+    #[test]
+    fn test_render_round_trips_under_each_profile() {
+        let inputs = ["a xor b and c or d", "a -> b -> c", "a nand (b nor c) iff d", "not (a or b) xor c"];
+        for precedence in [Precedence::Standard, Precedence::XorAsOr, Precedence::XorAsIff, Precedence::Flat] {
+            for input in inputs {
+                let expr = parse(input, Precedence::Standard);
+                let rendered = precedence.render(&expr);
+                assert_eq!(parse(&rendered, precedence), expr, "{:?} rendered as {}", precedence, rendered);
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::source::{Expr, BinaryOp, Precedence, quote_identifier, to_sexpr};
+
+/// The symbols a [`PrettyPrinter`] writes operators with
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SymbolStyle {
+    /// `¬ ∧ ∨ ↑ ↓ ⊕ → ↔ ∀ ∃`, as `Display` writes them
+    #[default]
+    Unicode,
+    /// `! && || ^ -> <->`, with `nand`, `nor`, `forall` and `exists` as words
+    Ascii,
+    /// `not and or nand nor xor iff forall exists`, with `->` for implication
+    Words,
+    /// Prefix s-expressions such as `(and a (not b))`, parenthesized the same
+    /// whatever the precedence profile
+    Sexpr,
+}
+
+impl SymbolStyle {
+    fn binary(self, op: BinaryOp) -> &'static str {
+        match (self, op) {
+            (SymbolStyle::Unicode, _) => op.symbol(),
+            (SymbolStyle::Ascii, BinaryOp::And) => "&&",
+            (SymbolStyle::Ascii, BinaryOp::Or) => "||",
+            (SymbolStyle::Ascii, BinaryOp::Xor) => "^",
+            (SymbolStyle::Ascii, BinaryOp::Iff) => "<->",
+            (_, BinaryOp::Implication) => "->",
+            (_, BinaryOp::And) => "and",
+            (_, BinaryOp::Or) => "or",
+            (_, BinaryOp::Nand) => "nand",
+            (_, BinaryOp::Nor) => "nor",
+            (_, BinaryOp::Xor) => "xor",
+            (_, BinaryOp::Iff) => "iff",
+        }
+    }
+
+    fn not(self) -> &'static str {
+        match self {
+            SymbolStyle::Unicode => "¬",
+            SymbolStyle::Ascii => "!",
+            SymbolStyle::Words | SymbolStyle::Sexpr => "not ",
+        }
+    }
+}
+
+/// Writes expressions with only the parentheses a precedence profile needs to
+/// read them back, in a choice of [`SymbolStyle`].
+///
+/// ```
+/// use ttt::expr;
+/// use ttt::source::{PrettyPrinter, SymbolStyle};
+///
+/// let expr = expr!((a or b) and not (c xor d));
+/// assert_eq!(PrettyPrinter::new().print(&expr), "(a ∨ b) ∧ ¬(c ⊕ d)");
+/// assert_eq!(PrettyPrinter::new().with_style(SymbolStyle::Ascii).print(&expr), "(a || b) && !(c ^ d)");
+/// assert_eq!(PrettyPrinter::new().with_style(SymbolStyle::Words).print(&expr), "(a or b) and not (c xor d)");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrettyPrinter {
+    precedence: Precedence,
+    style: SymbolStyle,
+}
+
+impl PrettyPrinter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parenthesize for the profile the output will be parsed with
+    pub fn with_precedence(mut self, precedence: Precedence) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
+    pub fn with_style(mut self, style: SymbolStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn print(&self, expr: &Expr) -> String {
+        match self.style {
+            SymbolStyle::Sexpr => to_sexpr(expr),
+            _ => self.print_at(expr, 0),
+        }
+    }
+
+    /// Print `expr` where an operand binding at least `min` is required; levels
+    /// are 0 for `? :` and quantifiers, then each binary level, then unary
+    fn print_at(&self, expr: &Expr, min: usize) -> String {
+        let unary = self.precedence.levels() + 1;
+        let (text, level) = match expr {
+            Expr::Identifier(_) | Expr::True | Expr::False => (expr.to_string(), unary),
+            Expr::Cardinality(kind, k, operands) => (format!("{}({}; {})", kind, k, self.print_list(operands)), unary),
+            Expr::Custom(operator, operands) => (format!("{}({})", operator.display_name(), self.print_list(operands)), unary),
+            Expr::Not(inner) => (format!("{}{}", self.style.not(), self.print_at(inner, unary)), unary),
+            Expr::Ite(cond, then, otherwise) => {
+                let text = format!("{} ? {} : {}", self.print_at(cond, 1), self.print_at(then, 0), self.print_at(otherwise, 0));
+                (text, 0)
+            }
+            Expr::Quantified(quantifier, var, body) => {
+                let text = match self.style {
+                    SymbolStyle::Unicode => format!("{}{}. {}", quantifier, quote_identifier(var), self.print_at(body, 0)),
+                    _ => format!("{} {}. {}", quantifier.keyword(), quote_identifier(var), self.print_at(body, 0)),
+                };
+                (text, 0)
+            }
+            _ => {
+                let (op, left, right) = expr.as_binary().expect("every other node is binary");
+                let level = self.precedence.level(op) + 1;
+                // Chained implications are parenthesized so they read the same under either associativity
+                let left_min = if op == BinaryOp::Implication { level + 1 } else { level };
+                let text = format!("{} {} {}", self.print_at(left, left_min), self.style.binary(op), self.print_at(right, level + 1));
+                (text, level)
+            }
+        };
+        if level < min { format!("({})", text) } else { text }
+    }
+
+    fn print_list(&self, operands: &[Expr]) -> String {
+        operands.iter().map(|operand| self.print_at(operand, 0)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{Parser, parse_sexpr};
+    use crate::expr;
+
+    #[test]
+    fn test_styles() {
+        let expr = expr!(forall x. (a nand b) -> not (x iff c ? d : e));
+        let printer = PrettyPrinter::new();
+        assert_eq!(printer.print(&expr), "∀x. a ↑ b → ¬(x ↔ c ? d : e)");
+        assert_eq!(printer.with_style(SymbolStyle::Ascii).print(&expr), "forall x. a nand b -> !(x <-> c ? d : e)");
+        assert_eq!(printer.with_style(SymbolStyle::Words).print(&expr), "forall x. a nand b -> not (x iff c ? d : e)");
+    }
+
+    #[test]
+    fn test_every_style_round_trips() {
+        let inputs = [
+            "a xor b and c or d",
+            "a -> b -> c",
+            "not not (a nor b) iff d",
+            "exists y. atmost(1; y, not a, b or c) ? and_ : exists z. z",
+            "`x0` and b",
+        ];
+        for style in [SymbolStyle::Unicode, SymbolStyle::Ascii, SymbolStyle::Words, SymbolStyle::Sexpr] {
+            for precedence in [Precedence::Standard, Precedence::XorAsIff, Precedence::Flat] {
+                for input in inputs {
+                    let expr = Parser::new(input).parse().unwrap();
+                    let printed = PrettyPrinter::new().with_precedence(precedence).with_style(style).print(&expr);
+                    let parsed = match style {
+                        SymbolStyle::Sexpr => parse_sexpr(&printed).unwrap(),
+                        _ => Parser::new(&printed).with_precedence(precedence).parse().unwrap(),
+                    };
+                    assert_eq!(parsed, expr, "{:?} under {:?} printed as {}", style, precedence, printed);
+                }
+            }
+        }
+    }
+}
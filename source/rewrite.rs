@@ -0,0 +1,264 @@
+use crate::source::{Expr, ExprVisitor};
+use crate::source::parser::substitute;
+use crate::expr;
+use serde::{Serialize, Deserialize};
+use std::ops::ControlFlow;
+
+/// Most rule applications [`Rewriter::rewrite`] makes by default
+pub const DEFAULT_REWRITE_STEPS: usize = 100_000;
+
+/// A simplification `pattern → replacement`.
+///
+/// Every identifier in the pattern is a placeholder that matches any
+/// subexpression; a placeholder used twice matches only equal subexpressions.
+/// The operands of commutative operators match in either order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    pub pattern: Expr,
+    pub replacement: Expr,
+}
+
+impl Rule {
+    /// Panics if the replacement uses a placeholder the pattern doesn't bind
+    pub fn new(name: impl Into<String>, pattern: Expr, replacement: Expr) -> Self {
+        let bound = placeholders(&pattern);
+        assert!(
+            placeholders(&replacement).iter().all(|name| bound.contains(name)),
+            "replacement uses placeholders the pattern doesn't bind"
+        );
+        Self { name: name.into(), pattern, replacement }
+    }
+
+    /// The replacement for `expr`, if the pattern matches it
+    pub fn apply(&self, expr: &Expr) -> Option<Expr> {
+        let bindings = matches(&self.pattern, expr, Vec::new()).into_iter().next()?;
+        Some(substitute(&self.replacement, &bindings))
+    }
+}
+
+fn placeholders(expr: &Expr) -> Vec<String> {
+    struct Names(Vec<String>);
+
+    impl ExprVisitor for Names {
+        type Break = ();
+
+        fn visit_identifier(&mut self, name: &str) -> ControlFlow<()> {
+            self.0.push(name.to_string());
+            ControlFlow::Continue(())
+        }
+    }
+
+    let mut names = Names(Vec::new());
+    let _ = names.visit_expr(expr);
+    names.0
+}
+
+type Bindings = Vec<(String, Expr)>;
+
+/// Every way `pattern` matches `expr` extending `bindings`
+fn matches(pattern: &Expr, expr: &Expr, bindings: Bindings) -> Vec<Bindings> {
+    let mut bindings = bindings;
+    if let Expr::Identifier(name) = pattern {
+        return match bindings.iter().find(|(placeholder, _)| placeholder == name) {
+            Some((_, bound)) if bound == expr => vec![bindings],
+            Some(_) => Vec::new(),
+            None => {
+                bindings.push((name.clone(), expr.clone()));
+                vec![bindings]
+            }
+        };
+    }
+    if let (Some((pattern_op, pl, pr)), Some((op, l, r))) = (pattern.as_binary(), expr.as_binary()) {
+        if pattern_op != op {
+            return Vec::new();
+        }
+        let mut orders = vec![(l, r)];
+        if op.is_commutative() {
+            orders.push((r, l));
+        }
+        return orders
+            .into_iter()
+            .flat_map(|(l, r)| {
+                matches(pl, l, bindings.clone())
+                    .into_iter()
+                    .flat_map(move |bindings| matches(pr, r, bindings))
+            })
+            .collect();
+    }
+    match (pattern, expr) {
+        (Expr::True, Expr::True) | (Expr::False, Expr::False) => vec![bindings],
+        (Expr::Not(p), Expr::Not(e)) => matches(p, e, bindings),
+        (Expr::Ite(pc, pt, pe), Expr::Ite(c, t, e)) => matches(pc, c, bindings)
+            .into_iter()
+            .flat_map(|bindings| matches(pt, t, bindings))
+            .flat_map(|bindings| matches(pe, e, bindings))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// One rule applied to one subexpression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewriteStep {
+    pub rule: String,
+    pub before: Expr,
+    pub after: Expr,
+}
+
+/// The result of rewriting an expression to a fixpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rewrite {
+    pub original: Expr,
+    pub rewritten: Expr,
+    /// Rule applications, in the order they were made
+    pub steps: Vec<RewriteStep>,
+    /// False when the step budget was used up, so rules may still apply
+    pub complete: bool,
+}
+
+/// Applies a list of rules until none matches anywhere in the expression.
+///
+/// Children are rewritten before their parent, and at each node the first
+/// matching rule is applied, so a rule list that only shrinks expressions or
+/// pushes negations inward always reaches a fixpoint.
+#[derive(Debug, Clone)]
+pub struct Rewriter {
+    rules: Vec<Rule>,
+    step_budget: usize,
+}
+
+impl Default for Rewriter {
+    fn default() -> Self {
+        Self::new(standard_rules())
+    }
+}
+
+impl Rewriter {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self { rules, step_budget: DEFAULT_REWRITE_STEPS }
+    }
+
+    /// Stop after `steps` rule applications
+    pub fn with_step_budget(mut self, steps: usize) -> Self {
+        self.step_budget = steps;
+        self
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn rewrite(&self, expr: &Expr) -> Rewrite {
+        let mut steps = Vec::new();
+        let rewritten = self.rewrite_node(expr, &mut steps);
+        let complete = steps.len() < self.step_budget;
+        Rewrite { original: expr.clone(), rewritten, steps, complete }
+    }
+
+    fn rewrite_node(&self, expr: &Expr, steps: &mut Vec<RewriteStep>) -> Expr {
+        let expr = expr.map_children(|child| self.rewrite_node(child, steps));
+        if steps.len() >= self.step_budget {
+            return expr;
+        }
+        match self.rules.iter().find_map(|rule| rule.apply(&expr).map(|after| (rule, after))) {
+            Some((rule, after)) => {
+                steps.push(RewriteStep { rule: rule.name.clone(), before: expr, after: after.clone() });
+                self.rewrite_node(&after, steps)
+            }
+            None => expr,
+        }
+    }
+}
+
+/// The built-in simplifications: constants, double negation, De Morgan,
+/// idempotence, complements, absorption and the trivial cases of `→`, `↔`,
+/// `⊕` and `? :`
+pub fn standard_rules() -> Vec<Rule> {
+    vec![
+        Rule::new("double negation", expr!(not not x), expr!(x)),
+        Rule::new("negated constant", expr!(not true), expr!(false)),
+        Rule::new("negated constant", expr!(not false), expr!(true)),
+        Rule::new("De Morgan", expr!(not (x and y)), expr!(not x or not y)),
+        Rule::new("De Morgan", expr!(not (x or y)), expr!(not x and not y)),
+        Rule::new("identity", expr!(x and true), expr!(x)),
+        Rule::new("identity", expr!(x or false), expr!(x)),
+        Rule::new("identity", expr!(x xor false), expr!(x)),
+        Rule::new("identity", expr!(x iff true), expr!(x)),
+        Rule::new("annihilation", expr!(x and false), expr!(false)),
+        Rule::new("annihilation", expr!(x or true), expr!(true)),
+        Rule::new("negation by constant", expr!(x xor true), expr!(not x)),
+        Rule::new("negation by constant", expr!(x iff false), expr!(not x)),
+        Rule::new("idempotence", expr!(x and x), expr!(x)),
+        Rule::new("idempotence", expr!(x or x), expr!(x)),
+        Rule::new("complement", expr!(x and not x), expr!(false)),
+        Rule::new("complement", expr!(x or not x), expr!(true)),
+        Rule::new("complement", expr!(x xor x), expr!(false)),
+        Rule::new("complement", expr!(x iff x), expr!(true)),
+        Rule::new("absorption", expr!(x and (x or y)), expr!(x)),
+        Rule::new("absorption", expr!(x or (x and y)), expr!(x)),
+        Rule::new("absorption", expr!(x and (not x or y)), expr!(x and y)),
+        Rule::new("absorption", expr!(x or (not x and y)), expr!(x or y)),
+        Rule::new("trivial implication", expr!(true -> x), expr!(x)),
+        Rule::new("trivial implication", expr!(false -> x), expr!(true)),
+        Rule::new("trivial implication", expr!(x -> true), expr!(true)),
+        Rule::new("trivial implication", expr!(x -> x), expr!(true)),
+        Rule::new("trivial implication", expr!(x -> false), expr!(not x)),
+        Rule::new("constant condition", expr!(true ? x : y), expr!(x)),
+        Rule::new("constant condition", expr!(false ? x : y), expr!(y)),
+        Rule::new("equal branches", expr!(c ? x : x), expr!(x)),
+    ]
+}
+
+impl Expr {
+    /// Simplify with the [standard rules](standard_rules), keeping the
+    /// expression's structure where no rule applies
+    pub fn simplify(&self) -> Expr {
+        Rewriter::default().rewrite(self).rewritten
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Evaluator;
+
+    #[test]
+    fn test_rules_match_commuted_operands() {
+        let absorption = Rule::new("absorption", expr!(x and (x or y)), expr!(x));
+        assert_eq!(absorption.apply(&expr!((b or (p and q)) and (p and q))), Some(expr!(p and q)));
+        assert_eq!(absorption.apply(&expr!((b or c) and a)), None);
+    }
+
+    #[test]
+    fn test_rewrite_reaches_fixpoint() {
+        let expr = expr!(not (not a and not (b or false)) or (c and not c));
+        let rewrite = Rewriter::default().rewrite(&expr);
+        assert_eq!(rewrite.rewritten, expr!(a or b));
+        assert!(rewrite.complete);
+        assert!(rewrite.steps.iter().any(|step| step.rule == "De Morgan"));
+        assert!(Evaluator::check_equivalence(&expr, &rewrite.rewritten).unwrap().equivalent);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let mut rules = standard_rules();
+        rules.push(Rule::new("consensus", expr!((x and y) or (not x and z) or (y and z)), expr!((x and y) or (not x and z))));
+        let rewrite = Rewriter::new(rules).rewrite(&expr!((p and q) or (not p and r) or (q and r)));
+        assert_eq!(rewrite.rewritten.to_string(), "((p ∧ q) ∨ (¬p ∧ r))");
+        assert_eq!(rewrite.steps.len(), 1);
+    }
+
+    #[test]
+    fn test_structure_is_kept_where_no_rule_applies() {
+        assert_eq!(expr!((a xor b) -> (c ? d : a)).simplify(), expr!((a xor b) -> (c ? d : a)));
+        assert_eq!(expr!(vote and (vote or x) and true).simplify(), expr!(vote));
+    }
+
+    #[test]
+    fn test_step_budget() {
+        let rewrite = Rewriter::default().with_step_budget(1).rewrite(&expr!(not not not not a));
+        assert_eq!(rewrite.steps.len(), 1);
+        assert!(!rewrite.complete);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::source::{Expr, Cardinality, Quantifier, quote_identifier};
+use crate::source::lexer::keyword;
+use crate::source::parser::fold_chain;
+use crate::config::MAX_NESTING_DEPTH;
+use std::borrow::Cow;
+use std::ops::Range;
+use thiserror::Error;
+use miette::{Diagnostic, SourceSpan};
+
+/// Errors in an s-expression such as `(and a (not b))`
+#[derive(Error, Debug, Diagnostic)]
+pub enum SExprError {
+    #[error("Malformed s-expression: {message}")]
+    #[diagnostic(code(ttt::sexpr::syntax))]
+    Syntax {
+        message: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[error("Unknown operator '{op}'")]
+    #[diagnostic(
+        code(ttt::sexpr::unknown_operator),
+        help("The operators are not, and, or, nand, nor, xor, implies, iff, ite, atleast, atmost, exactly, forall and exists")
+    )]
+    UnknownOperator {
+        op: String,
+        #[label("not an operator")]
+        span: SourceSpan,
+    },
+
+    #[error("'{op}' expects {expected} argument(s), found {found}")]
+    #[diagnostic(code(ttt::sexpr::arity_mismatch))]
+    ArityMismatch {
+        op: String,
+        expected: String,
+        found: usize,
+        #[label("applied here")]
+        span: SourceSpan,
+    },
+
+    #[error("Expected one expression, found {found}")]
+    #[diagnostic(code(ttt::sexpr::expression_count), help("Combine the expressions with an operator, e.g. `(and ...)`"))]
+    ExpressionCount { found: usize },
+}
+
+/// A malformed s-expression, as read before any meaning is given to it
+#[derive(Debug)]
+pub(crate) struct ReadError {
+    pub message: String,
+    pub span: SourceSpan,
+}
+
+impl From<ReadError> for SExprError {
+    fn from(error: ReadError) -> Self {
+        SExprError::Syntax { message: error.message, span: error.span }
+    }
+}
+
+/// An s-expression with the byte range it was read from
+#[derive(Debug, Clone)]
+pub(crate) enum SExpr {
+    Atom(String, Range<usize>),
+    List(Vec<SExpr>, Range<usize>),
+}
+
+impl SExpr {
+    pub fn span(&self) -> SourceSpan {
+        let range = match self {
+            SExpr::Atom(_, range) | SExpr::List(_, range) => range,
+        };
+        (range.start, range.len()).into()
+    }
+
+    pub fn atom(&self) -> Option<&str> {
+        match self {
+            SExpr::Atom(text, _) => Some(text),
+            SExpr::List(..) => None,
+        }
+    }
+
+    /// Whether the atom was written between `|` bars
+    fn is_quoted(&self) -> bool {
+        matches!(self, SExpr::Atom(text, range) if range.len() != text.len())
+    }
+}
+
+/// Render an s-expression back to text (for messages)
+pub(crate) fn render(sexpr: &SExpr) -> String {
+    match sexpr {
+        SExpr::Atom(text, _) => text.clone(),
+        SExpr::List(items, _) => format!("({})", items.iter().map(render).collect::<Vec<_>>().join(" ")),
+    }
+}
+
+/// Read every top-level s-expression, skipping `;` comments
+pub(crate) fn read_all(input: &str) -> Result<Vec<SExpr>, ReadError> {
+    let mut reader = Reader { input, pos: 0, depth: 0 };
+    let mut exprs = Vec::new();
+    while reader.skip_trivia() {
+        exprs.push(reader.read()?);
+    }
+    Ok(exprs)
+}
+
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Lists open around the one being read
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    /// Skip whitespace and comments; returns false at end of input
+    fn skip_trivia(&mut self) -> bool {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += c.len_utf8();
+            } else if c == ';' {
+                self.pos = self.input[self.pos..].find('\n').map_or(self.input.len(), |i| self.pos + i);
+            } else {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn read(&mut self) -> Result<SExpr, ReadError> {
+        let start = self.pos;
+        match self.peek() {
+            Some('(') => {
+                if self.depth >= MAX_NESTING_DEPTH {
+                    return Err(ReadError { message: too_deep(), span: (start, 1).into() });
+                }
+                self.pos += 1;
+                self.depth += 1;
+                let list = self.list(start);
+                self.depth -= 1;
+                list
+            }
+            Some(')') => Err(ReadError { message: "unexpected ')'".to_string(), span: (start, 1).into() }),
+            Some(quote @ ('|' | '"')) => {
+                let body_start = start + 1;
+                let Some(len) = self.input[body_start..].find(quote) else {
+                    return Err(ReadError { message: format!("unterminated {}", quote), span: (start, 1).into() });
+                };
+                self.pos = body_start + len + 1;
+                let text = if quote == '|' {
+                    self.input[body_start..body_start + len].to_string()
+                } else {
+                    self.input[start..self.pos].to_string()
+                };
+                Ok(SExpr::Atom(text, start..self.pos))
+            }
+            _ => {
+                let len = self.input[start..]
+                    .find(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ';' | '|' | '"'))
+                    .unwrap_or(self.input.len() - start);
+                self.pos = start + len;
+                Ok(SExpr::Atom(self.input[start..self.pos].to_string(), start..self.pos))
+            }
+        }
+    }
+
+    /// The rest of the list opened at `start`
+    fn list(&mut self, start: usize) -> Result<SExpr, ReadError> {
+        let mut items = Vec::new();
+        loop {
+            if !self.skip_trivia() {
+                return Err(ReadError { message: "unclosed parenthesis".to_string(), span: (start, 1).into() });
+            }
+            if self.peek() == Some(')') {
+                self.pos += 1;
+                return Ok(SExpr::List(items, start..self.pos));
+            }
+            items.push(self.read()?);
+        }
+    }
+}
+
+/// The message for lists, or expressions read from them, nested deeper than
+/// [`MAX_NESTING_DEPTH`] levels
+pub(crate) fn too_deep() -> String {
+    format!("nested more than {} levels deep", MAX_NESTING_DEPTH)
+}
+
+/// Parse one s-expression: `(op operand...)` lists over names, `true` and
+/// `false`. Names that aren't plain ttt identifiers are written between bars,
+/// e.g. `|input valid|`.
+///
+/// `and`, `or` and `xor` take any number of operands, grouped from the left;
+/// `implies` (or `->`) and `iff` (or `<->`) take two.
+pub fn parse_sexpr(input: &str) -> Result<Expr, SExprError> {
+    let exprs = read_all(input)?;
+    let expr = match exprs.as_slice() {
+        [sexpr] => term(sexpr)?,
+        _ => return Err(SExprError::ExpressionCount { found: exprs.len() }),
+    };
+    if expr.depth() > MAX_NESTING_DEPTH {
+        return Err(SExprError::Syntax { message: too_deep(), span: exprs[0].span() });
+    }
+    Ok(expr)
+}
+
+fn arity(op: &str, expected: &str, found: usize, at: &SExpr) -> SExprError {
+    SExprError::ArityMismatch { op: op.to_string(), expected: expected.to_string(), found, span: at.span() }
+}
+
+fn term(sexpr: &SExpr) -> Result<Expr, SExprError> {
+    let items = match sexpr {
+        SExpr::Atom(name, _) if sexpr.is_quoted() => return Ok(Expr::Identifier(name.clone())),
+        SExpr::Atom(name, _) => {
+            return match name.as_str() {
+                "true" => Ok(Expr::True),
+                "false" => Ok(Expr::False),
+                _ if keyword(name).is_some() || name.starts_with(|c: char| c.is_ascii_digit()) => Err(SExprError::Syntax {
+                    message: format!("expected an operand, found '{}'", name),
+                    span: sexpr.span(),
+                }),
+                _ => Ok(Expr::Identifier(name.clone())),
+            };
+        }
+        SExpr::List(items, _) => items,
+    };
+    let Some(head) = items.first() else {
+        return Err(SExprError::Syntax { message: "expected an operator".to_string(), span: sexpr.span() });
+    };
+    let op = match head.atom() {
+        Some(op) if !head.is_quoted() => op,
+        _ => return Err(SExprError::UnknownOperator { op: render(head), span: head.span() }),
+    };
+    let args = &items[1..];
+
+    match op {
+        "forall" | "exists" => {
+            let quantifier = if op == "forall" { Quantifier::ForAll } else { Quantifier::Exists };
+            return match args {
+                [SExpr::Atom(var, _), body] => Ok(Expr::Quantified(quantifier, var.clone(), Box::new(term(body)?))),
+                [_, _] => Err(SExprError::Syntax { message: "expected a variable name".to_string(), span: args[0].span() }),
+                _ => Err(arity(op, "2", args.len(), sexpr)),
+            };
+        }
+        "atleast" | "atmost" | "exactly" => {
+            let kind = match op {
+                "atleast" => Cardinality::AtLeast,
+                "atmost" => Cardinality::AtMost,
+                _ => Cardinality::Exactly,
+            };
+            let Some((k, operands)) = args.split_first() else {
+                return Err(arity(op, "at least 1", 0, sexpr));
+            };
+            let k = k.atom().and_then(|k| k.parse::<usize>().ok())
+                .ok_or_else(|| SExprError::Syntax { message: "expected a count".to_string(), span: k.span() })?;
+            let operands = operands.iter().map(term).collect::<Result<Vec<_>, _>>()?;
+            return Ok(Expr::Cardinality(kind, k, operands));
+        }
+        _ => {}
+    }
+
+    let mut operands = args.iter().map(term).collect::<Result<Vec<_>, _>>()?;
+    let fold = |make: fn(Box<Expr>, Box<Expr>) -> Expr, operands: Vec<Expr>| {
+        fold_chain(operands, make, MAX_NESTING_DEPTH)
+            .map(|chain| chain.expect("non-empty"))
+            .map_err(|_| SExprError::Syntax { message: too_deep(), span: sexpr.span() })
+    };
+    let binary = |make: fn(Box<Expr>, Box<Expr>) -> Expr, mut operands: Vec<Expr>| {
+        let right = operands.pop().expect("two operands");
+        let left = operands.pop().expect("two operands");
+        make(Box::new(left), Box::new(right))
+    };
+
+    let expr = match (op, operands.len()) {
+        ("not", 1) => Expr::Not(Box::new(operands.remove(0))),
+        ("not", n) => return Err(arity(op, "1", n, sexpr)),
+        ("and" | "or" | "xor", 0) => return Err(arity(op, "at least 1", 0, sexpr)),
+        ("and", _) => fold(Expr::And, operands)?,
+        ("or", _) => fold(Expr::Or, operands)?,
+        ("xor", _) => fold(Expr::Xor, operands)?,
+        ("nand", 2) => binary(Expr::Nand, operands),
+        ("nor", 2) => binary(Expr::Nor, operands),
+        ("implies" | "->", 2) => binary(Expr::Implication, operands),
+        ("iff" | "xnor" | "<->", 2) => binary(Expr::Iff, operands),
+        ("nand" | "nor" | "implies" | "->" | "iff" | "xnor" | "<->", n) => return Err(arity(op, "2", n, sexpr)),
+        ("ite", 3) => {
+            let otherwise = operands.pop().expect("three operands");
+            let then = operands.pop().expect("three operands");
+            let cond = operands.pop().expect("three operands");
+            Expr::Ite(Box::new(cond), Box::new(then), Box::new(otherwise))
+        }
+        ("ite", n) => return Err(arity(op, "3", n, sexpr)),
+        _ => return Err(SExprError::UnknownOperator { op: op.to_string(), span: head.span() }),
+    };
+    Ok(expr)
+}
+
+/// Write `expr` as an s-expression that [`parse_sexpr`] reads back to the same
+/// expression. Left-nested chains of `and`, `or` and `xor` are written as one
+/// list, and custom operators as a list headed by their name.
+pub fn to_sexpr(expr: &Expr) -> String {
+    let list = |head: &str, operands: Vec<String>| format!("({} {})", head, operands.join(" "));
+    match expr {
+        Expr::Identifier(name) => match quote_identifier(name) {
+            Cow::Borrowed(name) => name.to_string(),
+            Cow::Owned(_) => format!("|{}|", name),
+        },
+        Expr::True => "true".to_string(),
+        Expr::False => "false".to_string(),
+        Expr::Not(inner) => list("not", vec![to_sexpr(inner)]),
+        Expr::And(..) => list("and", chain(expr, |e| matches!(e, Expr::And(..)))),
+        Expr::Or(..) => list("or", chain(expr, |e| matches!(e, Expr::Or(..)))),
+        Expr::Xor(..) => list("xor", chain(expr, |e| matches!(e, Expr::Xor(..)))),
+        Expr::Nand(left, right) => list("nand", vec![to_sexpr(left), to_sexpr(right)]),
+        Expr::Nor(left, right) => list("nor", vec![to_sexpr(left), to_sexpr(right)]),
+        Expr::Implication(left, right) => list("implies", vec![to_sexpr(left), to_sexpr(right)]),
+        Expr::Iff(left, right) => list("iff", vec![to_sexpr(left), to_sexpr(right)]),
+        Expr::Ite(cond, then, otherwise) => list("ite", vec![to_sexpr(cond), to_sexpr(then), to_sexpr(otherwise)]),
+        Expr::Cardinality(kind, k, operands) => {
+            list(&kind.to_string(), std::iter::once(k.to_string()).chain(operands.iter().map(to_sexpr)).collect())
+        }
+        Expr::Custom(operator, operands) => list(operator.display_name(), operands.iter().map(to_sexpr).collect()),
+        Expr::Quantified(quantifier, var, body) => {
+            list(quantifier.keyword(), vec![to_sexpr(&Expr::Identifier(var.clone())), to_sexpr(body)])
+        }
+    }
+}
+
+/// The operands of a left-nested chain of the binary node `expr`, whose kind `same` matches
+fn chain(expr: &Expr, same: fn(&Expr) -> bool) -> Vec<String> {
+    let (_, left, right) = expr.as_binary().expect("chains are binary");
+    let mut operands = if same(left) { chain(left, same) } else { vec![to_sexpr(left)] };
+    operands.push(to_sexpr(right));
+    operands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    #[test]
+    fn test_parse_sexpr() {
+        let parsed = parse_sexpr("(implies (and a b c) (or (not d) |input valid| false)) ; comment").unwrap();
+        assert_eq!(parsed, Parser::new("(a and b and c) -> (not d or `input valid` or false)").parse().unwrap());
+        let parsed = parse_sexpr("(forall x (ite x (atleast 2 a b x) (iff a |and|)))").unwrap();
+        assert_eq!(parsed, Parser::new("forall x. x ? atleast(2; a, b, x) : (a iff `and`)").parse().unwrap());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let inputs = [
+            "a and (b and c) and d",
+            "(a xor b) -> not (c nor d) <-> e",
+            "exists `x y`. atmost(1; `x y`, a) ? (b nand c) : true",
+        ];
+        for input in inputs {
+            let expr = Parser::new(input).parse().unwrap();
+            assert_eq!(parse_sexpr(&to_sexpr(&expr)).unwrap(), expr, "{}", to_sexpr(&expr));
+        }
+        assert_eq!(to_sexpr(&Parser::new("a and b and not c").parse().unwrap()), "(and a b (not c))");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse_sexpr("(and a"), Err(SExprError::Syntax { .. })));
+        assert!(matches!(parse_sexpr("(plus a b)"), Err(SExprError::UnknownOperator { .. })));
+        assert!(matches!(parse_sexpr("(implies a b c)"), Err(SExprError::ArityMismatch { found: 3, .. })));
+        assert!(matches!(parse_sexpr("(and a not)"), Err(SExprError::Syntax { .. })));
+        assert!(matches!(parse_sexpr("a b"), Err(SExprError::ExpressionCount { found: 2 })));
+        let deep = format!("{}a{}", "(not ".repeat(100_000), ")".repeat(100_000));
+        assert!(matches!(parse_sexpr(&deep), Err(SExprError::Syntax { .. })));
+        let chain = format!("(and{})", " a".repeat(1000));
+        assert!(matches!(parse_sexpr(&chain), Err(SExprError::Syntax { .. })));
+    }
+}
@@ -0,0 +1,356 @@
+use crate::config::MAX_NESTING_DEPTH;
+use crate::source::Expr;
+use crate::source::parser::fold_chain;
+use crate::source::sexpr::{SExpr, ReadError, read_all, render, too_deep};
+use std::collections::HashMap;
+use thiserror::Error;
+use miette::{Diagnostic, SourceSpan};
+
+/// Errors in an SMT-LIB2 script
+#[derive(Error, Debug, Diagnostic)]
+pub enum SmtLibError {
+    #[error("Malformed SMT-LIB input: {message}")]
+    #[diagnostic(code(ttt::smtlib::syntax))]
+    Syntax {
+        message: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[error("Unsupported SMT-LIB construct: {construct}")]
+    #[diagnostic(
+        code(ttt::smtlib::unsupported),
+        help("Only Bool constants, `assert`, `define-fun` without parameters, and the core Boolean operators are supported")
+    )]
+    Unsupported {
+        construct: String,
+        #[label("not supported")]
+        span: SourceSpan,
+    },
+
+    #[error("'{name}' is declared with sort {sort}")]
+    #[diagnostic(code(ttt::smtlib::unsupported_sort), help("Only Bool constants are supported"))]
+    UnsupportedSort {
+        name: String,
+        sort: String,
+        #[label("declared here")]
+        span: SourceSpan,
+    },
+
+    #[error("'{name}' is not a valid ttt variable name")]
+    #[diagnostic(
+        code(ttt::smtlib::invalid_name),
+        help("Variable names may only contain letters, digits, and underscores")
+    )]
+    InvalidName {
+        name: String,
+        #[label("declared here")]
+        span: SourceSpan,
+    },
+
+    #[error("'{name}' is used but never declared")]
+    #[diagnostic(code(ttt::smtlib::undeclared))]
+    Undeclared {
+        name: String,
+        #[label("used here")]
+        span: SourceSpan,
+    },
+
+    #[error("'{name}' is already declared")]
+    #[diagnostic(code(ttt::smtlib::duplicate_declaration))]
+    DuplicateDeclaration {
+        name: String,
+        #[label("declared again here")]
+        span: SourceSpan,
+    },
+
+    #[error("'{op}' expects {expected} argument(s), found {found}")]
+    #[diagnostic(code(ttt::smtlib::arity_mismatch))]
+    ArityMismatch {
+        op: String,
+        expected: String,
+        found: usize,
+        #[label("applied here")]
+        span: SourceSpan,
+    },
+
+    #[error("The script contains no assertions")]
+    #[diagnostic(code(ttt::smtlib::no_assertions), help("Add at least one `(assert ...)`"))]
+    NoAssertions,
+}
+
+impl From<ReadError> for SmtLibError {
+    fn from(error: ReadError) -> Self {
+        SmtLibError::Syntax { message: error.message, span: error.span }
+    }
+}
+
+/// Commands that don't affect the asserted formula
+const IGNORED_COMMANDS: &[&str] = &[
+    "set-logic", "set-info", "set-option", "check-sat", "get-model", "get-value",
+    "get-assignment", "get-info", "echo", "exit",
+];
+
+/// Parse an SMT-LIB2 script into the conjunction of its assertions
+pub fn parse_smtlib(input: &str) -> Result<Expr, SmtLibError> {
+    let mut script = Script::default();
+    let mut assertions = Vec::new();
+
+    for command in read_all(input)? {
+        let SExpr::List(items, _) = &command else {
+            return Err(SmtLibError::Syntax { message: "expected a command in parentheses".to_string(), span: command.span() });
+        };
+        let Some(head) = items.first().and_then(SExpr::atom) else {
+            return Err(SmtLibError::Syntax { message: "expected a command name".to_string(), span: command.span() });
+        };
+
+        match head {
+            "declare-const" => match items.as_slice() {
+                [_, name, sort] => script.declare(name, sort)?,
+                _ => return Err(arity(head, "2", items.len() - 1, &command)),
+            },
+            "declare-fun" => match items.as_slice() {
+                [_, name, SExpr::List(params, _), sort] if params.is_empty() => script.declare(name, sort)?,
+                [_, _, SExpr::List(..), _] => {
+                    return Err(SmtLibError::Unsupported { construct: "function with parameters".to_string(), span: command.span() });
+                }
+                _ => return Err(SmtLibError::Syntax { message: "expected `(declare-fun name () Bool)`".to_string(), span: command.span() }),
+            },
+            "define-fun" => match items.as_slice() {
+                [_, name, SExpr::List(params, _), sort, body] if params.is_empty() => {
+                    let name = script.symbol(name)?;
+                    script.check_sort(&name, sort)?;
+                    let expr = bounded(script.term(body)?, body)?;
+                    script.define(name, command.span(), expr)?;
+                }
+                [_, _, SExpr::List(..), _, _] => {
+                    return Err(SmtLibError::Unsupported { construct: "function with parameters".to_string(), span: command.span() });
+                }
+                _ => return Err(SmtLibError::Syntax { message: "expected `(define-fun name () Bool term)`".to_string(), span: command.span() }),
+            },
+            "assert" => match items.as_slice() {
+                [_, term] => assertions.push(bounded(script.term(term)?, term)?),
+                _ => return Err(arity(head, "1", items.len() - 1, &command)),
+            },
+            _ if IGNORED_COMMANDS.contains(&head) => {}
+            _ => return Err(SmtLibError::Unsupported { construct: format!("command '{}'", head), span: command.span() }),
+        }
+    }
+
+    fold_chain(assertions, Expr::And, MAX_NESTING_DEPTH)
+        .map_err(|_| SmtLibError::Syntax { message: too_deep(), span: (0, input.len()).into() })?
+        .ok_or(SmtLibError::NoAssertions)
+}
+
+/// `expr`, read from `at`, unless it nests more than [`MAX_NESTING_DEPTH`]
+/// levels deep; names bound to terms can nest deeper than what was written
+fn bounded(expr: Expr, at: &SExpr) -> Result<Expr, SmtLibError> {
+    if expr.depth() > MAX_NESTING_DEPTH {
+        return Err(SmtLibError::Syntax { message: too_deep(), span: at.span() });
+    }
+    Ok(expr)
+}
+
+fn arity(op: &str, expected: &str, found: usize, at: &SExpr) -> SmtLibError {
+    SmtLibError::ArityMismatch { op: op.to_string(), expected: expected.to_string(), found, span: at.span() }
+}
+
+/// Symbols in scope while reading a script
+#[derive(Default)]
+struct Script {
+    declared: HashMap<String, Expr>,
+    /// `let` bindings, innermost last
+    scopes: Vec<HashMap<String, Expr>>,
+}
+
+impl Script {
+    fn symbol(&self, sexpr: &SExpr) -> Result<String, SmtLibError> {
+        sexpr.atom()
+            .map(str::to_string)
+            .ok_or_else(|| SmtLibError::Syntax { message: "expected a symbol".to_string(), span: sexpr.span() })
+    }
+
+    fn check_sort(&self, name: &str, sort: &SExpr) -> Result<(), SmtLibError> {
+        match sort.atom() {
+            Some("Bool") => Ok(()),
+            _ => Err(SmtLibError::UnsupportedSort { name: name.to_string(), sort: render(sort), span: sort.span() }),
+        }
+    }
+
+    fn declare(&mut self, name: &SExpr, sort: &SExpr) -> Result<(), SmtLibError> {
+        let symbol = self.symbol(name)?;
+        self.check_sort(&symbol, sort)?;
+        if symbol.is_empty() || !symbol.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(SmtLibError::InvalidName { name: symbol, span: name.span() });
+        }
+        let expr = Expr::Identifier(symbol.clone());
+        self.define(symbol, name.span(), expr)
+    }
+
+    fn define(&mut self, name: String, span: SourceSpan, expr: Expr) -> Result<(), SmtLibError> {
+        if self.declared.contains_key(&name) {
+            return Err(SmtLibError::DuplicateDeclaration { name, span });
+        }
+        self.declared.insert(name, expr);
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Expr> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.declared.get(name))
+    }
+
+    fn term(&mut self, sexpr: &SExpr) -> Result<Expr, SmtLibError> {
+        let items = match sexpr {
+            SExpr::Atom(name, _) => {
+                match name.as_str() {
+                    "true" => return Ok(Expr::True),
+                    "false" => return Ok(Expr::False),
+                    _ => {}
+                }
+                return self.lookup(name)
+                    .cloned()
+                    .ok_or_else(|| SmtLibError::Undeclared { name: name.clone(), span: sexpr.span() });
+            }
+            SExpr::List(items, _) => items,
+        };
+        let Some(op) = items.first().and_then(SExpr::atom) else {
+            return Err(SmtLibError::Syntax { message: "expected an operator".to_string(), span: sexpr.span() });
+        };
+        let args = &items[1..];
+
+        match op {
+            "let" => return self.let_term(args, sexpr),
+            // Annotations such as `(! t :named n)` don't change the term
+            "!" if !args.is_empty() => return self.term(&args[0]),
+            _ => {}
+        }
+
+        let operands = args.iter().map(|arg| self.term(arg)).collect::<Result<Vec<_>, _>>()?;
+        let binary = |make: fn(Box<Expr>, Box<Expr>) -> Expr, operands: Vec<Expr>| {
+            fold_chain(operands, make, MAX_NESTING_DEPTH)
+                .map(|chain| chain.expect("non-empty"))
+                .map_err(|_| SmtLibError::Syntax { message: too_deep(), span: sexpr.span() })
+        };
+        let iff = |left: &Expr, right: &Expr| Expr::Iff(Box::new(left.clone()), Box::new(right.clone()));
+
+        let expr = match (op, operands.len()) {
+            ("not", 1) => Expr::Not(Box::new(operands[0].clone())),
+            ("not", n) => return Err(arity(op, "1", n, sexpr)),
+            ("and" | "or" | "xor", 0) => return Err(arity(op, "at least 1", 0, sexpr)),
+            ("and", _) => binary(Expr::And, operands)?,
+            ("or", _) => binary(Expr::Or, operands)?,
+            ("xor", _) => binary(Expr::Xor, operands)?,
+            ("=>" | "=" | "distinct", n) if n < 2 => return Err(arity(op, "at least 2", n, sexpr)),
+            // Implication is right-associative
+            ("=>", _) => binary(|acc, next| Expr::Implication(next, acc), operands.into_iter().rev().collect())?,
+            // Chainable: each adjacent pair is equal
+            ("=", _) => binary(Expr::And, operands.windows(2).map(|pair| iff(&pair[0], &pair[1])).collect())?,
+            // Pairwise: every two operands differ
+            ("distinct", _) => {
+                let pairs = (0..operands.len())
+                    .flat_map(|i| (i + 1..operands.len()).map(move |j| (i, j)))
+                    .map(|(i, j)| Expr::Xor(Box::new(operands[i].clone()), Box::new(operands[j].clone())))
+                    .collect();
+                binary(Expr::And, pairs)?
+            }
+            ("ite", 3) => Expr::Ite(
+                Box::new(operands[0].clone()),
+                Box::new(operands[1].clone()),
+                Box::new(operands[2].clone()),
+            ),
+            ("ite", n) => return Err(arity(op, "3", n, sexpr)),
+            _ => return Err(SmtLibError::Unsupported { construct: format!("operator '{}'", op), span: items[0].span() }),
+        };
+        Ok(expr)
+    }
+
+    /// `(let ((x t) ...) body)`: bindings are evaluated in the outer scope
+    fn let_term(&mut self, args: &[SExpr], sexpr: &SExpr) -> Result<Expr, SmtLibError> {
+        let [SExpr::List(bindings, _), body] = args else {
+            return Err(SmtLibError::Syntax { message: "expected `(let ((name term) ...) body)`".to_string(), span: sexpr.span() });
+        };
+
+        let mut scope = HashMap::new();
+        for binding in bindings {
+            match binding {
+                SExpr::List(pair, _) if pair.len() == 2 => {
+                    let name = self.symbol(&pair[0])?;
+                    let value = bounded(self.term(&pair[1])?, &pair[1])?;
+                    scope.insert(name, value);
+                }
+                _ => return Err(SmtLibError::Syntax { message: "expected `(name term)`".to_string(), span: binding.span() }),
+            }
+        }
+
+        self.scopes.push(scope);
+        let result = self.term(body);
+        self.scopes.pop();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn equivalent(smt: &str, ttt: &str) {
+        let parsed = parse_smtlib(smt).unwrap();
+        let expected = Parser::new(ttt).parse().unwrap();
+        assert_eq!(parsed, expected, "{}", smt);
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script = "\
+(set-logic QF_UF)
+; two inputs
+(declare-const a Bool)
+(declare-fun b () Bool)
+(assert (or a b))
+(assert (not (and a b)))
+(check-sat)
+";
+        equivalent(script, "(a or b) and not (a and b)");
+    }
+
+    #[test]
+    fn test_nary_and_chained_operators() {
+        let decls = "(declare-const a Bool)(declare-const b Bool)(declare-const c Bool)";
+        equivalent(&format!("{} (assert (and a b c))", decls), "a and b and c");
+        equivalent(&format!("{} (assert (=> a b c))", decls), "a -> (b -> c)");
+        equivalent(&format!("{} (assert (= a b))", decls), "a <-> b");
+        equivalent(&format!("{} (assert (ite a b c))", decls), "ite(a, b, c)");
+        equivalent(&format!("{} (assert (or a false))", decls), "a or false");
+    }
+
+    #[test]
+    fn test_let_and_define_fun() {
+        let script = "\
+(declare-const a Bool)
+(declare-const b Bool)
+(define-fun both () Bool (and a b))
+(assert (let ((x (not a))) (or x both)))
+";
+        equivalent(script, "not a or (a and b)");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse_smtlib("(declare-const x Int)(assert x)"), Err(SmtLibError::UnsupportedSort { .. })));
+        assert!(matches!(parse_smtlib("(assert y)"), Err(SmtLibError::Undeclared { .. })));
+        assert!(matches!(parse_smtlib("(declare-const a Bool)"), Err(SmtLibError::NoAssertions)));
+        assert!(matches!(parse_smtlib("(declare-const a Bool)(push 1)"), Err(SmtLibError::Unsupported { .. })));
+        assert!(matches!(parse_smtlib("(assert (and a"), Err(SmtLibError::Syntax { .. })));
+        let deep = format!("(declare-const a Bool)(assert {}a{})", "(not ".repeat(100_000), ")".repeat(100_000));
+        assert!(matches!(parse_smtlib(&deep), Err(SmtLibError::Syntax { .. })));
+        // Each definition is shallow, but they nest inside one another
+        let definitions: String = (1..1000).map(|i| format!("(define-fun f{} () Bool (not f{}))", i, i - 1)).collect();
+        let script = format!("(declare-const f0 Bool){}(assert f999)", definitions);
+        assert!(matches!(parse_smtlib(&script), Err(SmtLibError::Syntax { .. })));
+    }
+}
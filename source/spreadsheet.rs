@@ -0,0 +1,430 @@
+use crate::config::{MAX_NESTING_DEPTH, MAX_VARIABLES};
+use crate::source::Expr;
+use crate::source::parser::fold_chain;
+use std::ops::Range;
+use thiserror::Error;
+use miette::{Diagnostic, SourceSpan};
+
+/// Errors in a spreadsheet formula such as `=AND(A1, NOT(B1))`
+#[derive(Error, Debug, Diagnostic)]
+pub enum SpreadsheetError {
+    #[error("Malformed formula: {message}")]
+    #[diagnostic(code(ttt::spreadsheet::syntax))]
+    Syntax {
+        message: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[error("Unsupported function '{name}'")]
+    #[diagnostic(
+        code(ttt::spreadsheet::unknown_function),
+        help("The logical functions are AND, OR, XOR, NOT, IF, TRUE and FALSE")
+    )]
+    UnknownFunction {
+        name: String,
+        #[label("not a logical function")]
+        span: SourceSpan,
+    },
+
+    #[error("{function} expects {expected} argument(s), found {found}")]
+    #[diagnostic(code(ttt::spreadsheet::arity_mismatch))]
+    ArityMismatch {
+        function: String,
+        expected: String,
+        found: usize,
+        #[label("called here")]
+        span: SourceSpan,
+    },
+
+    #[error("The range {range} covers {cells} cells")]
+    #[diagnostic(code(ttt::spreadsheet::range_too_large), help("A range may cover at most {max} cells, one variable each"))]
+    RangeTooLarge {
+        range: String,
+        cells: usize,
+        max: usize,
+        #[label("this range")]
+        span: SourceSpan,
+    },
+}
+
+/// Parse an Excel or Google Sheets logical formula, with cell references as
+/// variables.
+///
+/// The leading `=` is optional. `A1` and `$A$1` both become the variable `A1`;
+/// a reference to another sheet, such as `Inputs!B2` or `'Q1 data'!B2`, becomes
+/// `Inputs_B2` or `Q1_data_B2`. Other names, such as named ranges, are kept as
+/// written. Ranges like `A1:B2` stand for each of their cells and may only be
+/// given to `AND`, `OR` and `XOR`. Comparing two logical values with `=` or
+/// `<>` is read as `↔` or `⊕`. Arguments may be separated by `,` or, as in
+/// locales with a decimal comma, `;`.
+pub fn parse_spreadsheet(input: &str) -> Result<Expr, SpreadsheetError> {
+    let mut formula = Formula { input, pos: 0, depth: 0 };
+    formula.skip_whitespace();
+    if formula.peek() == Some('=') {
+        formula.pos += 1;
+    }
+    let expr = formula.comparison()?;
+    formula.skip_whitespace();
+    if formula.pos < input.len() {
+        return Err(syntax("expected the end of the formula", formula.pos..input.len()));
+    }
+    if expr.depth() > MAX_NESTING_DEPTH {
+        return Err(too_deep(0..input.len()));
+    }
+    Ok(expr)
+}
+
+/// A function argument: one value, or every cell of a range
+enum Argument {
+    Value(Expr),
+    Cells(Vec<Expr>),
+}
+
+struct Formula<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Parentheses and calls open around the current position
+    depth: usize,
+}
+
+impl<'a> Formula<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `token` after any whitespace
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.input[self.pos..].starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    /// Values compared with `=` and `<>`, from the left as spreadsheets do
+    fn comparison(&mut self) -> Result<Expr, SpreadsheetError> {
+        let depth = self.depth;
+        let mut left = self.operand()?;
+        loop {
+            if self.eat("<>") {
+                self.chained()?;
+                left = Expr::Xor(Box::new(left), Box::new(self.operand()?));
+            } else if self.eat("=") {
+                self.chained()?;
+                left = Expr::Iff(Box::new(left), Box::new(self.operand()?));
+            } else {
+                self.depth = depth;
+                return Ok(left);
+            }
+        }
+    }
+
+    fn operand(&mut self) -> Result<Expr, SpreadsheetError> {
+        let start = self.pos;
+        match self.argument()? {
+            Argument::Value(expr) => Ok(expr),
+            Argument::Cells(_) => Err(syntax("a range can only be an argument of AND, OR or XOR", start..self.pos)),
+        }
+    }
+
+    fn argument(&mut self) -> Result<Argument, SpreadsheetError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.eat("(") {
+            let expr = self.nested(start, Self::comparison)?;
+            if !self.eat(")") {
+                return Err(syntax("expected ')'", start..self.pos));
+            }
+            return Ok(Argument::Value(expr));
+        }
+        if self.peek() == Some('\'') {
+            let sheet = self.quoted_sheet()?;
+            return self.reference(Some(sheet), start);
+        }
+
+        let word = self.word();
+        if word.is_empty() {
+            let message = if self.pos == self.input.len() { "unexpected end of formula" } else { "expected a value" };
+            return Err(syntax(message, start..self.pos + self.peek().map_or(0, char::len_utf8)));
+        }
+        if self.eat("(") {
+            return self.nested(start, |formula| formula.call(word, start)).map(Argument::Value);
+        }
+        if self.input[self.pos..].starts_with('!') {
+            self.pos += 1;
+            return self.reference(Some(word.to_string()), start);
+        }
+        match word.to_ascii_uppercase().as_str() {
+            "TRUE" => return Ok(Argument::Value(Expr::True)),
+            "FALSE" => return Ok(Argument::Value(Expr::False)),
+            _ => {}
+        }
+        if cell(word).is_some() {
+            self.pos = start;
+            return self.reference(None, start);
+        }
+        if word.starts_with(|c: char| c.is_alphabetic() || c == '_') && word.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Ok(Argument::Value(Expr::Identifier(word.to_string())));
+        }
+        Err(syntax(&format!("'{}' is not a cell reference or a name", word), start..self.pos))
+    }
+
+    /// Letters, digits, `_`, `.` and `$`, the characters of names and references
+    fn word(&mut self) -> &'a str {
+        let start = self.pos;
+        let rest = &self.input[start..];
+        let len = rest.find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '$'))).unwrap_or(rest.len());
+        self.pos += len;
+        &self.input[start..start + len]
+    }
+
+    /// `'Sheet name'!`, with `''` for a quote inside the name
+    fn quoted_sheet(&mut self) -> Result<String, SpreadsheetError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut name = String::new();
+        loop {
+            match self.peek() {
+                Some('\'') if self.input[self.pos + 1..].starts_with('\'') => {
+                    name.push('\'');
+                    self.pos += 2;
+                }
+                Some('\'') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(c) => {
+                    name.push(c);
+                    self.pos += c.len_utf8();
+                }
+                None => return Err(syntax("unterminated sheet name", start..self.pos)),
+            }
+        }
+        if !self.input[self.pos..].starts_with('!') {
+            return Err(syntax("expected '!' after the sheet name", start..self.pos));
+        }
+        self.pos += 1;
+        Ok(name)
+    }
+
+    /// A cell, or a range of cells, on `sheet` or the formula's own sheet
+    fn reference(&mut self, sheet: Option<String>, start: usize) -> Result<Argument, SpreadsheetError> {
+        let cell_start = self.pos;
+        let word = self.word();
+        let Some(first) = cell(word) else {
+            return Err(syntax("expected a cell reference such as A1", cell_start..self.pos.max(cell_start + 1).min(self.input.len())));
+        };
+        let prefix = sheet.map(|name| format!("{}_", name.replace(|c: char| !c.is_alphanumeric(), "_"))).unwrap_or_default();
+        if !self.input[self.pos..].starts_with(':') {
+            return Ok(Argument::Value(Expr::Identifier(format!("{}{}", prefix, cell_name(first)))));
+        }
+
+        self.pos += 1;
+        let end_start = self.pos;
+        let Some(last) = cell(self.word()) else {
+            return Err(syntax("expected the cell that ends the range", end_start..self.pos.max(end_start + 1).min(self.input.len())));
+        };
+        let (columns, rows) = (first.0.min(last.0)..=first.0.max(last.0), first.1.min(last.1)..=first.1.max(last.1));
+        let cells = columns.clone().count() * rows.clone().count();
+        if cells > MAX_VARIABLES {
+            let range = self.input[start..self.pos].to_string();
+            return Err(SpreadsheetError::RangeTooLarge { range, cells, max: MAX_VARIABLES, span: span(start..self.pos) });
+        }
+        // Row by row, the order spreadsheets visit a range in
+        let cells = rows
+            .flat_map(|row| columns.clone().map(move |column| (column, row)))
+            .map(|cell| Expr::Identifier(format!("{}{}", prefix, cell_name(cell))))
+            .collect();
+        Ok(Argument::Cells(cells))
+    }
+
+    /// The arguments of `name(`, already consumed, and the expression it stands for
+    fn call(&mut self, name: &str, start: usize) -> Result<Expr, SpreadsheetError> {
+        let mut arguments = Vec::new();
+        if !self.eat(")") {
+            loop {
+                arguments.push(self.argument_or_comparison()?);
+                if self.eat(")") {
+                    break;
+                }
+                if !self.eat(",") && !self.eat(";") {
+                    return Err(syntax("expected ',' or ')'", self.pos..self.pos + self.peek().map_or(0, char::len_utf8)));
+                }
+            }
+        }
+        let at = start..self.pos;
+        // Newer functions are saved with a prefix, as in `_xlfn.XOR`
+        let function = name.trim_start_matches("_xlfn.").to_ascii_uppercase();
+        let arity = |expected: &str, found: usize| SpreadsheetError::ArityMismatch {
+            function: function.clone(),
+            expected: expected.to_string(),
+            found,
+            span: span(at.clone()),
+        };
+
+        let make: fn(Box<Expr>, Box<Expr>) -> Expr = match function.as_str() {
+            "AND" => Expr::And,
+            "OR" => Expr::Or,
+            "XOR" => Expr::Xor,
+            "TRUE" | "FALSE" if !arguments.is_empty() => return Err(arity("0", arguments.len())),
+            "TRUE" => return Ok(Expr::True),
+            "FALSE" => return Ok(Expr::False),
+            "NOT" | "IF" => {
+                let found = arguments.len();
+                let mut values = Vec::new();
+                for argument in arguments {
+                    match argument {
+                        Argument::Value(expr) => values.push(expr),
+                        Argument::Cells(_) => return Err(syntax(&format!("{} does not take a range", function), at)),
+                    }
+                }
+                return match (function.as_str(), values.as_slice()) {
+                    ("NOT", [value]) => Ok(Expr::Not(Box::new(value.clone()))),
+                    ("NOT", _) => Err(arity("1", found)),
+                    // Without a third argument, IF is FALSE when the condition doesn't hold
+                    ("IF", [cond, then]) => Ok(Expr::Ite(Box::new(cond.clone()), Box::new(then.clone()), Box::new(Expr::False))),
+                    ("IF", [cond, then, otherwise]) => {
+                        Ok(Expr::Ite(Box::new(cond.clone()), Box::new(then.clone()), Box::new(otherwise.clone())))
+                    }
+                    _ => Err(arity("2 or 3", found)),
+                };
+            }
+            _ => return Err(SpreadsheetError::UnknownFunction { name: name.to_string(), span: span(start..start + name.len()) }),
+        };
+        let operands = arguments.into_iter().flat_map(|argument| match argument {
+            Argument::Value(expr) => vec![expr],
+            Argument::Cells(cells) => cells,
+        });
+        fold_chain(operands, make, MAX_NESTING_DEPTH)
+            .map_err(|_| too_deep(at.clone()))?
+            .ok_or_else(|| arity("at least 1", 0))
+    }
+
+    /// An argument that may be a range, or a comparison of values
+    fn argument_or_comparison(&mut self) -> Result<Argument, SpreadsheetError> {
+        let start = self.pos;
+        let argument = self.argument()?;
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        if !rest.starts_with('=') && !rest.starts_with("<>") {
+            return Ok(argument);
+        }
+        self.pos = start;
+        self.comparison().map(Argument::Value)
+    }
+
+    /// Run `parse` one level further in, refusing to go deeper than
+    /// [`MAX_NESTING_DEPTH`]
+    fn nested<T>(&mut self, start: usize, parse: impl FnOnce(&mut Self) -> Result<T, SpreadsheetError>) -> Result<T, SpreadsheetError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(too_deep(start..self.pos));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Count the operand after the operator just read as a level, since each
+    /// operand of a chain nests the ones before it
+    fn chained(&mut self) -> Result<(), SpreadsheetError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(too_deep(self.pos..self.pos));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+/// The column and row of a reference such as `B12` or `$B$12`, columns counted from 1
+fn cell(word: &str) -> Option<(u32, u32)> {
+    let rest = word.strip_prefix('$').unwrap_or(word);
+    let letters = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+    let (column, rest) = rest.split_at(letters);
+    let row = rest.strip_prefix('$').unwrap_or(rest);
+    // Spreadsheets stop at column XFD, three letters
+    if !(1..=3).contains(&column.len()) || row.is_empty() || !row.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let column = column.bytes().fold(0, |acc, b| acc * 26 + u32::from(b.to_ascii_uppercase() - b'A' + 1));
+    row.parse().ok().filter(|&row| row > 0).map(|row| (column, row))
+}
+
+fn cell_name((column, row): (u32, u32)) -> String {
+    let mut letters = Vec::new();
+    let mut column = column;
+    while column > 0 {
+        letters.push(char::from(b'A' + ((column - 1) % 26) as u8));
+        column = (column - 1) / 26;
+    }
+    letters.iter().rev().collect::<String>() + &row.to_string()
+}
+
+fn span(range: Range<usize>) -> SourceSpan {
+    (range.start, range.len()).into()
+}
+
+fn syntax(message: &str, range: Range<usize>) -> SpreadsheetError {
+    SpreadsheetError::Syntax { message: message.to_string(), span: span(range) }
+}
+
+fn too_deep(range: Range<usize>) -> SpreadsheetError {
+    syntax(&format!("nested more than {} levels deep", MAX_NESTING_DEPTH), range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    /// Names with digits go in backticks in ttt's grammar
+    fn equivalent(formula: &str, ttt: &str) {
+        let parsed = parse_spreadsheet(formula).unwrap();
+        let expected = Parser::new(ttt).parse().unwrap();
+        assert_eq!(parsed, expected, "{}", formula);
+    }
+
+    #[test]
+    fn test_logical_functions() {
+        equivalent("=AND(A1, OR(B1, NOT(C1)))", "`A1` and (`B1` or not `C1`)");
+        equivalent("=and($a$1;b$2;TRUE)", "`A1` and `B2` and true");
+        equivalent("=_xlfn.XOR(A1, B1, C1)", "`A1` xor `B1` xor `C1`");
+        equivalent("IF(A1, B1)", "`A1` ? `B1` : false");
+        equivalent("=IF(A1 <> B1, Approved, FALSE())", "(`A1` xor `B1`) ? Approved : false");
+        equivalent("=NOT(A1=B1=C1)", "not ((`A1` iff `B1`) iff `C1`)");
+        equivalent("=OR(Inputs!A1, 'Q1 data'!$B$2, ((AA10)))", "`Inputs_A1` or `Q1_data_B2` or `AA10`");
+    }
+
+    #[test]
+    fn test_ranges() {
+        equivalent("=AND(A1:B2)", "`A1` and `B1` and `A2` and `B2`");
+        equivalent("=OR(Sheet2!B3:B1, C1)", "`Sheet2_B1` or `Sheet2_B2` or `Sheet2_B3` or `C1`");
+        assert_eq!(cell_name(cell("XFD7").unwrap()), "XFD7");
+        assert!(matches!(parse_spreadsheet("=AND(A1:Z100)"), Err(SpreadsheetError::RangeTooLarge { cells: 2600, .. })));
+        assert!(matches!(parse_spreadsheet("=NOT(A1:A2)"), Err(SpreadsheetError::Syntax { .. })));
+        assert!(matches!(parse_spreadsheet("=A1:A2"), Err(SpreadsheetError::Syntax { .. })));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse_spreadsheet("=SUM(A1, B1)"), Err(SpreadsheetError::UnknownFunction { .. })));
+        assert!(matches!(parse_spreadsheet("=NOT(A1, B1)"), Err(SpreadsheetError::ArityMismatch { found: 2, .. })));
+        assert!(matches!(parse_spreadsheet("=AND()"), Err(SpreadsheetError::ArityMismatch { found: 0, .. })));
+        assert!(matches!(parse_spreadsheet("=AND(A1, B1"), Err(SpreadsheetError::Syntax { .. })));
+        assert!(matches!(parse_spreadsheet("=A1 + B1"), Err(SpreadsheetError::Syntax { .. })));
+        assert!(matches!(parse_spreadsheet("='Sheet 1'A1"), Err(SpreadsheetError::Syntax { .. })));
+        let deep = format!("={}A1{}", "NOT(".repeat(1000), ")".repeat(1000));
+        assert!(matches!(parse_spreadsheet(&deep), Err(SpreadsheetError::Syntax { .. })));
+        let chain = format!("=A1{}", "=B1".repeat(1000));
+        assert!(matches!(parse_spreadsheet(&chain), Err(SpreadsheetError::Syntax { .. })));
+        let arguments = format!("=AND({}A1)", "B1, ".repeat(1000));
+        assert!(matches!(parse_spreadsheet(&arguments), Err(SpreadsheetError::Syntax { .. })));
+    }
+}
@@ -0,0 +1,412 @@
+use crate::config::{MAX_NESTING_DEPTH, MAX_VARIABLE_NAME_LENGTH};
+use crate::source::Expr;
+use crate::source::fingerprint::fnv1a;
+use std::ops::Range;
+use thiserror::Error;
+use miette::{Diagnostic, SourceSpan};
+
+/// Errors in an SQL predicate such as `status = 'open' AND NOT archived`
+#[derive(Error, Debug, Diagnostic)]
+pub enum SqlError {
+    #[error("Malformed SQL predicate: {message}")]
+    #[diagnostic(code(ttt::sql::syntax))]
+    Syntax {
+        message: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[error("Unclosed {delimiter}")]
+    #[diagnostic(code(ttt::sql::unclosed))]
+    Unclosed {
+        delimiter: String,
+        #[label("opened here")]
+        span: SourceSpan,
+    },
+}
+
+/// Words written in capitals when a condition is named; other unquoted words
+/// are folded to lower case, as PostgreSQL folds names
+const KEYWORDS: &[&str] = &[
+    "ALL", "AND", "ANY", "AS", "ASC", "BETWEEN", "BY", "CASE", "CAST", "DESC", "DISTINCT", "ELSE", "END",
+    "ESCAPE", "EXISTS", "FALSE", "FROM", "GROUP", "HAVING", "ILIKE", "IN", "IS", "JOIN", "LIKE", "LIMIT",
+    "NOT", "NULL", "ON", "OR", "ORDER", "SELECT", "SIMILAR", "SOME", "THEN", "TO", "TRUE", "UNKNOWN",
+    "WHEN", "WHERE",
+];
+
+/// Parse the boolean skeleton of an SQL predicate, such as a `WHERE` clause.
+///
+/// `AND`, `OR`, `NOT` and parentheses become operators, and every other
+/// condition, such as `o.total > 100` or `name LIKE 'A%'`, becomes a variable
+/// named after its text. Whitespace, comments and the case of unquoted words
+/// don't matter and `!=` is read as `<>`, so conditions written the same way
+/// share a variable, in this predicate and in any other read the same way. A name
+/// longer than ttt allows keeps the start of the condition and a hash of the
+/// whole. `TRUE` and `FALSE` are constants; a leading `WHERE` and a trailing
+/// `;` are skipped.
+pub fn parse_sql(input: &str) -> Result<Expr, SqlError> {
+    let mut tokens = tokenize(input)?;
+    if tokens.last().is_some_and(|token| token.text == ";") {
+        tokens.pop();
+    }
+    let mut predicate = Predicate { tokens: &tokens, pos: 0, depth: 0, end: input.len() };
+    if predicate.peek().is_some_and(|token| token.is_keyword("WHERE")) {
+        predicate.pos += 1;
+    }
+    let expr = predicate.or()?;
+    match predicate.peek() {
+        None => Ok(expr),
+        Some(token) if token.kind == Kind::Close => Err(syntax("')' without a matching '('", token.range.clone())),
+        Some(token) => Err(syntax("expected AND, OR or the end of the predicate", token.range.clone())),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A name, keyword or number
+    Word,
+    /// A string literal or quoted name, quotes included
+    Quoted,
+    Symbol,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    text: String,
+    range: Range<usize>,
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == Kind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+}
+
+struct Predicate<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    /// Groups open around the current position
+    depth: usize,
+    /// Where the input ends, for errors there
+    end: usize,
+}
+
+impl Predicate<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_some_and(|token| token.is_keyword(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, SqlError> {
+        let depth = self.depth;
+        let mut left = self.and()?;
+        while self.eat_keyword("OR") {
+            self.chained()?;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, SqlError> {
+        let depth = self.depth;
+        let mut left = self.not()?;
+        while self.eat_keyword("AND") {
+            self.chained()?;
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, SqlError> {
+        let start = self.pos;
+        if self.eat_keyword("NOT") {
+            return self.nested(start, |predicate| predicate.not()).map(|expr| Expr::Not(Box::new(expr)));
+        }
+        self.primary()
+    }
+
+    /// A parenthesized predicate, or a condition taken as a whole
+    fn primary(&mut self) -> Result<Expr, SqlError> {
+        let start = self.pos;
+        if self.peek().is_some_and(|token| token.kind == Kind::Open) && self.is_group() {
+            self.pos += 1;
+            let expr = self.nested(start, Self::or)?;
+            return match self.peek() {
+                Some(token) if token.kind == Kind::Close => {
+                    self.pos += 1;
+                    Ok(expr)
+                }
+                Some(token) => Err(syntax("expected AND, OR or ')'", token.range.clone())),
+                None => Err(SqlError::Unclosed { delimiter: "'('".to_string(), span: span(self.tokens[start].range.clone()) }),
+            };
+        }
+        self.atom()
+    }
+
+    /// Whether the parenthesis at the current position groups a predicate, as
+    /// in `(a = 1 OR b = 2) AND c`, rather than opening part of a condition, as
+    /// in `(a + b) > 3`
+    fn is_group(&self) -> bool {
+        let mut depth = 0;
+        for (idx, token) in self.tokens.iter().enumerate().skip(self.pos) {
+            match token.kind {
+                Kind::Open => depth += 1,
+                Kind::Close => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return match self.tokens.get(idx + 1) {
+                    None => true,
+                    Some(next) => next.kind == Kind::Close || next.is_keyword("AND") || next.is_keyword("OR"),
+                };
+            }
+        }
+        // Unbalanced; reading it as a group reports the missing ')'
+        true
+    }
+
+    /// The tokens of one condition, up to an `AND` or `OR` that joins it to the
+    /// next; the `AND` of a `BETWEEN` and anything inside parentheses or a
+    /// `CASE` belong to the condition
+    fn atom(&mut self) -> Result<Expr, SqlError> {
+        let start = self.pos;
+        let mut depth = 0usize;
+        let mut between = false;
+        while let Some(token) = self.peek() {
+            if depth == 0 {
+                if token.kind == Kind::Close || token.is_keyword("OR") {
+                    break;
+                }
+                if token.is_keyword("AND") {
+                    if !between {
+                        break;
+                    }
+                    between = false;
+                }
+                between |= token.is_keyword("BETWEEN");
+            }
+            match token.kind {
+                Kind::Open => depth += 1,
+                Kind::Close => depth -= 1,
+                Kind::Word if token.is_keyword("CASE") => depth += 1,
+                Kind::Word if token.is_keyword("END") => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        let atom = &self.tokens[start..self.pos];
+        match atom {
+            [] => {
+                let range = self.peek().map_or(self.end..self.end, |token| token.range.clone());
+                Err(syntax("expected a condition", range))
+            }
+            [token] if token.is_keyword("TRUE") => Ok(Expr::True),
+            [token] if token.is_keyword("FALSE") => Ok(Expr::False),
+            _ if depth > 0 => Err(SqlError::Unclosed { delimiter: "'(' or CASE".to_string(), span: span(atom[0].range.start..self.end) }),
+            _ => Ok(Expr::Identifier(condition_variable(render(atom)))),
+        }
+    }
+
+    /// Run `parse` one group further in, refusing to go deeper than
+    /// [`MAX_NESTING_DEPTH`]
+    fn nested(&mut self, start: usize, parse: impl FnOnce(&mut Self) -> Result<Expr, SqlError>) -> Result<Expr, SqlError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            let message = format!("nested more than {} levels deep", MAX_NESTING_DEPTH);
+            return Err(syntax(&message, self.tokens[start].range.clone()));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Count the operand after the operator just read as a level, since each
+    /// operand of a chain nests the ones before it
+    fn chained(&mut self) -> Result<(), SqlError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            let message = format!("nested more than {} levels deep", MAX_NESTING_DEPTH);
+            return Err(syntax(&message, self.tokens[self.pos - 1].range.clone()));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+/// A condition's tokens written out the same way whatever the spacing and case
+fn render(tokens: &[Token]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&Token> = None;
+    for token in tokens {
+        let joined = match previous {
+            None => true,
+            Some(previous) => {
+                previous.kind == Kind::Open
+                    || matches!(token.kind, Kind::Close)
+                    || token.text == ","
+                    || token.text == "::"
+                    || previous.text == "::"
+                    // A function call, as opposed to `IN (...)`
+                    || (token.kind == Kind::Open && previous.kind == Kind::Word && !is_keyword(&previous.text))
+            }
+        };
+        if !joined {
+            text.push(' ');
+        }
+        match token.kind {
+            Kind::Word if is_keyword(&token.text) => text.push_str(&token.text.to_ascii_uppercase()),
+            Kind::Word => text.push_str(&token.text.to_lowercase()),
+            Kind::Symbol if token.text == "!=" => text.push_str("<>"),
+            _ => text.push_str(&token.text),
+        }
+        previous = Some(token);
+    }
+    text
+}
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+/// The variable for a condition: its text, or for text too long to be a name,
+/// as much of it as fits with a hash of the whole
+pub(crate) fn condition_variable(text: String) -> String {
+    if text.len() <= MAX_VARIABLE_NAME_LENGTH {
+        return text;
+    }
+    let hash = format!("{:08x}", fnv1a(text.as_bytes()) as u32);
+    let mut end = MAX_VARIABLE_NAME_LENGTH - hash.len() - '…'.len_utf8();
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…{}", text[..end].trim_end(), hash)
+}
+
+/// Split a predicate into tokens, skipping whitespace and `--` and `/* */` comments
+fn tokenize(input: &str) -> Result<Vec<Token>, SqlError> {
+    const SYMBOLS: [&str; 6] = ["<>", "!=", "<=", ">=", "||", "::"];
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = input[pos..].chars().next() {
+        let rest = &input[pos..];
+        let start = pos;
+        let (kind, len) = if c.is_whitespace() {
+            pos += c.len_utf8();
+            continue;
+        } else if rest.starts_with("--") {
+            pos = rest.find('\n').map_or(input.len(), |i| pos + i);
+            continue;
+        } else if rest.starts_with("/*") {
+            let close = rest.find("*/").ok_or_else(|| SqlError::Unclosed { delimiter: "comment".to_string(), span: span(start..start + 2) })?;
+            pos += close + 2;
+            continue;
+        } else if let Some(close) = match c {
+            '\'' | '"' | '`' => Some(c),
+            '[' => Some(']'),
+            _ => None,
+        } {
+            let delimiter = if c == '\'' { "string" } else { "quoted name" };
+            (Kind::Quoted, quoted_length(rest, close).ok_or_else(|| SqlError::Unclosed { delimiter: delimiter.to_string(), span: span(start..start + 1) })?)
+        } else if c.is_alphanumeric() || matches!(c, '_' | '@' | '$' | '#') || (c == ':' && rest[1..].starts_with(|c: char| c.is_alphanumeric())) {
+            let len = rest[1..].find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '@' | '$' | '#'))).map_or(rest.len(), |i| i + 1);
+            (Kind::Word, len)
+        } else if c == '(' {
+            (Kind::Open, 1)
+        } else if c == ')' {
+            (Kind::Close, 1)
+        } else {
+            (Kind::Symbol, SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)).map_or(c.len_utf8(), |symbol| symbol.len()))
+        };
+        pos += len;
+        tokens.push(Token { kind, text: input[start..pos].to_string(), range: start..pos });
+    }
+    Ok(tokens)
+}
+
+/// The length of a literal opened by its first character and ended by `close`,
+/// where a doubled `close` stands for itself
+fn quoted_length(text: &str, close: char) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((idx, c)) = chars.next() {
+        if c == close && chars.next_if(|&(_, next)| next == close).is_none() {
+            return Some(idx + c.len_utf8());
+        }
+    }
+    None
+}
+
+fn span(range: Range<usize>) -> SourceSpan {
+    (range.start, range.len()).into()
+}
+
+fn syntax(message: &str, range: Range<usize>) -> SqlError {
+    SqlError::Syntax { message: message.to_string(), span: span(range) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Variables;
+    use crate::eval::equivalence::check_equivalence;
+
+    fn variables(sql: &str) -> Vec<String> {
+        Variables::from_expr(&parse_sql(sql).unwrap()).unwrap().to_vec()
+    }
+
+    #[test]
+    fn test_boolean_skeleton() {
+        let expr = parse_sql("WHERE (status = 'open' OR status = 'it''s') AND NOT archived;").unwrap();
+        assert_eq!(expr.to_string(), "((`status = 'open'` ∨ `status = 'it''s'`) ∧ ¬archived)");
+        // Parentheses that belong to a condition stay in it
+        assert_eq!(variables("(a + b) > 3 AND x IN (1, 2) AND price BETWEEN 1 AND 10"), [
+            "(a + b) > 3", "price BETWEEN 1 AND 10", "x IN (1, 2)",
+        ]);
+        assert_eq!(variables("CASE WHEN a OR b THEN 1 END = 1 OR EXISTS (SELECT 1 FROM t WHERE t.x = y AND z)"), [
+            "CASE WHEN a OR b THEN 1 END = 1", "EXISTS (SELECT 1 FROM t WHERE t.x = y AND z)",
+        ]);
+        assert_eq!(parse_sql("TRUE or false").unwrap(), Expr::Or(Box::new(Expr::True), Box::new(Expr::False)));
+    }
+
+    #[test]
+    fn test_identical_conditions_share_a_variable() {
+        let left = parse_sql("a=1 and (b IS NULL or coalesce(c,0) != 2) -- old\n or a = 1").unwrap();
+        let right = parse_sql("WHERE a = 1 /* new */ AND (B is null OR COALESCE(c, 0) <> 2) OR a  =  1").unwrap();
+        assert_eq!(Variables::from_expr(&left).unwrap().len(), 3);
+        assert!(Variables::from_expr(&left).unwrap().iter().eq(Variables::from_expr(&right).unwrap().iter()));
+        assert!(check_equivalence(&left, &right).unwrap().equivalent);
+
+        let refactored = parse_sql("a = 1").unwrap();
+        assert!(check_equivalence(&left, &refactored).unwrap().equivalent);
+
+        let long = "o.created_at >= CURRENT_DATE - INTERVAL '30 days' - INTERVAL '1 day' AND o.total > 100";
+        let names = variables(long);
+        assert!(names.iter().all(|name| name.len() <= MAX_VARIABLE_NAME_LENGTH));
+        assert!(names[0].starts_with("o.created_at >= current_date - interval") && names[0].contains('…'));
+        assert_eq!(variables(&long.to_lowercase()), names);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse_sql("a = 1 AND"), Err(SqlError::Syntax { .. })));
+        assert!(matches!(parse_sql("(a = 1 OR b = 2"), Err(SqlError::Unclosed { .. })));
+        assert!(matches!(parse_sql("a = 1)"), Err(SqlError::Syntax { .. })));
+        assert!(matches!(parse_sql("name = 'open"), Err(SqlError::Unclosed { .. })));
+        assert!(matches!(parse_sql("x IN (1, 2"), Err(SqlError::Unclosed { .. })));
+        assert!(matches!(parse_sql("()"), Err(SqlError::Syntax { .. })));
+        let deep = format!("{}a = 1{}", "(".repeat(1000), ")".repeat(1000));
+        assert!(matches!(parse_sql(&deep), Err(SqlError::Syntax { .. })));
+        let chain = format!("a = 1{}", " AND b = 2".repeat(1000));
+        assert!(matches!(parse_sql(&chain), Err(SqlError::Syntax { .. })));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::source::Expr;
+
+/// Which algebraic laws structural comparison may use to match two trees
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StructuralOptions {
+    /// Allow the operands of `and`, `or`, and `xor` to appear in either order
+    pub commutative: bool,
+    /// Treat nested chains of the same `and`, `or`, or `xor` operator as one flat chain
+    pub associative: bool,
+}
+
+impl StructuralOptions {
+    /// Trees must match node for node
+    pub fn exact() -> Self {
+        Self::default()
+    }
+    
+    /// Trees may differ by operand order and grouping of commutative, associative operators
+    pub fn modulo_ac() -> Self {
+        Self { commutative: true, associative: true }
+    }
+}
+
+impl Expr {
+    /// Compare two expressions syntactically (not logically).
+    ///
+    /// With [`StructuralOptions::exact`] this is plain tree equality; the options
+    /// relax it to ignore operand order and/or grouping. Use
+    /// [`Evaluator::check_equivalence`](crate::eval::Evaluator::check_equivalence)
+    /// for logical equivalence.
+    pub fn structurally_equal(&self, other: &Expr, options: StructuralOptions) -> bool {
+        match (options.commutative, options.associative) {
+            (false, false) => self == other,
+            (true, true) => self.normalize() == other.normalize(),
+            (true, false) => commutative_equal(self, other),
+            (false, true) => associative_equal(self, other),
+        }
+    }
+}
+
+fn commutative_equal(left: &Expr, right: &Expr) -> bool {
+    match (left, right) {
+        (Expr::Identifier(a), Expr::Identifier(b)) => a == b,
+        (Expr::True, Expr::True) | (Expr::False, Expr::False) => true,
+        (Expr::Not(a), Expr::Not(b)) => commutative_equal(a, b),
+        (Expr::And(a1, a2), Expr::And(b1, b2))
+        | (Expr::Or(a1, a2), Expr::Or(b1, b2))
+        | (Expr::Xor(a1, a2), Expr::Xor(b1, b2))
+        | (Expr::Iff(a1, a2), Expr::Iff(b1, b2))
+        | (Expr::Nand(a1, a2), Expr::Nand(b1, b2))
+        | (Expr::Nor(a1, a2), Expr::Nor(b1, b2)) => {
+            (commutative_equal(a1, b1) && commutative_equal(a2, b2))
+                || (commutative_equal(a1, b2) && commutative_equal(a2, b1))
+        }
+        (Expr::Implication(a1, a2), Expr::Implication(b1, b2)) => {
+            commutative_equal(a1, b1) && commutative_equal(a2, b2)
+        }
+        (Expr::Ite(a1, a2, a3), Expr::Ite(b1, b2, b3)) => {
+            commutative_equal(a1, b1) && commutative_equal(a2, b2) && commutative_equal(a3, b3)
+        }
+        // Operands match as a multiset; greedy pairing suffices since the relation is an equivalence
+        (Expr::Cardinality(kind_a, k_a, a), Expr::Cardinality(kind_b, k_b, b)) => {
+            if kind_a != kind_b || k_a != k_b || a.len() != b.len() {
+                return false;
+            }
+            let mut unmatched: Vec<&Expr> = b.iter().collect();
+            a.iter().all(|operand| match unmatched.iter().position(|other| commutative_equal(operand, other)) {
+                Some(i) => {
+                    unmatched.swap_remove(i);
+                    true
+                }
+                None => false,
+            })
+        }
+        (Expr::Quantified(q_a, var_a, a), Expr::Quantified(q_b, var_b, b)) => {
+            q_a == q_b && var_a == var_b && commutative_equal(a, b)
+        }
+        // Custom operators need not be symmetric, so operands match in order
+        (Expr::Custom(op_a, a), Expr::Custom(op_b, b)) => {
+            op_a == op_b && a.len() == b.len() && a.iter().zip(b).all(|(x, y)| commutative_equal(x, y))
+        }
+        _ => false,
+    }
+}
+
+fn associative_equal(left: &Expr, right: &Expr) -> bool {
+    match (left, right) {
+        (Expr::Identifier(a), Expr::Identifier(b)) => a == b,
+        (Expr::True, Expr::True) | (Expr::False, Expr::False) => true,
+        (Expr::Not(a), Expr::Not(b)) => associative_equal(a, b),
+        (Expr::Implication(a1, a2), Expr::Implication(b1, b2))
+        | (Expr::Nand(a1, a2), Expr::Nand(b1, b2))
+        | (Expr::Nor(a1, a2), Expr::Nor(b1, b2)) => {
+            associative_equal(a1, b1) && associative_equal(a2, b2)
+        }
+        (Expr::Ite(a1, a2, a3), Expr::Ite(b1, b2, b3)) => {
+            associative_equal(a1, b1) && associative_equal(a2, b2) && associative_equal(a3, b3)
+        }
+        (Expr::Cardinality(kind_a, k_a, a), Expr::Cardinality(kind_b, k_b, b)) => {
+            kind_a == kind_b && k_a == k_b && a.len() == b.len() && a.iter().zip(b).all(|(x, y)| associative_equal(x, y))
+        }
+        (Expr::Quantified(q_a, var_a, a), Expr::Quantified(q_b, var_b, b)) => {
+            q_a == q_b && var_a == var_b && associative_equal(a, b)
+        }
+        (Expr::Custom(op_a, a), Expr::Custom(op_b, b)) => {
+            op_a == op_b && a.len() == b.len() && a.iter().zip(b).all(|(x, y)| associative_equal(x, y))
+        }
+        (Expr::And(..), Expr::And(..))
+        | (Expr::Or(..), Expr::Or(..))
+        | (Expr::Xor(..), Expr::Xor(..))
+        | (Expr::Iff(..), Expr::Iff(..)) => {
+            let left_chain = chain_operands(left);
+            let right_chain = chain_operands(right);
+            left_chain.len() == right_chain.len()
+                && left_chain.iter().zip(&right_chain).all(|(a, b)| associative_equal(a, b))
+        }
+        _ => false,
+    }
+}
+
+/// Operands of a maximal chain of the same associative operator, in order
+pub(crate) fn chain_operands(expr: &Expr) -> Vec<&Expr> {
+    fn collect<'a>(expr: &'a Expr, kind: std::mem::Discriminant<Expr>, out: &mut Vec<&'a Expr>) {
+        match expr {
+            Expr::And(left, right) | Expr::Or(left, right) | Expr::Xor(left, right) | Expr::Iff(left, right)
+                if std::mem::discriminant(expr) == kind =>
+            {
+                collect(left, kind, out);
+                collect(right, kind, out);
+            }
+            _ => out.push(expr),
+        }
+    }
+    
+    let mut out = Vec::new();
+    collect(expr, std::mem::discriminant(expr), &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn equal(left: &str, right: &str, options: StructuralOptions) -> bool {
+        let left = Parser::new(left).parse().unwrap();
+        let right = Parser::new(right).parse().unwrap();
+        left.structurally_equal(&right, options)
+    }
+
+    #[test]
+    fn test_exact() {
+        assert!(equal("a and b", "a && b", StructuralOptions::exact()));
+        assert!(!equal("a and b", "b and a", StructuralOptions::exact()));
+    }
+
+    #[test]
+    fn test_commutative_only() {
+        let options = StructuralOptions { commutative: true, associative: false };
+        assert!(equal("a and (b or c)", "(c or b) and a", options));
+        assert!(!equal("(a and b) and c", "a and (b and c)", options));
+        assert!(!equal("a -> b", "b -> a", options));
+        assert!(equal("atleast(2; a, b and c, d)", "atleast(2; d, c and b, a)", options));
+        assert!(!equal("atleast(2; a, b)", "atmost(2; a, b)", options));
+    }
+
+    #[test]
+    fn test_associative_only() {
+        let options = StructuralOptions { commutative: false, associative: true };
+        assert!(equal("(a and b) and c", "a and (b and c)", options));
+        assert!(!equal("(a and b) and c", "a and (c and b)", options));
+        assert!(!equal("(a and b) or c", "a and (b or c)", options));
+    }
+
+    #[test]
+    fn test_modulo_ac() {
+        assert!(equal("(a xor b) xor c", "c xor (b xor a)", StructuralOptions::modulo_ac()));
+        // Logically equivalent but structurally different
+        assert!(!equal("not (a and b)", "not a or not b", StructuralOptions::modulo_ac()));
+    }
+}
@@ -0,0 +1,470 @@
+use crate::config::{MAX_EXPANSION_SIZE, MAX_NESTING_DEPTH};
+use crate::source::Expr;
+use std::collections::HashMap;
+use std::ops::Range;
+use thiserror::Error;
+use miette::{Diagnostic, SourceSpan};
+
+/// Errors in an IEC 61131-3 Structured Text program
+#[derive(Error, Debug, Diagnostic)]
+pub enum StructuredTextError {
+    #[error("Malformed Structured Text: {message}")]
+    #[diagnostic(code(ttt::structured_text::syntax))]
+    Syntax {
+        message: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[error("Unsupported Structured Text: {construct}")]
+    #[diagnostic(
+        code(ttt::structured_text::unsupported),
+        help("Only assignments of AND, OR, XOR, NOT, = and <> over BOOL variables are supported")
+    )]
+    Unsupported {
+        construct: String,
+        #[label("not supported")]
+        span: SourceSpan,
+    },
+
+    #[error("'{name}' grows to {nodes} nodes once the assignments before it are expanded")]
+    #[diagnostic(code(ttt::structured_text::too_large), help("Expressions may have at most {max} nodes"))]
+    TooLarge {
+        name: String,
+        nodes: usize,
+        max: usize,
+        #[label("assigned here")]
+        span: SourceSpan,
+    },
+
+    #[error("Nothing is assigned to '{name}'")]
+    #[diagnostic(code(ttt::structured_text::unknown_output), help("The program assigns {available}"))]
+    UnknownOutput { name: String, available: String },
+
+    #[error("The program contains no assignments")]
+    #[diagnostic(code(ttt::structured_text::no_assignments), help("Add an assignment such as `Run := Start AND NOT Stop;`"))]
+    NoAssignments,
+}
+
+/// The values a Structured Text program assigns, as expressions over the
+/// values its variables had before the scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuredText {
+    /// Each assigned variable with its final value, in the order of their last assignments
+    pub assignments: Vec<(String, Expr)>,
+}
+
+impl StructuredText {
+    /// The final value of `name`, matched without regard to case as Structured
+    /// Text does, or of the variable assigned last
+    pub fn output(&self, name: Option<&str>) -> Result<&Expr, StructuredTextError> {
+        let found = match name {
+            None => self.assignments.last(),
+            Some(name) => self.assignments.iter().find(|(assigned, _)| assigned.eq_ignore_ascii_case(name)),
+        };
+        found.map(|(_, expr)| expr).ok_or_else(|| match name {
+            None => StructuredTextError::NoAssignments,
+            Some(name) => StructuredTextError::UnknownOutput {
+                name: name.to_string(),
+                available: self.assignments.iter().map(|(assigned, _)| assigned.as_str()).collect::<Vec<_>>().join(", "),
+            },
+        })
+    }
+}
+
+/// Parse the assignments of an IEC 61131-3 Structured Text program, such as
+/// the interlock logic of a PLC:
+///
+/// ```text
+/// (* Pump runs on demand unless a fault or the low-level switch trips *)
+/// Fault := OverTemp OR OverCurrent;
+/// Pump := (Demand OR Manual) AND NOT Fault AND NOT LowLevel;
+/// ```
+///
+/// Statements run in order, as in one PLC scan: a variable read after it is
+/// assigned stands for the assigned expression, and one read before, such as
+/// the seal-in contact in `Motor := (Start OR Motor) AND NOT Stop;`, is the
+/// value from the previous scan and becomes a variable. Names are matched
+/// without regard to case and keep the spelling they are first written with;
+/// direct addresses such as `%IX0.1` are names too. `PROGRAM` and
+/// `FUNCTION_BLOCK` headers and `VAR ... END_VAR` declarations are skipped.
+/// Operators bind as the standard ranks them: `NOT`, then `=` and `<>`, then
+/// `AND` (or `&`), `XOR` and `OR`.
+pub fn parse_structured_text(input: &str) -> Result<StructuredText, StructuredTextError> {
+    let tokens = tokenize(input)?;
+    let mut program = Program { tokens: &tokens, pos: 0, depth: 0, end: input.len(), values: HashMap::new(), spellings: HashMap::new() };
+    let mut assignments: Vec<(String, Expr)> = Vec::new();
+
+    while let Some(token) = program.peek() {
+        let range = token.range.clone();
+        match token.keyword() {
+            Some("VAR" | "VAR_INPUT" | "VAR_OUTPUT" | "VAR_IN_OUT" | "VAR_TEMP" | "VAR_GLOBAL" | "VAR_EXTERNAL") => {
+                program.skip_declarations(range)?;
+                continue;
+            }
+            Some("PROGRAM" | "FUNCTION_BLOCK") => {
+                program.pos += 2;
+                continue;
+            }
+            Some("END_PROGRAM" | "END_FUNCTION_BLOCK") => {
+                program.pos += 1;
+                continue;
+            }
+            Some(keyword) if keyword != "NOT" => {
+                return Err(StructuredTextError::Unsupported { construct: format!("{} statements", keyword), span: span(range) });
+            }
+            _ => {}
+        }
+        if token.text == ";" {
+            program.pos += 1;
+            continue;
+        }
+
+        let Kind::Name = token.kind else {
+            return Err(syntax("expected an assignment such as `Out := In;`", range));
+        };
+        let name = program.spelling(&token.text);
+        program.pos += 1;
+        if !program.eat(":=") {
+            let range = program.peek().map_or(program.end..program.end, |token| token.range.clone());
+            return Err(syntax("expected ':='", range));
+        }
+        let expr = program.or()?;
+        if !program.eat(";") {
+            return Err(match program.peek() {
+                Some(token) if token.kind == Kind::Symbol && token.text != ")" => {
+                    StructuredTextError::Unsupported { construct: format!("operator {}", token.text), span: span(token.range.clone()) }
+                }
+                Some(token) => syntax("expected ';' after the assignment", token.range.clone()),
+                None => syntax("expected ';' after the assignment", program.end..program.end),
+            });
+        }
+
+        // Values substituted for variables can nest deeper than what was written
+        if expr.depth() > MAX_NESTING_DEPTH {
+            return Err(syntax(&format!("'{}' nests more than {} levels deep once the assignments before it are expanded", name, MAX_NESTING_DEPTH), range));
+        }
+        let nodes = expr.node_count();
+        if nodes > MAX_EXPANSION_SIZE {
+            return Err(StructuredTextError::TooLarge { name, nodes, max: MAX_EXPANSION_SIZE, span: span(range) });
+        }
+        program.values.insert(name.to_ascii_lowercase(), expr.clone());
+        assignments.retain(|(assigned, _)| *assigned != name);
+        assignments.push((name, expr));
+    }
+
+    if assignments.is_empty() {
+        return Err(StructuredTextError::NoAssignments);
+    }
+    Ok(StructuredText { assignments })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A name, keyword or typed literal such as `BOOL#1`
+    Name,
+    Symbol,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    text: String,
+    range: Range<usize>,
+}
+
+/// Words that can't be variable names
+const KEYWORDS: &[&str] = &[
+    "AND", "OR", "XOR", "NOT", "TRUE", "FALSE", "MOD", "IF", "THEN", "ELSIF", "ELSE", "END_IF", "CASE", "OF",
+    "END_CASE", "FOR", "TO", "BY", "DO", "END_FOR", "WHILE", "END_WHILE", "REPEAT", "UNTIL", "END_REPEAT",
+    "RETURN", "EXIT", "PROGRAM", "END_PROGRAM", "FUNCTION_BLOCK", "END_FUNCTION_BLOCK", "FUNCTION",
+    "END_FUNCTION", "VAR", "VAR_INPUT", "VAR_OUTPUT", "VAR_IN_OUT", "VAR_TEMP", "VAR_GLOBAL", "VAR_EXTERNAL",
+    "END_VAR",
+];
+
+impl Token {
+    /// The keyword this token is, in capitals
+    fn keyword(&self) -> Option<&'static str> {
+        match self.kind {
+            Kind::Name => KEYWORDS.iter().copied().find(|keyword| keyword.eq_ignore_ascii_case(&self.text)),
+            Kind::Symbol => None,
+        }
+    }
+}
+
+struct Program<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    /// Parentheses and `NOT`s open around the current position
+    depth: usize,
+    end: usize,
+    /// The latest value of each variable assigned so far, by lower-case name
+    values: HashMap<String, Expr>,
+    /// The spelling each name was first written with, by lower-case name
+    spellings: HashMap<String, String>,
+}
+
+impl<'a> Program<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found = self.peek().is_some_and(|token| token.kind == Kind::Symbol && token.text == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_some_and(|token| token.keyword() == Some(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn spelling(&mut self, name: &str) -> String {
+        self.spellings.entry(name.to_ascii_lowercase()).or_insert_with(|| name.to_string()).clone()
+    }
+
+    /// Skip a `VAR ... END_VAR` block
+    fn skip_declarations(&mut self, opened: Range<usize>) -> Result<(), StructuredTextError> {
+        while let Some(token) = self.tokens.get(self.pos) {
+            self.pos += 1;
+            if token.keyword() == Some("END_VAR") {
+                return Ok(());
+            }
+        }
+        Err(syntax("`VAR` without `END_VAR`", opened))
+    }
+
+    fn or(&mut self) -> Result<Expr, StructuredTextError> {
+        let depth = self.depth;
+        let mut left = self.xor()?;
+        while self.eat_keyword("OR") {
+            self.chained()?;
+            left = Expr::Or(Box::new(left), Box::new(self.xor()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn xor(&mut self) -> Result<Expr, StructuredTextError> {
+        let depth = self.depth;
+        let mut left = self.and()?;
+        while self.eat_keyword("XOR") {
+            self.chained()?;
+            left = Expr::Xor(Box::new(left), Box::new(self.and()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, StructuredTextError> {
+        let depth = self.depth;
+        let mut left = self.comparison()?;
+        while self.eat_keyword("AND") || self.eat("&") {
+            self.chained()?;
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    /// `=` and `<>` between BOOL values are `↔` and `⊕`
+    fn comparison(&mut self) -> Result<Expr, StructuredTextError> {
+        let depth = self.depth;
+        let mut left = self.unary()?;
+        loop {
+            if self.eat("=") {
+                self.chained()?;
+                left = Expr::Iff(Box::new(left), Box::new(self.unary()?));
+            } else if self.eat("<>") {
+                self.chained()?;
+                left = Expr::Xor(Box::new(left), Box::new(self.unary()?));
+            } else {
+                self.depth = depth;
+                return Ok(left);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, StructuredTextError> {
+        let Some(token) = self.peek() else {
+            return Err(syntax("unexpected end of input", self.end..self.end));
+        };
+        let range = token.range.clone();
+        if self.eat_keyword("NOT") {
+            return self.nested(range, |program| program.unary()).map(|expr| Expr::Not(Box::new(expr)));
+        }
+        if self.eat("(") {
+            let expr = self.nested(range.clone(), Self::or)?;
+            if !self.eat(")") {
+                return Err(syntax("expected ')'", range.start..self.peek().map_or(self.end, |token| token.range.end)));
+            }
+            return Ok(expr);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, StructuredTextError> {
+        let token = self.peek().expect("checked by the caller").clone();
+        self.pos += 1;
+        match (token.kind, token.keyword()) {
+            (Kind::Name, Some("TRUE")) => return Ok(Expr::True),
+            (Kind::Name, Some("FALSE")) => return Ok(Expr::False),
+            (Kind::Name, Some(keyword)) => return Err(syntax(&format!("expected a value, found {}", keyword), token.range)),
+            (Kind::Symbol, _) => return Err(syntax(&format!("expected a value, found '{}'", token.text), token.range)),
+            (Kind::Name, None) => {}
+        }
+        if let Some((kind, value)) = token.text.split_once('#') {
+            return match (kind.to_ascii_uppercase().as_str(), value.to_ascii_uppercase().as_str()) {
+                ("BOOL", "1" | "TRUE") => Ok(Expr::True),
+                ("BOOL", "0" | "FALSE") => Ok(Expr::False),
+                _ => Err(StructuredTextError::Unsupported { construct: format!("literal {}", token.text), span: span(token.range) }),
+            };
+        }
+        if self.peek().is_some_and(|next| next.text == "(") {
+            return Err(StructuredTextError::Unsupported { construct: format!("call to {}", token.text), span: span(token.range) });
+        }
+        if token.text.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(StructuredTextError::Unsupported { construct: format!("number {}", token.text), span: span(token.range) });
+        }
+        // Read after an assignment, a variable stands for the value assigned
+        match self.values.get(&token.text.to_ascii_lowercase()) {
+            Some(value) => Ok(value.clone()),
+            None => Ok(Expr::Identifier(self.spelling(&token.text))),
+        }
+    }
+
+    /// Run `parse` one level further in, refusing to go deeper than
+    /// [`MAX_NESTING_DEPTH`]
+    fn nested(&mut self, at: Range<usize>, parse: impl FnOnce(&mut Self) -> Result<Expr, StructuredTextError>) -> Result<Expr, StructuredTextError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(syntax(&format!("nested more than {} levels deep", MAX_NESTING_DEPTH), at));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Count the operand after the operator just read as a level, since each
+    /// operand of a chain nests the ones before it
+    fn chained(&mut self) -> Result<(), StructuredTextError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            let at = self.tokens[self.pos - 1].range.clone();
+            return Err(syntax(&format!("nested more than {} levels deep", MAX_NESTING_DEPTH), at));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+/// Split a program into names and symbols, skipping whitespace and `(* *)` and `//` comments
+fn tokenize(input: &str) -> Result<Vec<Token>, StructuredTextError> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = input[pos..].chars().next() {
+        let rest = &input[pos..];
+        let start = pos;
+        if c.is_whitespace() {
+            pos += c.len_utf8();
+            continue;
+        }
+        if rest.starts_with("(*") {
+            let close = rest.find("*)").ok_or_else(|| syntax("unclosed comment", start..start + 2))?;
+            pos += close + 2;
+            continue;
+        }
+        if rest.starts_with("//") {
+            pos = rest.find('\n').map_or(input.len(), |i| pos + i);
+            continue;
+        }
+        let (kind, len) = if c.is_alphanumeric() || c == '_' || c == '%' {
+            let len = rest[1..].find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '#'))).map_or(rest.len(), |i| i + 1);
+            (Kind::Name, len)
+        } else if [":=", "<>", "<=", ">=", "**"].iter().any(|symbol| rest.starts_with(symbol)) {
+            (Kind::Symbol, 2)
+        } else {
+            (Kind::Symbol, c.len_utf8())
+        };
+        pos += len;
+        tokens.push(Token { kind, text: input[start..pos].to_string(), range: start..pos });
+    }
+    Ok(tokens)
+}
+
+fn span(range: Range<usize>) -> SourceSpan {
+    (range.start, range.len()).into()
+}
+
+fn syntax(message: &str, range: Range<usize>) -> StructuredTextError {
+    StructuredTextError::Syntax { message: message.to_string(), span: span(range) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Parser;
+
+    fn output(program: &str, name: Option<&str>) -> Expr {
+        parse_structured_text(program).unwrap().output(name).unwrap().clone()
+    }
+
+    fn equivalent(program: &str, ttt: &str) {
+        assert_eq!(output(program, None), Parser::new(ttt).parse().unwrap(), "{}", program);
+    }
+
+    #[test]
+    fn test_operators() {
+        equivalent("Out := a OR b AND NOT c XOR d;", "a or ((b and not c) xor d)");
+        equivalent("Out := a & b = c <> TRUE;", "a and ((b iff c) xor true)");
+        equivalent("out := NOT (a OR BOOL#0) and bool#1;", "not (a or false) and true");
+    }
+
+    #[test]
+    fn test_scan_order() {
+        let program = "
+            PROGRAM Interlock
+            VAR_INPUT Demand, Manual : BOOL; END_VAR
+            VAR_OUTPUT Pump : BOOL := FALSE; END_VAR
+            (* trips *)
+            Fault := OverTemp OR OverCurrent;   // either trips the pump
+            Pump := (Demand OR manual) AND NOT FAULT AND NOT %IX0.3;
+            Motor := (Start OR Motor) AND NOT Stop;
+            END_PROGRAM
+        ";
+        let parsed = parse_structured_text(program).unwrap();
+        let names: Vec<&str> = parsed.assignments.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Fault", "Pump", "Motor"]);
+        assert_eq!(
+            *parsed.output(Some("pump")).unwrap(),
+            Parser::new("(Demand or manual) and not (OverTemp or OverCurrent) and not `%IX0.3`").parse().unwrap()
+        );
+        // The seal-in contact reads the previous scan's value
+        assert_eq!(*parsed.output(None).unwrap(), Parser::new("(Start or Motor) and not Stop").parse().unwrap());
+        // A later assignment replaces the value and moves to the end
+        equivalent("x := a; y := x; X := NOT x;", "not a");
+        assert!(matches!(parsed.output(Some("Valve")), Err(StructuredTextError::UnknownOutput { .. })));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse_structured_text("Out := a AND;"), Err(StructuredTextError::Syntax { .. })));
+        assert!(matches!(parse_structured_text("Out := a"), Err(StructuredTextError::Syntax { .. })));
+        assert!(matches!(parse_structured_text("IF a THEN Out := b; END_IF;"), Err(StructuredTextError::Unsupported { .. })));
+        assert!(matches!(parse_structured_text("Out := TON(a);"), Err(StructuredTextError::Unsupported { .. })));
+        assert!(matches!(parse_structured_text("Out := a + b;"), Err(StructuredTextError::Unsupported { .. })));
+        assert!(matches!(parse_structured_text("VAR a : BOOL;"), Err(StructuredTextError::Syntax { .. })));
+        assert!(matches!(parse_structured_text("(* nothing *)"), Err(StructuredTextError::NoAssignments)));
+        let doubling: String = (1..40).map(|i| format!("x{} := x{} AND x{};", i, i - 1, i - 1)).collect();
+        assert!(matches!(parse_structured_text(&doubling), Err(StructuredTextError::TooLarge { .. })));
+        let chain = format!("x := a{};", " AND b".repeat(1000));
+        assert!(matches!(parse_structured_text(&chain), Err(StructuredTextError::Syntax { .. })));
+        let negations = "x := NOT x;".repeat(1000);
+        assert!(matches!(parse_structured_text(&negations), Err(StructuredTextError::Syntax { .. })));
+    }
+}
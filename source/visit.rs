@@ -0,0 +1,271 @@
+use crate::source::{Expr, Cardinality, Quantifier, Operator, BinaryOp};
+use std::ops::ControlFlow;
+
+/// A read-only walk over an expression tree.
+///
+/// Every method has a default that keeps walking, so an analysis overrides only
+/// the nodes it cares about; an override calls the matching `walk_*` helper to
+/// continue into the children. Returning [`ControlFlow::Break`] stops the walk.
+///
+/// ```
+/// use std::ops::ControlFlow;
+/// use ttt::expr;
+/// use ttt::source::visit::{ExprVisitor, walk_expr};
+/// use ttt::source::Expr;
+///
+/// struct Depth { current: usize, max: usize }
+///
+/// impl ExprVisitor for Depth {
+///     type Break = ();
+///
+///     fn visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+///         self.current += 1;
+///         self.max = self.max.max(self.current);
+///         walk_expr(self, expr)?;
+///         self.current -= 1;
+///         ControlFlow::Continue(())
+///     }
+/// }
+///
+/// let mut depth = Depth { current: 0, max: 0 };
+/// let _ = depth.visit_expr(&expr!(a and not (b or c)));
+/// assert_eq!(depth.max, 4);
+/// ```
+pub trait ExprVisitor {
+    /// Carried out of the walk by an early exit
+    type Break;
+
+    /// Called for every node; the default dispatches to the other methods
+    fn visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        walk_expr(self, expr)
+    }
+
+    fn visit_identifier(&mut self, _name: &str) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_constant(&mut self, _value: bool) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called for `∀var. body` and `∃var. body`, where `var` is bound
+    fn visit_quantified(&mut self, _quantifier: Quantifier, _var: &str, body: &Expr) -> ControlFlow<Self::Break> {
+        self.visit_expr(body)
+    }
+}
+
+/// Hand `expr` to the matching [`ExprVisitor`] method, or visit its children
+/// when there is none
+pub fn walk_expr<V: ExprVisitor + ?Sized>(visitor: &mut V, expr: &Expr) -> ControlFlow<V::Break> {
+    match expr {
+        Expr::Identifier(name) => visitor.visit_identifier(name),
+        Expr::True => visitor.visit_constant(true),
+        Expr::False => visitor.visit_constant(false),
+        Expr::Quantified(quantifier, var, body) => visitor.visit_quantified(*quantifier, var, body),
+        _ => walk_children(visitor, expr),
+    }
+}
+
+/// Visit each direct child of `expr` in order
+pub fn walk_children<V: ExprVisitor + ?Sized>(visitor: &mut V, expr: &Expr) -> ControlFlow<V::Break> {
+    match expr {
+        Expr::Identifier(_) | Expr::True | Expr::False => ControlFlow::Continue(()),
+        Expr::Not(inner) | Expr::Quantified(_, _, inner) => visitor.visit_expr(inner),
+        Expr::And(left, right)
+        | Expr::Or(left, right)
+        | Expr::Nand(left, right)
+        | Expr::Nor(left, right)
+        | Expr::Xor(left, right)
+        | Expr::Implication(left, right)
+        | Expr::Iff(left, right) => {
+            visitor.visit_expr(left)?;
+            visitor.visit_expr(right)
+        }
+        Expr::Ite(cond, then, otherwise) => {
+            visitor.visit_expr(cond)?;
+            visitor.visit_expr(then)?;
+            visitor.visit_expr(otherwise)
+        }
+        Expr::Cardinality(_, _, operands) | Expr::Custom(_, operands) => {
+            operands.iter().try_for_each(|operand| visitor.visit_expr(operand))
+        }
+    }
+}
+
+/// A bottom-up fold of an expression tree into a value, such as a truth value,
+/// a metric or a rewritten expression.
+///
+/// [`walk_fold`] folds the children first and hands their results to the
+/// method for the node, so no implementation matches over [`Expr`] itself.
+/// Quantifiers are the exception: the body is passed unfolded, since
+/// evaluating it needs the bound variable set each way.
+pub trait ExprFolder {
+    type Output;
+
+    /// Called for every node; the default is [`walk_fold`]
+    fn fold_expr(&mut self, expr: &Expr) -> Self::Output {
+        walk_fold(self, expr)
+    }
+
+    fn fold_identifier(&mut self, name: &str) -> Self::Output;
+
+    fn fold_constant(&mut self, value: bool) -> Self::Output;
+
+    fn fold_not(&mut self, inner: Self::Output) -> Self::Output;
+
+    fn fold_binary(&mut self, op: BinaryOp, left: Self::Output, right: Self::Output) -> Self::Output;
+
+    fn fold_ite(&mut self, cond: Self::Output, then: Self::Output, otherwise: Self::Output) -> Self::Output;
+
+    fn fold_cardinality(&mut self, kind: Cardinality, k: usize, operands: Vec<Self::Output>) -> Self::Output;
+
+    fn fold_custom(&mut self, operator: &Operator, operands: Vec<Self::Output>) -> Self::Output;
+
+    fn fold_quantified(&mut self, quantifier: Quantifier, var: &str, body: &Expr) -> Self::Output;
+}
+
+/// Fold the children of `expr` with [`ExprFolder::fold_expr`], then combine
+/// them with the method for `expr`'s node
+pub fn walk_fold<F: ExprFolder + ?Sized>(folder: &mut F, expr: &Expr) -> F::Output {
+    match expr {
+        Expr::Identifier(name) => folder.fold_identifier(name),
+        Expr::True => folder.fold_constant(true),
+        Expr::False => folder.fold_constant(false),
+        Expr::Not(inner) => {
+            let inner = folder.fold_expr(inner);
+            folder.fold_not(inner)
+        }
+        Expr::Ite(cond, then, otherwise) => {
+            let cond = folder.fold_expr(cond);
+            let then = folder.fold_expr(then);
+            let otherwise = folder.fold_expr(otherwise);
+            folder.fold_ite(cond, then, otherwise)
+        }
+        Expr::Cardinality(kind, k, operands) => {
+            let operands = operands.iter().map(|operand| folder.fold_expr(operand)).collect();
+            folder.fold_cardinality(*kind, *k, operands)
+        }
+        Expr::Custom(operator, operands) => {
+            let operands = operands.iter().map(|operand| folder.fold_expr(operand)).collect();
+            folder.fold_custom(operator, operands)
+        }
+        Expr::Quantified(quantifier, var, body) => folder.fold_quantified(*quantifier, var, body),
+        _ => {
+            let (op, left, right) = expr.as_binary().expect("every other node is binary");
+            let left = folder.fold_expr(left);
+            let right = folder.fold_expr(right);
+            folder.fold_binary(op, left, right)
+        }
+    }
+}
+
+impl Expr {
+    /// The operator and operands of a binary node
+    pub fn as_binary(&self) -> Option<(BinaryOp, &Expr, &Expr)> {
+        let (op, left, right) = match self {
+            Expr::And(l, r) => (BinaryOp::And, l, r),
+            Expr::Or(l, r) => (BinaryOp::Or, l, r),
+            Expr::Nand(l, r) => (BinaryOp::Nand, l, r),
+            Expr::Nor(l, r) => (BinaryOp::Nor, l, r),
+            Expr::Xor(l, r) => (BinaryOp::Xor, l, r),
+            Expr::Implication(l, r) => (BinaryOp::Implication, l, r),
+            Expr::Iff(l, r) => (BinaryOp::Iff, l, r),
+            _ => return None,
+        };
+        Some((op, left, right))
+    }
+
+    /// The same node with `f` applied to each direct child
+    pub fn map_children(&self, mut f: impl FnMut(&Expr) -> Expr) -> Expr {
+        if let Some((op, left, right)) = self.as_binary() {
+            return op.build(f(left), f(right));
+        }
+        match self {
+            Expr::Identifier(_) | Expr::True | Expr::False => self.clone(),
+            Expr::Not(inner) => Expr::Not(Box::new(f(inner))),
+            Expr::Ite(cond, then, otherwise) => Expr::Ite(Box::new(f(cond)), Box::new(f(then)), Box::new(f(otherwise))),
+            Expr::Cardinality(kind, k, operands) => Expr::Cardinality(*kind, *k, operands.iter().map(f).collect()),
+            Expr::Custom(operator, operands) => Expr::Custom(operator.clone(), operands.iter().map(f).collect()),
+            Expr::Quantified(quantifier, var, body) => Expr::Quantified(*quantifier, var.clone(), Box::new(f(body))),
+            _ => unreachable!("binary nodes are mapped above"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr;
+
+    /// Counts identifier occurrences, stopping at the first named `stop`
+    struct Occurrences(usize);
+
+    impl ExprVisitor for Occurrences {
+        type Break = usize;
+
+        fn visit_identifier(&mut self, name: &str) -> ControlFlow<usize> {
+            if name == "stop" {
+                return ControlFlow::Break(self.0);
+            }
+            self.0 += 1;
+            ControlFlow::Continue(())
+        }
+    }
+
+    /// Swaps the operands of every conjunction
+    struct Commute;
+
+    impl ExprFolder for Commute {
+        type Output = Expr;
+
+        fn fold_identifier(&mut self, name: &str) -> Expr {
+            Expr::Identifier(name.to_string())
+        }
+
+        fn fold_constant(&mut self, value: bool) -> Expr {
+            if value { Expr::True } else { Expr::False }
+        }
+
+        fn fold_not(&mut self, inner: Expr) -> Expr {
+            Expr::Not(Box::new(inner))
+        }
+
+        fn fold_binary(&mut self, op: BinaryOp, left: Expr, right: Expr) -> Expr {
+            match op {
+                BinaryOp::And => op.build(right, left),
+                _ => op.build(left, right),
+            }
+        }
+
+        fn fold_ite(&mut self, cond: Expr, then: Expr, otherwise: Expr) -> Expr {
+            Expr::Ite(Box::new(cond), Box::new(then), Box::new(otherwise))
+        }
+
+        fn fold_cardinality(&mut self, kind: Cardinality, k: usize, operands: Vec<Expr>) -> Expr {
+            Expr::Cardinality(kind, k, operands)
+        }
+
+        fn fold_custom(&mut self, operator: &Operator, operands: Vec<Expr>) -> Expr {
+            Expr::Custom(Box::new(operator.clone()), operands)
+        }
+
+        fn fold_quantified(&mut self, quantifier: Quantifier, var: &str, body: &Expr) -> Expr {
+            Expr::Quantified(quantifier, var.to_string(), Box::new(self.fold_expr(body)))
+        }
+    }
+
+    #[test]
+    fn test_visitor_walks_in_order_and_breaks() {
+        let mut count = Occurrences(0);
+        assert_eq!(count.visit_expr(&expr!(a and (b ? c : d) or atleast(1; e, f))), ControlFlow::Continue(()));
+        assert_eq!(count.0, 6);
+        let mut count = Occurrences(0);
+        assert_eq!(count.visit_expr(&expr!(a or (b and stop) or c)), ControlFlow::Break(2));
+    }
+
+    #[test]
+    fn test_folder_rebuilds_bottom_up() {
+        let folded = Commute.fold_expr(&expr!(forall x. (a and (b and c)) or not (x and true)));
+        assert_eq!(folded, expr!(forall x. ((c and b) and a) or not (true and x)));
+    }
+}
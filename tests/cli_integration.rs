@@ -1,5 +1,6 @@
 use ttt::source::Parser;
-use ttt::eval::Evaluator;
+use ttt::eval::{Evaluator, RowOrder, RowSort};
+use ttt::io::output::{OutputFormat, format_truth_table, format_equivalence_result};
 use std::collections::HashMap;
 
 /// Test the full workflow from parsing to evaluation
@@ -43,8 +44,8 @@ fn test_equivalence_check_workflow() {
     assert!(check.equivalent);
     assert!(check.differences.is_empty());
     assert_eq!(check.variables.len(), 2);
-    assert!(check.variables.contains(&"a".to_string()));
-    assert!(check.variables.contains(&"b".to_string()));
+    assert!(check.variables.contains("a"));
+    assert!(check.variables.contains("b"));
 }
 
 #[test]
@@ -98,7 +99,7 @@ fn test_complex_expression_workflow() {
     // Should have 4 variables
     let expected_vars = vec!["a", "b", "c", "d"];
     for var in &expected_vars {
-        assert!(table.variables.contains(&var.to_string()));
+        assert!(table.variables.contains(var.as_ref()));
     }
     
     // Should have 2^4 = 16 rows
@@ -125,11 +126,13 @@ fn test_all_operator_types() {
         ("¬a", "unicode negation"),
         ("a ⊕ b", "unicode xor"),
         ("a → b", "unicode implication"),
+        ("ite(a, b, c)", "if-then-else"),
+        ("a ? b : c", "conditional"),
     ];
     
     for (expr_str, description) in test_cases {
         let mut parser = Parser::new(expr_str);
-        let expr = parser.parse().expect(&format!("Should parse {}", description));
+        let expr = parser.parse().unwrap_or_else(|_| panic!("Should parse {}", description));
         
         // Verify we can generate truth table
         let table = Evaluator::generate_truth_table(&expr).unwrap();
@@ -144,6 +147,55 @@ fn test_all_operator_types() {
     }
 }
 
+#[test]
+fn test_if_then_else_multiplexer() {
+    let mux = Parser::new("s ? a : b").parse().unwrap();
+    let sum_of_products = Parser::new("(s and a) or (not s and b)").parse().unwrap();
+    assert!(Evaluator::check_equivalence(&mux, &sum_of_products).unwrap().equivalent);
+    
+    // Both branches equal: the select line drops out
+    let redundant = Parser::new("ite(s, a or b, b or a)").parse().unwrap();
+    let reduction = Evaluator::reduce_expression(&redundant).unwrap();
+    assert_eq!(reduction.reduced.normalize().to_string(), "(a ∨ b)");
+}
+
+#[test]
+fn test_cardinality_constraints() {
+    let two_of_four = Parser::new("atleast(2; a, b, c, d)").parse().unwrap();
+    let table = Evaluator::generate_truth_table(&two_of_four).unwrap();
+    // C(4,2) + C(4,3) + C(4,4)
+    assert_eq!(table.rows.iter().filter(|row| row.result).count(), 11);
+    
+    let exactly = Parser::new("exactly(1; a, b, c)").parse().unwrap();
+    let at_most_and_least = Parser::new("atmost(1; a, b, c) and atleast(1; a, b, c)").parse().unwrap();
+    assert!(Evaluator::check_equivalence(&exactly, &at_most_and_least).unwrap().equivalent);
+}
+
+#[test]
+fn test_quantified_variables_are_bound() {
+    // For every value of the select line the outputs agree
+    let agree = Parser::new("forall s. (s ? a : b) == (s ? b : a)").parse().unwrap();
+    let variables = Evaluator::collect_expression_variables(&agree).unwrap();
+    assert_eq!(variables.to_vec(), ["a", "b"]);
+    let a_iff_b = Parser::new("a <-> b").parse().unwrap();
+    assert!(Evaluator::check_equivalence(&agree, &a_iff_b).unwrap().equivalent);
+    
+    let exists = Parser::new("exists x. x and a").parse().unwrap();
+    assert_eq!(Evaluator::reduce_expression(&exists).unwrap().reduced.to_string(), "a");
+}
+
+#[test]
+fn test_result_column_in_either_row_order() {
+    let expr = Parser::new("a and not b or c").parse().unwrap();
+    let table = Evaluator::generate_truth_table(&expr).unwrap();
+    let bits = |values: Vec<bool>| values.iter().map(|&v| if v { '1' } else { '0' }).collect::<String>();
+    assert_eq!(bits(table.column("result", RowOrder::Lsb).unwrap()), "01001111");
+    // With a as the most significant bit, row 0b011 is a=F b=T c=T
+    assert_eq!(bits(table.column("result", RowOrder::Msb).unwrap()), "01011101");
+    assert_eq!(bits(table.column("a", RowOrder::Msb).unwrap()), "00001111");
+    assert!(table.column("z", RowOrder::Lsb).is_err());
+}
+
 #[test]
 fn test_variable_collection() {
     let test_cases = [
@@ -156,7 +208,7 @@ fn test_variable_collection() {
     
     for (expr_str, expected_vars) in test_cases {
         let mut parser = Parser::new(expr_str);
-        let expr = parser.parse().expect(&format!("Should parse {}", expr_str));
+        let expr = parser.parse().unwrap_or_else(|_| panic!("Should parse {}", expr_str));
         
         let collected = Evaluator::collect_expression_variables(&expr).unwrap();
         
@@ -185,7 +237,7 @@ fn test_expression_evaluation_with_assignments() {
     
     for (expr_str, assignments, expected_result) in test_cases {
         let mut parser = Parser::new(expr_str);
-        let expr = parser.parse().expect(&format!("Should parse {}", expr_str));
+        let expr = parser.parse().unwrap_or_else(|_| panic!("Should parse {}", expr_str));
         
         let mut assignment_map = HashMap::new();
         for (var, value) in assignments {
@@ -217,4 +269,376 @@ fn test_error_handling_in_workflow() {
         assert!(result.is_err(), 
                "Should fail to parse invalid expression: '{}'", invalid_expr);
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_equivalence_with_dont_care() {
+    let left = Parser::new("a and b").parse().unwrap();
+    let right = Parser::new("a").parse().unwrap();
+    
+    // Without a don't-care set these differ when a=T, b=F
+    let check = Evaluator::check_equivalence(&left, &right).unwrap();
+    assert!(!check.equivalent);
+    
+    // Treating a=T, b=F as unreachable makes them agree everywhere else
+    let dont_care = Parser::new("a and not b").parse().unwrap();
+    let check = Evaluator::check_equivalence_with_dont_care(&left, &right, &dont_care).unwrap();
+    assert!(check.equivalent);
+    assert!(check.differences.is_empty());
+    assert_eq!(check.ignored, 1);
+    
+    // Don't-care variables are part of the enumerated space
+    let dont_care = Parser::new("c").parse().unwrap();
+    let check = Evaluator::check_equivalence_with_dont_care(&left, &right, &dont_care).unwrap();
+    assert!(!check.equivalent);
+    assert_eq!(check.variables.len(), 3);
+    assert_eq!(check.ignored, 4);
+    for diff in &check.differences {
+        assert!(!diff.assignment["c"]);
+    }
+}
+
+#[test]
+fn test_evaluate_rows_from_vectors() {
+    use ttt::io::csv::CsvDocument;
+    
+    let expr = Parser::new("a and not b").parse().unwrap();
+    let document = CsvDocument::parse("id,b,a\n1,false,true\n2,true,true\n3,0,0\n").unwrap();
+    let columns = vec![document.column("a").unwrap(), document.column("b").unwrap()];
+    let table = Evaluator::evaluate_rows(&expr, document.assignments(&columns).unwrap()).unwrap();
+    
+    let results: Vec<bool> = table.rows.iter().map(|row| row.result).collect();
+    assert_eq!(results, vec![true, false, false]);
+    assert_eq!(table.variables.to_vec(), vec!["a", "b"]);
+    
+    // A row missing a variable is rejected
+    let mut partial = HashMap::new();
+    partial.insert("a".to_string(), true);
+    assert!(Evaluator::evaluate_rows(&expr, vec![partial]).is_err());
+}
+
+#[test]
+fn test_vectors_output_keeps_every_column() {
+    use ttt::io::csv::CsvDocument;
+    use ttt::io::output::format_vectors;
+    
+    let expr = Parser::new("a and not b").parse().unwrap();
+    let document = CsvDocument::parse("id,b,a,note\n17,false,true,\"first, kept\"\n18,true,true,x\n").unwrap();
+    let columns = vec![document.column("a").unwrap(), document.column("b").unwrap()];
+    let table = Evaluator::evaluate_rows(&expr, document.assignments(&columns).unwrap()).unwrap();
+    let results: Vec<bool> = table.rows.iter().map(|row| row.result).collect();
+    
+    assert_eq!(
+        format_vectors(&document, &results, &OutputFormat::Csv),
+        "id,b,a,note,result\n17,false,true,\"first, kept\",true\n18,true,true,x,false\n"
+    );
+    let rows: serde_json::Value = serde_json::from_str(&format_vectors(&document, &results, &OutputFormat::Json)).unwrap();
+    assert_eq!(rows[1]["id"], "18");
+    assert_eq!(rows[1]["result"], false);
+    
+    let text = format_vectors(&document, &results, &OutputFormat::Table);
+    assert!(text.lines().next().unwrap().split_whitespace().eq(["id", "b", "a", "note", "Result"]));
+    assert!(text.lines().nth(2).unwrap().starts_with("  17"));
+}
+
+#[test]
+fn test_tablediff_exit_status() {
+    use std::process::Command;
+    
+    let dir = std::env::temp_dir();
+    let write = |name: &str, text: &str| {
+        let path = dir.join(format!("ttt-tablediff-test-{}-{}.csv", std::process::id(), name));
+        std::fs::write(&path, text).unwrap();
+        path
+    };
+    let and = write("and", "a,b,result\nF,F,F\nF,T,F\nT,F,F\nT,T,T\n");
+    let reordered = write("reordered", "b,a,result\nT,T,T\nF,F,F\nT,F,F\nF,T,F\n");
+    let or = write("or", "a,b,result\nF,F,F\nF,T,T\nT,F,T\nT,T,T\n");
+    let partial = write("partial", "a,b,result\nF,F,F\nT,T,T\n");
+    
+    // The exit code, and whether a comparison (rather than an error) was printed
+    let run = |right: &std::path::Path| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ttt")).arg("tablediff").arg(&and).arg(right).output().unwrap();
+        (output.status.code(), String::from_utf8_lossy(&output.stdout).contains("Truth tables"))
+    };
+    assert_eq!(run(&reordered), (Some(0), true));
+    assert_eq!(run(&or), (Some(1), true));
+    // Missing rows are a difference even when every shared row agrees
+    assert_eq!(run(&partial), (Some(1), true));
+    
+    for path in [and, reordered, or, partial] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_failing_status_is_still_logged() {
+    use std::process::Command;
+    
+    let log = std::env::temp_dir().join(format!("ttt-exit-log-test-{}.jsonl", std::process::id()));
+    let check = |expression: &str| {
+        Command::new(env!("CARGO_BIN_EXE_ttt")).arg("--log-file").arg(&log).args(["check", expression]).status().unwrap().code()
+    };
+    assert_eq!(check("a or not a"), Some(0));
+    assert_eq!(check("a and b"), Some(2));
+    assert_eq!(check("a and not a"), Some(3));
+    
+    let records: Vec<serde_json::Value> = std::fs::read_to_string(&log).unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let summaries: Vec<&str> = records.iter().map(|record| record["summary"].as_str().unwrap()).collect();
+    assert_eq!(summaries, ["tautology", "contingent", "contradiction"]);
+    assert!(records.iter().all(|record| record["status"] == "ok"));
+    std::fs::remove_file(log).unwrap();
+}
+
+#[test]
+fn test_truth_table_diff() {
+    use ttt::io::csv::CsvDocument;
+    
+    let and_table = Evaluator::generate_truth_table(&Parser::new("a and b").parse().unwrap()).unwrap();
+    let or_table = Evaluator::generate_truth_table(&Parser::new("a or b").parse().unwrap()).unwrap();
+    
+    let same = Evaluator::diff_truth_tables(&and_table, &and_table);
+    assert!(same.identical);
+    
+    let diff = Evaluator::diff_truth_tables(&and_table, &or_table);
+    assert!(!diff.identical);
+    assert_eq!(diff.differences.len(), 2);
+    assert!(diff.left_only_rows.is_empty() && diff.right_only_rows.is_empty());
+    
+    // Column order in an exported table doesn't matter
+    let reordered = CsvDocument::parse("b,a,result\nF,F,F\nF,T,F\nT,F,F\nT,T,T\n")
+        .unwrap()
+        .to_truth_table()
+        .unwrap();
+    assert!(Evaluator::diff_truth_tables(&and_table, &reordered).identical);
+    
+    // Missing rows are reported structurally
+    let partial = CsvDocument::parse("a,b,result\nF,F,F\n").unwrap().to_truth_table().unwrap();
+    let diff = Evaluator::diff_truth_tables(&and_table, &partial);
+    assert!(!diff.identical);
+    assert_eq!(diff.left_only_rows.len(), 3);
+    assert!(diff.differences.is_empty());
+}
+
+#[test]
+fn test_subexpression_analysis() {
+    use ttt::eval::SubexpressionOrder;
+    
+    let expr = Parser::new("(a and b) or not (a and b)").parse().unwrap();
+    let report = Evaluator::analyze_subexpressions(&expr, SubexpressionOrder::Appearance).unwrap();
+    
+    let shared = report.subexpressions
+        .iter()
+        .find(|sub| sub.expr.to_string() == "(a ∧ b)")
+        .expect("a and b should be listed once");
+    assert_eq!(shared.occurrences, 2);
+    assert_eq!(shared.true_count, 1);
+    assert_eq!(shared.density, 0.25);
+    
+    // The whole expression is listed last and is a tautology here
+    let whole = report.subexpressions.last().unwrap();
+    assert_eq!(whole.expr, expr);
+    assert_eq!(whole.density, 1.0);
+    
+    let by_size = Evaluator::analyze_subexpressions(&expr, SubexpressionOrder::Size).unwrap();
+    assert_eq!(by_size.subexpressions[0].expr, expr);
+    assert_eq!(by_size.subexpressions.len(), report.subexpressions.len());
+}
+
+#[test]
+fn test_reduction_trace_coverage() {
+    let expr = Parser::new("a and b or a and not b").parse().unwrap();
+    
+    let untraced = Evaluator::reduce_expression(&expr).unwrap();
+    assert!(untraced.coverage.is_none());
+    
+    let traced = Evaluator::reduce_expression_with_trace(&expr).unwrap();
+    assert_eq!(traced.reduced, untraced.reduced);
+    
+    let coverage = traced.coverage.expect("trace should be present");
+    assert_eq!(coverage.len(), 1);
+    assert_eq!(coverage[0].term.to_string(), "a");
+    assert_eq!(coverage[0].cube, "1-");
+    assert_eq!(coverage[0].minterms, vec![2, 3]);
+    assert_eq!(coverage[0].source_terms.len(), 2);
+}
+
+#[test]
+fn test_reduction_budget_exhausted() {
+    use ttt::eval::ReductionOptions;
+    
+    let expr = Parser::new("a and b or b and c or a and c or not a and not d").parse().unwrap();
+    
+    let full = Evaluator::reduce_expression(&expr).unwrap();
+    assert!(full.minimal);
+    
+    // With a tiny budget the best cover found so far is returned, still equivalent
+    let options = ReductionOptions { step_budget: 3, ..Default::default() };
+    let partial = Evaluator::reduce_expression_with_options(&expr, options).unwrap();
+    assert!(!partial.minimal);
+    assert!(Evaluator::check_equivalence(&expr, &partial.reduced).unwrap().equivalent);
+    
+    let strict = ReductionOptions { strict: true, ..options };
+    assert!(Evaluator::reduce_expression_with_options(&expr, strict).is_err());
+}
+
+#[test]
+fn test_prime_implicants_without_cover() {
+    // The consensus term b∧c is prime but not needed in a minimal cover
+    let expr = Parser::new("a and b or not a and c").parse().unwrap();
+    
+    let report = Evaluator::prime_implicants(&expr, true).unwrap();
+    assert_eq!(report.count, 3);
+    let cubes: Vec<&str> = report.implicants.iter().map(|i| i.cube.as_str()).collect();
+    assert_eq!(cubes, vec!["11-", "0-1", "-11"]);
+    
+    let count_only = Evaluator::prime_implicants(&expr, false).unwrap();
+    assert_eq!(count_only.count, 3);
+    assert!(count_only.implicants.is_empty());
+    
+    let contradiction = Parser::new("a and not a").parse().unwrap();
+    assert_eq!(Evaluator::prime_implicants(&contradiction, false).unwrap().count, 0);
+}
+
+#[test]
+fn test_range_shards_merge_into_full_table() {
+    let expr = Parser::new("a and b or c and not d").parse().unwrap();
+    let full = Evaluator::generate_truth_table(&expr).unwrap();
+    
+    let first = Evaluator::generate_truth_table_range(&expr, 0, Some(10)).unwrap();
+    let second = Evaluator::generate_truth_table_range(&expr, 10, None).unwrap();
+    assert_eq!(first.rows.len(), 10);
+    let range = second.range.expect("slice metadata");
+    assert_eq!((range.start, range.end, range.total), (10, 16, 16));
+    
+    let merged = Evaluator::merge_truth_tables(&[second, first]).unwrap();
+    assert!(merged.is_complete());
+    assert_eq!(merged.table.rows, full.rows);
+    
+    assert!(Evaluator::generate_truth_table_range(&expr, 4, Some(17)).is_err());
+}
+
+#[test]
+fn test_sorted_tables() {
+    let expr = Parser::new("a and (b or c and d)").parse().unwrap();
+    let mut table = Evaluator::generate_truth_table(&expr).unwrap();
+    Evaluator::sort_truth_table(&expr, &mut table, RowSort::Result).unwrap();
+    let results: Vec<bool> = table.rows.iter().map(|row| row.result).collect();
+    assert_eq!(results, [[true; 5].as_slice(), &[false; 11]].concat());
+    
+    // a matters most, then b, then c and d equally
+    Evaluator::sort_truth_table(&expr, &mut table, RowSort::Influence).unwrap();
+    let first_half: Vec<bool> = table.rows[..8].iter().map(|row| row.assignments["a"]).collect();
+    assert_eq!(first_half, [false; 8]);
+    assert!(!table.rows[8].assignments["b"] && table.rows[12].assignments["b"]);
+    assert_eq!(table.rows.iter().filter(|row| row.result).count(), 5);
+}
+
+#[test]
+fn test_sample_covers_tables_too_wide_to_enumerate() {
+    let names: Vec<String> = ('a'..='z').map(|c| format!("v{}", c)).collect();
+    let expr = Parser::new(&names.join(" xor ")).parse().unwrap();
+    assert!(Evaluator::generate_truth_table(&expr).is_err());
+    
+    let sample = Evaluator::sample_truth_table(&expr, 200, 42).unwrap();
+    assert_eq!(sample.variables.len(), 26);
+    assert_eq!(sample.rows.len(), 200);
+    assert_eq!(sample.sample.expect("sample metadata").seed, 42);
+    for row in &sample.rows {
+        assert_eq!(row.result, row.assignments.values().filter(|value| **value).count() % 2 == 1);
+    }
+    let true_rows = sample.rows.iter().filter(|row| row.result).count();
+    assert!((60..140).contains(&true_rows), "{} of 200 rows true", true_rows);
+    assert_eq!(Evaluator::sample_truth_table(&expr, 200, 42).unwrap().rows, sample.rows);
+    assert!(format_truth_table(&sample, &OutputFormat::Csv).starts_with("# random sample: 200 row(s) of 2^26 assignments, seed 42"));
+}
+
+#[test]
+fn test_plain_output_is_ascii_with_stable_columns() {
+    let expr = Parser::new("carry and not b").parse().unwrap();
+    let table = Evaluator::generate_truth_table(&expr).unwrap();
+    assert_eq!(
+        format_truth_table(&table, &OutputFormat::Plain),
+        "b carry result\n0 0     0\n1 0     0\n0 1     1\n1 1     0\n"
+    );
+    
+    let right = Parser::new("carry or b").parse().unwrap();
+    let check = Evaluator::check_equivalence(&expr, &right).unwrap();
+    let output = format_equivalence_result(&check, &expr.to_string(), &right.to_string(), &OutputFormat::Plain);
+    assert!(output.is_ascii());
+    assert!(output.starts_with("- Expressions are not equivalent\n  Left:  (carry && !b)\n"), "{}", output);
+    assert!(output.contains("b=1 carry=0 -> Left=0, Right=1"), "{}", output);
+}
+
+#[test]
+fn test_degree_and_anf_write_the_same_polynomial() {
+    use ttt::io::output::{format_anf, format_degree};
+    
+    let expr = Parser::new("(a and b) xor (a and b and c)").parse().unwrap();
+    let anf = Evaluator::algebraic_normal_form(&expr).unwrap();
+    assert_eq!(anf.polynomial, "ab ⊕ abc");
+    let degree = format_degree(&Evaluator::analyze_degree(&expr, false).unwrap(), &OutputFormat::Table);
+    assert!(degree.contains("\nanf: ab ⊕ abc\n"), "{}", degree);
+    
+    let nuon = format_anf(&anf, &OutputFormat::Nuon);
+    assert!(nuon.starts_with(&format!("{{expression: {:?}, anf: \"ab ⊕ abc\"", expr.to_string())), "{}", nuon);
+}
+
+#[test]
+fn test_every_generated_file_records_its_provenance() {
+    use std::process::Command;
+    use ttt::io::aiger;
+    
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ttt")).args(args).arg("a and not b").output().unwrap();
+        assert!(output.status.success(), "{:?}", args);
+        output.stdout
+    };
+    let header = |document: &[u8]| {
+        let text = String::from_utf8_lossy(document);
+        ["generated by ttt ", "expression: a and not b\n", "fingerprint: "].iter().all(|field| text.contains(field))
+    };
+    
+    for format in ["aag", "aig"] {
+        let file = run(&["export", "--format", format]);
+        assert!(header(&file), "{}", format);
+        // Readers stop at the comment section
+        assert_eq!(aiger::parse(&file).unwrap().inputs(), ["a", "b"]);
+    }
+    let cnf = String::from_utf8(run(&["export", "--format", "cnf"])).unwrap();
+    assert!(header(cnf.as_bytes()));
+    let problem = cnf.lines().position(|line| line.starts_with("p cnf ")).unwrap();
+    assert!(cnf.lines().take(problem).all(|line| line.starts_with("c ")), "{}", cnf);
+    for format in ["markdown", "html"] {
+        let document = run(&["report", "--format", format]);
+        assert!(header(&document), "{}", format);
+        assert!(String::from_utf8(document).unwrap().contains("<!--\ngenerated by ttt "));
+    }
+    
+    for args in [&["export", "--format", "cnf", "--no-provenance"][..], &["export", "--no-provenance"], &["report", "--format", "html", "--no-provenance"]] {
+        assert!(!header(&run(args)), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_lint_pattern_matching_nothing_fails() {
+    use std::process::Command;
+    
+    let dir = std::env::temp_dir().join(format!("ttt-lint-glob-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("guard.rs"), "// ttt-eq: not (a and b) == not a or not b\n").unwrap();
+    let lint = |patterns: &[&str]| {
+        let patterns: Vec<String> = patterns.iter().map(|pattern| format!("{}/{}", dir.display(), pattern)).collect();
+        Command::new(env!("CARGO_BIN_EXE_ttt")).arg("lint-files").args(&patterns).output().unwrap()
+    };
+    
+    assert!(lint(&["*.rs"]).status.success());
+    for patterns in [&["*.py"][..], &["*.rs", "**/*.py"]] {
+        let output = lint(patterns);
+        assert_eq!(output.status.code(), Some(1), "{:?}", patterns);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("matched no files"));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
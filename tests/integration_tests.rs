@@ -1,4 +1,7 @@
+use ttt::expr;
 use ttt::source::{Parser, Expr};
+use ttt::eval::{Evaluator, EvaluationError, EvalOptions};
+use ttt::config::MAX_NESTING_DEPTH;
 
 #[test]
 fn test_parse_workflow() {
@@ -16,7 +19,7 @@ fn test_parse_workflow() {
     
     for (input, expected_display) in test_cases {
         let mut parser = Parser::new(input);
-        let result = parser.parse().expect(&format!("Failed to parse: {}", input));
+        let result = parser.parse().unwrap_or_else(|_| panic!("Failed to parse: {}", input));
         assert_eq!(result.to_string(), expected_display, "Input: {}", input);
     }
 }
@@ -37,8 +40,8 @@ fn test_mixed_operator_formats() {
         let mut parser1 = Parser::new(expr1);
         let mut parser2 = Parser::new(expr2);
         
-        let result1 = parser1.parse().expect(&format!("Failed to parse: {}", expr1));
-        let result2 = parser2.parse().expect(&format!("Failed to parse: {}", expr2));
+        let result1 = parser1.parse().unwrap_or_else(|_| panic!("Failed to parse: {}", expr1));
+        let result2 = parser2.parse().unwrap_or_else(|_| panic!("Failed to parse: {}", expr2));
         
         // Both should produce the same AST structure
         assert_eq!(result1, result2, "Expressions should be equivalent: {} vs {}", expr1, expr2);
@@ -49,34 +52,16 @@ fn test_mixed_operator_formats() {
 fn test_operator_precedence() {
     let precedence_tests = [
         // AND has higher precedence than OR
-        ("a or b and c", Expr::Or(
-            Box::new(Expr::Identifier("a".to_string())),
-            Box::new(Expr::And(
-                Box::new(Expr::Identifier("b".to_string())),
-                Box::new(Expr::Identifier("c".to_string()))
-            ))
-        )),
+        ("a or b and c", expr!(a or (b and c))),
         // NOT has highest precedence
-        ("not a and b", Expr::And(
-            Box::new(Expr::Not(Box::new(Expr::Identifier("a".to_string())))),
-            Box::new(Expr::Identifier("b".to_string()))
-        )),
+        ("not a and b", expr!((not a) and b)),
         // Implication has lowest precedence
-        ("a and b -> c or d", Expr::Implication(
-            Box::new(Expr::And(
-                Box::new(Expr::Identifier("a".to_string())),
-                Box::new(Expr::Identifier("b".to_string()))
-            )),
-            Box::new(Expr::Or(
-                Box::new(Expr::Identifier("c".to_string())),
-                Box::new(Expr::Identifier("d".to_string()))
-            ))
-        )),
+        ("a and b -> c or d", expr!((a and b) -> (c or d))),
     ];
     
     for (input, expected) in precedence_tests {
         let mut parser = Parser::new(input);
-        let result = parser.parse().expect(&format!("Failed to parse: {}", input));
+        let result = parser.parse().unwrap_or_else(|_| panic!("Failed to parse: {}", input));
         assert_eq!(result, expected, "Input: {}", input);
     }
 }
@@ -84,25 +69,13 @@ fn test_operator_precedence() {
 #[test]
 fn test_parentheses_override_precedence() {
     let parentheses_tests = [
-        ("(a or b) and c", Expr::And(
-            Box::new(Expr::Or(
-                Box::new(Expr::Identifier("a".to_string())),
-                Box::new(Expr::Identifier("b".to_string()))
-            )),
-            Box::new(Expr::Identifier("c".to_string()))
-        )),
-        ("a and (b or c)", Expr::And(
-            Box::new(Expr::Identifier("a".to_string())),
-            Box::new(Expr::Or(
-                Box::new(Expr::Identifier("b".to_string())),
-                Box::new(Expr::Identifier("c".to_string()))
-            ))
-        )),
+        ("(a or b) and c", expr!((a or b) and c)),
+        ("a and (b or c)", expr!(a and (b or c))),
     ];
     
     for (input, expected) in parentheses_tests {
         let mut parser = Parser::new(input);
-        let result = parser.parse().expect(&format!("Failed to parse: {}", input));
+        let result = parser.parse().unwrap_or_else(|_| panic!("Failed to parse: {}", input));
         assert_eq!(result, expected, "Input: {}", input);
     }
 }
@@ -127,4 +100,44 @@ fn test_complex_nested_expressions() {
         let display = expr.to_string();
         assert!(!display.is_empty(), "Display should not be empty for: {}", input);
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_deep_expressions_are_not_evaluated() {
+    // Built directly, since the parser already stops at this depth
+    let deep = (0..=MAX_NESTING_DEPTH).fold(expr!(a), |inner, _| Expr::Not(Box::new(inner)));
+    assert_eq!(deep.depth(), MAX_NESTING_DEPTH + 1);
+    assert!(matches!(
+        Evaluator::generate_truth_table(&deep),
+        Err(EvaluationError::ExpressionTooDeep { depth, max: MAX_NESTING_DEPTH }) if depth == MAX_NESTING_DEPTH + 1
+    ));
+    assert!(Evaluator::check_equivalence(&deep, &expr!(a)).is_err());
+    // A caller with the stack for it can go deeper
+    let options = EvalOptions { max_depth: MAX_NESTING_DEPTH + 1, ..EvalOptions::default() };
+    assert!(Evaluator::with_options(&options, || Evaluator::generate_truth_table(&deep)).is_ok());
+}
+
+#[test]
+fn test_deepest_expressions_fit_a_spawned_threads_stack() {
+    // Tests run on spawned threads, with the default 2 MiB stack
+    let levels = MAX_NESTING_DEPTH;
+    let inputs = [
+        format!("a{}", " and b".repeat(levels)),
+        format!("a{}", " -> b".repeat(levels)),
+        "not ".repeat(levels) + "a",
+        format!("{}a{}", "(not ".repeat(levels), ")".repeat(levels)),
+        format!("{}b{}", "ite(a, ".repeat(levels), ", c)".repeat(levels)),
+        format!("{}b{}", "(a ? ".repeat(levels), " : c)".repeat(levels)),
+    ];
+    for input in &inputs {
+        let expr = Parser::new(input).parse().unwrap_or_else(|e| panic!("{}...: {}", &input[..20], e));
+        assert!(Parser::new(&format!("not ({})", input)).parse().is_err(), "{}...", &input[..20]);
+        assert_eq!(expr.depth(), levels);
+        assert_eq!(Parser::new(&expr.to_string()).parse().unwrap(), expr.clone());
+        assert!(Evaluator::generate_truth_table(&expr).is_ok());
+        assert!(Evaluator::reduce_expression(&expr).is_ok());
+        assert!(Evaluator::expression_warnings(&expr).is_ok());
+        assert!(Evaluator::to_cnf(&expr, true).is_ok());
+        assert_eq!(expr.normalize().fingerprint(), expr.normalize().fingerprint());
+    }
+}
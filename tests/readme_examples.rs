@@ -1,3 +1,4 @@
+use ttt::expr;
 use ttt::source::{Parser, Expr};
 
 /// Tests based on examples from the README.md
@@ -7,10 +8,7 @@ fn test_readme_table_example() {
     let mut parser = Parser::new("a or not b");
     let result = parser.parse().expect("Should parse README table example");
     
-    let expected = Expr::Or(
-        Box::new(Expr::Identifier("a".to_string())),
-        Box::new(Expr::Not(Box::new(Expr::Identifier("b".to_string()))))
-    );
+    let expected = expr!(a or (not b));
     
     assert_eq!(result, expected);
     assert_eq!(result.to_string(), "(a ∨ ¬b)");
@@ -29,15 +27,8 @@ fn test_readme_equivalence_examples() {
     let right_result = parser2.parse().expect("Should parse right expression");
     
     // Verify both parse successfully (equivalence logic would be separate)
-    let expected_left = Expr::Or(
-        Box::new(Expr::Identifier("a".to_string())),
-        Box::new(Expr::Not(Box::new(Expr::Identifier("b".to_string()))))
-    );
-    
-    let expected_right = Expr::Or(
-        Box::new(Expr::Not(Box::new(Expr::Identifier("a".to_string())))),
-        Box::new(Expr::Identifier("b".to_string()))
-    );
+    let expected_left = expr!(a or (not b));
+    let expected_right = expr!((not a) or b);
     
     assert_eq!(left_result, expected_left);
     assert_eq!(right_result, expected_right);
@@ -103,7 +94,7 @@ fn test_identifier_constraints() {
     let valid_identifiers = ["a", "variable", "var_name", "P", "Q", "proposition"];
     for id in valid_identifiers {
         let mut parser = Parser::new(id);
-        let result = parser.parse().expect(&format!("Should parse identifier: {}", id));
+        let result = parser.parse().unwrap_or_else(|_| panic!("Should parse identifier: {}", id));
         assert_eq!(result, Expr::Identifier(id.to_string()));
     }
     